@@ -0,0 +1,153 @@
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+use starling::prelude::*;
+
+/// What happens when an alarm goes off, in addition to a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Sequence, Deserialize, Serialize)]
+pub enum AlarmAction {
+    #[default]
+    Notify,
+    Pause,
+    RealTime,
+}
+
+/// Parses actions by their displayed name, ignoring case.
+impl std::str::FromStr for AlarmAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        enum_iterator::all::<Self>()
+            .find(|a| a.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown alarm action \"{}\"", s))
+    }
+}
+
+impl std::fmt::Display for AlarmAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Notify => write!(f, "notify"),
+            Self::Pause => write!(f, "pause"),
+            Self::RealTime => write!(f, "realtime"),
+        }
+    }
+}
+
+/// Where an alarm came from. Automatic alarms are kept up to date by the
+/// game and replaced rather than duplicated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmSource {
+    Manual,
+    TransferWindow(EntityId),
+    /// Set by a timeline note, and rebuilt from the notes whenever they
+    /// change.
+    Note,
+}
+
+#[derive(Debug, Clone)]
+pub struct Alarm {
+    pub name: String,
+    pub stamp: Nanotime,
+    pub action: AlarmAction,
+    pub source: AlarmSource,
+}
+
+/// Pending alarms, kept sorted by the sim time at which they go off.
+#[derive(Debug, Clone, Default)]
+pub struct AlarmClock {
+    alarms: Vec<Alarm>,
+}
+
+impl AlarmClock {
+    pub fn len(&self) -> usize {
+        self.alarms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alarms.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Alarm> + use<'_> {
+        self.alarms.iter()
+    }
+
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut Alarm> {
+        self.alarms.get_mut(i)
+    }
+
+    pub fn add(&mut self, alarm: Alarm) {
+        let i = self.alarms.partition_point(|a| a.stamp <= alarm.stamp);
+        self.alarms.insert(i, alarm);
+    }
+
+    /// Adds an alarm, replacing any other alarm from the same automatic
+    /// source. The existing alarm's action is kept.
+    pub fn upsert(&mut self, mut alarm: Alarm) {
+        if alarm.source != AlarmSource::Manual {
+            if let Some(i) = self.alarms.iter().position(|a| a.source == alarm.source) {
+                alarm.action = self.alarms.remove(i).action;
+            }
+        }
+        self.add(alarm);
+    }
+
+    pub fn remove(&mut self, i: usize) -> Option<Alarm> {
+        (i < self.alarms.len()).then(|| self.alarms.remove(i))
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(&Alarm) -> bool) {
+        self.alarms.retain(f);
+    }
+
+    /// Removes and returns every alarm due at or before `stamp`.
+    pub fn poll(&mut self, stamp: Nanotime) -> Vec<Alarm> {
+        let n = self.alarms.partition_point(|a| a.stamp <= stamp);
+        self.alarms.drain(..n).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alarm(name: &str, secs: i64, source: AlarmSource) -> Alarm {
+        Alarm {
+            name: name.to_string(),
+            stamp: Nanotime::secs(secs),
+            action: AlarmAction::Notify,
+            source,
+        }
+    }
+
+    #[test]
+    fn alarms_fire_in_order() {
+        let mut clock = AlarmClock::default();
+        clock.add(alarm("b", 20, AlarmSource::Manual));
+        clock.add(alarm("a", 10, AlarmSource::Manual));
+        clock.add(alarm("c", 30, AlarmSource::Manual));
+
+        assert!(clock.poll(Nanotime::secs(5)).is_empty());
+        let fired: Vec<_> = clock
+            .poll(Nanotime::secs(20))
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        assert_eq!(fired, vec!["a", "b"]);
+        assert_eq!(clock.len(), 1);
+    }
+
+    #[test]
+    fn automatic_alarms_are_replaced() {
+        let mut clock = AlarmClock::default();
+        let source = AlarmSource::TransferWindow(EntityId(7));
+        clock.upsert(alarm("window", 100, source));
+        clock.get_mut(0).unwrap().action = AlarmAction::Pause;
+        clock.upsert(alarm("window", 90, source));
+        clock.upsert(alarm("manual", 90, AlarmSource::Manual));
+        clock.upsert(alarm("manual", 90, AlarmSource::Manual));
+
+        assert_eq!(clock.len(), 3);
+        let window = clock.iter().find(|a| a.source == source).unwrap();
+        assert_eq!(window.stamp, Nanotime::secs(90));
+        assert_eq!(window.action, AlarmAction::Pause);
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use starling::prelude::*;
+use std::error::Error;
+use std::path::Path;
+
+/// A point an annotation is attached to. Entity anchors follow the entity
+/// around, keeping the offset they had when the annotation was pinned.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AnnotationAnchor {
+    Fixed(DVec2),
+    Entity(EntityId, DVec2),
+}
+
+impl AnnotationAnchor {
+    /// Anchors to the nearest orbiter or planet within `radius` of `pos`,
+    /// or to the fixed point if there are none.
+    pub fn snap(pos: DVec2, universe: &Universe, radius: f64) -> Self {
+        universe
+            .orbiter_ids()
+            .chain(universe.planets.planet_ids())
+            .chain(universe.waypoints.keys().copied())
+            .filter_map(|id| Some((id, universe.pv(id)?.pos)))
+            .map(|(id, p)| (id, p, p.distance(pos)))
+            .filter(|(_, _, d)| *d < radius)
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(id, p, _)| Self::Entity(id, pos - p))
+            .unwrap_or(Self::Fixed(pos))
+    }
+
+    /// World position of this anchor, if its entity still exists.
+    pub fn resolve(&self, universe: &Universe) -> Option<DVec2> {
+        self.resolve_with(|id| universe.pv(id).map(|pv| pv.pos))
+    }
+
+    /// World position of this anchor, given a way to look up where
+    /// entities are.
+    pub fn resolve_with(&self, pos: impl Fn(EntityId) -> Option<DVec2>) -> Option<DVec2> {
+        match self {
+            Self::Fixed(p) => Some(*p),
+            Self::Entity(id, offset) => Some(pos(*id)? + offset),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AnnotationKind {
+    Distance(AnnotationAnchor, AnnotationAnchor),
+    Angle {
+        center: AnnotationAnchor,
+        a: AnnotationAnchor,
+        b: AnnotationAnchor,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    pub visible: bool,
+}
+
+impl Annotation {
+    pub fn new(kind: AnnotationKind) -> Self {
+        Self {
+            kind,
+            visible: true,
+        }
+    }
+
+    pub fn anchors(&self) -> Vec<AnnotationAnchor> {
+        match self.kind {
+            AnnotationKind::Distance(a, b) => vec![a, b],
+            AnnotationKind::Angle { center, a, b } => vec![center, a, b],
+        }
+    }
+
+    /// Entities the annotation is attached to.
+    pub fn anchored_entities(&self) -> impl Iterator<Item = EntityId> + use<> {
+        self.anchors().into_iter().filter_map(|a| match a {
+            AnnotationAnchor::Entity(id, _) => Some(id),
+            AnnotationAnchor::Fixed(_) => None,
+        })
+    }
+
+    /// Points entity anchors at the remapped IDs, as for annotations saved
+    /// by an older version.
+    pub fn remap_ids(&mut self, remap: &IdRemap) {
+        let fix = |a: &mut AnnotationAnchor| {
+            if let AnnotationAnchor::Entity(id, _) = a {
+                remap.apply(id);
+            }
+        };
+        match &mut self.kind {
+            AnnotationKind::Distance(a, b) => {
+                fix(a);
+                fix(b);
+            }
+            AnnotationKind::Angle { center, a, b } => {
+                fix(center);
+                fix(a);
+                fix(b);
+            }
+        }
+    }
+
+    /// Resolved world positions of each anchor, in the order they're
+    /// declared in the annotation kind.
+    pub fn points(&self, universe: &Universe) -> Option<Vec<DVec2>> {
+        self.points_with(|id| universe.pv(id).map(|pv| pv.pos))
+    }
+
+    /// Like `points`, with entity positions looked up by `pos`.
+    pub fn points_with(&self, pos: impl Fn(EntityId) -> Option<DVec2>) -> Option<Vec<DVec2>> {
+        match &self.kind {
+            AnnotationKind::Distance(a, b) => {
+                Some(vec![a.resolve_with(&pos)?, b.resolve_with(&pos)?])
+            }
+            AnnotationKind::Angle { center, a, b } => Some(vec![
+                center.resolve_with(&pos)?,
+                a.resolve_with(&pos)?,
+                b.resolve_with(&pos)?,
+            ]),
+        }
+    }
+
+    /// Current value of the measurement, formatted for display.
+    pub fn value_str(&self, universe: &Universe) -> String {
+        let Some(p) = self.points(universe) else {
+            return "(anchor lost)".to_string();
+        };
+        match self.kind {
+            AnnotationKind::Distance(..) => distance_str(p[0].distance(p[1])),
+            AnnotationKind::Angle { .. } => {
+                let angle = (p[1] - p[0]).angle_to(p[2] - p[0]);
+                format!("{:0.1} deg", angle.to_degrees().abs())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for AnnotationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Distance(..) => write!(f, "Distance"),
+            Self::Angle { .. } => write!(f, "Angle"),
+        }
+    }
+}
+
+pub fn load_annotations_from_file(filename: &Path) -> Result<Vec<Annotation>, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    Ok(serde_yaml::from_str(&s)?)
+}
+
+pub fn write_annotations_to_file(
+    annotations: &[Annotation],
+    filename: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(annotations)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_anchors_follow_their_entity() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let id = universe
+            .add_surface_vehicle(planet, Vehicle::new(), 0.0, 100.0)
+            .unwrap();
+        let pos = universe.pv(id).unwrap().pos;
+
+        let near = AnnotationAnchor::snap(pos + DVec2::X, &universe, 5.0);
+        assert_eq!(near, AnnotationAnchor::Entity(id, DVec2::X));
+        assert_eq!(near.resolve(&universe), Some(pos + DVec2::X));
+
+        let far = pos + DVec2::X * 100.0;
+        assert_eq!(
+            AnnotationAnchor::snap(far, &universe, 5.0),
+            AnnotationAnchor::Fixed(far)
+        );
+
+        universe.soft_delete(id).unwrap();
+        assert_eq!(near.resolve(&universe), None);
+    }
+}
@@ -8,17 +8,95 @@ pub struct ProgramContext {
     /// Directory for game assets and saved files
     #[arg(long)]
     pub install_dir: PathBuf,
+
+    /// Load and cross-check every asset, print a report, and exit
+    #[arg(long)]
+    pub validate_assets: bool,
+
+    /// Player profile under which statistics are saved
+    #[arg(long, default_value = DEFAULT_PROFILE)]
+    pub profile: String,
 }
 
+pub const DEFAULT_PROFILE: &str = "default";
+
 impl ProgramContext {
     pub fn new(install_dir: PathBuf) -> Self {
-        Self { install_dir }
+        Self {
+            install_dir,
+            validate_assets: false,
+            profile: DEFAULT_PROFILE.to_string(),
+        }
+    }
+
+    pub fn profile_dir(&self) -> PathBuf {
+        let profile = match self.profile.as_str() {
+            "" => DEFAULT_PROFILE,
+            p => p,
+        };
+        self.install_dir.join("profiles").join(profile)
+    }
+
+    pub fn stats_path(&self) -> PathBuf {
+        self.profile_dir().join("stats.yaml")
+    }
+
+    pub fn save_path(&self) -> PathBuf {
+        self.profile_dir().join("save.strl")
+    }
+
+    pub fn autosave_path(&self) -> PathBuf {
+        self.profile_dir().join("autosave.strl")
+    }
+
+    pub fn timeline_notes_path(&self) -> PathBuf {
+        self.profile_dir().join("notes.yaml")
+    }
+
+    pub fn annotations_path(&self) -> PathBuf {
+        self.profile_dir().join("annotations.yaml")
+    }
+
+    pub fn waypoints_path(&self) -> PathBuf {
+        self.profile_dir().join("waypoints.yaml")
+    }
+
+    pub fn console_history_path(&self) -> PathBuf {
+        self.profile_dir().join("console.yaml")
+    }
+
+    pub fn filters_path(&self) -> PathBuf {
+        self.profile_dir().join("filters.yaml")
+    }
+
+    pub fn universe_path(&self) -> PathBuf {
+        self.profile_dir().join("universe.yaml")
+    }
+
+    pub fn structures_path(&self) -> PathBuf {
+        self.profile_dir().join("structures.yaml")
+    }
+
+    pub fn terrain_path(&self) -> PathBuf {
+        self.profile_dir().join("terrain.yaml")
+    }
+
+    pub fn scenario_path(&self) -> PathBuf {
+        self.profile_dir().join("scenario.yaml")
     }
 
     pub fn settings_path(&self) -> PathBuf {
         self.install_dir.join("settings.yaml")
     }
 
+    pub fn gestures_path(&self) -> PathBuf {
+        self.install_dir.join("gestures.yaml")
+    }
+
+    pub fn editor_recovery_path(&self) -> PathBuf {
+        self.install_dir.join("editor_recovery.yaml")
+    }
+
     pub fn names_path(&self) -> PathBuf {
         self.install_dir.join("ship_names.txt")
     }
@@ -27,6 +105,20 @@ impl ProgramContext {
         self.install_dir.join("vehicles")
     }
 
+    pub fn thumbnail_path(&self, vehicle: &str) -> PathBuf {
+        self.install_dir
+            .join("thumbnails")
+            .join(format!("{}.png", vehicle))
+    }
+
+    pub fn assemblies_dir(&self) -> PathBuf {
+        self.install_dir.join("assemblies")
+    }
+
+    pub fn planets_dir(&self) -> PathBuf {
+        self.install_dir.join("planets")
+    }
+
     pub fn parts_dir(&self) -> PathBuf {
         self.install_dir.join("parts")
     }
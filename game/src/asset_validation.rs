@@ -0,0 +1,274 @@
+use crate::args::ProgramContext;
+use crate::game::STATIC_SPRITES;
+use crate::generate_ship_sprites::read_image;
+use crate::names::load_names_from_file;
+use starling::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct AssetIssue {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// Result of loading and cross-checking every asset in the install
+/// directory. Collects all problems rather than stopping at the first.
+#[derive(Debug, Default)]
+pub struct AssetReport {
+    pub parts_checked: usize,
+    pub planets_checked: usize,
+    pub vehicles_checked: usize,
+    pub sprites_checked: usize,
+    pub names_checked: usize,
+    pub issues: Vec<AssetIssue>,
+}
+
+impl AssetReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn issue(&mut self, path: impl Into<PathBuf>, message: impl Into<String>) {
+        self.issues.push(AssetIssue {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+}
+
+impl std::fmt::Display for AssetReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "parts:    {}", self.parts_checked)?;
+        writeln!(f, "planets:  {}", self.planets_checked)?;
+        writeln!(f, "vehicles: {}", self.vehicles_checked)?;
+        writeln!(f, "sprites:  {}", self.sprites_checked)?;
+        writeln!(f, "names:    {}", self.names_checked)?;
+        writeln!(f, "issues:   {}", self.issues.len())?;
+        for issue in &self.issues {
+            writeln!(f, "  {}: {}", issue.path.display(), issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_part(report: &mut AssetReport, path: &Path, part: &PartPrototype) {
+    if part.dry_mass() == Mass::ZERO {
+        report.issue(path, format!("part \"{}\" has zero mass", part.part_name()));
+    }
+    if part.dims().min_element() == 0 {
+        report.issue(
+            path,
+            format!("part \"{}\" has zero dimensions", part.part_name()),
+        );
+    }
+    let sprite = path.join("skin.png");
+    if read_image(&sprite).is_none() {
+        report.issue(sprite, "missing or unreadable part sprite");
+    }
+}
+
+fn validate_parts(report: &mut AssetReport, dir: &Path) -> HashMap<String, PartPrototype> {
+    let mut parts = HashMap::new();
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) => {
+            report.issue(dir, format!("failed to read parts directory: {}", e));
+            return parts;
+        }
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        report.parts_checked += 1;
+        match part_from_path(&path) {
+            Ok(part) => {
+                validate_part(report, &path, &part);
+                if parts.contains_key(part.part_name()) {
+                    report.issue(
+                        &path,
+                        format!("duplicate part name \"{}\"", part.part_name()),
+                    );
+                }
+                parts.insert(part.part_name().to_string(), part);
+            }
+            Err(e) => report.issue(&path, e),
+        }
+    }
+
+    parts
+}
+
+fn validate_vehicles(report: &mut AssetReport, dir: &Path, parts: &HashMap<String, PartPrototype>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) => {
+            report.issue(dir, format!("failed to read vehicle directory: {}", e));
+            return;
+        }
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().map(|e| e != "vehicle").unwrap_or(true) {
+            continue;
+        }
+        report.vehicles_checked += 1;
+
+        let storage: VehicleFileStorage = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_yaml::from_str(&s).map_err(|e| e.to_string()))
+        {
+            Ok(s) => s,
+            Err(e) => {
+                report.issue(&path, format!("failed to parse: {}", e));
+                continue;
+            }
+        };
+
+        if storage.parts.is_empty() {
+            report.issue(&path, "vehicle has no parts");
+        }
+
+        for part in &storage.parts {
+            if !parts.contains_key(&part.partname) {
+                report.issue(&path, format!("unknown part \"{}\"", part.partname));
+            }
+        }
+    }
+}
+
+fn validate_sprites(report: &mut AssetReport, install_dir: &Path) {
+    for name in STATIC_SPRITES {
+        report.sprites_checked += 1;
+        let path = install_dir.join(format!("{}.png", name));
+        if read_image(&path).is_none() {
+            report.issue(path, "missing or unreadable sprite");
+        }
+    }
+}
+
+fn validate_planets(report: &mut AssetReport, dir: &Path, install_dir: &Path) {
+    let (catalogs, failures) = match load_planet_catalogs_from_dir(dir) {
+        Ok(c) => c,
+        Err(e) => {
+            report.issue(dir, format!("failed to read planets directory: {}", e));
+            return;
+        }
+    };
+
+    for e in failures {
+        report.issue(dir, e);
+    }
+
+    if !catalogs.contains_key(DEFAULT_PLANET_SYSTEM) {
+        report.issue(
+            dir,
+            format!("no default planetary system \"{}\"", DEFAULT_PLANET_SYSTEM),
+        );
+    }
+
+    for catalog in catalogs.values() {
+        for planet in &catalog.planets {
+            report.planets_checked += 1;
+            let sprite = install_dir.join(format!("{}.png", planet.sprite()));
+            if read_image(&sprite).is_none() {
+                report.issue(
+                    sprite,
+                    format!("missing or unreadable sprite for \"{}\"", planet.name),
+                );
+            }
+        }
+    }
+}
+
+fn validate_names(report: &mut AssetReport, path: &Path) {
+    match load_names_from_file(path) {
+        Ok(names) => {
+            report.names_checked = names.len();
+            if names.iter().all(|n| n.trim().is_empty()) {
+                report.issue(path, "names file contains no names");
+            }
+        }
+        Err(e) => report.issue(path, format!("failed to load names: {}", e)),
+    }
+}
+
+pub fn validate_assets(args: &ProgramContext) -> AssetReport {
+    let mut report = AssetReport::default();
+    let parts = validate_parts(&mut report, &args.parts_dir());
+    validate_vehicles(&mut report, &args.vehicle_dir(), &parts);
+    validate_sprites(&mut report, &args.install_dir);
+    validate_planets(&mut report, &args.planets_dir(), &args.install_dir);
+    validate_names(&mut report, &args.names_path());
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broken_vehicles_and_missing_sprites_are_reported() {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets");
+        let root = std::env::temp_dir().join(format!("asset-validation-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&root);
+        let args = ProgramContext::new(root.clone());
+        for dir in [args.parts_dir().join("docking-port"), args.planets_dir()] {
+            std::fs::create_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(args.vehicle_dir()).unwrap();
+
+        let copy = |from: &Path, to: &Path| _ = std::fs::copy(from, to).unwrap();
+        for file in ["metadata.yaml", "skin.png"] {
+            let path = Path::new("parts/docking-port").join(file);
+            copy(&assets.join(&path), &root.join(&path));
+        }
+        copy(
+            &assets.join("planets/rss.yaml"),
+            &root.join("planets/rss.yaml"),
+        );
+        copy(&assets.join("ship_names.txt"), &args.names_path());
+        // every sprite but the clouds
+        for entry in std::fs::read_dir(&assets).unwrap().filter_map(|e| e.ok()) {
+            let name = entry.file_name();
+            if entry.path().extension().is_some_and(|e| e == "png") && name != "cloud.png" {
+                copy(&entry.path(), &root.join(name));
+            }
+        }
+
+        let vehicle = |part: &str| {
+            format!(
+                "name: Test\nparts:\n- partname: {}\n  pos: [0, 0]\n  rot: East\nlines: []\n",
+                part
+            )
+        };
+        let vehicles = args.vehicle_dir();
+        std::fs::write(vehicles.join("good.vehicle"), vehicle("docking-port")).unwrap();
+        std::fs::write(vehicles.join("odd.vehicle"), vehicle("wing")).unwrap();
+        std::fs::write(vehicles.join("broken.vehicle"), "parts: [{partname: 3").unwrap();
+
+        let report = validate_assets(&args);
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(report.parts_checked, 1);
+        assert_eq!(report.vehicles_checked, 3);
+        assert!(!report.is_ok());
+        let mut issues: Vec<_> = report
+            .issues
+            .iter()
+            .map(|i| (i.path.file_name().unwrap().to_owned(), i.message.clone()))
+            .collect();
+        issues.sort();
+        let [(broken, parse), (cloud, sprite), (odd, unknown)] = issues.as_slice() else {
+            panic!("{}", report);
+        };
+        assert_eq!(broken, "broken.vehicle");
+        assert!(parse.starts_with("failed to parse"));
+        assert_eq!(cloud, "cloud.png");
+        assert_eq!(sprite, "missing or unreadable sprite");
+        assert_eq!(odd, "odd.vehicle");
+        assert_eq!(unknown, "unknown part \"wing\"");
+    }
+}
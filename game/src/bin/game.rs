@@ -2,8 +2,18 @@
 
 use bevy::prelude::*;
 use bevy_vector_shapes::prelude::*;
+use clap::Parser;
+use game::args::ProgramContext;
 
 fn main() {
+    if let Ok(args) = ProgramContext::try_parse() {
+        if args.validate_assets {
+            let report = game::asset_validation::validate_assets(&args);
+            print!("{}", report);
+            std::process::exit(if report.is_ok() { 0 } else { 1 });
+        }
+    }
+
     let window = Window {
         mode: bevy::window::WindowMode::BorderlessFullscreen(MonitorSelection::Primary),
         title: "Space UPS".into(),
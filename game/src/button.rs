@@ -9,7 +9,8 @@ pub struct ExpandButton {
     dims: Vec2,
     text: String,
     sprite: String,
-    animation: Lpf,
+    hover: Tween,
+    press: Tween,
     is_hovered: bool,
     is_clicked: bool,
     onclick: OnClick,
@@ -28,7 +29,8 @@ impl ExpandButton {
             dims,
             text: text.into(),
             sprite: sprite.into(),
-            animation: Lpf::new(0.0, 0.0, 0.3),
+            hover: Tween::new(0.0, Nanotime::millis(150), Easing::EaseOut),
+            press: Tween::new(0.0, Nanotime::millis(60), Easing::Linear),
             is_hovered: false,
             is_clicked: false,
             onclick,
@@ -39,9 +41,9 @@ impl ExpandButton {
         AABB::from_arbitrary(self.pos, self.pos + self.dims)
     }
 
-    pub fn label_bounds(&self) -> AABB {
+    pub fn label_bounds(&self, wall_time: Nanotime) -> AABB {
         let low = self.pos + Vec2::new(self.dims.x + 10.0, 0.0);
-        let width = (20.0 + self.text.len() as f32 * 18.0) * self.anim();
+        let width = (20.0 + self.text.len() as f32 * 18.0) * self.anim(wall_time);
         AABB::from_arbitrary(low, low + Vec2::new(width, self.dims.y))
     }
 
@@ -49,8 +51,14 @@ impl ExpandButton {
         &self.sprite
     }
 
-    pub fn anim(&self) -> f32 {
-        self.animation.actual
+    /// How far the label is expanded, from 0 to 1.
+    pub fn anim(&self, wall_time: Nanotime) -> f32 {
+        self.hover.value(wall_time)
+    }
+
+    /// How far the button is pushed in, from 0 to 1.
+    pub fn depression(&self, wall_time: Nanotime) -> f32 {
+        self.press.value(wall_time)
     }
 }
 
@@ -82,35 +90,31 @@ impl Interactive for ExpandButton {
         }
     }
 
-    fn step(&mut self) {
-        self.animation.target = self.is_hovered as u8 as f32;
-        self.animation.step();
+    fn step(&mut self, wall_time: Nanotime) {
         if !self.is_hovered {
             self.is_clicked = false;
         }
+        self.hover
+            .set_target(self.is_hovered as u8 as f32, wall_time);
+        self.press
+            .set_target(self.is_clicked as u8 as f32, wall_time);
     }
 }
 
-pub fn draw_button(canvas: &mut Canvas, button: &ExpandButton) {
-    let alpha = lerp(0.03, 1.0, button.anim());
-    let aabb = button.inner_bounds();
+pub fn draw_button(canvas: &mut Canvas, button: &ExpandButton, wall_time: Nanotime) {
+    let anim = button.anim(wall_time);
+    let alpha = lerp(0.03, 1.0, anim);
+    let press = button.depression(wall_time);
+    let aabb = button.inner_bounds().offset(-Vec2::splat(2.0 * press));
 
-    let aabb = if button.is_clicked {
-        aabb.offset(-Vec2::splat(2.0))
-    } else {
-        aabb
-    };
-
-    let color = if button.is_clicked { SLATE_BLUE } else { GRAY };
+    let color = GRAY.mix(&SLATE_BLUE, press);
 
     canvas.rect(aabb, ZOrdering::Ui, color.with_alpha(alpha));
     canvas
         .sprite(aabb.center, 0.0, button.sprite(), ZOrdering::Ui2, aabb.span)
         .color = Some(WHITE.with_alpha(alpha.clamp(0.3, 1.0)));
 
-    let aabb = button.label_bounds();
+    let aabb = button.label_bounds(wall_time);
     canvas.rect(aabb, ZOrdering::Ui, TEAL.with_alpha(alpha));
-    canvas
-        .text(button.text.clone(), aabb.center, button.anim())
-        .z_index = ZOrdering::Ui2;
+    canvas.text(button.text.clone(), aabb.center, anim).z_index = ZOrdering::Ui2;
 }
@@ -1,8 +1,12 @@
 use crate::input::InputState;
-use bevy::input::keyboard::KeyCode;
+use crate::keybindings::{KeyAction, Keybindings};
 use starling::math::DVec2;
 use starling::prelude::*;
 
+/// Limits on the base 2 logarithm of the camera scale.
+const MIN_ZOOM: f64 = -22.0;
+const MAX_ZOOM: f64 = 10.0;
+
 #[derive(Debug, Clone, Copy)]
 pub struct LinearCameraController {
     center: DVec2,
@@ -61,6 +65,16 @@ impl LinearCameraController {
         self.target_center = DVec2::ZERO;
     }
 
+    /// Pans smoothly to the given offset from the followed object.
+    pub fn pan_to(&mut self, offset: DVec2) {
+        self.target_center = offset;
+    }
+
+    /// Zooms smoothly to the given scale, in pixels per meter.
+    pub fn zoom_to(&mut self, scale: f64) {
+        self.target_scale = scale.log2().clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
     pub fn on_game_tick(&mut self) {
         const SCALE_SMOOTHING: f64 = 0.1;
         const CENTER_SMOOTHING: f64 = 0.1;
@@ -87,7 +101,7 @@ impl LinearCameraController {
         self.parent
     }
 
-    pub fn handle_input(&mut self, input: &InputState) {
+    pub fn handle_input(&mut self, input: &InputState, keys: &Keybindings) {
         const SCROLL_WHEEL_DELTA: f64 = 0.5;
         const BUTTON_ZOOM_SPEED: f64 = 0.05;
 
@@ -100,27 +114,27 @@ impl LinearCameraController {
             self.target_scale += SCROLL_WHEEL_DELTA;
         }
 
-        if input.is_pressed(KeyCode::Equal) {
+        if keys.is_pressed(KeyAction::ZoomIn, input) {
             self.target_scale += BUTTON_ZOOM_SPEED;
         }
-        if input.is_pressed(KeyCode::Minus) {
+        if keys.is_pressed(KeyAction::ZoomOut, input) {
             self.target_scale -= BUTTON_ZOOM_SPEED;
         }
 
-        if input.is_pressed(KeyCode::KeyD) {
+        if keys.is_pressed(KeyAction::PanRight, input) {
             self.target_center.x += speed / self.scale();
         }
-        if input.is_pressed(KeyCode::KeyA) {
+        if keys.is_pressed(KeyAction::PanLeft, input) {
             self.target_center.x -= speed / self.scale();
         }
-        if input.is_pressed(KeyCode::KeyW) {
+        if keys.is_pressed(KeyAction::PanUp, input) {
             self.target_center.y += speed / self.scale();
         }
-        if input.is_pressed(KeyCode::KeyS) {
+        if keys.is_pressed(KeyAction::PanDown, input) {
             self.target_center.y -= speed / self.scale();
         }
 
-        self.target_scale = self.target_scale.clamp(-22.0, 10.0);
+        self.target_scale = self.target_scale.clamp(MIN_ZOOM, MAX_ZOOM);
     }
 }
 
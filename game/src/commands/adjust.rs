@@ -0,0 +1,91 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Schedule a single burn which adjusts a vehicle's orbit, after any burns
+/// already planned for it. Altitudes are above the surface of the body
+/// being orbited
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct AdjustOrbit {
+    /// Circularize at the next apsis, either "ap" or "pe"
+    #[arg(long, short)]
+    circularize: Option<String>,
+
+    /// Burn at periapsis to move the apoapsis to this altitude, in km
+    #[arg(long, short)]
+    apoapsis: Option<f64>,
+
+    /// Burn at apoapsis to move the periapsis to this altitude, in km
+    #[arg(long, short)]
+    periapsis: Option<f64>,
+
+    /// Rotate the periapsis around the body by this many degrees
+    #[arg(long, allow_negative_numbers = true)]
+    argp: Option<f64>,
+
+    /// Show what the burn would cost without scheduling it
+    #[arg(long)]
+    dry_run: bool,
+
+    /// ID of the vehicle. Defaults to the piloted vehicle
+    #[arg(long)]
+    id: Option<EntityId>,
+}
+
+impl Command for AdjustOrbit {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let id = self
+            .id
+            .or(state.piloting())
+            .ok_or("No vehicle specified and not piloting anything")?;
+        let radius = state
+            .universe
+            .surface_vehicles
+            .get(&id)
+            .and_then(|sv| sv.current_orbit())
+            .map(|GlobalOrbit(_, orbit)| orbit.body.radius)
+            .ok_or(format!("Vehicle {} is not in orbit", id))?;
+
+        let mut adjustments = Vec::new();
+        match self
+            .circularize
+            .as_deref()
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("ap" | "apoapsis") => adjustments.push(OrbitAdjustment::CircularizeAtApoapsis),
+            Some("pe" | "periapsis") => adjustments.push(OrbitAdjustment::CircularizeAtPeriapsis),
+            Some(s) => return Err(format!("Can't circularize at \"{}\"; use ap or pe", s)),
+            None => (),
+        }
+        if let Some(km) = self.apoapsis {
+            adjustments.push(OrbitAdjustment::SetApoapsis(radius + km * 1000.0));
+        }
+        if let Some(km) = self.periapsis {
+            adjustments.push(OrbitAdjustment::SetPeriapsis(radius + km * 1000.0));
+        }
+        if let Some(deg) = self.argp {
+            adjustments.push(OrbitAdjustment::RotateArgp(deg.to_radians()));
+        }
+
+        let [adjustment] = adjustments[..] else {
+            return Err("Give exactly one adjustment to make".to_string());
+        };
+
+        if self.dry_run {
+            let (plan, cost) = state.plan_orbit_adjustment(id, adjustment)?;
+            state.console.print(format!(
+                "{}: {:0.1} m/s, {:0.1} m/s for the whole plan, ending in {}",
+                adjustment,
+                cost,
+                plan.dv(),
+                plan.terminal
+            ));
+            return Ok(());
+        }
+
+        state.schedule_orbit_adjustment(id, adjustment)
+    }
+}
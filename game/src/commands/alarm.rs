@@ -0,0 +1,96 @@
+use crate::alarms::AlarmAction;
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Set, list or remove sim-time alarms
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct SetAlarm {
+    /// Seconds of sim time from now until the alarm goes off
+    secs: Option<f64>,
+
+    /// Interpret the time as an absolute sim date in seconds instead
+    #[arg(long)]
+    at: bool,
+
+    /// Name shown when the alarm goes off
+    #[arg(long, short, default_value = "Alarm")]
+    name: String,
+
+    /// What to do when the alarm goes off: notify, pause or realtime
+    #[arg(long)]
+    action: Option<String>,
+
+    /// List upcoming alarms
+    #[arg(long, short)]
+    list: bool,
+
+    /// Remove the alarm at this position in the list
+    #[arg(long)]
+    remove: Option<usize>,
+}
+
+impl Command for SetAlarm {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let now = state.universe.stamp();
+
+        if self.list {
+            let lines: Vec<String> = state
+                .alarms
+                .iter()
+                .enumerate()
+                .map(|(i, a)| {
+                    format!(
+                        "{} {} {} (T-{}) [{}]",
+                        i,
+                        a.name,
+                        a.stamp.to_date(),
+                        duration_str(a.stamp - now),
+                        a.action
+                    )
+                })
+                .collect();
+            for line in lines {
+                state.console.print(line);
+            }
+            return Ok(());
+        }
+
+        if let Some(i) = self.remove {
+            let alarm = state
+                .alarms
+                .remove(i)
+                .ok_or(format!("No alarm at position {}", i))?;
+            state
+                .console
+                .print(format!("Removed alarm \"{}\"", alarm.name));
+            return Ok(());
+        }
+
+        let action = match &self.action {
+            Some(s) => s.parse::<AlarmAction>()?,
+            None => state.settings.default_alarm_action,
+        };
+
+        let secs = self.secs.ok_or("No alarm time given")?;
+        let stamp = if self.at {
+            Nanotime::secs_f64(secs)
+        } else {
+            now + Nanotime::secs_f64(secs)
+        };
+
+        if stamp <= now {
+            return Err(format!("Alarm time {} is in the past", stamp.to_date()));
+        }
+
+        state.add_alarm(self.name.clone(), stamp, action);
+        state.console.print(format!(
+            "Set alarm \"{}\" for {}",
+            self.name,
+            stamp.to_date()
+        ));
+        Ok(())
+    }
+}
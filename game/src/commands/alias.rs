@@ -0,0 +1,64 @@
+use crate::commands::command::{Command, CommandDecl};
+use crate::game::GameState;
+use clap::Parser;
+
+/// Define, list or remove console command aliases
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Alias {
+    /// Name of the alias. Lists all aliases if omitted
+    name: Option<String>,
+
+    /// Command the alias expands to, such as "load grid". Shows the
+    /// current expansion if omitted
+    command: Option<String>,
+
+    /// Remove the named alias
+    #[arg(long, short)]
+    remove: bool,
+}
+
+impl Command for Alias {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let Some(name) = &self.name else {
+            let lines: Vec<String> = state
+                .console
+                .history()
+                .aliases
+                .iter()
+                .map(|(name, cmd)| format!("{} = \"{}\"", name, cmd))
+                .collect();
+            for line in lines {
+                state.console.print(line);
+            }
+            return Ok(());
+        };
+
+        if self.remove {
+            return state
+                .console
+                .remove_alias(name)
+                .map(|_| ())
+                .ok_or(format!("No alias named \"{}\"", name));
+        }
+
+        let Some(command) = &self.command else {
+            let cmd = state
+                .console
+                .history()
+                .aliases
+                .get(name)
+                .cloned()
+                .ok_or(format!("No alias named \"{}\"", name))?;
+            state.console.print(format!("{} = \"{}\"", name, cmd));
+            return Ok(());
+        };
+
+        if CommandDecl::from_str(name).is_some() {
+            return Err(format!("\"{}\" is already a command", name));
+        }
+
+        state.console.set_alias(name, command);
+        Ok(())
+    }
+}
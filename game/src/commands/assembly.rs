@@ -0,0 +1,19 @@
+use crate::commands::command::Command;
+use crate::craft_editor::EditorContext;
+use crate::game::GameState;
+use clap::Parser;
+
+/// Save the parts selected in the editor as a named assembly
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct SaveAssembly {
+    /// Name of the assembly, which is also its file name
+    name: String,
+}
+
+impl Command for SaveAssembly {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        EditorContext::save_assembly(state, Some(self.name.clone()))
+            .ok_or("Failed to save assembly".to_string())
+    }
+}
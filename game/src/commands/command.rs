@@ -32,6 +32,30 @@ pub enum CommandDecl {
     Pwd,
     Listing,
     ListVehicles,
+    Rewind,
+    Undelete,
+    ControlLaw,
+    Alarm,
+    Events,
+    Profile,
+    Assembly,
+    Alias,
+    History,
+    SharePlan,
+    ImportVehicles,
+    Group,
+    Waypoint,
+    Note,
+    Spectate,
+    Rename,
+    Loadout,
+    Adjust,
+    Host,
+    Observe,
+    Handover,
+    Find,
+    Normalize,
+    SaveVehicle,
 }
 
 impl CommandDecl {
@@ -41,6 +65,30 @@ impl CommandDecl {
             CommandDecl::Pwd => do_command::<Pwd>(state, args),
             CommandDecl::Listing => do_command::<Listing>(state, args),
             CommandDecl::ListVehicles => do_command::<ListVehicles>(state, args),
+            CommandDecl::Rewind => do_command::<Rewind>(state, args),
+            CommandDecl::Undelete => do_command::<Undelete>(state, args),
+            CommandDecl::ControlLaw => do_command::<SetControlLaw>(state, args),
+            CommandDecl::Alarm => do_command::<SetAlarm>(state, args),
+            CommandDecl::Events => do_command::<Events>(state, args),
+            CommandDecl::Profile => do_command::<Profile>(state, args),
+            CommandDecl::Assembly => do_command::<SaveAssembly>(state, args),
+            CommandDecl::Alias => do_command::<Alias>(state, args),
+            CommandDecl::History => do_command::<History>(state, args),
+            CommandDecl::SharePlan => do_command::<SharePlan>(state, args),
+            CommandDecl::ImportVehicles => do_command::<ImportVehicles>(state, args),
+            CommandDecl::Group => do_command::<EditGroup>(state, args),
+            CommandDecl::Waypoint => do_command::<PlaceWaypoint>(state, args),
+            CommandDecl::Note => do_command::<TakeNote>(state, args),
+            CommandDecl::Spectate => do_command::<Spectate>(state, args),
+            CommandDecl::Rename => do_command::<Rename>(state, args),
+            CommandDecl::Loadout => do_command::<SelectLoadout>(state, args),
+            CommandDecl::Adjust => do_command::<AdjustOrbit>(state, args),
+            CommandDecl::Host => do_command::<Host>(state, args),
+            CommandDecl::Observe => do_command::<Observe>(state, args),
+            CommandDecl::Handover => do_command::<HandOver>(state, args),
+            CommandDecl::Find => do_command::<Find>(state, args),
+            CommandDecl::Normalize => do_command::<Normalize>(state, args),
+            CommandDecl::SaveVehicle => do_command::<SaveVehicle>(state, args),
         }
     }
 
@@ -0,0 +1,56 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use crate::paused_actions::PausedAction;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Assign a registered control law to a vehicle
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct SetControlLaw {
+    /// Name of the control law. Lists available laws if omitted
+    name: Option<String>,
+
+    /// ID of the vehicle. Defaults to the piloted vehicle
+    #[arg(long)]
+    id: Option<EntityId>,
+}
+
+impl Command for SetControlLaw {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let name = match &self.name {
+            Some(name) => name,
+            None => {
+                let lines: Vec<String> = state
+                    .universe
+                    .control_laws
+                    .iter()
+                    .map(|l| format!("{}: {}", l.name(), l.description()))
+                    .collect();
+                for line in lines {
+                    state.console.print(line);
+                }
+                return Ok(());
+            }
+        };
+
+        if state.universe.control_laws.get(name).is_none() {
+            return Err(format!("No control law named \"{}\"", name));
+        }
+
+        let id = self
+            .id
+            .or(state.piloting())
+            .ok_or("No vehicle specified and not piloting anything")?;
+
+        if state.universe.is_loading(id) {
+            return Err(format!("Vehicle {} is still loading", id));
+        }
+
+        let policy = VehicleControlPolicy::ControlLaw(name.clone());
+        state
+            .apply_or_queue(id, PausedAction::SetPolicy(policy))
+            .ok_or(format!("No vehicle with ID {}", id))?;
+        Ok(())
+    }
+}
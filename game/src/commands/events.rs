@@ -0,0 +1,30 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+
+/// Print the most recent entity events
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Events {
+    /// Number of events to show
+    #[arg(default_value_t = 20)]
+    count: usize,
+}
+
+impl Command for Events {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let lines: Vec<String> = state
+            .universe
+            .events
+            .recent(self.count)
+            .map(|e| e.to_string())
+            .collect();
+        if lines.is_empty() {
+            state.console.print("No events");
+        }
+        for line in lines {
+            state.console.print(line);
+        }
+        Ok(())
+    }
+}
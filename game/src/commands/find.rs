@@ -0,0 +1,130 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use crate::vehicle_query::*;
+use clap::Parser;
+
+/// Find vehicles matching a filter, such as `fuel < 20% and parent ==
+/// Luna`. Filters can be saved by name, and pinned to the sidebar as
+/// smart lists
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Find {
+    /// Filter expression
+    #[arg(allow_hyphen_values = true)]
+    expr: Vec<String>,
+
+    /// Select the vehicles found
+    #[arg(long, short)]
+    select: bool,
+
+    /// Save the filter under this name
+    #[arg(long)]
+    save: Option<String>,
+
+    /// Pin the saved filter to the sidebar
+    #[arg(long)]
+    pin: bool,
+
+    /// Forget the saved filter with this name
+    #[arg(long)]
+    remove: Option<String>,
+
+    /// List saved filters
+    #[arg(long)]
+    list: bool,
+
+    /// List the fields filters can test
+    #[arg(long)]
+    fields: bool,
+}
+
+impl Command for Find {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        if self.fields {
+            for (name, kind, desc) in QUERY_FIELDS {
+                state.console.print(format!(
+                    "{:<12} {:<8} {}",
+                    name,
+                    format!("{:?}", kind),
+                    desc
+                ));
+            }
+            return Ok(());
+        }
+
+        if self.list {
+            let lines: Vec<String> = state
+                .saved_filters
+                .iter()
+                .map(|f| {
+                    let pin = if f.pinned { " (pinned)" } else { "" };
+                    format!("{}{}: {}", f.name, pin, f.query)
+                })
+                .collect();
+            for line in lines {
+                state.console.print(line);
+            }
+            return Ok(());
+        }
+
+        if let Some(name) = &self.remove {
+            let n = state.saved_filters.len();
+            state.saved_filters.retain(|f| f.name != *name);
+            if state.saved_filters.len() == n {
+                return Err(format!("No saved filter named \"{}\"", name));
+            }
+            state.save_filters();
+            return Ok(());
+        }
+
+        // the console has already stripped quotes, so put them back
+        // around any words which had spaces in them
+        let expr = self
+            .expr
+            .iter()
+            .map(|s| {
+                if s.contains(char::is_whitespace) {
+                    format!("\"{}\"", s)
+                } else {
+                    s.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let query = VehicleQuery::parse(&expr)?;
+
+        let ids = state.find_vehicles(&query);
+        for id in &ids {
+            let name = state
+                .universe
+                .surface_vehicles
+                .get(id)
+                .map(|sv| sv.vehicle().name().to_string())
+                .unwrap_or_default();
+            state.console.print(format!("{}: {}", id, name));
+        }
+        state
+            .console
+            .print(format!("{} vehicle(s) match", ids.len()));
+
+        if self.select {
+            state.orbital_context.selected = ids.into_iter().collect();
+        }
+
+        if let Some(name) = &self.save {
+            let filter = SavedFilter {
+                name: name.clone(),
+                query: query.source().to_string(),
+                pinned: self.pin,
+            };
+            match state.saved_filters.iter_mut().find(|f| f.name == *name) {
+                Some(f) => *f = filter,
+                None => state.saved_filters.push(filter),
+            }
+            state.save_filters();
+        } else if self.pin {
+            return Err("Only saved filters can be pinned; give --save NAME".to_string());
+        }
+        Ok(())
+    }
+}
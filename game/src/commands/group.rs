@@ -0,0 +1,86 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use crate::groups::GroupStats;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Rename, recolor, merge or split groups of vehicles
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct EditGroup {
+    /// ID of the group. Lists every group if omitted
+    id: Option<EntityId>,
+
+    /// New name for the group
+    #[arg(long, short)]
+    name: Option<String>,
+
+    /// Index of the palette swatch to color the group with
+    #[arg(long, short)]
+    color: Option<usize>,
+
+    /// ID of another group whose members should join this one
+    #[arg(long)]
+    merge: Option<EntityId>,
+
+    /// Move the selected members into a new group
+    #[arg(long)]
+    split: bool,
+}
+
+impl Command for EditGroup {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let groups = state.universe.unique_groups();
+        let Some(gid) = self.id else {
+            let lines: Vec<String> = groups
+                .iter()
+                .map(|gid| {
+                    let stats = GroupStats::new(&state.universe, *gid);
+                    format!(
+                        "{} {} ({} members, {} fuel)",
+                        gid,
+                        state.group_name(*gid),
+                        stats.members,
+                        stats.fuel_mass
+                    )
+                })
+                .collect();
+            for line in lines {
+                state.console.print(line);
+            }
+            return Ok(());
+        };
+
+        if !groups.contains(&gid) {
+            return Err(format!("No group with ID {}", gid));
+        }
+
+        if let Some(name) = &self.name {
+            state.group_styles.entry(gid).or_default().name = Some(name.clone());
+        }
+
+        if let Some(i) = self.color {
+            let n = state.settings.color_palette.swatches().len();
+            if i >= n {
+                return Err(format!("Swatch must be less than {}", n));
+            }
+            state.group_styles.entry(gid).or_default().color = Some(i);
+        }
+
+        if let Some(other) = self.merge {
+            if !groups.contains(&other) {
+                return Err(format!("No group with ID {}", other));
+            }
+            state.merge_groups(gid, other);
+        }
+
+        if self.split {
+            let new = state
+                .split_group(gid)
+                .ok_or("Select some, but not all, members of the group to split")?;
+            state.console.print(format!("Split off group {}", new));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use crate::ownership::{Handover, HandoverTarget};
+use clap::Parser;
+use starling::prelude::*;
+
+/// Hand vehicles over to another group, or abandon or reclaim them as
+/// derelicts. Applies to the selected vehicles unless IDs are given, and
+/// asks for confirmation first
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct HandOver {
+    /// IDs of the vehicles to hand over
+    ids: Vec<EntityId>,
+
+    /// ID of the group to move them into
+    #[arg(long, short)]
+    to: Option<EntityId>,
+
+    /// Move them into a new group of their own
+    #[arg(long)]
+    new_group: bool,
+
+    /// Take them out of their groups
+    #[arg(long)]
+    ungroup: bool,
+
+    /// Leave them to drift as derelicts
+    #[arg(long)]
+    abandon: bool,
+
+    /// Take derelicts back into the fleet
+    #[arg(long)]
+    reclaim: bool,
+
+    /// List past handovers instead
+    #[arg(long)]
+    history: bool,
+}
+
+impl Command for HandOver {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        if self.history {
+            let lines: Vec<String> = state
+                .fleet
+                .history
+                .iter()
+                .map(|r| {
+                    let ids: Vec<String> =
+                        r.vehicles.iter().map(|(id, _)| id.to_string()).collect();
+                    format!(
+                        "[{}] {} {}",
+                        r.stamp,
+                        ids.join(", "),
+                        state.handover_destination(r.target)
+                    )
+                })
+                .collect();
+            for line in lines {
+                state.console.print(line);
+            }
+            return Ok(());
+        }
+
+        let mut targets = Vec::new();
+        if let Some(gid) = self.to {
+            targets.push(HandoverTarget::Group(gid));
+        }
+        for (flag, target) in [
+            (self.new_group, HandoverTarget::NewGroup),
+            (self.ungroup, HandoverTarget::Ungrouped),
+            (self.abandon, HandoverTarget::Abandon),
+            (self.reclaim, HandoverTarget::Reclaim),
+        ] {
+            if flag {
+                targets.push(target);
+            }
+        }
+        let [target] = targets[..] else {
+            return Err(
+                "Give exactly one of --to, --new-group, --ungroup, --abandon or --reclaim"
+                    .to_string(),
+            );
+        };
+
+        let ids = if self.ids.is_empty() {
+            let mut ids: Vec<EntityId> = state.orbital_context.selected.iter().copied().collect();
+            ids.sort();
+            ids
+        } else {
+            self.ids.clone()
+        };
+        state.request_handover(Handover { ids, target })
+    }
+}
@@ -0,0 +1,44 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+
+/// Show previously entered console commands
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct History {
+    /// Only show commands containing this text
+    filter: Option<String>,
+
+    /// Number of commands to show, most recent last
+    #[arg(long, short, default_value_t = 20)]
+    count: usize,
+
+    /// Forget all previously entered commands
+    #[arg(long)]
+    clear: bool,
+}
+
+impl Command for History {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        if self.clear {
+            state.console.clear_history();
+            return Ok(());
+        }
+
+        let filter = self.filter.as_deref().unwrap_or("");
+        let commands = &state.console.history().commands;
+        let mut lines: Vec<String> = commands
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, c)| c.contains(filter))
+            .take(self.count)
+            .map(|(i, c)| format!("{:>4}  {}", i + 1, c))
+            .collect();
+        lines.reverse();
+        for line in lines {
+            state.console.print(line);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+
+/// Share this universe with read-only observers on other instances
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Host {
+    /// Address and port to listen on. Only this machine can connect
+    /// unless a wider address, like 0.0.0.0:7313, is given
+    #[arg(default_value = "127.0.0.1:7313")]
+    bind: String,
+
+    /// Stop hosting, disconnecting every observer
+    #[arg(long)]
+    stop: bool,
+}
+
+impl Command for Host {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        if self.stop {
+            state.leave_network();
+            return Ok(());
+        }
+        state.host_observers(&self.bind)
+    }
+}
@@ -0,0 +1,29 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Import vehicle files from a directory into the vehicle directory
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct ImportVehicles {
+    /// Directory to search for vehicle files, including subdirectories
+    dir: PathBuf,
+
+    /// Check the files and report what would be imported, without
+    /// copying anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Command for ImportVehicles {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let n = state.start_vehicle_import(&self.dir, self.dry_run)?;
+        state.console.print(format!(
+            "Checking {} vehicle files in {}",
+            n,
+            self.dir.display()
+        ));
+        Ok(())
+    }
+}
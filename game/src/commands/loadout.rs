@@ -0,0 +1,116 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Pick the loadout preset newly spawned vehicles are filled with, or
+/// define presets for the vehicle open in the editor. With no arguments,
+/// lists the presets available
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct SelectLoadout {
+    /// Name of the preset to select or define
+    name: Vec<String>,
+
+    /// Stop applying a preset, spawning vehicles as they come
+    #[arg(long)]
+    none: bool,
+
+    /// Save a preset for the editor vehicle, stored with its file
+    #[arg(long)]
+    save: bool,
+
+    /// Remove a preset from the editor vehicle
+    #[arg(long)]
+    remove: bool,
+
+    /// Fraction of tank capacity to fill when saving. Defaults to how
+    /// full the editor vehicle's tanks are
+    #[arg(long)]
+    fuel: Option<f64>,
+
+    /// Cargo to load when saving, as ITEM=KG. May be given more than once
+    #[arg(long)]
+    cargo: Vec<String>,
+}
+
+impl Command for SelectLoadout {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let name = self.name.join(" ");
+
+        if self.none {
+            state.loadout = None;
+            state.console.print("Vehicles will spawn as they come");
+            return Ok(());
+        }
+
+        if self.save {
+            if name.trim().is_empty() {
+                return Err("No name given".to_string());
+            }
+            let mut loadout = Loadout::from_vehicle(name.trim(), &state.editor_context.vehicle);
+            if let Some(fuel) = self.fuel {
+                loadout.fuel = fuel.clamp(0.0, 1.0);
+            }
+            if !self.cargo.is_empty() {
+                loadout.manifest = self
+                    .cargo
+                    .iter()
+                    .map(|s| Loadout::parse_cargo(s))
+                    .collect::<Result<_, _>>()?;
+            }
+            let loadouts = &mut state.editor_context.loadouts;
+            loadouts.retain(|l| !l.name.eq_ignore_ascii_case(&loadout.name));
+            state.console.print(format!(
+                "Saved \"{}\" to the editor vehicle; save the vehicle to keep it",
+                loadout.name
+            ));
+            loadouts.push(loadout);
+            return Ok(());
+        }
+
+        if self.remove {
+            let loadouts = &mut state.editor_context.loadouts;
+            let n = loadouts.len();
+            loadouts.retain(|l| !l.name.eq_ignore_ascii_case(&name));
+            if loadouts.len() == n {
+                return Err(format!("No preset called \"{}\"", name));
+            }
+            return Ok(());
+        }
+
+        if name.is_empty() {
+            let presets = state
+                .editor_context
+                .loadouts
+                .iter()
+                .cloned()
+                .chain(Loadout::builtin());
+            for l in presets {
+                let selected = state
+                    .loadout
+                    .as_ref()
+                    .is_some_and(|s| s.eq_ignore_ascii_case(&l.name));
+                state.console.print(format!(
+                    "{} {} - {:0.0}% fuel, {} cargo items",
+                    if selected { "*" } else { " " },
+                    l.name,
+                    l.fuel * 100.0,
+                    l.manifest.len()
+                ));
+            }
+            return Ok(());
+        }
+
+        // presets stored in other vehicle files aren't known until one is
+        // spawned, so any name may be selected
+        if Loadout::find(&name, &state.editor_context.loadouts).is_none() {
+            state.console.print(format!(
+                "\"{}\" isn't a built-in preset; only vehicles which define it will use it",
+                name
+            ));
+        }
+        state.loadout = Some(name);
+        Ok(())
+    }
+}
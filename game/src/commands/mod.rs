@@ -9,3 +9,75 @@ pub use utilities::*;
 
 pub mod list_vehicles;
 pub use list_vehicles::*;
+
+pub mod rewind;
+pub use rewind::*;
+
+pub mod undelete;
+pub use undelete::*;
+
+pub mod control_law;
+pub use control_law::*;
+
+pub mod alarm;
+pub use alarm::*;
+
+pub mod events;
+pub use events::*;
+
+pub mod profile;
+pub use profile::*;
+
+pub mod assembly;
+pub use assembly::*;
+
+pub mod alias;
+pub use alias::*;
+
+pub mod history;
+pub use history::*;
+
+pub mod share_plan;
+pub use share_plan::*;
+
+pub mod import_vehicles;
+pub use import_vehicles::*;
+
+pub mod group;
+pub use group::*;
+
+pub mod waypoint;
+pub use waypoint::*;
+
+pub mod note;
+pub use note::*;
+
+pub mod spectate;
+pub use spectate::*;
+
+pub mod rename;
+pub use rename::*;
+
+pub mod loadout;
+pub use loadout::*;
+
+pub mod adjust;
+pub use adjust::*;
+
+pub mod host;
+pub use host::*;
+
+pub mod observe;
+pub use observe::*;
+
+pub mod handover;
+pub use handover::*;
+
+pub mod find;
+pub use find::*;
+
+pub mod normalize;
+pub use normalize::*;
+
+pub mod save_vehicle;
+pub use save_vehicle::*;
@@ -0,0 +1,72 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Re-derive every vehicle's orbit from its position and velocity,
+/// quarantining vehicles which can't be given a sound orbit. Also lists
+/// and discards quarantined vehicles
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Normalize {
+    /// Report orbits which put vehicles further than this from where they
+    /// are, in meters
+    #[arg(long)]
+    position: Option<f64>,
+
+    /// Report orbits whose velocity is off by more than this, in m/s
+    #[arg(long)]
+    velocity: Option<f64>,
+
+    /// List quarantined vehicles instead
+    #[arg(long, short)]
+    quarantined: bool,
+
+    /// Move a quarantined vehicle to the recycle bin
+    #[arg(long)]
+    discard: Option<EntityId>,
+}
+
+impl Command for Normalize {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        if let Some(id) = self.discard {
+            return state
+                .universe
+                .discard_quarantined(id)
+                .ok_or(format!("{} isn't quarantined", id));
+        }
+
+        if self.quarantined {
+            let lines: Vec<String> = state
+                .universe
+                .quarantine
+                .iter()
+                .map(|q| {
+                    format!(
+                        "[{}] {} \"{}\" {}",
+                        q.quarantined_at,
+                        q.id,
+                        q.vehicle.vehicle().name(),
+                        q.reason
+                    )
+                })
+                .collect();
+            for line in lines {
+                state.console.print(line);
+            }
+            return Ok(());
+        }
+
+        let default = NormalizationTolerance::default();
+        let report = state.normalize_orbits(NormalizationTolerance {
+            position: self.position.unwrap_or(default.position),
+            velocity: self.velocity.unwrap_or(default.velocity),
+        });
+        if report.is_clean() {
+            state
+                .console
+                .print(format!("Checked {} orbits, all consistent", report.checked));
+        }
+        Ok(())
+    }
+}
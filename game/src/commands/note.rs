@@ -0,0 +1,93 @@
+use crate::alarms::AlarmAction;
+use crate::commands::command::Command;
+use crate::game::GameState;
+use crate::timeline_notes::TimelineNote;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Leave, list or remove notes on the mission timeline
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct TakeNote {
+    /// Text of the note
+    text: Vec<String>,
+
+    /// Seconds of sim time from now at which to place the note
+    #[arg(long = "in", default_value_t = 0.0)]
+    secs: f64,
+
+    /// Absolute sim date in seconds at which to place the note instead
+    #[arg(long)]
+    at: Option<f64>,
+
+    /// Also set an alarm for the note: notify, pause or realtime
+    #[arg(long)]
+    alarm: Option<String>,
+
+    /// List every note
+    #[arg(long, short)]
+    list: bool,
+
+    /// Remove the note at this position in the list
+    #[arg(long)]
+    remove: Option<usize>,
+}
+
+impl Command for TakeNote {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let now = state.universe.stamp();
+
+        if self.list {
+            let lines: Vec<String> = state
+                .timeline_notes
+                .iter()
+                .enumerate()
+                .map(|(i, n)| {
+                    let alarm = n.alarm.map(|a| format!(" [{}]", a)).unwrap_or_default();
+                    format!("{} {} {}{}", i, n.stamp.to_date(), n.text, alarm)
+                })
+                .collect();
+            for line in lines {
+                state.console.print(line);
+            }
+            return Ok(());
+        }
+
+        if let Some(i) = self.remove {
+            let note = state
+                .timeline_notes
+                .remove(i)
+                .ok_or(format!("No note at position {}", i))?;
+            state.sync_timeline_note_alarms();
+            state
+                .console
+                .print(format!("Removed note \"{}\"", note.text));
+            return Ok(());
+        }
+
+        let text = self.text.join(" ");
+        if text.is_empty() {
+            return Err("No note text given".to_string());
+        }
+
+        let stamp = match self.at {
+            Some(secs) => Nanotime::secs_f64(secs),
+            None => now + Nanotime::secs_f64(self.secs),
+        };
+
+        let mut note = TimelineNote::new(text, stamp);
+        if let Some(s) = &self.alarm {
+            let action: AlarmAction = s.parse()?;
+            if stamp <= now {
+                return Err(format!("Alarm time {} is in the past", stamp.to_date()));
+            }
+            note = note.with_alarm(action);
+        }
+
+        state
+            .console
+            .print(format!("Noted \"{}\" at {}", note.text, stamp.to_date()));
+        state.add_timeline_note(note);
+        Ok(())
+    }
+}
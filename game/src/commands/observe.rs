@@ -0,0 +1,18 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+
+/// Watch a universe hosted by another instance. Turning off spectator
+/// mode disconnects
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Observe {
+    /// Address of the host, with the port if it isn't the default
+    host: String,
+}
+
+impl Command for Observe {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        state.observe(&self.host)
+    }
+}
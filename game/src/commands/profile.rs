@@ -0,0 +1,26 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+
+/// Toggle the profiling overlay showing per-stage frame timings
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Profile {
+    /// Discard the timings collected so far
+    #[arg(long)]
+    reset: bool,
+}
+
+impl Command for Profile {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        if self.reset {
+            state.profiler.clear();
+            state.console.print("Cleared profiler timings");
+            return Ok(());
+        }
+        state.profiler.show = !state.profiler.show;
+        let s = if state.profiler.show { "on" } else { "off" };
+        state.console.print(format!("Profiling overlay {}", s));
+        Ok(())
+    }
+}
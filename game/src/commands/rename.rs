@@ -0,0 +1,41 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Give a vehicle a new callsign. Names already in use get a designation
+/// number, like "Infallible-2"
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Rename {
+    /// New name of the vehicle
+    name: Vec<String>,
+
+    /// ID of the vehicle. Defaults to the piloted vehicle
+    #[arg(long)]
+    id: Option<EntityId>,
+}
+
+impl Command for Rename {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let name = self.name.join(" ");
+        if name.trim().is_empty() {
+            return Err("No name given".to_string());
+        }
+
+        let id = self
+            .id
+            .or(state.piloting())
+            .ok_or("No vehicle specified and not piloting anything")?;
+
+        let old = state.callsign(id);
+        let callsign = state
+            .universe
+            .rename_vehicle(id, &name)
+            .ok_or(format!("No vehicle with ID {}", id))?;
+        state
+            .console
+            .print(format!("Renamed \"{}\" to \"{}\"", old, callsign));
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Restore the simulation to a previous checkpoint
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Rewind {
+    /// How far back to rewind, in seconds of sim time
+    #[arg(default_value_t = 30)]
+    secs: i64,
+}
+
+impl Command for Rewind {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let now = state.universe.stamp();
+        let stamp = state
+            .checkpoints
+            .rewind(&mut state.universe, Nanotime::secs(self.secs))
+            .ok_or(format!(
+                "No checkpoint at least {} seconds old ({} available)",
+                self.secs,
+                state.checkpoints.len()
+            ))?;
+        state.console.print(format!(
+            "Rewound from {} to {}",
+            now.to_date(),
+            stamp.to_date()
+        ));
+        Ok(())
+    }
+}
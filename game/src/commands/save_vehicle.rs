@@ -0,0 +1,37 @@
+use crate::commands::command::Command;
+use crate::craft_editor::EditorContext;
+use crate::game::GameState;
+use clap::Parser;
+
+/// Save the vehicle being edited into the vehicle library, where it shows
+/// up in the Vehicles menu. Asks before replacing a vehicle already saved
+/// under the same name
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct SaveVehicle {
+    /// Name to save the vehicle under, which is also its file name
+    name: Vec<String>,
+
+    /// Replace a vehicle already saved under this name
+    #[arg(long)]
+    overwrite: bool,
+}
+
+impl Command for SaveVehicle {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let name = self.name.join(" ");
+        let path = EditorContext::library_path(&state.args, &name)?;
+        if path.exists() && !self.overwrite {
+            state.console.print(format!(
+                "\"{}\" is already in the library; press enter to overwrite it",
+                name.trim()
+            ));
+            state
+                .console
+                .set_cmd(format!("savevehicle {} --overwrite", name.trim()));
+            return Err(format!("{} already exists", path.display()));
+        }
+        EditorContext::save_to_library(state, &path)
+            .ok_or(format!("Failed to write {}", path.display()))
+    }
+}
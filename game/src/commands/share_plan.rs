@@ -0,0 +1,44 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Share a maneuver plan with the selected vehicles
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct SharePlan {
+    /// ID of the vehicle to copy the plan from. Defaults to the piloted
+    /// vehicle's plan to the selected orbit
+    #[arg(long, short)]
+    from: Option<EntityId>,
+
+    /// Minimum seconds between each vehicle's first burn. Defaults to
+    /// the value in the settings
+    #[arg(long, short)]
+    interval: Option<u32>,
+
+    /// Cancel the scheduled plans of the selected vehicles instead
+    #[arg(long)]
+    cancel: bool,
+}
+
+impl Command for SharePlan {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        if self.cancel {
+            let ids: Vec<EntityId> = state.orbital_context.selected.iter().copied().collect();
+            let n = ids
+                .into_iter()
+                .filter(|id| state.universe.maneuvers.cancel(*id).is_some())
+                .count();
+            state.console.print(format!("Cancelled {} plans", n));
+            return Ok(());
+        }
+
+        state.copy_plan(self.from)?;
+        let secs = self
+            .interval
+            .unwrap_or(state.settings.shared_plan_stagger_secs);
+        state.paste_plan(Nanotime::secs(secs as i64))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+
+/// Enter read-only spectator mode, optionally with the camera director
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Spectate {
+    /// Cycle the camera between interesting vehicles
+    #[arg(long, short)]
+    director: bool,
+}
+
+impl Command for Spectate {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        state.set_spectating(true);
+        state.spectator.set_director(self.director);
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::commands::command::Command;
+use crate::game::GameState;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Restore a deleted vehicle from the recycle bin
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct Undelete {
+    /// ID of the vehicle to restore. Defaults to the most recently deleted
+    id: Option<EntityId>,
+
+    /// List the contents of the recycle bin instead
+    #[arg(long, short)]
+    list: bool,
+}
+
+impl Command for Undelete {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        let stamp = state.universe.stamp();
+        let bin = &state.universe.recycle_bin;
+
+        if self.list {
+            let lines: Vec<String> = bin
+                .iter()
+                .map(|e| {
+                    let remaining = bin.time_remaining(e.id, stamp).unwrap_or_default();
+                    format!(
+                        "{} {} ({} remaining)",
+                        e.id,
+                        e.vehicle.vehicle.title(),
+                        remaining
+                    )
+                })
+                .collect();
+            for line in lines {
+                state.console.print(line);
+            }
+            return Ok(());
+        }
+
+        let id = match self.id {
+            Some(id) => id,
+            None => bin.iter().last().ok_or("Recycle bin is empty")?.id,
+        };
+
+        state
+            .undelete_orbiter(id)
+            .ok_or(format!("Orbiter {} is not in the recycle bin", id))
+    }
+}
@@ -0,0 +1,81 @@
+use crate::camera_controller::CameraProjection;
+use crate::commands::command::Command;
+use crate::game::GameState;
+use crate::paused_actions::PausedAction;
+use clap::Parser;
+use starling::prelude::*;
+
+/// Place, list, remove or target named waypoints
+#[derive(Parser, Debug, Default, Clone)]
+#[command(about, long_about)]
+pub struct PlaceWaypoint {
+    /// Name of the new waypoint, placed at the center of the camera
+    name: Option<String>,
+
+    /// Place the waypoint at the piloted vehicle instead
+    #[arg(long, short)]
+    pilot: bool,
+
+    /// List every waypoint
+    #[arg(long, short)]
+    list: bool,
+
+    /// ID of a waypoint to remove
+    #[arg(long, short)]
+    remove: Option<EntityId>,
+
+    /// ID of a waypoint for the piloted vehicle to target
+    #[arg(long, short)]
+    target: Option<EntityId>,
+}
+
+impl Command for PlaceWaypoint {
+    fn execute(&self, state: &mut GameState) -> Result<(), String> {
+        if self.list {
+            let mut ids: Vec<EntityId> = state.universe.waypoints.keys().copied().collect();
+            ids.sort();
+            for id in ids {
+                let w = &state.universe.waypoints[&id];
+                let line = format!("{} {} around {}", id, w.name, w.planet_id);
+                state.console.print(line);
+            }
+            return Ok(());
+        }
+
+        if let Some(id) = self.remove {
+            return match state.universe.remove_waypoint(id) {
+                Some(w) => {
+                    state.console.print(format!("Removed waypoint {}", w.name));
+                    Ok(())
+                }
+                None => Err(format!("No waypoint with ID {}", id)),
+            };
+        }
+
+        if let Some(id) = self.target {
+            if !state.universe.waypoints.contains_key(&id) {
+                return Err(format!("No waypoint with ID {}", id));
+            }
+            let pilot = state.piloting().ok_or("Not piloting a vehicle")?;
+            state.apply_or_queue(pilot, PausedAction::SetTarget(Some(id)));
+            return Ok(());
+        }
+
+        let pos = if self.pilot {
+            let pilot = state.piloting().ok_or("Not piloting a vehicle")?;
+            state
+                .universe
+                .pv(pilot)
+                .ok_or("Piloted vehicle not found")?
+                .pos
+        } else {
+            state.orbital_context.origin()
+        };
+
+        let id = state
+            .place_waypoint(self.name.clone(), pos)
+            .ok_or("No planet nearby to place a waypoint around")?;
+        state.console.print(format!("Placed waypoint {}", id));
+        Ok(())
+    }
+}
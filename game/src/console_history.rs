@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+/// Most commands kept in the console history; older ones are forgotten.
+pub const CONSOLE_HISTORY_LIMIT: usize = 500;
+
+/// Commands entered into the debug console, most recent last, and
+/// user-defined aliases for them. Saved per profile.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConsoleHistory {
+    #[serde(default)]
+    pub commands: Vec<String>,
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl ConsoleHistory {
+    /// Records an entered command. Repeats of the previous command aren't
+    /// recorded again.
+    pub fn push(&mut self, cmd: &str) {
+        let cmd = cmd.trim();
+        if cmd.is_empty() || self.commands.last().is_some_and(|c| c == cmd) {
+            return;
+        }
+        self.commands.push(cmd.to_string());
+        if self.commands.len() > CONSOLE_HISTORY_LIMIT {
+            let excess = self.commands.len() - CONSOLE_HISTORY_LIMIT;
+            self.commands.drain(..excess);
+        }
+    }
+
+    /// The command entered `back` commands ago, where zero is the most
+    /// recent one.
+    pub fn get(&self, back: usize) -> Option<&str> {
+        self.commands.iter().rev().nth(back).map(|s| s.as_str())
+    }
+
+    /// The most recent command containing `query`, after skipping `skip`
+    /// more recent matches.
+    pub fn search(&self, query: &str, skip: usize) -> Option<&str> {
+        self.commands
+            .iter()
+            .rev()
+            .filter(|c| c.contains(query))
+            .nth(skip)
+            .map(|s| s.as_str())
+    }
+
+    /// Splits a command into arguments, substituting its first word if
+    /// it names an alias. Aliases aren't expanded recursively.
+    pub fn expand(&self, cmd: &str) -> Result<Vec<String>, shellwords::MismatchedQuotes> {
+        let mut args = shellwords::split(cmd)?;
+        if let Some(alias) = args.first().and_then(|a| self.aliases.get(a)) {
+            let mut expanded = shellwords::split(alias)?;
+            expanded.extend(args.drain(1..));
+            args = expanded;
+        }
+        Ok(args)
+    }
+}
+
+pub fn load_console_history_from_file(filename: &Path) -> Result<ConsoleHistory, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    Ok(serde_yaml::from_str(&s)?)
+}
+
+pub fn write_console_history_to_file(
+    history: &ConsoleHistory,
+    filename: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(history)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_recalls_searches_and_expands_aliases() {
+        let mut h = ConsoleHistory::default();
+        for cmd in ["load grid", "rewind 3", "rewind 3", "  ", "load ship"] {
+            h.push(cmd);
+        }
+        assert_eq!(h.commands, vec!["load grid", "rewind 3", "load ship"]);
+        assert_eq!(h.get(0), Some("load ship"));
+        assert_eq!(h.get(2), Some("load grid"));
+        assert_eq!(h.get(3), None);
+
+        assert_eq!(h.search("load", 0), Some("load ship"));
+        assert_eq!(h.search("load", 1), Some("load grid"));
+        assert_eq!(h.search("load", 2), None);
+
+        h.aliases.insert("lg".into(), "load grid".into());
+        assert_eq!(
+            h.expand("lg --fast").unwrap(),
+            vec!["load", "grid", "--fast"]
+        );
+        assert_eq!(h.expand("\"lg\" x").unwrap(), vec!["load", "grid", "x"]);
+        assert_eq!(h.expand("rewind 3").unwrap(), vec!["rewind", "3"]);
+        assert!(h.expand("lg \"oops").is_err());
+
+        for i in 0..CONSOLE_HISTORY_LIMIT + 10 {
+            h.push(&format!("cmd {}", i));
+        }
+        assert_eq!(h.commands.len(), CONSOLE_HISTORY_LIMIT);
+        assert_eq!(
+            h.get(0),
+            Some(format!("cmd {}", CONSOLE_HISTORY_LIMIT + 9).as_str())
+        );
+
+        let yaml = serde_yaml::to_string(&h).unwrap();
+        let back: ConsoleHistory = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back, h);
+    }
+}
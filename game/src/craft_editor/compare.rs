@@ -0,0 +1,253 @@
+use crate::canvas::Canvas;
+use crate::drawing::draw_aabb;
+use crate::onclick::OnClick;
+use crate::ui::UI_BACKGROUND_COLOR;
+use bevy::color::palettes::css::*;
+use bevy::prelude::*;
+use layout::layout::{Node, Size};
+use starling::prelude::*;
+use std::path::PathBuf;
+
+/// Gap in meters between the edited vehicle and the ghost of the vehicle
+/// it's being compared against.
+pub const COMPARE_GAP_METERS: f32 = 4.0;
+
+const BETTER_COLOR: [f32; 4] = [0.1, 0.4, 0.1, 1.0];
+const WORSE_COLOR: [f32; 4] = [0.4, 0.1, 0.1, 1.0];
+
+/// Headline figures used to compare two vehicle designs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VehicleStats {
+    pub mass: Mass,
+    pub dv: f64,
+    pub twr: f64,
+    pub parts: usize,
+    pub cost: u32,
+}
+
+impl VehicleStats {
+    pub fn new(vehicle: &Vehicle) -> Self {
+        Self {
+            mass: vehicle.total_mass(),
+            dv: vehicle.remaining_dv(),
+            twr: vehicle.accel() / 9.81,
+            parts: vehicle.parts().count(),
+            cost: vehicle.build_cost(),
+        }
+    }
+}
+
+/// One line of the comparison table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatRow {
+    pub label: &'static str,
+    pub current: String,
+    pub other: String,
+    pub delta: String,
+    /// Whether the edited vehicle beats the other one on this stat, or
+    /// None if they're equal.
+    pub better: Option<bool>,
+}
+
+impl StatRow {
+    fn new(
+        label: &'static str,
+        current: f64,
+        other: f64,
+        higher_is_better: bool,
+        fmt: impl Fn(f64) -> String,
+    ) -> Self {
+        let delta = current - other;
+        let better = if delta == 0.0 {
+            None
+        } else {
+            Some((delta > 0.0) == higher_is_better)
+        };
+        let sign = if delta > 0.0 { "+" } else { "" };
+        Self {
+            label,
+            current: fmt(current),
+            other: fmt(other),
+            delta: format!("{}{}", sign, fmt(delta)),
+            better,
+        }
+    }
+}
+
+/// Row-by-row difference between the edited vehicle and another design.
+/// Deltas are the edited vehicle's value minus the other's.
+pub fn stat_diff(current: &VehicleStats, other: &VehicleStats) -> Vec<StatRow> {
+    vec![
+        StatRow::new(
+            "Mass",
+            current.mass.to_kg_f64(),
+            other.mass.to_kg_f64(),
+            false,
+            |x| format!("{:0.0} kg", x),
+        ),
+        StatRow::new("DV", current.dv, other.dv, true, |x| {
+            format!("{:0.0} m/s", x)
+        }),
+        StatRow::new("TWR", current.twr, other.twr, true, |x| {
+            format!("{:0.2}", x)
+        }),
+        StatRow::new(
+            "Parts",
+            current.parts as f64,
+            other.parts as f64,
+            false,
+            |x| format!("{:0.0}", x),
+        ),
+        StatRow::new("Cost", current.cost as f64, other.cost as f64, false, |x| {
+            format!("{:0.0}", x)
+        }),
+    ]
+}
+
+/// A saved vehicle loaded alongside the edited one for comparison.
+#[derive(Debug)]
+pub struct Comparison {
+    pub path: PathBuf,
+    pub vehicle: Vehicle,
+    pub stats: VehicleStats,
+}
+
+impl Comparison {
+    pub fn new(path: PathBuf, vehicle: Vehicle) -> Self {
+        let stats = VehicleStats::new(&vehicle);
+        Self {
+            path,
+            vehicle,
+            stats,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Where the ghost is drawn in the editor's frame, so that it sits
+    /// just to the right of the edited vehicle.
+    pub fn ghost_offset(&self, current: &Vehicle) -> Vec2 {
+        let lhs = current.aabb();
+        let rhs = self.vehicle.aabb();
+        Vec2::X * (lhs.upper().x - rhs.lower().x + COMPARE_GAP_METERS)
+    }
+}
+
+pub fn compare_table(
+    button_height: f32,
+    current: &Vehicle,
+    comparison: &Comparison,
+) -> Node<OnClick> {
+    let text = |s: String| Node::text(Size::Grow, button_height, s).enabled(false);
+
+    let header = Node::row(Size::Fit)
+        .invisible()
+        .with_padding(0.0)
+        .with_child(text("".into()))
+        .with_child(text("This".into()))
+        .with_child(text(comparison.name()))
+        .with_child(text("Delta".into()));
+
+    let stats = VehicleStats::new(current);
+    let rows = stat_diff(&stats, &comparison.stats).into_iter().map(|row| {
+        let delta = Node::text(Size::Grow, button_height, row.delta).enabled(false);
+        let delta = match row.better {
+            Some(true) => delta.with_color(BETTER_COLOR),
+            Some(false) => delta.with_color(WORSE_COLOR),
+            None => delta,
+        };
+        Node::row(Size::Fit)
+            .invisible()
+            .with_padding(0.0)
+            .with_child(text(row.label.to_string()))
+            .with_child(text(row.current))
+            .with_child(text(row.other))
+            .with_child(delta)
+    });
+
+    Node::structural(Size::Grow, Size::Fit)
+        .with_color(UI_BACKGROUND_COLOR)
+        .down()
+        .with_child(header)
+        .with_child(Node::hline())
+        .with_children(rows)
+        .with_child(Node::hline())
+        .with_child(Node::button(
+            "Clear Comparison",
+            OnClick::ClearComparison,
+            Size::Grow,
+            button_height,
+        ))
+}
+
+fn draw_silhouette(canvas: &mut Canvas, vehicle: &Vehicle, pos: Vec2, scale: f32, color: Srgba) {
+    for (_, part) in vehicle.parts() {
+        let dims = part.dims_meters() * scale;
+        let center = pos + part.center_meters() * scale;
+        draw_aabb(canvas, AABB::new(center, dims), color);
+    }
+}
+
+/// Draws the compared vehicle as a ghost beside the edited one, and both
+/// silhouettes overlaid on the ghost at the same scale.
+pub fn draw_comparison(
+    canvas: &mut Canvas,
+    current: &Vehicle,
+    comparison: &Comparison,
+    origin: Vec2,
+    scale: f32,
+) {
+    let ghost = origin + comparison.ghost_offset(current) * scale;
+    draw_silhouette(
+        canvas,
+        &comparison.vehicle,
+        ghost,
+        scale,
+        WHITE.with_alpha(0.25),
+    );
+    draw_silhouette(canvas, current, ghost, scale, ORANGE.with_alpha(0.35));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stat_diff_judges_each_direction() {
+        let current = VehicleStats {
+            mass: Mass::from_kg_f32(1000.0),
+            dv: 800.0,
+            twr: 1.5,
+            parts: 12,
+            cost: 400,
+        };
+        let other = VehicleStats {
+            mass: Mass::from_kg_f32(1200.0),
+            dv: 900.0,
+            twr: 1.5,
+            parts: 10,
+            cost: 400,
+        };
+
+        let rows = stat_diff(&current, &other);
+        let better: Vec<_> = rows.iter().map(|r| (r.label, r.better)).collect();
+        assert_eq!(
+            better,
+            vec![
+                ("Mass", Some(true)),
+                ("DV", Some(false)),
+                ("TWR", None),
+                ("Parts", Some(false)),
+                ("Cost", None),
+            ]
+        );
+        assert_eq!(rows[0].delta, "-200 kg");
+        assert_eq!(rows[1].delta, "-100 m/s");
+        assert_eq!(rows[3].delta, "+2");
+    }
+}
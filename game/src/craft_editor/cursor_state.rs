@@ -5,6 +5,7 @@ pub enum CursorState {
     #[default]
     None,
     Part(PartPrototype),
+    Assembly(Assembly),
 }
 
 impl CursorState {
@@ -14,4 +15,11 @@ impl CursorState {
             _ => None,
         }
     }
+
+    pub fn current_assembly(&self) -> Option<&Assembly> {
+        match self {
+            Self::Assembly(a) => Some(a),
+            _ => None,
+        }
+    }
 }
@@ -6,13 +6,14 @@ use crate::drawing::*;
 use crate::game::GameState;
 use crate::input::InputState;
 use crate::input::{FrameId, MouseButt};
+use crate::keybindings::KeyAction;
 use crate::names::*;
 use crate::onclick::OnClick;
 use crate::scenes::Render;
+use crate::tween::Collapsible;
 use crate::ui::*;
 use crate::z_index::ZOrdering;
 use bevy::color::palettes::css::*;
-use bevy::input::keyboard::KeyCode;
 use bevy::prelude::*;
 use layout::layout::{Node, Size, Tree};
 use rfd::FileDialog;
@@ -24,6 +25,8 @@ use std::path::{Path, PathBuf};
 pub enum Action {
     Add(IVec2, Rotation, PartPrototype),
     Remove(IVec2, Rotation, PartPrototype),
+    AddAssembly(Vec<(IVec2, Rotation, PartPrototype)>, Vec<IVec2>),
+    AddPipes(Vec<IVec2>),
 }
 
 impl Action {
@@ -31,6 +34,8 @@ impl Action {
         match self {
             Self::Add(_, _, proto) => format!("Add {}", proto.part_name()),
             Self::Remove(_, _, proto) => format!("Remove {}", proto.part_name()),
+            Self::AddAssembly(parts, _) => format!("Add {} parts", parts.len()),
+            Self::AddPipes(pipes) => format!("Add {} pipes", pipes.len()),
         }
     }
 }
@@ -40,9 +45,20 @@ pub struct EditorContext {
     camera: LinearCameraController,
     cursor_state: CursorState,
     rotation: Rotation,
+    /// Whether placed parts get mirrored copies.
+    pub symmetry: Symmetry,
     filepath: Option<PathBuf>,
     focus_layer: Option<PartLayer>,
     selected_part: Option<PartId>,
+    /// Stage that newly placed parts are put in.
+    pub stage: usize,
+    /// Pixel bounds dragged out around parts to save as an assembly.
+    selection: Option<(IVec2, IVec2)>,
+    /// Part picked as the start of an automatically routed pipe.
+    route_from: Option<PartId>,
+    /// Part picked as the end of the route, and the pipe cells joining
+    /// them, awaiting acceptance.
+    route_preview: Option<(PartId, Vec<IVec2>)>,
     snap_info: Option<(IVec2, UVec2)>,
     action_queue: Vec<Action>,
     occupied: HashMap<PartLayer, HashMap<IVec2, PartId>>,
@@ -54,9 +70,38 @@ pub struct EditorContext {
 
     // menus
     pub show_vehicle_info: bool,
-    pub parts_menu_collapsed: bool,
-    pub vehicles_menu_collapsed: bool,
-    pub layers_menu_collapsed: bool,
+    /// Whether to show which tanks feed which engines, and animate flow
+    /// along the pipes.
+    pub show_flow_preview: bool,
+    pub parts_menu: Collapsible,
+    /// Search text and layer narrowing down the parts menu.
+    pub part_search: PartSearch,
+    pub vehicles_menu: Collapsible,
+    pub layers_menu: Collapsible,
+    pub assemblies_menu: Collapsible,
+    pub stages_menu: Collapsible,
+
+    /// Saved vehicles shown in the vehicles menu. None when the gallery
+    /// needs to be rebuilt from disk.
+    pub gallery: Option<Vec<VehicleSummary>>,
+
+    /// Saved assemblies shown in the assemblies menu. None when they need
+    /// to be read from disk again.
+    pub assemblies: Option<Vec<Assembly>>,
+
+    /// Another saved vehicle drawn beside this one, with a table of how
+    /// their stats differ.
+    pub comparison: Option<Comparison>,
+
+    /// Loadout presets saved along with the vehicle.
+    pub loadouts: Vec<Loadout>,
+
+    /// Recent versions of the vehicle, for getting back unsaved work. None
+    /// until read from disk on first opening the editor.
+    pub recovery: Option<RecoveryRing>,
+    /// Wall time the vehicle was last captured for recovery.
+    last_recovery: Nanotime,
+    pub show_recovery: bool,
 
     // construction bots
     pub bots: Vec<ConBot>,
@@ -68,9 +113,14 @@ impl EditorContext {
             camera: LinearCameraController::new(DVec2::ZERO, 18.0, 1100.0),
             cursor_state: CursorState::None,
             rotation: Rotation::East,
+            symmetry: Symmetry::Off,
             filepath: None,
             focus_layer: None,
             selected_part: None,
+            stage: 0,
+            selection: None,
+            route_from: None,
+            route_preview: None,
             snap_info: None,
             action_queue: Vec::new(),
             occupied: HashMap::new(),
@@ -79,9 +129,20 @@ impl EditorContext {
             build_particles: Vec::new(),
             atmo: 3,
             show_vehicle_info: false,
-            parts_menu_collapsed: false,
-            vehicles_menu_collapsed: true,
-            layers_menu_collapsed: false,
+            show_flow_preview: false,
+            parts_menu: Collapsible::new(false),
+            part_search: PartSearch::default(),
+            vehicles_menu: Collapsible::new(true),
+            layers_menu: Collapsible::new(false),
+            assemblies_menu: Collapsible::new(true),
+            stages_menu: Collapsible::new(true),
+            gallery: None,
+            assemblies: None,
+            comparison: None,
+            loadouts: Vec::new(),
+            recovery: None,
+            last_recovery: Nanotime::zero(),
+            show_recovery: false,
             bots: (0..24)
                 .map(|_| {
                     let p = randvec(10.0, 50.0);
@@ -104,10 +165,59 @@ impl EditorContext {
                 Err(s) => println!("Failed to remove: {}", s),
             },
             Action::Remove(pos, rot, proto) => self.add_part(pos, rot, proto),
+            Action::AddAssembly(parts, pipes) => {
+                for (pos, _, proto) in parts {
+                    _ = self.vehicle.remove_part_at(pos, proto.layer());
+                }
+                for p in pipes {
+                    self.vehicle.remove_pipe(p);
+                }
+                self.update();
+            }
+            Action::AddPipes(pipes) => {
+                for p in pipes {
+                    self.vehicle.remove_pipe(p);
+                }
+                self.update();
+            }
         }
         Some(())
     }
 
+    /// Starts routing a pipe from a part; the route is previewed once
+    /// another part is picked.
+    pub fn start_pipe_route(&mut self, from: PartId) {
+        self.route_from = Some(from);
+        self.route_preview = None;
+    }
+
+    /// Previews the shortest route from the part picked first to `to`.
+    /// Picking another part reroutes to that one instead.
+    fn route_pipe_to(&mut self, to: PartId) {
+        let Some(from) = self.route_from else {
+            return;
+        };
+        self.route_preview = route_pipe(&self.vehicle, from, to).map(|cells| (to, cells));
+    }
+
+    pub fn accept_pipe_route(&mut self) -> Option<()> {
+        let (_, cells) = self.route_preview.take()?;
+        self.route_from = None;
+        let new: Vec<_> = cells
+            .into_iter()
+            .filter(|p| !self.vehicle.has_pipe(*p))
+            .collect();
+        self.vehicle.add_pipes(new.iter().copied());
+        self.action_queue.push(Action::AddPipes(new));
+        self.update();
+        Some(())
+    }
+
+    pub fn cancel_pipe_route(&mut self) {
+        self.route_from = None;
+        self.route_preview = None;
+    }
+
     pub fn selected_part(&self) -> Option<&InstantiatedPart> {
         self.vehicle.get_part(self.selected_part?)
     }
@@ -121,10 +231,66 @@ impl EditorContext {
         ))
     }
 
+    /// Parts lying entirely within the selection.
+    pub fn selected_parts(&self) -> impl Iterator<Item = &InstantiatedPart> + use<'_> {
+        self.vehicle.parts().map(|(_, p)| p).filter(|p| {
+            self.selection.is_some_and(|(lower, upper)| {
+                let end = p.origin() + p.dims_grid().as_ivec2();
+                p.origin().cmpge(lower).all() && end.cmple(upper).all()
+            })
+        })
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    pub fn set_current_assembly(&mut self, name: &str) {
+        if let Some(a) = self.assemblies.iter().flatten().find(|a| a.name == name) {
+            self.cursor_state = CursorState::Assembly(a.clone());
+        }
+    }
+
+    /// Saves the selected parts as an assembly. Unnamed assemblies are
+    /// numbered after the ones already saved.
+    pub fn save_assembly(state: &mut GameState, name: Option<String>) -> Option<()> {
+        let dir = state.args.assemblies_dir();
+        let name = name.unwrap_or_else(|| {
+            (1..)
+                .map(|i| format!("assembly-{}", i))
+                .find(|n| !dir.join(format!("{}.yaml", n)).exists())
+                .unwrap_or_default()
+        });
+
+        let ctx = &state.editor_context;
+        let Some(assembly) = ctx
+            .selection
+            .and_then(|(lower, upper)| Assembly::from_region(&name, &ctx.vehicle, lower, upper))
+        else {
+            state.notice("Select some parts to save as an assembly");
+            return None;
+        };
+
+        let path = dir.join(format!("{}.yaml", name));
+        if let Err(e) = write_assembly_to_file(&assembly, &path) {
+            state.notice(format!("Failed to save assembly: {}", e));
+            return None;
+        }
+        state.notice(format!(
+            "Saved {} parts as assembly \"{}\"",
+            assembly.parts.len(),
+            name
+        ));
+        state.editor_context.assemblies = None;
+        state.editor_context.selection = None;
+        Some(())
+    }
+
     pub fn new_craft(&mut self) {
         self.filepath = None;
         self.vehicle = Vehicle::new();
         self.cursor_state = CursorState::None;
+        self.cancel_pipe_route();
         self.update();
     }
 
@@ -132,6 +298,10 @@ impl EditorContext {
         write_image_to_file(&self.vehicle, args, "vehicle");
     }
 
+    pub fn write_svg_to_file(&self) -> Option<()> {
+        write_svg_to_file(&self.vehicle, "vehicle")
+    }
+
     pub fn rotate_craft(&mut self) {
         let new_instances: Vec<_> = self
             .vehicle
@@ -179,6 +349,31 @@ impl EditorContext {
         }
     }
 
+    pub fn is_placing_part(&self) -> bool {
+        matches!(self.cursor_state, CursorState::Part(_))
+    }
+
+    pub fn is_plumbing(&self) -> bool {
+        self.focus_layer == Some(PartLayer::Plumbing)
+    }
+
+    /// Swaps a stage with the one after it if `later`, or else the one
+    /// before it, keeping the selected stage on the same parts.
+    pub fn move_stage(&mut self, stage: usize, later: bool) -> Option<()> {
+        let other = if later {
+            stage + 1
+        } else {
+            stage.checked_sub(1)?
+        };
+        self.vehicle.swap_stages(stage, other);
+        if self.stage == stage {
+            self.stage = other;
+        } else if self.stage == other {
+            self.stage = stage;
+        }
+        Some(())
+    }
+
     pub fn toggle_layer(&mut self, layer: PartLayer) {
         self.focus_layer = if self.focus_layer == Some(layer) {
             None
@@ -190,26 +385,50 @@ impl EditorContext {
     pub fn save_to_file(state: &mut GameState) -> Option<()> {
         let choice: PathBuf = state.editor_context.open_file_to_save()?;
         state.notice(format!("Saving to {}", choice.display()));
+        EditorContext::write_vehicle_file(state, &choice)
+    }
 
-        let parts = state
-            .editor_context
-            .vehicle
-            .parts()
-            .map(|(_, instance)| VehiclePartFileStorage {
-                partname: instance.prototype().sprite_path().to_string(),
-                pos: instance.origin(),
-                rot: instance.rotation(),
-            })
-            .collect();
+    /// Where a vehicle saved into the library under `name` goes, if the
+    /// name can be used as a file name.
+    pub fn library_path(args: &ProgramContext, name: &str) -> Result<PathBuf, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("No name given".to_string());
+        }
+        if name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(format!("\"{}\" can't be used as a file name", name));
+        }
+        Ok(args.vehicle_dir().join(format!("{}.vehicle", name)))
+    }
+
+    /// Saves the vehicle being edited into the vehicle library, so that it
+    /// shows up in the Vehicles menu right away. Later saves go to the
+    /// same file.
+    pub fn save_to_library(state: &mut GameState, path: &Path) -> Option<()> {
+        std::fs::create_dir_all(path.parent()?).ok()?;
+        EditorContext::write_vehicle_file(state, path)?;
+        state.editor_context.filepath = Some(path.to_path_buf());
+        state.notice(format!("Saved to library as {}", path.display()));
+        Some(())
+    }
 
+    fn write_vehicle_file(state: &mut GameState, choice: &Path) -> Option<()> {
         let storage = VehicleFileStorage {
-            name: state.editor_context.vehicle.model().to_string(),
-            parts,
-            lines: state.editor_context.vehicle.pipes().collect(),
+            loadouts: state.editor_context.loadouts.clone(),
+            ..VehicleFileStorage::from_vehicle(&state.editor_context.vehicle)
         };
 
         let s = serde_yaml::to_string(&storage).ok()?;
-        std::fs::write(choice, s).ok()
+        std::fs::write(choice, s).ok()?;
+
+        let name = choice.file_stem()?.to_string_lossy().to_string();
+        write_thumbnail(
+            &state.editor_context.vehicle,
+            &state.args.parts_dir(),
+            &state.args.thumbnail_path(&name),
+        );
+        state.refresh_vehicles();
+        Some(())
     }
 
     pub fn load_from_file(state: &mut GameState) -> Option<()> {
@@ -217,6 +436,66 @@ impl EditorContext {
         EditorContext::load_vehicle(&choice, state)
     }
 
+    /// Adds the vehicle being edited to the recovery ring, writing the
+    /// ring to disk if it changed.
+    pub fn capture_recovery(state: &mut GameState) {
+        state.editor_context.last_recovery = state.wall_time;
+        let ctx = &mut state.editor_context;
+        let Some(ring) = ctx.recovery.as_mut() else {
+            return;
+        };
+        let storage = VehicleFileStorage {
+            loadouts: ctx.loadouts.clone(),
+            ..VehicleFileStorage::from_vehicle(&ctx.vehicle)
+        };
+        if !ring.push(RecoveryVersion::new(storage, ctx.filepath.clone())) {
+            return;
+        }
+        if let Err(e) = write_recovery_to_file(ring, &state.args.editor_recovery_path()) {
+            error!("Failed to write editor recovery file: {e}");
+        }
+    }
+
+    /// Replaces the vehicle being edited with a recovered version, after
+    /// capturing the current one so that it can be stepped forward to.
+    pub fn restore_recovery(state: &mut GameState, i: usize) -> Option<()> {
+        EditorContext::capture_recovery(state);
+        let version = state.editor_context.recovery.as_ref()?.get(i)?.clone();
+        let name = get_random_ship_name(&state.vehicle_names);
+        let vehicle = match version.vehicle.build(name, &state.part_database) {
+            Ok(v) => v,
+            Err(e) => {
+                state.notice(format!("Failed to recover vehicle: {}", e));
+                return None;
+            }
+        };
+
+        let age = version.age();
+        let ctx = &mut state.editor_context;
+        ctx.vehicle = vehicle;
+        ctx.loadouts = version.vehicle.loadouts;
+        ctx.filepath = version.filepath;
+        ctx.cancel_pipe_route();
+        ctx.update();
+        ctx.action_queue.clear();
+        ctx.show_recovery = false;
+        state.notice(format!("Recovered vehicle from {} ago", duration_str(age)));
+        Some(())
+    }
+
+    pub fn discard_recovery(state: &mut GameState) {
+        let ctx = &mut state.editor_context;
+        ctx.show_recovery = false;
+        if let Some(ring) = ctx.recovery.as_mut() {
+            ring.clear();
+        }
+        if let Err(e) = std::fs::remove_file(state.args.editor_recovery_path()) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                error!("Failed to remove editor recovery file: {e}");
+            }
+        }
+    }
+
     pub fn load_vehicle(path: &Path, state: &mut GameState) -> Option<()> {
         let name = get_random_ship_name(&state.vehicle_names);
         let vehicle = match load_vehicle(path, name, &state.part_database) {
@@ -227,14 +506,38 @@ impl EditorContext {
             }
         };
 
+        EditorContext::capture_recovery(state);
         state.editor_context.vehicle = vehicle;
+        state.editor_context.loadouts = load_loadouts(path);
         state.editor_context.filepath = Some(path.to_path_buf());
+        state.editor_context.cancel_pipe_route();
         state.editor_context.update();
-        state.editor_context.vehicles_menu_collapsed = true;
+        state
+            .editor_context
+            .vehicles_menu
+            .set_collapsed(true, state.wall_time);
         state.editor_context.action_queue.clear();
         Some(())
     }
 
+    pub fn load_comparison(path: &Path, state: &mut GameState) -> Option<()> {
+        let name = get_random_ship_name(&state.vehicle_names);
+        let vehicle = match load_vehicle(path, name, &state.part_database) {
+            Ok(v) => v,
+            Err(e) => {
+                state.notice(format!("Failed to load vehicle: {}", e));
+                return None;
+            }
+        };
+
+        state.editor_context.comparison = Some(Comparison::new(path.to_path_buf(), vehicle));
+        state
+            .editor_context
+            .vehicles_menu
+            .set_collapsed(true, state.wall_time);
+        Some(())
+    }
+
     fn get_part_at(&self, p: Vec2) -> Option<(PartId, &InstantiatedPart)> {
         let pixel_p = vround(p * PIXELS_PER_METER);
 
@@ -280,10 +583,14 @@ impl EditorContext {
     }
 
     fn add_part(&mut self, p: IVec2, rot: Rotation, proto: PartPrototype) {
-        self.vehicle.add_part(proto, p, rot);
+        let id = self.vehicle.add_part(proto, p, rot);
+        self.vehicle.set_part_stage(id, self.stage);
         self.update();
     }
 
+    /// Places the part along with its mirrored copies, if symmetry is on.
+    /// Copies which would overlap something are skipped, but the part
+    /// itself must fit.
     fn try_place_part(&mut self, p: IVec2, new_part: PartPrototype) -> Option<()> {
         let layer = new_part.layer();
 
@@ -291,25 +598,85 @@ impl EditorContext {
             return None;
         }
 
-        let new_pixels = occupied_pixels(p, self.rotation, &new_part);
-
-        if let Some(occ) = self.occupied.get(&layer) {
-            for p in &new_pixels {
-                if occ.contains_key(p) {
+        let dims = pixel_dims_with_rotation(self.rotation, &new_part).as_ivec2();
+        let copies = self.symmetry.counterparts(p, self.rotation, dims);
+        let candidates = std::iter::once((p, self.rotation)).chain(copies);
+
+        let mut taken: HashSet<IVec2> = self
+            .occupied
+            .get(&layer)
+            .map(|occ| occ.keys().copied().collect())
+            .unwrap_or_default();
+        let mut placed = Vec::new();
+        for (pos, rot) in candidates {
+            let pixels = occupied_pixels(pos, rot, &new_part);
+            if pixels.iter().any(|q| taken.contains(q)) {
+                if placed.is_empty() {
                     return None;
                 }
+                continue;
             }
+            taken.extend(pixels);
+            let id = self.vehicle.add_part(new_part.clone(), pos, rot);
+            self.vehicle.set_part_stage(id, self.stage);
+            placed.push((pos, rot, new_part.clone()));
+        }
+
+        // mirrored copies are undone together with the part
+        if placed.len() == 1 {
+            self.action_queue
+                .push(Action::Add(p, self.rotation, new_part));
+        } else {
+            self.action_queue
+                .push(Action::AddAssembly(placed, Vec::new()));
         }
 
-        self.vehicle.add_part(new_part.clone(), p, self.rotation);
+        self.update();
+        Some(())
+    }
 
-        self.action_queue
-            .push(Action::Add(p, self.rotation, new_part));
+    pub fn cycle_symmetry(&mut self) {
+        self.symmetry = enum_iterator::next_cycle(&self.symmetry);
+    }
+
+    /// Places every part and pipe of the assembly with its lower left
+    /// corner at `origin`, unless any part would overlap one already there.
+    fn try_place_assembly(
+        &mut self,
+        origin: IVec2,
+        assembly: &Assembly,
+        prototypes: &HashMap<String, PartPrototype>,
+    ) -> Option<()> {
+        let parts = assembly.placed(origin, prototypes).ok()?;
+        for (pos, rot, proto) in &parts {
+            let occ = self.occupied.get(&proto.layer());
+            let pixels = occupied_pixels(*pos, *rot, proto);
+            if occ.is_some_and(|occ| pixels.iter().any(|p| occ.contains_key(p))) {
+                return None;
+            }
+        }
+
+        for (pos, rot, proto) in &parts {
+            self.vehicle.add_part(proto.clone(), *pos, *rot);
+        }
+        let pipes: Vec<_> = assembly.pipes_at(origin).collect();
+        for p in &pipes {
+            self.vehicle.add_pipe(*p);
+        }
 
+        self.action_queue.push(Action::AddAssembly(parts, pipes));
         self.update();
         Some(())
     }
 
+    fn assembly_cursor_position(state: &GameState) -> Option<(IVec2, &Assembly)> {
+        let ctx = &state.editor_context;
+        let assembly = ctx.cursor_state.current_assembly()?;
+        let pos = state.input.position(MouseButt::Hover, FrameId::Current)?;
+        let pos = vround_f64(ctx.c2w(pos) * PIXELS_PER_METER as f64);
+        Some((pos - assembly.dims.as_ivec2() / 2, assembly))
+    }
+
     fn remove_part_at(&mut self, p: Vec2) {
         let pixel_p = vround(p * PIXELS_PER_METER);
         if let Ok(part) = self.vehicle.remove_part_at(pixel_p, self.focus_layer) {
@@ -399,6 +766,52 @@ fn highlight_part(
     }
 }
 
+/// Highlights which engines are fed and which starve, and marches arrows
+/// along the pipes from the tanks towards the engines.
+fn draw_flow_preview(canvas: &mut Canvas, ctx: &EditorContext, wall_time: Nanotime) {
+    let preview = fuel_flow_preview(&ctx.vehicle);
+
+    for (id, part) in ctx.vehicle.parts() {
+        let color = if let Some(feed) = preview.feed(*id) {
+            if feed.is_starved() {
+                RED
+            } else {
+                GREEN
+            }
+        } else if preview.is_feeding(*id) {
+            TEAL
+        } else {
+            continue;
+        };
+        highlight_part(
+            canvas,
+            part,
+            ctx,
+            color.with_alpha(0.4),
+            ZOrdering::EditorConnGroupHighlight,
+        );
+    }
+
+    const SPACING: u32 = 4;
+    let phase = SPACING - (wall_time.to_secs() * 8.0) as u32 % SPACING;
+    let size = 0.35 / PIXELS_PER_METER;
+    for cell in &preview.cells {
+        if cell.dir == IVec2::ZERO || !(cell.distance + phase).is_multiple_of(SPACING) {
+            continue;
+        }
+        let center = (cell.pos.as_vec2() + Vec2::splat(0.5)) / PIXELS_PER_METER;
+        let u = cell.dir.as_vec2();
+        let tip = center + u * size;
+        let back = center - u * size;
+        let side = u.perp() * size;
+        for wing in [back + side, back - side] {
+            canvas
+                .gizmos
+                .line_2d(ctx.w2c(tip.as_dvec2()), ctx.w2c(wing.as_dvec2()), YELLOW);
+        }
+    }
+}
+
 impl Render for EditorContext {
     fn background_color(_state: &GameState) -> bevy::color::Srgba {
         GRAY.with_luminance(0.12)
@@ -416,17 +829,29 @@ impl Render for EditorContext {
         let parts = part_selection(state);
         let layers = layer_selection(state);
         let vehicles = vehicle_selection(state);
+        let assemblies = assembly_selection(state);
+        let stages = stage_selection(state);
+        let selection = selection_buttons(state);
+        let routing = pipe_route_buttons(state);
+        let flow = flow_preview_panel(state);
 
-        let other_buttons = other_buttons(state.settings.ui_button_height, &state.universe);
+        let recoverable = state
+            .editor_context
+            .recovery
+            .as_ref()
+            .map_or(0, |r| r.len());
+        let other_buttons = other_buttons(
+            state.button_height(),
+            &state.universe,
+            recoverable,
+            state.editor_context.symmetry,
+        );
+        let recovery = recovery_panel(state);
         // let actions = action_queue(&state.editor_context.action_queue);
 
         let part_buttons = if let Some(id) = state.editor_context.selected_part {
             if let Some(instance) = state.editor_context.vehicle.get_part(id) {
-                Some(part_ui_layout(
-                    state.settings.ui_button_height,
-                    id,
-                    instance,
-                ))
+                Some(part_ui_layout(state.button_height(), id, instance))
             } else {
                 None
             }
@@ -434,9 +859,22 @@ impl Render for EditorContext {
             None
         };
 
-        let right_column = Node::column(400)
+        let comparison = state
+            .editor_context
+            .comparison
+            .as_ref()
+            .map(|c| compare_table(state.button_height(), &state.editor_context.vehicle, c));
+
+        let right_width = if state.is_compact_layout() { 300 } else { 400 };
+
+        let right_column = Node::column(right_width)
             .invisible()
             .with_child(other_buttons)
+            .with_child(recovery)
+            .with_child(selection)
+            .with_child(routing)
+            .with_child(flow)
+            .with_child(comparison)
             // .with_child(actions)
             .with_child(part_buttons);
 
@@ -448,7 +886,9 @@ impl Render for EditorContext {
                     .down()
                     .with_padding(0.0)
                     .invisible()
-                    .with_child(layers),
+                    .with_child(layers)
+                    .with_child(assemblies)
+                    .with_child(stages),
             )
             .with_child(vehicles)
             .with_child(Node::grow().invisible())
@@ -477,8 +917,18 @@ impl Render for EditorContext {
 
         draw_thrust_particles(canvas, ctx, &ctx.particles, &Universe::empty());
 
+        if let Some(comparison) = &ctx.comparison {
+            draw_comparison(
+                canvas,
+                &ctx.vehicle,
+                comparison,
+                ctx.w2c(DVec2::ZERO),
+                gcast(ctx.scale()),
+            );
+        }
+
         match &ctx.cursor_state {
-            CursorState::None | CursorState::Part(_) => {
+            CursorState::None | CursorState::Part(_) | CursorState::Assembly(_) => {
                 if let Some(p) = state.input.current() {
                     canvas.circle(p, 4.0, WHITE);
                 }
@@ -536,6 +986,14 @@ impl Render for EditorContext {
             canvas.gizmos.line_2d(o, q, GREEN.with_alpha(0.3));
             canvas.gizmos.line_2d(o, np, RED.with_alpha(0.1));
             canvas.gizmos.line_2d(o, nq, GREEN.with_alpha(0.1));
+
+            // axes parts are mirrored across
+            if ctx.symmetry != Symmetry::Off {
+                canvas.gizmos.line_2d(q, nq, ORANGE.with_alpha(0.5));
+            }
+            if ctx.symmetry == Symmetry::Quad {
+                canvas.gizmos.line_2d(p, np, ORANGE.with_alpha(0.5));
+            }
         }
 
         if let Some((p, current_part)) = Self::current_part_and_cursor_position(state) {
@@ -564,6 +1022,10 @@ impl Render for EditorContext {
             }
         }
 
+        if ctx.show_flow_preview {
+            draw_flow_preview(canvas, ctx, state.wall_time);
+        }
+
         if ctx.show_vehicle_info {
             draw_aabb(canvas, ctx.w2c_aabb(bounds), TEAL.with_alpha(0.1));
 
@@ -621,7 +1083,7 @@ impl Render for EditorContext {
                 // highlight parts in this connectivity group
                 if is_focus {
                     for (group_id, group) in ctx.vehicle.conn_groups().enumerate() {
-                        let color = crate::sprites::hashable_to_color(&group_id);
+                        let color = state.settings.color_palette.hashable_to_color(&group_id);
                         let color: Srgba = color.into();
                         for id in group.ids() {
                             if let Some(part) = ctx.vehicle.get_part(id) {
@@ -663,14 +1125,8 @@ impl Render for EditorContext {
                 let dims = instance.dims_meters().as_dvec2();
                 let sprite_dims = instance.prototype().dims_meters();
                 let center = instance.center_meters().as_dvec2();
-                let p = instance.percent_built();
-                let sprite_name = instance.prototype().sprite_path().to_string();
-                let sprite_name = if p == 1.0 {
-                    sprite_name.to_string()
-                } else {
-                    let idx = (p * 10.0).floor() as i32;
-                    format!("{}-building-{}", sprite_name, idx)
-                };
+                let (sprite_name, tint) =
+                    state.part_sprite(instance.prototype().sprite_path(), instance.percent_built());
 
                 let z_index = match layer {
                     PartLayer::Exterior => ZOrdering::EditorExteriorPart,
@@ -687,7 +1143,7 @@ impl Render for EditorContext {
                         z_index,
                         graphics_cast(sprite_dims.as_dvec2() * ctx.scale()),
                     )
-                    .set_color(WHITE.with_alpha(alpha));
+                    .set_color(tint.with_alpha(tint.alpha * alpha));
 
                 if detailed_part_info {
                     if let Some((t, d)) = instance.as_tank() {
@@ -740,6 +1196,21 @@ impl Render for EditorContext {
                     }
 
                     if let Some((c, d)) = instance.as_cargo() {
+                        let pct = c.percent_filled(d);
+                        let upper = center + dims / 2.0;
+                        let gauge_width = dims.x.min(dims.y) * 0.08;
+                        let lower = DVec2::new(upper.x - gauge_width, upper.y - dims.y * pct);
+                        let aabb = AABB::from_arbitrary(
+                            aabb_stopgap_cast(lower),
+                            aabb_stopgap_cast(upper),
+                        );
+                        let color = Srgba::from_f32_array(diagram_color(&instance.prototype()));
+                        canvas.rect(
+                            ctx.w2c_aabb(aabb),
+                            ZOrdering::EditorTankFill,
+                            color.with_alpha(0.9),
+                        );
+
                         let mut lower = center - dims / 2.0;
 
                         for (item, mass) in d.contents() {
@@ -842,15 +1313,84 @@ impl Render for EditorContext {
         if let Some((p, current_part)) = Self::current_part_and_cursor_position(state) {
             let dims = pixel_dims_with_rotation(ctx.rotation, &current_part);
             let sprite_dims = current_part.dims();
-            canvas.sprite(
-                ctx.w2c((p.as_dvec2() + dims.as_dvec2() / 2.0) / PIXELS_PER_METER as f64),
-                gcast(ctx.rotation.to_angle()),
-                current_part.sprite_path().to_string(),
-                ZOrdering::EditorCursor,
-                sprite_dims.as_vec2() / PIXELS_PER_METER * gcast(ctx.scale()),
+            let copies = ctx.symmetry.counterparts(p, ctx.rotation, dims.as_ivec2());
+            for (p, rot) in std::iter::once((p, ctx.rotation)).chain(copies) {
+                canvas.sprite(
+                    ctx.w2c((p.as_dvec2() + dims.as_dvec2() / 2.0) / PIXELS_PER_METER as f64),
+                    gcast(rot.to_angle()),
+                    current_part.sprite_path().to_string(),
+                    ZOrdering::EditorCursor,
+                    sprite_dims.as_vec2() / PIXELS_PER_METER * gcast(ctx.scale()),
+                );
+            }
+        }
+
+        if let Some(from) = ctx.route_from.and_then(|id| ctx.vehicle.get_part(id)) {
+            highlight_part(
+                canvas,
+                from,
+                ctx,
+                PURPLE.with_alpha(0.4),
+                ZOrdering::EditorMouseoverPartHighlight,
             );
         }
 
+        if let Some((_, cells)) = &ctx.route_preview {
+            for cell in cells {
+                let p = cell.as_vec2() / PIXELS_PER_METER;
+                let q = (cell + IVec2::ONE).as_vec2() / PIXELS_PER_METER;
+                let aabb = AABB::from_arbitrary(p, q).scale_about_center(1.2);
+                canvas.rect(
+                    ctx.w2c_aabb(aabb),
+                    ZOrdering::EditorPipe,
+                    PURPLE.with_alpha(0.5),
+                );
+            }
+        }
+
+        if let Some((lower, upper)) = ctx.selection {
+            let aabb = AABB::from_arbitrary(
+                lower.as_vec2() / PIXELS_PER_METER,
+                upper.as_vec2() / PIXELS_PER_METER,
+            );
+            draw_aabb(canvas, ctx.w2c_aabb(aabb), TEAL.with_alpha(0.6));
+            for instance in ctx.selected_parts() {
+                highlight_part(
+                    canvas,
+                    instance,
+                    ctx,
+                    TEAL.with_alpha(0.4),
+                    ZOrdering::EditorMouseoverPartHighlight,
+                );
+            }
+        }
+
+        if matches!(ctx.cursor_state, CursorState::None) && !ctx.is_plumbing() {
+            if let Some(aabb) = ctx.cursor_box(&state.input) {
+                let aabb = AABB::from_arbitrary(aabb.lower(), aabb.upper());
+                draw_aabb(canvas, ctx.w2c_aabb(aabb), TEAL.with_alpha(0.3));
+            }
+        }
+
+        if let Some((origin, assembly)) = Self::assembly_cursor_position(state) {
+            if let Ok(parts) = assembly.placed(origin, &state.part_database) {
+                for (pos, rot, proto) in parts {
+                    let dims = pixel_dims_with_rotation(rot, &proto);
+                    canvas.sprite(
+                        ctx.w2c((pos.as_dvec2() + dims.as_dvec2() / 2.0) / PIXELS_PER_METER as f64),
+                        gcast(rot.to_angle()),
+                        proto.sprite_path().to_string(),
+                        ZOrdering::EditorCursor,
+                        proto.dims().as_vec2() / PIXELS_PER_METER * gcast(ctx.scale()),
+                    );
+                }
+            }
+            let lower = origin.as_vec2() / PIXELS_PER_METER;
+            let upper = lower + assembly.dims.as_vec2() / PIXELS_PER_METER;
+            let aabb = AABB::from_arbitrary(lower, upper);
+            draw_aabb(canvas, ctx.w2c_aabb(aabb), WHITE.with_alpha(0.3));
+        }
+
         for particle in &ctx.build_particles {
             let p = ctx.w2c(particle.pos());
             canvas
@@ -889,54 +1429,343 @@ fn expandable_menu(button_height: f32, text: &str, onclick: OnClick) -> Node<OnC
 }
 
 fn part_selection(state: &GameState) -> Node<OnClick> {
-    let mut part_names: Vec<_> = state.part_database.keys().collect();
-    part_names.sort();
+    let h = state.button_height();
+    let search = &state.editor_context.part_search;
+    let part_names = search.filter(&state.part_database);
+
+    let mut n = expandable_menu(h, "Parts", OnClick::TogglePartsMenuCollapsed);
+
+    if state.editor_context.parts_menu.openness(state.wall_time) == 0.0 {
+        return n;
+    }
+
+    n.add_child(Node::hline());
+
+    let layers = std::iter::once(None).chain(PartLayer::all().map(Some));
+    n.add_child(
+        Node::new(Size::Grow, h)
+            .tight()
+            .invisible()
+            .with_children(layers.map(|layer| {
+                let s = match layer {
+                    Some(l) => format!("{:?}", l),
+                    None => "All".to_string(),
+                };
+                Node::button(s, OnClick::PartCategory(layer), Size::Grow, h)
+                    .enabled(search.layer() != layer)
+            })),
+    );
+
+    let s = if search.is_focused() {
+        format!("Search: {}_", search.query())
+    } else if search.query().is_empty() {
+        "Search parts...".to_string()
+    } else {
+        format!("Search: {}", search.query())
+    };
+    n.add_child(Node::button(s, OnClick::FocusPartSearch, Size::Grow, h));
+
+    if part_names.is_empty() {
+        n.add_child(Node::row(h).with_text("No parts match").enabled(false));
+    }
+
+    let rows = state
+        .editor_context
+        .parts_menu
+        .visible_rows(part_names.len(), state.wall_time);
+    n.add_children(part_names.into_iter().take(rows).map(|s| {
+        let onclick = OnClick::SelectPart(s.clone());
+        Node::button(s, onclick, Size::Grow, h)
+    }));
 
+    n
+}
+
+fn assembly_selection(state: &GameState) -> Node<OnClick> {
     let mut n = expandable_menu(
-        state.settings.ui_button_height,
-        "Parts",
-        OnClick::TogglePartsMenuCollapsed,
+        state.button_height(),
+        "Assemblies",
+        OnClick::ToggleAssembliesMenuCollapsed,
     );
 
-    if !state.editor_context.parts_menu_collapsed {
+    let assemblies = state.editor_context.assemblies.as_deref().unwrap_or(&[]);
+    let rows = state
+        .editor_context
+        .assemblies_menu
+        .visible_rows(assemblies.len(), state.wall_time);
+    if rows > 0 {
         n.add_child(Node::hline());
-        n.add_children(part_names.into_iter().map(|s| {
-            let onclick = OnClick::SelectPart(s.clone());
-            Node::button(s, onclick, Size::Grow, state.settings.ui_button_height)
+        n.add_children(assemblies.iter().take(rows).map(|a| {
+            let s = format!("{} ({})", a.name, a.parts.len());
+            let onclick = OnClick::SelectAssembly(a.name.clone());
+            Node::button(s, onclick, Size::Grow, state.button_height())
         }));
     }
 
     n
 }
 
-pub fn get_list_of_vehicles(state: &GameState) -> Option<Vec<(String, PathBuf)>> {
-    let mut ret = vec![];
-    if let Ok(paths) = std::fs::read_dir(&state.args.vehicle_dir()) {
-        for path in paths {
-            if let Ok(path) = path {
-                let s = path.path().file_stem()?.to_string_lossy().to_string();
-                ret.push((s, path.path()));
+/// Stages from the last to fire down to the first, each with buttons to
+/// move it earlier or later in the firing order.
+fn stage_selection(state: &GameState) -> Node<OnClick> {
+    let h = state.button_height();
+    let ctx = &state.editor_context;
+    let mut n = expandable_menu(h, "Stages", OnClick::ToggleStagesMenuCollapsed);
+
+    let mut info = ctx.vehicle.stage_info();
+    if info.iter().all(|s| s.stage != ctx.stage) {
+        info.push(StageInfo {
+            stage: ctx.stage,
+            parts: 0,
+            start_mass: Mass::ZERO,
+            end_mass: Mass::ZERO,
+            thrust: 0.0,
+            dv: 0.0,
+        });
+        info.sort_by_key(|s| s.stage);
+    }
+    let next = info.last().map_or(0, |s| s.stage + 1);
+
+    let rows = ctx
+        .stages_menu
+        .visible_rows(info.len() + 1, state.wall_time);
+    if rows == 0 {
+        return n;
+    }
+
+    n.add_child(Node::hline());
+    n.add_children(info.iter().rev().take(rows).map(|s| {
+        let text = format!("Stage {}: {} parts, {:0.0} m/s", s.stage, s.parts, s.dv);
+        let mut button = Node::button(text, OnClick::SelectEditorStage(s.stage), Size::Grow, h);
+        if s.stage == ctx.stage {
+            button = button.with_color(state.settings.color_palette.tracked().to_f32_array());
+        }
+        Node::new(Size::Grow, h)
+            .tight()
+            .invisible()
+            .with_child(button)
+            .with_child(Node::button(
+                "+",
+                OnClick::MoveEditorStage(s.stage, true),
+                h,
+                h,
+            ))
+            .with_child(
+                Node::button("-", OnClick::MoveEditorStage(s.stage, false), h, h)
+                    .enabled(s.stage > 0),
+            )
+    }));
+    if rows > info.len() {
+        n.add_child(Node::button(
+            "New Stage",
+            OnClick::SelectEditorStage(next),
+            Size::Grow,
+            h,
+        ));
+    }
+
+    n
+}
+
+fn selection_buttons(state: &GameState) -> Option<Node<OnClick>> {
+    let h = state.button_height();
+    let count = state.editor_context.selected_parts().count();
+    if count == 0 {
+        return None;
+    }
+
+    Some(
+        Node::structural(Size::Grow, Size::Fit)
+            .with_color(UI_BACKGROUND_COLOR)
+            .down()
+            .with_child(
+                Node::row(h)
+                    .with_text(format!("{} parts selected", count))
+                    .enabled(false),
+            )
+            .with_child(Node::button(
+                "Save Assembly",
+                OnClick::SaveAssembly,
+                Size::Grow,
+                h,
+            ))
+            .with_child(Node::button(
+                "Clear Selection",
+                OnClick::ClearEditorSelection,
+                Size::Grow,
+                h,
+            )),
+    )
+}
+
+fn pipe_route_buttons(state: &GameState) -> Option<Node<OnClick>> {
+    let h = state.button_height();
+    let ctx = &state.editor_context;
+
+    let mut n = Node::structural(Size::Grow, Size::Fit)
+        .with_color(UI_BACKGROUND_COLOR)
+        .down();
+    let status = match (ctx.route_from, &ctx.route_preview) {
+        (None, _) => {
+            let (id, part) = ctx.selected_part.zip(ctx.selected_part())?;
+            if part.prototype().layer() != PartLayer::Internal {
+                return None;
             }
+            return Some(n.with_child(Node::button(
+                "Route Pipe From Here",
+                OnClick::RoutePipeFrom(id),
+                Size::Grow,
+                h,
+            )));
         }
+        (Some(_), None) => Node::row(h)
+            .with_text("Click a part to route a pipe to")
+            .enabled(false),
+        (Some(_), Some((_, cells))) => Node::button(
+            format!("Accept Route ({} cells)", cells.len()),
+            OnClick::AcceptPipeRoute,
+            Size::Grow,
+            h,
+        ),
+    };
+
+    n.add_child(status);
+    n.add_child(Node::button(
+        "Cancel Route",
+        OnClick::CancelPipeRoute,
+        Size::Grow,
+        h,
+    ));
+    Some(n)
+}
+
+fn flow_preview_panel(state: &GameState) -> Option<Node<OnClick>> {
+    let ctx = &state.editor_context;
+    if !ctx.show_flow_preview {
+        return None;
     }
-    Some(ret)
+
+    let h = state.button_height();
+    let preview = fuel_flow_preview(&ctx.vehicle);
+    let mut n = Node::structural(Size::Grow, Size::Fit)
+        .with_color(UI_BACKGROUND_COLOR)
+        .down()
+        .with_child(Node::row(h).with_text("Fuel Flow").enabled(false));
+
+    if preview.feeds.is_empty() {
+        n.add_child(Node::row(h).with_text("No engines").enabled(false));
+    }
+
+    for feed in &preview.feeds {
+        let name = ctx
+            .vehicle
+            .get_part(feed.engine)
+            .map_or("?".to_string(), |p| p.prototype().part_name().to_string());
+        let text = if feed.tanks.is_empty() {
+            format!("{}: no tanks", name)
+        } else {
+            format!(
+                "{}: {:0.0}/{:0.0} kg/s, {} tanks",
+                name,
+                feed.available,
+                feed.demand,
+                feed.tanks.len()
+            )
+        };
+        let mut row = Node::row(h).with_text(text);
+        if feed.is_starved() {
+            row = row.with_color([0.5, 0.1, 0.1, 0.9]);
+        }
+        n.add_child(row);
+    }
+
+    let starved = preview.starved().count();
+    if starved > 0 {
+        n.add_child(
+            Node::row(h)
+                .with_text(format!("{} engines starved at full throttle", starved))
+                .enabled(false),
+        );
+    }
+
+    Some(n)
 }
 
 fn vehicle_selection(state: &GameState) -> Node<OnClick> {
-    let vehicles = get_list_of_vehicles(state).unwrap_or(vec![]);
-
     let mut n = expandable_menu(
-        state.settings.ui_button_height,
+        state.button_height(),
         "Vehicles",
         OnClick::ToggleVehiclesMenuCollapsed,
     );
 
-    if !state.editor_context.vehicles_menu_collapsed {
-        n.add_child(Node::hline());
-        n.add_children(vehicles.into_iter().map(|(name, path)| {
-            let onclick = OnClick::LoadVehicle(path);
-            Node::button(name, onclick, Size::Grow, state.settings.ui_button_height)
-        }));
+    if state.editor_context.vehicles_menu.openness(state.wall_time) == 0.0 {
+        return n;
+    }
+
+    n.add_child(Node::hline());
+
+    n.add_child(Node::button(
+        format!("Refresh ({} vehicles)", state.vehicles.len()),
+        OnClick::RefreshVehicles,
+        Size::Grow,
+        state.button_height(),
+    ));
+
+    if let Some(import) = &state.vehicle_import {
+        let (checked, total) = import.progress();
+        n.add_child(
+            Node::row(state.button_height())
+                .with_text(format!("Importing {}/{}", checked, total))
+                .enabled(false),
+        );
+    }
+
+    let Some(gallery) = &state.editor_context.gallery else {
+        n.add_child(
+            Node::row(state.button_height())
+                .with_text("Loading...")
+                .enabled(false),
+        );
+        return n;
+    };
+
+    let thumbnail_size = 120.0;
+    let text_height = state.button_height() * 0.6;
+    let text = |s: String| Node::text(Size::Grow, text_height, s).enabled(false);
+
+    let rows = state
+        .editor_context
+        .vehicles_menu
+        .visible_rows(gallery.len().div_ceil(2), state.wall_time);
+    for row in gallery.chunks(2).take(rows) {
+        let mut r = Node::new(Size::Grow, Size::Fit)
+            .invisible()
+            .with_padding(0.0);
+        for v in row {
+            let thumbnail = Node::button(
+                "",
+                OnClick::LoadVehicle(v.path.clone()),
+                Size::Grow,
+                thumbnail_size,
+            )
+            .with_sprite(v.thumbnail_sprite());
+            r.add_child(
+                Node::new(Size::Grow, Size::Fit)
+                    .down()
+                    .with_padding(2.0)
+                    .with_child(thumbnail)
+                    .with_child(Node::button(
+                        "Compare",
+                        OnClick::CompareVehicle(v.path.clone()),
+                        Size::Grow,
+                        text_height,
+                    ))
+                    .with_child(text(v.name.clone()))
+                    .with_child(text(format!("{} parts, {}", v.parts, v.mass)))
+                    .with_child(text(format!("{:0.0} m/s", v.dv))),
+            );
+        }
+        n.add_child(r);
     }
 
     n
@@ -954,9 +1783,62 @@ fn action_queue(button_height: f32, queue: &Vec<Action>) -> Node<OnClick> {
         )
 }
 
-fn other_buttons(button_height: f32, universe: &Universe) -> Node<OnClick> {
+/// Versions of the vehicle which can be recovered, newest first.
+fn recovery_panel(state: &GameState) -> Option<Node<OnClick>> {
+    let ctx = &state.editor_context;
+    let ring = ctx.recovery.as_ref().filter(|_| ctx.show_recovery)?;
+    let h = state.button_height();
+    let mut n = Node::structural(Size::Grow, Size::Fit)
+        .with_color(UI_BACKGROUND_COLOR)
+        .down()
+        .with_child(
+            Node::row(h)
+                .with_text("Recover unsaved work")
+                .enabled(false),
+        );
+    for (i, version) in ring.newest_first() {
+        let file = version
+            .filepath
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .map(|s| format!(" ({})", s.to_string_lossy()))
+            .unwrap_or_default();
+        let s = format!(
+            "{} parts{}, {} ago",
+            version.vehicle.parts.len(),
+            file,
+            duration_str(version.age())
+        );
+        n.add_child(Node::button(
+            s,
+            OnClick::RestoreEditorVersion(i),
+            Size::Grow,
+            h,
+        ));
+    }
+    n.add_child(Node::hline());
+    n.add_child(
+        Node::button("Discard", OnClick::DiscardEditorRecovery, Size::Grow, h)
+            .with_color(DELETE_SOMETHING_COLOR),
+    );
+    Some(n)
+}
+
+fn other_buttons(
+    button_height: f32,
+    universe: &Universe,
+    recoverable: usize,
+    symmetry: Symmetry,
+) -> Node<OnClick> {
     let rotate = Node::button("Rotate", OnClick::RotateCraft, Size::Grow, button_height);
 
+    let symmetry = Node::button(
+        format!("Symmetry: {}", symmetry.label()),
+        OnClick::CycleSymmetry,
+        Size::Grow,
+        button_height,
+    );
+
     let normalize = Node::button(
         "Normalize",
         OnClick::NormalizeCraft,
@@ -966,6 +1848,34 @@ fn other_buttons(button_height: f32, universe: &Universe) -> Node<OnClick> {
 
     let new_button = Node::button("New", OnClick::OpenNewCraft, Size::Grow, button_height);
 
+    let to_image = Node::button(
+        "To Image",
+        OnClick::WriteVehicleToImage,
+        Size::Grow,
+        button_height,
+    );
+
+    let to_svg = Node::button(
+        "To SVG",
+        OnClick::WriteVehicleToSvg,
+        Size::Grow,
+        button_height,
+    );
+
+    let save_to_library = Node::button(
+        "Save to Library",
+        OnClick::SaveToLibrary,
+        Size::Grow,
+        button_height,
+    );
+
+    let import = Node::button(
+        "Import Vehicles",
+        OnClick::ImportVehicles,
+        Size::Grow,
+        button_height,
+    );
+
     let toggle_info = Node::button(
         "Info",
         OnClick::ToggleVehicleInfo,
@@ -973,6 +1883,21 @@ fn other_buttons(button_height: f32, universe: &Universe) -> Node<OnClick> {
         button_height,
     );
 
+    let toggle_flow = Node::button(
+        "Flow Preview",
+        OnClick::ToggleFlowPreview,
+        Size::Grow,
+        button_height,
+    );
+
+    let recover = Node::button(
+        format!("Recover ({})", recoverable),
+        OnClick::ToggleEditorRecovery,
+        Size::Grow,
+        button_height,
+    )
+    .enabled(recoverable > 0);
+
     let surface_buttons = universe.planets.planet_ids().into_iter().map(|id| {
         Node::button(
             "Send to Surface",
@@ -986,27 +1911,40 @@ fn other_buttons(button_height: f32, universe: &Universe) -> Node<OnClick> {
         .with_color(UI_BACKGROUND_COLOR)
         .down()
         .with_child(new_button)
+        .with_child(save_to_library)
+        .with_child(recover)
+        .with_child(import)
         .with_child(Node::hline())
         .with_child(rotate)
         .with_child(normalize)
+        .with_child(symmetry)
+        .with_child(Node::hline())
+        .with_child(to_image)
+        .with_child(to_svg)
         .with_child(Node::hline())
         .with_child(toggle_info)
+        .with_child(toggle_flow)
         .with_children(surface_buttons)
 }
 
 fn layer_selection(state: &GameState) -> Node<OnClick> {
     let mut n = expandable_menu(
-        state.settings.ui_button_height,
+        state.button_height(),
         "Layers",
         OnClick::ToggleLayersMenuCollapsed,
     );
 
-    if !state.editor_context.layers_menu_collapsed {
+    let layers = enum_iterator::all::<PartLayer>().collect::<Vec<_>>();
+    let rows = state
+        .editor_context
+        .layers_menu
+        .visible_rows(layers.len(), state.wall_time);
+    if rows > 0 {
         n.add_child(Node::hline());
-        n.add_children(enum_iterator::all::<PartLayer>().into_iter().map(|p| {
+        n.add_children(layers.into_iter().take(rows).map(|p| {
             let s = format!("{:?}", p);
             let onclick = OnClick::ToggleLayer(p);
-            let mut n = Node::button(s, onclick, Size::Grow, state.settings.ui_button_height);
+            let mut n = Node::button(s, onclick, Size::Grow, state.button_height());
             if !state.editor_context.is_layer_visible(p) {
                 n = n.with_color(GRAY.to_f32_array());
             }
@@ -1037,13 +1975,40 @@ impl CameraProjection for EditorContext {
 
 impl EditorContext {
     pub fn on_render_tick(state: &mut GameState) {
-        state.editor_context.camera.handle_input(&state.input);
+        // typing goes into the part search rather than moving the camera
+        // or working the editor
+        if state.editor_context.part_search.is_focused() {
+            if state
+                .editor_context
+                .part_search
+                .process_input(&mut state.input)
+            {
+                let first = state
+                    .editor_context
+                    .part_search
+                    .filter(&state.part_database)
+                    .first()
+                    .map(|s| s.to_string());
+                if let Some(name) = first {
+                    EditorContext::set_current_part(state, &name);
+                }
+            }
+            return;
+        }
+
+        state
+            .editor_context
+            .camera
+            .handle_input(&state.input, &state.keybindings);
 
         if state.is_hovering_over_ui() {
             return;
         }
 
-        if state.input.is_pressed(KeyCode::KeyB) {
+        if state
+            .keybindings
+            .is_pressed(KeyAction::BuildAll, &state.input)
+        {
             for _ in 0..100 {
                 state.editor_context.vehicle.build_once();
             }
@@ -1052,13 +2017,23 @@ impl EditorContext {
         if let Some(p) = state.input.on_frame(MouseButt::Left, FrameId::Down) {
             let p = state.editor_context.c2w(p);
             if let Some((id, _)) = state.editor_context.get_part_at(graphics_cast(p)) {
-                state.editor_context.selected_part = Some(id)
+                state.editor_context.selected_part = Some(id);
+                if state
+                    .editor_context
+                    .route_from
+                    .is_some_and(|from| from != id)
+                {
+                    state.editor_context.route_pipe_to(id);
+                }
             } else {
                 state.editor_context.selected_part = None;
             }
         }
 
-        if state.input.is_pressed(KeyCode::ShiftLeft) {
+        if state
+            .keybindings
+            .is_pressed(KeyAction::SnapPart, &state.input)
+        {
             if let Some((pos, proto)) = EditorContext::current_part_and_cursor_position(state) {
                 if state.editor_context.snap_info.is_none() {
                     let rot = state.editor_context.rotation;
@@ -1072,16 +2047,53 @@ impl EditorContext {
             state.editor_context.snap_info = None;
         }
 
+        if state
+            .input
+            .on_frame(MouseButt::Left, FrameId::Down)
+            .is_some()
+        {
+            if let Some((p, assembly)) = EditorContext::assembly_cursor_position(state) {
+                let assembly = assembly.clone();
+                let ctx = &mut state.editor_context;
+                if ctx
+                    .try_place_assembly(p, &assembly, &state.part_database)
+                    .is_some()
+                {
+                    state.stats.stats.parts_placed += assembly.parts.len() as u64;
+                }
+            }
+        }
+
+        if matches!(state.editor_context.cursor_state, CursorState::None)
+            && !state.editor_context.is_plumbing()
+        {
+            let down = state.input.position(MouseButt::Left, FrameId::Down);
+            let up = state.input.on_frame(MouseButt::Left, FrameId::Up);
+            if let Some((down, up)) = down.zip(up) {
+                state.editor_context.selection = (down.distance(up) > 8.0).then(|| {
+                    let ctx = &state.editor_context;
+                    let a = vround_f64(ctx.c2w(down) * PIXELS_PER_METER as f64);
+                    let b = vround_f64(ctx.c2w(up) * PIXELS_PER_METER as f64);
+                    (a.min(b), a.max(b))
+                });
+            }
+        }
+
         if let Some(_) = state.input.position(MouseButt::Left, FrameId::Current) {
             if let Some((p, part)) = EditorContext::current_part_and_cursor_position(state) {
-                state.editor_context.try_place_part(p, part);
+                if state.editor_context.try_place_part(p, part).is_some() {
+                    state.stats.stats.parts_placed += 1;
+                }
             }
         } else if let Some(p) = state.input.on_frame(MouseButt::Right, FrameId::Down) {
             state
                 .editor_context
                 .remove_part_at(graphics_cast(state.editor_context.c2w(p)));
-        } else if state.input.just_pressed(KeyCode::KeyQ) {
-            if state.editor_context.cursor_state.current_part().is_some() {
+        } else if state
+            .keybindings
+            .just_pressed(KeyAction::PickPart, &state.input)
+        {
+            if !matches!(state.editor_context.cursor_state, CursorState::None) {
                 state.editor_context.cursor_state = CursorState::None;
             } else if let Some(p) = state.input.position(MouseButt::Hover, FrameId::Current) {
                 if let Some((_, instance)) = state
@@ -1098,11 +2110,21 @@ impl EditorContext {
             }
         }
 
-        if state.input.just_pressed(KeyCode::KeyR) {
+        if state
+            .keybindings
+            .just_pressed(KeyAction::RotatePart, &state.input)
+        {
             state.editor_context.rotation =
                 enum_iterator::next_cycle(&state.editor_context.rotation);
         }
 
+        if state
+            .keybindings
+            .just_pressed(KeyAction::CycleSymmetry, &state.input)
+        {
+            state.editor_context.cycle_symmetry();
+        }
+
         if state.editor_context.focus_layer == Some(PartLayer::Plumbing) {
             if let Some(p) = state.input.position(MouseButt::Left, FrameId::Current) {
                 let p = vfloor(graphics_cast(state.editor_context.c2w(p)) * PIXELS_PER_METER);
@@ -1114,15 +2136,24 @@ impl EditorContext {
             }
         }
 
-        if state.input.is_pressed(KeyCode::ControlLeft) && state.input.just_pressed(KeyCode::KeyZ) {
+        if state
+            .keybindings
+            .just_pressed(KeyAction::Undo, &state.input)
+        {
             state.editor_context.undo();
         }
 
-        if state.input.just_pressed(KeyCode::KeyO) {
+        if state
+            .keybindings
+            .just_pressed(KeyAction::MoreAtmosphere, &state.input)
+        {
             state.editor_context.atmo += 1;
         }
 
-        if state.input.just_pressed(KeyCode::KeyL) {
+        if state
+            .keybindings
+            .just_pressed(KeyAction::LessAtmosphere, &state.input)
+        {
             state.editor_context.atmo -= 1;
         }
 
@@ -1132,6 +2163,27 @@ impl EditorContext {
     pub fn on_game_tick(state: &mut GameState) {
         state.editor_context.camera.on_game_tick();
 
+        if state.editor_context.recovery.is_none() {
+            let ring =
+                load_recovery_from_file(&state.args.editor_recovery_path()).unwrap_or_default();
+            if let Some(latest) = ring.latest() {
+                if state.editor_context.vehicle.parts().count() == 0 {
+                    state.editor_context.show_recovery = true;
+                    let age = duration_str(latest.age());
+                    state.notice(format!("Unsaved work from {} ago can be recovered", age));
+                }
+            }
+            state.editor_context.recovery = Some(ring);
+            state.editor_context.last_recovery = state.wall_time;
+        } else if state.wall_time - state.editor_context.last_recovery >= RECOVERY_INTERVAL {
+            EditorContext::capture_recovery(state);
+        }
+
+        if state.editor_context.assemblies.is_none() {
+            let assemblies = load_assemblies_from_dir(&state.args.assemblies_dir());
+            state.editor_context.assemblies = Some(assemblies);
+        }
+
         let ctx = &mut state.editor_context;
 
         let all_parts: HashSet<_> = ctx
@@ -1235,6 +2287,17 @@ pub fn write_image_to_file(vehicle: &Vehicle, ctx: &ProgramContext, name: &str)
     img.save(outpath).ok()
 }
 
+pub fn write_svg_to_file(vehicle: &Vehicle, name: &str) -> Option<()> {
+    let outpath: String = format!("/tmp/{}.svg", name);
+    println!(
+        "Writing vehicle {} diagram to path {}",
+        vehicle.discriminator(),
+        outpath
+    );
+    let svg = generate_svg(vehicle)?;
+    std::fs::write(outpath, svg).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1251,8 +2314,7 @@ mod tests {
 
         let g = GameState::new(args.clone());
 
-        let vehicles = get_list_of_vehicles(&g).expect("Expected list of vehicles");
-        dbg!(vehicles);
+        dbg!(g.vehicles.listings());
 
         for name in ["remora", "lander", "pollux", "manta", "spacestation"] {
             let vehicle = g.get_vehicle_by_model(name).expect("Expected a vehicle");
@@ -0,0 +1,107 @@
+use crate::game::GameState;
+use crate::generate_ship_sprites::read_image;
+use crate::names::get_random_ship_name;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use image::DynamicImage;
+use starling::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Summary of a saved vehicle file, shown in the editor's vehicle gallery.
+#[derive(Debug, Clone)]
+pub struct VehicleSummary {
+    pub name: String,
+    pub path: PathBuf,
+    pub parts: usize,
+    pub mass: Mass,
+    pub dv: f64,
+}
+
+impl VehicleSummary {
+    pub fn thumbnail_sprite(&self) -> String {
+        thumbnail_sprite_name(&self.name)
+    }
+}
+
+pub fn thumbnail_sprite_name(name: &str) -> String {
+    format!("{}-thumbnail", name)
+}
+
+/// Renders a thumbnail for the vehicle and writes it to disk.
+pub fn write_thumbnail(vehicle: &Vehicle, parts_dir: &Path, path: &Path) -> Option<DynamicImage> {
+    let img = generate_image(vehicle, parts_dir, false)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).ok()?;
+    }
+    if let Err(e) = img.save(path) {
+        error!("Failed to write thumbnail {}: {}", path.display(), e);
+    }
+    Some(img)
+}
+
+/// Whether the cached thumbnail is missing or older than the vehicle file.
+fn thumbnail_is_stale(vehicle_path: &Path, thumbnail_path: &Path) -> bool {
+    let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    match (modified(vehicle_path), modified(thumbnail_path)) {
+        (Some(v), Some(t)) => v > t,
+        _ => true,
+    }
+}
+
+/// Loads every vehicle in the vehicle directory, summarizing it and making
+/// sure its thumbnail is cached on disk and loaded as a sprite.
+pub fn build_vehicle_gallery(
+    state: &mut GameState,
+    images: &mut Assets<Image>,
+) -> Vec<VehicleSummary> {
+    let vehicles: Vec<_> = state
+        .vehicles
+        .listings()
+        .iter()
+        .map(|l| (l.model.clone(), l.path.clone()))
+        .collect();
+
+    let mut ret = Vec::new();
+    for (name, path) in vehicles {
+        let ship_name = get_random_ship_name(&state.vehicle_names);
+        let mut vehicle = match load_vehicle(&path, ship_name, &state.part_database) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to load vehicle {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        vehicle.build_all();
+
+        let thumbnail_path = state.args.thumbnail_path(&name);
+        let img = if thumbnail_is_stale(&path, &thumbnail_path) {
+            write_thumbnail(&vehicle, &state.args.parts_dir(), &thumbnail_path)
+        } else {
+            read_image(&thumbnail_path).map(DynamicImage::ImageRgba8)
+        };
+
+        if let Some(img) = img {
+            let mut img = Image::from_dynamic(
+                img,
+                true,
+                RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+            );
+            img.sampler = bevy::image::ImageSampler::nearest();
+            let dims = img.size();
+            let handle = images.add(img);
+            state
+                .image_handles
+                .insert(thumbnail_sprite_name(&name), (handle, dims));
+        }
+
+        ret.push(VehicleSummary {
+            name,
+            path,
+            parts: vehicle.parts().count(),
+            mass: vehicle.total_mass(),
+            dv: vehicle.remaining_dv(),
+        });
+    }
+
+    ret
+}
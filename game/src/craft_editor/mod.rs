@@ -1,9 +1,19 @@
+mod compare;
 mod cursor_state;
 mod editor;
+mod gallery;
+mod part_search;
 mod part_ui;
+mod recovery;
+mod symmetry;
 mod welding_particle;
 
+pub use compare::*;
 pub use cursor_state::*;
 pub use editor::*;
+pub use gallery::*;
+pub use part_search::*;
 pub use part_ui::*;
+pub use recovery::*;
+pub use symmetry::*;
 pub use welding_particle::*;
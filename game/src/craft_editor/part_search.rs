@@ -0,0 +1,134 @@
+use crate::input::InputState;
+use bevy::input::keyboard::Key;
+use bevy::input::ButtonState;
+use starling::prelude::*;
+
+/// Narrows the parts menu down by name and by layer. While the search is
+/// focused, typing goes into it the way it goes into the debug console.
+#[derive(Debug, Clone, Default)]
+pub struct PartSearch {
+    focused: bool,
+    query: String,
+    layer: Option<PartLayer>,
+}
+
+impl PartSearch {
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    pub fn unfocus(&mut self) {
+        self.focused = false;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Layer which listed parts must be on, or None for every layer.
+    pub fn layer(&self) -> Option<PartLayer> {
+        self.layer
+    }
+
+    pub fn set_layer(&mut self, layer: Option<PartLayer>) {
+        self.layer = layer;
+    }
+
+    /// Whether the part is on the chosen layer, and its name contains
+    /// every word of the query, ignoring case.
+    pub fn matches(&self, name: &str, part: &PartPrototype) -> bool {
+        if self.layer.is_some_and(|l| part.layer() != l) {
+            return false;
+        }
+        let name = name.to_lowercase();
+        self.query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| name.contains(word))
+    }
+
+    /// Names of the parts which match, alphabetized.
+    pub fn filter<'a>(
+        &self,
+        parts: impl IntoIterator<Item = (&'a String, &'a PartPrototype)>,
+    ) -> Vec<&'a String> {
+        let mut ret: Vec<_> = parts
+            .into_iter()
+            .filter(|(name, part)| self.matches(name, part))
+            .map(|(name, _)| name)
+            .collect();
+        ret.sort();
+        ret
+    }
+
+    /// Takes typed keys while focused. Returns true when Enter is pressed,
+    /// to pick the first part listed.
+    pub fn process_input(&mut self, input: &mut InputState) -> bool {
+        if !self.focused {
+            return false;
+        }
+
+        let mut entered = false;
+        for key in &input.keyboard_events {
+            if key.state == ButtonState::Released {
+                continue;
+            }
+            match &key.logical_key {
+                Key::Character(c) => self.query += c,
+                Key::Space => self.query += " ",
+                Key::Backspace => _ = self.query.pop(),
+                Key::Enter => {
+                    self.focused = false;
+                    entered = true;
+                }
+                _ => (),
+            }
+        }
+
+        input.keyboard_events.clear();
+
+        entered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn parts_are_narrowed_by_every_word_and_by_layer() {
+        let tank = PartPrototype::Tank(TankModel::new(
+            "small-tank".to_string(),
+            UVec2::new(10, 10),
+            Mass::kilograms(100),
+            Mass::kilograms(1000),
+        ));
+        let port = PartPrototype::DockingPort(DockingPort::new(
+            "docking-port".to_string(),
+            UVec2::new(4, 8),
+            Mass::kilograms(50),
+        ));
+        let parts = HashMap::from([
+            ("small-tank".to_string(), tank),
+            ("docking-port".to_string(), port),
+        ]);
+
+        let mut search = PartSearch::default();
+        assert_eq!(search.filter(&parts), ["docking-port", "small-tank"]);
+
+        search.query = "TANK sm".to_string();
+        assert_eq!(search.filter(&parts), ["small-tank"]);
+
+        search.query = "o".to_string();
+        search.set_layer(Some(PartLayer::Structural));
+        assert_eq!(search.filter(&parts), ["docking-port"]);
+
+        search.set_layer(Some(PartLayer::Exterior));
+        assert!(search.filter(&parts).is_empty());
+    }
+}
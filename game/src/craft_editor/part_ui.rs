@@ -50,6 +50,12 @@ fn cargo_ui(
             format!("Capacity: {}", cargo.capacity_mass()),
             None,
         ),
+        text_node(button_height, format!("Holds: {:?}", cargo.filter()), None),
+        text_node(
+            button_height,
+            format!("Filled: {:0.1}%", cargo.percent_filled(data) * 100.0),
+            None,
+        ),
         text_node(button_height, "Clear Contents", OnClick::ClearContents(id)),
     ]
     .into_iter()
@@ -80,6 +86,39 @@ fn machine_ui(
     ]
 }
 
+fn decoupler_ui(button_height: f32, decoupler: &Decoupler) -> Vec<Node<OnClick>> {
+    vec![text_node(
+        button_height,
+        format!("Ejection speed: {:0.1} m/s", decoupler.ejection_speed()),
+        None,
+    )]
+}
+
+/// Stage the part is in, with buttons to move it to the stage before or
+/// after.
+fn stage_row(button_height: f32, id: PartId, instance: &InstantiatedPart) -> Node<OnClick> {
+    let stage = instance.stage();
+    let earlier = Node::button(
+        "-",
+        OnClick::SetPartStage(id, stage.saturating_sub(1)),
+        button_height,
+        button_height,
+    )
+    .enabled(stage > 0);
+    let later = Node::button(
+        "+",
+        OnClick::SetPartStage(id, stage + 1),
+        button_height,
+        button_height,
+    );
+    Node::new(Size::Grow, button_height)
+        .tight()
+        .invisible()
+        .with_child(text_node(button_height, format!("Stage {}", stage), None))
+        .with_child(later)
+        .with_child(earlier)
+}
+
 pub fn part_ui_layout(
     button_height: f32,
     id: PartId,
@@ -96,6 +135,7 @@ pub fn part_ui_layout(
         InstantiatedPartVariant::Tank(t, d) => tank_ui(button_height, id, t, d),
         InstantiatedPartVariant::Cargo(c, d) => cargo_ui(button_height, id, c, d),
         InstantiatedPartVariant::Machine(m, d) => machine_ui(button_height, id, m, d),
+        InstantiatedPartVariant::Decoupler(d) => decoupler_ui(button_height, d),
         _ => Vec::new(),
     }
     .into_iter();
@@ -104,5 +144,6 @@ pub fn part_ui_layout(
         .down()
         .with_color(UI_BACKGROUND_COLOR)
         .with_child(header)
+        .with_child(stage_row(button_height, id, instance))
         .with_children(children)
 }
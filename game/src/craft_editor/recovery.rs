@@ -0,0 +1,163 @@
+use crate::vehicle_import::vehicle_content_hash;
+use serde::{Deserialize, Serialize};
+use starling::prelude::*;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Versions of the editor's vehicle kept for recovery.
+pub const RECOVERY_VERSIONS: usize = 10;
+
+/// How often, in wall time, the editor's vehicle is captured.
+pub const RECOVERY_INTERVAL: Nanotime = Nanotime::millis(30_000);
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The editor's vehicle as it was at one point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryVersion {
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+    /// File the vehicle was opened from or last saved to, if any.
+    pub filepath: Option<PathBuf>,
+    pub vehicle: VehicleFileStorage,
+}
+
+impl RecoveryVersion {
+    pub fn new(vehicle: VehicleFileStorage, filepath: Option<PathBuf>) -> Self {
+        Self {
+            saved_at: unix_secs(),
+            filepath,
+            vehicle,
+        }
+    }
+
+    /// How long ago this version was captured.
+    pub fn age(&self) -> Nanotime {
+        Nanotime::secs(unix_secs().saturating_sub(self.saved_at) as i64)
+    }
+}
+
+/// The last few versions of work in progress in the editor, oldest first,
+/// kept on disk so that unsaved edits survive a crash or an accidental
+/// New.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecoveryRing {
+    versions: VecDeque<RecoveryVersion>,
+}
+
+impl RecoveryRing {
+    pub fn len(&self) -> usize {
+        self.versions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<&RecoveryVersion> {
+        self.versions.get(i)
+    }
+
+    pub fn latest(&self) -> Option<&RecoveryVersion> {
+        self.versions.back()
+    }
+
+    /// Newest first, with the index of each.
+    pub fn newest_first(&self) -> impl Iterator<Item = (usize, &RecoveryVersion)> + use<'_> {
+        self.versions.iter().enumerate().rev()
+    }
+
+    /// Adds a version, dropping the oldest once the ring is full. Empty
+    /// vehicles and vehicles no different from the latest version aren't
+    /// kept. Returns whether the version was added.
+    pub fn push(&mut self, version: RecoveryVersion) -> bool {
+        if version.vehicle.parts.is_empty() {
+            return false;
+        }
+        let hash = vehicle_content_hash(&version.vehicle);
+        if self
+            .latest()
+            .is_some_and(|v| vehicle_content_hash(&v.vehicle) == hash)
+        {
+            return false;
+        }
+        self.versions.push_back(version);
+        while self.versions.len() > RECOVERY_VERSIONS {
+            self.versions.pop_front();
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.versions.clear();
+    }
+}
+
+pub fn load_recovery_from_file(filename: &Path) -> Result<RecoveryRing, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    Ok(serde_yaml::from_str(&s)?)
+}
+
+pub fn write_recovery_to_file(ring: &RecoveryRing, filename: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(ring)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn design(parts: usize) -> VehicleFileStorage {
+        VehicleFileStorage {
+            name: "Wip".to_string(),
+            parts: (0..parts)
+                .map(|i| VehiclePartFileStorage {
+                    partname: "frame".to_string(),
+                    pos: IVec2::new(i as i32 * 10, 0),
+                    rot: Rotation::East,
+                    stage: 0,
+                })
+                .collect(),
+            lines: HashSet::new(),
+            loadouts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ring_keeps_recent_distinct_versions() {
+        let mut ring = RecoveryRing::default();
+        assert!(!ring.push(RecoveryVersion::new(design(0), None)));
+        assert!(ring.push(RecoveryVersion::new(design(1), None)));
+        assert!(!ring.push(RecoveryVersion::new(design(1), None)));
+        for n in 2..=RECOVERY_VERSIONS + 3 {
+            assert!(ring.push(RecoveryVersion::new(design(n), None)));
+        }
+        assert_eq!(ring.len(), RECOVERY_VERSIONS);
+        assert_eq!(
+            ring.latest().unwrap().vehicle.parts.len(),
+            RECOVERY_VERSIONS + 3
+        );
+        assert_eq!(ring.get(0).unwrap().vehicle.parts.len(), 4);
+
+        let newest: Vec<usize> = ring.newest_first().map(|(i, _)| i).take(2).collect();
+        assert_eq!(newest, vec![RECOVERY_VERSIONS - 1, RECOVERY_VERSIONS - 2]);
+
+        let path = std::env::temp_dir().join(format!("recovery-{}.yaml", std::process::id()));
+        write_recovery_to_file(&ring, &path).unwrap();
+        let loaded = load_recovery_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.len(), ring.len());
+        assert!(loaded.latest().unwrap().age() < Nanotime::secs(60));
+    }
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use starling::prelude::*;
+
+/// How parts placed in the editor are mirrored about the vehicle's axes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Sequence)]
+pub enum Symmetry {
+    #[default]
+    Off,
+    /// Mirrored across the Y axis, left to right.
+    Mirror,
+    /// Mirrored across both axes, for four copies in all.
+    Quad,
+}
+
+impl Symmetry {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Mirror => "Mirror",
+            Self::Quad => "Quad",
+        }
+    }
+
+    /// Where the mirrored copies of a part at `pos` go, and how they're
+    /// turned. `dims` are the part's pixel dimensions after rotation.
+    /// Copies which would land right on top of the part, as for one
+    /// centered on an axis, are left out.
+    pub fn counterparts(&self, pos: IVec2, rot: Rotation, dims: IVec2) -> Vec<(IVec2, Rotation)> {
+        let flip_x = |(p, r): (IVec2, Rotation)| {
+            let r = match r {
+                Rotation::East => Rotation::West,
+                Rotation::West => Rotation::East,
+                r => r,
+            };
+            (IVec2::new(-p.x - dims.x, p.y), r)
+        };
+        let flip_y = |(p, r): (IVec2, Rotation)| {
+            let r = match r {
+                Rotation::North => Rotation::South,
+                Rotation::South => Rotation::North,
+                r => r,
+            };
+            (IVec2::new(p.x, -p.y - dims.y), r)
+        };
+
+        let original = (pos, rot);
+        let copies = match self {
+            Self::Off => vec![],
+            Self::Mirror => vec![flip_x(original)],
+            Self::Quad => vec![flip_x(original), flip_y(original), flip_y(flip_x(original))],
+        };
+
+        let mut ret: Vec<(IVec2, Rotation)> = Vec::new();
+        for (p, r) in copies {
+            if p != pos && !ret.iter().any(|(q, _)| *q == p) {
+                ret.push((p, r));
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirrored_parts_land_opposite_and_face_away() {
+        let dims = IVec2::new(4, 2);
+        let off = Symmetry::Off.counterparts(IVec2::new(3, 1), Rotation::East, dims);
+        assert!(off.is_empty());
+
+        // occupies x in 3..7, so the copy occupies -7..-3
+        let mirror = Symmetry::Mirror.counterparts(IVec2::new(3, 1), Rotation::East, dims);
+        assert_eq!(mirror, vec![(IVec2::new(-7, 1), Rotation::West)]);
+
+        let quad = Symmetry::Quad.counterparts(IVec2::new(3, 1), Rotation::North, dims);
+        assert_eq!(
+            quad,
+            vec![
+                (IVec2::new(-7, 1), Rotation::North),
+                (IVec2::new(3, -3), Rotation::South),
+                (IVec2::new(-7, -3), Rotation::South),
+            ]
+        );
+
+        // a part straddling the Y axis has no separate copy across it
+        let centered = Symmetry::Quad.counterparts(IVec2::new(-2, 1), Rotation::East, dims);
+        assert_eq!(centered, vec![(IVec2::new(-2, -3), Rotation::East)]);
+    }
+}
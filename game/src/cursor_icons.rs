@@ -0,0 +1,253 @@
+use crate::canvas::Canvas;
+use crate::game::GameState;
+use crate::input::{FrameId, MouseButt};
+use crate::scenes::orbital::CursorMode;
+use crate::scenes::SceneType;
+use crate::z_index::ZOrdering;
+use bevy::color::palettes::css::*;
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use image::{Rgba, RgbaImage};
+
+/// Pixel size of generated cursor icon textures.
+pub const CURSOR_ICON_TEXTURE_SIZE: u32 = 32;
+
+/// On-screen size of cursor icons.
+pub const CURSOR_ICON_SIZE: f32 = 20.0;
+
+/// Where icons are drawn relative to the cursor, so that they sit beside
+/// the pointer instead of underneath it.
+const CURSOR_ICON_OFFSET: Vec2 = Vec2::new(16.0, -16.0);
+
+/// Stroke width of cursor icons, in texture pixels.
+const STROKE_WIDTH: f32 = 2.0;
+
+/// Small icon drawn next to the cursor to show what clicking will do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+pub enum CursorIcon {
+    Select,
+    AddOrbit,
+    NearOrbit,
+    MeasuringTape,
+    Protractor,
+    ManeuverNode,
+    Part,
+    Pipe,
+    Entity,
+    Ui,
+}
+
+enum Stroke {
+    Line(Vec2, Vec2),
+    Circle(Vec2, f32),
+}
+
+impl Stroke {
+    fn distance(&self, p: Vec2) -> f32 {
+        match self {
+            Self::Line(a, b) => {
+                let ab = *b - *a;
+                let t = ((p - *a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0);
+                p.distance(*a + ab * t)
+            }
+            Self::Circle(c, r) => (p.distance(*c) - r).abs(),
+        }
+    }
+}
+
+impl CursorIcon {
+    pub fn from_mode(mode: CursorMode) -> Self {
+        match mode {
+            CursorMode::Rect => Self::Select,
+            CursorMode::AddOrbit => Self::AddOrbit,
+            CursorMode::NearOrbit => Self::NearOrbit,
+            CursorMode::MeasuringTape => Self::MeasuringTape,
+            CursorMode::Protractor => Self::Protractor,
+            CursorMode::ManeuverNode => Self::ManeuverNode,
+        }
+    }
+
+    /// Picks the icon for whatever is under the cursor. UI takes priority,
+    /// then the scene's own cursor state.
+    pub fn for_state(state: &GameState) -> Option<Self> {
+        if state.is_hovering_over_ui() {
+            return Some(Self::Ui);
+        }
+        match state.scene {
+            SceneType::Orbital => {
+                if state.orbital_context.hovered_entity.is_some() {
+                    Some(Self::Entity)
+                } else {
+                    Some(Self::from_mode(state.orbital_context.cursor_mode))
+                }
+            }
+            SceneType::Editor => {
+                let ctx = &state.editor_context;
+                if ctx.is_plumbing() {
+                    Some(Self::Pipe)
+                } else if ctx.is_placing_part() {
+                    Some(Self::Part)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn sprite_name(&self) -> String {
+        format!("cursor-{:?}", self).to_lowercase()
+    }
+
+    pub fn color(&self) -> Srgba {
+        match self {
+            Self::Select | Self::Ui => WHITE,
+            Self::AddOrbit | Self::NearOrbit => TEAL,
+            Self::MeasuringTape | Self::Protractor => YELLOW,
+            Self::ManeuverNode => LIME,
+            Self::Part => ORANGE,
+            Self::Pipe => LIGHT_BLUE,
+            Self::Entity => GRAY,
+        }
+    }
+
+    /// Outline of the icon, in coordinates spanning -1 to 1 with y up.
+    fn strokes(&self) -> Vec<Stroke> {
+        let v = Vec2::new;
+        let line = |a: Vec2, b: Vec2| Stroke::Line(a, b);
+        match self {
+            Self::Select => {
+                let mut s = Vec::new();
+                for (x, y) in [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)] {
+                    let c = v(x, y) * 0.8;
+                    s.push(line(c, c - v(x * 0.5, 0.0)));
+                    s.push(line(c, c - v(0.0, y * 0.5)));
+                }
+                s
+            }
+            Self::AddOrbit => vec![
+                Stroke::Circle(Vec2::ZERO, 0.75),
+                line(v(-0.35, 0.0), v(0.35, 0.0)),
+                line(v(0.0, -0.35), v(0.0, 0.35)),
+            ],
+            Self::NearOrbit => vec![
+                Stroke::Circle(Vec2::ZERO, 0.75),
+                Stroke::Circle(Vec2::ZERO, 0.4),
+                Stroke::Circle(v(0.53, 0.53), 0.12),
+            ],
+            Self::MeasuringTape => {
+                let (a, b) = (v(-0.8, -0.8), v(0.8, 0.8));
+                let n = v(-1.0, 1.0).normalize() * 0.25;
+                let mut s = vec![line(a, b)];
+                for i in 0..5 {
+                    let p = a.lerp(b, i as f32 / 4.0);
+                    s.push(line(p, p + n));
+                }
+                s
+            }
+            Self::Protractor => {
+                let o = v(-0.8, -0.8);
+                let mut s = vec![
+                    line(o, v(0.85, -0.8)),
+                    line(o, o + Vec2::from_angle(0.9) * 1.6),
+                ];
+                let arc: Vec<_> = (0..=6)
+                    .map(|i| o + Vec2::from_angle(0.9 * i as f32 / 6.0) * 0.9)
+                    .collect();
+                s.extend(arc.windows(2).map(|w| line(w[0], w[1])));
+                s
+            }
+            Self::ManeuverNode => vec![
+                Stroke::Circle(v(-0.3, -0.3), 0.35),
+                line(v(-0.3, 0.05), v(-0.3, 0.9)),
+                line(v(0.05, -0.3), v(0.9, -0.3)),
+                line(v(-0.3, 0.9), v(-0.5, 0.65)),
+                line(v(-0.3, 0.9), v(-0.1, 0.65)),
+            ],
+            Self::Part => vec![
+                line(v(-0.7, -0.7), v(0.7, -0.7)),
+                line(v(0.7, -0.7), v(0.7, 0.7)),
+                line(v(0.7, 0.7), v(-0.7, 0.7)),
+                line(v(-0.7, 0.7), v(-0.7, -0.7)),
+                line(v(-0.3, 0.0), v(0.3, 0.0)),
+                line(v(0.0, -0.3), v(0.0, 0.3)),
+            ],
+            Self::Pipe => vec![
+                line(v(-0.8, 0.5), v(0.3, 0.5)),
+                line(v(-0.8, 0.1), v(-0.1, 0.1)),
+                line(v(0.3, 0.5), v(0.3, -0.8)),
+                line(v(-0.1, 0.1), v(-0.1, -0.8)),
+            ],
+            Self::Entity => vec![
+                Stroke::Circle(Vec2::ZERO, 0.5),
+                line(v(0.0, 0.6), v(0.0, 0.95)),
+                line(v(0.0, -0.6), v(0.0, -0.95)),
+                line(v(0.6, 0.0), v(0.95, 0.0)),
+                line(v(-0.6, 0.0), v(-0.95, 0.0)),
+            ],
+            Self::Ui => vec![
+                line(v(-0.6, 0.9), v(-0.6, -0.5)),
+                line(v(-0.6, 0.9), v(0.4, -0.1)),
+                line(v(-0.6, -0.5), v(-0.2, -0.2)),
+                line(v(0.4, -0.1), v(-0.1, -0.1)),
+                line(v(-0.3, -0.15), v(0.0, -0.8)),
+            ],
+        }
+    }
+}
+
+/// Rasterizes a cursor icon in white, so that it can be tinted when drawn.
+pub fn generate_cursor_icon(icon: CursorIcon, size: u32) -> RgbaImage {
+    let strokes = icon.strokes();
+    let half = size as f32 / 2.0;
+    let mut img = RgbaImage::new(size, size);
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        let p = Vec2::new(x as f32 + 0.5 - half, half - y as f32 - 0.5) / half;
+        let d = strokes
+            .iter()
+            .map(|s| s.distance(p))
+            .fold(f32::MAX, f32::min);
+        let alpha = (STROKE_WIDTH / 2.0 + 0.5 - d * half).clamp(0.0, 1.0);
+        *pixel = Rgba([255, 255, 255, (alpha * 255.0) as u8]);
+    }
+    img
+}
+
+pub fn draw_cursor_icon(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let icon = CursorIcon::for_state(state)?;
+    let cursor = state.input.position(MouseButt::Hover, FrameId::Current)?;
+    canvas
+        .sprite(
+            cursor + CURSOR_ICON_OFFSET,
+            0.0,
+            icon.sprite_name(),
+            ZOrdering::Cursor,
+            Vec2::splat(CURSOR_ICON_SIZE),
+        )
+        .set_color(icon.color().with_alpha(0.8));
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enum_iterator::all;
+    use std::collections::HashSet;
+
+    #[test]
+    fn cursor_icons_are_distinct() {
+        let names: HashSet<_> = all::<CursorIcon>().map(|i| i.sprite_name()).collect();
+        assert_eq!(names.len(), all::<CursorIcon>().count());
+
+        let images: Vec<_> = all::<CursorIcon>()
+            .map(|i| generate_cursor_icon(i, CURSOR_ICON_TEXTURE_SIZE).into_raw())
+            .collect();
+        for (i, a) in images.iter().enumerate() {
+            assert!(a.chunks(4).any(|p| p[3] == 255));
+            assert!(a.chunks(4).any(|p| p[3] == 0));
+            for b in &images[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+}
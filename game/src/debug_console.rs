@@ -1,20 +1,43 @@
 use crate::commands::command::CommandDecl;
+use crate::console_history::ConsoleHistory;
 use crate::input::InputState;
 use bevy::input::keyboard::Key;
 use bevy::input::ButtonState;
+use bevy::prelude::KeyCode;
+
+/// Reverse incremental search through the command history, as started
+/// with Ctrl+R.
+struct HistorySearch {
+    query: String,
+    skip: usize,
+}
 
 pub struct DebugConsole {
     is_active: bool,
     text: String,
-    history: Vec<String>,
+    lines: Vec<String>,
+    history: ConsoleHistory,
+    /// How far back in the history the up and down arrows have gone, and
+    /// the text which was being typed before browsing started.
+    browsing: Option<(usize, String)>,
+    search: Option<HistorySearch>,
+    unsaved: bool,
 }
 
 impl DebugConsole {
     pub fn new() -> Self {
+        Self::with_history(ConsoleHistory::default())
+    }
+
+    pub fn with_history(history: ConsoleHistory) -> Self {
         Self {
             is_active: false,
             text: String::new(),
-            history: Vec::new(),
+            lines: Vec::new(),
+            history,
+            browsing: None,
+            search: None,
+            unsaved: false,
         }
     }
 
@@ -38,25 +61,75 @@ impl DebugConsole {
         &self.text
     }
 
+    /// Replaces the input line, such as to start a command for the player
+    /// to finish typing.
+    pub fn set_cmd(&mut self, cmd: impl Into<String>) {
+        self.text = cmd.into();
+        self.browsing = None;
+        self.search = None;
+    }
+
+    /// The input line as it should be displayed, including any history
+    /// search in progress.
+    pub fn prompt(&self) -> String {
+        match &self.search {
+            Some(s) => format!(
+                "(reverse-i-search)'{}': {}",
+                s.query,
+                self.search_match().unwrap_or("")
+            ),
+            None => format!("> {}", self.text),
+        }
+    }
+
     pub fn lines(&self) -> &Vec<String> {
+        &self.lines
+    }
+
+    pub fn history(&self) -> &ConsoleHistory {
         &self.history
     }
 
+    pub fn set_alias(&mut self, name: impl Into<String>, cmd: impl Into<String>) {
+        self.history.aliases.insert(name.into(), cmd.into());
+        self.unsaved = true;
+    }
+
+    pub fn remove_alias(&mut self, name: &str) -> Option<String> {
+        let ret = self.history.aliases.remove(name);
+        self.unsaved |= ret.is_some();
+        ret
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.commands.clear();
+        self.unsaved = true;
+    }
+
+    /// Whether the history or aliases have changed since this was last
+    /// called, and so need to be saved.
+    pub fn take_unsaved(&mut self) -> bool {
+        std::mem::take(&mut self.unsaved)
+    }
+
     pub fn log(&mut self, s: impl Into<String>) {
         let s = s.into();
-        self.history.push(s);
+        self.lines.push(s);
     }
 
     fn enter(&mut self) -> Option<(CommandDecl, Vec<String>)> {
+        self.browsing = None;
         if self.text.is_empty() {
             return None;
         }
         let cmd = self.text.clone();
-        self.history.push("".into());
-        self.history.push(format!("> {}", cmd));
+        self.lines.push("".into());
+        self.lines.push(format!("> {}", cmd));
         self.text.clear();
+        self.history.push(&cmd);
+        self.unsaved = true;
 
-        match shellwords::split(&cmd) {
+        match self.history.expand(&cmd) {
             Ok(args) => {
                 let name = args.get(0).cloned().unwrap_or("".to_string());
                 let v = CommandDecl::from_str(&name);
@@ -78,7 +151,7 @@ impl DebugConsole {
         let lines = lines.into();
         for line in lines.lines() {
             for wrapped in textwrap::wrap(line, 80) {
-                self.history.push(wrapped.to_string());
+                self.lines.push(wrapped.to_string());
             }
         }
     }
@@ -87,28 +160,137 @@ impl DebugConsole {
         self.text.pop();
     }
 
+    fn browse_older(&mut self) {
+        let back = match &self.browsing {
+            Some((back, _)) => back + 1,
+            None => 0,
+        };
+        let Some(cmd) = self.history.get(back) else {
+            return;
+        };
+        let cmd = cmd.to_string();
+        let draft = match self.browsing.take() {
+            Some((_, draft)) => draft,
+            None => std::mem::take(&mut self.text),
+        };
+        self.text = cmd;
+        self.browsing = Some((back, draft));
+    }
+
+    fn browse_newer(&mut self) {
+        self.browsing = match self.browsing.take() {
+            Some((0, draft)) => {
+                self.text = draft;
+                None
+            }
+            Some((back, draft)) => {
+                self.text = self.history.get(back - 1).unwrap_or("").to_string();
+                Some((back - 1, draft))
+            }
+            None => None,
+        };
+    }
+
+    fn search_match(&self) -> Option<&str> {
+        let s = self.search.as_ref()?;
+        self.history.search(&s.query, s.skip)
+    }
+
+    /// Starts a history search, or if one is underway, moves on to the
+    /// next older match, if there is one.
+    fn search_older(&mut self) {
+        let Some(s) = &self.search else {
+            self.search = Some(HistorySearch {
+                query: String::new(),
+                skip: 0,
+            });
+            return;
+        };
+        let skip = s.skip + 1;
+        if self.history.search(&s.query, skip).is_some() {
+            if let Some(s) = &mut self.search {
+                s.skip = skip;
+            }
+        }
+    }
+
+    /// Ends the history search, putting the match on the input line.
+    fn accept_search(&mut self) {
+        if let Some(m) = self.search_match() {
+            self.text = m.to_string();
+        }
+        self.search = None;
+        self.browsing = None;
+    }
+
+    fn process_search_key(&mut self, key: &Key) -> Option<(CommandDecl, Vec<String>)> {
+        let search = self.search.as_mut()?;
+        match key {
+            Key::Character(c) => {
+                search.query += c;
+                search.skip = 0;
+            }
+            Key::Space => {
+                search.query += " ";
+                search.skip = 0;
+            }
+            Key::Backspace => {
+                search.query.pop();
+                search.skip = 0;
+            }
+            Key::Enter => {
+                self.accept_search();
+                return self.enter();
+            }
+            Key::ArrowUp | Key::ArrowDown | Key::ArrowLeft | Key::ArrowRight | Key::Tab => {
+                self.accept_search()
+            }
+            _ => (),
+        }
+        None
+    }
+
     pub fn process_input(&mut self, input: &mut InputState) -> Option<(CommandDecl, Vec<String>)> {
         if !self.is_active {
             return None;
         }
 
+        let ctrl =
+            input.is_pressed(KeyCode::ControlLeft) || input.is_pressed(KeyCode::ControlRight);
+
         for key in &input.keyboard_events {
             match key.state {
                 ButtonState::Pressed => (),
                 ButtonState::Released => continue,
             };
 
-            match &key.logical_key {
-                Key::Character(c) => {
-                    // TODO handle this better
-                    if c == "`" {
-                        continue;
+            if let Key::Character(c) = &key.logical_key {
+                // TODO handle this better
+                if c == "`" {
+                    continue;
+                }
+                if ctrl {
+                    if c.eq_ignore_ascii_case("r") {
+                        self.search_older();
                     }
-                    self.text += c;
+                    continue;
                 }
+            }
+
+            if self.search.is_some() {
+                if let Some(ret) = self.process_search_key(&key.logical_key) {
+                    return Some(ret);
+                }
+                continue;
+            }
+
+            match &key.logical_key {
+                Key::Character(c) => self.text += c,
                 Key::Enter => return self.enter(),
                 Key::Backspace => self.backspace(),
                 Key::Space => self.text += " ",
+                Key::ArrowUp => self.browse_older(),
+                Key::ArrowDown => self.browse_newer(),
                 _ => (),
             }
         }
@@ -0,0 +1,79 @@
+use starling::prelude::*;
+
+/// Sim time at which labels and markers are placed. The sim only advances
+/// on game ticks, so at high warp entities jump a long way between them;
+/// this clock instead runs on through each render frame at the rate the
+/// last tick advanced, so UI elements glide along with their entities.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayClock {
+    stamp: Nanotime,
+    step: Nanotime,
+    fraction: f32,
+}
+
+impl DisplayClock {
+    pub fn on_game_tick(&mut self, stamp: Nanotime) {
+        // time going backwards means a rewind or load, not a tick
+        self.step = if stamp >= self.stamp {
+            stamp - self.stamp
+        } else {
+            Nanotime::zero()
+        };
+        self.stamp = stamp;
+        self.fraction = 0.0;
+    }
+
+    /// Sets how far through the wait for the next game tick this render
+    /// frame is, from zero to one.
+    pub fn on_render_tick(&mut self, fraction: f32) {
+        self.fraction = fraction.clamp(0.0, 1.0);
+    }
+
+    pub fn stamp(&self) -> Nanotime {
+        self.stamp + self.step * self.fraction as f64
+    }
+}
+
+/// World position of an entity at the given time, for placing UI. Planets
+/// and vehicles coasting on rails are moved along their orbits; anything
+/// else stays where the sim last put it.
+pub fn display_position(universe: &Universe, id: EntityId, stamp: Nanotime) -> Option<DVec2> {
+    if let Some((_, pv, _, _)) = universe.planets.lookup(id, stamp) {
+        return Some(pv.pos);
+    }
+    let coasting = universe
+        .surface_vehicles
+        .get(&id)
+        .filter(|sv| sv.can_be_on_rails())
+        .and_then(|sv| sv.current_orbit());
+    if let Some(GlobalOrbit(parent, orbit)) = coasting {
+        let (_, origin, _, _) = universe.planets.lookup(parent, stamp)?;
+        if let Ok(pv) = orbit.pv(stamp) {
+            return Some(pv.pos + origin.pos);
+        }
+    }
+    universe.pv(id).map(|pv| pv.pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_clock_runs_on_between_ticks() {
+        let mut clock = DisplayClock::default();
+        clock.on_game_tick(Nanotime::secs(10));
+        clock.on_game_tick(Nanotime::secs(70));
+        assert_eq!(clock.stamp(), Nanotime::secs(70));
+
+        clock.on_render_tick(0.5);
+        assert_eq!(clock.stamp(), Nanotime::secs(100));
+        clock.on_render_tick(3.0);
+        assert_eq!(clock.stamp(), Nanotime::secs(130));
+
+        // rewinding doesn't run the clock backwards past the new stamp
+        clock.on_game_tick(Nanotime::secs(20));
+        clock.on_render_tick(0.5);
+        assert_eq!(clock.stamp(), Nanotime::secs(20));
+    }
+}
@@ -0,0 +1,155 @@
+use enum_iterator::{next_cycle, Sequence};
+use serde::{Deserialize, Serialize};
+
+/// What was under the cursor when it was double-clicked in the orbital
+/// view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Sequence)]
+pub enum DoubleClickTarget {
+    Orbiter,
+    Planet,
+    LandingSite,
+    EmptySpace,
+}
+
+impl std::fmt::Display for DoubleClickTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Orbiter => write!(f, "orbiter"),
+            Self::Planet => write!(f, "planet"),
+            Self::LandingSite => write!(f, "site"),
+            Self::EmptySpace => write!(f, "space"),
+        }
+    }
+}
+
+/// Something a double-click can do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence, Deserialize, Serialize)]
+pub enum DoubleClickAction {
+    Nothing,
+    /// Keep the camera on the orbiter or planet, or the planet a landing
+    /// site is on.
+    Follow,
+    /// Take the controls of the orbiter.
+    Pilot,
+    /// Zoom out to fit the planet's sphere of influence, or that of the
+    /// planet an orbiter or landing site belongs to.
+    FrameSoi,
+    /// Zoom down to the landing site's surface.
+    ViewSite,
+    ClearFollow,
+}
+
+impl DoubleClickAction {
+    pub fn applies_to(&self, target: DoubleClickTarget) -> bool {
+        use DoubleClickTarget::*;
+        match self {
+            Self::Nothing | Self::ClearFollow => true,
+            Self::Follow | Self::FrameSoi => target != EmptySpace,
+            Self::Pilot => target == Orbiter,
+            Self::ViewSite => target == LandingSite,
+        }
+    }
+}
+
+impl std::fmt::Display for DoubleClickAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nothing => write!(f, "nothing"),
+            Self::Follow => write!(f, "follow"),
+            Self::Pilot => write!(f, "pilot"),
+            Self::FrameSoi => write!(f, "frame SOI"),
+            Self::ViewSite => write!(f, "view surface"),
+            Self::ClearFollow => write!(f, "stop following"),
+        }
+    }
+}
+
+/// What double-clicking each kind of thing does, as kept in the settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DoubleClickBindings {
+    pub orbiter: DoubleClickAction,
+    pub planet: DoubleClickAction,
+    pub landing_site: DoubleClickAction,
+    pub empty_space: DoubleClickAction,
+}
+
+impl Default for DoubleClickBindings {
+    fn default() -> Self {
+        Self {
+            orbiter: DoubleClickAction::Follow,
+            planet: DoubleClickAction::FrameSoi,
+            landing_site: DoubleClickAction::ViewSite,
+            empty_space: DoubleClickAction::ClearFollow,
+        }
+    }
+}
+
+impl DoubleClickBindings {
+    pub fn get(&self, target: DoubleClickTarget) -> DoubleClickAction {
+        match target {
+            DoubleClickTarget::Orbiter => self.orbiter,
+            DoubleClickTarget::Planet => self.planet,
+            DoubleClickTarget::LandingSite => self.landing_site,
+            DoubleClickTarget::EmptySpace => self.empty_space,
+        }
+    }
+
+    fn get_mut(&mut self, target: DoubleClickTarget) -> &mut DoubleClickAction {
+        match target {
+            DoubleClickTarget::Orbiter => &mut self.orbiter,
+            DoubleClickTarget::Planet => &mut self.planet,
+            DoubleClickTarget::LandingSite => &mut self.landing_site,
+            DoubleClickTarget::EmptySpace => &mut self.empty_space,
+        }
+    }
+
+    /// Steps the target's binding on to the next action which makes sense
+    /// for it.
+    pub fn cycle(&mut self, target: DoubleClickTarget) -> DoubleClickAction {
+        let action = self.get_mut(target);
+        loop {
+            *action = next_cycle(action);
+            if action.applies_to(target) {
+                return *action;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enum_iterator::{all, cardinality};
+
+    #[test]
+    fn bindings_cycle_through_applicable_actions() {
+        let mut b = DoubleClickBindings::default();
+        for t in all::<DoubleClickTarget>() {
+            assert!(b.get(t).applies_to(t));
+        }
+
+        let seen: Vec<_> = (0..cardinality::<DoubleClickAction>())
+            .map(|_| b.cycle(DoubleClickTarget::EmptySpace))
+            .collect();
+        assert!(seen.iter().all(|a| matches!(
+            a,
+            DoubleClickAction::Nothing | DoubleClickAction::ClearFollow
+        )));
+        assert_eq!(b.orbiter, DoubleClickAction::Follow);
+
+        b.planet = DoubleClickAction::Follow;
+        assert_eq!(
+            b.cycle(DoubleClickTarget::Planet),
+            DoubleClickAction::FrameSoi
+        );
+
+        // missing entries keep their defaults
+        let b: DoubleClickBindings = serde_yaml::from_str("orbiter: Pilot").unwrap();
+        assert_eq!(b.get(DoubleClickTarget::Orbiter), DoubleClickAction::Pilot);
+        assert_eq!(
+            b.get(DoubleClickTarget::LandingSite),
+            DoubleClickAction::ViewSite
+        );
+    }
+}
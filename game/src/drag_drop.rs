@@ -0,0 +1,140 @@
+use starling::prelude::*;
+
+/// Screen distance, in pixels, within which a dragged vehicle snaps to a
+/// planet's surface or to a queued orbit.
+pub const DROP_SNAP_PIXELS: f64 = 30.0;
+
+/// Cursor travel, in pixels, before a press on a gallery thumbnail becomes
+/// a drag rather than a click.
+pub const DRAG_START_PIXELS: f32 = 10.0;
+
+/// A vehicle picked up from the editor's gallery, carried across scenes
+/// until it's dropped into the world.
+#[derive(Debug, Clone)]
+pub struct DraggedVehicle {
+    pub name: String,
+    pub vehicle: Vehicle,
+}
+
+/// Where a dragged vehicle would be placed if it were dropped now.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DropTarget {
+    Surface { planet: EntityId, angle: f64 },
+    Orbit { index: usize, orbit: GlobalOrbit },
+}
+
+impl DropTarget {
+    /// Finds the planet surface or queued orbit nearest to the world
+    /// position `pos`, within `snap` meters. Orbit targets start the
+    /// vehicle at the point on the orbit nearest to `pos`.
+    pub fn find(
+        universe: &Universe,
+        queued_orbits: &[GlobalOrbit],
+        pos: DVec2,
+        snap: f64,
+    ) -> Option<Self> {
+        let stamp = universe.stamp();
+
+        let surfaces = universe.planets.planet_ids().into_iter().filter_map(|id| {
+            let (body, pv, _, _) = universe.planets.lookup(id, stamp)?;
+            let rel = pos - pv.pos;
+            let d = (rel.length() - body.radius).abs();
+            let angle = rel.to_angle();
+            Some((Self::Surface { planet: id, angle }, d))
+        });
+
+        let orbits = queued_orbits.iter().enumerate().filter_map(|(i, go)| {
+            let origin = universe.pv(go.0)?.pos;
+            let (pv, _) = go.1.nearest(pos - origin);
+            let d = pv.pos.distance(pos - origin);
+            let orbit = SparseOrbit::from_pv(pv, go.1.body, stamp)?;
+            let target = Self::Orbit {
+                index: i,
+                orbit: GlobalOrbit(go.0, orbit),
+            };
+            Some((target, d))
+        });
+
+        surfaces
+            .chain(orbits)
+            .filter(|(_, d)| *d < snap)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(t, _)| t)
+    }
+
+    /// World position at which the vehicle would appear.
+    pub fn position(&self, universe: &Universe) -> Option<DVec2> {
+        match self {
+            Self::Surface { planet, angle } => {
+                let (body, pv, _, _) = universe.planets.lookup(*planet, universe.stamp())?;
+                Some(pv.pos + rotate_f64(DVec2::X * body.radius, *angle))
+            }
+            Self::Orbit { orbit, .. } => {
+                let origin = universe.pv(orbit.0)?.pos;
+                Some(origin + orbit.1.pv(universe.stamp()).ok()?.pos)
+            }
+        }
+    }
+
+    /// Adds the vehicle to the universe at this target.
+    pub fn place(&self, universe: &mut Universe, vehicle: Vehicle) -> Option<EntityId> {
+        match *self {
+            Self::Surface { planet, angle } => {
+                let id = universe.add_surface_vehicle(planet, vehicle, angle, 5.0)?;
+                universe
+                    .surface_vehicles
+                    .get_mut(&id)?
+                    .controller
+                    .set_idle();
+                Some(id)
+            }
+            Self::Orbit { orbit, .. } => universe.add_orbital_vehicle(vehicle, orbit),
+        }
+    }
+}
+
+impl std::fmt::Display for DropTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Surface { planet, .. } => write!(f, "surface of {}", planet),
+            Self::Orbit { index, .. } => write!(f, "queued orbit {}", index + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_snap_to_surfaces_and_orbits() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let body = universe.planets.body;
+        let orbit = SparseOrbit::circular(body.radius * 2.0, body, Nanotime::zero(), false);
+        let queued = [GlobalOrbit(planet, orbit)];
+
+        let near_surface = DVec2::Y * (body.radius + 10.0);
+        let target = DropTarget::find(&universe, &queued, near_surface, 50.0);
+        let Some(DropTarget::Surface { planet: p, angle }) = target else {
+            panic!("expected a surface target, got {:?}", target);
+        };
+        assert_eq!(p, planet);
+        assert!((angle - PI_64 / 2.0).abs() < 1E-9);
+
+        let near_orbit = DVec2::X * (body.radius * 2.0 - 20.0);
+        let target = DropTarget::find(&universe, &queued, near_orbit, 50.0);
+        assert!(matches!(target, Some(DropTarget::Orbit { index: 0, .. })));
+        let p = target.unwrap().position(&universe).unwrap();
+        assert!(p.distance(near_orbit) < 50.0);
+
+        let nowhere = DVec2::X * body.radius * 1.5;
+        assert_eq!(DropTarget::find(&universe, &queued, nowhere, 50.0), None);
+
+        let id = target
+            .unwrap()
+            .place(&mut universe, Vehicle::new())
+            .unwrap();
+        assert!(universe.lup_orbiter(id).is_some());
+    }
+}
@@ -4,15 +4,23 @@ use bevy::prelude::*;
 use bevy_vector_shapes::prelude::*;
 use starling::prelude::*;
 
+use crate::annotations::AnnotationKind;
 use crate::button::*;
 use crate::camera_controller::*;
 use crate::canvas::Canvas;
+use crate::drag_drop::DropTarget;
+use crate::frame_budget::CostlyVisual;
 use crate::game::GameState;
 use crate::graph::*;
 use crate::input::*;
+use crate::keybindings::KeyAction;
 use crate::notifications::*;
+use crate::offscreen_markers::offscreen_markers;
 use crate::onclick::OnClick;
+use crate::palette::Severity;
+use crate::profiler::*;
 use crate::scenes::*;
+use crate::stats::CRASH_SPEED;
 use crate::z_index::*;
 
 pub fn draw_cross(gizmos: &mut Gizmos, p: Vec2, size: f32, color: Srgba) {
@@ -138,26 +146,8 @@ pub fn draw_orbit(
     color: Srgba,
     ctx: &impl CameraProjection,
 ) {
-    if orb.ecc() >= 1.0 {
-        // orb.will_escape() {
-        let ta = if orb.is_hyperbolic() {
-            let hrta = hyperbolic_range_ta(orb.ecc() as f32);
-            linspace(-0.999 * hrta, 0.999 * hrta, 1000)
-        } else {
-            linspace(-PI, PI, 1000)
-        };
-
-        let points: Vec<_> = ta
-            .iter()
-            .filter_map(|t| {
-                let p = orb.position_at(*t as f64);
-                if p.length() > orb.body.soi as f64 {
-                    return None;
-                }
-                Some(ctx.w2c(origin + p))
-            })
-            .collect();
-        canvas.gizmos.linestrip_2d(points, color);
+    if orb.ecc() >= 1.0 || orb.apoapsis_r() > orb.body.soi {
+        draw_escape_trajectory(canvas, orb, origin, color, ctx);
     } else {
         let b = orb.semi_minor_axis();
         let center = origin + (orb.periapsis() + orb.apoapsis()) / 2.0;
@@ -180,6 +170,39 @@ pub fn draw_orbit(
     }
 }
 
+/// Largest deviation, in pixels, of a drawn escape trajectory from the
+/// true curve.
+const ESCAPE_TRAJECTORY_TOLERANCE: f32 = 0.5;
+
+/// Draws the part of an open or escaping orbit inside the sphere of
+/// influence, sampled more densely where it bends most on screen, and
+/// marks where it leaves.
+fn draw_escape_trajectory(
+    canvas: &mut Canvas,
+    orb: &SparseOrbit,
+    origin: DVec2,
+    color: Srgba,
+    ctx: &impl CameraProjection,
+) {
+    let limit = if orb.is_hyperbolic() {
+        0.999 * hyperbolic_range_ta(orb.ecc() as f32) as f64
+    } else {
+        PI_64
+    };
+    let exit = orb.ta_at_radius(orb.body.soi).map(|ta| ta.min(limit));
+    let end = exit.unwrap_or(limit);
+    let project = |ta: f64| ctx.w2c(origin + orb.position_at(ta));
+    let points: Vec<Vec2> = adaptive_sample(-end, end, ESCAPE_TRAJECTORY_TOLERANCE, 12, project)
+        .into_iter()
+        .map(project)
+        .collect();
+    canvas.gizmos.linestrip_2d(points, color);
+
+    if let Some(ta) = exit {
+        draw_x(&mut canvas.gizmos, project(ta), 10.0, color);
+    }
+}
+
 fn draw_global_orbit(
     canvas: &mut Canvas,
     orbit: &GlobalOrbit,
@@ -215,11 +238,12 @@ fn draw_orbit_between(
 
 fn draw_planets(
     canvas: &mut Canvas,
+    state: &GameState,
     planet: &PlanetarySystem,
     stamp: Nanotime,
     origin: DVec2,
-    ctx: &OrbitalContext,
 ) {
+    let ctx = &state.orbital_context;
     let a = match ctx.draw_mode {
         DrawMode::Default => 0.1,
         _ => 0.8,
@@ -246,7 +270,7 @@ fn draw_planets(
     canvas.sprite(
         screen_origin,
         0.0,
-        planet.name.clone(),
+        state.planet_sprite(&planet.name),
         ZOrdering::Planet,
         graphics_cast(DVec2::splat(planet.body.radius) * 2.0 * ctx.scale()),
     );
@@ -265,6 +289,30 @@ fn draw_planets(
             gcast(planet.body.soi * ctx.scale()),
             GRAY.with_alpha(a),
         );
+    } else if ctx.draw_mode == DrawMode::SphereOfInfluence {
+        let soi = gcast(planet.body.soi * ctx.scale());
+        canvas.painter.reset();
+        canvas
+            .painter
+            .set_translation(screen_origin.extend(ZOrdering::Orbit.as_f32()));
+        canvas.painter.hollow = false;
+        for s in linspace(0.2, 1.0, 8) {
+            canvas.painter.set_color(ORANGE.with_alpha(0.025));
+            canvas.painter.circle(soi * s);
+        }
+        draw_circle(
+            &mut canvas.gizmos,
+            screen_origin,
+            soi,
+            ORANGE.with_alpha(0.6),
+        );
+        canvas
+            .text(
+                format!("{} SOI {}", planet.name, distance_str(planet.body.soi)),
+                screen_origin + Vec2::Y * (soi + 16.0),
+                0.7,
+            )
+            .color = ORANGE.with_alpha(0.8);
     } else {
         for (a, ds) in [(1.0, 1.0), (0.3, 0.98), (0.1, 0.95)] {
             draw_circle(
@@ -279,7 +327,7 @@ fn draw_planets(
     for (orbit, pl) in &planet.subsystems {
         if let Some(pv) = orbit.pv(stamp).ok() {
             draw_orbit(canvas, orbit, origin, GRAY.with_alpha(a / 2.0), ctx);
-            draw_planets(canvas, pl, stamp, origin + pv.pos, ctx)
+            draw_planets(canvas, state, pl, stamp, origin + pv.pos)
         }
     }
 }
@@ -400,6 +448,7 @@ pub fn draw_vehicle(
             ZOrdering::Vehicle,
             vehicle.aabb().span * scale,
         );
+        draw_cargo_fill(canvas, vehicle, pos, scale, angle);
     }
 
     if thrusters {
@@ -420,6 +469,33 @@ pub fn draw_vehicle(
     }
 }
 
+/// Shades the filled part of each cargo container, from the bottom of
+/// the container in the vehicle's body frame.
+fn draw_cargo_fill(canvas: &mut Canvas, vehicle: &Vehicle, pos: Vec2, scale: f32, angle: f32) {
+    for (_, part) in vehicle.parts() {
+        let Some((cargo, data)) = part.as_cargo() else {
+            continue;
+        };
+        let pct = cargo.percent_filled(data) as f32;
+        if pct <= 0.0 {
+            continue;
+        }
+        let dims = part.dims_meters();
+        let fill = Vec2::new(dims.x, dims.y * pct);
+        let center = part.center_meters() - Vec2::Y * (dims.y - fill.y) / 2.0;
+        let color = Srgba::from_f32_array(diagram_color(&part.prototype()));
+        canvas
+            .sprite(
+                pos + rotate(center, angle) * scale,
+                angle,
+                "error",
+                ZOrdering::CargoFill,
+                fill * scale,
+            )
+            .set_color(color.with_alpha(0.5));
+    }
+}
+
 fn draw_prograde_marker(gizmos: &mut Gizmos, p: Vec2, size: f32, color: Srgba) {
     let mut draw_notch = |a: f32| {
         let start = p + rotate(Vec2::X * 0.5 * size, a);
@@ -782,9 +858,12 @@ fn draw_orbiter(canvas: &mut Canvas, state: &GameState, id: EntityId) -> Option<
     // }
 
     if is_thrusting {
-        draw_diamond(&mut canvas.gizmos, screen_pos, 28.0, RED);
+        let color = state.settings.color_palette.severity(Severity::Danger);
+        draw_diamond(&mut canvas.gizmos, screen_pos, 28.0, color);
+        draw_diamond(&mut canvas.gizmos, screen_pos, 36.0, color);
     } else if !sv.can_be_on_rails() && blinking {
-        draw_diamond(&mut canvas.gizmos, screen_pos, 28.0, TEAL);
+        let color = state.settings.color_palette.severity(Severity::Info);
+        draw_diamond(&mut canvas.gizmos, screen_pos, 28.0, color);
     }
 
     let show_orbits = match ctx.show_orbits {
@@ -805,14 +884,23 @@ fn draw_orbiter(canvas: &mut Canvas, state: &GameState, id: EntityId) -> Option<
         );
     }
 
+    // the constellations draw mode colors orbits by group
+    let constellation = match ctx.draw_mode {
+        DrawMode::Constellations => state.universe.group_membership(&id),
+        _ => None,
+    };
+
+    let palette = state.settings.color_palette;
     let color = if !show_orbits {
         return None;
     } else if piloting {
-        ORANGE
+        palette.piloting()
     } else if targeting {
-        TEAL
+        palette.targeting()
     } else if tracked {
-        PURPLE
+        palette.tracked()
+    } else if let Some(gid) = constellation {
+        state.group_color(gid).with_alpha(0.5).into()
     } else {
         GRAY.with_alpha(0.3)
     };
@@ -826,11 +914,577 @@ fn draw_orbiter(canvas: &mut Canvas, state: &GameState, id: EntityId) -> Option<
     Some(())
 }
 
-fn draw_scenario(canvas: &mut Canvas, state: &GameState) {
+fn draw_soi_overlay(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let ctx = &state.orbital_context;
+    if ctx.draw_mode != DrawMode::SphereOfInfluence {
+        return None;
+    }
+
+    let stamp = state.universe.stamp();
+    let sv = state.universe.surface_vehicles.get(&state.piloting()?)?;
+    let parent = state.universe.lup_planet(sv.parent())?;
+    let center = ctx.w2c(parent.pv().pos);
+    let soi = gcast(parent.body()?.soi * ctx.scale());
+
+    canvas.painter.reset();
+    canvas
+        .painter
+        .set_translation(center.extend(ZOrdering::Planet.as_f32()));
+    canvas.painter.hollow = true;
+    canvas.painter.thickness = 4.0;
+    canvas.painter.set_color(TEAL);
+    canvas.painter.circle(soi);
+
+    let (t, event, GlobalOrbit(beyond, orbit)) = ctx.soi_crossing?.next?;
+    let (_, parent_pv, _, _) = state.universe.planets.lookup(beyond, stamp)?;
+    let p = ctx.w2c(orbit.pv(t).ok()?.pos + parent_pv.pos);
+
+    let label = match event {
+        EventType::Escape(_) => "SOI exit",
+        _ => "SOI entry",
+    };
+
+    draw_x(&mut canvas.gizmos, p, 14.0, TEAL);
+    draw_circle(&mut canvas.gizmos, p, 10.0, TEAL.with_alpha(0.5));
+    canvas
+        .text(
+            format!("{}\n{}", label, t.to_date()),
+            p + Vec2::new(20.0, 0.0),
+            0.7,
+        )
+        .anchor_left()
+        .color = TEAL;
+
+    Some(())
+}
+
+/// Draws the arrival and departure asymptotes of a hyperbolic orbit, with
+/// the hyperbolic excess speed and, for bodies which themselves orbit
+/// something, whether the escape is prograde or retrograde relative to
+/// the body's motion.
+fn draw_asymptotes(
+    canvas: &mut Canvas,
+    state: &GameState,
+    orbit: &GlobalOrbit,
+    color: Srgba,
+) -> Option<()> {
+    let ctx = &state.orbital_context;
+    let stamp = state.universe.stamp();
+    let GlobalOrbit(parent, orbit) = orbit;
+    let vinf = orbit.v_infinity()?;
+    let (arriving, departing) = orbit.asymptote_directions()?;
+    let origin = state.universe.lup_planet(*parent)?.pv().pos;
+    let length = orbit.body.soi;
+    let center = origin + orbit.center();
+
+    let parent_vel = state
+        .universe
+        .planets
+        .orbit_of(*parent)
+        .and_then(|o| o.1.pv(stamp).ok())
+        .map(|pv| pv.vel);
+
+    let c = ctx.w2c(center);
+    let a = ctx.w2c(center - arriving * length);
+    let d = ctx.w2c(center + departing * length);
+    canvas.gizmos.line_2d(c, a, color.with_alpha(0.3));
+    canvas.gizmos.line_2d(c, d, color.with_alpha(0.6));
+
+    let speed = format!("Vinf {:0.1} m/s", vinf);
+    canvas
+        .text(format!("Arrival\n{}", speed), a + Vec2::new(20.0, 0.0), 0.6)
+        .anchor_left()
+        .color = color.with_alpha(0.6);
+
+    let escape = match parent_vel {
+        Some(v) => {
+            let angle = escape_angle(departing, v);
+            let dir = if angle.abs() < PI_64 / 2.0 {
+                "Prograde"
+            } else {
+                "Retrograde"
+            };
+            let p = ctx.w2c(origin);
+            let q = ctx.w2c(origin + v.normalize_or_zero() * length * 0.5);
+            canvas.gizmos.line_2d(p, q, GRAY.with_alpha(0.4));
+            format!("\n{} escape ({:0.0} deg)", dir, angle.to_degrees())
+        }
+        None => String::new(),
+    };
+
+    canvas
+        .text(
+            format!("Departure\n{}{}", speed, escape),
+            d + Vec2::new(20.0, 0.0),
+            0.6,
+        )
+        .anchor_left()
+        .color = color;
+
+    Some(())
+}
+
+/// Widest view, in meters, in which landing site markers are drawn.
+pub const SITE_MARKER_MAX_SPAN: f64 = 50_000.0;
+
+fn draw_landing_pads(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let ctx = &state.orbital_context;
+    let meters = camera_span_meters(state.input.screen_bounds.span, ctx);
+    if meters.max_element() > SITE_MARKER_MAX_SPAN {
+        return None;
+    }
+
+    for (pad_id, pad) in &state.universe.landing_pads {
+        let lup = state.universe.lup_planet(pad.planet_id)?;
+        let radius = lup.body()?.radius;
+        let origin = lup.pv().pos;
+        let occupied = state.universe.pad_occupant(*pad_id, None).is_some();
+        let color = if occupied { RED } else { GREEN };
+
+        let [(l, _), (r, _)] = pad.corridor(radius, 0.0);
+        let l = ctx.w2c(origin + l);
+        let r = ctx.w2c(origin + r);
+        canvas.gizmos.line_2d(l, r, color);
+        draw_diamond(
+            &mut canvas.gizmos,
+            (l + r) / 2.0,
+            10.0,
+            color.with_alpha(0.6),
+        );
+        canvas
+            .text(pad.name.clone(), (l + r) / 2.0 + Vec2::Y * 20.0, 0.6)
+            .color = WHITE.with_alpha(0.6);
+
+        draw_site_drones(canvas, ctx, pad, origin, radius);
+        if let Some(terrain) = state.universe.terrain.get(pad_id) {
+            let center = pad.surface_offset(ctx.origin() - origin, radius);
+            let span = meters.max_element();
+            let view = (center - span, center + span);
+            draw_site_terrain(canvas, ctx, terrain, pad, origin, radius, view);
+        }
+
+        // approach corridor for any vehicle on final above this pad
+        let on_final = state.universe.surface_vehicles.values().any(|sv| {
+            let pos = sv.body.pv.pos;
+            let altitude = pos.length() - radius;
+            sv.parent() == pad.planet_id
+                && !sv.is_landed()
+                && altitude < APPROACH_CORRIDOR_HEIGHT
+                && sv.body.pv.vel.dot(pos) < 0.0
+                && pad.surface_offset(pos, radius).abs()
+                    < pad.width / 2.0 + altitude * CORRIDOR_SLOPE
+        });
+
+        if on_final {
+            for (a, b) in pad.corridor(radius, APPROACH_CORRIDOR_HEIGHT) {
+                let a = ctx.w2c(origin + a);
+                let b = ctx.w2c(origin + b);
+                canvas.gizmos.line_2d(a, b, color.with_alpha(0.4));
+            }
+        }
+    }
+
+    Some(())
+}
+
+const APPROACH_CORRIDOR_HEIGHT: f64 = 3_000.0;
+
+/// Draws surface structures, reddening as they take damage. The
+/// structure being inspected is highlighted.
+fn draw_structures(canvas: &mut Canvas, state: &GameState) {
+    let ctx = &state.orbital_context;
+    for (id, s) in &state.universe.structures {
+        let Some(lup) = state.universe.lup_planet(s.planet_id) else {
+            continue;
+        };
+        let Some(body) = lup.body() else {
+            continue;
+        };
+        let origin = lup.pv().pos;
+        let corners = s.corners(body.radius).map(|p| ctx.w2c(origin + p));
+        let color = if ctx.inspected_structure == Some(*id) {
+            TEAL
+        } else {
+            RED.mix(&GRAY, s.health_fraction() as f32).with_alpha(0.7)
+        };
+        canvas.gizmos.linestrip_2d(corners, color);
+    }
+}
+
+/// Draws a site's service drones at work.
+fn draw_site_drones(
+    canvas: &mut Canvas,
+    ctx: &OrbitalContext,
+    pad: &LandingPad,
+    origin: DVec2,
+    radius: f64,
+) {
+    for drone in &pad.drones {
+        let p = origin + pad.from_site_position(drone.pos(), radius);
+        let color = if drone.is_idle() { GRAY } else { ORANGE };
+        let corners = [0.0, 0.5, 1.0, 1.5, 2.0]
+            .map(|a| ctx.w2c(p + rotate_f64(DVec2::X, drone.angle() + a * PI_64)));
+        canvas.gizmos.linestrip_2d(corners, color);
+    }
+}
+
+/// Scorch marks and craters around a landing site, drawing only the
+/// chunks between the given offsets along the surface from the pad, and
+/// the salvage lying there.
+fn draw_site_terrain(
+    canvas: &mut Canvas,
+    ctx: &OrbitalContext,
+    terrain: &SiteTerrain,
+    pad: &LandingPad,
+    origin: DVec2,
+    radius: f64,
+    (from, to): (f64, f64),
+) {
+    let at = |offset: f64| ctx.w2c(origin + pad.from_site_position(DVec2::X * offset, radius));
+    let scale = ctx.scale() as f32;
+    for decal in terrain.decals_between(from, to) {
+        let p = at(decal.offset);
+        let r = decal.radius as f32 * scale;
+        match decal.kind {
+            DecalKind::Scorch => {
+                canvas.circle(p, r, BLACK.with_alpha(0.5));
+            }
+            DecalKind::Crater => {
+                canvas.circle(p, r, BLACK.with_alpha(0.7));
+                draw_circle(&mut canvas.gizmos, p, r, GRAY.with_alpha(0.6));
+            }
+        }
+    }
+    for salvage in terrain.salvage() {
+        draw_square(&mut canvas.gizmos, at(salvage.offset), 4.0, ORANGE);
+    }
+}
+
+/// Draws where the piloted and selected vehicles will end up after their
+/// scheduled maneuver plans, marking each burn still to come.
+fn draw_scheduled_maneuvers(canvas: &mut Canvas, state: &GameState) {
+    let ctx = &state.orbital_context;
+    let color = state.settings.color_palette.tracked();
+    let ids = ctx.selected.iter().copied().chain(state.piloting());
+    for id in ids {
+        let Some(plan) = state.universe.maneuvers.get(id) else {
+            continue;
+        };
+        let Some(parent) = state
+            .universe
+            .surface_vehicles
+            .get(&id)
+            .map(|sv| sv.parent())
+        else {
+            continue;
+        };
+        let go = GlobalOrbit(parent, plan.terminal);
+        draw_global_orbit(canvas, &go, state, color.with_alpha(0.5));
+        let Some(origin) = state.universe.lup_planet(parent).map(|lup| lup.pv().pos) else {
+            continue;
+        };
+        for (t, _) in plan.future_dvs(state.universe.stamp()) {
+            if let Some(pv) = plan.pv(t) {
+                draw_x(&mut canvas.gizmos, ctx.w2c(pv.pos + origin), 12.0, color);
+            }
+        }
+        draw_burn_alignment(canvas, state, id);
+    }
+}
+
+/// The piloted vehicle's maneuver node with its handles, and the orbit
+/// its burn leads to.
+fn draw_maneuver_node(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let ctx = &state.orbital_context;
+    let id = state.piloting()?;
+    let node = ctx.maneuver_nodes.get(&id)?;
+    let handles = ctx.maneuver_node_handles(&state.universe, id)?;
+    let color = state.settings.color_palette.piloting();
+
+    let (_, c) = handles[0];
+    for (handle, p) in handles {
+        match handle {
+            NodeHandle::Time => draw_circle(&mut canvas.gizmos, p, 10.0, color),
+            NodeHandle::Prograde => {
+                canvas.gizmos.line_2d(c, p, GREEN.with_alpha(0.4));
+                draw_prograde_marker(&mut canvas.gizmos, p, 14.0, GREEN);
+            }
+            NodeHandle::Radial => {
+                canvas.gizmos.line_2d(c, p, LIGHT_BLUE.with_alpha(0.4));
+                draw_diamond(&mut canvas.gizmos, p, 16.0, LIGHT_BLUE);
+            }
+        }
+    }
+
+    if let Ok((plan, _)) = state.plan_maneuver_node(id) {
+        let parent = state.universe.surface_vehicles.get(&id)?.parent();
+        let go = GlobalOrbit(parent, plan.terminal);
+        draw_global_orbit(canvas, &go, state, color.with_alpha(0.6));
+    }
+
+    let s = format!(
+        "{:0.1} m/s\n{}",
+        node.magnitude(),
+        node.stamp - state.universe.stamp()
+    );
+    canvas.text(s, c - Vec2::Y * 36.0, 0.6).color = color;
+    Some(())
+}
+
+/// The vehicle's heading beside the heading its next burn needs, and the
+/// angle between them.
+fn draw_burn_alignment(canvas: &mut Canvas, state: &GameState, id: EntityId) -> Option<()> {
+    let alignment = state.universe.burn_alignment(id)?;
+    let palette = state.settings.color_palette;
+    let c = state.orbital_context.w2c(state.universe.pv(id)?.pos);
+    let r = 60.0;
+    let color = palette.severity(if alignment.is_aligned() {
+        Severity::Success
+    } else {
+        Severity::Warning
+    });
+    let current = Vec2::from_angle(alignment.current as f32);
+    let required = Vec2::from_angle(alignment.required as f32);
+    draw_pointing_vector(&mut canvas.gizmos, c, r, current, WHITE.with_alpha(0.6));
+    draw_pointing_vector(&mut canvas.gizmos, c, r, required, color);
+    canvas
+        .gizmos
+        .line_2d(c, c + required * r, color.with_alpha(0.4));
+    let s = format!("{:0.1} deg", alignment.error().to_degrees().abs());
+    canvas.text(s, c + required * r * 1.2, 0.6).color = color;
+    Some(())
+}
+
+/// The orbit the vehicle whose history is shown was on at the scrubbed
+/// time, and where on it the vehicle was.
+fn draw_orbit_history_scrub(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let ctx = &state.orbital_context;
+    let stamp = ctx.history_scrub?;
+    let (_, history) = state.orbit_history_subject()?;
+    let record = history.at(stamp)?;
+    let color = state.settings.color_palette.targeting();
+    draw_global_orbit(canvas, &record.orbit, state, color.with_alpha(0.6));
+    let GlobalOrbit(parent, orbit) = record.orbit;
+    let origin = state.universe.lup_planet(parent)?.pv().pos;
+    let pv = orbit.pv(stamp).ok()?;
+    draw_diamond(&mut canvas.gizmos, ctx.w2c(pv.pos + origin), 12.0, color);
+    Some(())
+}
+
+/// Waypoints as diamonds, highlighting the piloted vehicle's target.
+fn draw_waypoints(canvas: &mut Canvas, state: &GameState) {
+    let ctx = &state.orbital_context;
+    let palette = state.settings.color_palette;
+    let target = state
+        .piloting()
+        .and_then(|p| state.universe.surface_vehicles.get(&p))
+        .and_then(|sv| sv.target());
+    for id in state.universe.waypoints.keys() {
+        let Some(pv) = state.universe.pv(*id) else {
+            continue;
+        };
+        let p = ctx.w2c(pv.pos);
+        if target == Some(*id) {
+            draw_diamond(&mut canvas.gizmos, p, 18.0, palette.targeting());
+        }
+        draw_diamond(&mut canvas.gizmos, p, 12.0, WHITE);
+    }
+}
+
+/// How far ahead falling vehicles are followed to find where they land.
+const IMPACT_HORIZON: Nanotime = Nanotime::millis(30 * 60 * 1000);
+
+/// Marks where each falling piloted, followed or selected vehicle will hit
+/// the ground and when, colored by how hard it will hit.
+fn draw_impact_predictions(canvas: &mut Canvas, state: &GameState) {
+    let ctx = &state.orbital_context;
+    let palette = state.settings.color_palette;
+    let mut ids: Vec<EntityId> = ctx.selected.iter().copied().collect();
+    ids.extend(state.piloting());
+    ids.extend(ctx.following);
+    ids.sort();
+    ids.dedup();
+
+    for id in ids {
+        let Some(sv) = state.universe.surface_vehicles.get(&id) else {
+            continue;
+        };
+        if sv.is_landed() {
+            continue;
+        }
+        let Some((body, pv, _, _)) = state
+            .universe
+            .planets
+            .lookup(sv.parent(), state.universe.stamp())
+        else {
+            continue;
+        };
+        let Some(impact) = predict_impact(sv.body.pv, &body, IMPACT_HORIZON) else {
+            continue;
+        };
+
+        let speed = impact.speed();
+        let severity = if speed <= CRASH_SPEED {
+            Severity::Success
+        } else if speed <= 3.0 * CRASH_SPEED {
+            Severity::Warning
+        } else {
+            Severity::Danger
+        };
+        let color = palette.severity(severity);
+
+        let points: Vec<Vec2> = impact.path.iter().map(|p| ctx.w2c(*p + pv.pos)).collect();
+        canvas.gizmos.linestrip_2d(points, color.with_alpha(0.4));
+
+        let p = ctx.w2c(impact.pos + pv.pos);
+        draw_x(&mut canvas.gizmos, p, 14.0, color);
+        draw_circle(&mut canvas.gizmos, p, 10.0, color.with_alpha(0.5));
+        canvas
+            .text(
+                format!(
+                    "T-{} {:0.1} m/s",
+                    duration_str(impact.time_to_impact),
+                    speed
+                ),
+                p + Vec2::new(16.0, -16.0),
+                0.6,
+            )
+            .anchor_left()
+            .color = color;
+    }
+}
+
+/// Marks where the piloted vehicle's orbit crosses its target's, with the
+/// time to get there, how much later the target arrives, and the delta-v
+/// needed to match its velocity there.
+fn draw_orbit_crossings(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let ctx = &state.orbital_context;
+    let sv = state.universe.surface_vehicles.get(&state.piloting()?)?;
+    let target = state.universe.surface_vehicles.get(&sv.target()?)?;
+    let GlobalOrbit(parent, own) = sv.current_orbit()?;
+    let GlobalOrbit(target_parent, other) = target.current_orbit()?;
+    if parent != target_parent {
+        return None;
+    }
+
     let stamp = state.universe.stamp();
+    let origin = state.universe.lup_planet(parent)?.pv().pos;
+    let color = state.settings.color_palette.targeting();
+    for crossing in orbit_crossings(&own, &other, stamp) {
+        let p = ctx.w2c(crossing.pos + origin);
+        draw_diamond(&mut canvas.gizmos, p, 14.0, color);
+        draw_circle(&mut canvas.gizmos, p, 5.0, color);
+        let dt = crossing.arrival_difference();
+        let sign = if dt < Nanotime::zero() { "-" } else { "+" };
+        canvas
+            .text(
+                format!(
+                    "T-{}\nTarget {}{}\nDV {:0.1} m/s",
+                    duration_str(crossing.own_time - stamp),
+                    sign,
+                    duration_str(dt.abs()),
+                    crossing.dv
+                ),
+                p + Vec2::new(16.0, -16.0),
+                0.6,
+            )
+            .anchor_left()
+            .color = color;
+    }
+    Some(())
+}
+
+/// Fractional errors in burn magnitude drawn in the sensitivity fan.
+const FAN_MAGNITUDE_ERRORS: [f64; 5] = [-0.02, -0.01, 0.0, 0.01, 0.02];
+
+/// Errors in burn timing, in seconds, drawn in the sensitivity fan.
+const FAN_TIMING_ERRORS: [i64; 3] = [-10, 0, 10];
+
+/// Draws trajectories for small errors in the first burn of the planned
+/// maneuver, fading with the size of the error, and marks where each
+/// ends up at the nominal encounter time.
+fn draw_sensitivity_fan(canvas: &mut Canvas, state: &GameState) -> Option<()> {
     let ctx = &state.orbital_context;
+    let (parent, plan) = state.planned_maneuver()?;
+    let origin = state.universe.lup_planet(parent)?.pv().pos;
+    let encounter_time = plan.encounter_time()?;
+    let nominal = plan.encounter()?;
+
+    let max_magnitude = FAN_MAGNITUDE_ERRORS
+        .iter()
+        .fold(0.0, |a: f64, e| a.max(e.abs()));
+    let max_timing = FAN_TIMING_ERRORS.iter().fold(0, |a: i64, e| a.max(e.abs()));
+
+    let errors: Vec<(f64, i64)> = FAN_MAGNITUDE_ERRORS
+        .iter()
+        .flat_map(|m| FAN_TIMING_ERRORS.iter().map(|t| (*m, *t)))
+        .filter(|(m, t)| *m != 0.0 || *t != 0)
+        .collect();
+    let backend = state.universe.propagation;
+    let perturbed = backend.map(&errors, |(m, t)| plan.perturbed(*m, Nanotime::secs(*t)));
+    let (errors, perturbed): (Vec<_>, Vec<_>) = errors
+        .into_iter()
+        .zip(perturbed)
+        .filter_map(|(e, p)| Some((e, p?)))
+        .unzip();
+    let jobs: Vec<SampleJob> = perturbed
+        .iter()
+        .map(|p| SampleJob::new(p.orbit, tspace(p.start, encounter_time, 80)))
+        .collect();
+    let samples = backend.sample(&jobs);
+
+    let mut spread: f64 = 0.0;
+    for (((m, t), p), pvs) in errors.into_iter().zip(&perturbed).zip(samples) {
+        let severity = (m.abs() / max_magnitude).max(t.abs() as f64 / max_timing as f64);
+        let alpha = 0.5 * (1.0 - 0.7 * severity as f32);
+
+        let points: Vec<Vec2> = pvs
+            .into_iter()
+            .flatten()
+            .map(|pv| ctx.w2c(pv.pos + origin))
+            .collect();
+        canvas.gizmos.linestrip_2d(points, YELLOW.with_alpha(alpha));
+
+        if let Some(e) = p.encounter {
+            spread = spread.max(e.pos.distance(nominal.pos));
+            draw_x(
+                &mut canvas.gizmos,
+                ctx.w2c(e.pos + origin),
+                6.0,
+                YELLOW.with_alpha(alpha),
+            );
+        }
+    }
+
+    let center = ctx.w2c(nominal.pos + origin);
+    draw_x(&mut canvas.gizmos, center, 10.0, WHITE);
+    draw_circle(
+        &mut canvas.gizmos,
+        center,
+        gcast(spread * ctx.scale()).max(10.0),
+        YELLOW.with_alpha(0.4),
+    );
+    canvas
+        .text(
+            format!(
+                "Spread {} (±{:.0}% dV, ±{} s)",
+                distance_str(spread),
+                max_magnitude * 100.0,
+                max_timing
+            ),
+            center + Vec2::new(20.0, 20.0),
+            0.6,
+        )
+        .anchor_left()
+        .color = YELLOW;
+
+    Some(())
+}
+
+fn draw_scenario(canvas: &mut Canvas, state: &GameState) {
+    let stamp = state.universe.stamp();
 
-    draw_planets(canvas, &state.universe.planets, stamp, DVec2::ZERO, ctx);
+    draw_planets(canvas, state, &state.universe.planets, stamp, DVec2::ZERO);
 
     let sids = state.universe.surface_vehicles.iter().map(|(id, _)| id);
 
@@ -959,12 +1613,17 @@ pub fn is_blinking(wall_time: Nanotime) -> bool {
 
 pub fn draw_notifications(gizmos: &mut Gizmos, state: &GameState) {
     let ctx = &state.orbital_context;
+    let palette = state.settings.color_palette;
+    let fade = ctx.label_alpha.value();
+    if fade < 0.01 {
+        return;
+    }
 
     for notif in &state.notifications {
         let p = match notif.parent {
             None => return,
-            Some(ObjectId::Orbiter(id)) => match state.universe.pv(id) {
-                Some(pv) => pv.pos + notif.offset + notif.jitter,
+            Some(ObjectId::Orbiter(id)) => match state.display_position(id) {
+                Some(pos) => pos + notif.offset + notif.jitter,
                 None => continue,
             },
             Some(ObjectId::Planet(id)) => match state.universe.lup_planet(id) {
@@ -975,35 +1634,52 @@ pub fn draw_notifications(gizmos: &mut Gizmos, state: &GameState) {
 
         let size = 20.0;
         let s = (state.wall_time - notif.wall_time).to_secs() / notif.duration().to_secs();
-        let a = (1.0 - 2.0 * s).max(0.2);
+        let a = (1.0 - 2.0 * s).max(0.2) * fade;
 
         let p = ctx.w2c(p);
+        let color = palette.severity(notif.kind.severity()).with_alpha(a);
 
+        // critical markers differ in shape as well as color, so they can
+        // be told apart without relying on color vision
         match notif.kind {
             NotificationType::OrbiterCrashed(_) => {
-                draw_diamond(gizmos, p, size, RED.with_alpha(a));
+                draw_diamond(gizmos, p, size, color);
+                draw_x(gizmos, p, size / 2.0, color);
             }
             NotificationType::OrbiterEscaped(_) => {
-                draw_diamond(gizmos, p, size, TEAL.with_alpha(a));
+                draw_diamond(gizmos, p, size, color);
+                draw_circle(gizmos, p, size * 0.75, color);
             }
             NotificationType::NumericalError(_) => {
-                draw_diamond(gizmos, p, size, YELLOW.with_alpha(a));
+                draw_diamond(gizmos, p, size, color);
+                draw_triangle(gizmos, p, size / 4.0, color);
             }
             NotificationType::OrbiterDeleted(_) => {
-                draw_x(gizmos, p, size, RED.with_alpha(a));
+                draw_x(gizmos, p, size, color);
             }
             NotificationType::ManeuverStarted(_) => {
-                draw_diamond(gizmos, p, size, ORANGE.with_alpha(a));
+                draw_diamond(gizmos, p, size, color);
             }
             NotificationType::ManeuverComplete(_) => {
                 // TODO fix circle size
-                // draw_circle(gizmos, p, size / 2.0, GREEN.with_alpha(a));
+                // draw_circle(gizmos, p, size / 2.0, color);
             }
             NotificationType::ManeuverFailed(_) => {
-                draw_square(gizmos, p, size, RED.with_alpha(a));
+                draw_square(gizmos, p, size, color);
+                draw_x(gizmos, p, size, color);
             }
             NotificationType::NotControllable(_) => (),
+            NotificationType::HandedOver(..) => (),
             NotificationType::OrbitChanged(_) => (),
+            NotificationType::Touchdown(_, points)
+            | NotificationType::ApproachScored(_, points) => {
+                draw_square(gizmos, p, size, color);
+                if points == 0 {
+                    draw_x(gizmos, p, size, color);
+                }
+            }
+            NotificationType::AchievementUnlocked(_) => (),
+            NotificationType::AlarmTriggered(_) => (),
             NotificationType::Notice(_) => (),
         }
     }
@@ -1072,7 +1748,10 @@ pub fn draw_ui_layout(canvas: &mut Canvas, state: &GameState) -> Option<()> {
 }
 
 pub fn draw_orbit_spline(canvas: &mut Canvas, state: &GameState) -> Option<()> {
-    if !state.input.is_pressed(KeyCode::KeyP) {
+    if !state
+        .keybindings
+        .is_pressed(KeyAction::ShowSplines, &state.input)
+    {
         return None;
     }
 
@@ -1164,6 +1843,505 @@ fn draw_rendezvous_info(canvas: &mut Canvas, state: &GameState) -> Option<()> {
     Some(())
 }
 
+/// Ghost of a vehicle being dragged from the editor, drawn where it would
+/// be placed if dropped now. Green when it would snap to a surface or
+/// queued orbit, red otherwise.
+pub fn draw_drag_preview(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let dragged = state.dragging.as_ref()?;
+    let cursor = state.input.position(MouseButt::Hover, FrameId::Current)?;
+    let ctx = &state.orbital_context;
+    let target = state.drop_target();
+
+    let (pos, color) = match target.and_then(|t| Some((t, t.position(&state.universe)?))) {
+        Some((t, p)) => {
+            if let DropTarget::Orbit { orbit, .. } = t {
+                let origin = state.universe.pv(orbit.0)?.pos;
+                draw_orbit(canvas, &orbit.1, origin, GREEN.with_alpha(0.8), ctx);
+            }
+            (ctx.w2c(p), GREEN)
+        }
+        None => (cursor, RED),
+    };
+
+    let size = 60.0;
+    let scale = size / 2.0 / dragged.vehicle.bounding_radius().max(1.0) as f32;
+    draw_vehicle(canvas, &dragged.vehicle, pos, scale, PI / 2.0, true, false);
+    draw_circle(&mut canvas.gizmos, pos, size * 0.6, color.with_alpha(0.6));
+
+    let s = match target {
+        Some(t) => format!("{} -> {}", dragged.name, t),
+        None => dragged.name.clone(),
+    };
+    canvas.text(s, pos + Vec2::Y * size * 0.8, 0.7).color = color.with_alpha(0.8);
+    Some(())
+}
+
+/// Highlights the corner of the screen the dragged HUD widget would be
+/// moved to.
+pub fn draw_hud_drag_preview(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let widget = state.hud_drag?;
+    let anchor = state.hud_drop_anchor()?;
+    let cursor = state.input.position(MouseButt::Hover, FrameId::Current)?;
+    let region = anchor.region(state.input.screen_bounds.span);
+    draw_aabb(canvas, region.padded(-10.0), TEAL.with_alpha(0.6));
+    canvas
+        .text(
+            format!("{} -> {:?}", widget, anchor),
+            cursor + Vec2::Y * 30.0,
+            0.7,
+        )
+        .color = TEAL.with_alpha(0.8);
+    Some(())
+}
+
+/// Red border around the screen which pulses faster and brighter as the
+/// piloted vehicle's predicted impact approaches.
+fn draw_collision_vignette(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let urgency = state.collision_urgency()? as f32;
+    let rate = 1.0 + 4.0 * urgency;
+    let phase = (state.wall_time.to_secs() * rate * 2.0 * PI).sin() * 0.5 + 0.5;
+    let alpha = (0.15 + 0.5 * urgency) * (0.4 + 0.6 * phase);
+
+    let bounds = state.input.screen_bounds.with_center(Vec2::ZERO);
+    for i in 0..12 {
+        let inset = i as f32 * 4.0;
+        let aabb = AABB::new(bounds.center, bounds.span - Vec2::splat(inset * 2.0));
+        let falloff = 1.0 - i as f32 / 12.0;
+        draw_aabb(canvas, aabb, RED.with_alpha(alpha * falloff));
+    }
+    Some(())
+}
+
+/// Predicted trajectory of the piloted vehicle under its ascent guidance.
+fn draw_ascent_prediction(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let id = state.is_ascending()?;
+    let profile = state.ascent_profile(id)?;
+    let sv = state.universe.surface_vehicles.get(&id)?;
+    let (body, _, _, _) = state
+        .universe
+        .planets
+        .lookup(sv.parent(), state.universe.stamp())?;
+    let origin = state.universe.pv(sv.parent())?.pos;
+    let max_accel = sv.vehicle.max_forward_thrust() / sv.vehicle.total_mass().to_kg_f64();
+
+    let ctx = &state.orbital_context;
+    let points: Vec<Vec2> = profile
+        .predict(&body, sv.body.pv, max_accel)
+        .into_iter()
+        .map(|p| ctx.w2c(origin + p))
+        .collect();
+    canvas
+        .gizmos
+        .linestrip_2d(points.iter().copied(), YELLOW.with_alpha(0.6));
+
+    let apoapsis = points.last()?;
+    draw_diamond(&mut canvas.gizmos, *apoapsis, 8.0, YELLOW.with_alpha(0.8));
+    Some(())
+}
+
+fn draw_phase_arcs(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let ctx = &state.orbital_context;
+    if !ctx.show_phase_arcs {
+        return None;
+    }
+
+    let (src, dst) = state.phase_orbits()?;
+    let info = state.phase_info()?;
+    let stamp = state.universe.stamp();
+
+    let origin = state.universe.pv(src.0)?.pos;
+    let p1 = src.1.pv(stamp).ok()?.pos;
+    let p2 = dst.1.pv(stamp).ok()?.pos;
+    let dir = if src.1.is_retrograde() { -1.0 } else { 1.0 };
+
+    let c = ctx.w2c(origin);
+    let a = ctx.w2c(origin + p1);
+    let b = ctx.w2c(origin + p2);
+    let r = c.distance(a).min(c.distance(b)) * 0.6;
+
+    canvas.gizmos.line_2d(c, a, ORANGE.with_alpha(0.5));
+    canvas.gizmos.line_2d(c, b, TEAL.with_alpha(0.5));
+
+    let start: Isometry2d = Isometry2d::new(c, ((a - c).to_angle() - PI / 2.0).into());
+    for (angle, radius, color) in [(info.current, r, WHITE), (info.required, r * 1.1, GREEN)] {
+        canvas
+            .gizmos
+            .arc_2d(start, (dir * angle) as f32, radius, color.with_alpha(0.6))
+            .resolution(100);
+        let tip = c + rotate(a - c, (dir * angle) as f32).normalize_or_zero() * radius;
+        let s = format!("{:0.1} deg", angle.to_degrees());
+        canvas.text(s, tip, 0.6).color = color;
+    }
+
+    Some(())
+}
+
+/// Geometric construction of the piloted or followed vehicle's orbit: its
+/// foci, axes and eccentricity vector, and the true, eccentric and mean
+/// anomalies of the vehicle as they advance.
+fn draw_conic_construction(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let ctx = &state.orbital_context;
+    if !ctx.show_conic_construction {
+        return None;
+    }
+
+    let id = state.piloting().or(ctx.following)?;
+    let GlobalOrbit(parent, orbit) = state.universe.surface_vehicles.get(&id)?.current_orbit()?;
+    let origin = state.universe.lup_planet(parent)?.pv().pos;
+    let stamp = state.universe.stamp();
+    let anomalies = orbit.anomalies_at(stamp)?;
+    let pos = orbit.position_at(anomalies.true_anomaly);
+
+    let w = |p: DVec2| ctx.w2c(origin + p);
+    let a = orbit.semi_major_axis.abs();
+    let e = orbit.ecc();
+    let u = DVec2::from_angle(orbit.arg_periapsis);
+    let v = if orbit.is_retrograde() {
+        -u.perp()
+    } else {
+        u.perp()
+    };
+    let dir = if orbit.is_retrograde() { -1.0 } else { 1.0 };
+    let label = |canvas: &mut Canvas, s: String, p: Vec2, color: Srgba| {
+        canvas.text(s, p + Vec2::Y * 16.0, 0.6).color = color;
+    };
+
+    let focus = w(DVec2::ZERO);
+    draw_x(&mut canvas.gizmos, focus, 12.0, WHITE.with_alpha(0.8));
+
+    // the eccentricity vector, drawn a semi-major axis long so that it
+    // spans the distance between the center and each focus
+    let tip = w(orbit.eccentricity_vector() * a);
+    canvas.gizmos.line_2d(focus, tip, RED.with_alpha(0.8));
+    draw_triangle(&mut canvas.gizmos, tip, 5.0, RED.with_alpha(0.8));
+    label(canvas, format!("e = {:0.3}", e), tip, RED);
+
+    // true anomaly, swept at the focus from periapsis to the vehicle
+    let r = focus
+        .distance(w(pos))
+        .min(focus.distance(w(orbit.periapsis())))
+        * 0.3;
+    let start = Isometry2d::new(focus, (u.to_angle() as f32 - PI / 2.0).into());
+    let ta = anomalies.true_anomaly;
+    canvas
+        .gizmos
+        .arc_2d(start, (dir * ta) as f32, r, WHITE.with_alpha(0.6))
+        .resolution(100);
+    canvas.gizmos.line_2d(focus, w(pos), WHITE.with_alpha(0.4));
+    canvas
+        .gizmos
+        .line_2d(focus, w(orbit.periapsis()), WHITE.with_alpha(0.4));
+    let tip = focus + rotate(Vec2::X, u.to_angle() as f32 + (dir * ta / 2.0) as f32) * r;
+    label(
+        canvas,
+        format!("True {:0.1} deg", ta.to_degrees()),
+        tip,
+        WHITE,
+    );
+
+    if orbit.is_hyperbolic() || e >= 1.0 {
+        return Some(());
+    }
+
+    let center = orbit.center();
+    let b = orbit.semi_minor_axis();
+    let c = w(center);
+
+    draw_x(
+        &mut canvas.gizmos,
+        w(center * 2.0),
+        12.0,
+        WHITE.with_alpha(0.5),
+    );
+    draw_circle(&mut canvas.gizmos, c, 3.0, WHITE.with_alpha(0.5));
+
+    canvas.gizmos.line_2d(
+        w(orbit.periapsis()),
+        w(orbit.apoapsis()),
+        TEAL.with_alpha(0.5),
+    );
+    label(
+        canvas,
+        format!("a = {}", distance_str(a)),
+        w(center + u * a / 2.0),
+        TEAL,
+    );
+    canvas
+        .gizmos
+        .line_2d(w(center - v * b), w(center + v * b), ORANGE.with_alpha(0.5));
+    label(
+        canvas,
+        format!("b = {}", distance_str(b)),
+        w(center + v * b / 2.0),
+        ORANGE,
+    );
+
+    // auxiliary circle, on which the eccentric anomaly is measured; the
+    // vehicle lies straight across from its point on the circle, along
+    // the minor axis direction
+    draw_circle(
+        &mut canvas.gizmos,
+        c,
+        (a * ctx.scale()) as f32,
+        GRAY.with_alpha(0.3),
+    );
+    let on_circle = |angle: f64| center + (u * angle.cos() + v * angle.sin()) * a;
+
+    let ea = on_circle(anomalies.eccentric);
+    canvas.gizmos.line_2d(c, w(ea), YELLOW.with_alpha(0.6));
+    canvas.gizmos.line_2d(w(ea), w(pos), YELLOW.with_alpha(0.3));
+    draw_circle(&mut canvas.gizmos, w(ea), 4.0, YELLOW.with_alpha(0.8));
+    label(
+        canvas,
+        format!("Eccentric {:0.1} deg", anomalies.eccentric.to_degrees()),
+        w(ea),
+        YELLOW,
+    );
+
+    // where the vehicle would be if it moved around the circle at a
+    // constant rate
+    let ma = on_circle(anomalies.mean);
+    canvas.gizmos.line_2d(c, w(ma), GREEN.with_alpha(0.6));
+    draw_diamond(&mut canvas.gizmos, w(ma), 8.0, GREEN.with_alpha(0.8));
+    label(
+        canvas,
+        format!(
+            "Mean {:0.1} deg",
+            wrap_0_2pi_f64(anomalies.mean).to_degrees()
+        ),
+        w(ma),
+        GREEN,
+    );
+
+    Some(())
+}
+
+/// Holding points and approach paths around selected or followed vehicles
+/// which have docking traffic, with each queued vehicle tied to the point
+/// it's steering for.
+fn draw_docking_queues(canvas: &mut Canvas, state: &GameState) {
+    let ctx = &state.orbital_context;
+    for queue in state.universe.docking.values() {
+        if !ctx.selected.contains(&queue.target) && ctx.following != Some(queue.target) {
+            continue;
+        }
+        let Some(target) = state.universe.surface_vehicles.get(&queue.target) else {
+            continue;
+        };
+        let Some(origin) = state.universe.pv(queue.target).map(|pv| pv.pos) else {
+            continue;
+        };
+        for request in queue.requests() {
+            let Some((offset, _)) = queue.guidance(request.vehicle, target.body.angle) else {
+                continue;
+            };
+            let Some(pv) = state.universe.pv(request.vehicle) else {
+                continue;
+            };
+            let color = match request.phase {
+                DockingPhase::Cleared(_) => GREEN,
+                DockingPhase::Holding => YELLOW,
+            };
+            let p = ctx.w2c(origin + offset);
+            draw_diamond(&mut canvas.gizmos, p, 10.0, color.with_alpha(0.6));
+            canvas
+                .gizmos
+                .line_2d(ctx.w2c(pv.pos), p, color.with_alpha(0.2));
+        }
+    }
+}
+
+/// The capture range of the pilot's target's docking port, once the
+/// pilot is close enough to care, lit up while it holds the pilot.
+fn draw_capture_range(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let ctx = &state.orbital_context;
+    let pilot = state.piloting()?;
+    let target_id = state.universe.surface_vehicles.get(&pilot)?.target()?;
+    let target = state.universe.surface_vehicles.get(&target_id)?;
+    if !target.capture_assist {
+        return None;
+    }
+    let origin = state.universe.pv(target_id)?.pos;
+    let port = origin + rotate_f64(DVec2::X, target.body.angle) * DOCKING_CONTACT_DISTANCE / 2.0;
+    if state.universe.pv(pilot)?.pos.distance(port) > 3.0 * CAPTURE_RANGE {
+        return None;
+    }
+    let color = if state.universe.captures.get(&pilot) == Some(&target_id) {
+        TEAL
+    } else {
+        GRAY.with_alpha(0.5)
+    };
+    let radius = (CAPTURE_RANGE * ctx.scale()) as f32;
+    draw_circle(&mut canvas.gizmos, ctx.w2c(port), radius, color);
+    Some(())
+}
+
+fn draw_rotating_frame_view(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    if !state.orbital_context.rotating_frame {
+        return None;
+    }
+
+    let pilot = state.piloting()?;
+    let sv = state.universe.surface_vehicles.get(&pilot)?;
+    let target = sv.target()?;
+    let po = sv.current_orbit()?;
+    let to = state
+        .universe
+        .surface_vehicles
+        .get(&target)?
+        .current_orbit()?;
+
+    if po.0 != to.0 {
+        return None;
+    }
+
+    const SHORT_PREDICTION: Nanotime = Nanotime::mins(5);
+
+    let stamp = state.universe.stamp();
+    let frame = LvlhFrame::new(to.1.pv(stamp).ok()?)?;
+    let ego = frame.to_frame(po.1.pv(stamp).ok()?);
+    let period = to.1.period_or(Nanotime::hours(2));
+    let traj = relative_trajectory(&po.1, &to.1, stamp, stamp + period, 200);
+    let short = relative_trajectory(&po.1, &to.1, stamp, stamp + SHORT_PREDICTION, 30);
+    let history: Vec<DVec2> = state
+        .orbital_context
+        .relative_track
+        .points(pilot, target)
+        .collect();
+
+    let extent = traj
+        .iter()
+        .chain(history.iter())
+        .chain([ego.pos].iter())
+        .map(|p| p.abs().max_element())
+        .fold(50.0, f64::max);
+
+    let window_dims = state.input.screen_bounds.span;
+    let r = window_dims.y * 0.2;
+    let center = Vec2::new(
+        -window_dims.x / 2.0 + r * 1.2,
+        -window_dims.y / 2.0 + r * 1.2,
+    );
+    let scale = r as f64 * 0.9 / extent;
+    let to_screen = |p: DVec2| center + graphics_cast(p * scale);
+
+    canvas.rect(
+        AABB::new(center, Vec2::splat(r * 2.0)),
+        ZOrdering::Shipscope,
+        BLACK.with_alpha(0.7),
+    );
+
+    canvas.gizmos.line_2d(
+        center - Vec2::X * r,
+        center + Vec2::X * r,
+        GRAY.with_alpha(0.4),
+    );
+    canvas.gizmos.line_2d(
+        center - Vec2::Y * r,
+        center + Vec2::Y * r,
+        GRAY.with_alpha(0.4),
+    );
+    canvas
+        .text("V-BAR", center + Vec2::new(r - 4.0, 12.0), 0.5)
+        .anchor_right()
+        .color = GRAY;
+    canvas
+        .text("R-BAR", center + Vec2::new(6.0, r - 12.0), 0.5)
+        .anchor_left()
+        .color = GRAY;
+
+    let range = ego.pos.length();
+    let rate = range_rate(ego);
+    let gate = approach_gate(range);
+    for (gate_range, limit) in APPROACH_GATES {
+        let radius = (gate_range * scale) as f32;
+        if radius > r || radius < 3.0 {
+            continue;
+        }
+        let color = match gate {
+            Some((g, _)) if g == gate_range && -rate > limit => RED,
+            Some((g, _)) if g == gate_range => GREEN,
+            _ => GRAY,
+        };
+        draw_circle(&mut canvas.gizmos, center, radius, color.with_alpha(0.3));
+        canvas
+            .text(
+                velocity_str(limit),
+                center + rotate(Vec2::X, PI / 4.0) * radius,
+                0.4,
+            )
+            .color = color.with_alpha(0.6);
+    }
+
+    let history: Vec<Vec2> = history.into_iter().map(to_screen).collect();
+    canvas.gizmos.linestrip_2d(history, WHITE.with_alpha(0.4));
+
+    let traj: Vec<Vec2> = traj.into_iter().map(to_screen).collect();
+    canvas.gizmos.linestrip_2d(traj, ORANGE.with_alpha(0.2));
+
+    let short: Vec<Vec2> = short.into_iter().map(to_screen).collect();
+    if let Some(end) = short.last() {
+        draw_circle(&mut canvas.gizmos, *end, 3.0, ORANGE);
+    }
+    canvas.gizmos.linestrip_2d(short, ORANGE.with_alpha(0.9));
+
+    // everything else in the target's SOI, with those beyond the edge of
+    // the view pinned to it in their direction
+    for (id, other) in &state.universe.surface_vehicles {
+        if *id == pilot || *id == target || other.parent() != to.0 {
+            continue;
+        }
+        let Some(rel) = other
+            .current_orbit()
+            .and_then(|GlobalOrbit(_, o)| o.pv(stamp).ok())
+            .map(|pv| frame.to_frame(pv))
+        else {
+            continue;
+        };
+        if rel.pos.abs().max_element() > extent {
+            let edge = rel.pos * extent / rel.pos.abs().max_element();
+            draw_circle(&mut canvas.gizmos, to_screen(edge), 2.0, GRAY);
+            continue;
+        }
+        let p = to_screen(rel.pos);
+        draw_x(&mut canvas.gizmos, p, 6.0, GRAY);
+        canvas
+            .gizmos
+            .line_2d(p, to_screen(rel.pos + rel.vel * 30.0), GRAY.with_alpha(0.4));
+        canvas
+            .text(state.callsign(*id), p + Vec2::new(8.0, 0.0), 0.4)
+            .anchor_left()
+            .color = GRAY;
+    }
+
+    draw_circle(&mut canvas.gizmos, center, 8.0, TEAL);
+    let p = to_screen(ego.pos);
+    draw_x(&mut canvas.gizmos, p, 10.0, ORANGE);
+    canvas.gizmos.line_2d(
+        p,
+        to_screen(ego.pos + ego.vel * 30.0),
+        ORANGE.with_alpha(0.6),
+    );
+
+    canvas
+        .text(
+            format!(
+                "LVLH +/- {}\nRNG {}\nRNG RATE {}\nRVEL {}\nGATE {}",
+                distance_str(extent),
+                distance_str(range),
+                velocity_str(rate),
+                velocity_str(ego.vel.length()),
+                gate.map(|(_, limit)| velocity_str(limit))
+                    .unwrap_or("--".to_string()),
+            ),
+            center + Vec2::new(-r, -r - 64.0),
+            0.6,
+        )
+        .anchor_left();
+
+    Some(())
+}
+
 pub fn draw_bezier(gizmos: &mut Gizmos, bezier: &Bezier, color: Srgba) {
     let points: Vec<_> = linspace(0.0, 1.0, 20)
         .into_iter()
@@ -1409,11 +2587,92 @@ pub fn circle_entity(
     }
 }
 
+/// Chevrons at the edge of the screen pointing towards the piloted vehicle
+/// and its target when they're out of view. Their distance readouts are
+/// buttons in the orbital scene's UI.
+fn draw_offscreen_markers(canvas: &mut Canvas, state: &GameState) {
+    for m in offscreen_markers(state) {
+        let dir = Vec2::from_angle(m.angle);
+        for (tip, size) in [(m.pos, 16.0), (m.pos - dir * 9.0, 12.0)] {
+            let back = tip - dir * size;
+            let side = dir.perp() * size * 0.6;
+            canvas.gizmos.line_2d(tip, back + side, m.color);
+            canvas.gizmos.line_2d(tip, back - side, m.color);
+        }
+    }
+}
+
+fn draw_annotations(canvas: &mut Canvas, state: &GameState) {
+    let ctx = &state.orbital_context;
+    let alpha = ctx.label_alpha.value();
+    if alpha < 0.01 {
+        return;
+    }
+    let color = YELLOW.with_alpha(0.6 * alpha);
+    for a in state.annotations.iter().filter(|a| a.visible) {
+        let Some(p) = a.points_with(|id| state.display_position(id)) else {
+            continue;
+        };
+        let p: Vec<Vec2> = p.into_iter().map(|p| ctx.w2c(p)).collect();
+        for q in &p {
+            draw_x(&mut canvas.gizmos, *q, 7.0, color);
+        }
+        match a.kind {
+            AnnotationKind::Distance(..) => {
+                canvas.gizmos.line_2d(p[0], p[1], color);
+            }
+            AnnotationKind::Angle { .. } => {
+                let (c, a, b) = (p[0], p[1], p[2]);
+                canvas.gizmos.line_2d(c, a, color);
+                canvas.gizmos.line_2d(c, b, color);
+                let angle = (a - c).angle_to(b - c);
+                let r = (c.distance(a) * 0.75).min(c.distance(b));
+                let iso = Isometry2d::new(c, ((a - c).to_angle() - PI / 2.0).into());
+                canvas
+                    .gizmos
+                    .arc_2d(iso, angle, r, YELLOW.with_alpha(0.4 * alpha))
+                    .resolution(100);
+            }
+        }
+    }
+}
+
+/// Trail of the mouse gesture being drawn, labeled with the strokes so far
+/// and what they'll do.
+fn draw_mouse_gesture(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+    let trail = state.gestures.trail();
+    let last = *trail.last()?;
+    if trail.len() < 2 {
+        return None;
+    }
+    canvas
+        .gizmos
+        .linestrip_2d(trail.iter().copied(), TEAL.with_alpha(0.7));
+    let strokes = state.gestures.strokes();
+    if strokes.is_empty() {
+        return None;
+    }
+    let action = state
+        .gesture_map
+        .get(strokes)
+        .map(|a| a.description())
+        .unwrap_or("?");
+    canvas
+        .text(
+            format!("{} {}", strokes, action),
+            last + Vec2::new(20.0, 20.0),
+            0.6,
+        )
+        .anchor_left()
+        .color = TEAL;
+    Some(())
+}
+
 pub fn draw_orbital_view(canvas: &mut Canvas, state: &GameState) {
     let ctx = &state.orbital_context;
 
     for button in &state.buttons {
-        draw_button(canvas, button);
+        draw_button(canvas, button, state.wall_time);
     }
 
     draw_camera_info(canvas, ctx, state.input.screen_bounds.span);
@@ -1422,7 +2681,12 @@ pub fn draw_orbital_view(canvas: &mut Canvas, state: &GameState) {
         draw_transforms(canvas, &ctx.camera, &state.universe);
     }
 
-    for (p, c, r, _) in &state.starfield {
+    let starfield = if state.degradation.is_shed(CostlyVisual::Starfield) {
+        &[][..]
+    } else {
+        &state.starfield[..]
+    };
+    for (p, c, r, _) in starfield {
         if p.x <= 0.0 {
             continue;
         }
@@ -1440,7 +2704,18 @@ pub fn draw_orbital_view(canvas: &mut Canvas, state: &GameState) {
 
     draw_rendezvous_info(canvas, state);
 
+    draw_rotating_frame_view(canvas, state);
+
+    draw_phase_arcs(canvas, state);
+    draw_conic_construction(canvas, state);
+    draw_docking_queues(canvas, state);
+    draw_capture_range(canvas, state);
+    draw_structures(canvas, state);
+    draw_ascent_prediction(canvas, state);
+    draw_collision_vignette(canvas, state);
+
     draw_orbit_spline(canvas, state);
+    draw_mouse_gesture(canvas, state);
 
     if let Some((m1, m2, corner)) = state.measuring_tape() {
         let m1 = ctx.w2c(m1);
@@ -1476,10 +2751,18 @@ pub fn draw_orbital_view(canvas: &mut Canvas, state: &GameState) {
             .resolution(100);
     }
 
+    draw_annotations(canvas, state);
+
     for orbit in &ctx.queued_orbits {
         draw_global_orbit(canvas, orbit, &state, RED);
     }
 
+    for (id, _) in state.paused_actions.iter() {
+        if let Some(pv) = state.universe.pv(*id) {
+            draw_diamond(&mut canvas.gizmos, ctx.w2c(pv.pos), 24.0, YELLOW);
+        }
+    }
+
     circle_entity(canvas, ctx.hovered_entity, ctx, &state.universe, GRAY);
     circle_entity(canvas, ctx.piloting, ctx, &state.universe, ORANGE);
 
@@ -1519,25 +2802,51 @@ pub fn draw_orbital_view(canvas: &mut Canvas, state: &GameState) {
         };
 
         draw_with_offset(0.0);
-        let dt = (state.wall_time % Nanotime::secs_f64(anim_dur)).to_secs_f64();
-        for off in linspace(0.0, 1.0, 3) {
-            let off = off as f64;
-            let s = (dt / anim_dur + off) % 1.0;
-            draw_with_offset(-s);
-            draw_with_offset(s);
+        if !state.degradation.is_shed(CostlyVisual::OrbitAnimations) {
+            let dt = (state.wall_time % Nanotime::secs_f64(anim_dur)).to_secs_f64();
+            for off in linspace(0.0, 1.0, 3) {
+                let off = off as f64;
+                let s = (dt / anim_dur + off) % 1.0;
+                draw_with_offset(-s);
+                draw_with_offset(s);
+            }
         }
     }
 
     if let Some(orbit) = state.cursor_orbit_if_mode() {
         draw_global_orbit(canvas, &orbit, &state, ORANGE);
+        draw_asymptotes(canvas, state, &orbit, ORANGE);
     }
 
     if let Some(orbit) = state.current_orbit() {
         draw_global_orbit(canvas, &orbit, &state, TEAL);
     }
 
+    if let Some(orbit) = state
+        .piloting()
+        .and_then(|id| state.universe.surface_vehicles.get(&id))
+        .and_then(|sv| sv.current_orbit())
+    {
+        draw_asymptotes(canvas, state, &orbit, TEAL);
+    }
+
+    draw_sensitivity_fan(canvas, state);
+
+    draw_scheduled_maneuvers(canvas, state);
+    draw_maneuver_node(canvas, state);
+    draw_orbit_history_scrub(canvas, state);
+    draw_impact_predictions(canvas, state);
+    draw_orbit_crossings(canvas, state);
+    draw_waypoints(canvas, state);
+
+    draw_offscreen_markers(canvas, state);
+
     draw_scenario(canvas, state);
 
+    draw_landing_pads(canvas, state);
+
+    draw_soi_overlay(canvas, state);
+
     draw_x(
         &mut canvas.gizmos,
         state.light_source(),
@@ -1547,21 +2856,25 @@ pub fn draw_orbital_view(canvas: &mut Canvas, state: &GameState) {
 
     draw_notifications(&mut canvas.gizmos, &state);
 
-    draw_thrust_particles(
-        canvas,
-        ctx,
-        &state.universe.thrust_particles,
-        &state.universe,
-    );
+    if !state.degradation.is_shed(CostlyVisual::ThrustParticles) {
+        draw_thrust_particles(
+            canvas,
+            ctx,
+            &state.universe.thrust_particles,
+            &state.universe,
+        );
+    }
 }
 
 pub fn draw_game_state(gizmos: Gizmos, mut state: ResMut<GameState>, painter: ShapePainter) {
+    let span = ProfileSpan::start(ProfileStage::Drawing);
     let mut canvas = Canvas::new(gizmos, painter);
 
     GameState::draw(&mut canvas, &state);
 
     state.text_labels = canvas.text_labels;
     state.sprites = canvas.sprites;
+    state.profiler.finish(span);
 }
 
 pub fn draw_transforms(canvas: &mut Canvas, ctx: &LinearCameraController, universe: &Universe) {
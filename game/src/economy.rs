@@ -0,0 +1,189 @@
+use starling::prelude::*;
+use std::collections::HashSet;
+
+/// Credits per unit of build effort. A vehicle is worth its build cost at
+/// this rate.
+pub const CREDITS_PER_BUILD: f64 = 10.0;
+
+/// Fraction of a vehicle's value charged to insure it for one mission.
+pub const INSURANCE_PREMIUM: f64 = 0.05;
+
+/// Fraction of an insured vehicle's value paid out when it's lost.
+pub const INSURANCE_COVERAGE: f64 = 0.75;
+
+pub const STARTING_FUNDS: f64 = 250_000.0;
+
+pub fn vehicle_value(vehicle: &Vehicle) -> f64 {
+    vehicle.build_cost() as f64 * CREDITS_PER_BUILD
+}
+
+pub fn insurance_premium(value: f64) -> f64 {
+    value * INSURANCE_PREMIUM
+}
+
+pub fn credits_str(credits: f64) -> String {
+    format!("{:0.0} cr", credits)
+}
+
+/// A vehicle which was destroyed, kept so that its design can be rebuilt.
+#[derive(Debug, Clone)]
+pub struct LostVehicle {
+    pub id: EntityId,
+    pub design: Vehicle,
+    pub value: f64,
+    pub lost_at: Nanotime,
+}
+
+#[derive(Debug, Clone)]
+pub struct LedgerEntry {
+    pub stamp: Nanotime,
+    pub amount: f64,
+    pub reason: String,
+}
+
+/// Career mode finances. Losing a vehicle writes off its value, of which
+/// insurance recovers a share if a premium was paid for the mission it
+/// was lost on. Lost designs can be rebuilt at a landing site for their
+/// value.
+#[derive(Debug, Clone)]
+pub struct Ledger {
+    funds: f64,
+    insured: HashSet<EntityId>,
+    /// Rebuilt vehicles which haven't yet landed on their site's pad to
+    /// be constructed.
+    pub rebuilding: HashSet<EntityId>,
+    pub lost: Vec<LostVehicle>,
+    pub entries: Vec<LedgerEntry>,
+}
+
+impl Ledger {
+    pub fn new(funds: f64) -> Self {
+        Self {
+            funds,
+            insured: HashSet::new(),
+            rebuilding: HashSet::new(),
+            lost: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn funds(&self) -> f64 {
+        self.funds
+    }
+
+    fn transact(&mut self, stamp: Nanotime, amount: f64, reason: String) {
+        self.funds += amount;
+        self.entries.push(LedgerEntry {
+            stamp,
+            amount,
+            reason,
+        });
+    }
+
+    pub fn spend(
+        &mut self,
+        stamp: Nanotime,
+        amount: f64,
+        reason: impl Into<String>,
+    ) -> Result<(), String> {
+        if amount > self.funds {
+            return Err(format!(
+                "Insufficient funds: need {}, have {}",
+                credits_str(amount),
+                credits_str(self.funds)
+            ));
+        }
+        self.transact(stamp, -amount, reason.into());
+        Ok(())
+    }
+
+    pub fn is_insured(&self, id: EntityId) -> bool {
+        self.insured.contains(&id)
+    }
+
+    /// Pays the premium to insure a vehicle of the given value until it
+    /// next lands safely.
+    pub fn insure(&mut self, id: EntityId, value: f64, stamp: Nanotime) -> Result<(), String> {
+        if self.is_insured(id) {
+            return Err(format!("{} is already insured", id));
+        }
+        self.spend(stamp, insurance_premium(value), format!("Insure {}", id))?;
+        self.insured.insert(id);
+        Ok(())
+    }
+
+    /// Ends the vehicle's current mission, and with it any insurance.
+    pub fn end_mission(&mut self, id: EntityId) {
+        self.insured.remove(&id);
+    }
+
+    /// Records the loss of a vehicle of the given value, returning the
+    /// insurance payout.
+    pub fn record_loss(
+        &mut self,
+        id: EntityId,
+        design: Vehicle,
+        value: f64,
+        stamp: Nanotime,
+    ) -> f64 {
+        let payout = if self.insured.remove(&id) {
+            value * INSURANCE_COVERAGE
+        } else {
+            0.0
+        };
+        if payout > 0.0 {
+            self.transact(stamp, payout, format!("Insurance payout for {}", id));
+        }
+        self.rebuilding.remove(&id);
+        self.lost.push(LostVehicle {
+            id,
+            design,
+            value,
+            lost_at: stamp,
+        });
+        payout
+    }
+
+    /// Pays to rebuild a lost vehicle, returning its design.
+    pub fn rebuild(&mut self, index: usize, stamp: Nanotime) -> Result<Vehicle, String> {
+        let lost = self.lost.get(index).ok_or("No such lost vehicle")?;
+        let reason = format!("Rebuild {}", lost.design.title());
+        self.spend(stamp, lost.value, reason)?;
+        Ok(self.lost.remove(index).design)
+    }
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self::new(STARTING_FUNDS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insurance_covers_one_mission() {
+        let mut ledger = Ledger::new(1000.0);
+        let (a, b) = (EntityId(5), EntityId(6));
+        let t = Nanotime::ZERO;
+
+        ledger.insure(a, 2000.0, t).unwrap();
+        ledger.insure(b, 2000.0, t).unwrap();
+        assert!(ledger.insure(a, 2000.0, t).is_err());
+        assert_eq!(ledger.funds(), 800.0);
+
+        // b lands safely, so its coverage lapses before it's lost
+        ledger.end_mission(b);
+        assert_eq!(ledger.record_loss(a, Vehicle::new(), 2000.0, t), 1500.0);
+        assert_eq!(ledger.record_loss(b, Vehicle::new(), 2000.0, t), 0.0);
+        assert_eq!(ledger.funds(), 2300.0);
+        assert_eq!(ledger.lost.len(), 2);
+
+        assert!(ledger.rebuild(0, t).is_ok());
+        assert!(ledger.rebuild(0, t).is_err());
+        assert_eq!(ledger.funds(), 300.0);
+        assert_eq!(ledger.lost.len(), 1);
+    }
+}
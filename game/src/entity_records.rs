@@ -0,0 +1,243 @@
+use starling::prelude::*;
+use std::collections::HashMap;
+
+/// Running tally of one vehicle's life, kept up to date as it flies so
+/// that leaderboards never have to look back through history.
+#[derive(Debug, Clone)]
+pub struct EntityRecord {
+    pub callsign: String,
+    pub first_seen: Nanotime,
+    /// Sim time the vehicle was destroyed, if it has been.
+    pub lost: Option<Nanotime>,
+    /// Angle swept around the current parent body, in radians.
+    swept: f64,
+    pub orbits: u64,
+    pub dv_spent: f64,
+    pub distance: f64,
+    /// Smallest distance ever seen to the vehicle's target, and which
+    /// target that was.
+    pub closest_approach: Option<(f64, EntityId)>,
+    last: Option<(EntityId, DVec2, f64)>,
+}
+
+impl EntityRecord {
+    fn new(callsign: String, stamp: Nanotime) -> Self {
+        Self {
+            callsign,
+            first_seen: stamp,
+            lost: None,
+            swept: 0.0,
+            orbits: 0,
+            dv_spent: 0.0,
+            distance: 0.0,
+            closest_approach: None,
+            last: None,
+        }
+    }
+
+    pub fn age(&self, now: Nanotime) -> Nanotime {
+        self.lost.unwrap_or(now) - self.first_seen
+    }
+
+    /// Accounts for a vehicle's motion since the last update, given its
+    /// parent, position relative to it, remaining delta-v and distance to
+    /// its target.
+    fn update(&mut self, parent: EntityId, pos: DVec2, dv: f64, target: Option<(EntityId, f64)>) {
+        if let Some((last_parent, last_pos, last_dv)) = self.last {
+            if last_parent == parent {
+                self.distance += pos.distance(last_pos);
+                self.swept += last_pos.angle_to(pos);
+                let laps = (self.swept.abs() / (2.0 * PI_64)).floor();
+                if laps >= 1.0 {
+                    self.orbits += laps as u64;
+                    self.swept -= self.swept.signum() * laps * 2.0 * PI_64;
+                }
+            } else {
+                self.swept = 0.0;
+            }
+            if last_dv > dv {
+                self.dv_spent += last_dv - dv;
+            }
+        }
+        self.last = Some((parent, pos, dv));
+
+        if let Some((id, d)) = target {
+            if self.closest_approach.is_none_or(|(c, _)| d < c) {
+                self.closest_approach = Some((d, id));
+            }
+        }
+    }
+}
+
+/// Lifetime records of every vehicle seen this session, plus crash counts
+/// by where they happened: a landing site, or else the body hit.
+#[derive(Debug, Clone, Default)]
+pub struct EntityRecords {
+    records: HashMap<EntityId, EntityRecord>,
+    crashes: HashMap<EntityId, u64>,
+}
+
+/// Entry in one of the leaderboards: who holds it, and the figure they
+/// hold it with, formatted for display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordHolder {
+    pub title: &'static str,
+    pub holder: String,
+    pub value: String,
+}
+
+impl EntityRecords {
+    pub fn get(&self, id: EntityId) -> Option<&EntityRecord> {
+        self.records.get(&id)
+    }
+
+    /// Updates the record of every vehicle in the universe. Costs one
+    /// step per vehicle, however long the session has run.
+    pub fn update(&mut self, universe: &Universe) {
+        let stamp = universe.stamp();
+        for (id, sv) in &universe.surface_vehicles {
+            let record = self
+                .records
+                .entry(*id)
+                .or_insert_with(|| EntityRecord::new(sv.vehicle().name().to_string(), stamp));
+            record.callsign = sv.vehicle().name().to_string();
+            let target = sv
+                .target()
+                .zip(sv.target_relative_pv)
+                .map(|(t, pv)| (t, pv.pos.length()));
+            record.update(
+                sv.parent(),
+                sv.body.pv.pos,
+                sv.vehicle().remaining_dv(),
+                target,
+            );
+        }
+    }
+
+    pub fn on_destroyed(&mut self, id: EntityId, stamp: Nanotime) {
+        if let Some(r) = self.records.get_mut(&id) {
+            r.lost = Some(stamp);
+            r.last = None;
+        }
+    }
+
+    pub fn on_restored(&mut self, id: EntityId) {
+        if let Some(r) = self.records.get_mut(&id) {
+            r.lost = None;
+        }
+    }
+
+    pub fn on_crash(&mut self, site: EntityId) {
+        *self.crashes.entry(site).or_default() += 1;
+    }
+
+    fn best_by<T: PartialOrd>(
+        &self,
+        alive: bool,
+        key: impl Fn(&EntityRecord) -> Option<T>,
+    ) -> Option<(&EntityRecord, T)> {
+        self.records
+            .values()
+            .filter(|r| !alive || r.lost.is_none())
+            .filter_map(|r| Some((r, key(r)?)))
+            .fold(None, |best, (r, k)| match best {
+                Some((_, ref b)) if *b >= k => best,
+                _ => Some((r, k)),
+            })
+    }
+
+    /// The current holder of each record. Sites are named with
+    /// `site_name`.
+    pub fn leaderboard(
+        &self,
+        now: Nanotime,
+        site_name: impl Fn(EntityId) -> String,
+    ) -> Vec<RecordHolder> {
+        let mut ret = Vec::new();
+        let mut push = |title, holder: &EntityRecord, value| {
+            ret.push(RecordHolder {
+                title,
+                holder: holder.callsign.clone(),
+                value,
+            })
+        };
+
+        if let Some((r, age)) = self.best_by(true, |r| Some(r.age(now))) {
+            push("Oldest satellite", r, duration_str(age));
+        }
+        if let Some((r, d)) = self.best_by(false, |r| Some(r.distance)) {
+            push("Most traveled", r, distance_str(d));
+        }
+        if let Some((r, n)) = self.best_by(false, |r| Some(r.orbits)) {
+            push("Most orbits", r, n.to_string());
+        }
+        if let Some((r, dv)) = self.best_by(false, |r| Some(r.dv_spent)) {
+            push("Most delta-v spent", r, format!("{:0.0} m/s", dv));
+        }
+        if let Some((r, d)) = self.best_by(false, |r| r.closest_approach.map(|(d, _)| -d)) {
+            push("Closest approach", r, distance_str(-d));
+        }
+
+        if let Some((site, n)) = self
+            .crashes
+            .iter()
+            .max_by_key(|(id, n)| (**n, std::cmp::Reverse(**id)))
+        {
+            ret.push(RecordHolder {
+                title: "Most crashes",
+                holder: site_name(*site),
+                value: n.to_string(),
+            });
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_track_orbits_dv_and_approaches() {
+        let mut r = EntityRecord::new("Infallible".to_string(), Nanotime::secs(5));
+        let earth = EntityId(0);
+        for i in 0..=25 {
+            let angle = i as f64 * PI_64 / 10.0;
+            let target = Some((EntityId(7), 100.0 - i as f64));
+            r.update(
+                earth,
+                rotate_f64(DVec2::X * 1000.0, angle),
+                500.0 - i as f64,
+                target,
+            );
+        }
+        assert_eq!(r.orbits, 1);
+        assert!((r.dv_spent - 25.0).abs() < 1e-9);
+        assert_eq!(r.closest_approach, Some((75.0, EntityId(7))));
+        assert!(r.distance > 2.0 * PI_64 * 1000.0);
+
+        // changing parent starts the count of laps over
+        r.update(EntityId(1), DVec2::X, 475.0, None);
+        assert_eq!(r.swept, 0.0);
+
+        let mut records = EntityRecords::default();
+        records.records.insert(EntityId(3), r);
+        records.on_crash(EntityId(9));
+        records.on_crash(EntityId(9));
+        records.on_crash(EntityId(8));
+
+        let board = records.leaderboard(Nanotime::secs(65), |id| format!("Site {}", id));
+        assert_eq!(board[0].title, "Oldest satellite");
+        assert_eq!(board[0].value, duration_str(Nanotime::secs(60)));
+        let crashes = board.iter().find(|h| h.title == "Most crashes").unwrap();
+        assert_eq!(crashes.holder, "Site 9");
+        assert_eq!(crashes.value, "2");
+
+        records.on_destroyed(EntityId(3), Nanotime::secs(30));
+        let board = records.leaderboard(Nanotime::secs(65), |id| format!("Site {}", id));
+        assert!(board.iter().all(|h| h.title != "Oldest satellite"));
+        assert!(board
+            .iter()
+            .any(|h| h.title == "Most orbits" && h.value == "1"));
+    }
+}
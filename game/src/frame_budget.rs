@@ -0,0 +1,179 @@
+use crate::scenes::SceneType;
+use enum_iterator::{all, cardinality, Sequence};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of frames averaged before the degradation level is allowed to
+/// change again.
+pub const DEGRADATION_WINDOW: usize = 30;
+
+/// Fraction of the budget the average frame must come in under before a
+/// shed visual is brought back.
+pub const RESTORE_HEADROOM: f64 = 0.7;
+
+/// Expensive visuals which are switched off, in this order, when frames
+/// run over the scene's budget, and switched back on in reverse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Sequence)]
+pub enum CostlyVisual {
+    ThrustParticles,
+    Starfield,
+    OrbitAnimations,
+    Labels,
+}
+
+impl std::fmt::Display for CostlyVisual {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::ThrustParticles => "Thrust particles",
+            Self::Starfield => "Starfield",
+            Self::OrbitAnimations => "Orbit animations",
+            Self::Labels => "Labels",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Longest acceptable frame time for each scene, in milliseconds. Zero
+/// means the scene is never degraded.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FrameBudgets {
+    pub orbital: u32,
+    pub telescope: u32,
+    pub editor: u32,
+    pub stats: u32,
+}
+
+impl Default for FrameBudgets {
+    fn default() -> Self {
+        Self {
+            orbital: 25,
+            telescope: 25,
+            editor: 0,
+            stats: 0,
+        }
+    }
+}
+
+impl FrameBudgets {
+    pub fn get(&self, scene: SceneType) -> Option<Duration> {
+        let ms = match scene {
+            SceneType::Orbital => self.orbital,
+            SceneType::Telescope => self.telescope,
+            SceneType::Editor => self.editor,
+            SceneType::Stats => self.stats,
+            SceneType::Scenario | SceneType::MainMenu => 0,
+        };
+        (ms > 0).then(|| Duration::from_millis(ms as u64))
+    }
+}
+
+/// Tracks recent frame times against the current scene's budget, shedding
+/// one costly visual at a time while frames are slow and restoring them
+/// once there's room to spare.
+#[derive(Debug, Default)]
+pub struct Degradation {
+    level: usize,
+    samples: VecDeque<Duration>,
+}
+
+impl Degradation {
+    pub fn record(&mut self, frame: Duration, budget: Option<Duration>) {
+        let Some(budget) = budget else {
+            self.reset();
+            return;
+        };
+
+        self.samples.push_back(frame);
+        if self.samples.len() < DEGRADATION_WINDOW {
+            return;
+        }
+
+        let average = self.average().unwrap_or_default();
+        if average > budget && self.level < cardinality::<CostlyVisual>() {
+            self.level += 1;
+        } else if average.as_secs_f64() < budget.as_secs_f64() * RESTORE_HEADROOM && self.level > 0
+        {
+            self.level -= 1;
+        } else {
+            self.samples.pop_front();
+            return;
+        }
+        // judge the new level on frames drawn with it
+        self.samples.clear();
+    }
+
+    pub fn reset(&mut self) {
+        self.level = 0;
+        self.samples.clear();
+    }
+
+    /// Number of visuals currently switched off.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let total: Duration = self.samples.iter().sum();
+        Some(total / self.samples.len() as u32)
+    }
+
+    pub fn is_shed(&self, visual: CostlyVisual) -> bool {
+        self.shed().any(|v| v == visual)
+    }
+
+    pub fn shed(&self) -> impl Iterator<Item = CostlyVisual> {
+        all::<CostlyVisual>().take(self.level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visuals_shed_in_order_and_come_back() {
+        let budget = FrameBudgets::default().get(SceneType::Orbital);
+        assert!(budget.is_some());
+        assert!(FrameBudgets::default().get(SceneType::Editor).is_none());
+
+        let mut d = Degradation::default();
+        let slow = Duration::from_millis(40);
+        let fast = Duration::from_millis(5);
+
+        for _ in 0..DEGRADATION_WINDOW - 1 {
+            d.record(slow, budget);
+        }
+        assert_eq!(d.level(), 0);
+        d.record(slow, budget);
+        assert_eq!(d.level(), 1);
+        assert!(d.is_shed(CostlyVisual::ThrustParticles));
+        assert!(!d.is_shed(CostlyVisual::Starfield));
+
+        for _ in 0..DEGRADATION_WINDOW * 10 {
+            d.record(slow, budget);
+        }
+        assert_eq!(d.level(), cardinality::<CostlyVisual>());
+        assert!(d.is_shed(CostlyVisual::Labels));
+
+        // frames just under budget don't count as headroom
+        for _ in 0..DEGRADATION_WINDOW * 2 {
+            d.record(budget.unwrap() - Duration::from_millis(1), budget);
+        }
+        assert_eq!(d.level(), cardinality::<CostlyVisual>());
+
+        for _ in 0..DEGRADATION_WINDOW {
+            d.record(fast, budget);
+        }
+        assert!(!d.is_shed(CostlyVisual::Labels));
+        assert!(d.is_shed(CostlyVisual::OrbitAnimations));
+
+        // scenes without a budget are never degraded
+        d.record(slow, None);
+        assert_eq!(d.level(), 0);
+    }
+}
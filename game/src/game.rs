@@ -12,19 +12,11 @@ use enum_iterator::next_cycle;
 use image::DynamicImage;
 use layout::layout::Tree;
 use starling::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 pub struct GamePlugin;
 
-fn combo_just_pressed(input: &InputState, keys: &[KeyCode]) -> bool {
-    if let Some(l) = keys.last() {
-        keys.iter().all(|k| input.is_pressed(*k)) && input.just_pressed(*l)
-    } else {
-        false
-    }
-}
-
 fn gamepad_usage_system(gamepads: Query<(&Name, &Gamepad)>, mut state: ResMut<GameState>) {
     for (_name, gamepad) in &gamepads {
         for button in gamepad.get_just_pressed() {
@@ -89,12 +81,68 @@ impl Plugin for GamePlugin {
                 on_game_tick,
                 // rendering
                 crate::sounds::sound_system,
+                crate::music::music_system,
             )
                 .chain(),
         );
     }
 }
 
+/// Pixel size of generated planet textures.
+pub const PLANET_TEXTURE_SIZE: u32 = 256;
+
+/// Distance to the pilot's target within which the music treats the
+/// vehicle as on final approach, in meters.
+const MUSIC_APPROACH_DISTANCE: f64 = 2_000.0;
+
+/// Altitude below which a descending vehicle is treated as on final
+/// approach to the surface, in meters.
+const MUSIC_APPROACH_ALTITUDE: f64 = 10_000.0;
+
+/// Clearance above the surface targeted by suggested avoidance burns, in
+/// meters.
+pub const AVOIDANCE_MARGIN: f64 = 5_000.0;
+
+/// Screen distance from the ground, in pixels, within which a new waypoint
+/// is placed on the surface instead of in orbit.
+const WAYPOINT_SNAP_PIXELS: f64 = 50.0;
+
+fn procedural_sprite_name(planet: &str) -> String {
+    format!("{}-procedural", planet)
+}
+
+/// Sprites loaded from the install directory by name, i.e. "cloud" is
+/// loaded from "<install_dir>/cloud.png".
+pub const STATIC_SPRITES: &[&str] = &[
+    "cloud",
+    "diamond",
+    // items
+    "item-bread",
+    "item-corn",
+    "item-h2",
+    "item-ice",
+    "item-methane",
+    "item-o2",
+    "item-potato",
+    "item-wheat",
+    "Earth",
+    "Luna",
+    "Asteroid",
+    "conbot",
+    "low-fuel",
+    "low-fuel-dim",
+    "radar",
+    "radar-dim",
+    "ctrl",
+    "ctrl-dim",
+    "shipscope",
+    "launch-icon",
+    "prograde-icon",
+    "retrograde-icon",
+    "clear-icon",
+    "heading-icon",
+];
+
 #[derive(Component, Debug)]
 pub struct BackgroundCamera;
 
@@ -152,6 +200,27 @@ pub struct GameState {
 
     pub sounds: EnvironmentSounds,
 
+    pub music: MusicManager,
+
+    /// Vehicle files being copied in from elsewhere, a few per frame.
+    pub vehicle_import: Option<VehicleImport>,
+
+    /// Names and colors the player has given to groups.
+    pub group_styles: HashMap<EntityId, GroupStyle>,
+
+    /// Sim time at which labels and markers are placed.
+    pub display_clock: DisplayClock,
+
+    /// Wall time of the newest notification the music has reacted to.
+    last_music_notification: Nanotime,
+
+    /// Autosave being written a little each frame, into the archive it
+    /// updates.
+    autosave: Option<(SaveArchive, IncrementalSave)>,
+
+    /// Wall time the last autosave was started.
+    last_autosave: Nanotime,
+
     /// Contains all states related to window size, mouse clicks and positions,
     /// and button presses and holds.
     pub input: InputState,
@@ -165,6 +234,10 @@ pub struct GameState {
     /// autonomously without any user input with on_sim_tick.
     pub universe: Universe,
 
+    /// Periodic snapshots of the universe, used to rewind the simulation
+    /// by some amount of sim time without reloading.
+    pub checkpoints: CheckpointBuffer,
+
     /// Stores information and provides an API for interacting with the simulation
     /// from the perspective of a global solar/planetary system view.
     ///
@@ -173,13 +246,13 @@ pub struct GameState {
     pub orbital_context: OrbitalContext,
 
     pub telescope_context: TelescopeContext,
+    pub scenario_context: ScenarioEditorContext,
 
     pub editor_context: EditorContext,
 
     /// Wall clock, i.e. time since program began.
     pub wall_time: Nanotime,
 
-    pub physics_duration: Nanotime,
     pub universe_ticks_per_game_tick: SimRate,
     pub paused: bool,
     pub exec_time: std::time::Duration,
@@ -191,6 +264,13 @@ pub struct GameState {
     /// the assets/parts directory
     pub part_database: HashMap<String, PartPrototype>,
 
+    /// Planetary systems by name, loaded from the assets/planets directory.
+    pub planet_catalogs: PlanetCatalogs,
+
+    /// Vehicle files available to build and spawn.
+    pub vehicles: VehicleRegistry,
+    last_vehicle_scan: Nanotime,
+
     pub starfield: Vec<(Vec3, Srgba, f32, f32)>,
 
     pub scene: SceneType,
@@ -203,6 +283,40 @@ pub struct GameState {
 
     pub is_exit_prompt: bool,
 
+    /// Which vehicles are still the player's, and handovers made so far.
+    pub fleet: Fleet,
+
+    /// Handover waiting on confirmation in a dialog.
+    pub pending_handover: Option<Handover>,
+
+    /// Vehicle filters kept by name; pinned ones are smart lists.
+    pub saved_filters: Vec<SavedFilter>,
+
+    /// Set when a save or scenario is loaded. Orbits are normalized once
+    /// every vehicle queued by the load has been built.
+    pub normalize_on_load: bool,
+
+    /// Whether the sim was paused when the pause menu was opened, while
+    /// it's open.
+    pub pause_menu: Option<bool>,
+
+    /// Keyboard shortcuts, which can be remapped at runtime.
+    pub keybindings: Keybindings,
+
+    /// Mouse gestures and what they do, and the one being drawn.
+    pub gesture_map: GestureMap,
+    pub gestures: GestureTracker,
+
+    /// Show the overlay listing the current scene's bindings.
+    pub show_help: bool,
+
+    /// Per-stage frame timings, and whether the overlay showing them is up.
+    pub profiler: Profiler,
+
+    /// Costly visuals currently switched off to stay within the scene's
+    /// frame budget.
+    pub degradation: Degradation,
+
     pub text_labels: Vec<TextLabel>,
     pub sprites: Vec<StaticSpriteDescriptor>,
     pub image_handles: HashMap<String, (Handle<Image>, UVec2)>,
@@ -210,6 +324,68 @@ pub struct GameState {
     pub vehicle_names: Vec<String>,
 
     pub buttons: Vec<ExpandButton>,
+
+    /// Approach scoring against a spawned practice target, if any.
+    pub practice: Option<PracticeSession>,
+
+    /// Lifetime statistics for the current profile.
+    pub stats: StatsStore,
+
+    pub session: SessionSummary,
+
+    /// Lifetime records of each vehicle seen this session.
+    pub records: EntityRecords,
+
+    /// Name of the loadout preset applied to newly spawned vehicles, if
+    /// any. Looked up among the vehicle's own presets, then the built-in
+    /// ones.
+    pub loadout: Option<String>,
+
+    /// Funds, insurance and lost vehicles, used in career mode.
+    pub ledger: Ledger,
+    last_remaining_dv: Option<(EntityId, f64)>,
+    last_stats_save: Nanotime,
+
+    /// Measurements pinned to the world in the orbital view.
+    pub annotations: Vec<Annotation>,
+
+    /// Pinned annotations picked out for bulk actions.
+    pub pinned_selection: ListSelection,
+
+    /// Read-only mode for kiosks and streams, and its camera director.
+    pub spectator: Spectator,
+
+    /// Sharing of the universe with observers on other instances.
+    pub network: NetworkSession,
+
+    /// Pending sim-time alarms.
+    pub alarms: AlarmClock,
+
+    /// Notes left on the mission timeline.
+    pub timeline_notes: TimelineNotes,
+
+    /// Vehicle commands issued while paused, applied on resume.
+    pub paused_actions: PausedActionQueue,
+
+    /// Vehicle being dragged from the editor's gallery into the world.
+    pub dragging: Option<DraggedVehicle>,
+
+    /// Whether HUD widgets can be shown, hidden and dragged around.
+    pub hud_edit: bool,
+
+    /// HUD widget being dragged to a new anchor.
+    pub hud_drag: Option<HudWidget>,
+
+    /// Recent history of the piloted vehicle, for the telemetry widget.
+    pub telemetry: Telemetry,
+
+    /// Sim time at which the piloted vehicle will hit a body, if that's
+    /// within the warning window.
+    pub collision_warning: Option<Nanotime>,
+    last_collision_alert: Nanotime,
+
+    /// Place in the universe's event stream.
+    events: SubscriberId,
 }
 
 fn generate_starfield() -> Vec<(Vec3, Srgba, f32, f32)> {
@@ -233,7 +409,29 @@ fn generate_starfield() -> Vec<(Vec3, Srgba, f32, f32)> {
 
 impl GameState {
     pub fn new(args: ProgramContext) -> Self {
-        let planets = default_example();
+        let planet_catalogs = match load_planet_catalogs_from_dir(&args.planets_dir()) {
+            Ok((catalogs, failures)) => {
+                for e in failures {
+                    error!("Failed to load planets: {e}");
+                }
+                catalogs
+            }
+            Err(e) => {
+                error!("Failed to load planets: {e}");
+                HashMap::new()
+            }
+        };
+
+        let planets = match planet_catalogs.get(DEFAULT_PLANET_SYSTEM) {
+            Some(c) => c.to_system().unwrap_or_else(|e| {
+                error!("Failed to build planetary system: {e}");
+                default_example()
+            }),
+            None => {
+                error!("No planetary system named \"{DEFAULT_PLANET_SYSTEM}\"; using built-in");
+                default_example()
+            }
+        };
 
         let part_database = match load_parts_from_dir(&args.parts_dir()) {
             Ok(d) => d,
@@ -243,6 +441,8 @@ impl GameState {
             }
         };
 
+        let vehicles = VehicleRegistry::new(&args.vehicle_dir(), &part_database);
+
         let settings = match load_settings_from_file(&args.settings_path()) {
             Ok(s) => s,
             Err(e) => {
@@ -251,8 +451,45 @@ impl GameState {
             }
         };
 
-        let mut sounds = EnvironmentSounds::new();
-        sounds.play_loop("building.ogg", 0.1);
+        let stats = match load_stats_from_file(&args.stats_path()) {
+            Ok(s) => s,
+            Err(e) => {
+                info!("No stats loaded for profile \"{}\": {e}", args.profile);
+                StatsStore::default()
+            }
+        };
+
+        let console_history = match load_console_history_from_file(&args.console_history_path()) {
+            Ok(h) => h,
+            Err(e) => {
+                info!(
+                    "No console history loaded for profile \"{}\": {e}",
+                    args.profile
+                );
+                ConsoleHistory::default()
+            }
+        };
+
+        let gesture_map = match load_gesture_map_from_file(&args.gestures_path()) {
+            Ok(m) => m,
+            Err(e) => {
+                info!("Using default mouse gestures: {e}");
+                GestureMap::default()
+            }
+        };
+
+        let saved_filters = match load_saved_filters_from_file(&args.filters_path()) {
+            Ok(f) => f,
+            Err(e) => {
+                info!(
+                    "No saved filters loaded for profile \"{}\": {e}",
+                    args.profile
+                );
+                Vec::new()
+            }
+        };
+
+        let sounds = EnvironmentSounds::new();
 
         let vehicle_names = match load_names_from_file(&args.names_path()) {
             Ok(n) => n,
@@ -266,12 +503,7 @@ impl GameState {
         let w = 60.0;
         let s = w + 10.0;
         for (y, onclick, text, sp) in [
-            (
-                0,
-                OnClick::SetControllerPolicy(VehicleControlPolicy::LaunchToOrbit(450_000.0)),
-                "Launch to Orbit",
-                "launch-icon",
-            ),
+            (0, OnClick::LaunchToOrbit, "Launch to Orbit", "launch-icon"),
             (
                 1,
                 OnClick::SetControllerPolicy(VehicleControlPolicy::BurnPrograde),
@@ -301,21 +533,32 @@ impl GameState {
             buttons.push(ExpandButton::new(text, onclick, p, Vec2::splat(w), sp));
         }
 
+        let mut universe = Universe::new(planets.clone());
+        let events = universe.events.subscribe();
+
         let mut g = GameState {
             render_ticks: 0,
             game_ticks: 0,
             cursor_position: Vec2::ZERO,
             settings,
             sounds,
+            music: MusicManager::new(),
+            vehicle_import: None,
+            group_styles: HashMap::new(),
+            display_clock: DisplayClock::default(),
+            last_music_notification: Nanotime::zero(),
+            autosave: None,
+            last_autosave: Nanotime::zero(),
             input: InputState::default(),
             args: args.clone(),
-            universe: Universe::new(planets.clone()),
-            console: DebugConsole::new(),
+            universe,
+            checkpoints: CheckpointBuffer::new(Nanotime::secs(5), 120),
+            console: DebugConsole::with_history(console_history),
             orbital_context: OrbitalContext::new(EntityId(0)),
             telescope_context: TelescopeContext::new(),
+            scenario_context: ScenarioEditorContext::new(),
             editor_context: EditorContext::new(),
             wall_time: Nanotime::zero(),
-            physics_duration: Nanotime::days(7),
             universe_ticks_per_game_tick: SimRate::RealTime,
             actual_universe_ticks_per_game_tick: 0,
             using_batch_mode: false,
@@ -323,21 +566,67 @@ impl GameState {
             paused: false,
             exec_time: std::time::Duration::new(0, 0),
             part_database,
+            planet_catalogs,
+            vehicles,
+            last_vehicle_scan: Nanotime::zero(),
             starfield: generate_starfield(),
             scene: SceneType::MainMenu,
             current_orbit: None,
             ui: Tree::new(),
             notifications: Vec::new(),
             is_exit_prompt: false,
+            fleet: Fleet::default(),
+            pending_handover: None,
+            saved_filters,
+            normalize_on_load: false,
+            pause_menu: None,
+            keybindings: Keybindings::default(),
+            gesture_map,
+            gestures: GestureTracker::default(),
+            show_help: false,
+            profiler: Profiler::default(),
+            degradation: Degradation::default(),
             text_labels: Vec::new(),
             sprites: Vec::new(),
             image_handles: HashMap::new(),
             vehicle_names,
             buttons,
+            practice: None,
+            stats,
+            session: SessionSummary::default(),
+            records: EntityRecords::default(),
+            loadout: None,
+            ledger: Ledger::default(),
+            last_remaining_dv: None,
+            last_stats_save: Nanotime::ZERO,
+            annotations: Vec::new(),
+            pinned_selection: ListSelection::default(),
+            spectator: Spectator::default(),
+            network: NetworkSession::default(),
+            alarms: AlarmClock::default(),
+            timeline_notes: TimelineNotes::default(),
+            paused_actions: PausedActionQueue::default(),
+            dragging: None,
+            hud_edit: false,
+            hud_drag: None,
+            telemetry: Telemetry::default(),
+            collision_warning: None,
+            last_collision_alert: Nanotime::zero(),
+            events,
         };
 
-        let earth_id = g.universe.lup_planet_by_name("Earth").unwrap();
-        let luna_id = g.universe.lup_planet_by_name("Luna").unwrap();
+        g.universe.recycle_bin.retention =
+            Nanotime::secs(g.settings.deleted_vehicle_retention as i64);
+        g.universe.maneuvers.auto_hold = g.settings.burn_attitude_hold;
+        g.universe.scatter_salvage = g.settings.crash_salvage;
+
+        // the demo fleet only makes sense in the stock system
+        let (Some(earth_id), Some(luna_id)) = (
+            g.universe.lup_planet_by_name("Earth"),
+            g.universe.lup_planet_by_name("Luna"),
+        ) else {
+            return g;
+        };
 
         for model in ["icecream"] {
             if let Some(v) = g.get_vehicle_by_model(model) {
@@ -350,6 +639,15 @@ impl GameState {
             }
         }
 
+        for (i, da) in [0.0, 0.002, 0.004].iter().enumerate() {
+            g.universe.add_landing_pad(LandingPad::new(
+                format!("Luna Pad {}", i + 1),
+                luna_id,
+                PI_64 / 2.0 + da,
+                60.0,
+            ));
+        }
+
         let t = g.universe.stamp();
 
         let get_random_orbit = |pid: EntityId| {
@@ -370,23 +668,122 @@ impl GameState {
             ("bellerophon", luna_id),
         ];
 
-        for (name, parent) in vehicles {
-            let vehicle = g.get_vehicle_by_model(name);
-            let orbit = get_random_orbit(parent);
-            if let (Some(orbit), Some(vehicle)) = (orbit, vehicle) {
-                g.spawn_with_random_perturbance(orbit, vehicle);
+        // background traffic is streamed in over the first few ticks;
+        // see GameState::stream_pending_vehicles
+        let extra = (0..g.settings.background_traffic)
+            .map(|_| vehicles[randint(0, vehicles.len() as i32) as usize]);
+
+        for (name, parent) in vehicles.into_iter().chain(extra) {
+            let orbit = get_random_orbit(parent).and_then(|o| g.random_perturbance(o));
+            if let Some(orbit) = orbit {
+                let id = g.universe.queue_orbital_vehicle(name, orbit);
+                g.universe.traffic.enlist(id, t);
             }
         }
 
         g
     }
 
+    /// Loads the sprite for a part, and unless in low-memory mode, a
+    /// variant for each stage of building it.
+    fn load_part_sprite(
+        &self,
+        name: &str,
+        images: &mut Assets<Image>,
+        handles: &mut HashMap<String, (Handle<Image>, UVec2)>,
+    ) -> bool {
+        let path = self.args.part_sprite_path(name);
+        let Some(img) = crate::generate_ship_sprites::read_image(Path::new(&path)) else {
+            error!("Failed to load sprite for part {}", name);
+            return false;
+        };
+
+        let mut img = Image::from_dynamic(
+            DynamicImage::ImageRgba8(img),
+            true,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        );
+        img.sampler = bevy::image::ImageSampler::nearest();
+        let dims = img.size();
+        let handle = images.add(img.clone());
+        handles.insert(name.to_string(), (handle.clone(), dims));
+
+        if self.settings.low_memory_sprites {
+            return true;
+        }
+
+        for pct in (0..=9).rev() {
+            for w in 0..img.width() {
+                for h in 0..img.height() {
+                    if rand(0.0, 1.0) < 0.5 {
+                        if let Some(pixel) = img.pixel_bytes_mut(UVec3::new(w, h, 0)) {
+                            pixel[3] = pixel[3].min(10);
+                            pixel[2] = 255;
+                        }
+                    }
+                }
+            }
+            let handle = images.add(img.clone());
+            handles.insert(format!("{}-building-{}", name, pct), (handle, dims));
+        }
+        true
+    }
+
+    /// In low-memory mode, loads sprites for any parts drawn last frame
+    /// which haven't been loaded yet. Parts whose sprite can't be read are
+    /// drawn with the error sprite from then on.
+    pub fn load_part_sprites_in_use(&mut self, images: &mut Assets<Image>) {
+        if !self.settings.low_memory_sprites {
+            return;
+        }
+        let mut wanted: Vec<String> = self
+            .sprites
+            .iter()
+            .map(|s| &s.path)
+            .filter(|p| self.part_database.contains_key(*p) && !self.image_handles.contains_key(*p))
+            .cloned()
+            .collect();
+        wanted.sort();
+        wanted.dedup();
+
+        let mut handles = std::mem::take(&mut self.image_handles);
+        for name in wanted {
+            if !self.load_part_sprite(&name, images, &mut handles) {
+                if let Some(error) = handles.get("error").cloned() {
+                    handles.insert(name, error);
+                }
+            }
+        }
+        self.image_handles = handles;
+    }
+
+    /// Sprite and tint to draw a part with, given how far along it is in
+    /// being built. In low-memory mode the finished sprite is tinted in
+    /// place of the baked variants, fading in as it's built.
+    pub fn part_sprite(&self, part: &str, percent_built: f32) -> (String, Srgba) {
+        if percent_built >= 1.0 {
+            (part.to_string(), WHITE)
+        } else if self.settings.low_memory_sprites {
+            let a = 0.15 + 0.5 * percent_built;
+            (part.to_string(), Srgba::new(0.4, 0.6, 1.0, a))
+        } else {
+            let idx = (percent_built * 10.0).floor() as i32;
+            (format!("{}-building-{}", part, idx), WHITE)
+        }
+    }
+
     pub fn load_sprites(&mut self, images: &mut Assets<Image>) {
         let mut handles = HashMap::new();
 
-        for (name, _) in &self.part_database {
-            let path = self.args.part_sprite_path(name);
-            if let Some(img) = crate::generate_ship_sprites::read_image(Path::new(&path)) {
+        if !self.settings.low_memory_sprites {
+            for name in self.part_database.keys() {
+                self.load_part_sprite(name, images, &mut handles);
+            }
+        }
+
+        for name in STATIC_SPRITES.iter().copied() {
+            let path = self.args.install_dir.join(format!("{}.png", name));
+            if let Some(img) = crate::generate_ship_sprites::read_image(&path) {
                 let mut img = Image::from_dynamic(
                     DynamicImage::ImageRgba8(img),
                     true,
@@ -394,57 +791,20 @@ impl GameState {
                 );
                 img.sampler = bevy::image::ImageSampler::nearest();
                 let dims = img.size();
-                let handle = images.add(img.clone());
-                handles.insert(name.to_string(), (handle.clone(), dims));
-
-                for pct in (0..=9).rev() {
-                    for w in 0..img.width() {
-                        for h in 0..img.height() {
-                            if rand(0.0, 1.0) < 0.5 {
-                                if let Some(pixel) = img.pixel_bytes_mut(UVec3::new(w, h, 0)) {
-                                    pixel[3] = pixel[3].min(10);
-                                    pixel[2] = 255;
-                                }
-                            }
-                        }
-                    }
-                    let handle = images.add(img.clone());
-                    handles.insert(format!("{}-building-{}", name, pct), (handle, dims));
-                }
+                let handle = images.add(img);
+                handles.insert(name.into(), (handle, dims));
             } else {
-                error!("Failed to load sprite for part {}", name);
-            }
-        }
-
-        for name in [
-            "cloud",
-            "diamond",
-            // items
-            "item-bread",
-            "item-corn",
-            "item-h2",
-            "item-ice",
-            "item-methane",
-            "item-o2",
-            "item-potato",
-            "item-wheat",
-            "Earth",
-            "Luna",
-            "Asteroid",
-            "conbot",
-            "low-fuel",
-            "low-fuel-dim",
-            "radar",
-            "radar-dim",
-            "ctrl",
-            "ctrl-dim",
-            "shipscope",
-            "launch-icon",
-            "prograde-icon",
-            "retrograde-icon",
-            "clear-icon",
-            "heading-icon",
-        ] {
+                error!("Failed to load sprite: {}", path.display());
+            }
+        }
+
+        let planet_sprites: Vec<_> = self
+            .planet_catalogs
+            .values()
+            .flat_map(|c| c.planets.iter().map(|p| p.sprite().to_string()))
+            .filter(|s| !handles.contains_key(s))
+            .collect();
+        for name in planet_sprites {
             let path = self.args.install_dir.join(format!("{}.png", name));
             if let Some(img) = crate::generate_ship_sprites::read_image(&path) {
                 let mut img = Image::from_dynamic(
@@ -455,12 +815,38 @@ impl GameState {
                 img.sampler = bevy::image::ImageSampler::nearest();
                 let dims = img.size();
                 let handle = images.add(img);
-                handles.insert(name.into(), (handle, dims));
-            } else {
-                error!("Failed to load sprite: {}", path.display());
+                handles.insert(name, (handle, dims));
             }
         }
 
+        for id in self.universe.planets.planet_ids() {
+            let Some((_, _, _, sys)) = self.universe.planets.lookup(id, self.universe.stamp())
+            else {
+                continue;
+            };
+            let img = generate_planet_texture(planet_seed(&sys.name), PLANET_TEXTURE_SIZE);
+            let img = Image::from_dynamic(
+                DynamicImage::ImageRgba8(img),
+                true,
+                RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+            );
+            let dims = img.size();
+            let handle = images.add(img);
+            handles.insert(procedural_sprite_name(&sys.name), (handle, dims));
+        }
+
+        for icon in enum_iterator::all::<CursorIcon>() {
+            let img = generate_cursor_icon(icon, CURSOR_ICON_TEXTURE_SIZE);
+            let img = Image::from_dynamic(
+                DynamicImage::ImageRgba8(img),
+                true,
+                RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+            );
+            let dims = img.size();
+            let handle = images.add(img);
+            handles.insert(icon.sprite_name(), (handle, dims));
+        }
+
         let image = generate_error_sprite();
         let dims = image.size();
         let handle = images.add(image);
@@ -468,6 +854,22 @@ impl GameState {
 
         self.image_handles = handles;
     }
+
+    /// Definition of the named planet in any loaded planetary system.
+    pub fn planet_definition(&self, name: &str) -> Option<&PlanetDefinition> {
+        self.planet_catalogs.values().find_map(|c| c.get(name))
+    }
+
+    /// Sprite to draw for the named planet. Hand-made sprites are preferred
+    /// unless procedural textures are forced on in the settings.
+    pub fn planet_sprite(&self, name: &str) -> String {
+        let sprite = self.planet_definition(name).map_or(name, |p| p.sprite());
+        if !self.settings.procedural_planet_textures && self.image_handles.contains_key(sprite) {
+            sprite.to_string()
+        } else {
+            procedural_sprite_name(name)
+        }
+    }
 }
 
 impl Render for GameState {
@@ -476,6 +878,8 @@ impl Render for GameState {
             SceneType::Orbital => OrbitalContext::background_color(state),
             SceneType::Editor => EditorContext::background_color(state),
             SceneType::Telescope => TelescopeContext::background_color(state),
+            SceneType::Stats => StatsContext::background_color(state),
+            SceneType::Scenario => ScenarioEditorContext::background_color(state),
             SceneType::MainMenu => BLACK,
         }
     }
@@ -514,32 +918,40 @@ impl Render for GameState {
         //     .text(debug_info, Vec2::splat(-300.0), 0.7)
         //     .anchor_left();
 
-        match state.scene {
+        let ret = match state.scene {
             SceneType::Orbital => OrbitalContext::draw(canvas, state),
             SceneType::Editor => EditorContext::draw(canvas, state),
             SceneType::Telescope => TelescopeContext::draw(canvas, state),
+            SceneType::Stats => StatsContext::draw(canvas, state),
+            SceneType::Scenario => ScenarioEditorContext::draw(canvas, state),
             SceneType::MainMenu => MainMenuContext::draw(canvas, state),
-        }
+        };
+
+        draw_drag_preview(canvas, state);
+        draw_hud_drag_preview(canvas, state);
+        draw_cursor_icon(canvas, state);
+        ret
     }
 }
 
-fn keyboard_control_law(input: &InputState) -> VehicleControl {
+fn keyboard_control_law(input: &InputState, keys: &Keybindings) -> VehicleControl {
     let mut ctrl = VehicleControl::NULLOPT;
 
-    let docking_mode = input.is_pressed(KeyCode::ControlLeft);
+    let pressed = |action| keys.is_pressed(action, input);
+    let docking_mode = pressed(KeyAction::DockingMode);
 
     if docking_mode {
-        ctrl.plus_x.throttle = input.is_pressed(KeyCode::ArrowUp) as u8 as f32;
-        ctrl.plus_y.throttle = input.is_pressed(KeyCode::ArrowLeft) as u8 as f32;
-        ctrl.neg_x.throttle = input.is_pressed(KeyCode::ArrowDown) as u8 as f32;
-        ctrl.neg_y.throttle = input.is_pressed(KeyCode::ArrowRight) as u8 as f32;
+        ctrl.plus_x.throttle = pressed(KeyAction::ThrustForward) as u8 as f32;
+        ctrl.plus_y.throttle = pressed(KeyAction::TurnLeft) as u8 as f32;
+        ctrl.neg_x.throttle = pressed(KeyAction::ThrustBackward) as u8 as f32;
+        ctrl.neg_y.throttle = pressed(KeyAction::TurnRight) as u8 as f32;
     } else {
-        ctrl.plus_x.throttle = input.is_pressed(KeyCode::ArrowUp) as u8 as f32;
-        ctrl.neg_x.throttle = input.is_pressed(KeyCode::ArrowDown) as u8 as f32;
+        ctrl.plus_x.throttle = pressed(KeyAction::ThrustForward) as u8 as f32;
+        ctrl.neg_x.throttle = pressed(KeyAction::ThrustBackward) as u8 as f32;
 
-        ctrl.attitude = if input.is_pressed(KeyCode::ArrowLeft) {
+        ctrl.attitude = if pressed(KeyAction::TurnLeft) {
             10.0
-        } else if input.is_pressed(KeyCode::ArrowRight) {
+        } else if pressed(KeyAction::TurnRight) {
             -10.0
         } else {
             0.0
@@ -587,32 +999,104 @@ impl GameState {
 
     pub fn disband_group(&mut self, gid: EntityId) {
         self.universe.constellations.retain(|_, g| *g != gid);
+        self.group_styles.remove(&gid);
+        if self.orbital_context.inspected_group == Some(gid) {
+            self.orbital_context.inspected_group = None;
+        }
     }
 
-    pub fn create_group(&mut self, gid: EntityId) {
-        for id in &self.orbital_context.selected {
-            self.universe.constellations.insert(*id, gid.clone());
-        }
+    /// Vehicles anchoring the pinned annotations picked out in the list.
+    pub fn pinned_vehicles(&self) -> Vec<EntityId> {
+        let mut ids: Vec<_> = self
+            .pinned_selection
+            .iter()
+            .filter_map(|i| self.annotations.get(i))
+            .flat_map(|a| a.anchored_entities())
+            .filter(|id| self.universe.surface_vehicles.contains_key(id))
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
     }
 
-    pub fn get_vehicle_by_model(&self, name: &str) -> Option<Vehicle> {
-        let vehicles = crate::scenes::get_list_of_vehicles(self)?;
+    /// Deletes every pinned annotation picked out in the list.
+    pub fn unpin_selected(&mut self) {
+        for i in self.pinned_selection.iter().rev() {
+            if i < self.annotations.len() {
+                self.annotations.remove(i);
+            }
+        }
+        self.pinned_selection.clear();
+    }
 
-        if vehicles.is_empty() {
+    /// Puts the selected vehicles in a new group.
+    pub fn create_group(&mut self) -> Option<EntityId> {
+        if self.orbital_context.selected.is_empty() {
             return None;
         }
+        let selected = self.orbital_context.selected.iter().copied();
+        Some(self.universe.create_group(selected.collect::<Vec<_>>()))
+    }
+
+    /// Moves every member of `from` into `into`, which keeps its name and
+    /// color.
+    pub fn merge_groups(&mut self, into: EntityId, from: EntityId) {
+        if into == from {
+            return;
+        }
+        let (from_name, into_name) = (self.group_name(from), self.group_name(into));
+        self.universe.merge_groups(into, from);
+        self.group_styles.remove(&from);
+        self.notice(format!("Merged {} into {}", from_name, into_name));
+    }
+
+    /// Moves the selected members of a group into a new group.
+    pub fn split_group(&mut self, gid: EntityId) -> Option<EntityId> {
+        let new = self
+            .universe
+            .split_group(gid, &self.orbital_context.selected)?;
+        self.orbital_context.inspected_group = Some(new);
+        Some(new)
+    }
+
+    pub fn group_style(&self, gid: EntityId) -> GroupStyle {
+        self.group_styles.get(&gid).cloned().unwrap_or_default()
+    }
+
+    pub fn group_name(&self, gid: EntityId) -> String {
+        self.group_style(gid).name(gid)
+    }
+
+    pub fn group_color(&self, gid: EntityId) -> Hsla {
+        self.group_style(gid)
+            .color(gid, self.settings.color_palette)
+    }
 
-        let (_, path) = vehicles.iter().find(|(model, _)| model == name)?;
+    pub fn get_vehicle_by_model(&self, name: &str) -> Option<Vehicle> {
+        let path = &self.vehicles.get(name)?.path;
 
         let name = get_random_ship_name(&self.vehicle_names);
 
         let mut vehicle = load_vehicle(path, name, &self.part_database).ok()?;
 
         vehicle.build_all();
+        self.apply_loadout(&mut vehicle, &load_loadouts(path));
 
         Some(vehicle)
     }
 
+    /// Fills a vehicle about to be spawned with the selected loadout, if
+    /// one is selected and it's among `presets` or the built-in ones.
+    pub fn apply_loadout(&self, vehicle: &mut Vehicle, presets: &[Loadout]) {
+        if let Some(loadout) = self
+            .loadout
+            .as_ref()
+            .and_then(|name| Loadout::find(name, presets))
+        {
+            vehicle.apply_loadout(&loadout);
+        }
+    }
+
     pub fn measuring_tape(&self) -> Option<(DVec2, DVec2, DVec2)> {
         if self.orbital_context.cursor_mode != CursorMode::MeasuringTape {
             return None;
@@ -645,11 +1129,60 @@ impl GameState {
         self.orbital_context.piloting
     }
 
+    /// Callsign of a vehicle, including one in the recycle bin.
+    pub fn vehicle_callsign(&self, id: EntityId) -> Option<&str> {
+        if let Some(sv) = self.universe.surface_vehicles.get(&id) {
+            return Some(sv.vehicle().name());
+        }
+        let deleted = self.universe.recycle_bin.iter().find(|e| e.id == id)?;
+        Some(deleted.vehicle.vehicle().name())
+    }
+
+    /// Name to show for an entity in labels, lists and notifications: a
+    /// vehicle's callsign or a waypoint's name, or else its ID.
+    pub fn callsign(&self, id: EntityId) -> String {
+        if let Some(name) = self.vehicle_callsign(id) {
+            name.to_string()
+        } else if let Some(w) = self.universe.waypoints.get(&id) {
+            w.name.clone()
+        } else {
+            id.to_string()
+        }
+    }
+
+    /// Vehicle whose orbit history is shown: the piloted vehicle, or
+    /// failing that the followed one.
+    pub fn orbit_history_subject(&self) -> Option<(EntityId, &OrbitHistory)> {
+        [self.piloting(), self.orbital_context.following]
+            .into_iter()
+            .flatten()
+            .find_map(|id| Some((id, self.universe.orbit_history.get(id)?)))
+    }
+
+    /// Moves the history scrub by a hundredth of the recorded span per
+    /// step, starting from the present.
+    fn scrub_orbit_history(&mut self, steps: i32) {
+        let now = self.universe.stamp();
+        let Some((start, end)) = self.orbit_history_subject().and_then(|(_, h)| h.span(now)) else {
+            return;
+        };
+        let current = self.orbital_context.history_scrub.unwrap_or(end);
+        let step = (end - start) / 100;
+        let t = (current + step * steps as i64).clamp(start, end);
+        self.orbital_context.history_scrub = Some(t);
+    }
+
     pub fn spawn_with_random_perturbance(
         &mut self,
         global: GlobalOrbit,
         vehicle: Vehicle,
     ) -> Option<()> {
+        let orbit = self.random_perturbance(global)?;
+        self.universe.add_orbital_vehicle(vehicle, orbit);
+        Some(())
+    }
+
+    fn random_perturbance(&self, global: GlobalOrbit) -> Option<GlobalOrbit> {
         let GlobalOrbit(parent, orbit) = global;
         let pv_local = orbit.pv(self.universe.stamp()).ok()?;
         let perturb = PV::from_f64(
@@ -663,21 +1196,135 @@ impl GameState {
             ),
         );
         let orbit = SparseOrbit::from_pv(pv_local + perturb, orbit.body, self.universe.stamp())?;
-        self.universe
-            .add_orbital_vehicle(vehicle, GlobalOrbit(parent, orbit));
+        Some(GlobalOrbit(parent, orbit))
+    }
+
+    /// Constructs queued vehicles until the time budget is spent. Vehicles
+    /// whose model can't be loaded are dropped with an error.
+    fn stream_pending_vehicles(&mut self, budget: std::time::Duration) {
+        let start = std::time::Instant::now();
+        while start.elapsed() < budget {
+            let Some(pending) = self.universe.loading.pop() else {
+                return;
+            };
+            let Some(vehicle) = self.get_vehicle_by_model(&pending.model) else {
+                error!("Failed to load queued vehicle model {}", pending.model);
+                continue;
+            };
+            self.universe.finish_loading(pending, vehicle);
+        }
+    }
+
+    /// Starts importing the vehicle files found under `dir` into the
+    /// vehicle directory, returning how many were found.
+    pub fn start_vehicle_import(&mut self, dir: &Path, dry_run: bool) -> Result<usize, String> {
+        if self.vehicle_import.is_some() {
+            return Err("An import is already in progress".to_string());
+        }
+        let import = VehicleImport::new(dir, &self.args.vehicle_dir(), dry_run)?;
+        let (_, total) = import.progress();
+        self.vehicle_import = Some(import);
+        Ok(total)
+    }
+
+    /// Rereads the vehicle directory, rebuilding the editor's gallery if
+    /// anything changed.
+    pub fn refresh_vehicles(&mut self) {
+        if self.vehicles.refresh(&self.part_database) {
+            self.editor_context.gallery = None;
+        }
+    }
+
+    fn stream_vehicle_import(&mut self, budget: std::time::Duration) {
+        let Some(import) = &mut self.vehicle_import else {
+            return;
+        };
+        let start = std::time::Instant::now();
+        while start.elapsed() < budget {
+            let Some(entry) = import.step(&self.part_database) else {
+                break;
+            };
+            if !matches!(entry.outcome, ImportOutcome::Accepted(_)) {
+                let s = format!("{}: {}", entry.source.display(), entry.outcome);
+                self.console.print(s);
+            }
+        }
+        if !import.is_done() {
+            return;
+        }
+        let summary = import.summary();
+        if !import.is_dry_run() {
+            self.refresh_vehicles();
+        }
+        self.vehicle_import = None;
+        self.notice(summary);
+    }
+
+    /// Points a vehicle turned away from an occupied pad at the nearest
+    /// one it can set down on instead.
+    fn suggest_free_pad(&mut self, id: EntityId) -> Option<()> {
+        let sv = self.universe.surface_vehicles.get(&id)?;
+        let (parent, pos) = (sv.parent(), sv.body.pv.pos);
+        let Some(pad) = self.universe.free_pad_near(parent, pos, Some(id)) else {
+            self.notice(format!("{} landed on an occupied pad", self.callsign(id)));
+            return None;
+        };
+        let name = self.universe.landing_pads.get(&pad)?.name.clone();
+        self.notice(format!(
+            "{} landed on an occupied pad; divert to {}",
+            self.callsign(id),
+            name
+        ));
         Some(())
     }
 
+    /// Spawns an inert drone just ahead of the piloted vehicle, targets it,
+    /// and starts scoring the approach.
+    pub fn spawn_practice_target(&mut self) -> Option<EntityId> {
+        let pilot = self.piloting()?;
+        let sv = self.universe.surface_vehicles.get(&pilot)?;
+        let parent = sv.parent();
+        let (body, _, _, _) = self
+            .universe
+            .planets
+            .lookup(parent, self.universe.stamp())?;
+        let mut pv = sv.pv();
+        pv.pos += pv.vel.normalize_or(DVec2::X) * PRACTICE_TARGET_SPAWN_DISTANCE;
+        let orbit = SparseOrbit::from_pv(pv, body, self.universe.stamp())?;
+        let vehicle = self.get_vehicle_by_model(PRACTICE_TARGET_MODEL)?;
+        let id = self
+            .universe
+            .add_orbital_vehicle(vehicle, GlobalOrbit(parent, orbit))?;
+
+        let drone = self.universe.surface_vehicles.get_mut(&id)?;
+        drone.body.angular_velocity = 0.0;
+        let angle = drone.body.angle;
+        drone
+            .controller
+            .set_policy(VehicleControlPolicy::HoldAttitude(Some(angle)));
+
+        if let Some(sv) = self.universe.surface_vehicles.get_mut(&pilot) {
+            sv.set_target(id);
+        }
+
+        self.practice = Some(PracticeSession::new(pilot, id));
+        self.notice(format!("Spawned practice target {}", self.callsign(id)));
+        Some(id)
+    }
+
     pub fn spawn_new(&mut self) -> Option<()> {
         let orbit = self.cursor_orbit_if_mode()?;
         let vehicle = self.get_random_vehicle()?;
         self.spawn_with_random_perturbance(orbit, vehicle)
     }
 
+    /// Moves an orbiter to the recycle bin. It can be restored with
+    /// `undelete_orbiter` until the retention period elapses.
     pub fn delete_orbiter(&mut self, id: EntityId) -> Option<()> {
-        let ov = self.universe.surface_vehicles.remove(&id)?;
+        let ov = self.universe.surface_vehicles.get(&id)?;
         let parent = ov.parent();
         let pv = ov.pv();
+        self.universe.soft_delete(id)?;
         self.notify(
             ObjectId::Planet(parent),
             NotificationType::OrbiterDeleted(id),
@@ -686,6 +1333,14 @@ impl GameState {
         Some(())
     }
 
+    pub fn undelete_orbiter(&mut self, id: EntityId) -> Option<()> {
+        self.universe.undelete(id)?;
+        self.notifications
+            .retain(|n| n.kind != NotificationType::OrbiterDeleted(id));
+        self.notice(format!("Restored {}", self.callsign(id)));
+        Some(())
+    }
+
     pub fn delete_objects(&mut self) {
         self.orbital_context
             .selected
@@ -700,32 +1355,458 @@ impl GameState {
         self.orbital_context.queued_orbits.get(self.current_orbit?)
     }
 
-    pub fn commit_mission(&mut self) -> Option<()> {
-        println!("TODO");
-        Some(())
+    /// Best maneuver plan from the piloted vehicle's orbit to the currently
+    /// selected orbit, along with the ID of the body both orbits are around.
+    pub fn planned_maneuver(&self) -> Option<(EntityId, ManeuverPlan)> {
+        let dst = self.current_orbit()?;
+        let src = self
+            .universe
+            .surface_vehicles
+            .get(&self.piloting()?)?
+            .current_orbit()?;
+        if src.0 != dst.0 {
+            return None;
+        }
+        let plan = best_maneuver_plan(&src.1, &dst.1, self.universe.stamp()).ok()?;
+        Some((src.0, plan))
     }
 
-    pub fn swap_ownship_target(&mut self) {
-        if let Some(old_pilot_id) = self.orbital_context.piloting {
-            if let Some(old_pilot) = self.universe.surface_vehicles.get_mut(&old_pilot_id) {
-                if let Some(new_pilot) = old_pilot.target() {
-                    self.orbital_context.piloting = Some(new_pilot);
-                    if let Some(sv) = self.universe.surface_vehicles.get_mut(&new_pilot) {
-                        sv.set_target(old_pilot_id);
+    /// Copies the maneuver plan of the given vehicle, or of the piloted
+    /// vehicle, so that it can be shared with others. A vehicle's
+    /// scheduled plan is copied if it has one, otherwise the piloted
+    /// vehicle's plan to the selected orbit.
+    pub fn copy_plan(&mut self, id: Option<EntityId>) -> Result<(), String> {
+        let id = id
+            .or(self.piloting())
+            .ok_or("No vehicle to copy a plan from")?;
+        let parent = self
+            .universe
+            .surface_vehicles
+            .get(&id)
+            .ok_or(format!("No vehicle with id {}", id))?
+            .parent();
+        let template = match self.universe.maneuvers.get(id) {
+            Some(plan) => PlanTemplate::new(parent, plan),
+            None if self.piloting() == Some(id) => self
+                .planned_maneuver()
+                .and_then(|(parent, plan)| PlanTemplate::new(parent, &plan)),
+            None => None,
+        };
+        let template = template.ok_or(format!("{} has no maneuver plan", self.callsign(id)))?;
+        self.notice(format!(
+            "Copied {:0.1} m/s maneuver plan from {}",
+            template.dv(),
+            self.callsign(id)
+        ));
+        self.orbital_context.copied_plan = Some(template);
+        Ok(())
+    }
+
+    /// Schedules the copied maneuver plan for each selected vehicle, fitted
+    /// to where each one is in its orbit. Each vehicle's first burn comes
+    /// at least `interval` after the one before it. Vehicles which can't
+    /// fly the plan are skipped with a notice. Returns how many were
+    /// scheduled.
+    pub fn paste_plan(&mut self, interval: Nanotime) -> Result<usize, String> {
+        let template = self
+            .orbital_context
+            .copied_plan
+            .clone()
+            .ok_or("No maneuver plan has been copied")?;
+        let mut ids: Vec<EntityId> = self.orbital_context.selected.iter().copied().collect();
+        ids.sort();
+
+        let mut earliest = self.universe.stamp();
+        let mut scheduled = 0;
+        for id in &ids {
+            let Some(sv) = self.universe.surface_vehicles.get(id) else {
+                continue;
+            };
+            let fitted = match sv.current_orbit() {
+                Some(orbit) => template.fit(&orbit, earliest, sv.vehicle.remaining_dv()),
+                None => Err(PlanShareError::NoPhaseMatch),
+            };
+            match fitted {
+                Ok(plan) => {
+                    if let Some((t, _)) = plan.dvs().next() {
+                        earliest = t + interval;
                     }
+                    self.universe.maneuvers.schedule(*id, plan);
+                    scheduled += 1;
                 }
+                Err(e) => self.notice(format!("{} {}", self.callsign(*id), e)),
             }
         }
+
+        self.notice(format!(
+            "Shared maneuver plan with {} of {} vehicles",
+            scheduled,
+            ids.len()
+        ));
+        Ok(scheduled)
     }
 
-    pub fn write_editor_to_ownship(&mut self) -> Option<()> {
-        let id = match self.piloting() {
-            Some(p) => p,
-            None => {
-                self.notice("No ownship to write to");
-                return None;
-            }
-        };
+    /// Where to place labels and markers for the given entity this frame.
+    pub fn display_position(&self, id: EntityId) -> Option<DVec2> {
+        display_position(&self.universe, id, self.display_clock.stamp())
+    }
+
+    /// Launch guidance for the given vehicle: the player's tuned profile if
+    /// there is one, otherwise defaults for the vehicle's TWR on its
+    /// current planet.
+    pub fn ascent_profile(&self, id: EntityId) -> Option<AscentProfile> {
+        if let Some(p) = self.orbital_context.ascent_profiles.get(&id) {
+            return Some(*p);
+        }
+        let sv = self.universe.surface_vehicles.get(&id)?;
+        let (body, _, _, _) = self
+            .universe
+            .planets
+            .lookup(sv.parent(), self.universe.stamp())?;
+        Some(AscentProfile::for_vehicle(&sv.vehicle, &body, 450_000.0))
+    }
+
+    /// Stores a tuned ascent profile, and updates the vehicle's guidance if
+    /// it's already launching.
+    fn set_ascent_profile(&mut self, id: EntityId, profile: AscentProfile) {
+        self.orbital_context.ascent_profiles.insert(id, profile);
+        let launching = self.universe.surface_vehicles.get(&id).is_some_and(|sv| {
+            matches!(sv.controller.mode(), VehicleControlPolicy::LaunchToOrbit(_))
+        });
+        if launching {
+            self.apply_or_queue(
+                id,
+                PausedAction::SetPolicy(VehicleControlPolicy::LaunchToOrbit(profile)),
+            );
+        }
+    }
+
+    /// Whether the piloted vehicle is on the ground or climbing to orbit,
+    /// and so should show launch guidance.
+    pub fn is_ascending(&self) -> Option<EntityId> {
+        let id = self.piloting()?;
+        let sv = self.universe.surface_vehicles.get(&id)?;
+        let launching = matches!(sv.controller.mode(), VehicleControlPolicy::LaunchToOrbit(_));
+        (sv.is_landed() || launching).then_some(id)
+    }
+
+    /// Landing site directly beneath the piloted vehicle, if any.
+    pub fn landing_site(&self) -> Option<&LandingPad> {
+        self.universe.landing_pads.get(&self.landing_site_id()?)
+    }
+
+    pub fn landing_site_id(&self) -> Option<EntityId> {
+        let sv = self.universe.surface_vehicles.get(&self.piloting()?)?;
+        self.universe.pad_at(sv.parent(), sv.body.pv.pos)
+    }
+
+    /// An event as shown to the player, with vehicles named by callsign.
+    pub fn describe_event(&self, event: &UniverseEvent) -> String {
+        event.describe(|id| self.callsign(id))
+    }
+
+    /// Updates statistics and notifications from the events the universe
+    /// has emitted since the last game tick.
+    fn handle_universe_events(&mut self) {
+        for e in self.universe.events.poll(self.events) {
+            self.session.events += 1;
+            match e.event {
+                UniverseEvent::Landed(id, score) => {
+                    let crashed = score.speed > CRASH_SPEED;
+                    // lifetime stats are the player's, not the traffic's
+                    let owned = self.fleet.owns(&self.universe, id);
+                    if crashed {
+                        if owned {
+                            self.stats.stats.crashes += 1;
+                        }
+                        let site = score
+                            .pad
+                            .or_else(|| Some(self.universe.surface_vehicles.get(&id)?.parent()));
+                        if let Some(site) = site {
+                            self.records.on_crash(site);
+                        }
+                    } else if owned {
+                        self.stats.stats.landings += 1;
+                    }
+                    self.notice(format!("{} touchdown: {}", self.callsign(id), score));
+                    self.notify(
+                        ObjectId::Orbiter(id),
+                        NotificationType::Touchdown(id, score.points()),
+                        None,
+                    );
+                    if score.occupied {
+                        self.suggest_free_pad(id);
+                    }
+                    if self.settings.career_mode {
+                        if crashed {
+                            self.lose_vehicle(id);
+                        } else {
+                            self.ledger.end_mission(id);
+                            self.start_construction(id, score.accepted_pad());
+                        }
+                    }
+                }
+                UniverseEvent::Docked(id, _) if self.fleet.owns(&self.universe, id) => {
+                    self.stats.stats.dockings += 1
+                }
+                UniverseEvent::Destroyed(id) => self.records.on_destroyed(id, e.stamp),
+                UniverseEvent::Restored(id) => self.records.on_restored(id),
+                UniverseEvent::WavedOff(..)
+                | UniverseEvent::DockingAborted(..)
+                | UniverseEvent::SalvageRecovered(..)
+                | UniverseEvent::Staged(..) => self.notice(self.describe_event(&e.event)),
+                UniverseEvent::Latched(guest, host) => {
+                    self.notice(self.describe_event(&e.event));
+                    if self.orbital_context.piloting == Some(guest) {
+                        self.orbital_context.piloting = Some(host);
+                    }
+                    if self.orbital_context.following == Some(guest) {
+                        self.orbital_context.following = Some(host);
+                    }
+                }
+                UniverseEvent::Undocked(..)
+                | UniverseEvent::TransferComplete(..)
+                | UniverseEvent::TransferAborted(..) => self.notice(self.describe_event(&e.event)),
+                UniverseEvent::RendezvousAchieved(id, _) => {
+                    self.notice(self.describe_event(&e.event));
+                    self.notify(
+                        ObjectId::Orbiter(id),
+                        NotificationType::ManeuverComplete(id),
+                        None,
+                    );
+                }
+                UniverseEvent::BurnAborted(id, _) | UniverseEvent::RendezvousAborted(id, ..) => {
+                    self.notice(self.describe_event(&e.event));
+                    self.notify(
+                        ObjectId::Orbiter(id),
+                        NotificationType::ManeuverFailed(id),
+                        None,
+                    );
+                }
+                UniverseEvent::Scripted(i) => {
+                    let Some(event) = self.universe.script.get(i) else {
+                        continue;
+                    };
+                    match &event.action {
+                        ScriptAction::Message(s) => {
+                            let s = s.clone();
+                            self.notify(None, NotificationType::Notice(s), None);
+                        }
+                        action => self.notice(action.to_string()),
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Asks the player to confirm a handover, once it's been checked that
+    /// it can be made.
+    pub fn request_handover(&mut self, handover: Handover) -> Result<(), String> {
+        self.fleet.check(&self.universe, &handover)?;
+        self.console.hide();
+        self.pending_handover = Some(handover);
+        Ok(())
+    }
+
+    /// Where a handover sends vehicles, as in "handed over to Group 5".
+    pub fn handover_destination(&self, target: HandoverTarget) -> String {
+        match target {
+            HandoverTarget::Group(gid) => format!("to {}", self.group_name(gid)),
+            HandoverTarget::NewGroup => "to a new group".to_string(),
+            HandoverTarget::Ungrouped => "out of their group".to_string(),
+            HandoverTarget::Abandon => "to nobody, as a derelict".to_string(),
+            HandoverTarget::Reclaim => "back to the fleet".to_string(),
+        }
+    }
+
+    /// Carries out the handover waiting on confirmation. Abandoned
+    /// vehicles are no longer insured nor due to be rebuilt, and stop
+    /// being piloted.
+    pub fn confirm_handover(&mut self) {
+        let Some(handover) = self.pending_handover.take() else {
+            return;
+        };
+        let record = match self.fleet.hand_over(&mut self.universe, &handover) {
+            Ok(r) => r.clone(),
+            Err(e) => {
+                self.notice(e);
+                return;
+            }
+        };
+
+        if record.target == HandoverTarget::Abandon {
+            for (id, _) in &record.vehicles {
+                self.ledger.end_mission(*id);
+                self.ledger.rebuilding.remove(id);
+            }
+            if self
+                .piloting()
+                .is_some_and(|p| record.vehicles.iter().any(|(id, _)| *id == p))
+            {
+                self.orbital_context.piloting = None;
+            }
+        }
+        if let Some(gid) = record.group {
+            self.orbital_context.inspected_group = Some(gid);
+        }
+
+        let to = self.handover_destination(record.target);
+        for (id, _) in &record.vehicles {
+            self.notify(
+                ObjectId::Orbiter(*id),
+                NotificationType::HandedOver(*id, to.clone()),
+                None,
+            );
+        }
+        self.notice(format!(
+            "Handed over {} vehicle(s) {}",
+            record.vehicles.len(),
+            to
+        ));
+    }
+
+    /// Destroys a crashed vehicle and any vehicles docked to it, writing
+    /// off the value of each and collecting any insurance on it. Derelicts
+    /// are no longer the player's to lose.
+    fn lose_vehicle(&mut self, id: EntityId) -> Option<()> {
+        let stamp = self.universe.stamp();
+        let destroyed = self.universe.remove(id);
+        if destroyed.is_empty() {
+            return None;
+        }
+        for (id, design) in destroyed {
+            if self.fleet.is_derelict(id) {
+                self.fleet.forget(id);
+                continue;
+            }
+            let value = vehicle_value(&design);
+            let payout = self.ledger.record_loss(id, design, value, stamp);
+            self.notice(format!(
+                "Lost {} worth {}; insurance paid {}",
+                id,
+                credits_str(value),
+                credits_str(payout)
+            ));
+        }
+        Some(())
+    }
+
+    /// Landing site with repair drones where lost vehicles are rebuilt:
+    /// the piloted vehicle's site if it has them, or else the first
+    /// vacant one.
+    pub fn rebuild_site(&self) -> Option<EntityId> {
+        let can_build = |id: &EntityId| {
+            self.universe
+                .landing_pads
+                .get(id)
+                .is_some_and(|p| p.has_service(SiteService::Repair))
+        };
+        if let Some(id) = self.landing_site_id().filter(can_build) {
+            return Some(id);
+        }
+        let mut ids: Vec<_> = self.universe.landing_pads.keys().copied().collect();
+        ids.sort();
+        ids.into_iter()
+            .find(|id| can_build(id) && self.universe.pad_occupant(*id, None).is_none())
+    }
+
+    /// Pays for a lost design and drops an unbuilt copy of it on the
+    /// rebuild site's pad, where drones construct it once it lands.
+    fn rebuild_lost_vehicle(&mut self, index: usize) -> Option<()> {
+        let Some(site) = self.rebuild_site() else {
+            self.notice("No landing site with repair drones to rebuild at");
+            return None;
+        };
+        let pad = self.universe.landing_pads.get(&site)?;
+        let (planet, angle, name) = (pad.planet_id, pad.angle, pad.name.clone());
+        let stamp = self.universe.stamp();
+        let mut vehicle = match self.ledger.rebuild(index, stamp) {
+            Ok(v) => v,
+            Err(e) => {
+                self.notice(e);
+                return None;
+            }
+        };
+        vehicle.unbuild_all();
+        let id = self
+            .universe
+            .add_surface_vehicle(planet, vehicle, angle, 2.0)?;
+        let sv = self.universe.surface_vehicles.get_mut(&id)?;
+        sv.controller = VehicleController::idle();
+        sv.body.pv.vel = DVec2::ZERO;
+        self.ledger.rebuilding.insert(id);
+        self.notice(format!("Rebuilding as {} at {}", self.callsign(id), name));
+        Some(())
+    }
+
+    /// Queues construction of a rebuilt vehicle which has just landed on
+    /// its site's pad.
+    fn start_construction(&mut self, id: EntityId, pad: Option<EntityId>) -> Option<()> {
+        if !self.ledger.rebuilding.remove(&id) {
+            return None;
+        }
+        let sv = self.universe.surface_vehicles.get(&id)?;
+        let pad = self.universe.landing_pads.get_mut(&pad?)?;
+        for (pid, _) in sv.vehicle().parts().filter(|(_, p)| !p.is_built()) {
+            pad.queue_task(ServiceTask::Repair(id, *pid));
+        }
+        Some(())
+    }
+
+    /// Orbits between which phase angles are measured for the pilot's
+    /// current target: the pilot and a target around the same body, or
+    /// failing that, the pilot's parent planet and a target orbiting the
+    /// same body as that planet.
+    pub fn phase_orbits(&self) -> Option<(GlobalOrbit, GlobalOrbit)> {
+        let sv = self.universe.surface_vehicles.get(&self.piloting()?)?;
+        let target = sv.target()?;
+        let src = sv.current_orbit()?;
+        if target == src.0 {
+            return None;
+        }
+        let dst = match self.universe.surface_vehicles.get(&target) {
+            Some(t) => t.current_orbit()?,
+            None => self.universe.planets.orbit_of(target)?,
+        };
+        if src.0 == dst.0 {
+            return Some((src, dst));
+        }
+        let parent = self.universe.planets.orbit_of(src.0)?;
+        (parent.0 == dst.0).then_some((parent, dst))
+    }
+
+    pub fn phase_info(&self) -> Option<PhaseInfo> {
+        let (src, dst) = self.phase_orbits()?;
+        phase_info(&src.1, &dst.1, self.universe.stamp())
+    }
+
+    pub fn commit_mission(&mut self) -> Option<()> {
+        println!("TODO");
+        Some(())
+    }
+
+    pub fn swap_ownship_target(&mut self) {
+        if let Some(old_pilot_id) = self.orbital_context.piloting {
+            if let Some(old_pilot) = self.universe.surface_vehicles.get_mut(&old_pilot_id) {
+                if let Some(new_pilot) = old_pilot.target() {
+                    self.orbital_context.piloting = Some(new_pilot);
+                    if let Some(sv) = self.universe.surface_vehicles.get_mut(&new_pilot) {
+                        sv.set_target(old_pilot_id);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn write_editor_to_ownship(&mut self) -> Option<()> {
+        let id = match self.piloting() {
+            Some(p) => p,
+            None => {
+                self.notice("No ownship to write to");
+                return None;
+            }
+        };
 
         let ov = match self.universe.surface_vehicles.get_mut(&id) {
             Some(v) => v,
@@ -735,11 +1816,13 @@ impl GameState {
             }
         };
 
-        let new_vehicle = self.editor_context.vehicle.clone();
+        let mut new_vehicle = self.editor_context.vehicle.clone();
 
         let old_title = ov.vehicle().name().to_string();
-        let new_title = new_vehicle.name().to_string();
+        let new_title = self.universe.callsign_for(new_vehicle.name(), Some(id));
+        new_vehicle.set_name(new_title.clone());
 
+        let ov = self.universe.surface_vehicles.get_mut(&id)?;
         ov.overwrite_vehicle(new_vehicle);
 
         self.notice(format!(
@@ -756,12 +1839,116 @@ impl GameState {
         self.console.log(s);
     }
 
+    /// Turns spectator mode on or off. The console closes on the way in,
+    /// since commands can't be run while spectating.
+    pub fn set_spectating(&mut self, on: bool) {
+        self.spectator.set_active(on);
+        if on {
+            self.console.hide();
+            self.notice("Spectator mode on");
+        } else {
+            self.notice("Spectator mode off");
+            if self.network.is_observing() {
+                self.leave_network();
+            }
+        }
+    }
+
+    /// Starts streaming the universe to observers which connect to
+    /// `addr`.
+    pub fn host_observers(&mut self, addr: &str) -> Result<(), String> {
+        self.leave_network();
+        let host = SnapshotHost::bind(addr)?;
+        self.network = NetworkSession::Hosting(host);
+        if let Some(status) = self.network.status() {
+            self.notice(status);
+        }
+        Ok(())
+    }
+
+    /// Connects to a host and watches its universe in place of this one.
+    /// Spectator mode stays on for as long as the connection lasts, and
+    /// turning it off disconnects.
+    pub fn observe(&mut self, host: &str) -> Result<(), String> {
+        self.leave_network();
+        let observer = SnapshotObserver::connect(host)?;
+        self.network = NetworkSession::Observing(observer);
+        if let Some(status) = self.network.status() {
+            self.notice(status);
+        }
+        self.set_spectating(true);
+        Ok(())
+    }
+
+    pub fn leave_network(&mut self) {
+        match std::mem::take(&mut self.network) {
+            NetworkSession::Offline => (),
+            NetworkSession::Hosting(_) => self.notice("Stopped hosting"),
+            NetworkSession::Observing(_) => self.notice("Disconnected from host"),
+        }
+    }
+
+    /// Sends snapshots to observers, or applies those which arrived from
+    /// the host.
+    fn update_network(&mut self) {
+        let news = match &mut self.network {
+            NetworkSession::Offline => Vec::new(),
+            NetworkSession::Hosting(host) => host.update(&self.universe, self.wall_time),
+            NetworkSession::Observing(observer) => match observer.poll() {
+                Ok(snapshots) => {
+                    let mut unbuilt = 0;
+                    for snapshot in &snapshots {
+                        let failed = self.universe.apply_snapshot(snapshot, &self.part_database);
+                        unbuilt += observer.note_unbuilt(failed);
+                    }
+                    (unbuilt > 0)
+                        .then(|| {
+                            format!(
+                                "Couldn't build {} vehicle(s) from the host; are the parts the same?",
+                                unbuilt
+                            )
+                        })
+                        .into_iter()
+                        .collect()
+                }
+                Err(e) => {
+                    self.network = NetworkSession::Offline;
+                    vec![format!("Lost connection to host: {}", e)]
+                }
+            },
+        };
+        for s in news {
+            self.notice(s);
+        }
+    }
+
+    /// Cuts the camera to the next subject chosen by the spectator
+    /// director, when it's due.
+    fn update_director(&mut self) {
+        if self.scene != SceneType::Orbital {
+            return;
+        }
+        let universe = &self.universe;
+        let candidates = universe
+            .surface_vehicles
+            .keys()
+            .filter_map(|id| Some((*id, director_interest(universe, *id)?)));
+        if let Some(id) = self.spectator.next_shot(self.wall_time, candidates) {
+            self.orbital_context.following = Some(id);
+            self.orbital_context.camera.clear_offset();
+        }
+    }
+
     pub fn notify(
         &mut self,
         parent: impl Into<Option<ObjectId>>,
         kind: NotificationType,
         offset: impl Into<Option<DVec2>>,
     ) {
+        let callsign = kind
+            .subject()
+            .and_then(|id| self.vehicle_callsign(id))
+            .map(str::to_string);
         let notif = Notification {
             parent: parent.into(),
             offset: offset.into().unwrap_or(DVec2::ZERO),
@@ -770,6 +1957,7 @@ impl GameState {
             wall_time: self.wall_time,
             extra_time: Nanotime::secs_f32(rand(0.0, 1.0)),
             kind,
+            callsign,
         };
 
         if self.notifications.iter().any(|e| notif.is_duplicate(e)) {
@@ -787,6 +1975,16 @@ impl GameState {
     pub fn save(&mut self) -> Option<()> {
         match self.scene {
             SceneType::Editor => EditorContext::save_to_file(self),
+            SceneType::Scenario => self.export_scenario(),
+            SceneType::Orbital if self.settings.compressed_saves => self.save_archive(),
+            SceneType::Orbital => {
+                self.save_universe();
+                self.save_structures();
+                self.save_terrain();
+                self.save_waypoints();
+                self.save_timeline_notes();
+                self.save_annotations()
+            }
             _ => None,
         }
     }
@@ -794,26 +1992,692 @@ impl GameState {
     pub fn load(&mut self) -> Option<()> {
         match self.scene {
             SceneType::Editor => EditorContext::load_from_file(self),
+            SceneType::Scenario => self.import_scenario(),
+            SceneType::Orbital if self.settings.compressed_saves => self.load_archive(),
+            SceneType::Orbital => {
+                self.load_universe();
+                self.load_structures();
+                self.load_terrain();
+                self.load_waypoints();
+                self.load_timeline_notes();
+                self.load_annotations()
+            }
             _ => None,
         }
     }
 
+    /// The universe, less what's saved alongside it in records or files
+    /// of its own.
+    fn universe_storage(&self) -> UniverseFileStorage {
+        UniverseFileStorage {
+            structures: Vec::new(),
+            terrain: Vec::new(),
+            waypoints: Vec::new(),
+            ..self.universe.to_file_storage()
+        }
+    }
+
+    /// Everything saved with the orbital scene, one record per vehicle,
+    /// structure and waypoint so that autosaves only rewrite those which
+    /// changed. Records are built and encoded as they're written, so that
+    /// autosaves can spread that over several frames too.
+    fn save_records(&self) -> Vec<SaveRecord> {
+        let mut records = Vec::new();
+        for (id, sv) in &self.universe.surface_vehicles {
+            let (id, sv) = (*id, sv.clone());
+            let docked = self.universe.docked.get(&id).cloned().unwrap_or_default();
+            let rendezvous = self
+                .universe
+                .rendezvous
+                .get(&id)
+                .and_then(|c| c.rendezvous_target());
+            let key = format!("vehicle/{:012}", id.0);
+            records.push(SaveRecord::lazy(key, move || VehicleStateFileStorage {
+                rendezvous,
+                ..VehicleStateFileStorage::new(id, &sv, &docked)
+            }));
+        }
+        for (id, s) in &self.universe.structures {
+            let key = format!("structure/{:012}", id.0);
+            records.push(SaveRecord::deferred(key, s.clone()));
+        }
+        for (id, t) in &self.universe.terrain {
+            let key = format!("terrain/{:012}", id.0);
+            records.push(SaveRecord::deferred(key, t.clone()));
+        }
+        for (id, w) in &self.universe.waypoints {
+            let key = format!("waypoint/{:012}", id.0);
+            records.push(SaveRecord::deferred(key, w.clone()));
+        }
+        let universe = UniverseFileStorage {
+            structures: Vec::new(),
+            terrain: Vec::new(),
+            waypoints: Vec::new(),
+            ..self.universe.to_file_storage_without_vehicles()
+        };
+        records.push(SaveRecord::deferred("universe", universe));
+        records.push(SaveRecord::deferred(
+            "timeline_notes",
+            self.timeline_notes.as_slice().to_vec(),
+        ));
+        records.push(SaveRecord::deferred(
+            "annotations",
+            self.annotations.clone(),
+        ));
+        records
+    }
+
+    fn save_archive(&mut self) -> Option<()> {
+        let path = self.args.save_path();
+        match SaveArchive::write_full(&path, self.save_records()) {
+            Ok(archive) => {
+                self.notice(format!(
+                    "Saved {} records to {} ({} KB)",
+                    archive.keys().count(),
+                    path.display(),
+                    archive.file_len().div_ceil(1000)
+                ));
+                Some(())
+            }
+            Err(e) => {
+                error!("Failed to save: {e}");
+                None
+            }
+        }
+    }
+
+    /// Loads whichever of the save and the autosave was written last.
+    fn load_archive(&mut self) -> Option<()> {
+        let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+        let path = [self.args.save_path(), self.args.autosave_path()]
+            .into_iter()
+            .filter_map(|p| Some((modified(&p)?, p)))
+            .max()
+            .map(|(_, p)| p)?;
+
+        let load = || -> Result<_, Box<dyn std::error::Error>> {
+            let archive = SaveArchive::open(&path)?;
+            let mut universe: Option<UniverseFileStorage> = archive.read("universe")?;
+            let vehicles: Vec<VehicleStateFileStorage> = archive.read_all("vehicle/")?;
+            // older saves kept their vehicles in the universe record
+            if let Some(u) = universe.as_mut().filter(|_| !vehicles.is_empty()) {
+                u.vehicles = vehicles;
+            }
+            let structures: Vec<(String, Structure)> = archive.read_all_keyed("structure/")?;
+            let terrain: Vec<SiteTerrain> = archive.read_all("terrain/")?;
+            let waypoints: Vec<(String, Waypoint)> = archive.read_all_keyed("waypoint/")?;
+            let notes: Vec<TimelineNote> = archive.read("timeline_notes")?.unwrap_or_default();
+            let annotations: Vec<Annotation> = archive.read("annotations")?.unwrap_or_default();
+            Ok((universe, structures, terrain, waypoints, notes, annotations))
+        };
+        let (universe, structures, terrain, waypoints, notes, annotations) = match load() {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.notice(format!("Failed to load {}: {}", path.display(), e));
+                return None;
+            }
+        };
+
+        // saves from before the universe was kept only have what's below
+        if let Some(storage) = universe {
+            self.resume_universe(storage)?;
+        }
+
+        // records are keyed by the ID they were saved under
+        let keyed = |key: &str| key.parse::<i64>().ok().map(EntityId);
+        self.universe.replace_structures(
+            structures
+                .into_iter()
+                .map(|(k, s)| (keyed(&k), s))
+                .collect(),
+        );
+        self.orbital_context.inspected_structure = None;
+        self.universe.replace_terrain(terrain);
+        self.universe
+            .replace_waypoints(waypoints.into_iter().map(|(k, w)| (keyed(&k), w)).collect());
+        self.timeline_notes = TimelineNotes::new(notes);
+        self.sync_timeline_note_alarms();
+        self.annotations = annotations;
+        for a in &mut self.annotations {
+            a.remap_ids(self.universe.legacy_ids());
+        }
+        self.pinned_selection.clear();
+        self.normalize_on_load = true;
+        self.notice(format!("Loaded {}", path.display()));
+        Some(())
+    }
+
+    /// Starts an autosave once the interval has passed, then writes as
+    /// much of it as fits in the frame budget. Only records which changed
+    /// since the last autosave are written, unless the archive has built
+    /// up enough superseded records to be worth rewriting whole.
+    fn step_autosave(&mut self) {
+        let interval = self.settings.autosave_interval_secs;
+        // an observer's universe is the host's to save
+        if !self.settings.compressed_saves || interval == 0 || self.network.is_observing() {
+            self.autosave = None;
+            return;
+        }
+
+        if self.autosave.is_none() {
+            if self.wall_time - self.last_autosave < Nanotime::secs(interval as i64) {
+                return;
+            }
+            self.last_autosave = self.wall_time;
+            let path = self.args.autosave_path();
+            let records = self.save_records();
+            // archives which are mostly superseded records are rewritten
+            // from scratch, under the same budget
+            let begun = match SaveArchive::open(&path) {
+                Ok(archive) if archive.wasted_bytes() < archive.file_len() / 2 => {
+                    let save = archive.begin_save(records);
+                    Ok((archive, save))
+                }
+                _ => SaveArchive::begin_full(&path, records),
+            };
+            match begun {
+                Ok(autosave) => self.autosave = Some(autosave),
+                Err(e) => {
+                    error!("Failed to autosave: {e}");
+                    return;
+                }
+            }
+        }
+
+        let Some((archive, save)) = self.autosave.as_mut() else {
+            return;
+        };
+        let budget = std::time::Duration::from_millis(self.settings.autosave_budget_ms as u64);
+        match save.step(archive, budget) {
+            Ok(false) => (),
+            Ok(true) => self.autosave = None,
+            Err(e) => {
+                error!("Failed to autosave: {e}");
+                self.autosave = None;
+            }
+        }
+    }
+
+    fn export_scenario(&mut self) -> Option<()> {
+        let path = self.args.scenario_path();
+        let scenario = &self.scenario_context.scenario;
+        if let Err(e) = write_scenario_to_file(scenario, &path) {
+            error!("Failed to export scenario: {e}");
+            return None;
+        }
+        self.notice(format!("Exported scenario to {}", path.display()));
+        Some(())
+    }
+
+    fn import_scenario(&mut self) -> Option<()> {
+        let path = self.args.scenario_path();
+        match load_scenario_from_file(&path) {
+            Ok(mut scenario) => {
+                if let Err(e) = resolve_scenario_system(&mut scenario, &self.planet_catalogs) {
+                    self.notice(format!("Failed to import scenario: {e}"));
+                    return None;
+                }
+                self.scenario_context.scenario = scenario;
+                self.scenario_context.selected = None;
+                self.notice(format!("Imported scenario from {}", path.display()));
+                Some(())
+            }
+            Err(e) => {
+                self.notice(format!("Failed to import scenario: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Replaces the universe with a fresh one built from the scenario in
+    /// the scenario editor, and switches to the orbital view.
+    fn play_scenario(&mut self) -> Option<()> {
+        let universe = match self.scenario_context.scenario.to_universe() {
+            Ok(u) => u,
+            Err(e) => {
+                self.notice(format!("Can't play scenario: {e}"));
+                return None;
+            }
+        };
+        self.universe = universe;
+        self.universe.recycle_bin.retention =
+            Nanotime::secs(self.settings.deleted_vehicle_retention as i64);
+        self.universe.maneuvers.auto_hold = self.settings.burn_attitude_hold;
+        self.universe.scatter_salvage = self.settings.crash_salvage;
+        self.events = self.universe.events.subscribe();
+        self.checkpoints = CheckpointBuffer::new(Nanotime::secs(5), 120);
+        let root = self.scenario_context.scenario.root()?.id;
+        self.orbital_context = OrbitalContext::new(root);
+        self.normalize_on_load = true;
+        self.notice(format!(
+            "Started scenario {}",
+            self.scenario_context.scenario.name
+        ));
+        self.set_current_scene(SceneType::Orbital)
+    }
+
+    /// Replaces the universe with one picked back up from a save.
+    fn resume_universe(&mut self, storage: UniverseFileStorage) -> Option<()> {
+        let rendezvous: Vec<_> = storage
+            .vehicles
+            .iter()
+            .filter_map(|v| Some((v.id, v.rendezvous?)))
+            .collect();
+        let (universe, failed) = match Universe::from_file_storage(storage, &self.part_database) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.notice(format!("Failed to load universe: {e}"));
+                return None;
+            }
+        };
+        if !failed.is_empty() {
+            self.notice(format!(
+                "{} vehicles use parts which no longer exist and were left out",
+                failed.len()
+            ));
+        }
+        let root = universe.planets.id;
+        self.universe = universe;
+        self.universe.recycle_bin.retention =
+            Nanotime::secs(self.settings.deleted_vehicle_retention as i64);
+        self.universe.maneuvers.auto_hold = self.settings.burn_attitude_hold;
+        self.universe.scatter_salvage = self.settings.crash_salvage;
+        self.events = self.universe.events.subscribe();
+        self.checkpoints = CheckpointBuffer::new(Nanotime::secs(5), 120);
+        self.orbital_context = OrbitalContext::new(root);
+
+        // rendezvous are planned afresh on load, which doesn't always work
+        for (id, target) in rendezvous {
+            if self.universe.surface_vehicles.contains_key(&id)
+                && !self.universe.rendezvous.contains_key(&id)
+            {
+                let event = UniverseEvent::RendezvousAborted(id, target, RendezvousAbort::NoPlan);
+                self.universe.events.emit(self.universe.stamp(), event);
+            }
+        }
+        Some(())
+    }
+
+    fn save_universe(&mut self) -> Option<()> {
+        let path = self.args.universe_path();
+        let storage = self.universe_storage();
+        if let Err(e) = write_universe_to_file(&storage, &path) {
+            error!("Failed to save universe: {e}");
+            return None;
+        }
+        self.notice(format!(
+            "Saved {} vehicles to {}",
+            storage.vehicles.len(),
+            path.display()
+        ));
+        Some(())
+    }
+
+    fn load_universe(&mut self) -> Option<()> {
+        let path = self.args.universe_path();
+        match load_universe_from_file(&path) {
+            Ok(storage) => {
+                self.resume_universe(storage)?;
+                self.pinned_selection.clear();
+                self.normalize_on_load = true;
+                self.notice(format!(
+                    "Loaded {} vehicles",
+                    self.universe.surface_vehicles.len()
+                ));
+                Some(())
+            }
+            Err(e) => {
+                error!("Failed to load universe: {e}");
+                None
+            }
+        }
+    }
+
+    fn save_structures(&mut self) -> Option<()> {
+        let path = self.args.structures_path();
+        let mut structures: Vec<_> = self
+            .universe
+            .structures
+            .iter()
+            .map(|(id, s)| (*id, s.clone()))
+            .collect();
+        structures.sort_by_key(|(id, _)| *id);
+        if let Err(e) = write_structures_to_file(&structures, &path) {
+            error!("Failed to save structures: {e}");
+            return None;
+        }
+        self.notice(format!(
+            "Saved {} structures to {}",
+            structures.len(),
+            path.display()
+        ));
+        Some(())
+    }
+
+    fn load_structures(&mut self) -> Option<()> {
+        let path = self.args.structures_path();
+        match load_structures_from_file(&path) {
+            Ok(s) => {
+                self.universe.replace_structures(s);
+                self.orbital_context.inspected_structure = None;
+                self.notice(format!(
+                    "Loaded {} structures",
+                    self.universe.structures.len()
+                ));
+                Some(())
+            }
+            Err(e) => {
+                error!("Failed to load structures: {e}");
+                None
+            }
+        }
+    }
+
+    fn save_terrain(&mut self) -> Option<()> {
+        let path = self.args.terrain_path();
+        let terrain: Vec<_> = self.universe.terrain.values().cloned().collect();
+        if let Err(e) = write_terrain_to_file(&terrain, &path) {
+            error!("Failed to save terrain: {e}");
+            return None;
+        }
+        Some(())
+    }
+
+    fn load_terrain(&mut self) -> Option<()> {
+        let path = self.args.terrain_path();
+        match load_terrain_from_file(&path) {
+            Ok(t) => {
+                self.universe.replace_terrain(t);
+                Some(())
+            }
+            Err(e) => {
+                error!("Failed to load terrain: {e}");
+                None
+            }
+        }
+    }
+
+    fn save_waypoints(&mut self) -> Option<()> {
+        let path = self.args.waypoints_path();
+        let mut waypoints: Vec<_> = self
+            .universe
+            .waypoints
+            .iter()
+            .map(|(id, w)| (*id, w.clone()))
+            .collect();
+        waypoints.sort_by_key(|(id, _)| *id);
+        if let Err(e) = write_waypoints_to_file(&waypoints, &path) {
+            error!("Failed to save waypoints: {e}");
+            return None;
+        }
+        self.notice(format!(
+            "Saved {} waypoints to {}",
+            waypoints.len(),
+            path.display()
+        ));
+        Some(())
+    }
+
+    fn load_waypoints(&mut self) -> Option<()> {
+        let path = self.args.waypoints_path();
+        match load_waypoints_from_file(&path) {
+            Ok(w) => {
+                self.universe.replace_waypoints(w);
+                self.notice(format!(
+                    "Loaded {} waypoints",
+                    self.universe.waypoints.len()
+                ));
+                Some(())
+            }
+            Err(e) => {
+                error!("Failed to load waypoints: {e}");
+                None
+            }
+        }
+    }
+
+    /// Places a waypoint at a world position, around the planet with the
+    /// smallest sphere of influence containing it. Waypoints placed close
+    /// to the ground are put on it.
+    pub fn place_waypoint(&mut self, name: Option<String>, pos: DVec2) -> Option<EntityId> {
+        let stamp = self.universe.stamp();
+        let planets = &self.universe.planets;
+        let (planet, body, origin) = planets
+            .planet_ids()
+            .into_iter()
+            .filter_map(|id| {
+                let (body, pv, _, _) = planets.lookup(id, stamp)?;
+                Some((id, body, pv.pos))
+            })
+            .filter(|(_, body, p)| p.distance(pos) < body.soi)
+            .min_by(|a, b| a.1.soi.total_cmp(&b.1.soi))?;
+        let name = name.unwrap_or_else(|| format!("WP-{}", self.universe.waypoints.len() + 1));
+        let snap = WAYPOINT_SNAP_PIXELS / self.orbital_context.scale();
+        let waypoint = Waypoint::at(name, planet, &body, pos - origin, snap);
+        let id = self.universe.add_waypoint(waypoint)?;
+        self.notice(format!(
+            "Placed waypoint {}",
+            self.universe.waypoints[&id].name
+        ));
+        Some(id)
+    }
+
+    fn save_timeline_notes(&mut self) -> Option<()> {
+        let path = self.args.timeline_notes_path();
+        if let Err(e) = write_timeline_notes_to_file(&self.timeline_notes, &path) {
+            error!("Failed to save timeline notes: {e}");
+            return None;
+        }
+        self.notice(format!(
+            "Saved {} timeline notes to {}",
+            self.timeline_notes.len(),
+            path.display()
+        ));
+        Some(())
+    }
+
+    fn load_timeline_notes(&mut self) -> Option<()> {
+        let path = self.args.timeline_notes_path();
+        match load_timeline_notes_from_file(&path) {
+            Ok(notes) => {
+                self.timeline_notes = notes;
+                self.sync_timeline_note_alarms();
+                self.notice(format!(
+                    "Loaded {} timeline notes",
+                    self.timeline_notes.len()
+                ));
+                Some(())
+            }
+            Err(e) => {
+                error!("Failed to load timeline notes: {e}");
+                None
+            }
+        }
+    }
+
+    fn save_annotations(&mut self) -> Option<()> {
+        let path = self.args.annotations_path();
+        if let Err(e) = write_annotations_to_file(&self.annotations, &path) {
+            error!("Failed to save annotations: {e}");
+            return None;
+        }
+        self.notice(format!(
+            "Saved {} annotations to {}",
+            self.annotations.len(),
+            path.display()
+        ));
+        Some(())
+    }
+
+    fn load_annotations(&mut self) -> Option<()> {
+        let path = self.args.annotations_path();
+        match load_annotations_from_file(&path) {
+            Ok(a) => {
+                self.annotations = a;
+                self.pinned_selection.clear();
+                self.notice(format!("Loaded {} annotations", self.annotations.len()));
+                Some(())
+            }
+            Err(e) => {
+                error!("Failed to load annotations: {e}");
+                None
+            }
+        }
+    }
+
+    /// Pins the measuring tape or protractor currently being dragged as a
+    /// persistent annotation. Endpoints near an orbiter or planet are
+    /// anchored to it and follow it around.
+    pub fn pin_measurement(&mut self) -> Option<()> {
+        let radius = 20.0 / self.orbital_context.scale();
+        let snap = |p: DVec2| AnnotationAnchor::snap(p, &self.universe, radius);
+        let kind = if let Some((a, b, _)) = self.measuring_tape() {
+            AnnotationKind::Distance(snap(a), snap(b))
+        } else {
+            let (c, a, b) = self.protractor()?;
+            AnnotationKind::Angle {
+                center: snap(c),
+                a: snap(a),
+                b: snap(b?),
+            }
+        };
+        self.annotations.push(Annotation::new(kind));
+        self.notice(format!(
+            "Pinned {} annotation",
+            kind.to_string().to_lowercase()
+        ));
+        Some(())
+    }
+
     pub fn on_button_event(&mut self, id: OnClick) -> Option<()> {
         self.sounds.play_once("button-up.ogg", 1.0);
 
+        if !self.spectator.allows_click(&id) {
+            self.notice("Not available in spectator mode");
+            return None;
+        }
+
         match id {
             OnClick::CurrentBody(id) => self.orbital_context.following = Some(id),
             OnClick::Orbiter(id) => self.orbital_context.following = Some(id),
+            OnClick::CenterCamera(id) => {
+                self.orbital_context.following = Some(id);
+                self.orbital_context.camera.clear_offset();
+            }
             OnClick::ToggleDrawMode => {
                 self.orbital_context.draw_mode = next_cycle(&self.orbital_context.draw_mode)
             }
+            OnClick::ToggleRotatingFrame => {
+                self.orbital_context.rotating_frame = !self.orbital_context.rotating_frame
+            }
+            OnClick::TogglePhaseArcs => {
+                self.orbital_context.show_phase_arcs = !self.orbital_context.show_phase_arcs
+            }
+            OnClick::ToggleConicConstruction => {
+                let ctx = &mut self.orbital_context;
+                ctx.show_conic_construction = !ctx.show_conic_construction;
+            }
+            OnClick::SuggestAvoidanceBurn => _ = self.suggest_avoidance_burn(),
+            OnClick::AdjustOrbit(adjustment) => {
+                let id = self.piloting()?;
+                if let Err(e) = self.schedule_orbit_adjustment(id, adjustment) {
+                    self.notice(e);
+                }
+            }
+            OnClick::CommitManeuverNode => {
+                let id = self.piloting()?;
+                if let Err(e) = self.commit_maneuver_node(id) {
+                    self.notice(e);
+                }
+            }
+            OnClick::DeleteManeuverNode => {
+                let id = self.piloting()?;
+                self.orbital_context.maneuver_nodes.remove(&id);
+            }
+            OnClick::LaunchToOrbit => {
+                let profile = self.ascent_profile(self.piloting()?)?;
+                self.set_controller_policy(VehicleControlPolicy::LaunchToOrbit(profile));
+            }
+            OnClick::AdjustAscent(param, steps) => {
+                let id = self.piloting()?;
+                let mut profile = self.ascent_profile(id)?;
+                profile.adjust(param, steps);
+                self.set_ascent_profile(id, profile);
+            }
+            OnClick::QueueServiceTask(task) => {
+                let id = self.landing_site_id()?;
+                let pad = self.universe.landing_pads.get_mut(&id)?;
+                if !pad.queue_task(task) {
+                    let s = format!("Can't queue \"{}\" at {}", task, pad.name);
+                    self.notice(s);
+                }
+            }
+            OnClick::CancelServiceTask(i) => {
+                let id = self.landing_site_id()?;
+                self.universe.landing_pads.get_mut(&id)?.cancel_task(i);
+            }
+            OnClick::InsureVehicle(id) => {
+                let value = vehicle_value(self.universe.surface_vehicles.get(&id)?.vehicle());
+                let stamp = self.universe.stamp();
+                match self.ledger.insure(id, value, stamp) {
+                    Ok(()) => {
+                        self.notice(format!("Insured {} for this mission", self.callsign(id)))
+                    }
+                    Err(e) => self.notice(e),
+                }
+            }
+            OnClick::RebuildLostVehicle(i) => _ = self.rebuild_lost_vehicle(i),
+            OnClick::ResetAscent => {
+                let id = self.piloting()?;
+                self.orbital_context.ascent_profiles.remove(&id);
+                let profile = self.ascent_profile(id)?;
+                self.set_ascent_profile(id, profile);
+            }
             OnClick::ClearTracks => self.orbital_context.selected.clear(),
             OnClick::ClearOrbits => self.orbital_context.queued_orbits.clear(),
             OnClick::Group(gid) => self.toggle_group(gid),
             OnClick::CreateGroup => {
-                // let id = self.ids.next();
-                // self.create_group(id);
-                println!("todo!");
+                if let Some(gid) = self.create_group() {
+                    self.orbital_context.inspected_group = Some(gid);
+                }
+            }
+            OnClick::HandOverSelected(target) => {
+                let ids = self.orbital_context.selected.iter().copied().collect();
+                if let Err(e) = self.request_handover(Handover { ids, target }) {
+                    self.notice(e);
+                }
+            }
+            OnClick::ConfirmHandover => self.confirm_handover(),
+            OnClick::DismissHandover => self.pending_handover = None,
+            OnClick::ApplySavedFilter(i) => self.apply_saved_filter(i),
+            OnClick::TogglePinnedFilter(i) => {
+                if let Some(f) = self.saved_filters.get_mut(i) {
+                    f.pinned = !f.pinned;
+                    self.save_filters();
+                }
+            }
+            OnClick::DeleteSavedFilter(i) => {
+                if i < self.saved_filters.len() {
+                    self.saved_filters.remove(i);
+                    self.save_filters();
+                }
+            }
+            OnClick::InspectGroup(gid) => {
+                let ctx = &mut self.orbital_context;
+                ctx.inspected_group = (ctx.inspected_group != Some(gid)).then_some(gid);
+            }
+            OnClick::SetGroupColor(gid, i) => {
+                self.group_styles.entry(gid).or_default().color = Some(i);
+            }
+            OnClick::RenameGroup(gid) => {
+                self.console.show();
+                self.console.set_cmd(format!("group {} --name ", gid));
+            }
+            OnClick::MergeGroups(into, from) => self.merge_groups(into, from),
+            OnClick::SplitGroup(gid) => {
+                if self.split_group(gid).is_none() {
+                    self.notice("Select some, but not all, members of the group to split");
+                }
             }
             OnClick::DisbandGroup(gid) => self.disband_group(gid),
             OnClick::CommitMission => {
@@ -832,6 +2696,71 @@ impl GameState {
                 self.orbital_context.following = Some(orbit.0);
                 self.current_orbit = Some(i);
             }
+            OnClick::ToggleMusic => {
+                self.settings.music.enabled = !self.settings.music.enabled;
+                self.save_settings();
+            }
+            OnClick::CycleDoubleClick(target) => {
+                self.settings.double_click.cycle(target);
+                self.save_settings();
+            }
+            OnClick::ToggleBurnAutoHold => {
+                self.settings.burn_attitude_hold = !self.settings.burn_attitude_hold;
+                self.universe.maneuvers.auto_hold = self.settings.burn_attitude_hold;
+                self.save_settings();
+            }
+            OnClick::ActivateStage(id) => {
+                if let Err(e) = self.universe.activate_stage(id) {
+                    self.notice(format!("Can't stage {}: {}", self.callsign(id), e));
+                }
+            }
+            OnClick::CopyPlan => {
+                if let Err(e) = self.copy_plan(None) {
+                    self.notice(e);
+                }
+            }
+            OnClick::PastePlan => {
+                let interval = Nanotime::secs(self.settings.shared_plan_stagger_secs as i64);
+                if let Err(e) = self.paste_plan(interval) {
+                    self.notice(e);
+                }
+            }
+            OnClick::ToggleOrbitHistory => {
+                let ctx = &mut self.orbital_context;
+                ctx.show_orbit_history = !ctx.show_orbit_history;
+                if !ctx.show_orbit_history {
+                    ctx.history_scrub = None;
+                }
+            }
+            OnClick::ToggleSpectator => self.set_spectating(!self.spectator.is_active()),
+            OnClick::ToggleDirector => {
+                let on = !self.spectator.is_directing();
+                self.spectator.set_director(on);
+            }
+            OnClick::ToggleDvMap => {
+                let ctx = &mut self.orbital_context;
+                ctx.show_dv_map = !ctx.show_dv_map;
+            }
+            OnClick::ToggleRecords => {
+                let ctx = &mut self.orbital_context;
+                ctx.show_records = !ctx.show_records;
+            }
+            OnClick::ScrubOrbitHistory(steps) => self.scrub_orbit_history(steps),
+            OnClick::ScrubToOrbitRecord(i) => {
+                let start = self
+                    .orbit_history_subject()
+                    .and_then(|(_, h)| h.records().nth(i))
+                    .map(|r| r.start);
+                if let Some(start) = start {
+                    self.orbital_context.history_scrub = Some(start);
+                }
+            }
+            OnClick::ClearOrbitScrub => self.orbital_context.history_scrub = None,
+            OnClick::CycleColorPalette => {
+                self.settings.color_palette = self.settings.color_palette.next();
+                self.save_settings();
+                self.notice(format!("Color palette: {}", self.settings.color_palette));
+            }
             OnClick::Nullopt => (),
             OnClick::Save => {
                 self.save();
@@ -839,57 +2768,171 @@ impl GameState {
             OnClick::Load => {
                 self.load();
             }
+            OnClick::SaveToLibrary => {
+                let model = self.editor_context.vehicle.model().to_string();
+                self.console.show();
+                self.console.set_cmd(format!("savevehicle {}", model));
+            }
+            OnClick::RefreshVehicles => {
+                self.vehicles.refresh(&self.part_database);
+                self.editor_context.gallery = None;
+            }
+            OnClick::ImportVehicles => {
+                let dir = rfd::FileDialog::new().set_directory("/").pick_folder()?;
+                match self.start_vehicle_import(&dir, false) {
+                    Ok(n) => self.notice(format!("Importing {} vehicle files", n)),
+                    Err(e) => self.notice(e),
+                }
+            }
             OnClick::CursorMode(c) => self.orbital_context.cursor_mode = c,
             OnClick::GoToScene(s) => {
+                self.close_pause_menu();
                 self.set_current_scene(s);
             }
             OnClick::ClearPilot => self.orbital_context.piloting = None,
             OnClick::ClearTarget => {
-                if let Some(p) = self.piloting() {
-                    if let Some(sv) = self.universe.surface_vehicles.get_mut(&p) {
-                        sv.set_target(None);
-                    }
-                }
+                let p = self.piloting()?;
+                self.apply_or_queue(p, PausedAction::SetTarget(None));
             }
             OnClick::SetPilot(p) => self.orbital_context.piloting = Some(p),
             OnClick::SetTarget(t) => {
-                if let Some(p) = self.piloting() {
-                    if let Some(sv) = self.universe.surface_vehicles.get_mut(&p) {
-                        sv.set_target(t);
-                    }
-                }
+                let p = self.piloting()?;
+                self.apply_or_queue(p, PausedAction::SetTarget(Some(t)));
+            }
+            OnClick::SelectPart(name) => {
+                self.editor_context.part_search.unfocus();
+                EditorContext::set_current_part(self, &name);
             }
-            OnClick::SelectPart(name) => EditorContext::set_current_part(self, &name),
             OnClick::ToggleLayer(layer) => self.editor_context.toggle_layer(layer),
             OnClick::LoadVehicle(path) => _ = EditorContext::load_vehicle(&path, self),
+            OnClick::CompareVehicle(path) => _ = EditorContext::load_comparison(&path, self),
+            OnClick::ClearComparison => self.editor_context.comparison = None,
             OnClick::ConfirmExitDialog => self.shutdown(),
             OnClick::DismissExitDialog => self.is_exit_prompt = false,
+            OnClick::ResumeGame => self.close_pause_menu(),
             OnClick::TogglePartsMenuCollapsed => {
-                self.editor_context.parts_menu_collapsed = !self.editor_context.parts_menu_collapsed
+                self.editor_context.parts_menu.toggle(self.wall_time)
             }
+            OnClick::FocusPartSearch => self.editor_context.part_search.focus(),
+            OnClick::PartCategory(layer) => self.editor_context.part_search.set_layer(layer),
             OnClick::ToggleVehiclesMenuCollapsed => {
-                self.editor_context.vehicles_menu_collapsed =
-                    !self.editor_context.vehicles_menu_collapsed
+                self.editor_context.vehicles_menu.toggle(self.wall_time);
+                if !self.editor_context.vehicles_menu.is_collapsed() {
+                    self.refresh_vehicles();
+                }
             }
             OnClick::ToggleLayersMenuCollapsed => {
-                self.editor_context.layers_menu_collapsed =
-                    !self.editor_context.layers_menu_collapsed
+                self.editor_context.layers_menu.toggle(self.wall_time)
+            }
+            OnClick::ToggleStagesMenuCollapsed => {
+                self.editor_context.stages_menu.toggle(self.wall_time)
+            }
+            OnClick::SelectEditorStage(stage) => self.editor_context.stage = stage,
+            OnClick::MoveEditorStage(stage, later) => {
+                self.editor_context.move_stage(stage, later);
+            }
+            OnClick::SetPartStage(id, stage) => {
+                self.editor_context.vehicle.set_part_stage(id, stage);
             }
+            OnClick::ToggleAssembliesMenuCollapsed => {
+                self.editor_context.assemblies_menu.toggle(self.wall_time);
+                if !self.editor_context.assemblies_menu.is_collapsed() {
+                    self.editor_context.assemblies = None;
+                }
+            }
+            OnClick::SelectAssembly(name) => self.editor_context.set_current_assembly(&name),
+            OnClick::SaveAssembly => EditorContext::save_assembly(self, None)?,
+            OnClick::ClearEditorSelection => self.editor_context.clear_selection(),
+            OnClick::RoutePipeFrom(id) => self.editor_context.start_pipe_route(id),
+            OnClick::AcceptPipeRoute => self.editor_context.accept_pipe_route()?,
+            OnClick::CancelPipeRoute => self.editor_context.cancel_pipe_route(),
             OnClick::OpenNewCraft => {
+                EditorContext::capture_recovery(self);
                 self.editor_context.new_craft();
             }
+            OnClick::ToggleEditorRecovery => {
+                self.editor_context.show_recovery = !self.editor_context.show_recovery;
+            }
+            OnClick::RestoreEditorVersion(i) => EditorContext::restore_recovery(self, i)?,
+            OnClick::DiscardEditorRecovery => EditorContext::discard_recovery(self),
             OnClick::WriteVehicleToImage => {
                 self.editor_context.write_image_to_file(&self.args);
             }
+            OnClick::WriteVehicleToSvg => match self.editor_context.write_svg_to_file() {
+                Some(()) => self.notice("Wrote vehicle diagram to /tmp/vehicle.svg"),
+                None => self.notice("Failed to write vehicle diagram"),
+            },
             OnClick::RotateCraft => {
                 self.editor_context.rotate_craft();
             }
             OnClick::ToggleVehicleInfo => {
                 self.editor_context.show_vehicle_info = !self.editor_context.show_vehicle_info;
             }
+            OnClick::ToggleFlowPreview => {
+                self.editor_context.show_flow_preview = !self.editor_context.show_flow_preview;
+            }
+            OnClick::CycleSymmetry => self.editor_context.cycle_symmetry(),
+            OnClick::ToggleHudEditor => self.hud_edit = !self.hud_edit,
+            OnClick::ToggleRailPanel(i) => {
+                let ctx = &mut self.orbital_context;
+                ctx.rail_panel = (ctx.rail_panel != Some(i)).then_some(i);
+            }
+            OnClick::ScenarioTool(t) => self.scenario_context.tool = t,
+            OnClick::SelectScenarioBody(id) => self.scenario_context.selected = Some(id),
+            OnClick::AdjustScenarioBody(id, param, dir) => {
+                self.scenario_context.adjust(id, param, dir)?;
+            }
+            OnClick::ToggleScenarioRetrograde(id) => {
+                self.scenario_context.toggle_retrograde(id)?;
+            }
+            OnClick::DeleteScenarioBody(id) => self.scenario_context.remove_body(id),
+            OnClick::DeleteScenarioSite(i) => {
+                let sites = &mut self.scenario_context.scenario.sites;
+                if i < sites.len() {
+                    sites.remove(i);
+                }
+            }
+            OnClick::DeleteScenarioVehicle(i) => {
+                let vehicles = &mut self.scenario_context.scenario.vehicles;
+                if i < vehicles.len() {
+                    vehicles.remove(i);
+                }
+            }
+            OnClick::DeleteScenarioEvent(i) => {
+                let events = &mut self.scenario_context.scenario.events;
+                if i < events.len() {
+                    events.remove(i);
+                }
+            }
+            OnClick::CycleScenarioModel => {
+                let models: Vec<_> = self
+                    .vehicles
+                    .listings()
+                    .iter()
+                    .map(|l| l.model.clone())
+                    .collect();
+                self.scenario_context.cycle_model(&models);
+            }
+            OnClick::ScenarioFromUniverse => {
+                let ctx = &mut self.scenario_context;
+                ctx.scenario =
+                    Scenario::from_system(ctx.scenario.name.clone(), &self.universe.planets);
+                ctx.selected = None;
+            }
+            OnClick::PlayScenario => self.play_scenario()?,
+            OnClick::ToggleHudWidget(w) => {
+                if let Some(hud) = self.settings.hud.get_mut(self.scene) {
+                    let p = hud.get_mut(w);
+                    p.visible = !p.visible;
+                    self.save_settings();
+                }
+            }
+            // widgets are moved by dragging their handles
+            OnClick::HudWidgetHandle(_) => (),
             OnClick::SendToSurface(e) => {
                 let mut vehicle = self.editor_context.vehicle.clone();
                 vehicle.build_all();
+                self.apply_loadout(&mut vehicle, &self.editor_context.loadouts);
                 let name = get_random_ship_name(&self.vehicle_names);
                 vehicle.set_name(name);
                 self.universe.add_surface_vehicle(
@@ -900,6 +2943,78 @@ impl GameState {
                 );
             }
             OnClick::NormalizeCraft => self.editor_context.normalize_coordinates(),
+            OnClick::SpawnPracticeTarget => _ = self.spawn_practice_target(),
+            OnClick::UndoDelete(id) => _ = self.undelete_orbiter(id),
+            OnClick::ToggleCaptureAssist(id) => {
+                let sv = self.universe.surface_vehicles.get_mut(&id)?;
+                sv.capture_assist = !sv.capture_assist;
+            }
+            OnClick::AddWaypoint => {
+                let pos = self.orbital_context.origin();
+                self.place_waypoint(None, pos);
+            }
+            OnClick::DeleteWaypoint(id) => {
+                self.universe.remove_waypoint(id);
+            }
+            OnClick::ToggleAnnotation(i) => {
+                if self.input.is_pressed(KeyCode::ShiftLeft) {
+                    self.pinned_selection.extend_to(i);
+                } else {
+                    let a = self.annotations.get_mut(i)?;
+                    a.visible = !a.visible;
+                }
+            }
+            OnClick::SelectPinned(i) => {
+                let extend = self.input.is_pressed(KeyCode::ShiftLeft);
+                self.pinned_selection.click(i, extend);
+            }
+            OnClick::ClearPinnedSelection => self.pinned_selection.clear(),
+            OnClick::UnpinSelected => self.unpin_selected(),
+            OnClick::TogglePinnedVisibility => {
+                let selected: Vec<_> = self.pinned_selection.iter().collect();
+                let show = selected
+                    .iter()
+                    .all(|i| self.annotations.get(*i).is_none_or(|a| !a.visible));
+                for i in selected {
+                    if let Some(a) = self.annotations.get_mut(i) {
+                        a.visible = show;
+                    }
+                }
+            }
+            OnClick::SelectPinnedVehicles => {
+                let ids = self.pinned_vehicles();
+                self.orbital_context.selected.extend(ids);
+            }
+            OnClick::GroupPinnedVehicles => {
+                let ids = self.pinned_vehicles();
+                if !ids.is_empty() {
+                    let gid = self.universe.create_group(ids);
+                    self.notice(format!("Created {}", self.group_name(gid)));
+                }
+            }
+            OnClick::DeleteAlarm(i) => _ = self.alarms.remove(i),
+            OnClick::CycleAlarmAction(i) => {
+                let a = self.alarms.get_mut(i)?;
+                a.action = next_cycle(&a.action);
+            }
+            OnClick::AddTimelineNote => {
+                self.console.show();
+                self.console.set_cmd("note ");
+            }
+            OnClick::DeleteTimelineNote(i) => {
+                self.timeline_notes.remove(i);
+                self.sync_timeline_note_alarms();
+            }
+            OnClick::CycleTimelineNoteAlarm(i) => {
+                self.timeline_notes.get_mut(i)?.cycle_alarm();
+                self.sync_timeline_note_alarms();
+            }
+            OnClick::DeleteAnnotation(i) => {
+                if i < self.annotations.len() {
+                    self.annotations.remove(i);
+                    self.pinned_selection.on_removed(i);
+                }
+            }
             OnClick::SwapOwnshipTarget => _ = self.swap_ownship_target(),
             OnClick::ReloadGame => _ = self.reload(),
             OnClick::SetRecipe(id, recipe) => {
@@ -912,31 +3027,661 @@ impl GameState {
                     ));
                 }
             }
-            OnClick::ClearContents(id) => {
-                if self.editor_context.vehicle.clear_contents(id) {
-                    self.notice(format!("Cleared inventory for part {:?}", id));
-                } else {
-                    self.notice(format!("Failed to clear inventory for part {:?}", id));
-                }
+            OnClick::ClearContents(id) => {
+                if self.editor_context.vehicle.clear_contents(id) {
+                    self.notice(format!("Cleared inventory for part {:?}", id));
+                } else {
+                    self.notice(format!("Failed to clear inventory for part {:?}", id));
+                }
+            }
+            OnClick::SetControllerPolicy(policy) => {
+                self.set_controller_policy(policy);
+            }
+            OnClick::CycleStructureKind(id) => {
+                let s = self.universe.structures.get_mut(&id)?;
+                s.set_kind(s.kind.next());
+            }
+            OnClick::ResizeStructure(id, dw, dh) => {
+                let s = self.universe.structures.get_mut(&id)?;
+                s.resize(s.width + dw as f64, s.height + dh as f64);
+            }
+            OnClick::RepairStructure(id) => self.universe.structures.get_mut(&id)?.repair(),
+            OnClick::DemolishStructure(id) => {
+                self.universe.remove_structure(id)?;
+                self.orbital_context.inspected_structure = None;
+                self.notice(format!("Demolished structure {}", id));
+            }
+            OnClick::CloseStructureInspector => self.orbital_context.inspected_structure = None,
+            OnClick::RequestDocking => {
+                self.request_docking()?;
+            }
+            OnClick::CancelDocking(id) => {
+                self.universe.cancel_docking(id);
+                self.notice(format!("Cancelled docking for {}", self.callsign(id)));
+            }
+            OnClick::Undock(host, guest) => match self.universe.undock(host, guest) {
+                Ok(()) => self.notice(format!("{} undocked", self.callsign(guest))),
+                Err(e) => self.notice(e),
+            },
+            OnClick::Rendezvous => {
+                let p = self.piloting()?;
+                let t = self.universe.surface_vehicles.get(&p)?.target()?;
+                match self.universe.start_rendezvous(p, t) {
+                    Ok(()) => self.notice(format!(
+                        "{} heading for rendezvous with {}",
+                        self.callsign(p),
+                        self.callsign(t)
+                    )),
+                    Err(e) => self.notice(e),
+                }
+            }
+            OnClick::CancelRendezvous(id) => {
+                if self.universe.cancel_rendezvous(id) {
+                    self.notice(format!("Cancelled rendezvous for {}", self.callsign(id)));
+                }
+            }
+            OnClick::StartTransfer(from, to, item) => {
+                match self.universe.start_transfer(from, to, item) {
+                    Ok(()) => self.notice(format!(
+                        "Transferring {:?} from {} to {}",
+                        item,
+                        self.callsign(from),
+                        self.callsign(to)
+                    )),
+                    Err(e) => self.notice(e),
+                }
+            }
+            OnClick::CancelTransfer(a, b) => {
+                if self.universe.cancel_transfer(a, b) {
+                    self.notice(format!(
+                        "Stopped transfer between {} and {}",
+                        self.callsign(a),
+                        self.callsign(b)
+                    ));
+                }
+            }
+
+            // BOOKMARK unhandled event
+            _ => info!("Unhandled button event: {id:?}"),
+        };
+
+        Some(())
+    }
+
+    /// Queues the piloted vehicle and every other selected vehicle to dock
+    /// at the pilot's target, nearest first.
+    fn request_docking(&mut self) -> Option<()> {
+        let pilot = self.piloting()?;
+        let target = self.universe.surface_vehicles.get(&pilot)?.target()?;
+        let target_pos = self.universe.pv(target)?.pos;
+
+        let mut ids: Vec<(EntityId, f64)> = self
+            .orbital_context
+            .selected
+            .iter()
+            .chain(std::iter::once(&pilot))
+            .filter(|id| **id != target)
+            .filter_map(|id| Some((*id, self.universe.pv(*id)?.pos.distance(target_pos))))
+            .collect();
+        ids.sort_by(|a, b| a.1.total_cmp(&b.1));
+        ids.dedup_by_key(|(id, _)| *id);
+
+        let queued = ids
+            .iter()
+            .filter(|(id, _)| self.universe.request_docking(*id, target).is_some())
+            .count();
+        self.notice(format!(
+            "Queued {} vehicle(s) to dock at {}",
+            queued, target
+        ));
+        Some(())
+    }
+
+    pub fn set_controller_policy(&mut self, policy: VehicleControlPolicy) -> Option<()> {
+        let piloting = self.piloting()?;
+        self.apply_or_queue(piloting, PausedAction::SetPolicy(policy))
+    }
+
+    /// Applies a planning command to a vehicle, or if the simulation is
+    /// paused, queues it to take effect when the simulation resumes.
+    pub fn apply_or_queue(&mut self, id: EntityId, action: PausedAction) -> Option<()> {
+        if !self.universe.surface_vehicles.contains_key(&id) {
+            return None;
+        }
+        if self.paused {
+            self.notice(format!(
+                "Queued for {} on resume: {}",
+                self.callsign(id),
+                action
+            ));
+            self.paused_actions.push(id, action);
+            return Some(());
+        }
+        self.apply_paused_action(id, action)
+    }
+
+    fn apply_paused_action(&mut self, id: EntityId, action: PausedAction) -> Option<()> {
+        let sv = self.universe.surface_vehicles.get_mut(&id)?;
+        match action {
+            PausedAction::SetPolicy(policy) => sv.controller.set_policy(policy),
+            PausedAction::SetTarget(target) => sv.set_target(target),
+        }
+        Some(())
+    }
+
+    /// Accumulates statistics for the piloted vehicle over the last `dt` of
+    /// sim time, and announces any newly unlocked achievements.
+    fn update_stats(&mut self, dt: Nanotime) {
+        if let Some(id) = self.piloting() {
+            if let Some(sv) = self.universe.surface_vehicles.get(&id) {
+                self.stats.stats.distance_traveled += sv.body.pv.vel.length() * dt.to_secs_f64();
+                let dv = sv.vehicle.remaining_dv();
+                if let Some((last_id, last_dv)) = self.last_remaining_dv {
+                    if last_id == id && last_dv > dv {
+                        self.stats.stats.dv_expended += last_dv - dv;
+                    }
+                }
+                self.last_remaining_dv = Some((id, dv));
+            }
+        }
+
+        let fuel = self
+            .universe
+            .surface_vehicles
+            .iter()
+            .map(|(id, sv)| (*id, sv.vehicle.fuel_mass()));
+        self.session.update(self.universe.stamp(), fuel);
+        self.records.update(&self.universe);
+
+        let unlocked = self.stats.check_achievements();
+        for a in &unlocked {
+            self.notice(format!(
+                "Achievement unlocked: {} ({})",
+                a.name, a.description
+            ));
+            self.notify(None, NotificationType::AchievementUnlocked(a.name), None);
+        }
+
+        if !unlocked.is_empty() || self.wall_time - self.last_stats_save > Nanotime::secs(30) {
+            self.save_stats();
+            self.last_stats_save = self.wall_time;
+        }
+    }
+
+    pub fn add_alarm(&mut self, name: impl Into<String>, stamp: Nanotime, action: AlarmAction) {
+        self.alarms.add(Alarm {
+            name: name.into(),
+            stamp,
+            action,
+            source: AlarmSource::Manual,
+        });
+    }
+
+    pub fn add_timeline_note(&mut self, note: TimelineNote) {
+        self.timeline_notes.add(note);
+        self.sync_timeline_note_alarms();
+    }
+
+    /// Replaces the alarms set by timeline notes with one for each note
+    /// with an alarm that hasn't gone off yet.
+    pub fn sync_timeline_note_alarms(&mut self) {
+        let now = self.universe.stamp();
+        self.alarms.retain(|a| a.source != AlarmSource::Note);
+        for note in self.timeline_notes.iter().filter(|n| n.stamp > now) {
+            if let Some(action) = note.alarm {
+                self.alarms.add(Alarm {
+                    name: note.text.clone(),
+                    stamp: note.stamp,
+                    action,
+                    source: AlarmSource::Note,
+                });
             }
-            OnClick::SetControllerPolicy(policy) => {
-                self.set_controller_policy(policy);
+        }
+    }
+
+    /// Fires any alarms which have come due, then refreshes the automatic
+    /// transfer window alarm for the pilot's current target.
+    fn update_alarms(&mut self) {
+        for alarm in self.alarms.poll(self.universe.stamp()) {
+            match alarm.action {
+                AlarmAction::Notify => (),
+                AlarmAction::Pause => self.paused = true,
+                AlarmAction::RealTime => self.universe_ticks_per_game_tick = SimRate::RealTime,
             }
+            self.notify(None, NotificationType::AlarmTriggered(alarm.name), None);
+        }
 
-            // BOOKMARK unhandled event
-            _ => info!("Unhandled button event: {id:?}"),
+        let window = self
+            .settings
+            .transfer_window_alarms
+            .then(|| {
+                let target = self
+                    .universe
+                    .surface_vehicles
+                    .get(&self.piloting()?)?
+                    .target()?;
+                let t = self.phase_info()?.time_to_window?;
+                Some((target, self.universe.stamp() + t))
+            })
+            .flatten();
+
+        self.alarms.retain(|a| match (a.source, window) {
+            (AlarmSource::TransferWindow(id), Some((target, _))) => id == target,
+            (AlarmSource::TransferWindow(_), None) => false,
+            _ => true,
+        });
+
+        if let Some((target, stamp)) = window {
+            self.alarms.upsert(Alarm {
+                name: format!("Transfer window to {}", target),
+                stamp,
+                action: self.settings.default_alarm_action,
+                source: AlarmSource::TransferWindow(target),
+            });
+        }
+    }
+
+    /// Vehicles the player is paying attention to: the piloted and followed
+    /// vehicles, the pilot's target, selected vehicles, and vehicles which
+    /// anchor annotations.
+    pub fn focused_entities(&self) -> HashSet<EntityId> {
+        let ctx = &self.orbital_context;
+        let target = self
+            .piloting()
+            .and_then(|id| self.universe.surface_vehicles.get(&id)?.target());
+        let anchored = self.annotations.iter().flat_map(|a| a.anchored_entities());
+
+        [ctx.piloting, ctx.following, target]
+            .into_iter()
+            .flatten()
+            .chain(ctx.selected.iter().copied())
+            .chain(anchored)
+            .collect()
+    }
+
+    /// Extends predicted trajectories, far ahead for focused vehicles and
+    /// briefly for background traffic. Vehicles under thrust get the short
+    /// horizon, since their predictions are invalidated every tick.
+    fn update_predictions(&mut self) {
+        let focus = self.focused_entities();
+        let long = Nanotime::days(self.settings.focus_horizon_days as i64);
+        let short = Nanotime::hours(self.settings.background_horizon_hours as i64);
+        self.universe.propagate_orbiters(|id, sv| {
+            if focus.contains(&id) && !sv.vehicle.is_thrusting() {
+                long
+            } else {
+                short
+            }
+        });
+    }
+
+    fn update_telemetry(&mut self) {
+        let Some(id) = self.piloting() else {
+            self.telemetry.clear();
+            return;
+        };
+        let Some(sv) = self.universe.surface_vehicles.get(&id) else {
+            self.telemetry.clear();
+            return;
+        };
+        let Some((body, _, _, _)) = self
+            .universe
+            .planets
+            .lookup(sv.parent(), self.universe.stamp())
+        else {
+            return;
         };
+        let pv = sv.body.pv;
+        let altitude = pv.pos.length() - body.radius;
+        self.telemetry
+            .record(id, altitude, pv.vel.length(), self.wall_time);
+    }
 
+    /// Looks ahead along the piloted vehicle's trajectory for a collision,
+    /// sounding an alert which repeats faster as impact approaches.
+    fn update_collision_warning(&mut self) -> Option<()> {
+        self.collision_warning = None;
+        let sv = self.universe.surface_vehicles.get(&self.piloting()?)?;
+        if sv.is_landed() {
+            return None;
+        }
+
+        let stamp = self.universe.stamp();
+        let window = Nanotime::secs(self.settings.collision_warning_secs as i64);
+        let mut orbiter = Orbiter::new(sv.current_orbit()?, stamp);
+        orbiter
+            .propagate_to(stamp, window, &self.universe.planets)
+            .ok()?;
+        let impact = orbiter.collision_time()?;
+        self.collision_warning = Some(impact);
+
+        let urgency = self.collision_urgency()?;
+        let interval = Nanotime::secs_f64(2.0 - 1.75 * urgency);
+        if self.wall_time - self.last_collision_alert >= interval {
+            self.last_collision_alert = self.wall_time;
+            self.sounds
+                .play_once("soft-pulse-higher.ogg", 0.3 + 0.5 * urgency as f32);
+        }
         Some(())
     }
 
-    pub fn set_controller_policy(&mut self, policy: VehicleControlPolicy) -> Option<()> {
-        let piloting = self.piloting()?;
-        let sv = self.universe.surface_vehicles.get_mut(&piloting)?;
-        sv.controller.set_policy(policy);
+    /// What the soundtrack should be reacting to right now.
+    fn music_cue(&self) -> MusicCue {
+        let mut cue = MusicCue {
+            warning: self.collision_warning.is_some(),
+            ..MusicCue::default()
+        };
+        let Some(sv) = self
+            .piloting()
+            .and_then(|id| self.universe.surface_vehicles.get(&id))
+        else {
+            return cue;
+        };
+        cue.burning = sv.vehicle.is_thrusting();
+        let near_target = sv
+            .target_relative_pv
+            .is_some_and(|pv| pv.pos.length() < MUSIC_APPROACH_DISTANCE);
+        let descending = !sv.is_landed()
+            && sv.body.pv.vel.dot(sv.body.pv.pos) < 0.0
+            && self
+                .universe
+                .planets
+                .lookup(sv.parent(), self.universe.stamp())
+                .is_some_and(|(body, _, _, _)| {
+                    sv.body.pv.pos.length() - body.radius < MUSIC_APPROACH_ALTITUDE
+                });
+        cue.approaching = near_target || descending;
+        cue
+    }
+
+    fn update_music(&mut self) {
+        let cue = self.music_cue();
+        let settings = self.settings.music;
+        self.music.update(cue, &settings, self.wall_time);
+
+        let newest = self.notifications.iter().map(|n| n.wall_time).max();
+        let alerts = self.notifications.iter().any(|n| {
+            n.wall_time > self.last_music_notification
+                && matches!(n.kind.severity(), Severity::Warning | Severity::Danger)
+        });
+        if alerts {
+            self.music.alert(&settings, self.wall_time);
+        }
+        if let Some(t) = newest {
+            self.last_music_notification = self.last_music_notification.max(t);
+        }
+
+        if self.music.take_stinger() {
+            self.sounds.play_once(MUSIC_STINGER, settings.volume * 3.0);
+        }
+    }
+
+    /// How close an impending collision is, from 0 at the edge of the
+    /// warning window to 1 at impact.
+    pub fn collision_urgency(&self) -> Option<f64> {
+        let remaining = self.collision_warning? - self.universe.stamp();
+        let window = self.settings.collision_warning_secs.max(1) as f64;
+        Some((1.0 - remaining.to_secs_f64() / window).clamp(0.0, 1.0))
+    }
+
+    /// Queues the orbit resulting from the smallest burn, made now, that
+    /// raises the piloted vehicle's periapsis clear of the surface.
+    pub fn suggest_avoidance_burn(&mut self) -> Option<()> {
+        let sv = self.universe.surface_vehicles.get(&self.piloting()?)?;
+        let GlobalOrbit(parent, orbit) = sv.current_orbit()?;
+        let stamp = self.universe.stamp();
+        let min_periapsis = orbit.body.radius + AVOIDANCE_MARGIN;
+
+        let Some(dv) = periapsis_raise_burn(&orbit, stamp, min_periapsis) else {
+            self.notice("No avoidance burn found");
+            return None;
+        };
+
+        let pv = orbit.pv(stamp).ok()?;
+        let prograde = dv.dot(pv.vel.normalize_or_zero());
+        let radial = dv.dot(pv.pos.normalize_or_zero());
+        let new = SparseOrbit::from_pv(PV::from_f64(pv.pos, pv.vel + dv), orbit.body, stamp)?;
+
+        self.orbital_context
+            .queued_orbits
+            .push(GlobalOrbit(parent, new));
+        self.current_orbit = Some(self.orbital_context.queued_orbits.len() - 1);
+        self.notice(format!(
+            "Avoidance burn: {:0.1} m/s ({:0.1} prograde, {:0.1} radial)",
+            dv.length(),
+            prograde,
+            radial
+        ));
         Some(())
     }
 
+    /// Plan which makes the given adjustment to a vehicle's orbit, after
+    /// the burns of any plan already scheduled for it, along with the
+    /// cost of the adjustment alone.
+    pub fn plan_orbit_adjustment(
+        &self,
+        id: EntityId,
+        adjustment: OrbitAdjustment,
+    ) -> Result<(ManeuverPlan, f64), String> {
+        let sv = self
+            .universe
+            .surface_vehicles
+            .get(&id)
+            .ok_or(format!("No vehicle with id {}", id))?;
+        let GlobalOrbit(_, orbit) = sv.current_orbit().ok_or("Vehicle is not in orbit")?;
+        let stamp = self.universe.stamp();
+
+        let scheduled = self.universe.maneuvers.get(id);
+        let (after, start) = match scheduled {
+            Some(p) => (p.terminal, p.end().max(stamp)),
+            None => (orbit, stamp),
+        };
+        let adjusted = orbit_adjustment_plan(&after, start, adjustment)
+            .ok_or(format!("Can't {} from this orbit", adjustment))?;
+        let cost = adjusted.dv();
+        Ok((self.after_scheduled_plan(id, orbit, adjusted)?, cost))
+    }
+
+    /// Appends a plan to the burns still to come of the plan already
+    /// scheduled for a vehicle, if it has one.
+    fn after_scheduled_plan(
+        &self,
+        id: EntityId,
+        orbit: SparseOrbit,
+        plan: ManeuverPlan,
+    ) -> Result<ManeuverPlan, String> {
+        let stamp = self.universe.stamp();
+        let Some(scheduled) = self.universe.maneuvers.get(id) else {
+            return Ok(plan);
+        };
+        let initial = scheduled
+            .segment_at(stamp)
+            .map(|s| s.orbit)
+            .unwrap_or(orbit);
+        let dvs: Vec<_> = scheduled.future_dvs(stamp).chain(plan.dvs()).collect();
+        ManeuverPlan::new(stamp, initial, &dvs).ok_or("Failed to extend plan".into())
+    }
+
+    /// Plan carrying out a vehicle's maneuver node after the burns of any
+    /// plan already scheduled for it, along with the cost of the node
+    /// alone.
+    pub fn plan_maneuver_node(&self, id: EntityId) -> Result<(ManeuverPlan, f64), String> {
+        let node = self
+            .orbital_context
+            .maneuver_nodes
+            .get(&id)
+            .ok_or(format!("Vehicle {} has no maneuver node", id))?;
+        let sv = self
+            .universe
+            .surface_vehicles
+            .get(&id)
+            .ok_or(format!("No vehicle with id {}", id))?;
+        let GlobalOrbit(_, orbit) = sv.current_orbit().ok_or("Vehicle is not in orbit")?;
+        let (GlobalOrbit(_, after), start) = OrbitalContext::maneuver_node_base(&self.universe, id)
+            .ok_or("Vehicle is not in orbit")?;
+        let planned = node
+            .plan(start, &after)
+            .ok_or("Maneuver node is no longer ahead of the vehicle")?;
+        Ok((
+            self.after_scheduled_plan(id, orbit, planned)?,
+            node.magnitude(),
+        ))
+    }
+
+    /// Schedules the burn of a vehicle's maneuver node and removes the
+    /// node, if the vehicle has the delta-v for it.
+    pub fn commit_maneuver_node(&mut self, id: EntityId) -> Result<(), String> {
+        let (plan, cost) = self.plan_maneuver_node(id)?;
+        let remaining = self
+            .universe
+            .surface_vehicles
+            .get(&id)
+            .map(|sv| sv.vehicle().remaining_dv())
+            .unwrap_or(0.0);
+        if plan.dv() > remaining {
+            return Err(format!(
+                "Not enough delta-v: maneuver needs {:0.1} m/s, {:0.1} m/s remaining",
+                plan.dv(),
+                remaining
+            ));
+        }
+        self.universe.maneuvers.schedule(id, plan);
+        self.orbital_context.maneuver_nodes.remove(&id);
+        self.notice(format!(
+            "{:0.1} m/s maneuver scheduled for {}",
+            cost,
+            self.callsign(id)
+        ));
+        Ok(())
+    }
+
+    /// Adds the burn for an orbit adjustment to a vehicle's scheduled
+    /// maneuvers, if it has the delta-v for it.
+    pub fn schedule_orbit_adjustment(
+        &mut self,
+        id: EntityId,
+        adjustment: OrbitAdjustment,
+    ) -> Result<(), String> {
+        let (plan, cost) = self.plan_orbit_adjustment(id, adjustment)?;
+        let remaining = self
+            .universe
+            .surface_vehicles
+            .get(&id)
+            .map(|sv| sv.vehicle().remaining_dv())
+            .unwrap_or(0.0);
+        if plan.dv() > remaining {
+            return Err(format!(
+                "Not enough delta-v: {} needs {:0.1} m/s, {:0.1} m/s remaining",
+                adjustment,
+                plan.dv(),
+                remaining
+            ));
+        }
+        self.universe.maneuvers.schedule(id, plan);
+        self.notice(format!(
+            "{}: {:0.1} m/s burn scheduled for {}",
+            adjustment,
+            cost,
+            self.callsign(id)
+        ));
+        Ok(())
+    }
+
+    pub fn save_stats(&self) {
+        if let Err(e) = write_stats_to_file(&self.stats, &self.args.stats_path()) {
+            error!("Failed to save stats: {e}");
+        }
+    }
+
+    pub fn save_console_history(&self) {
+        let path = self.args.console_history_path();
+        if let Err(e) = write_console_history_to_file(self.console.history(), &path) {
+            error!("Failed to save console history: {e}");
+        }
+    }
+
+    /// Re-derives every orbit from its vehicle's state, reporting those
+    /// which changed beyond `tolerance` and those which had to be
+    /// quarantined.
+    pub fn normalize_orbits(&mut self, tolerance: NormalizationTolerance) -> NormalizationReport {
+        let report = self.universe.normalize_orbits(tolerance);
+        for (id, change) in &report.rectified {
+            self.console.print(format!("{}: {}", id, change));
+        }
+        for (id, reason) in &report.quarantined {
+            self.console
+                .print(format!("{} quarantined: {}", id, reason));
+            if self.piloting() == Some(*id) {
+                self.orbital_context.piloting = None;
+            }
+            self.orbital_context.selected.remove(id);
+        }
+        if !report.is_clean() {
+            self.notice(format!(
+                "Checked {} orbits: {} rectified, {} quarantined",
+                report.checked,
+                report.rectified.len(),
+                report.quarantined.len()
+            ));
+        }
+        report
+    }
+
+    pub fn save_filters(&self) {
+        if let Err(e) = write_saved_filters_to_file(&self.saved_filters, &self.args.filters_path())
+        {
+            error!("Failed to save filters: {e}");
+        }
+    }
+
+    pub fn vehicle_facts(&self, id: EntityId) -> Option<VehicleFacts> {
+        let mut facts = VehicleFacts::gather(&self.universe, id)?;
+        facts.group = self
+            .universe
+            .group_membership(&id)
+            .map(|gid| self.group_name(gid));
+        facts.derelict = self.fleet.is_derelict(id);
+        Some(facts)
+    }
+
+    /// Every vehicle matching a filter, in ID order.
+    pub fn find_vehicles(&self, query: &VehicleQuery) -> Vec<EntityId> {
+        let mut ids: Vec<EntityId> = self
+            .universe
+            .surface_vehicles
+            .keys()
+            .filter(|id| {
+                self.vehicle_facts(**id)
+                    .is_some_and(|facts| query.matches(&facts))
+            })
+            .copied()
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Selects the vehicles matching a saved filter.
+    pub fn apply_saved_filter(&mut self, i: usize) {
+        let Some(filter) = self.saved_filters.get(i) else {
+            return;
+        };
+        match VehicleQuery::parse(&filter.query) {
+            Ok(query) => {
+                let ids = self.find_vehicles(&query);
+                let s = format!("{}: {} vehicle(s)", filter.name, ids.len());
+                self.orbital_context.selected = ids.into_iter().collect();
+                self.notice(s);
+            }
+            Err(e) => self.notice(format!("{}: {}", filter.name, e)),
+        }
+    }
+
+    pub fn save_settings(&self) {
+        if let Err(e) = write_settings_to_file(&self.args.settings_path(), &self.settings) {
+            error!("Failed to save settings: {e}");
+        }
+    }
+
     pub fn shutdown_with_prompt(&mut self) {
         if self.is_exit_prompt {
             self.shutdown()
@@ -945,7 +3690,21 @@ impl GameState {
         }
     }
 
+    pub fn open_pause_menu(&mut self) {
+        self.pause_menu.get_or_insert(self.paused);
+        self.paused = true;
+    }
+
+    /// Closes the pause menu, resuming the sim unless it was paused
+    /// before the menu was opened.
+    pub fn close_pause_menu(&mut self) {
+        if let Some(was_paused) = self.pause_menu.take() {
+            self.paused = was_paused;
+        }
+    }
+
     pub fn shutdown(&self) {
+        self.save_stats();
         // for a sensation of weightiness
         std::thread::sleep(core::time::Duration::from_millis(50));
         std::process::exit(0)
@@ -957,14 +3716,12 @@ impl GameState {
     }
 
     pub fn get_random_vehicle(&self) -> Option<Vehicle> {
-        let vehicles = crate::scenes::get_list_of_vehicles(self).unwrap_or(vec![]);
-
-        if vehicles.is_empty() {
+        if self.vehicles.is_empty() {
             return None;
         }
 
-        let choice = randint(0, vehicles.len() as i32);
-        let (_, path) = vehicles.get(choice as usize)?;
+        let choice = randint(0, self.vehicles.len() as i32);
+        let path = &self.vehicles.listings().get(choice as usize)?.path;
 
         let name = get_random_ship_name(&self.vehicle_names);
 
@@ -981,6 +3738,16 @@ impl GameState {
         self.ui.at(p, wb).map(|n| n.on_click()).flatten()
     }
 
+    /// Height of UI buttons, scaled down from the configured height in
+    /// short windows.
+    pub fn button_height(&self) -> f32 {
+        self.settings.ui_button_height * crate::ui::ui_scale(self.input.screen_bounds.span.y)
+    }
+
+    pub fn is_compact_layout(&self) -> bool {
+        self.input.screen_bounds.span.x < crate::ui::COMPACT_LAYOUT_WIDTH
+    }
+
     pub fn is_hovering_over_ui(&self) -> bool {
         let wb = self.input.screen_bounds.span;
         let p = match self.input.position(MouseButt::Hover, FrameId::Current) {
@@ -1028,6 +3795,131 @@ impl GameState {
         return Some(());
     }
 
+    /// Picks up a vehicle when a gallery thumbnail in the editor is pressed
+    /// and dragged.
+    fn maybe_start_drag(&mut self) -> Option<()> {
+        use FrameId::*;
+        use MouseButt::*;
+
+        if self.scene != SceneType::Editor {
+            return None;
+        }
+
+        let p = self.input.position(Left, Down)?;
+        let q = self.input.position(Left, Current)?;
+        if p.distance(q) < DRAG_START_PIXELS {
+            return None;
+        }
+
+        let wb = self.input.screen_bounds.span;
+        let OnClick::LoadVehicle(path) = self.ui.at(p, wb)?.on_click()? else {
+            return None;
+        };
+
+        let name = path.file_stem()?.to_string_lossy().to_string();
+        let ship_name = get_random_ship_name(&self.vehicle_names);
+        let mut vehicle = load_vehicle(path, ship_name, &self.part_database).ok()?;
+        vehicle.build_all();
+        self.dragging = Some(DraggedVehicle { name, vehicle });
+        Some(())
+    }
+
+    /// Where the dragged vehicle would be placed if dropped at the cursor.
+    pub fn drop_target(&self) -> Option<DropTarget> {
+        if self.scene != SceneType::Orbital {
+            return None;
+        }
+        let ctx = &self.orbital_context;
+        let p = self.input.position(MouseButt::Hover, FrameId::Current)?;
+        DropTarget::find(
+            &self.universe,
+            &ctx.queued_orbits,
+            ctx.c2w(p),
+            DROP_SNAP_PIXELS / ctx.scale(),
+        )
+    }
+
+    /// Carries the dragged vehicle out of the editor into the orbital view
+    /// once it leaves the editor's menus, and drops it on release.
+    fn update_drag(&mut self) {
+        if self.scene == SceneType::Editor && !self.is_hovering_over_ui() {
+            self.set_current_scene(SceneType::Orbital);
+        }
+
+        if self.input.on_frame(MouseButt::Left, FrameId::Up).is_none() {
+            return;
+        }
+
+        let Some(dragged) = self.dragging.take() else {
+            return;
+        };
+
+        let Some(target) = self.drop_target() else {
+            if self.scene == SceneType::Orbital {
+                self.notice(format!("Can't drop {} here", dragged.name));
+            }
+            return;
+        };
+
+        match target.place(&mut self.universe, dragged.vehicle) {
+            Some(id) => self.notice(format!(
+                "Dropped {} ({}) on {}",
+                dragged.name,
+                self.callsign(id),
+                target
+            )),
+            None => self.notice(format!("Failed to drop {} on {}", dragged.name, target)),
+        }
+    }
+
+    /// Picks up a HUD widget when its handle is pressed and dragged.
+    fn maybe_start_hud_drag(&mut self) -> Option<()> {
+        use FrameId::*;
+        use MouseButt::*;
+
+        if !self.hud_edit {
+            return None;
+        }
+
+        let p = self.input.position(Left, Down)?;
+        let q = self.input.position(Left, Current)?;
+        if p.distance(q) < DRAG_START_PIXELS {
+            return None;
+        }
+
+        let wb = self.input.screen_bounds.span;
+        let OnClick::HudWidgetHandle(w) = self.ui.at(p, wb)?.on_click()? else {
+            return None;
+        };
+        self.hud_drag = Some(*w);
+        Some(())
+    }
+
+    /// Anchor the dragged HUD widget would be moved to if dropped now.
+    pub fn hud_drop_anchor(&self) -> Option<HudAnchor> {
+        self.hud_drag?;
+        let p = self.input.position(MouseButt::Hover, FrameId::Current)?;
+        Some(HudAnchor::nearest(p))
+    }
+
+    /// Moves the dragged HUD widget to the anchor nearest the cursor on
+    /// release, and saves the new arrangement.
+    fn update_hud_drag(&mut self) {
+        if self.input.on_frame(MouseButt::Left, FrameId::Up).is_none() {
+            return;
+        }
+
+        let anchor = self.hud_drop_anchor();
+        let Some(w) = self.hud_drag.take() else {
+            return;
+        };
+        let (Some(anchor), Some(hud)) = (anchor, self.settings.hud.get_mut(self.scene)) else {
+            return;
+        };
+        hud.get_mut(w).anchor = anchor;
+        self.save_settings();
+    }
+
     fn handle_click_events(&mut self) {
         use FrameId::*;
         use MouseButt::*;
@@ -1046,10 +3938,17 @@ impl GameState {
             button.on_mouse_move(&mut take);
         }
 
+        if self.console.is_active() && self.spectator.is_active() {
+            self.console.hide();
+        }
+
         if self.console.is_active() {
             if let Some((decl, args)) = self.console.process_input(&mut self.input) {
                 decl.execute(self, args);
             }
+            if self.console.take_unsaved() {
+                self.save_console_history();
+            }
             return;
         }
 
@@ -1076,46 +3975,79 @@ impl GameState {
             return;
         }
 
-        if combo_just_pressed(
-            &self.input,
-            &[KeyCode::ControlLeft, KeyCode::ShiftLeft, KeyCode::KeyT],
-        ) {
+        if self
+            .keybindings
+            .just_pressed(KeyAction::ToggleTransformTree, &self.input)
+        {
             self.settings.draw_transform_tree = !self.settings.draw_transform_tree;
         }
 
-        if self.input.is_pressed(KeyCode::ShiftLeft) && self.input.is_pressed(KeyCode::ControlLeft)
+        let delta = if self
+            .keybindings
+            .just_pressed(KeyAction::UiSmaller, &self.input)
         {
-            let delta = if self.input.just_pressed(KeyCode::Minus) {
-                -1.0
-            } else if self.input.just_pressed(KeyCode::Equal) {
-                1.0
-            } else {
-                0.0
-            };
+            -1.0
+        } else if self
+            .keybindings
+            .just_pressed(KeyAction::UiLarger, &self.input)
+        {
+            1.0
+        } else {
+            0.0
+        };
+        self.settings.ui_button_height = (self.settings.ui_button_height + delta).clamp(3.0, 40.0);
 
-            self.settings.ui_button_height =
-                (self.settings.ui_button_height + delta).clamp(3.0, 40.0);
+        let dragging = self.dragging.is_some() || self.hud_drag.is_some();
+        if self.dragging.is_some() {
+            self.update_drag();
+        } else if self.hud_drag.is_some() {
+            self.update_hud_drag();
+        } else {
+            self.handle_click_events();
+            if !self.spectator.is_active() {
+                self.maybe_start_drag();
+            }
+            self.maybe_start_hud_drag();
         }
 
-        self.handle_click_events();
-
-        let on_ui = self.is_hovering_over_ui() || take.take().is_none();
+        let on_ui = self.is_hovering_over_ui() || take.take().is_none() || dragging;
 
+        let spectating = self.spectator.is_active();
         match self.scene {
+            SceneType::Editor if spectating => (),
             SceneType::Editor => {
                 EditorContext::on_render_tick(self);
             }
-            SceneType::MainMenu => (),
+            SceneType::MainMenu | SceneType::Stats => (),
             SceneType::Orbital => {
+                let frame = OrbitalInput {
+                    input: &self.input,
+                    keys: &self.keybindings,
+                    double_click: &self.settings.double_click,
+                };
                 self.orbital_context.on_render_tick(
                     on_ui,
-                    &self.input,
+                    spectating,
+                    frame,
                     &mut self.universe,
                     &mut self.sounds,
                 );
+                if let Some(id) = self.piloting().filter(|id| self.fleet.is_derelict(*id)) {
+                    self.orbital_context.piloting = None;
+                    self.notice(format!(
+                        "{} is derelict; reclaim it to fly it",
+                        self.callsign(id)
+                    ));
+                }
             }
             SceneType::Telescope => {
-                self.telescope_context.on_render_tick(&self.input);
+                self.telescope_context
+                    .on_render_tick(&self.input, &self.keybindings);
+            }
+            SceneType::Scenario if spectating => (),
+            SceneType::Scenario => {
+                self.scenario_context
+                    .on_render_tick(on_ui, &self.input, &self.keybindings);
             }
         }
     }
@@ -1124,13 +4056,14 @@ impl GameState {
         self.game_ticks += 1;
 
         for button in &mut self.buttons {
-            button.step();
+            button.step(self.wall_time);
         }
 
         let mut signals = ControlSignals::new();
 
-        if let Some(id) = self.piloting() {
-            let cmd = keyboard_control_law(&self.input);
+        if let Some(id) = self.piloting().filter(|_| !self.spectator.is_active()) {
+            self.universe.traffic.release(id);
+            let cmd = keyboard_control_law(&self.input, &self.keybindings);
             if !cmd.is_nullopt() {
                 signals.piloting_commands.insert(id, cmd);
             }
@@ -1140,10 +4073,27 @@ impl GameState {
             self.universe_ticks_per_game_tick = SimRate::RealTime;
         }
 
+        self.stream_pending_vehicles(std::time::Duration::from_millis(4));
+        self.stream_vehicle_import(std::time::Duration::from_millis(4));
+        if self.wall_time - self.last_vehicle_scan > Nanotime::secs(1) {
+            self.last_vehicle_scan = self.wall_time;
+            if self.vehicles.is_stale() {
+                self.refresh_vehicles();
+            }
+        }
+
+        if !self.paused {
+            for (id, action) in self.paused_actions.drain() {
+                self.apply_paused_action(id, action);
+            }
+        }
+
         // BOOKMARK gameloop
         self.actual_universe_ticks_per_game_tick = 0;
         self.exec_time = std::time::Duration::ZERO;
-        if !self.paused {
+        // observers follow the host's clock instead of their own
+        if !self.paused && !self.network.is_observing() {
+            let span = ProfileSpan::start(ProfileStage::UniverseTicks);
             (
                 self.actual_universe_ticks_per_game_tick,
                 self.exec_time,
@@ -1152,11 +4102,91 @@ impl GameState {
                 self.universe_ticks_per_game_tick.as_ticks(),
                 &signals,
                 std::time::Duration::from_millis(10),
-            )
+            );
+            self.profiler.finish(span);
+            // checkpoints taken mid-load would drop vehicles that get
+            // loaded afterwards if the universe were rewound to them
+            if self.universe.loading.is_empty() {
+                self.checkpoints.update(&self.universe);
+            }
+        }
+
+        self.update_network();
+        if self.normalize_on_load && self.universe.loading.is_empty() {
+            self.normalize_on_load = false;
+            self.normalize_orbits(NormalizationTolerance::default());
+        }
+        self.update_alarms();
+        self.update_collision_warning();
+        self.update_music();
+        let span = ProfileSpan::start(ProfileStage::Propagation);
+        self.update_predictions();
+        self.profiler.finish(span);
+        self.update_telemetry();
+        self.update_director();
+
+        if let Some(mut session) = self.practice.take() {
+            match session.update(&self.universe) {
+                Some(true) => {
+                    let stamp = self.universe.stamp();
+                    self.universe
+                        .events
+                        .emit(stamp, UniverseEvent::Docked(session.pilot, session.target));
+                    self.notice(format!("Practice approach scored {}", session));
+                    self.notify(
+                        ObjectId::Orbiter(session.pilot),
+                        NotificationType::ApproachScored(session.pilot, session.points()),
+                        None,
+                    );
+                }
+                Some(false) => self.practice = Some(session),
+                None => (),
+            }
         }
 
+        for e in self.universe.recycle_bin.purge(self.universe.stamp()) {
+            self.notice(format!(
+                "{} permanently deleted",
+                e.vehicle.vehicle().name()
+            ));
+        }
+
+        self.handle_universe_events();
+
+        self.update_stats(PHYSICS_CONSTANT_DELTA_TIME * self.actual_universe_ticks_per_game_tick);
+
         self.wall_time += PHYSICS_CONSTANT_DELTA_TIME;
 
+        let throttle = self
+            .piloting()
+            .and_then(|id| self.universe.surface_vehicles.get(&id))
+            .map(|sv| {
+                let max = sv.vehicle.max_forward_thrust();
+                let current = sv.vehicle.current_thrust_along_heading(0.0, false);
+                if max > 0.0 {
+                    (current / max).clamp(0.0, 1.0) as f32
+                } else {
+                    0.0
+                }
+            })
+            .unwrap_or(0.0);
+        self.orbital_context
+            .throttle_display
+            .update(throttle, self.wall_time);
+
+        self.display_clock.on_game_tick(self.universe.stamp());
+        let warp = self.universe_ticks_per_game_tick.as_ticks();
+        let labels = if warp > self.settings.label_max_warp
+            || self.degradation.is_shed(CostlyVisual::Labels)
+        {
+            0.0
+        } else {
+            1.0
+        };
+        self.orbital_context
+            .label_alpha
+            .update(labels, self.wall_time);
+
         self.notifications.iter_mut().for_each(|n| n.jitter());
 
         self.notifications
@@ -1165,10 +4195,14 @@ impl GameState {
         match self.scene {
             SceneType::Orbital => {
                 self.orbital_context.on_game_tick(&self.universe);
+                self.step_autosave();
             }
             SceneType::Telescope => {
                 self.telescope_context.on_game_tick();
             }
+            SceneType::Scenario => {
+                self.scenario_context.on_game_tick();
+            }
             SceneType::Editor => {
                 EditorContext::on_game_tick(self);
             }
@@ -1183,11 +4217,25 @@ fn on_game_tick(mut state: ResMut<GameState>, mut images: ResMut<Assets<Image>>)
     if state.image_handles.is_empty() {
         state.load_sprites(&mut images)
     }
+    state.load_part_sprites_in_use(&mut images);
 
     crate::generate_ship_sprites::proc_gen_ship_sprites(&mut state, &mut images);
+
+    if state.scene == SceneType::Editor
+        && !state.editor_context.vehicles_menu.is_collapsed()
+        && state.editor_context.gallery.is_none()
+    {
+        let gallery = build_vehicle_gallery(&mut state, &mut images);
+        state.editor_context.gallery = Some(gallery);
+    }
 }
 
-fn on_render_tick(mut state: ResMut<GameState>) {
+fn on_render_tick(mut state: ResMut<GameState>, fixed: Res<Time<Fixed>>, real: Res<Time<Real>>) {
+    state
+        .display_clock
+        .on_render_tick(fixed.overstep_fraction());
+    let budget = state.settings.frame_budgets.get(state.scene);
+    state.degradation.record(real.delta(), budget);
     state.on_render_tick();
 }
 
@@ -1207,6 +4255,9 @@ fn process_interaction(
         InteractionEvent::ClearSelection => {
             state.orbital_context.selected.clear();
         }
+        InteractionEvent::FollowPilot => {
+            state.orbital_context.following = Some(state.piloting()?);
+        }
         InteractionEvent::ClearOrbitQueue => {
             state.orbital_context.queued_orbits.clear();
         }
@@ -1232,6 +4283,12 @@ fn process_interaction(
         InteractionEvent::DrawMode => {
             state.orbital_context.draw_mode = next_cycle(&state.orbital_context.draw_mode);
         }
+        InteractionEvent::ToggleRotatingFrame => {
+            state.orbital_context.rotating_frame = !state.orbital_context.rotating_frame;
+        }
+        InteractionEvent::PinMeasurement => {
+            state.pin_measurement();
+        }
         InteractionEvent::Orbits => {
             state.orbital_context.show_orbits = next_cycle(&state.orbital_context.show_orbits);
         }
@@ -1249,13 +4306,28 @@ fn process_interaction(
         InteractionEvent::ToggleDebugConsole => {
             state.console.toggle();
         }
+        InteractionEvent::ToggleHelp => {
+            state.show_help = !state.show_help;
+        }
+        InteractionEvent::ToggleSpectator => {
+            let on = !state.spectator.is_active();
+            state.set_spectating(on);
+        }
         InteractionEvent::Escape => {
             if state.console.is_active() {
                 state.console.hide()
-            } else if !state.is_exit_prompt {
-                state.is_exit_prompt = true;
+            } else if state.editor_context.part_search.is_focused() {
+                state.editor_context.part_search.unfocus();
+            } else if state.show_help {
+                state.show_help = false;
+            } else if state.is_exit_prompt {
+                state.is_exit_prompt = false;
+            } else if state.pending_handover.is_some() {
+                state.pending_handover = None;
+            } else if state.pause_menu.is_some() {
+                state.close_pause_menu();
             } else {
-                state.shutdown()
+                state.open_pause_menu();
             }
         }
         InteractionEvent::ToggleObject(id) => {
@@ -1268,9 +4340,7 @@ fn process_interaction(
             state.disband_group(*gid);
         }
         InteractionEvent::CreateGroup => {
-            // let gid = state.ids.next();
-            // state.create_group(gid);
-            println!("todo!");
+            state.create_group();
         }
         _ => (),
     };
@@ -1284,6 +4354,15 @@ fn handle_interactions(
 ) {
     for e in events.read() {
         debug!("Interaction event: {e:?}");
+        if !state.spectator.allows_interaction(e) {
+            continue;
+        }
+        // keys typed into the part search aren't shortcuts
+        let typing =
+            state.scene == SceneType::Editor && state.editor_context.part_search.is_focused();
+        if typing && !matches!(e, InteractionEvent::Escape) {
+            continue;
+        }
         process_interaction(e, &mut state, &mut window);
     }
 }
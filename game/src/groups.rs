@@ -0,0 +1,104 @@
+use crate::palette::ColorPalette;
+use bevy::color::Hsla;
+use starling::prelude::*;
+
+/// Name and color chosen by the player for a group, in place of the ones
+/// derived from its ID.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroupStyle {
+    pub name: Option<String>,
+    /// Index into the palette's swatches.
+    pub color: Option<usize>,
+}
+
+impl GroupStyle {
+    pub fn name(&self, gid: EntityId) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("Group {}", gid))
+    }
+
+    pub fn color(&self, gid: EntityId, palette: ColorPalette) -> Hsla {
+        match self.color {
+            Some(i) => {
+                let swatches = palette.swatches();
+                swatches[i % swatches.len()]
+            }
+            None => palette.hashable_to_color(&gid),
+        }
+    }
+}
+
+/// Totals over the members of a group.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GroupStats {
+    pub members: usize,
+    pub fuel_mass: Mass,
+    /// Mean fraction of tank capacity remaining.
+    pub mean_fuel: f64,
+    /// Delta-V of the member with the least remaining, in m/s.
+    pub min_dv: Option<f64>,
+}
+
+impl GroupStats {
+    pub fn new(universe: &Universe, gid: EntityId) -> Self {
+        let mut stats = Self::default();
+        let vehicles = universe
+            .constellations
+            .iter()
+            .filter(|(_, g)| **g == gid)
+            .filter_map(|(id, _)| universe.surface_vehicles.get(id))
+            .map(|sv| &sv.vehicle);
+        for vehicle in vehicles {
+            let dv = vehicle.remaining_dv();
+            stats.members += 1;
+            stats.fuel_mass += vehicle.fuel_mass();
+            stats.mean_fuel += vehicle.fuel_percentage();
+            stats.min_dv = Some(stats.min_dv.map_or(dv, |m| m.min(dv)));
+        }
+        if stats.members > 0 {
+            stats.mean_fuel /= stats.members as f64;
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fleet_in_leo;
+    use std::collections::HashSet;
+
+    #[test]
+    fn groups_can_be_merged_and_split() {
+        let (mut universe, ids) = fleet_in_leo(4);
+
+        let a = universe.create_group(ids[..2].iter().copied());
+        let b = universe.create_group(ids[2..].iter().copied());
+        assert_ne!(a, b);
+        assert_eq!(GroupStats::new(&universe, a).members, 2);
+
+        universe.merge_groups(a, b);
+        assert_eq!(universe.unique_groups(), vec![a]);
+        assert_eq!(GroupStats::new(&universe, a).members, 4);
+        assert_eq!(GroupStats::new(&universe, b), GroupStats::default());
+
+        let moving = HashSet::from([ids[0], ids[3]]);
+        let c = universe.split_group(a, &moving).unwrap();
+        assert_eq!(universe.group_membership(&ids[0]), Some(c));
+        assert_eq!(universe.group_membership(&ids[1]), Some(a));
+        assert_eq!(GroupStats::new(&universe, c).members, 2);
+        // a split which would empty the group does nothing
+        assert_eq!(universe.split_group(c, &moving), None);
+
+        let style = GroupStyle::default();
+        assert_eq!(style.name(a), format!("Group {}", a));
+        let palette = ColorPalette::Tritanopia;
+        let picked = GroupStyle {
+            name: Some("Relays".to_string()),
+            color: Some(1),
+        };
+        assert_eq!(picked.name(a), "Relays");
+        assert_eq!(picked.color(a, palette), palette.swatches()[1]);
+    }
+}
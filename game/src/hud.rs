@@ -0,0 +1,451 @@
+use crate::game::GameState;
+use crate::onclick::OnClick;
+use crate::palette::Severity;
+use crate::scenes::SceneType;
+use crate::ui::{annotation_buttons, notification_bar, progress_bar, UI_BACKGROUND_COLOR};
+use bevy::prelude::*;
+use enum_iterator::{all, Sequence};
+use layout::layout::{Node, Size};
+use serde::{Deserialize, Serialize};
+use starling::prelude::*;
+use std::collections::VecDeque;
+
+/// Width of HUD widgets, other than the notification bar.
+pub const HUD_WIDGET_WIDTH: f32 = 250.0;
+
+/// Number of samples shown in each telemetry mini-graph.
+pub const TELEMETRY_SAMPLES: usize = 60;
+
+/// Wall time between telemetry samples.
+pub const TELEMETRY_PERIOD: Nanotime = Nanotime::millis(500);
+
+const SPARKLINE_COLOR: [f32; 4] = [0.3, 0.6, 0.9, 1.0];
+
+/// A piece of the heads-up display which can be shown, hidden and moved
+/// to a corner of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Sequence, Deserialize, Serialize)]
+pub enum HudWidget {
+    Throttle,
+    Pinned,
+    Notifications,
+    Telemetry,
+    DateBar,
+}
+
+impl std::fmt::Display for HudWidget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Throttle => write!(f, "Throttle"),
+            Self::Pinned => write!(f, "Pinned"),
+            Self::Notifications => write!(f, "Notifications"),
+            Self::Telemetry => write!(f, "Telemetry"),
+            Self::DateBar => write!(f, "Date"),
+        }
+    }
+}
+
+/// Corner of the screen which a HUD widget is stacked into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence, Deserialize, Serialize)]
+pub enum HudAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl HudAnchor {
+    /// Anchor nearest a point in camera coordinates, which are centered on
+    /// the screen with y up.
+    pub fn nearest(p: Vec2) -> Self {
+        match (p.x < 0.0, p.y > 0.0) {
+            (true, true) => Self::TopLeft,
+            (false, true) => Self::TopRight,
+            (true, false) => Self::BottomLeft,
+            (false, false) => Self::BottomRight,
+        }
+    }
+
+    pub fn is_top(&self) -> bool {
+        matches!(self, Self::TopLeft | Self::TopRight)
+    }
+
+    pub fn is_left(&self) -> bool {
+        matches!(self, Self::TopLeft | Self::BottomLeft)
+    }
+
+    /// The quarter of a screen of the given size, centered on the origin,
+    /// which this anchor stacks widgets into.
+    pub fn region(&self, span: Vec2) -> AABB {
+        let x = if self.is_left() { -1.0 } else { 1.0 };
+        let y = if self.is_top() { 1.0 } else { -1.0 };
+        AABB::new(Vec2::new(x, y) * span / 4.0, span / 2.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct WidgetPlacement {
+    pub visible: bool,
+    pub anchor: HudAnchor,
+}
+
+impl WidgetPlacement {
+    pub const fn new(visible: bool, anchor: HudAnchor) -> Self {
+        Self { visible, anchor }
+    }
+}
+
+/// Which HUD widgets a scene shows, and where.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HudLayout {
+    pub throttle: WidgetPlacement,
+    pub pinned: WidgetPlacement,
+    pub notifications: WidgetPlacement,
+    pub telemetry: WidgetPlacement,
+    pub date_bar: WidgetPlacement,
+}
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        use HudAnchor::*;
+        Self {
+            throttle: WidgetPlacement::new(true, TopLeft),
+            pinned: WidgetPlacement::new(true, TopLeft),
+            notifications: WidgetPlacement::new(true, BottomLeft),
+            telemetry: WidgetPlacement::new(false, BottomRight),
+            date_bar: WidgetPlacement::new(true, TopRight),
+        }
+    }
+}
+
+impl HudLayout {
+    pub fn get(&self, widget: HudWidget) -> &WidgetPlacement {
+        match widget {
+            HudWidget::Throttle => &self.throttle,
+            HudWidget::Pinned => &self.pinned,
+            HudWidget::Notifications => &self.notifications,
+            HudWidget::Telemetry => &self.telemetry,
+            HudWidget::DateBar => &self.date_bar,
+        }
+    }
+
+    pub fn get_mut(&mut self, widget: HudWidget) -> &mut WidgetPlacement {
+        match widget {
+            HudWidget::Throttle => &mut self.throttle,
+            HudWidget::Pinned => &mut self.pinned,
+            HudWidget::Notifications => &mut self.notifications,
+            HudWidget::Telemetry => &mut self.telemetry,
+            HudWidget::DateBar => &mut self.date_bar,
+        }
+    }
+
+    /// Visible widgets stacked at the given anchor, in stacking order.
+    pub fn widgets_at(&self, anchor: HudAnchor) -> impl Iterator<Item = HudWidget> + use<'_> {
+        all::<HudWidget>().filter(move |w| {
+            let p = self.get(*w);
+            p.visible && p.anchor == anchor
+        })
+    }
+}
+
+/// HUD arrangement for each scene which has one.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SceneHuds {
+    pub orbital: HudLayout,
+    pub telescope: HudLayout,
+    pub stats: HudLayout,
+}
+
+impl SceneHuds {
+    pub fn get(&self, scene: SceneType) -> Option<&HudLayout> {
+        match scene {
+            SceneType::Orbital => Some(&self.orbital),
+            SceneType::Telescope => Some(&self.telescope),
+            SceneType::Stats => Some(&self.stats),
+            SceneType::Editor | SceneType::Scenario | SceneType::MainMenu => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, scene: SceneType) -> Option<&mut HudLayout> {
+        match scene {
+            SceneType::Orbital => Some(&mut self.orbital),
+            SceneType::Telescope => Some(&mut self.telescope),
+            SceneType::Stats => Some(&mut self.stats),
+            SceneType::Editor | SceneType::Scenario | SceneType::MainMenu => None,
+        }
+    }
+}
+
+/// Recent altitude and speed of the piloted vehicle, relative to its
+/// parent body, for the telemetry mini-graphs.
+#[derive(Debug, Clone, Default)]
+pub struct Telemetry {
+    id: Option<EntityId>,
+    last_sample: Nanotime,
+    pub altitude: VecDeque<f32>,
+    pub speed: VecDeque<f32>,
+}
+
+impl Telemetry {
+    /// Adds a sample for the given vehicle, if one is due, starting the
+    /// history over if it's a different vehicle than the last sample was
+    /// for.
+    pub fn record(&mut self, id: EntityId, altitude: f64, speed: f64, now: Nanotime) {
+        if self.id != Some(id) {
+            self.clear();
+            self.id = Some(id);
+        } else if now < self.last_sample + TELEMETRY_PERIOD {
+            return;
+        }
+        self.last_sample = now;
+        for (history, value) in [(&mut self.altitude, altitude), (&mut self.speed, speed)] {
+            history.push_back(value as f32);
+            while history.len() > TELEMETRY_SAMPLES {
+                history.pop_front();
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.id = None;
+        self.altitude.clear();
+        self.speed.clear();
+    }
+}
+
+/// Bar graph of a series of values, scaled to fill the given height
+/// between the series' minimum and maximum.
+pub fn sparkline(values: &VecDeque<f32>, width: f32, height: f32) -> Node<OnClick> {
+    let lo = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let hi = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let bar_width = width / TELEMETRY_SAMPLES as f32;
+    Node::new(width, height)
+        .tight()
+        .with_color(UI_BACKGROUND_COLOR)
+        .with_children(values.iter().map(|v| {
+            let s = if hi > lo { (v - lo) / (hi - lo) } else { 0.5 };
+            Node::new(bar_width, height)
+                .down()
+                .tight()
+                .invisible()
+                .with_child(Node::new(bar_width, Size::Grow).invisible())
+                .with_child(Node::new(bar_width, height * s.max(0.05)).with_color(SPARKLINE_COLOR))
+        }))
+}
+
+fn throttle_widget(state: &GameState, width: f32) -> Node<OnClick> {
+    let wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let Some(sv) = state
+        .piloting()
+        .and_then(|id| state.universe.surface_vehicles.get(&id))
+    else {
+        return wrapper;
+    };
+
+    let h = state.button_height();
+    let throttle = state.orbital_context.throttle_display.value();
+    let wrapper = wrapper
+        .with_child(
+            Node::row(h)
+                .with_text(format!("Throttle {:0.0}%", throttle * 100.0))
+                .enabled(false),
+        )
+        .with_child(progress_bar(throttle as f64, width, h / 4.0));
+
+    let Some(aero) = sv.aero() else {
+        return wrapper;
+    };
+
+    // flight path and angle of attack, for gliding in to land
+    let pv = sv.pv();
+    let fpa = flight_path_angle(pv.pos, pv.vel).to_degrees();
+    let aoa = aero.angle_of_attack.to_degrees();
+    let wrapper = wrapper
+        .with_child(
+            Node::row(h)
+                .with_text(format!("Flight path {:+0.1} deg", fpa))
+                .enabled(false),
+        )
+        .with_child(
+            Node::row(h)
+                .with_text(format!("Angle of attack {:+0.1} deg", aoa))
+                .enabled(false),
+        );
+    if aero.stalled {
+        let color = state.settings.color_palette.severity(Severity::Danger);
+        wrapper.with_child(
+            Node::row(h)
+                .with_text("STALL")
+                .with_color(color.to_f32_array())
+                .enabled(false),
+        )
+    } else {
+        wrapper
+    }
+}
+
+fn telemetry_widget(state: &GameState, width: f32) -> Node<OnClick> {
+    let wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let t = &state.telemetry;
+    let (Some(alt), Some(speed)) = (t.altitude.back(), t.speed.back()) else {
+        return wrapper;
+    };
+
+    let h = state.button_height();
+    let label = |s: String| Node::row(h).with_text(s).enabled(false);
+    wrapper
+        .with_child(label(format!("Altitude {:0.1} km", alt / 1000.0)))
+        .with_child(sparkline(&t.altitude, width, h * 1.5))
+        .with_child(label(format!("Speed {:0.0} m/s", speed)))
+        .with_child(sparkline(&t.speed, width, h * 1.5))
+}
+
+fn date_bar_widget(state: &GameState, width: f32) -> Node<OnClick> {
+    Node::text(
+        width * 2.0,
+        state.button_height(),
+        crate::scenes::orbital::date_info(state),
+    )
+    .enabled(false)
+}
+
+fn widget_node(state: &GameState, widget: HudWidget) -> Node<OnClick> {
+    let w = HUD_WIDGET_WIDTH;
+    let node = match widget {
+        HudWidget::Throttle => throttle_widget(state, w),
+        HudWidget::Pinned => annotation_buttons(state, Size::Fixed(w)),
+        HudWidget::Notifications => notification_bar(state, Size::Fixed(900.0)),
+        HudWidget::Telemetry => telemetry_widget(state, w),
+        HudWidget::DateBar => date_bar_widget(state, w),
+    };
+
+    if !state.hud_edit {
+        return node;
+    }
+
+    // while editing, each widget gets a handle to drag it by, which also
+    // keeps widgets with nothing to show from vanishing
+    Node::new(Size::Fit, Size::Fit)
+        .down()
+        .tight()
+        .invisible()
+        .with_child(Node::button(
+            format!("[{}]", widget),
+            OnClick::HudWidgetHandle(widget),
+            w,
+            state.button_height(),
+        ))
+        .with_child(node)
+}
+
+/// Toggles for which widgets are shown, shown while editing the HUD.
+pub fn hud_editor_panel(state: &GameState, hud: &HudLayout) -> Node<OnClick> {
+    let h = state.button_height();
+    Node::new(HUD_WIDGET_WIDTH, Size::Fit)
+        .down()
+        .with_color(UI_BACKGROUND_COLOR)
+        .with_child(
+            Node::row(h)
+                .with_text("Drag widgets to move them")
+                .enabled(false),
+        )
+        .with_children(all::<HudWidget>().map(|w| {
+            let s = format!(
+                "{}: {}",
+                w,
+                if hud.get(w).visible {
+                    "shown"
+                } else {
+                    "hidden"
+                }
+            );
+            Node::button(s, OnClick::ToggleHudWidget(w), Size::Grow, h)
+        }))
+        .with_child(Node::button(
+            "Done",
+            OnClick::ToggleHudEditor,
+            Size::Grow,
+            h,
+        ))
+}
+
+/// The area beneath the top bar, with the scene's HUD widgets stacked in
+/// the corners. `panels` are scene-specific panels placed in the top left
+/// corner, above any widgets anchored there.
+pub fn hud_area(state: &GameState, panels: Vec<Node<OnClick>>) -> Node<OnClick> {
+    let Some(hud) = state.settings.hud.get(state.scene) else {
+        return Node::grow()
+            .invisible()
+            .down()
+            .with_children(panels.into_iter());
+    };
+
+    let stack = |top: HudAnchor, bottom: HudAnchor| {
+        let top = hud.widgets_at(top).map(|w| widget_node(state, w));
+        let bottom = hud.widgets_at(bottom).map(|w| widget_node(state, w));
+        (top.collect::<Vec<_>>(), bottom.collect::<Vec<_>>())
+    };
+
+    let (top_left, bottom_left) = stack(HudAnchor::TopLeft, HudAnchor::BottomLeft);
+    let (top_right, bottom_right) = stack(HudAnchor::TopRight, HudAnchor::BottomRight);
+
+    let editor = state.hud_edit.then(|| hud_editor_panel(state, hud));
+
+    let left = Node::grow()
+        .down()
+        .invisible()
+        .with_child(editor)
+        .with_children(panels.into_iter())
+        .with_children(top_left.into_iter())
+        .with_child(Node::grow().invisible())
+        .with_children(bottom_left.into_iter());
+
+    let right = Node::column(Size::Fit)
+        .invisible()
+        .with_children(top_right.into_iter())
+        .with_child(Node::new(0.0, Size::Grow).invisible())
+        .with_children(bottom_right.into_iter());
+
+    Node::grow()
+        .tight()
+        .invisible()
+        .with_child(left)
+        .with_child(right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widgets_stack_at_their_anchors() {
+        let span = Vec2::new(1600.0, 900.0);
+        for anchor in all::<HudAnchor>() {
+            let center = anchor.region(span).center;
+            assert_eq!(HudAnchor::nearest(center), anchor);
+        }
+
+        let mut hud = HudLayout::default();
+        let top_left: Vec<_> = hud.widgets_at(HudAnchor::TopLeft).collect();
+        assert_eq!(top_left, vec![HudWidget::Throttle, HudWidget::Pinned]);
+        assert_eq!(hud.widgets_at(HudAnchor::BottomRight).count(), 0);
+
+        hud.get_mut(HudWidget::Telemetry).visible = true;
+        hud.get_mut(HudWidget::Pinned).anchor = HudAnchor::BottomRight;
+        let bottom_right: Vec<_> = hud.widgets_at(HudAnchor::BottomRight).collect();
+        assert_eq!(bottom_right, vec![HudWidget::Pinned, HudWidget::Telemetry]);
+
+        let huds = SceneHuds::default();
+        assert!(huds.get(SceneType::Orbital).is_some());
+        assert!(huds.get(SceneType::Editor).is_none());
+    }
+}
@@ -8,5 +8,5 @@ pub trait Interactive {
 
     fn on_mouse_move(&mut self, p: &mut Take<Vec2>);
 
-    fn step(&mut self);
+    fn step(&mut self, wall_time: Nanotime);
 }
@@ -1,9 +1,484 @@
 use crate::game::GameState;
+use crate::input::{FrameId, InputState, MouseButt};
+use crate::scenes::SceneType;
 use crate::sim_rate::SimRate;
 use crate::ui::InteractionEvent;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 
+/// Something the player can do from the keyboard. Actions with an
+/// `event` are dispatched as interaction events when their binding fires;
+/// the rest are polled by the scenes which use them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    SimFaster,
+    SimSlower,
+    SimRealTime,
+    SimPause,
+    Escape,
+    ToggleFullscreen,
+    ToggleDebugConsole,
+    ToggleHelp,
+    ToggleSpectator,
+    ToggleTransformTree,
+    UiLarger,
+    UiSmaller,
+
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    ZoomIn,
+    ZoomOut,
+
+    Delete,
+    CreateGroup,
+    ClearMissions,
+    CommitMission,
+    CursorMode,
+    DrawMode,
+    ToggleRotatingFrame,
+    PinMeasurement,
+    Spawn,
+    NextControlMode,
+    ShowSplines,
+    ThrustForward,
+    ThrustBackward,
+    TurnLeft,
+    TurnRight,
+    DockingMode,
+    ActivateStage,
+
+    BuildAll,
+    SnapPart,
+    PickPart,
+    RotatePart,
+    CycleSymmetry,
+    Undo,
+    MoreAtmosphere,
+    LessAtmosphere,
+}
+
+impl KeyAction {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::SimFaster => "Speed up simulation",
+            Self::SimSlower => "Slow down simulation",
+            Self::SimRealTime => "Simulate in real time",
+            Self::SimPause => "Pause or resume",
+            Self::Escape => "Close console or menu, or pause",
+            Self::ToggleFullscreen => "Toggle fullscreen",
+            Self::ToggleDebugConsole => "Toggle debug console",
+            Self::ToggleHelp => "Toggle this help",
+            Self::ToggleSpectator => "Toggle spectator mode",
+            Self::ToggleTransformTree => "Toggle transform tree",
+            Self::UiLarger => "Enlarge UI",
+            Self::UiSmaller => "Shrink UI",
+            Self::PanUp => "Pan up",
+            Self::PanDown => "Pan down",
+            Self::PanLeft => "Pan left",
+            Self::PanRight => "Pan right",
+            Self::ZoomIn => "Zoom in",
+            Self::ZoomOut => "Zoom out",
+            Self::Delete => "Delete selected vehicles",
+            Self::CreateGroup => "Group selected vehicles",
+            Self::ClearMissions => "Clear missions",
+            Self::CommitMission => "Commit mission",
+            Self::CursorMode => "Cycle cursor mode",
+            Self::DrawMode => "Cycle draw mode",
+            Self::ToggleRotatingFrame => "Toggle rotating frame",
+            Self::PinMeasurement => "Pin measurement",
+            Self::Spawn => "Spawn vehicles",
+            Self::NextControlMode => "Next control mode",
+            Self::ShowSplines => "Show orbit splines",
+            Self::ThrustForward => "Thrust forward",
+            Self::ThrustBackward => "Thrust backward",
+            Self::TurnLeft => "Turn left",
+            Self::TurnRight => "Turn right",
+            Self::DockingMode => "Docking mode: arrows translate",
+            Self::ActivateStage => "Jettison active stage",
+            Self::BuildAll => "Build vehicle",
+            Self::SnapPart => "Snap part placement",
+            Self::PickPart => "Pick part under cursor",
+            Self::RotatePart => "Rotate part",
+            Self::CycleSymmetry => "Cycle part symmetry",
+            Self::Undo => "Undo",
+            Self::MoreAtmosphere => "Thicken atmosphere",
+            Self::LessAtmosphere => "Thin atmosphere",
+        }
+    }
+
+    /// Event sent when a binding for this action fires, for actions
+    /// handled centrally.
+    pub fn event(&self) -> Option<InteractionEvent> {
+        Some(match self {
+            Self::SimFaster => InteractionEvent::SimFaster,
+            Self::SimSlower => InteractionEvent::SimSlower,
+            Self::SimRealTime => InteractionEvent::SetSim(SimRate::RealTime),
+            Self::SimPause => InteractionEvent::SimPause,
+            Self::Escape => InteractionEvent::Escape,
+            Self::ToggleFullscreen => InteractionEvent::ToggleFullscreen,
+            Self::ToggleDebugConsole => InteractionEvent::ToggleDebugConsole,
+            Self::ToggleHelp => InteractionEvent::ToggleHelp,
+            Self::ToggleSpectator => InteractionEvent::ToggleSpectator,
+            Self::Delete => InteractionEvent::Delete,
+            Self::CreateGroup => InteractionEvent::CreateGroup,
+            Self::ClearMissions => InteractionEvent::ClearMissions,
+            Self::CommitMission => InteractionEvent::CommitMission,
+            Self::CursorMode => InteractionEvent::CursorMode,
+            Self::DrawMode => InteractionEvent::DrawMode,
+            Self::ToggleRotatingFrame => InteractionEvent::ToggleRotatingFrame,
+            Self::PinMeasurement => InteractionEvent::PinMeasurement,
+            Self::Spawn => InteractionEvent::Spawn,
+            _ => return None,
+        })
+    }
+}
+
+/// A key, with the state required of the modifier keys. A modifier of
+/// None matches whether or not it is held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub ctrl: Option<bool>,
+    pub shift: Option<bool>,
+}
+
+impl KeyChord {
+    pub const fn key(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: None,
+            shift: None,
+        }
+    }
+
+    pub const fn ctrl(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: Some(true),
+            shift: None,
+        }
+    }
+
+    pub const fn ctrl_shift(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: Some(true),
+            shift: Some(true),
+        }
+    }
+
+    fn modifiers_match(&self, input: &InputState) -> bool {
+        let ctrl = input.is_pressed(KeyCode::ControlLeft);
+        let shift = input.is_pressed(KeyCode::ShiftLeft);
+        self.ctrl.is_none_or(|c| c == ctrl) && self.shift.is_none_or(|s| s == shift)
+    }
+
+    pub fn is_pressed(&self, input: &InputState) -> bool {
+        self.modifiers_match(input) && input.is_pressed(self.key)
+    }
+
+    pub fn just_pressed(&self, input: &InputState) -> bool {
+        self.modifiers_match(input) && input.just_pressed(self.key)
+    }
+}
+
+impl std::fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl == Some(true) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift == Some(true) {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", key_name(self.key))
+    }
+}
+
+/// Short name for a key, as shown in help.
+pub fn key_name(key: KeyCode) -> String {
+    let s = match key {
+        KeyCode::Period => ".",
+        KeyCode::Comma => ",",
+        KeyCode::Slash => "/",
+        KeyCode::Minus => "-",
+        KeyCode::Equal => "=",
+        KeyCode::Backquote => "`",
+        KeyCode::ControlLeft => "Ctrl",
+        KeyCode::ShiftLeft => "Shift",
+        KeyCode::ArrowUp => "Up",
+        KeyCode::ArrowDown => "Down",
+        KeyCode::ArrowLeft => "Left",
+        KeyCode::ArrowRight => "Right",
+        _ => {
+            let s = format!("{:?}", key);
+            return s
+                .strip_prefix("Key")
+                .or_else(|| s.strip_prefix("Digit"))
+                .unwrap_or(&s)
+                .to_string();
+        }
+    };
+    s.to_string()
+}
+
+/// Scenes in which a binding is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingScope {
+    Global,
+    Scenes(&'static [SceneType]),
+}
+
+impl BindingScope {
+    const CAMERA: Self = Self::Scenes(&[
+        SceneType::Orbital,
+        SceneType::Telescope,
+        SceneType::Editor,
+        SceneType::Scenario,
+    ]);
+    const ORBITAL: Self = Self::Scenes(&[SceneType::Orbital]);
+    const EDITOR: Self = Self::Scenes(&[SceneType::Editor]);
+
+    pub fn contains(&self, scene: SceneType) -> bool {
+        match self {
+            Self::Global => true,
+            Self::Scenes(s) => s.contains(&scene),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keybinding {
+    pub action: KeyAction,
+    pub chord: KeyChord,
+    pub scope: BindingScope,
+    /// Fires every frame the chord is held, rather than once per press.
+    pub hold: bool,
+}
+
+/// A mouse gesture, listed in help alongside the keybindings. These
+/// aren't remappable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseGesture {
+    pub gesture: &'static str,
+    pub description: &'static str,
+    pub scope: BindingScope,
+}
+
+const MOUSE_GESTURES: [MouseGesture; 11] = [
+    MouseGesture {
+        gesture: "Scroll",
+        description: "Zoom",
+        scope: BindingScope::CAMERA,
+    },
+    MouseGesture {
+        gesture: "Left click",
+        description: "Pilot hovered vehicle",
+        scope: BindingScope::ORBITAL,
+    },
+    MouseGesture {
+        gesture: "Ctrl+Left click",
+        description: "Follow hovered object",
+        scope: BindingScope::ORBITAL,
+    },
+    MouseGesture {
+        gesture: "Right click",
+        description: "Target hovered object",
+        scope: BindingScope::ORBITAL,
+    },
+    MouseGesture {
+        gesture: "Double click",
+        description: "Follow, frame or view, as set in the pause menu",
+        scope: BindingScope::ORBITAL,
+    },
+    MouseGesture {
+        gesture: "Right drag strokes",
+        description: "Gesture command, if enabled",
+        scope: BindingScope::ORBITAL,
+    },
+    MouseGesture {
+        gesture: "Left click",
+        description: "Select part",
+        scope: BindingScope::EDITOR,
+    },
+    MouseGesture {
+        gesture: "Left drag",
+        description: "Place part or pipe",
+        scope: BindingScope::EDITOR,
+    },
+    MouseGesture {
+        gesture: "Right click",
+        description: "Remove part",
+        scope: BindingScope::EDITOR,
+    },
+    MouseGesture {
+        gesture: "Right drag",
+        description: "Remove pipe",
+        scope: BindingScope::EDITOR,
+    },
+    MouseGesture {
+        gesture: "Drag thumbnail",
+        description: "Place saved vehicle in the world",
+        scope: BindingScope::EDITOR,
+    },
+];
+
+/// A titled list of (input, description) pairs, for the help overlay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpSection {
+    pub title: String,
+    pub entries: Vec<(String, String)>,
+}
+
+/// The registry of keybindings, which drives both input handling and the
+/// help overlay.
+#[derive(Debug, Clone)]
+pub struct Keybindings {
+    bindings: Vec<Keybinding>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        use BindingScope as S;
+        use KeyAction as A;
+        use KeyChord as C;
+        use KeyCode as K;
+
+        let press = |action, chord, scope| Keybinding {
+            action,
+            chord,
+            scope,
+            hold: false,
+        };
+        let hold = |action, chord, scope| Keybinding {
+            action,
+            chord,
+            scope,
+            hold: true,
+        };
+
+        Self {
+            bindings: vec![
+                press(A::SimFaster, C::key(K::Period), S::Global),
+                press(A::SimSlower, C::key(K::Comma), S::Global),
+                press(A::SimRealTime, C::key(K::Slash), S::Global),
+                press(A::SimPause, C::key(K::Space), S::Global),
+                press(A::Escape, C::key(K::Escape), S::Global),
+                press(A::ToggleFullscreen, C::key(K::F11), S::Global),
+                press(A::ToggleDebugConsole, C::key(K::Backquote), S::Global),
+                press(A::ToggleHelp, C::key(K::F1), S::Global),
+                press(A::ToggleSpectator, C::key(K::F10), S::Global),
+                press(A::ToggleTransformTree, C::ctrl_shift(K::KeyT), S::Global),
+                press(A::UiLarger, C::ctrl_shift(K::Equal), S::Global),
+                press(A::UiSmaller, C::ctrl_shift(K::Minus), S::Global),
+                hold(A::PanUp, C::key(K::KeyW), S::CAMERA),
+                hold(A::PanLeft, C::key(K::KeyA), S::CAMERA),
+                hold(A::PanDown, C::key(K::KeyS), S::CAMERA),
+                hold(A::PanRight, C::key(K::KeyD), S::CAMERA),
+                hold(A::ZoomIn, C::key(K::Equal), S::CAMERA),
+                hold(A::ZoomOut, C::key(K::Minus), S::CAMERA),
+                press(A::Delete, C::key(K::Delete), S::ORBITAL),
+                press(A::CreateGroup, C::key(K::KeyG), S::ORBITAL),
+                press(A::ClearMissions, C::key(K::KeyC), S::ORBITAL),
+                press(A::CommitMission, C::key(K::Enter), S::ORBITAL),
+                press(A::CursorMode, C::key(K::KeyV), S::ORBITAL),
+                press(A::DrawMode, C::key(K::KeyM), S::ORBITAL),
+                press(A::ToggleRotatingFrame, C::key(K::KeyL), S::ORBITAL),
+                press(A::PinMeasurement, C::key(K::KeyH), S::ORBITAL),
+                hold(A::Spawn, C::key(K::KeyK), S::ORBITAL),
+                press(A::NextControlMode, C::key(K::KeyN), S::ORBITAL),
+                hold(A::ShowSplines, C::key(K::KeyP), S::ORBITAL),
+                hold(A::ThrustForward, C::key(K::ArrowUp), S::ORBITAL),
+                hold(A::ThrustBackward, C::key(K::ArrowDown), S::ORBITAL),
+                hold(A::TurnLeft, C::key(K::ArrowLeft), S::ORBITAL),
+                hold(A::TurnRight, C::key(K::ArrowRight), S::ORBITAL),
+                hold(A::DockingMode, C::key(K::ControlLeft), S::ORBITAL),
+                press(A::ActivateStage, C::key(K::KeyX), S::ORBITAL),
+                hold(A::BuildAll, C::key(K::KeyB), S::EDITOR),
+                hold(A::SnapPart, C::key(K::ShiftLeft), S::EDITOR),
+                press(A::PickPart, C::key(K::KeyQ), S::EDITOR),
+                press(A::RotatePart, C::key(K::KeyR), S::EDITOR),
+                press(A::CycleSymmetry, C::key(K::KeyM), S::EDITOR),
+                press(A::Undo, C::ctrl(K::KeyZ), S::EDITOR),
+                press(A::MoreAtmosphere, C::key(K::KeyO), S::EDITOR),
+                press(A::LessAtmosphere, C::key(K::KeyL), S::EDITOR),
+            ],
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn bindings(&self) -> &[Keybinding] {
+        &self.bindings
+    }
+
+    /// Bindings which apply in the given scene.
+    pub fn active(&self, scene: SceneType) -> impl Iterator<Item = &Keybinding> + use<'_> {
+        self.bindings
+            .iter()
+            .filter(move |b| b.scope.contains(scene))
+    }
+
+    /// Whether any chord bound to the action is held.
+    pub fn is_pressed(&self, action: KeyAction, input: &InputState) -> bool {
+        self.bindings
+            .iter()
+            .any(|b| b.action == action && b.chord.is_pressed(input))
+    }
+
+    /// Whether any chord bound to the action was pressed this frame.
+    pub fn just_pressed(&self, action: KeyAction, input: &InputState) -> bool {
+        self.bindings
+            .iter()
+            .any(|b| b.action == action && b.chord.just_pressed(input))
+    }
+
+    /// Binds the action to a new chord in place of its current ones.
+    /// Returns false if the action has no binding to remap.
+    pub fn rebind(&mut self, action: KeyAction, chord: KeyChord) -> bool {
+        let mut found = false;
+        self.bindings.retain_mut(|b| {
+            if b.action != action {
+                return true;
+            }
+            b.chord = chord;
+            let keep = !found;
+            found = true;
+            keep
+        });
+        found
+    }
+
+    /// Help for the given scene: global bindings first, then those
+    /// specific to the scene, each followed by their mouse gestures.
+    pub fn help(&self, scene: SceneType) -> Vec<HelpSection> {
+        let section = |title: String, global: bool| {
+            let in_section = |scope: &BindingScope| (*scope == BindingScope::Global) == global;
+            let keys = self
+                .active(scene)
+                .filter(|b| in_section(&b.scope))
+                .map(|b| (b.chord.to_string(), b.action.description().to_string()));
+            let mouse = MOUSE_GESTURES
+                .iter()
+                .filter(|g| g.scope.contains(scene) && in_section(&g.scope))
+                .map(|g| (g.gesture.to_string(), g.description.to_string()));
+            HelpSection {
+                title,
+                entries: keys.chain(mouse).collect(),
+            }
+        };
+
+        [
+            section("Global".to_string(), true),
+            section(format!("{:?}", scene), false),
+        ]
+        .into_iter()
+        .filter(|s| !s.entries.is_empty())
+        .collect()
+    }
+}
+
 pub fn keyboard_input(
     keys: Res<ButtonInput<KeyCode>>,
     mut state: ResMut<GameState>,
@@ -13,45 +488,72 @@ pub fn keyboard_input(
     state.input.set_buttons(keys.clone());
     state.input.set_scroll(scroll);
 
-    let ctrl = keys.pressed(KeyCode::ControlLeft);
-    let shift = keys.pressed(KeyCode::ShiftLeft);
-
-    for key in keys.get_just_pressed() {
-        let e = match (ctrl, shift, key) {
-            (_, _, KeyCode::Period) => InteractionEvent::SimFaster,
-            (_, _, KeyCode::Comma) => InteractionEvent::SimSlower,
-            (_, _, KeyCode::Slash) => InteractionEvent::SetSim(SimRate::RealTime),
-            (_, _, KeyCode::Delete) => InteractionEvent::Delete,
-            (_, _, KeyCode::KeyG) => InteractionEvent::CreateGroup,
-            (_, _, KeyCode::KeyC) => InteractionEvent::ClearMissions,
-            (_, _, KeyCode::Enter) => InteractionEvent::CommitMission,
-            (_, _, KeyCode::Minus) => InteractionEvent::ZoomOut,
-            (_, _, KeyCode::Equal) => InteractionEvent::ZoomIn,
-            (_, _, KeyCode::KeyR) => InteractionEvent::Reset,
-            (_, _, KeyCode::Space) => InteractionEvent::SimPause,
-            (_, _, KeyCode::Escape) => InteractionEvent::Escape,
-            (_, _, KeyCode::KeyV) => InteractionEvent::CursorMode,
-            (_, _, KeyCode::KeyM) => InteractionEvent::DrawMode,
-            (_, _, KeyCode::F11) => InteractionEvent::ToggleFullscreen,
-            (_, _, KeyCode::Backquote) => InteractionEvent::ToggleDebugConsole,
-            _ => continue,
+    for b in state.keybindings.active(state.scene) {
+        let Some(e) = b.action.event() else {
+            continue;
         };
+        let fired = if b.hold {
+            b.chord.is_pressed(&state.input)
+        } else {
+            b.chord.just_pressed(&state.input)
+        };
+        if fired {
+            events.send(e);
+        }
+    }
 
-        events.send(e);
+    if !state.settings.mouse_gestures || state.scene != SceneType::Orbital {
+        state.gestures.cancel();
+        return;
+    }
+    // dragging with both buttons measures angles instead
+    if state
+        .input
+        .position(MouseButt::Left, FrameId::Current)
+        .is_some()
+    {
+        state.gestures.cancel();
+        return;
     }
+    let held = state.input.position(MouseButt::Right, FrameId::Current);
+    if let Some(strokes) = state.gestures.update(held) {
+        match state.gesture_map.get(&strokes) {
+            Some(action) => {
+                events.send(action.event());
+            }
+            None => state.notice(format!("No gesture mapped to {}", strokes)),
+        }
+    }
+}
 
-    for key in keys.get_pressed() {
-        let e = match (keys.pressed(KeyCode::ControlLeft), key) {
-            (_, KeyCode::KeyK) => InteractionEvent::Spawn,
-            (_, KeyCode::ArrowUp) => InteractionEvent::Thrust(1),
-            (_, KeyCode::ArrowDown) => InteractionEvent::Thrust(-1),
-            (false, KeyCode::ArrowLeft) => InteractionEvent::TurnLeft,
-            (false, KeyCode::ArrowRight) => InteractionEvent::TurnRight,
-            (true, KeyCode::ArrowLeft) => InteractionEvent::StrafeLeft,
-            (true, KeyCode::ArrowRight) => InteractionEvent::StrafeRight,
-            _ => continue,
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn help_is_generated_from_bindings() {
+        let mut bindings = Keybindings::default();
+
+        let help = bindings.help(SceneType::Editor);
+        assert_eq!(help.len(), 2);
+        assert_eq!(help[0].title, "Global");
+        assert!(help[0]
+            .entries
+            .contains(&("F1".to_string(), "Toggle this help".to_string())));
+        assert!(help[1]
+            .entries
+            .contains(&("Ctrl+Z".to_string(), "Undo".to_string())));
+        assert!(help[1].entries.iter().any(|(k, _)| k == "Scroll"));
+        assert!(!help[1].entries.iter().any(|(_, d)| d == "Pin measurement"));
+
+        assert!(bindings.rebind(KeyAction::Undo, KeyChord::ctrl(KeyCode::KeyY)));
+        let help = bindings.help(SceneType::Editor);
+        assert!(help[1]
+            .entries
+            .contains(&("Ctrl+Y".to_string(), "Undo".to_string())));
+        assert!(!help[1].entries.iter().any(|(k, _)| k == "Ctrl+Z"));
 
-        events.send(e);
+        let menu = bindings.help(SceneType::MainMenu);
+        assert_eq!(menu.len(), 1);
     }
 }
@@ -1,26 +1,58 @@
+pub mod alarms;
+pub mod annotations;
 pub mod args;
+pub mod asset_validation;
 pub mod button;
 pub mod camera_controller;
 pub mod canvas;
 pub mod commands;
+pub mod console_history;
 pub mod craft_editor;
+pub mod cursor_icons;
 pub mod debug_console;
+pub mod display_clock;
+pub mod double_click;
+pub mod drag_drop;
 pub mod drawing;
+pub mod economy;
+pub mod entity_records;
+pub mod frame_budget;
 pub mod game;
 pub mod generate_ship_sprites;
 pub mod graph;
+pub mod groups;
+pub mod hud;
 pub mod input;
 pub mod interactive;
 pub mod keybindings;
+pub mod list_selection;
+pub mod mouse_gestures;
+pub mod music;
 pub mod names;
 pub mod new_input;
 pub mod notifications;
+pub mod observer_net;
+pub mod offscreen_markers;
 pub mod onclick;
+pub mod ownership;
+pub mod palette;
+pub mod paused_actions;
+pub mod practice;
 pub mod prelude;
+pub mod profiler;
 pub mod scenes;
 pub mod settings;
 pub mod sim_rate;
 pub mod sounds;
+pub mod spectator;
 pub mod sprites;
+pub mod stats;
+#[cfg(test)]
+pub(crate) mod testing;
+pub mod timeline_notes;
+pub mod tween;
 pub mod ui;
+pub mod vehicle_import;
+pub mod vehicle_query;
+pub mod vehicle_registry;
 pub mod z_index;
@@ -0,0 +1,106 @@
+use std::collections::BTreeSet;
+
+/// Items picked out of a list panel by position, for acting on in bulk.
+/// Like selecting vehicles in the world view, clicking an item toggles it;
+/// shift-clicking also selects everything between it and the item last
+/// clicked.
+#[derive(Debug, Clone, Default)]
+pub struct ListSelection {
+    selected: BTreeSet<usize>,
+    last: Option<usize>,
+}
+
+impl ListSelection {
+    pub fn len(&self) -> usize {
+        self.selected.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.selected.contains(&i)
+    }
+
+    /// Selected positions, in ascending order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = usize> + use<'_> {
+        self.selected.iter().copied()
+    }
+
+    pub fn toggle(&mut self, i: usize) {
+        if !self.selected.remove(&i) {
+            self.selected.insert(i);
+        }
+        self.last = Some(i);
+    }
+
+    /// Selects every item from the one last clicked through `i`, or just
+    /// `i` if nothing has been clicked yet.
+    pub fn extend_to(&mut self, i: usize) {
+        let from = self.last.unwrap_or(i);
+        self.selected.extend(from.min(i)..=from.max(i));
+        self.last = Some(i);
+    }
+
+    /// Toggles `i`, or with `extend`, selects up through it.
+    pub fn click(&mut self, i: usize, extend: bool) {
+        if extend {
+            self.extend_to(i);
+        } else {
+            self.toggle(i);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.selected.clear();
+        self.last = None;
+    }
+
+    /// Keeps the selection pointing at the same items after the one at
+    /// position `i` is removed from the list.
+    pub fn on_removed(&mut self, i: usize) {
+        let shift = |j: usize| if j > i { j - 1 } else { j };
+        self.selected = self
+            .selected
+            .iter()
+            .filter(|j| **j != i)
+            .map(|j| shift(*j))
+            .collect();
+        self.last = self.last.filter(|j| *j != i).map(shift);
+    }
+
+    /// Drops positions past the end of a list of `len` items.
+    pub fn truncate(&mut self, len: usize) {
+        self.selected.retain(|i| *i < len);
+        self.last = self.last.filter(|i| *i < len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_selection_toggles_extends_and_follows_removals() {
+        let mut sel = ListSelection::default();
+        sel.click(2, false);
+        sel.click(5, true);
+        assert_eq!(sel.iter().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+
+        sel.click(3, false);
+        assert_eq!(sel.iter().collect::<Vec<_>>(), vec![2, 4, 5]);
+
+        sel.on_removed(3);
+        assert_eq!(sel.iter().collect::<Vec<_>>(), vec![2, 3, 4]);
+        sel.on_removed(2);
+        assert_eq!(sel.iter().collect::<Vec<_>>(), vec![2, 3]);
+
+        sel.truncate(3);
+        assert_eq!(sel.iter().collect::<Vec<_>>(), vec![2]);
+
+        sel.clear();
+        sel.click(1, true);
+        assert_eq!(sel.iter().collect::<Vec<_>>(), vec![1]);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::ui::InteractionEvent;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+/// Pixels the cursor must travel along one axis to count as a stroke.
+const STROKE_PIXELS: f32 = 40.0;
+
+/// Something a mouse gesture can do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GestureAction {
+    ClearSelection,
+    Pause,
+    CycleDrawMode,
+    CycleCursorMode,
+    GoToOwnship,
+    ToggleRotatingFrame,
+}
+
+impl GestureAction {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::ClearSelection => "Clear selection",
+            Self::Pause => "Pause or resume",
+            Self::CycleDrawMode => "Cycle draw mode",
+            Self::CycleCursorMode => "Cycle cursor mode",
+            Self::GoToOwnship => "Go to ownship",
+            Self::ToggleRotatingFrame => "Toggle rotating frame",
+        }
+    }
+
+    pub fn event(&self) -> InteractionEvent {
+        match self {
+            Self::ClearSelection => InteractionEvent::ClearSelection,
+            Self::Pause => InteractionEvent::SimPause,
+            Self::CycleDrawMode => InteractionEvent::DrawMode,
+            Self::CycleCursorMode => InteractionEvent::CursorMode,
+            Self::GoToOwnship => InteractionEvent::FollowPilot,
+            Self::ToggleRotatingFrame => InteractionEvent::ToggleRotatingFrame,
+        }
+    }
+}
+
+/// A gesture, written as the directions of its strokes: U, D, L and R.
+/// "DR" is a stroke down followed by one to the right.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GestureBinding {
+    pub strokes: String,
+    pub action: GestureAction,
+}
+
+/// Which gesture does what, read from its own file so that players can
+/// remap them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GestureMap {
+    pub gestures: Vec<GestureBinding>,
+}
+
+impl GestureMap {
+    pub fn get(&self, strokes: &str) -> Option<GestureAction> {
+        self.gestures
+            .iter()
+            .find(|g| g.strokes.eq_ignore_ascii_case(strokes))
+            .map(|g| g.action)
+    }
+}
+
+impl Default for GestureMap {
+    fn default() -> Self {
+        let bind = |strokes: &str, action| GestureBinding {
+            strokes: strokes.to_string(),
+            action,
+        };
+        Self {
+            gestures: vec![
+                bind("L", GestureAction::ClearSelection),
+                bind("D", GestureAction::Pause),
+                bind("R", GestureAction::CycleDrawMode),
+                bind("U", GestureAction::GoToOwnship),
+                bind("DR", GestureAction::CycleCursorMode),
+                bind("UD", GestureAction::ToggleRotatingFrame),
+            ],
+        }
+    }
+}
+
+pub fn load_gesture_map_from_file(filename: &Path) -> Result<GestureMap, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    Ok(serde_yaml::from_str(&s)?)
+}
+
+/// Turns the path of the cursor, while the right button is held, into a
+/// string of strokes.
+#[derive(Debug, Clone, Default)]
+pub struct GestureTracker {
+    trail: Vec<Vec2>,
+    strokes: String,
+    /// Where the stroke in progress started.
+    anchor: Option<Vec2>,
+}
+
+impl GestureTracker {
+    /// Cursor positions since the gesture began, in screen space.
+    pub fn trail(&self) -> &[Vec2] {
+        &self.trail
+    }
+
+    /// Strokes recognized so far.
+    pub fn strokes(&self) -> &str {
+        &self.strokes
+    }
+
+    pub fn cancel(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Follows the cursor while the right button is held, at `held`.
+    /// Returns the strokes drawn once it's released, if there were any.
+    pub fn update(&mut self, held: Option<Vec2>) -> Option<String> {
+        let Some(p) = held else {
+            let strokes = std::mem::take(&mut self.strokes);
+            self.cancel();
+            return (!strokes.is_empty()).then_some(strokes);
+        };
+
+        if self.trail.last() != Some(&p) {
+            self.trail.push(p);
+        }
+        let anchor = *self.anchor.get_or_insert(p);
+        let d = p - anchor;
+        if d.abs().max_element() < STROKE_PIXELS {
+            return None;
+        }
+        let dir = if d.x.abs() > d.y.abs() {
+            if d.x > 0.0 {
+                'R'
+            } else {
+                'L'
+            }
+        } else if d.y > 0.0 {
+            'U'
+        } else {
+            'D'
+        };
+        if !self.strokes.ends_with(dir) {
+            self.strokes.push(dir);
+        }
+        self.anchor = Some(p);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strokes_are_recognized_and_mapped() {
+        let mut tracker = GestureTracker::default();
+        let path = [(0.0, 0.0), (0.0, -20.0), (5.0, -60.0), (0.0, -90.0)]
+            .into_iter()
+            .chain([(30.0, -90.0), (80.0, -95.0), (140.0, -90.0)]);
+        for (x, y) in path {
+            assert_eq!(tracker.update(Some(Vec2::new(x, y))), None);
+        }
+        assert_eq!(tracker.strokes(), "DR");
+        assert_eq!(tracker.trail().len(), 7);
+
+        let strokes = tracker.update(None).unwrap();
+        assert!(tracker.trail().is_empty());
+        let map = GestureMap::default();
+        assert_eq!(map.get(&strokes), Some(GestureAction::CycleCursorMode));
+        assert_eq!(map.get("lrlr"), None);
+
+        // a click without dragging isn't a gesture
+        tracker.update(Some(Vec2::new(3.0, 4.0)));
+        tracker.update(Some(Vec2::new(8.0, 4.0)));
+        assert_eq!(tracker.update(None), None);
+    }
+}
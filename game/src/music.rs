@@ -0,0 +1,253 @@
+use crate::game::GameState;
+use bevy::audio::*;
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+use starling::prelude::*;
+
+/// Sound played over the music when a warning comes up.
+pub const MUSIC_STINGER: &str = "soft-pulse.ogg";
+
+/// Shortest wall time between stingers, so a burst of warnings doesn't
+/// turn into a drum roll.
+const STINGER_COOLDOWN: Nanotime = Nanotime::millis(4000);
+
+/// Layers of the soundtrack. Every stem loops for as long as the game
+/// runs, and the mix is changed by fading each one in and out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence, Component)]
+pub enum MusicStem {
+    Calm,
+    Percussion,
+    Tension,
+}
+
+impl MusicStem {
+    pub fn filename(&self) -> &'static str {
+        match self {
+            MusicStem::Calm => "building.ogg",
+            MusicStem::Percussion => "music-percussion.ogg",
+            MusicStem::Tension => "music-tension.ogg",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MusicSettings {
+    pub enabled: bool,
+    /// Volume of the music at full intensity, from zero to one.
+    pub volume: f32,
+    /// Bring in percussion during burns and final approaches, and tension
+    /// while warnings are active. Otherwise only the calm layer plays.
+    pub dynamic: bool,
+    /// Play a stinger over the music when warnings come up.
+    pub stingers: bool,
+    /// Seconds over which a layer fades fully in or out.
+    pub fade_secs: f32,
+}
+
+impl Default for MusicSettings {
+    fn default() -> Self {
+        MusicSettings {
+            enabled: true,
+            volume: 0.1,
+            dynamic: true,
+            stingers: true,
+            fade_secs: 2.0,
+        }
+    }
+}
+
+/// What's happening in the game, as far as the music is concerned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MusicCue {
+    /// The piloted vehicle is firing its engines.
+    pub burning: bool,
+    /// The piloted vehicle is closing in on its target or the ground.
+    pub approaching: bool,
+    /// A warning, like an impending collision, is active.
+    pub warning: bool,
+}
+
+/// Mixes the soundtrack's stems according to what's going on, easing
+/// each stem's volume towards its target rather than cutting.
+#[derive(Debug, Clone)]
+pub struct MusicManager {
+    volumes: [f32; 3],
+    warning: bool,
+    stinger: bool,
+    last_stinger: Option<Nanotime>,
+    last_update: Option<Nanotime>,
+    started: bool,
+}
+
+impl Default for MusicManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MusicManager {
+    pub fn new() -> Self {
+        Self {
+            volumes: [0.0; 3],
+            warning: false,
+            stinger: false,
+            last_stinger: None,
+            last_update: None,
+            started: false,
+        }
+    }
+
+    /// Volume the given stem is heading towards.
+    pub fn target(stem: MusicStem, cue: MusicCue, settings: &MusicSettings) -> f32 {
+        if !settings.enabled {
+            return 0.0;
+        }
+        let on = match stem {
+            MusicStem::Calm => true,
+            MusicStem::Percussion => settings.dynamic && (cue.burning || cue.approaching),
+            MusicStem::Tension => settings.dynamic && cue.warning,
+        };
+        if on {
+            settings.volume
+        } else {
+            0.0
+        }
+    }
+
+    pub fn volume(&self, stem: MusicStem) -> f32 {
+        self.volumes[stem as usize]
+    }
+
+    /// Eases every stem towards its target volume by as much as the wall
+    /// time since the last update allows, and queues a stinger when a
+    /// warning comes up.
+    pub fn update(&mut self, cue: MusicCue, settings: &MusicSettings, wall_time: Nanotime) {
+        let dt = self
+            .last_update
+            .map(|t| wall_time - t)
+            .unwrap_or(Nanotime::zero());
+        self.last_update = Some(wall_time);
+        let step = if settings.fade_secs > 0.0 {
+            settings.volume.max(0.01) * dt.to_secs() / settings.fade_secs
+        } else {
+            f32::INFINITY
+        };
+        for stem in enum_iterator::all::<MusicStem>() {
+            let target = Self::target(stem, cue, settings);
+            let v = &mut self.volumes[stem as usize];
+            *v += (target - *v).clamp(-step, step);
+        }
+
+        if cue.warning && !self.warning {
+            self.alert(settings, wall_time);
+        }
+        self.warning = cue.warning;
+    }
+
+    /// Queues a stinger, unless one played too recently.
+    pub fn alert(&mut self, settings: &MusicSettings, wall_time: Nanotime) {
+        if !settings.enabled || !settings.stingers {
+            return;
+        }
+        if self
+            .last_stinger
+            .is_some_and(|t| wall_time - t < STINGER_COOLDOWN)
+        {
+            return;
+        }
+        self.last_stinger = Some(wall_time);
+        self.stinger = true;
+    }
+
+    /// Whether a stinger is due to be played.
+    pub fn take_stinger(&mut self) -> bool {
+        std::mem::take(&mut self.stinger)
+    }
+}
+
+/// Starts every stem looping, silently, and then keeps their volumes in
+/// line with the music manager's mix.
+pub fn music_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut state: ResMut<GameState>,
+    sinks: Query<(&MusicStem, &AudioSink)>,
+) {
+    if !state.music.started {
+        state.music.started = true;
+        for stem in enum_iterator::all::<MusicStem>() {
+            let handle = match std::fs::canonicalize(state.args.audio_dir().join(stem.filename())) {
+                Ok(path) => asset_server.load(path),
+                Err(e) => {
+                    error!("Failed to load music stem {:?}: {}", stem, e);
+                    continue;
+                }
+            };
+            let settings = PlaybackSettings::LOOP.with_volume(Volume::new(0.0));
+            commands.spawn((AudioPlayer::new(handle), settings, stem));
+        }
+    }
+
+    for (stem, sink) in &sinks {
+        sink.set_volume(state.music.volume(*stem));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stems_fade_with_intensity_and_warnings_sting_once() {
+        let settings = MusicSettings::default();
+        let mut music = MusicManager::new();
+        let mut t = Nanotime::zero();
+        music.update(MusicCue::default(), &settings, t);
+        let mut run = |music: &mut MusicManager, cue: MusicCue, secs: i64| {
+            for _ in 0..secs {
+                t += Nanotime::secs(1);
+                music.update(cue, &settings, t);
+            }
+        };
+
+        // halfway through the fade, then all the way in
+        run(&mut music, MusicCue::default(), 1);
+        assert!((music.volume(MusicStem::Calm) - settings.volume / 2.0).abs() < 1e-6);
+        run(&mut music, MusicCue::default(), 5);
+        assert!((music.volume(MusicStem::Calm) - settings.volume).abs() < 1e-6);
+        assert!((music.volume(MusicStem::Percussion) - 0.0).abs() < 1e-6);
+
+        let burning = MusicCue {
+            burning: true,
+            ..default()
+        };
+        run(&mut music, burning, 5);
+        assert!((music.volume(MusicStem::Percussion) - settings.volume).abs() < 1e-6);
+        assert!(!music.take_stinger());
+
+        let warning = MusicCue {
+            warning: true,
+            ..default()
+        };
+        run(&mut music, warning, 5);
+        assert!(music.take_stinger());
+        assert!(!music.take_stinger());
+        assert!((music.volume(MusicStem::Percussion) - 0.0).abs() < 1e-6);
+        assert!((music.volume(MusicStem::Tension) - settings.volume).abs() < 1e-6);
+
+        let quiet = MusicSettings {
+            dynamic: false,
+            ..settings
+        };
+        assert_eq!(
+            MusicManager::target(MusicStem::Tension, warning, &quiet),
+            0.0
+        );
+        assert_eq!(
+            MusicManager::target(MusicStem::Calm, warning, &quiet),
+            quiet.volume
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::palette::Severity;
 use starling::prelude::*;
 
 #[derive(Debug, Clone)]
@@ -9,6 +10,9 @@ pub struct Notification {
     pub wall_time: Nanotime,
     pub extra_time: Nanotime,
     pub kind: NotificationType,
+    /// Callsign of the subject when the notification was raised, so it
+    /// reads the same after the vehicle is renamed or removed.
+    pub callsign: Option<String>,
 }
 
 impl Notification {
@@ -23,12 +27,17 @@ impl Notification {
             NotificationType::OrbiterCrashed(_) => self.extra_time + Nanotime::secs(10),
             NotificationType::OrbiterEscaped(_) => self.extra_time + Nanotime::secs(10),
             NotificationType::NumericalError(_) => self.extra_time + Nanotime::secs(30),
-            NotificationType::OrbiterDeleted(_) => self.extra_time + Nanotime::secs(5),
+            NotificationType::OrbiterDeleted(_) => self.extra_time + Nanotime::secs(15),
             NotificationType::ManeuverStarted(_) => self.extra_time + Nanotime::secs(2),
             NotificationType::ManeuverComplete(_) => self.extra_time + Nanotime::secs(7),
             NotificationType::ManeuverFailed(_) => self.extra_time + Nanotime::secs(3),
             NotificationType::NotControllable(_) => self.extra_time + Nanotime::secs(5),
+            NotificationType::HandedOver(..) => self.extra_time + Nanotime::secs(5),
             NotificationType::OrbitChanged(_) => self.extra_time + Nanotime::secs(2),
+            NotificationType::Touchdown(_, _) => self.extra_time + Nanotime::secs(7),
+            NotificationType::ApproachScored(_, _) => self.extra_time + Nanotime::secs(7),
+            NotificationType::AchievementUnlocked(_) => Nanotime::secs(10),
+            NotificationType::AlarmTriggered(_) => Nanotime::secs(10),
             NotificationType::Notice(_) => Nanotime::secs(7),
         }
     }
@@ -54,38 +63,100 @@ pub enum NotificationType {
     ManeuverFailed(EntityId),
     OrbitChanged(EntityId),
     NotControllable(EntityId),
+    /// A vehicle changed hands; the string says where it went.
+    HandedOver(EntityId, String),
+    Touchdown(EntityId, u32),
+    ApproachScored(EntityId, u32),
+    AchievementUnlocked(&'static str),
+    AlarmTriggered(String),
     Notice(String),
 }
 
-impl std::fmt::Display for NotificationType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl NotificationType {
+    /// The vehicle this notification is about, if any.
+    pub fn subject(&self) -> Option<EntityId> {
+        match self {
+            Self::OrbiterCrashed(id)
+            | Self::OrbiterEscaped(id)
+            | Self::NumericalError(id)
+            | Self::OrbiterDeleted(id)
+            | Self::ManeuverStarted(id)
+            | Self::ManeuverComplete(id)
+            | Self::ManeuverFailed(id)
+            | Self::OrbitChanged(id)
+            | Self::NotControllable(id)
+            | Self::HandedOver(id, _)
+            | Self::Touchdown(id, _)
+            | Self::ApproachScored(id, _) => Some(*id),
+            Self::AchievementUnlocked(_) | Self::AlarmTriggered(_) | Self::Notice(_) => None,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::OrbiterCrashed(_) | Self::OrbiterDeleted(_) | Self::ManeuverFailed(_) => {
+                Severity::Danger
+            }
+            Self::NumericalError(_) | Self::NotControllable(_) | Self::AlarmTriggered(_) => {
+                Severity::Warning
+            }
+            Self::Touchdown(_, 0) | Self::ApproachScored(_, 0) => Severity::Danger,
+            Self::ManeuverComplete(_)
+            | Self::Touchdown(..)
+            | Self::ApproachScored(..)
+            | Self::AchievementUnlocked(_) => Severity::Success,
+            Self::OrbiterEscaped(_)
+            | Self::ManeuverStarted(_)
+            | Self::OrbitChanged(_)
+            | Self::HandedOver(..)
+            | Self::Notice(_) => Severity::Info,
+        }
+    }
+
+    /// Writes the notification, referring to its subject as `who`.
+    fn describe(&self, f: &mut std::fmt::Formatter<'_>, who: &str) -> std::fmt::Result {
         match self {
-            Self::OrbiterCrashed(id) => {
-                write!(f, "Orbiter {id} crashed")
+            Self::OrbiterCrashed(_) => {
+                write!(f, "{who} crashed")
             }
-            Self::OrbiterEscaped(id) => {
-                write!(f, "Orbiter {id} escaped the system")
+            Self::OrbiterEscaped(_) => {
+                write!(f, "{who} escaped the system")
             }
-            Self::NumericalError(id) => {
-                write!(f, "Orbiter {id} encountered a numerical error")
+            Self::NumericalError(_) => {
+                write!(f, "{who} encountered a numerical error")
             }
-            Self::OrbiterDeleted(id) => {
-                write!(f, "Orbiter {id} was removed from system tracking")
+            Self::OrbiterDeleted(_) => {
+                write!(f, "{who} was removed from system tracking")
             }
-            Self::ManeuverStarted(id) => {
-                write!(f, "Orbiter {id} has initiated a mission")
+            Self::ManeuverStarted(_) => {
+                write!(f, "{who} has initiated a mission")
             }
-            Self::ManeuverComplete(id) => {
-                write!(f, "Orbiter {id} has completed a mission")
+            Self::ManeuverComplete(_) => {
+                write!(f, "{who} has completed a mission")
             }
-            Self::ManeuverFailed(id) => {
-                write!(f, "Orbiter {id} failed to execute a maneuver")
+            Self::ManeuverFailed(_) => {
+                write!(f, "{who} failed to execute a maneuver")
             }
-            Self::OrbitChanged(id) => {
-                write!(f, "Orbiter {id}'s orbit has changed")
+            Self::OrbitChanged(_) => {
+                write!(f, "{who}'s orbit has changed")
             }
-            Self::NotControllable(id) => {
-                write!(f, "Orbiter {id} is not controllable")
+            Self::NotControllable(_) => {
+                write!(f, "{who} is not controllable")
+            }
+            Self::HandedOver(_, to) => {
+                write!(f, "{who} was handed over {to}")
+            }
+            Self::Touchdown(_, points) => {
+                write!(f, "{who} touched down, scored {points}/100")
+            }
+            Self::ApproachScored(_, points) => {
+                write!(f, "{who} made contact, scored {points}/100")
+            }
+            Self::AchievementUnlocked(name) => {
+                write!(f, "Achievement unlocked: {name}")
+            }
+            Self::AlarmTriggered(name) => {
+                write!(f, "Alarm: {name}")
             }
             Self::Notice(str) => {
                 write!(f, "Notice: {str}")
@@ -94,8 +165,19 @@ impl std::fmt::Display for NotificationType {
     }
 }
 
+impl std::fmt::Display for NotificationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let who = self.subject().map(|id| format!("Orbiter {id}"));
+        self.describe(f, who.as_deref().unwrap_or_default())
+    }
+}
+
 impl std::fmt::Display for Notification {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}] {}", self.sim_time, self.kind)
+        write!(f, "[{}] ", self.sim_time)?;
+        match (&self.callsign, self.kind.subject()) {
+            (Some(callsign), Some(id)) => self.kind.describe(f, &format!("{callsign} ({id})")),
+            _ => write!(f, "{}", self.kind),
+        }
     }
 }
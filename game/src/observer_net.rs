@@ -0,0 +1,292 @@
+use bevy::log::error;
+use starling::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Port hosts listen on unless told otherwise.
+pub const DEFAULT_OBSERVER_PORT: u16 = 7313;
+
+/// How often, in wall time, hosts send out a snapshot.
+const SNAPSHOT_INTERVAL: Nanotime = Nanotime::millis(100);
+
+/// Unsent bytes an observer may fall behind by before it's dropped.
+const MAX_BACKLOG: usize = 16 * 1024 * 1024;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One observer connected to a host.
+struct RemoteObserver {
+    stream: TcpStream,
+    addr: SocketAddr,
+    /// Designs this observer has been sent, by discriminator.
+    sent: HashMap<EntityId, u64>,
+    /// Bytes queued for this observer which the socket hasn't taken yet.
+    outbox: Vec<u8>,
+}
+
+impl RemoteObserver {
+    /// Writes as much of the outbox as the socket will take without
+    /// blocking. Returns false if the observer has gone away.
+    fn flush(&mut self) -> bool {
+        while !self.outbox.is_empty() {
+            match self.stream.write(&self.outbox) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.outbox.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => return false,
+            }
+        }
+        self.outbox.len() <= MAX_BACKLOG
+    }
+}
+
+/// Streams snapshots of the local universe to every observer which
+/// connects. Observers can't send anything back.
+pub struct SnapshotHost {
+    listener: TcpListener,
+    observers: Vec<RemoteObserver>,
+    last_sent: Option<Nanotime>,
+}
+
+impl SnapshotHost {
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, String> {
+        let listener = TcpListener::bind(addr).map_err(|e| e.to_string())?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        Ok(Self {
+            listener,
+            observers: Vec::new(),
+            last_sent: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr().ok()
+    }
+
+    pub fn observer_count(&self) -> usize {
+        self.observers.len()
+    }
+
+    /// Accepts new observers, and sends each one a snapshot once per
+    /// interval. Observers which are too far behind to catch up get a
+    /// snapshot only once they've drained what they were sent before.
+    /// Returns news of observers joining and leaving.
+    pub fn update(&mut self, universe: &Universe, wall_time: Nanotime) -> Vec<String> {
+        let mut news = Vec::new();
+        while let Ok((stream, addr)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_err() {
+                continue;
+            }
+            _ = stream.set_nodelay(true);
+            news.push(format!("Observer connected from {}", addr));
+            self.observers.push(RemoteObserver {
+                stream,
+                addr,
+                sent: HashMap::new(),
+                outbox: Vec::new(),
+            });
+        }
+
+        let due = self
+            .last_sent
+            .is_none_or(|t| wall_time - t >= SNAPSHOT_INTERVAL);
+        if due {
+            self.last_sent = Some(wall_time);
+        }
+
+        self.observers.retain_mut(|o| {
+            if due && o.outbox.is_empty() {
+                match encode_frame(&universe.snapshot(&mut o.sent)) {
+                    Ok(frame) => o.outbox = frame,
+                    Err(e) => error!("Failed to encode snapshot: {}", e),
+                }
+            }
+            let alive = o.flush();
+            if !alive {
+                news.push(format!("Observer {} disconnected", o.addr));
+            }
+            alive
+        });
+        news
+    }
+}
+
+/// Connection to a host, from which snapshots are read as they arrive.
+pub struct SnapshotObserver {
+    stream: TcpStream,
+    host: SocketAddr,
+    decoder: FrameDecoder,
+    /// Vehicles which couldn't be built from the designs sent.
+    unbuilt: HashSet<EntityId>,
+}
+
+/// Works out the address of a host given as an IP address or hostname,
+/// with or without a port. IPv6 addresses need brackets to take a port,
+/// as in "[::1]:port".
+fn resolve_host(host: &str) -> Result<SocketAddr, String> {
+    if let Ok(addr) = host.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, DEFAULT_OBSERVER_PORT));
+    }
+    let with_port = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, DEFAULT_OBSERVER_PORT)
+    };
+    with_port
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or(format!("No address found for \"{}\"", host))
+}
+
+impl SnapshotObserver {
+    /// Connects to a host, given as "address" or "address:port".
+    pub fn connect(host: &str) -> Result<Self, String> {
+        let addr = resolve_host(host)?;
+        let stream =
+            TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(|e| e.to_string())?;
+        stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+        Ok(Self {
+            stream,
+            host: addr,
+            decoder: FrameDecoder::default(),
+            unbuilt: HashSet::new(),
+        })
+    }
+
+    pub fn host(&self) -> SocketAddr {
+        self.host
+    }
+
+    /// Keeps track of vehicles which couldn't be built, since they'll
+    /// fail again with every snapshot. Returns how many are new.
+    pub fn note_unbuilt(&mut self, ids: impl IntoIterator<Item = EntityId>) -> usize {
+        ids.into_iter()
+            .filter(|id| self.unbuilt.insert(*id))
+            .count()
+    }
+
+    /// Every snapshot which has arrived since the last poll, oldest
+    /// first. Each one must be applied, since only the first snapshot to
+    /// mention a vehicle carries its design. Fails once the host has gone
+    /// away or sent something unreadable.
+    pub fn poll(&mut self) -> Result<Vec<UniverseSnapshot>, String> {
+        let mut buf = [0u8; 64 * 1024];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => return Err("Host closed the connection".to_string()),
+                Ok(n) => self.decoder.push(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        let mut ret = Vec::new();
+        while let Some(snapshot) = self
+            .decoder
+            .next_frame::<UniverseSnapshot>()
+            .map_err(|e| e.to_string())?
+        {
+            if snapshot.protocol != SNAPSHOT_PROTOCOL {
+                return Err(format!(
+                    "Host speaks snapshot protocol {}, but this is {}",
+                    snapshot.protocol, SNAPSHOT_PROTOCOL
+                ));
+            }
+            ret.push(snapshot);
+        }
+        Ok(ret)
+    }
+}
+
+/// Whether this instance is sharing its universe, watching someone
+/// else's, or neither.
+#[derive(Default)]
+pub enum NetworkSession {
+    #[default]
+    Offline,
+    Hosting(SnapshotHost),
+    Observing(SnapshotObserver),
+}
+
+impl NetworkSession {
+    pub fn is_observing(&self) -> bool {
+        matches!(self, Self::Observing(_))
+    }
+
+    pub fn status(&self) -> Option<String> {
+        match self {
+            Self::Offline => None,
+            Self::Hosting(host) => Some(format!(
+                "Hosting on {}, {} observer(s)",
+                host.local_addr().map(|a| a.to_string()).unwrap_or_default(),
+                host.observer_count()
+            )),
+            Self::Observing(obs) => Some(format!("Observing {}", obs.host())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observers_receive_host_snapshots() {
+        let mut host = SnapshotHost::bind("127.0.0.1:0").unwrap();
+        let addr = host.local_addr().unwrap();
+        let mut observer = SnapshotObserver::connect(&addr.to_string()).unwrap();
+
+        let universe = Universe::empty();
+        let mut received = Vec::new();
+        for i in 0..200i64 {
+            let news = host.update(&universe, SNAPSHOT_INTERVAL * i);
+            if i == 0 {
+                assert!(news.is_empty() || news[0].starts_with("Observer connected"));
+            }
+            received.extend(observer.poll().unwrap());
+            if received.len() >= 2 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(host.observer_count(), 1);
+        assert!(received.len() >= 2);
+        assert_eq!(received[0].stamp, universe.stamp());
+
+        drop(host);
+        let closed = (0..200).any(|_| {
+            std::thread::sleep(Duration::from_millis(5));
+            observer.poll().is_err()
+        });
+        assert!(closed);
+    }
+
+    #[test]
+    fn hosts_resolve_with_and_without_ports() {
+        let v6 = IpAddr::V6(std::net::Ipv6Addr::LOCALHOST);
+        let v4 = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+        assert_eq!(
+            resolve_host("::1"),
+            Ok(SocketAddr::new(v6, DEFAULT_OBSERVER_PORT))
+        );
+        assert_eq!(resolve_host("[::1]:9000"), Ok(SocketAddr::new(v6, 9000)));
+        assert_eq!(
+            resolve_host("127.0.0.1"),
+            Ok(SocketAddr::new(v4, DEFAULT_OBSERVER_PORT))
+        );
+        assert_eq!(
+            resolve_host("127.0.0.1:9000"),
+            Ok(SocketAddr::new(v4, 9000))
+        );
+        assert!(resolve_host("localhost")
+            .is_ok_and(|a| a.port() == DEFAULT_OBSERVER_PORT && a.ip().is_loopback()));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::camera_controller::CameraProjection;
+use crate::game::GameState;
+use bevy::prelude::*;
+use starling::prelude::*;
+
+/// Distance in pixels between the edge of the screen and the chevrons
+/// pointing at off-screen vehicles.
+pub const OFFSCREEN_MARKER_MARGIN: f32 = 60.0;
+
+/// Chevron at the edge of the screen pointing towards an off-screen
+/// vehicle of interest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffscreenMarker {
+    pub id: EntityId,
+    /// Position of the chevron in camera coordinates.
+    pub pos: Vec2,
+    /// Direction the chevron points, in radians.
+    pub angle: f32,
+    /// Distance to the vehicle, in meters, from the piloted vehicle for
+    /// its target, or from the center of the screen otherwise.
+    pub distance: f64,
+    pub color: Srgba,
+}
+
+/// Where a chevron pointing at the camera-space point `p` goes, inset by
+/// `margin` from a screen with the given span, and the direction it
+/// points. None if the point is on screen.
+pub fn edge_point(p: Vec2, span: Vec2, margin: f32) -> Option<(Vec2, f32)> {
+    let half = (span / 2.0 - Vec2::splat(margin)).max(Vec2::splat(1.0));
+    if p.x.abs() <= span.x / 2.0 && p.y.abs() <= span.y / 2.0 {
+        return None;
+    }
+    let t = (half.x / p.x.abs()).min(half.y / p.y.abs());
+    Some((p * t, p.to_angle()))
+}
+
+/// Chevrons for the piloted vehicle and its target, when they're off
+/// screen in the orbital view.
+pub fn offscreen_markers(state: &GameState) -> Vec<OffscreenMarker> {
+    let ctx = &state.orbital_context;
+    let span = state.input.screen_bounds.span;
+    let palette = state.settings.color_palette;
+    let pilot = state.piloting();
+    let target = pilot
+        .and_then(|p| state.universe.surface_vehicles.get(&p))
+        .and_then(|sv| sv.target());
+    let center = ctx.c2w(Vec2::ZERO);
+
+    [(pilot, palette.piloting()), (target, palette.targeting())]
+        .into_iter()
+        .filter_map(|(id, color)| {
+            let id = id?;
+            let pos = state.display_position(id)?;
+            let (edge, angle) = edge_point(ctx.w2c(pos), span, OFFSCREEN_MARKER_MARGIN)?;
+            let from = match pilot {
+                Some(p) if p != id => state.display_position(p).unwrap_or(center),
+                _ => center,
+            };
+            Some(OffscreenMarker {
+                id,
+                pos: edge,
+                angle,
+                distance: pos.distance(from),
+                color,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chevrons_sit_on_the_inset_edge() {
+        let span = Vec2::new(800.0, 600.0);
+        assert_eq!(edge_point(Vec2::new(100.0, -200.0), span, 50.0), None);
+
+        let (p, angle) = edge_point(Vec2::new(4000.0, 0.0), span, 50.0).unwrap();
+        assert_eq!(p, Vec2::new(350.0, 0.0));
+        assert_eq!(angle, 0.0);
+
+        // clipped by whichever edge it reaches first
+        let (p, _) = edge_point(Vec2::new(-1000.0, 2000.0), span, 50.0).unwrap();
+        assert_eq!(p, Vec2::new(-125.0, 250.0));
+    }
+}
@@ -1,5 +1,9 @@
+use crate::double_click::DoubleClickTarget;
+use crate::hud::HudWidget;
+use crate::ownership::HandoverTarget;
 use crate::scenes::CursorMode;
 use crate::scenes::SceneType;
+use crate::scenes::{ScenarioParam, ScenarioTool};
 use crate::sim_rate::SimRate;
 use starling::prelude::*;
 use std::path::PathBuf;
@@ -7,13 +11,41 @@ use std::path::PathBuf;
 #[derive(Debug, Clone, PartialEq)]
 pub enum OnClick {
     Orbiter(EntityId),
+    CenterCamera(EntityId),
     Exit,
     Save,
     Load,
+    ImportVehicles,
+    RefreshVehicles,
+    SaveToLibrary,
     ToggleDrawMode,
+    ToggleRotatingFrame,
+    TogglePhaseArcs,
+    LaunchToOrbit,
+    SuggestAvoidanceBurn,
+    AdjustOrbit(OrbitAdjustment),
+    CommitManeuverNode,
+    DeleteManeuverNode,
+    AdjustAscent(AscentParam, i32),
+    ResetAscent,
+    QueueServiceTask(ServiceTask),
+    CancelServiceTask(usize),
+    InsureVehicle(EntityId),
+    RebuildLostVehicle(usize),
     ClearTracks,
     CreateGroup,
     DisbandGroup(EntityId),
+    InspectGroup(EntityId),
+    SetGroupColor(EntityId, usize),
+    RenameGroup(EntityId),
+    MergeGroups(EntityId, EntityId),
+    SplitGroup(EntityId),
+    HandOverSelected(HandoverTarget),
+    ConfirmHandover,
+    DismissHandover,
+    ApplySavedFilter(usize),
+    TogglePinnedFilter(usize),
+    DeleteSavedFilter(usize),
     ClearOrbits,
     CurrentBody(EntityId),
     SelectedCount,
@@ -26,6 +58,20 @@ pub enum OnClick {
     GlobalOrbit(usize),
     DeleteOrbit(usize),
     DeleteOrbiter,
+    UndoDelete(EntityId),
+    ToggleAnnotation(usize),
+    DeleteAnnotation(usize),
+    SelectPinned(usize),
+    ClearPinnedSelection,
+    UnpinSelected,
+    TogglePinnedVisibility,
+    SelectPinnedVehicles,
+    GroupPinnedVehicles,
+    DeleteAlarm(usize),
+    CycleAlarmAction(usize),
+    AddTimelineNote,
+    DeleteTimelineNote(usize),
+    CycleTimelineNoteAlarm(usize),
     ClearMission,
     CommitMission,
     CursorMode(CursorMode),
@@ -35,21 +81,73 @@ pub enum OnClick {
     ClearTarget,
     ClearPilot,
     SwapOwnshipTarget,
+    SpawnPracticeTarget,
+    CycleStructureKind(EntityId),
+    ResizeStructure(EntityId, i32, i32),
+    RepairStructure(EntityId),
+    DemolishStructure(EntityId),
+    CloseStructureInspector,
+    RequestDocking,
+    CancelDocking(EntityId),
+    Undock(EntityId, EntityId),
+    Rendezvous,
+    CancelRendezvous(EntityId),
+    StartTransfer(EntityId, EntityId, Item),
+    CancelTransfer(EntityId, EntityId),
     PinObject(EntityId),
     UnpinObject(EntityId),
     SelectPart(String),
     ToggleLayer(PartLayer),
     LoadVehicle(PathBuf),
+    CompareVehicle(PathBuf),
+    ClearComparison,
     DismissExitDialog,
+    ResumeGame,
     ConfirmExitDialog,
     TogglePartsMenuCollapsed,
+    FocusPartSearch,
+    PartCategory(Option<PartLayer>),
     ToggleVehiclesMenuCollapsed,
     ToggleLayersMenuCollapsed,
+    ToggleAssembliesMenuCollapsed,
+    ToggleStagesMenuCollapsed,
+    SelectEditorStage(usize),
+    /// Swaps an editor stage with the one after it if true, or the one
+    /// before it.
+    MoveEditorStage(usize, bool),
+    SetPartStage(PartId, usize),
+    SelectAssembly(String),
+    SaveAssembly,
+    ClearEditorSelection,
+    RoutePipeFrom(PartId),
+    AcceptPipeRoute,
+    CancelPipeRoute,
     ToggleVehicleInfo,
+    ToggleFlowPreview,
+    CycleSymmetry,
+    ToggleHudEditor,
+    ToggleRailPanel(usize),
+    ScenarioTool(ScenarioTool),
+    SelectScenarioBody(EntityId),
+    AdjustScenarioBody(EntityId, ScenarioParam, i32),
+    ToggleScenarioRetrograde(EntityId),
+    DeleteScenarioBody(EntityId),
+    DeleteScenarioSite(usize),
+    DeleteScenarioVehicle(usize),
+    DeleteScenarioEvent(usize),
+    CycleScenarioModel,
+    ScenarioFromUniverse,
+    PlayScenario,
+    ToggleHudWidget(HudWidget),
+    HudWidgetHandle(HudWidget),
     SendToSurface(EntityId),
     IncrementThrottle(i32),
     OpenNewCraft,
+    ToggleEditorRecovery,
+    RestoreEditorVersion(usize),
+    DiscardEditorRecovery,
     WriteVehicleToImage,
+    WriteVehicleToSvg,
     RotateCraft,
     NormalizeCraft,
     ToggleThruster(usize),
@@ -58,5 +156,24 @@ pub enum OnClick {
     ClearContents(PartId),
     GoToSurface(EntityId),
     SetControllerPolicy(VehicleControlPolicy),
+    CycleColorPalette,
+    ToggleMusic,
+    CopyPlan,
+    PastePlan,
+    ToggleBurnAutoHold,
+    CycleDoubleClick(DoubleClickTarget),
+    ActivateStage(EntityId),
+    ToggleOrbitHistory,
+    ToggleDvMap,
+    ToggleConicConstruction,
+    ToggleRecords,
+    ToggleSpectator,
+    ToggleDirector,
+    ScrubOrbitHistory(i32),
+    ScrubToOrbitRecord(usize),
+    ClearOrbitScrub,
+    ToggleCaptureAssist(EntityId),
+    AddWaypoint,
+    DeleteWaypoint(EntityId),
     Nullopt,
 }
@@ -0,0 +1,216 @@
+use starling::prelude::*;
+use std::collections::HashSet;
+
+/// Where a handover puts the vehicles handed over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandoverTarget {
+    /// Into an existing group.
+    Group(EntityId),
+    /// Into a new group of their own.
+    NewGroup,
+    /// Out of whatever group they're in, but still in the fleet.
+    Ungrouped,
+    /// Out of the fleet altogether, left to drift as derelicts.
+    Abandon,
+    /// Back into the fleet, from being derelict.
+    Reclaim,
+}
+
+/// A transfer of vehicles, waiting on the player to confirm it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handover {
+    pub ids: Vec<EntityId>,
+    pub target: HandoverTarget,
+}
+
+/// A handover which was carried out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandoverRecord {
+    pub stamp: Nanotime,
+    pub target: HandoverTarget,
+    /// Each vehicle handed over, and the group it was in beforehand.
+    pub vehicles: Vec<(EntityId, Option<EntityId>)>,
+    /// Group the vehicles ended up in, if any.
+    pub group: Option<EntityId>,
+}
+
+/// Which vehicles still belong to the player's fleet, and every handover
+/// made so far. Derelicts stay in the universe, but can't be flown,
+/// grouped or given orders until they're reclaimed.
+#[derive(Debug, Clone, Default)]
+pub struct Fleet {
+    derelicts: HashSet<EntityId>,
+    pub history: Vec<HandoverRecord>,
+}
+
+impl Fleet {
+    pub fn is_derelict(&self, id: EntityId) -> bool {
+        self.derelicts.contains(&id)
+    }
+
+    /// Whether a vehicle is the player's own: still in the fleet, and not
+    /// one of the background traffic.
+    pub fn owns(&self, universe: &Universe, id: EntityId) -> bool {
+        universe.surface_vehicles.contains_key(&id)
+            && !self.is_derelict(id)
+            && universe.traffic.get(id).is_none()
+    }
+
+    pub fn derelict_count(&self) -> usize {
+        self.derelicts.len()
+    }
+
+    /// Forgets a vehicle which is gone for good.
+    pub fn forget(&mut self, id: EntityId) {
+        self.derelicts.remove(&id);
+    }
+
+    /// Checks that a handover can be made, without making it.
+    pub fn check(&self, universe: &Universe, handover: &Handover) -> Result<(), String> {
+        if handover.ids.is_empty() {
+            return Err("No vehicles to hand over".to_string());
+        }
+        if let HandoverTarget::Group(gid) = handover.target {
+            if !universe.unique_groups().contains(&gid) {
+                return Err(format!("No group with ID {}", gid));
+            }
+        }
+        for id in &handover.ids {
+            if !universe.surface_vehicles.contains_key(id) {
+                return Err(format!("No vehicle with ID {}", id));
+            }
+            let derelict = self.is_derelict(*id);
+            match handover.target {
+                HandoverTarget::Reclaim if !derelict => {
+                    return Err(format!("{} isn't derelict", id));
+                }
+                HandoverTarget::Reclaim => (),
+                _ if derelict => {
+                    return Err(format!("{} is derelict; reclaim it first", id));
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Carries out a handover. Abandoned vehicles leave their group and
+    /// lose their autopilot, background traffic duties, scheduled
+    /// maneuvers and place in any docking queue.
+    pub fn hand_over(
+        &mut self,
+        universe: &mut Universe,
+        handover: &Handover,
+    ) -> Result<&HandoverRecord, String> {
+        self.check(universe, handover)?;
+
+        let vehicles = handover
+            .ids
+            .iter()
+            .map(|id| (*id, universe.group_membership(id)))
+            .collect();
+
+        let group = match handover.target {
+            HandoverTarget::Group(gid) => {
+                for id in &handover.ids {
+                    universe.constellations.insert(*id, gid);
+                }
+                Some(gid)
+            }
+            HandoverTarget::NewGroup => Some(universe.create_group(handover.ids.clone())),
+            HandoverTarget::Ungrouped => {
+                for id in &handover.ids {
+                    universe.constellations.remove(id);
+                }
+                None
+            }
+            HandoverTarget::Abandon => {
+                for id in &handover.ids {
+                    universe.constellations.remove(id);
+                    universe.traffic.release(*id);
+                    universe.maneuvers.cancel(*id);
+                    universe.cancel_docking(*id);
+                    if let Some(sv) = universe.surface_vehicles.get_mut(id) {
+                        sv.controller.set_idle();
+                    }
+                    self.derelicts.insert(*id);
+                }
+                None
+            }
+            HandoverTarget::Reclaim => {
+                for id in &handover.ids {
+                    self.derelicts.remove(id);
+                }
+                None
+            }
+        };
+
+        self.history.push(HandoverRecord {
+            stamp: universe.stamp(),
+            target: handover.target,
+            vehicles,
+            group,
+        });
+        Ok(self.history.last().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fleet_in_leo;
+
+    #[test]
+    fn vehicles_change_hands_and_derelicts_are_locked_out() {
+        let (mut universe, ids) = fleet_in_leo(3);
+        let mut fleet = Fleet::default();
+        let handover = |ids: &[EntityId], target| Handover {
+            ids: ids.to_vec(),
+            target,
+        };
+
+        let gid = fleet
+            .hand_over(
+                &mut universe,
+                &handover(&ids[..2], HandoverTarget::NewGroup),
+            )
+            .unwrap()
+            .group
+            .unwrap();
+        assert_eq!(universe.group_membership(&ids[1]), Some(gid));
+
+        fleet
+            .hand_over(
+                &mut universe,
+                &handover(&ids[2..], HandoverTarget::Group(gid)),
+            )
+            .unwrap();
+        assert_eq!(universe.group_membership(&ids[2]), Some(gid));
+
+        let record = fleet
+            .hand_over(&mut universe, &handover(&ids[..1], HandoverTarget::Abandon))
+            .unwrap();
+        assert_eq!(record.vehicles, vec![(ids[0], Some(gid))]);
+        assert!(fleet.is_derelict(ids[0]));
+        assert!(!fleet.owns(&universe, ids[0]));
+        assert!(fleet.owns(&universe, ids[1]));
+        assert_eq!(universe.group_membership(&ids[0]), None);
+
+        // derelicts have to be reclaimed before they can be regrouped
+        let regroup = handover(&ids[..1], HandoverTarget::Group(gid));
+        assert!(fleet.hand_over(&mut universe, &regroup).is_err());
+        let reclaim = handover(&ids[1..2], HandoverTarget::Reclaim);
+        assert!(fleet.check(&universe, &reclaim).is_err());
+        let reclaim = handover(&ids[..1], HandoverTarget::Reclaim);
+        fleet.hand_over(&mut universe, &reclaim).unwrap();
+        assert!(!fleet.is_derelict(ids[0]));
+        assert!(fleet.hand_over(&mut universe, &regroup).is_ok());
+
+        let missing = handover(&[EntityId(99999)], HandoverTarget::Ungrouped);
+        assert!(fleet.check(&universe, &missing).is_err());
+        assert_eq!(fleet.history.len(), 5);
+
+        universe.traffic.enlist(ids[2], universe.stamp());
+        assert!(!fleet.owns(&universe, ids[2]));
+    }
+}
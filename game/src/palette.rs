@@ -0,0 +1,185 @@
+use bevy::color::palettes::basic::*;
+use bevy::color::palettes::css::*;
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+/// Colors used to tell groups, orbits and notifications apart. Besides the
+/// standard rainbow, there are palettes chosen to stay distinguishable
+/// under the common color vision deficiencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Sequence, Deserialize, Serialize)]
+pub enum ColorPalette {
+    #[default]
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+/// How urgent something shown to the player is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Danger,
+}
+
+/// Okabe-Ito palette, which stays distinct for both kinds of red-green
+/// color blindness.
+const OKABE_ITO: [Srgba; 7] = [
+    Srgba::rgb(0.90, 0.62, 0.0),
+    Srgba::rgb(0.34, 0.71, 0.91),
+    Srgba::rgb(0.0, 0.62, 0.45),
+    Srgba::rgb(0.94, 0.89, 0.26),
+    Srgba::rgb(0.0, 0.45, 0.70),
+    Srgba::rgb(0.84, 0.37, 0.0),
+    Srgba::rgb(0.80, 0.47, 0.65),
+];
+
+/// Reds, pinks and cyans, varied in lightness, avoiding the blue-green and
+/// yellow-violet pairs that look alike with tritanopia.
+const TRITAN: [Srgba; 7] = [
+    Srgba::rgb(0.85, 0.11, 0.38),
+    Srgba::rgb(0.0, 0.67, 0.76),
+    Srgba::rgb(1.0, 0.54, 0.50),
+    Srgba::rgb(0.0, 0.41, 0.36),
+    Srgba::rgb(0.88, 0.88, 0.88),
+    Srgba::rgb(0.43, 0.30, 0.25),
+    Srgba::rgb(0.50, 0.87, 0.92),
+];
+
+impl ColorPalette {
+    pub fn next(&self) -> Self {
+        enum_iterator::next_cycle(self)
+    }
+
+    fn categorical(&self) -> Option<&'static [Srgba]> {
+        match self {
+            Self::Standard => None,
+            Self::Deuteranopia | Self::Protanopia => Some(&OKABE_ITO),
+            Self::Tritanopia => Some(&TRITAN),
+        }
+    }
+
+    /// Stable color for anything hashable, such as a group or item name.
+    /// The standard palette spreads hues over the whole color wheel; the
+    /// others pick from a short list of mutually distinct colors.
+    pub fn hashable_to_color(&self, h: &impl std::hash::Hash) -> Hsla {
+        use std::hash::Hasher;
+        let Some(colors) = self.categorical() else {
+            return crate::sprites::hashable_to_color(h);
+        };
+        let mut s = std::hash::DefaultHasher::new();
+        h.hash(&mut s);
+        let i = s.finish() % colors.len() as u64;
+        colors[i as usize].into()
+    }
+
+    /// Colors the player can pick from for a group, from the same family
+    /// as those `hashable_to_color` hands out.
+    pub fn swatches(&self) -> Vec<Hsla> {
+        match self.categorical() {
+            Some(colors) => colors.iter().map(|c| (*c).into()).collect(),
+            None => (0..8)
+                .map(|i| Hsla::new(45.0 * i as f32, 1.0, 0.5, 1.0))
+                .collect(),
+        }
+    }
+
+    pub fn severity(&self, severity: Severity) -> Srgba {
+        match (self, severity) {
+            (Self::Standard, Severity::Info) => TEAL,
+            (Self::Standard, Severity::Success) => GREEN,
+            (Self::Standard, Severity::Warning) => YELLOW,
+            (Self::Standard, Severity::Danger) => RED,
+            (Self::Deuteranopia | Self::Protanopia, Severity::Info) => OKABE_ITO[1],
+            (Self::Deuteranopia | Self::Protanopia, Severity::Success) => OKABE_ITO[4],
+            (Self::Deuteranopia | Self::Protanopia, Severity::Warning) => OKABE_ITO[3],
+            // reds look dark to protans, so danger is made brighter
+            (Self::Protanopia, Severity::Danger) => Srgba::rgb(1.0, 0.45, 0.0),
+            (Self::Deuteranopia, Severity::Danger) => OKABE_ITO[5],
+            (Self::Tritanopia, Severity::Info) => TRITAN[1],
+            (Self::Tritanopia, Severity::Success) => TRITAN[3],
+            (Self::Tritanopia, Severity::Warning) => TRITAN[2],
+            (Self::Tritanopia, Severity::Danger) => TRITAN[0],
+        }
+    }
+
+    /// Orbit color of the piloted vehicle.
+    pub fn piloting(&self) -> Srgba {
+        match self {
+            Self::Standard => ORANGE,
+            Self::Deuteranopia | Self::Protanopia => OKABE_ITO[0],
+            Self::Tritanopia => TRITAN[2],
+        }
+    }
+
+    /// Orbit color of the piloted vehicle's target.
+    pub fn targeting(&self) -> Srgba {
+        match self {
+            Self::Standard => TEAL,
+            Self::Deuteranopia | Self::Protanopia => OKABE_ITO[1],
+            Self::Tritanopia => TRITAN[1],
+        }
+    }
+
+    /// Orbit color of selected vehicles.
+    pub fn tracked(&self) -> Srgba {
+        match self {
+            Self::Standard => PURPLE,
+            Self::Deuteranopia | Self::Protanopia => OKABE_ITO[6],
+            Self::Tritanopia => TRITAN[4],
+        }
+    }
+}
+
+impl std::fmt::Display for ColorPalette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Standard => write!(f, "standard"),
+            Self::Deuteranopia => write!(f, "deuteranopia"),
+            Self::Protanopia => write!(f, "protanopia"),
+            Self::Tritanopia => write!(f, "tritanopia"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palettes_are_stable_and_distinct() {
+        for palette in enum_iterator::all::<ColorPalette>() {
+            for key in ["alpha", "beta", "gamma"] {
+                assert_eq!(
+                    palette.hashable_to_color(&key),
+                    palette.hashable_to_color(&key)
+                );
+            }
+
+            let roles = [palette.piloting(), palette.targeting(), palette.tracked()];
+            let severities: Vec<_> = enum_iterator::all::<Severity>()
+                .map(|s| palette.severity(s))
+                .collect();
+            for colors in [&roles[..], &severities[..]] {
+                for (i, a) in colors.iter().enumerate() {
+                    for b in &colors[i + 1..] {
+                        assert_ne!(a, b, "{} reuses a color", palette);
+                    }
+                }
+            }
+
+            if palette != ColorPalette::Standard {
+                let c = Vec4::from(Srgba::from(palette.hashable_to_color(&"alpha")).to_f32_array());
+                let colors = palette.categorical().unwrap();
+                assert!(colors
+                    .iter()
+                    .any(|p| Vec4::from(p.to_f32_array()).distance(c) < 1e-3));
+            }
+        }
+
+        assert_eq!(ColorPalette::Tritanopia.next(), ColorPalette::Standard);
+    }
+}
@@ -0,0 +1,97 @@
+use starling::prelude::*;
+
+/// A planning command issued while the simulation is paused, applied to
+/// its vehicle when the simulation resumes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PausedAction {
+    SetPolicy(VehicleControlPolicy),
+    SetTarget(Option<EntityId>),
+}
+
+impl PausedAction {
+    /// Whether two actions set the same thing, so that the later one
+    /// supersedes the earlier.
+    fn same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl std::fmt::Display for PausedAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SetPolicy(p) => write!(f, "{}", p.to_status_str()),
+            Self::SetTarget(Some(id)) => write!(f, "Target {}", id),
+            Self::SetTarget(None) => write!(f, "Clear target"),
+        }
+    }
+}
+
+/// Commands waiting for the simulation to resume, in the order issued.
+#[derive(Debug, Clone, Default)]
+pub struct PausedActionQueue {
+    actions: Vec<(EntityId, PausedAction)>,
+}
+
+impl PausedActionQueue {
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(EntityId, PausedAction)> + use<'_> {
+        self.actions.iter()
+    }
+
+    /// Queues an action, replacing any earlier action of the same kind
+    /// for the same vehicle.
+    pub fn push(&mut self, id: EntityId, action: PausedAction) {
+        self.actions
+            .retain(|(i, a)| *i != id || !a.same_kind(&action));
+        self.actions.push((id, action));
+    }
+
+    pub fn for_entity(&self, id: EntityId) -> impl Iterator<Item = &PausedAction> + use<'_> {
+        self.actions
+            .iter()
+            .filter(move |(i, _)| *i == id)
+            .map(|(_, a)| a)
+    }
+
+    pub fn drain(&mut self) -> Vec<(EntityId, PausedAction)> {
+        std::mem::take(&mut self.actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_actions_supersede_earlier() {
+        let mut queue = PausedActionQueue::default();
+        let (a, b) = (EntityId(1), EntityId(2));
+        queue.push(a, PausedAction::SetPolicy(VehicleControlPolicy::Idle));
+        queue.push(a, PausedAction::SetTarget(Some(b)));
+        queue.push(b, PausedAction::SetTarget(Some(a)));
+        queue.push(
+            a,
+            PausedAction::SetPolicy(VehicleControlPolicy::BurnPrograde),
+        );
+
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.for_entity(a).count(), 2);
+
+        let drained = queue.drain();
+        assert!(queue.is_empty());
+        assert_eq!(
+            drained.last(),
+            Some(&(
+                a,
+                PausedAction::SetPolicy(VehicleControlPolicy::BurnPrograde)
+            ))
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use starling::prelude::*;
+
+/// Vehicle model used for spawned practice targets, which has a docking
+/// port facing along its +X axis.
+pub const PRACTICE_TARGET_MODEL: &str = "practice-drone";
+
+/// Distance ahead of the pilot, along the velocity vector, at which
+/// practice targets are spawned.
+pub const PRACTICE_TARGET_SPAWN_DISTANCE: f64 = 150.0;
+
+/// Separation at which the pilot is considered to have made contact.
+pub const PRACTICE_CONTACT_DISTANCE: f64 = 8.0;
+
+/// Tracks approach quality while the pilot closes on a practice target.
+/// The target's docking axis is its body +X axis, so a good approach
+/// ends with the pilot's nose pointed opposite the target's nose.
+#[derive(Debug, Clone)]
+pub struct PracticeSession {
+    pub pilot: EntityId,
+    pub target: EntityId,
+    pub max_closing_rate: f64,
+    pub min_distance: f64,
+    pub alignment_error: f64,
+    pub contact_speed: f64,
+}
+
+impl PracticeSession {
+    pub fn new(pilot: EntityId, target: EntityId) -> Self {
+        Self {
+            pilot,
+            target,
+            max_closing_rate: 0.0,
+            min_distance: f64::INFINITY,
+            alignment_error: 0.0,
+            contact_speed: 0.0,
+        }
+    }
+
+    /// Records the current relative state. Returns None if either vehicle
+    /// no longer exists, and Some(true) once contact has been made.
+    pub fn update(&mut self, universe: &Universe) -> Option<bool> {
+        let ego = universe.surface_vehicles.get(&self.pilot)?;
+        let target = universe.surface_vehicles.get(&self.target)?;
+        let rel = universe.pv(self.pilot)? - universe.pv(self.target)?;
+
+        let distance = rel.pos.length();
+        let closing_rate = -rel.pos.normalize_or_zero().dot(rel.vel);
+        self.max_closing_rate = self.max_closing_rate.max(closing_rate);
+
+        if distance < self.min_distance {
+            self.min_distance = distance;
+            self.alignment_error =
+                wrap_pi_npi_f64(ego.body.angle - target.body.angle - PI_64).abs();
+        }
+
+        if distance < PRACTICE_CONTACT_DISTANCE {
+            self.contact_speed = rel.vel.length();
+            return Some(true);
+        }
+
+        Some(false)
+    }
+
+    /// Score out of 100, based on closing rate and alignment at contact.
+    pub fn points(&self) -> u32 {
+        let rate_penalty = (self.max_closing_rate - 1.0).max(0.0) * 10.0;
+        let contact_penalty = (self.contact_speed * 20.0).min(30.0);
+        let alignment_penalty = self.alignment_error.to_degrees().min(30.0);
+        (100.0 - rate_penalty.min(40.0) - contact_penalty - alignment_penalty).max(0.0) as u32
+    }
+}
+
+impl std::fmt::Display for PracticeSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/100: max closing rate {:.2} m/s, contact at {:.2} m/s, {:.1} deg misaligned",
+            self.points(),
+            self.max_closing_rate,
+            self.contact_speed,
+            self.alignment_error.to_degrees()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn practice_drone() -> Vehicle {
+        let assets = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets");
+        let parts = load_parts_from_dir(&assets.join("parts")).unwrap();
+        let path = assets
+            .join("vehicles")
+            .join(PRACTICE_TARGET_MODEL)
+            .with_extension("vehicle");
+        load_vehicle(&path, "Drone".to_string(), &parts).unwrap()
+    }
+
+    #[test]
+    fn practice_drones_can_be_docked_with() {
+        let mut universe = Universe::new(starling::examples::rss());
+        let body = universe.planets.body;
+        let orbit = SparseOrbit::circular(body.radius + 400_000.0, body, universe.stamp(), false);
+        let orbit = GlobalOrbit(universe.planets.id, orbit);
+        let id = universe
+            .add_orbital_vehicle(practice_drone(), orbit)
+            .unwrap();
+
+        // a single port, out in front along the docking axis
+        let drone = &universe.surface_vehicles[&id].vehicle;
+        let ports: Vec<_> = drone.docking_ports().collect();
+        assert_eq!(ports.len(), 1);
+        let (_, face, rotation) = ports[0];
+        assert_eq!(rotation, Rotation::East);
+        assert!(face.x > drone.center_of_mass().x);
+    }
+
+    #[test]
+    fn approaches_are_tracked_until_contact() {
+        let mut universe = Universe::new(starling::examples::rss());
+        let body = universe.planets.body;
+        let orbit = SparseOrbit::circular(body.radius + 400_000.0, body, universe.stamp(), false);
+        let orbit = GlobalOrbit(universe.planets.id, orbit);
+        let pilot = universe
+            .add_orbital_vehicle(practice_drone(), orbit)
+            .unwrap();
+        let target = universe
+            .add_orbital_vehicle(practice_drone(), orbit)
+            .unwrap();
+        let mut session = PracticeSession::new(pilot, target);
+
+        // closing at 2 m/s, nose to nose but 0.1 rad off
+        let place = |universe: &mut Universe, distance: f64| {
+            let t = universe.surface_vehicles[&target].body;
+            let sv = universe.surface_vehicles.get_mut(&pilot).unwrap();
+            sv.body.pv = t.pv + PV::from_f64(DVec2::X * distance, DVec2::X * -2.0);
+            sv.body.angle = t.angle + PI_64 + 0.1;
+        };
+        place(&mut universe, 100.0);
+        assert_eq!(session.update(&universe), Some(false));
+        assert!((session.min_distance - 100.0).abs() < 1E-6);
+        assert!((session.max_closing_rate - 2.0).abs() < 1E-6);
+
+        place(&mut universe, 5.0);
+        assert_eq!(session.update(&universe), Some(true));
+        assert!((session.min_distance - 5.0).abs() < 1E-6);
+        assert!((session.alignment_error - 0.1).abs() < 1E-6);
+        assert!((session.contact_speed - 2.0).abs() < 1E-6);
+
+        universe.remove(target);
+        assert_eq!(session.update(&universe), None);
+    }
+
+    #[test]
+    fn slow_aligned_approaches_score_best() {
+        let id = EntityId(0);
+        let perfect = PracticeSession {
+            max_closing_rate: 0.5,
+            min_distance: 1.0,
+            alignment_error: 0.0,
+            contact_speed: 0.0,
+            ..PracticeSession::new(id, id)
+        };
+        assert_eq!(perfect.points(), 100);
+
+        // 1 m/s too fast, touching at 0.5 m/s, 5 degrees off
+        let sloppy = PracticeSession {
+            max_closing_rate: 2.0,
+            contact_speed: 0.5,
+            alignment_error: 5f64.to_radians(),
+            ..perfect.clone()
+        };
+        assert_eq!(sloppy.points(), 100 - 10 - 10 - 5);
+        assert!(sloppy.to_string().starts_with("75/100"));
+
+        // each penalty is capped, and the score never goes below zero
+        let reckless = PracticeSession {
+            max_closing_rate: 50.0,
+            contact_speed: 10.0,
+            alignment_error: PI_64,
+            ..perfect
+        };
+        assert_eq!(reckless.points(), 0);
+    }
+}
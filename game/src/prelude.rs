@@ -1,29 +1,59 @@
+pub use crate::alarms::*;
+pub use crate::annotations::*;
 pub use crate::args::*;
 pub use crate::button::*;
 pub use crate::camera_controller::*;
 pub use crate::canvas::*;
 pub use crate::commands::*;
+pub use crate::console_history::*;
 pub use crate::craft_editor::*;
+pub use crate::cursor_icons::*;
 pub use crate::debug_console::*;
+pub use crate::display_clock::*;
+pub use crate::double_click::*;
+pub use crate::drag_drop::*;
 pub use crate::drawing::*;
+pub use crate::economy::*;
+pub use crate::entity_records::*;
+pub use crate::frame_budget::*;
 pub use crate::game::*;
 pub use crate::generate_ship_sprites::*;
 pub use crate::graph::*;
+pub use crate::groups::*;
+pub use crate::hud::*;
 pub use crate::input::*;
 pub use crate::interactive::*;
 pub use crate::keybindings::*;
+pub use crate::list_selection::*;
+pub use crate::mouse_gestures::*;
+pub use crate::music::*;
 pub use crate::names::*;
 pub use crate::new_input::*;
 pub use crate::notifications::*;
+pub use crate::observer_net::*;
+pub use crate::offscreen_markers::*;
 pub use crate::onclick::*;
+pub use crate::ownership::*;
+pub use crate::palette::*;
+pub use crate::paused_actions::*;
+pub use crate::practice::*;
+pub use crate::profiler::*;
 pub use crate::scenes::orbital::*;
 pub use crate::scenes::TextLabel;
 pub use crate::scenes::{
-    MainMenuContext, Render, SceneType, StaticSpriteDescriptor, TelescopeContext,
+    MainMenuContext, Render, ScenarioEditorContext, SceneType, StaticSpriteDescriptor,
+    StatsContext, TelescopeContext,
 };
 pub use crate::settings::*;
 pub use crate::sim_rate::*;
 pub use crate::sounds::*;
+pub use crate::spectator::*;
 pub use crate::sprites::*;
+pub use crate::stats::*;
+pub use crate::timeline_notes::*;
+pub use crate::tween::*;
 pub use crate::ui::InteractionEvent;
+pub use crate::vehicle_import::*;
+pub use crate::vehicle_query::*;
+pub use crate::vehicle_registry::*;
 pub use crate::z_index::*;
@@ -0,0 +1,151 @@
+use enum_iterator::{all, Sequence};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Number of frames the rolling averages are taken over.
+pub const PROFILER_WINDOW: usize = 120;
+
+/// Major stages of each frame which are timed by the profiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Sequence)]
+pub enum ProfileStage {
+    UniverseTicks,
+    Propagation,
+    Drawing,
+    UiBuild,
+    Sprites,
+}
+
+impl ProfileStage {
+    /// Target time for this stage. A 60 Hz frame has about 16 ms to
+    /// spend, most of which is given to the simulation.
+    pub fn budget(&self) -> Duration {
+        match self {
+            Self::UniverseTicks => Duration::from_millis(10),
+            Self::Propagation => Duration::from_millis(3),
+            Self::Drawing => Duration::from_millis(2),
+            Self::UiBuild => Duration::from_millis(1),
+            Self::Sprites => Duration::from_millis(1),
+        }
+    }
+}
+
+impl std::fmt::Display for ProfileStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::UniverseTicks => "Universe ticks",
+            Self::Propagation => "Propagation",
+            Self::Drawing => "Drawing",
+            Self::UiBuild => "UI build",
+            Self::Sprites => "Sprite spawning",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// In-flight timing of a single stage, started with `ProfileSpan::start`
+/// and handed back to `Profiler::finish` once the stage is done.
+#[must_use]
+pub struct ProfileSpan {
+    stage: ProfileStage,
+    start: Instant,
+}
+
+impl ProfileSpan {
+    pub fn start(stage: ProfileStage) -> Self {
+        Self {
+            stage,
+            start: Instant::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StageTiming {
+    pub last: Duration,
+    pub average: Duration,
+    pub peak: Duration,
+}
+
+/// Rolling per-stage frame timings, shown by the profiling overlay.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    pub show: bool,
+    samples: HashMap<ProfileStage, VecDeque<Duration>>,
+}
+
+impl Profiler {
+    pub fn finish(&mut self, span: ProfileSpan) {
+        self.record(span.stage, span.start.elapsed());
+    }
+
+    pub fn record(&mut self, stage: ProfileStage, dur: Duration) {
+        let samples = self.samples.entry(stage).or_default();
+        samples.push_back(dur);
+        while samples.len() > PROFILER_WINDOW {
+            samples.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn timing(&self, stage: ProfileStage) -> Option<StageTiming> {
+        let samples = self.samples.get(&stage)?;
+        let last = *samples.back()?;
+        let total: Duration = samples.iter().sum();
+        Some(StageTiming {
+            last,
+            average: total / samples.len() as u32,
+            peak: samples.iter().max().copied().unwrap_or_default(),
+        })
+    }
+
+    pub fn is_over_budget(&self, stage: ProfileStage) -> bool {
+        self.timing(stage)
+            .map(|t| t.average > stage.budget())
+            .unwrap_or(false)
+    }
+
+    /// Stages whose rolling average exceeds their budget.
+    pub fn over_budget(&self) -> Vec<ProfileStage> {
+        all::<ProfileStage>()
+            .filter(|s| self.is_over_budget(*s))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiler_rolling_average_and_budget() {
+        let mut profiler = Profiler::default();
+        assert!(profiler.timing(ProfileStage::Drawing).is_none());
+        assert!(profiler.over_budget().is_empty());
+
+        for _ in 0..PROFILER_WINDOW {
+            profiler.record(ProfileStage::Drawing, Duration::from_millis(1));
+        }
+        profiler.record(ProfileStage::Drawing, Duration::from_millis(3));
+        let t = profiler.timing(ProfileStage::Drawing).unwrap();
+        assert_eq!(t.last, Duration::from_millis(3));
+        assert_eq!(t.peak, Duration::from_millis(3));
+        assert!(t.average > Duration::from_millis(1));
+        assert!(t.average < Duration::from_millis(2));
+        assert!(profiler.over_budget().is_empty());
+
+        // old samples fall out of the window
+        for _ in 0..PROFILER_WINDOW {
+            profiler.record(ProfileStage::Drawing, Duration::from_millis(4));
+        }
+        let t = profiler.timing(ProfileStage::Drawing).unwrap();
+        assert_eq!(t.average, Duration::from_millis(4));
+        assert_eq!(profiler.over_budget(), vec![ProfileStage::Drawing]);
+
+        let span = ProfileSpan::start(ProfileStage::UiBuild);
+        profiler.finish(span);
+        assert!(profiler.timing(ProfileStage::UiBuild).is_some());
+    }
+}
@@ -30,7 +30,7 @@ impl Render for MainMenuContext {
             Ok(dir) => dir.to_string_lossy().to_string(),
             Err(e) => format!("{} (\"{}\")", e, state.args.install_dir.clone().display()),
         };
-        let n_vehicles = get_list_of_vehicles(state).map(|l| l.len()).unwrap_or(0);
+        let n_vehicles = state.vehicles.len();
         let s = format!(
             "Compiled on {}\nInstall directory: {}\n{} parts loaded\n{} vehicles loaded\n{} sprites loaded",
             time,
@@ -63,23 +63,44 @@ impl Render for MainMenuContext {
                     s.to_string(),
                     OnClick::Nullopt,
                     Size::Grow,
-                    state.settings.ui_button_height,
+                    state.button_height(),
                 )
                 .with_color(button_color)
             }))
+            .with_child(
+                Node::button(
+                    format!("Palette: {}", state.settings.color_palette),
+                    OnClick::CycleColorPalette,
+                    Size::Grow,
+                    state.button_height(),
+                )
+                .with_color(button_color),
+            )
+            .with_child(
+                Node::button(
+                    match state.settings.music.enabled {
+                        true => "Music: On",
+                        false => "Music: Off",
+                    },
+                    OnClick::ToggleMusic,
+                    Size::Grow,
+                    state.button_height(),
+                )
+                .with_color(button_color),
+            )
             .with_children(SceneType::all().enumerate().map(|(i, s)| {
                 Node::button(
                     format!("{:?}", s),
                     OnClick::GoToScene(s),
                     Size::Grow,
-                    state.settings.ui_button_height,
+                    state.button_height(),
                 )
                 .with_color(button_color)
             }))
             .with_child({
                 let s = "Reload";
                 let onclick = OnClick::ReloadGame;
-                Node::button(s, onclick, Size::Grow, state.settings.ui_button_height)
+                Node::button(s, onclick, Size::Grow, state.button_height())
             });
 
         Some(Tree::new().with_layout(wrapper, Vec2::splat(300.0)))
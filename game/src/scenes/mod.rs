@@ -2,12 +2,16 @@ pub mod craft_editor;
 pub mod main_menu;
 pub mod orbital;
 pub mod render;
+pub mod scenario_editor;
 pub mod scene;
+pub mod stats;
 pub mod telescope;
 
 pub use craft_editor::*;
 pub use main_menu::MainMenuContext;
 pub use orbital::*;
 pub use render::*;
+pub use scenario_editor::*;
 pub use scene::SceneType;
+pub use stats::StatsContext;
 pub use telescope::TelescopeContext;
@@ -1,10 +1,18 @@
+use crate::annotations::AnnotationKind;
 use crate::camera_controller::*;
 use crate::canvas::Canvas;
+use crate::double_click::*;
+use crate::drawing::SITE_MARKER_MAX_SPAN;
 use crate::game::GameState;
+use crate::hud::hud_area;
 use crate::input::{FrameId, InputState, MouseButt};
+use crate::keybindings::{KeyAction, Keybindings};
+use crate::offscreen_markers::offscreen_markers;
 use crate::onclick::OnClick;
+use crate::ownership::HandoverTarget;
 use crate::scenes::{Render, TextLabel};
 use crate::sounds::EnvironmentSounds;
+use crate::tween::Smoothed;
 use crate::ui::*;
 use bevy::color::palettes::css::*;
 use bevy::prelude::*;
@@ -12,7 +20,7 @@ use enum_iterator::all;
 use enum_iterator::Sequence;
 use layout::layout::{Node, Size, Tree};
 use starling::prelude::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Sequence)]
 pub enum CursorMode {
@@ -22,8 +30,27 @@ pub enum CursorMode {
     NearOrbit,
     MeasuringTape,
     Protractor,
+    ManeuverNode,
 }
 
+/// Part of a maneuver node which can be dragged: the node itself, to move
+/// it along the orbit, or one of its delta-v handles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeHandle {
+    Time,
+    Prograde,
+    Radial,
+}
+
+/// Screen distance from a maneuver node to its delta-v handles.
+pub const NODE_HANDLE_DISTANCE: f32 = 60.0;
+
+/// How close the cursor must be to a maneuver node handle to grab it.
+const NODE_HANDLE_RADIUS: f32 = 14.0;
+
+/// Delta-v added per pixel a maneuver node handle is dragged.
+const NODE_DV_PER_PIXEL: f64 = 0.5;
+
 #[derive(Debug, Clone, Copy, Default, Sequence)]
 pub enum ShowOrbitsState {
     #[default]
@@ -39,6 +66,26 @@ pub enum DrawMode {
     Constellations,
     Stability,
     Occlusion,
+    SphereOfInfluence,
+}
+
+/// Next sphere of influence crossing along an orbit, worked out once per
+/// orbit rather than on every frame.
+#[derive(Debug, Clone, Copy)]
+pub struct SoiCrossing {
+    pub orbit: GlobalOrbit,
+    /// Time and kind of the crossing, and the orbit beyond it, if there is
+    /// one within the lookahead.
+    pub next: Option<(Nanotime, EventType, GlobalOrbit)>,
+}
+
+/// Player input for one frame of the orbital scene, along with the
+/// bindings which give it meaning.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitalInput<'a> {
+    pub input: &'a InputState,
+    pub keys: &'a Keybindings,
+    pub double_click: &'a DoubleClickBindings,
 }
 
 #[allow(unused)]
@@ -55,6 +102,61 @@ pub struct OrbitalContext {
     pub draw_mode: DrawMode,
     pub piloting: Option<EntityId>,
     pub hovered_entity: Option<EntityId>,
+    /// Surface structure shown in the structure inspector.
+    pub inspected_structure: Option<EntityId>,
+    /// Panel expanded from the icon rail, in the compact layout.
+    pub rail_panel: Option<usize>,
+    /// Show the piloted vehicle, and every other vehicle in its target's
+    /// SOI, relative to the target in the target's rotating LVLH frame.
+    pub rotating_frame: bool,
+
+    /// Recent path of the piloted vehicle in its target's LVLH frame.
+    pub relative_track: RelativeTrack,
+
+    /// Next SOI crossing of the piloted vehicle, for the sphere of
+    /// influence overlay.
+    pub soi_crossing: Option<SoiCrossing>,
+
+    /// Draw the current and required phase angles to the pilot's target.
+    pub show_phase_arcs: bool,
+
+    /// Launch guidance tuned by the player, per vehicle.
+    pub ascent_profiles: HashMap<EntityId, AscentProfile>,
+
+    /// Forward throttle of the piloted vehicle, eased for display.
+    pub throttle_display: Smoothed,
+
+    /// Maneuver plan copied from a vehicle, to be shared with others.
+    pub copied_plan: Option<PlanTemplate>,
+
+    /// Opacity of labels and markers, which fade out at high warp.
+    pub label_alpha: Smoothed,
+
+    /// List the orbits the piloted or followed vehicle has been on.
+    pub show_orbit_history: bool,
+
+    /// Show the delta-v costs between the canonical orbits of the system.
+    pub show_dv_map: bool,
+
+    /// Annotate the piloted or followed vehicle's orbit with its foci,
+    /// axes and anomalies.
+    pub show_conic_construction: bool,
+
+    /// Show who holds each of the vehicle lifetime records.
+    pub show_records: bool,
+
+    /// Past time at which to draw the orbit the vehicle was on.
+    pub history_scrub: Option<Nanotime>,
+
+    /// Group shown in the group detail panel.
+    pub inspected_group: Option<EntityId>,
+
+    /// Burns placed by hand on each vehicle's orbit, not yet scheduled.
+    pub maneuver_nodes: HashMap<EntityId, ManeuverNode>,
+
+    /// Maneuver node handle being dragged, and the node as it was when
+    /// the drag started.
+    node_drag: Option<(NodeHandle, ManeuverNode)>,
 }
 
 impl CameraProjection for OrbitalContext {
@@ -91,9 +193,122 @@ impl OrbitalContext {
             draw_mode: DrawMode::Default,
             piloting: None,
             hovered_entity: None,
+            inspected_structure: None,
+            rail_panel: None,
+            rotating_frame: false,
+            relative_track: RelativeTrack::default(),
+            soi_crossing: None,
+            show_phase_arcs: false,
+            ascent_profiles: HashMap::new(),
+            throttle_display: Smoothed::new(0.0, Nanotime::millis(80)),
+            copied_plan: None,
+            label_alpha: Smoothed::new(1.0, Nanotime::millis(250)),
+            show_orbit_history: false,
+            show_dv_map: false,
+            show_conic_construction: false,
+            show_records: false,
+            history_scrub: None,
+            inspected_group: None,
+            maneuver_nodes: HashMap::new(),
+            node_drag: None,
         }
     }
 
+    /// Orbit a vehicle's maneuver node is placed on, which is the one it
+    /// will be on once any plan scheduled for it is done, and the time
+    /// after which the node can go.
+    pub fn maneuver_node_base(
+        universe: &Universe,
+        id: EntityId,
+    ) -> Option<(GlobalOrbit, Nanotime)> {
+        let GlobalOrbit(parent, orbit) = universe.surface_vehicles.get(&id)?.current_orbit()?;
+        let stamp = universe.stamp();
+        Some(match universe.maneuvers.get(id) {
+            Some(p) => (GlobalOrbit(parent, p.terminal), p.end().max(stamp)),
+            None => (GlobalOrbit(parent, orbit), stamp),
+        })
+    }
+
+    /// Screen positions of the handles of a vehicle's maneuver node.
+    pub fn maneuver_node_handles(
+        &self,
+        universe: &Universe,
+        id: EntityId,
+    ) -> Option<[(NodeHandle, Vec2); 3]> {
+        let node = self.maneuver_nodes.get(&id)?;
+        let (GlobalOrbit(parent, orbit), _) = Self::maneuver_node_base(universe, id)?;
+        let origin = universe.lup_planet(parent)?.pv().pos;
+        let c = self.w2c(origin + orbit.pv(node.stamp).ok()?.pos);
+        let (prograde, radial) = node.axes(&orbit)?;
+        Some([
+            (NodeHandle::Time, c),
+            (
+                NodeHandle::Prograde,
+                c + graphics_cast(prograde) * NODE_HANDLE_DISTANCE,
+            ),
+            (
+                NodeHandle::Radial,
+                c + graphics_cast(radial) * NODE_HANDLE_DISTANCE,
+            ),
+        ])
+    }
+
+    /// Places the piloted vehicle's maneuver node where its orbit is
+    /// clicked, or drags the node or its handles.
+    fn edit_maneuver_node(&mut self, input: &InputState, universe: &Universe) -> Option<()> {
+        let id = self.piloting?;
+        let Some(current) = input.position(MouseButt::Left, FrameId::Current) else {
+            self.node_drag = None;
+            return None;
+        };
+        let (GlobalOrbit(parent, orbit), earliest) = Self::maneuver_node_base(universe, id)?;
+        let origin = universe.lup_planet(parent)?.pv().pos;
+        let down = input.position(MouseButt::Left, FrameId::Down)?;
+
+        if input.on_frame(MouseButt::Left, FrameId::Down).is_some() {
+            let grabbed = self
+                .maneuver_node_handles(universe, id)
+                .into_iter()
+                .flatten()
+                .find(|(_, p)| p.distance(down) < NODE_HANDLE_RADIUS)
+                .map(|(h, _)| h);
+            let existing = self.maneuver_nodes.get(&id).copied();
+            self.node_drag = match (grabbed, existing) {
+                (Some(h), Some(node)) => Some((h, node)),
+                _ => {
+                    // clicking elsewhere moves the node, keeping its burn
+                    let placed =
+                        ManeuverNode::at_position(&orbit, earliest, self.c2w(down) - origin)?;
+                    let node = existing.map_or(placed, |n| n.with_stamp(placed.stamp));
+                    self.maneuver_nodes.insert(id, node);
+                    Some((NodeHandle::Time, node))
+                }
+            };
+        }
+
+        let (handle, start) = self.node_drag?;
+        let (prograde, radial) = start.axes(&orbit)?;
+        let dragged =
+            |axis: DVec2| (current - down).dot(graphics_cast(axis)) as f64 * NODE_DV_PER_PIXEL;
+        let node = match handle {
+            NodeHandle::Time => {
+                let moved =
+                    ManeuverNode::at_position(&orbit, earliest, self.c2w(current) - origin)?;
+                start.with_stamp(moved.stamp)
+            }
+            NodeHandle::Prograde => ManeuverNode {
+                prograde: start.prograde + dragged(prograde),
+                ..start
+            },
+            NodeHandle::Radial => ManeuverNode {
+                radial: start.radial + dragged(radial),
+                ..start
+            },
+        };
+        self.maneuver_nodes.insert(id, node);
+        Some(())
+    }
+
     pub fn toggle_track(&mut self, id: EntityId) {
         if self.selected.contains(&id) {
             self.selected.retain(|e| *e != id);
@@ -179,6 +394,87 @@ impl OrbitalContext {
         Self::cursor_orbit(a, b, state)
     }
 
+    /// Landing site whose marker is drawn under the given screen position.
+    fn site_marker_at(&self, p: Vec2, span: DVec2, universe: &Universe) -> Option<EntityId> {
+        const MARKER_RADIUS: f32 = 15.0;
+        if span.max_element() / self.scale() > SITE_MARKER_MAX_SPAN {
+            return None;
+        }
+        universe.landing_pads.iter().find_map(|(id, pad)| {
+            let lup = universe.lup_planet(pad.planet_id)?;
+            let marker = lup.pv().pos + pad.local_position(lup.body()?.radius);
+            (self.w2c(marker).distance(p) < MARKER_RADIUS).then_some(*id)
+        })
+    }
+
+    /// Works out what was double-clicked and does whatever the settings
+    /// bind to it. Spectators can't take the controls this way.
+    fn on_double_click(
+        &mut self,
+        p: Vec2,
+        span: DVec2,
+        spectating: bool,
+        bindings: &DoubleClickBindings,
+        universe: &Universe,
+    ) -> Option<()> {
+        let dist = (SPACECRAFT_HOVER_RADIUS / self.scale()).max(10.0);
+        let (target, id) = if let Some(id) = self.site_marker_at(p, span, universe) {
+            (DoubleClickTarget::LandingSite, Some(id))
+        } else if let Some(id) = nearest_orbiter_or_planet(universe, self.c2w(p), dist) {
+            if universe.lup_planet(id).is_some() {
+                (DoubleClickTarget::Planet, Some(id))
+            } else {
+                (DoubleClickTarget::Orbiter, Some(id))
+            }
+        } else {
+            (DoubleClickTarget::EmptySpace, None)
+        };
+
+        // the planet that a site is on, or an orbiter is within
+        let planet = id.and_then(|id| match target {
+            DoubleClickTarget::LandingSite => Some(universe.landing_pads.get(&id)?.planet_id),
+            DoubleClickTarget::Orbiter => Some(universe.surface_vehicles.get(&id)?.parent()),
+            _ => Some(id),
+        });
+
+        match bindings.get(target) {
+            DoubleClickAction::Nothing => (),
+            DoubleClickAction::Follow => {
+                self.following = if target == DoubleClickTarget::LandingSite {
+                    planet
+                } else {
+                    id
+                };
+                self.camera.clear_offset();
+            }
+            DoubleClickAction::Pilot => {
+                if !spectating && target == DoubleClickTarget::Orbiter {
+                    self.piloting = id;
+                }
+            }
+            DoubleClickAction::FrameSoi => {
+                let planet = planet?;
+                let soi = universe.lup_planet(planet)?.body()?.soi;
+                self.following = Some(planet);
+                self.camera.clear_offset();
+                self.camera.zoom_to(span.min_element() / (2.2 * soi));
+            }
+            DoubleClickAction::ViewSite => {
+                const SITE_VIEW_SPAN: f64 = 2_000.0;
+                let pad = universe.landing_pads.get(&id?)?;
+                let lup = universe.lup_planet(pad.planet_id)?;
+                // follow the planet straight away, since changing what's
+                // followed on the next tick would undo the pan
+                self.following = Some(pad.planet_id);
+                self.camera.follow(pad.planet_id, lup.pv().pos);
+                self.camera.pan_to(pad.local_position(lup.body()?.radius));
+                self.camera.zoom_to(span.max_element() / SITE_VIEW_SPAN);
+            }
+            DoubleClickAction::ClearFollow => self.following = None,
+        }
+        Some(())
+    }
+
     pub fn on_game_tick(&mut self, universe: &Universe) {
         if let Some(follow) = self.following {
             if let Some(pv) = universe.pv(follow) {
@@ -188,21 +484,66 @@ impl OrbitalContext {
 
         self.camera.on_game_tick();
 
+        let target = self
+            .piloting
+            .and_then(|p| Some((p, universe.surface_vehicles.get(&p)?.target()?)));
+        match target.and_then(|(p, t)| Some((p, t, universe.lvlh_state(p, t)?))) {
+            Some((p, t, rel)) => self.relative_track.record(p, t, universe.stamp(), rel.pos),
+            None => self.relative_track.clear(),
+        }
+
+        self.update_soi_crossing(universe);
+
         let mut track_list = self.selected.clone();
         track_list.retain(|o| universe.surface_vehicles.contains_key(o));
         self.selected = track_list;
+
+        self.maneuver_nodes.retain(|id, node| {
+            universe.surface_vehicles.contains_key(id) && node.stamp > universe.stamp()
+        });
+    }
+
+    /// Works out the piloted vehicle's next SOI crossing for the overlay
+    /// whenever the orbit it's on changes.
+    fn update_soi_crossing(&mut self, universe: &Universe) {
+        let orbit = self
+            .piloting
+            .filter(|_| self.draw_mode == DrawMode::SphereOfInfluence)
+            .and_then(|id| universe.surface_vehicles.get(&id)?.current_orbit());
+        let Some(orbit) = orbit else {
+            self.soi_crossing = None;
+            return;
+        };
+        if self.soi_crossing.is_some_and(|c| c.orbit == orbit) {
+            return;
+        }
+
+        let stamp = universe.stamp();
+        let mut orbiter = Orbiter::new(orbit, stamp);
+        let next = orbiter
+            .propagate_to(stamp, Nanotime::days(30), &universe.planets)
+            .ok()
+            .and_then(|_| orbiter.next_soi_crossing())
+            .map(|(t, event, prop)| (t, event, prop.orbit));
+        self.soi_crossing = Some(SoiCrossing { orbit, next });
     }
 
     pub fn on_render_tick(
         &mut self,
         on_ui: bool,
-        input: &InputState,
+        spectating: bool,
+        frame: OrbitalInput,
         universe: &mut Universe,
         sounds: &mut EnvironmentSounds,
     ) {
-        self.camera.handle_input(input);
-
-        if input.just_pressed(KeyCode::KeyN) {
+        let OrbitalInput {
+            input,
+            keys,
+            double_click,
+        } = frame;
+        self.camera.handle_input(input, keys);
+
+        if !spectating && keys.just_pressed(KeyAction::NextControlMode, input) {
             if let Some(id) = self.piloting {
                 if let Some(sv) = universe.surface_vehicles.get_mut(&id) {
                     sv.controller.go_to_next_mode();
@@ -210,6 +551,13 @@ impl OrbitalContext {
             }
         }
 
+        if !spectating && keys.just_pressed(KeyAction::ActivateStage, input) {
+            if let Some(id) = self.piloting {
+                // failures are shown on the sidebar's staging button
+                _ = universe.activate_stage(id);
+            }
+        }
+
         if on_ui {
             return;
         }
@@ -222,7 +570,26 @@ impl OrbitalContext {
             None
         };
 
-        if let Some(_) = input.on_frame(MouseButt::Left, FrameId::Down) {
+        if let Some(p) = input.double_click() {
+            let span = input.screen_bounds.span.as_dvec2();
+            self.on_double_click(p, span, spectating, double_click, universe);
+        }
+
+        // spectators can look around and follow things, but not take the
+        // controls or pick targets
+        if spectating {
+            if input.on_frame(MouseButt::Left, FrameId::Down).is_some() {
+                if let Some(h) = self.hovered_entity {
+                    self.following = Some(h);
+                    self.camera.clear_offset();
+                }
+            }
+            return;
+        }
+
+        if self.cursor_mode == CursorMode::ManeuverNode {
+            self.edit_maneuver_node(input, universe);
+        } else if input.on_frame(MouseButt::Left, FrameId::Down).is_some() {
             if input.is_pressed(KeyCode::ControlLeft) {
                 self.following = self.hovered_entity;
                 self.camera.clear_offset();
@@ -234,6 +601,9 @@ impl OrbitalContext {
                     self.piloting = None;
                     sounds.play_once("soft-pulse.ogg", 0.3);
                 }
+                self.inspected_structure = input
+                    .position(MouseButt::Left, FrameId::Down)
+                    .and_then(|p| universe.structure_at(self.c2w(p)));
             }
         }
 
@@ -264,6 +634,11 @@ pub fn get_orbital_labels(state: &GameState) -> Vec<TextLabel> {
         .flatten()
         .flatten();
 
+    let fade = state.orbital_context.label_alpha.value();
+    if fade < 0.01 {
+        return ret;
+    }
+
     for (id, alpha) in [
         (state.orbital_context.piloting, 0.3),
         (state.orbital_context.hovered_entity, 0.9),
@@ -273,6 +648,7 @@ pub fn get_orbital_labels(state: &GameState) -> Vec<TextLabel> {
             Some(id) => id,
             None => continue,
         };
+        let alpha = alpha * fade;
 
         let lup = if let Some(lup) = state.universe.lup_orbiter(id) {
             lup
@@ -282,7 +658,9 @@ pub fn get_orbital_labels(state: &GameState) -> Vec<TextLabel> {
             continue;
         };
 
-        let pw = lup.pv().pos;
+        let Some(pw) = state.display_position(id) else {
+            continue;
+        };
         let pc = state.orbital_context.w2c(pw);
 
         let label = if let Some((name, body)) = lup.named_body() {
@@ -297,7 +675,22 @@ pub fn get_orbital_labels(state: &GameState) -> Vec<TextLabel> {
                 .map(|ov| ov.vehicle().title())
                 .unwrap_or("UFO".to_string());
 
-            let text = format!("{} {}", code, id);
+            let task = state.universe.traffic.get(id).and_then(|a| a.task());
+            let burn = state
+                .universe
+                .maneuvers
+                .get(id)
+                .and_then(|p| p.future_dvs(state.universe.stamp()).next());
+            let text = match (task, burn) {
+                (_, Some((t, _))) => format!(
+                    "{} {}\nShared plan, burn in {}",
+                    code,
+                    id,
+                    t - state.universe.stamp()
+                ),
+                (Some(task), None) => format!("{} {}\n{}", code, id, task.to_status_str()),
+                (None, None) => format!("{} {}", code, id),
+            };
             let pos = pc + Vec2::X * 40.0;
 
             let mut t = TextLabel::new(text, pos, 0.6).with_anchor_left();
@@ -311,9 +704,14 @@ pub fn get_orbital_labels(state: &GameState) -> Vec<TextLabel> {
 
 pub fn date_info(state: &GameState) -> String {
     let date = state.universe.stamp().to_date();
+    let paused = match (state.paused, state.paused_actions.len()) {
+        (false, _) => String::new(),
+        (true, 0) => "[PAUSED] ".to_string(),
+        (true, n) => format!("[PAUSED, {} queued] ", n),
+    };
     format!(
         "{}({}) {} (x{}/{} {} us)",
-        if state.paused { "[PAUSED] " } else { "" },
+        paused,
         if state.using_batch_mode { "B" } else { "S" },
         date,
         state.actual_universe_ticks_per_game_tick,
@@ -334,6 +732,49 @@ fn text_labels(state: &GameState) -> Vec<TextLabel> {
         }
     }
 
+    let mut queued: Vec<EntityId> = state.paused_actions.iter().map(|(id, _)| *id).collect();
+    queued.sort();
+    queued.dedup();
+    for id in queued {
+        let Some(pv) = state.universe.pv(id) else {
+            continue;
+        };
+        let s: Vec<String> = state
+            .paused_actions
+            .for_entity(id)
+            .map(|a| a.to_string())
+            .collect();
+        let p = state.orbital_context.w2c(pv.pos) + Vec2::new(0.0, 40.0);
+        text_labels.push(
+            TextLabel::new(format!("QUEUED WHILE PAUSED\n{}", s.join("\n")), p, 0.6)
+                .with_color(YELLOW),
+        );
+    }
+
+    for (id, w) in &state.universe.waypoints {
+        let Some(pv) = state.universe.pv(*id) else {
+            continue;
+        };
+        let p = state.orbital_context.w2c(pv.pos) + Vec2::new(0.0, 20.0);
+        text_labels.push(TextLabel::new(w.name.clone(), p, 0.7));
+    }
+
+    for a in state.annotations.iter().filter(|a| a.visible) {
+        let Some(p) = a.points(&state.universe) else {
+            continue;
+        };
+        let anchor = match a.kind {
+            AnnotationKind::Distance(..) => (p[0] + p[1]) / 2.0,
+            AnnotationKind::Angle { .. } => {
+                let da = p[1] - p[0];
+                p[0] + rotate_f64(da * 0.75, da.angle_to(p[2] - p[0]) / 2.0)
+            }
+        };
+        let anchor = state.orbital_context.w2c(anchor);
+        text_labels
+            .push(TextLabel::new(a.value_str(&state.universe), anchor, 0.8).with_color(YELLOW));
+    }
+
     if let Some((c, a, b)) = state.protractor() {
         for (a, b) in [(c, Some(a)), (c, b)] {
             if let Some(b) = b {
@@ -364,6 +805,7 @@ impl Render for OrbitalContext {
             DrawMode::Constellations => GRAY.with_luminance(0.1),
             DrawMode::Stability => GRAY.with_luminance(0.13),
             DrawMode::Occlusion => GRAY.with_luminance(0.04),
+            DrawMode::SphereOfInfluence => GRAY.with_luminance(0.02),
         }
     }
 
@@ -385,9 +827,6 @@ impl Render for OrbitalContext {
 
         let mut sidebar = Node::column(300).with_color(UI_BACKGROUND_COLOR);
 
-        let body_color_lup: std::collections::HashMap<&'static str, Srgba> =
-            std::collections::HashMap::from([("Earth", BLUE), ("Luna", GRAY), ("Asteroid", BROWN)]);
-
         if let Some(lup) = nearest_relevant_body(
             &state.universe.planets,
             state.orbital_context.origin(),
@@ -397,9 +836,11 @@ impl Render for OrbitalContext {
         .flatten()
         {
             if let Some((s, _)) = lup.named_body() {
-                let color: Srgba = body_color_lup
-                    .get(s.as_str())
-                    .unwrap_or(&Srgba::from(crate::sprites::hashable_to_color(s)))
+                let color: Srgba = state
+                    .planet_definition(s)
+                    .and_then(|p| p.color)
+                    .map(|[r, g, b]| Srgba::new(r, g, b, 1.0))
+                    .unwrap_or(Srgba::from(crate::sprites::hashable_to_color(s)))
                     .with_luminance(0.2)
                     .with_alpha(0.9);
                 sidebar.add_child(
@@ -407,7 +848,7 @@ impl Render for OrbitalContext {
                         s,
                         OnClick::CurrentBody(lup.id()),
                         Size::Grow,
-                        state.settings.ui_button_height,
+                        state.button_height(),
                     )
                     .with_color(color.to_f32_array()),
                 );
@@ -418,7 +859,19 @@ impl Render for OrbitalContext {
             format!("Visual: {:?}", state.orbital_context.draw_mode),
             OnClick::ToggleDrawMode,
             Size::Grow,
-            state.settings.ui_button_height,
+            state.button_height(),
+        ));
+
+        let s = if state.orbital_context.show_conic_construction {
+            "Hide Conic Construction"
+        } else {
+            "Show Conic Construction"
+        };
+        sidebar.add_child(Node::button(
+            s,
+            OnClick::ToggleConicConstruction,
+            Size::Grow,
+            state.button_height(),
         ));
 
         sidebar.add_child(
@@ -426,17 +879,40 @@ impl Render for OrbitalContext {
                 "Clear Orbits",
                 OnClick::ClearOrbits,
                 Size::Grow,
-                state.settings.ui_button_height,
+                state.button_height(),
             )
             .enabled(!state.orbital_context.queued_orbits.is_empty()),
         );
 
+        sidebar.add_child(
+            Node::button(
+                "Copy Plan",
+                OnClick::CopyPlan,
+                Size::Grow,
+                state.button_height(),
+            )
+            .enabled(state.planned_maneuver().is_some()),
+        );
+
+        sidebar.add_child(
+            Node::button(
+                "Paste Plan to Selection",
+                OnClick::PastePlan,
+                Size::Grow,
+                state.button_height(),
+            )
+            .enabled(
+                state.orbital_context.copied_plan.is_some()
+                    && !state.orbital_context.selected.is_empty(),
+            ),
+        );
+
         sidebar.add_child(
             Node::button(
                 "Commit Mission",
                 OnClick::CommitMission,
                 Size::Grow,
-                state.settings.ui_button_height,
+                state.button_height(),
             )
             .enabled(state.current_orbit().is_some() && !state.orbital_context.selected.is_empty()),
         );
@@ -446,7 +922,7 @@ impl Render for OrbitalContext {
         sidebar.add_children(all::<CursorMode>().map(|c| {
             let s = format!("{:?}", c);
             let id = OnClick::CursorMode(c);
-            Node::button(s, id, Size::Grow, state.settings.ui_button_height)
+            Node::button(s, id, Size::Grow, state.button_height())
                 .enabled(c != state.orbital_context.cursor_mode)
         }));
 
@@ -455,24 +931,48 @@ impl Render for OrbitalContext {
         }
 
         for gid in state.universe.unique_groups() {
-            let color: Srgba = crate::sprites::hashable_to_color(&gid)
-                .with_luminance(0.3)
-                .into();
-            let s = format!("{}", gid);
-            let id = OnClick::Group(gid.clone());
-            let button = Node::button(s, id, Size::Grow, state.settings.ui_button_height)
-                .with_color(color.to_f32_array());
-            sidebar.add_child(delete_wrapper(
-                OnClick::DisbandGroup(gid.clone()),
-                button,
-                state.settings.ui_button_height as f32,
-            ));
+            let color: Srgba = state.group_color(gid).with_luminance(0.3).into();
+            let s = state.group_name(gid);
+            let id = OnClick::Group(gid);
+            let h = state.button_height();
+            let button = Node::row(h)
+                .invisible()
+                .tight()
+                .with_child(Node::button(s, id, Size::Grow, h).with_color(color.to_f32_array()))
+                .with_child(Node::button("...", OnClick::InspectGroup(gid), h, h));
+            sidebar.add_child(delete_wrapper(OnClick::DisbandGroup(gid), button, h));
+        }
+
+        for (i, _) in state
+            .saved_filters
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.pinned)
+        {
+            sidebar.add_child(smart_list_button(state, i, Size::Grow));
         }
 
         sidebar.add_child(Node::hline());
 
         sidebar.add_child(piloting_buttons(state, Size::Grow));
 
+        let shown = state
+            .piloting()
+            .or(state.orbital_context.following)
+            .or_else(|| match state.orbital_context.selected.len() {
+                1 => state.orbital_context.selected.iter().next().copied(),
+                _ => None,
+            });
+        if let Some(card) = shown.and_then(|id| vehicle_card(state, id, Size::Grow)) {
+            sidebar.add_child(card);
+        }
+        if let Some(b) = shown.and_then(|id| burn_alignment_buttons(state, id, Size::Grow)) {
+            sidebar.add_child(b);
+        }
+        if let Some(b) = shown.and_then(|id| staging_buttons(state, id, Size::Grow)) {
+            sidebar.add_child(b);
+        }
+
         sidebar.add_child(selected_button(state, Size::Grow));
 
         if !state.orbital_context.selected.is_empty() {
@@ -486,7 +986,23 @@ impl Render for OrbitalContext {
                 "Create Group",
                 OnClick::CreateGroup,
                 Size::Grow,
-                state.settings.ui_button_height,
+                state.button_height(),
+            ));
+            let any_derelict = state
+                .orbital_context
+                .selected
+                .iter()
+                .any(|id| state.fleet.is_derelict(*id));
+            let (s, target) = if any_derelict {
+                ("Reclaim Selected", HandoverTarget::Reclaim)
+            } else {
+                ("Abandon Selected", HandoverTarget::Abandon)
+            };
+            sidebar.add_child(Node::button(
+                s,
+                OnClick::HandOverSelected(target),
+                Size::Grow,
+                state.button_height(),
             ));
         }
 
@@ -496,31 +1012,54 @@ impl Render for OrbitalContext {
             let orbit_button = {
                 let s = format!("{}", orbit);
                 let id = OnClick::GlobalOrbit(i);
-                Node::button(s, id, 400, state.settings.ui_button_height)
+                Node::button(s, id, 400, state.button_height())
             };
 
             inner_topbar.add_child(delete_wrapper(
                 OnClick::DeleteOrbit(i),
                 orbit_button,
-                state.settings.ui_button_height,
+                state.button_height(),
             ));
         }
 
-        let notif_bar = notification_bar(state, Size::Fixed(900.0));
+        let w = Size::Fixed(250.0);
+        let panels = vec![
+            ("Orb", inner_topbar),
+            ("Imp", collision_warning_panel(state, w)),
+            ("Ld", loading_indicator(state, w)),
+            ("Law", control_law_buttons(state, w)),
+            ("Ph", phase_angle_panel(state, w)),
+            ("Adj", orbit_adjustment_panel(state, w)),
+            ("Mnv", maneuver_node_panel(state, w)),
+            ("Dck", docking_panel(state, w)),
+            ("Xfr", transfer_panel(state, w)),
+            ("Pad", landing_site_panel(state, w)),
+            ("Bld", structure_inspector(state, w)),
+            ("Car", career_panel(state, w)),
+            ("Asc", ascent_panel(state, w)),
+            ("Alm", alarm_calendar(state, w)),
+            ("Nts", timeline_notes_panel(state, w)),
+            ("Hst", orbit_history_panel(state, w)),
+            ("Grp", group_panel(state, w)),
+            ("Fnd", smart_list_panel(state, w)),
+            ("Wpt", waypoint_panel(state, w)),
+            ("dV", dv_map_panel(state, w)),
+            ("Spc", spectator_panel(state, w)),
+            ("Rec", records_panel(state, w)),
+        ];
+
+        let panels = if state.is_compact_layout() {
+            vec![icon_rail(
+                state,
+                panels,
+                state.orbital_context.rail_panel,
+                OnClick::ToggleRailPanel,
+            )]
+        } else {
+            panels.into_iter().map(|(_, p)| p).collect()
+        };
 
-        let world = Node::grow()
-            .down()
-            .invisible()
-            .tight()
-            .with_child(Node::grow().down().invisible().with_child(inner_topbar))
-            .with_child(
-                Node::grow()
-                    .tight()
-                    .down()
-                    .invisible()
-                    .with_child(Node::grow().invisible())
-                    .with_child(notif_bar),
-            );
+        let world = hud_area(state, panels);
 
         let root = Node::new(vb.span.x, vb.span.y)
             .down()
@@ -535,6 +1074,19 @@ impl Render for OrbitalContext {
                     .with_child(world),
             );
 
-        Some(Tree::new().with_layout(root, Vec2::ZERO))
+        let mut tree = Tree::new().with_layout(root, Vec2::ZERO);
+        for m in offscreen_markers(state) {
+            let (w, h) = (110.0, 24.0);
+            let c = m.pos - Vec2::from_angle(m.angle) * 50.0;
+            let origin = Vec2::new(
+                c.x + vb.span.x / 2.0 - w / 2.0,
+                vb.span.y / 2.0 - c.y - h / 2.0,
+            );
+            let button = Node::button(distance_str(m.distance), OnClick::CenterCamera(m.id), w, h)
+                .with_color(m.color.with_alpha(0.3).to_f32_array());
+            tree.add_layout(button, origin);
+        }
+
+        Some(tree)
     }
 }
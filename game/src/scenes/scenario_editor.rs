@@ -0,0 +1,470 @@
+use crate::camera_controller::*;
+use crate::canvas::Canvas;
+use crate::drawing::*;
+use crate::game::GameState;
+use crate::input::{FrameId, InputState, MouseButt};
+use crate::keybindings::Keybindings;
+use crate::onclick::OnClick;
+use crate::scenes::Render;
+use crate::ui::{delete_wrapper, top_bar, DELETE_SOMETHING_COLOR};
+use bevy::color::palettes::css::*;
+use bevy::prelude::*;
+use enum_iterator::{all, Sequence};
+use layout::layout::{Node, Size, Tree};
+use starling::prelude::*;
+
+/// Width of a newly placed landing site.
+const DEFAULT_SITE_WIDTH: f64 = 60.0;
+
+/// Step each press of the +/- buttons scales a size or distance by.
+const ADJUST_FACTOR: f64 = 1.1;
+
+/// Step each press of the +/- buttons turns an orbit by.
+const ADJUST_ANGLE: f64 = PI_64 / 12.0;
+
+/// What a click on the map does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+pub enum ScenarioTool {
+    Select,
+    Body,
+    Site,
+    Vehicle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+pub enum ScenarioParam {
+    Radius,
+    Mass,
+    Soi,
+    Periapsis,
+    Apoapsis,
+    ArgPeriapsis,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScenarioEditorContext {
+    camera: LinearCameraController,
+    pub scenario: Scenario,
+    pub selected: Option<EntityId>,
+    pub tool: ScenarioTool,
+    /// Model of vehicle placed by the vehicle tool.
+    pub model: String,
+}
+
+impl CameraProjection for ScenarioEditorContext {
+    fn origin(&self) -> DVec2 {
+        self.camera.origin()
+    }
+
+    fn scale(&self) -> f64 {
+        self.camera.scale()
+    }
+
+    fn offset(&self) -> DVec2 {
+        self.camera.offset()
+    }
+
+    fn parent(&self) -> EntityId {
+        self.camera.parent()
+    }
+}
+
+impl Default for ScenarioEditorContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScenarioEditorContext {
+    pub fn new() -> Self {
+        Self {
+            camera: LinearCameraController::new(DVec2::ZERO, 0.000001, 600.0),
+            scenario: Scenario::from_system("Custom", &default_example()),
+            selected: None,
+            tool: ScenarioTool::Select,
+            model: "pollux".to_string(),
+        }
+    }
+
+    pub fn on_game_tick(&mut self) {
+        self.camera.on_game_tick();
+    }
+
+    pub fn on_render_tick(&mut self, on_ui: bool, input: &InputState, keys: &Keybindings) {
+        self.camera.handle_input(input, keys);
+        if on_ui {
+            return;
+        }
+        if let Some(p) = input.on_frame(MouseButt::Left, FrameId::Down) {
+            self.place(self.c2w(p));
+        }
+    }
+
+    /// Applies the current tool at the given point on the map. Bodies and
+    /// vehicles go into a circular orbit through the point, about the
+    /// innermost body whose SOI contains it; sites go on the surface of
+    /// that body, directly below the point.
+    pub fn place(&mut self, pos: DVec2) -> Option<()> {
+        let parent = self.scenario.body_at(pos);
+        if self.tool == ScenarioTool::Select {
+            self.selected = parent;
+            return Some(());
+        }
+
+        if self.tool == ScenarioTool::Body && self.scenario.root().is_none() {
+            self.selected = Some(self.scenario.add_body(None, OrbitSpec::circular(0.0, 0.0)));
+            return Some(());
+        }
+
+        let parent = parent?;
+        let rel = pos - self.scenario.position(parent)?;
+        let orbit = OrbitSpec::circular(rel.length(), rel.to_angle());
+        match self.tool {
+            ScenarioTool::Select => (),
+            ScenarioTool::Body => {
+                self.selected = Some(self.scenario.add_body(Some(parent), orbit));
+            }
+            ScenarioTool::Site => {
+                let name = self.scenario.body(parent)?.name.clone();
+                let n = self.scenario.sites.len() + 1;
+                self.scenario.sites.push(ScenarioSite {
+                    name: format!("{} Site {}", name, n),
+                    planet_id: parent,
+                    angle: wrap_0_2pi_f64(rel.to_angle()),
+                    width: DEFAULT_SITE_WIDTH,
+                });
+            }
+            ScenarioTool::Vehicle => {
+                self.scenario.vehicles.push(ScenarioVehicle {
+                    model: self.model.clone(),
+                    parent,
+                    orbit,
+                });
+            }
+        }
+        Some(())
+    }
+
+    pub fn adjust(&mut self, id: EntityId, param: ScenarioParam, dir: i32) -> Option<()> {
+        let b = self.scenario.body_mut(id)?;
+        let f = ADJUST_FACTOR.powi(dir);
+        match param {
+            ScenarioParam::Radius => b.body.radius *= f,
+            ScenarioParam::Mass => b.body.mu *= f,
+            ScenarioParam::Soi => b.body.soi *= f,
+            ScenarioParam::Periapsis => {
+                let (_, o) = b.orbit.as_mut()?;
+                o.periapsis *= f;
+                o.apoapsis = o.apoapsis.max(o.periapsis);
+            }
+            ScenarioParam::Apoapsis => {
+                let (_, o) = b.orbit.as_mut()?;
+                o.apoapsis *= f;
+                o.periapsis = o.periapsis.min(o.apoapsis);
+            }
+            ScenarioParam::ArgPeriapsis => {
+                let (_, o) = b.orbit.as_mut()?;
+                o.arg_periapsis = wrap_0_2pi_f64(o.arg_periapsis + ADJUST_ANGLE * dir as f64);
+            }
+        }
+        Some(())
+    }
+
+    pub fn toggle_retrograde(&mut self, id: EntityId) -> Option<()> {
+        let (_, o) = self.scenario.body_mut(id)?.orbit.as_mut()?;
+        o.retrograde = !o.retrograde;
+        Some(())
+    }
+
+    pub fn remove_body(&mut self, id: EntityId) {
+        self.scenario.remove_body(id);
+        if self
+            .selected
+            .is_some_and(|s| self.scenario.body(s).is_none())
+        {
+            self.selected = None;
+        }
+    }
+
+    pub fn cycle_model(&mut self, models: &[String]) {
+        let i = models.iter().position(|m| *m == self.model);
+        let next = i.map(|i| (i + 1) % models.len()).unwrap_or(0);
+        if let Some(m) = models.get(next) {
+            self.model = m.clone();
+        }
+    }
+}
+
+fn param_value(b: &ScenarioBody, param: ScenarioParam) -> Option<String> {
+    let o = b.orbit.map(|(_, o)| o);
+    Some(match param {
+        ScenarioParam::Radius => format!("Radius {:0.0} km", b.body.radius / 1000.0),
+        ScenarioParam::Mass => format!("GM {:0.3e}", b.body.mu),
+        ScenarioParam::Soi => format!("SOI {:0.0} km", b.body.soi / 1000.0),
+        ScenarioParam::Periapsis => format!("Pe {:0.0} km", o?.periapsis / 1000.0),
+        ScenarioParam::Apoapsis => format!("Ap {:0.0} km", o?.apoapsis / 1000.0),
+        ScenarioParam::ArgPeriapsis => format!("ArgP {:0.0} deg", o?.arg_periapsis.to_degrees()),
+    })
+}
+
+fn body_panel(state: &GameState, width: f32) -> Node<OnClick> {
+    let ctx = &state.scenario_context;
+    let h = state.button_height();
+    let mut panel = Node::column(width).invisible().with_padding(0.0);
+
+    panel.add_child(Node::row(h).with_text("Bodies").enabled(false));
+    for b in &ctx.scenario.bodies {
+        let button = Node::button(
+            b.name.clone(),
+            OnClick::SelectScenarioBody(b.id),
+            Size::Grow,
+            h,
+        )
+        .enabled(ctx.selected != Some(b.id));
+        panel.add_child(delete_wrapper(OnClick::DeleteScenarioBody(b.id), button, h));
+    }
+
+    let Some(b) = ctx.selected.and_then(|id| ctx.scenario.body(id)) else {
+        return panel;
+    };
+
+    panel.add_child(Node::hline());
+    let parent = b
+        .orbit
+        .and_then(|(p, _)| ctx.scenario.body(p))
+        .map(|p| p.name.clone())
+        .unwrap_or("None".to_string());
+    panel.add_child(
+        Node::row(h)
+            .with_text(format!("{} ({}), orbits {}", b.name, b.id, parent))
+            .enabled(false),
+    );
+
+    for param in all::<ScenarioParam>() {
+        let Some(text) = param_value(b, param) else {
+            continue;
+        };
+        panel.add_child(
+            Node::row(h)
+                .invisible()
+                .tight()
+                .with_child(Node::button(
+                    "-",
+                    OnClick::AdjustScenarioBody(b.id, param, -1),
+                    h,
+                    h,
+                ))
+                .with_child(Node::row(h).with_text(text).enabled(false))
+                .with_child(Node::button(
+                    "+",
+                    OnClick::AdjustScenarioBody(b.id, param, 1),
+                    h,
+                    h,
+                )),
+        );
+    }
+
+    if let Some((_, o)) = b.orbit {
+        let s = if o.retrograde {
+            "Retrograde"
+        } else {
+            "Prograde"
+        };
+        panel.add_child(Node::button(
+            s,
+            OnClick::ToggleScenarioRetrograde(b.id),
+            Size::Grow,
+            h,
+        ));
+    }
+
+    panel
+}
+
+fn scenario_panel(state: &GameState, width: f32) -> Node<OnClick> {
+    let ctx = &state.scenario_context;
+    let h = state.button_height();
+    let mut panel = Node::column(width).invisible().with_padding(0.0);
+
+    panel.add_child(
+        Node::row(h)
+            .with_text(ctx.scenario.name.clone())
+            .enabled(false),
+    );
+    panel.add_child(
+        Node::row(h)
+            .invisible()
+            .tight()
+            .with_children(all::<ScenarioTool>().map(|t| {
+                Node::button(format!("{:?}", t), OnClick::ScenarioTool(t), Size::Grow, h)
+                    .enabled(ctx.tool != t)
+            })),
+    );
+    panel.add_child(Node::button(
+        format!("Model: {}", ctx.model),
+        OnClick::CycleScenarioModel,
+        Size::Grow,
+        h,
+    ));
+
+    let issues = ctx.scenario.validate();
+    panel.add_child(
+        Node::row(h)
+            .invisible()
+            .tight()
+            .with_child(Node::button("Export", OnClick::Save, Size::Grow, h))
+            .with_child(Node::button("Import", OnClick::Load, Size::Grow, h))
+            .with_child(Node::button(
+                "From Universe",
+                OnClick::ScenarioFromUniverse,
+                Size::Grow,
+                h,
+            ))
+            .with_child(
+                Node::button("Play", OnClick::PlayScenario, Size::Grow, h)
+                    .enabled(issues.is_empty()),
+            ),
+    );
+
+    panel.add_child(Node::hline());
+    if issues.is_empty() {
+        panel.add_child(Node::row(h).with_text("No issues").enabled(false));
+    }
+    for issue in issues {
+        panel.add_child(
+            Node::row(h)
+                .with_text(issue.to_string())
+                .with_color(DELETE_SOMETHING_COLOR)
+                .enabled(false),
+        );
+    }
+
+    panel.add_child(Node::hline());
+    for (i, site) in ctx.scenario.sites.iter().enumerate() {
+        let node = Node::row(h).with_text(site.name.clone()).enabled(false);
+        panel.add_child(delete_wrapper(OnClick::DeleteScenarioSite(i), node, h));
+    }
+    for (i, v) in ctx.scenario.vehicles.iter().enumerate() {
+        let parent = ctx
+            .scenario
+            .body(v.parent)
+            .map(|b| b.name.clone())
+            .unwrap_or_default();
+        let node = Node::row(h)
+            .with_text(format!("{} at {}", v.model, parent))
+            .enabled(false);
+        panel.add_child(delete_wrapper(OnClick::DeleteScenarioVehicle(i), node, h));
+    }
+    for (i, e) in ctx.scenario.events.iter().enumerate() {
+        let node = Node::row(h)
+            .with_text(format!("{} {}", e.stamp.to_date(), e.action))
+            .enabled(false);
+        panel.add_child(delete_wrapper(OnClick::DeleteScenarioEvent(i), node, h));
+    }
+
+    panel
+}
+
+impl Render for ScenarioEditorContext {
+    fn background_color(_state: &GameState) -> Srgba {
+        GRAY.with_luminance(0.06)
+    }
+
+    fn ui(state: &GameState) -> Option<Tree<OnClick>> {
+        let vb = state.input.screen_bounds;
+        if vb.span.x == 0.0 || vb.span.y == 0.0 {
+            return None;
+        }
+
+        let width = if state.is_compact_layout() {
+            300.0
+        } else {
+            400.0
+        };
+
+        let body = Node::grow()
+            .invisible()
+            .with_child(body_panel(state, width))
+            .with_child(Node::grow().invisible())
+            .with_child(scenario_panel(state, width));
+
+        let layout = Node::new(vb.span.x, vb.span.y)
+            .tight()
+            .invisible()
+            .down()
+            .with_child(top_bar(state))
+            .with_child(body);
+
+        Some(Tree::new().with_layout(layout, Vec2::ZERO))
+    }
+
+    fn draw(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+        let ctx = &state.scenario_context;
+        let scenario = &ctx.scenario;
+        let issues = scenario.validate();
+
+        for b in &scenario.bodies {
+            let Some(pos) = scenario.position(b.id) else {
+                continue;
+            };
+            let p = ctx.w2c(pos);
+            let bad = issues.iter().any(|i| i.involves(b.id));
+            let color = if bad {
+                RED
+            } else if ctx.selected == Some(b.id) {
+                ORANGE
+            } else {
+                WHITE
+            };
+
+            let r = (b.body.radius * ctx.scale()) as f32;
+            draw_circle(&mut canvas.gizmos, p, r.max(3.0), color);
+            draw_circle(
+                &mut canvas.gizmos,
+                p,
+                (b.body.soi * ctx.scale()) as f32,
+                color.with_alpha(0.2),
+            );
+            canvas
+                .text(b.name.clone(), p + Vec2::Y * (r.max(3.0) + 20.0), 0.7)
+                .color = color.with_alpha(0.8);
+
+            if let Some((parent, spec)) = b.orbit {
+                let origin = scenario.position(parent);
+                let orbit = scenario.body(parent).and_then(|p| spec.to_orbit(p.body));
+                if let Some((origin, orbit)) = origin.zip(orbit) {
+                    draw_orbit(canvas, &orbit, origin, color.with_alpha(0.4), ctx);
+                }
+            }
+        }
+
+        for site in &scenario.sites {
+            let Some((b, pos)) = scenario
+                .body(site.planet_id)
+                .zip(scenario.position(site.planet_id))
+            else {
+                continue;
+            };
+            let p = ctx.w2c(pos + rotate_f64(DVec2::X * b.body.radius, site.angle));
+            draw_diamond(&mut canvas.gizmos, p, 10.0, GREEN);
+        }
+
+        for v in &scenario.vehicles {
+            let Some((b, pos)) = scenario.body(v.parent).zip(scenario.position(v.parent)) else {
+                continue;
+            };
+            let p = ctx.w2c(pos + v.orbit.initial_position());
+            draw_square(&mut canvas.gizmos, p, 7.0, TEAL);
+            if let Some(orbit) = v.orbit.to_orbit(b.body) {
+                draw_orbit(canvas, &orbit, pos, TEAL.with_alpha(0.3), ctx);
+            }
+        }
+
+        let cursor = state.input.position(MouseButt::Hover, FrameId::Current)?;
+        if !state.is_hovering_over_ui() && ctx.tool != ScenarioTool::Select {
+            draw_cross(&mut canvas.gizmos, cursor, 8.0, YELLOW);
+        }
+
+        Some(())
+    }
+}
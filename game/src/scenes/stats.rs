@@ -0,0 +1,86 @@
+use crate::canvas::Canvas;
+use crate::game::GameState;
+use crate::onclick::OnClick;
+use crate::scenes::{Render, TextLabel};
+use crate::stats::ACHIEVEMENTS;
+use bevy::color::palettes::css::*;
+use bevy::prelude::*;
+use layout::layout::Tree;
+
+pub struct StatsContext;
+
+impl Render for StatsContext {
+    fn background_color(_state: &GameState) -> Srgba {
+        GRAY.with_luminance(0.05)
+    }
+
+    fn ui(state: &GameState) -> Option<Tree<OnClick>> {
+        Some(crate::ui::basic_scenes_layout(state))
+    }
+
+    fn draw(canvas: &mut Canvas, state: &GameState) -> Option<()> {
+        let line_height = 28.0;
+        let left = Vec2::new(-400.0, 200.0);
+        let right = Vec2::new(100.0, 200.0);
+
+        canvas.label(
+            TextLabel::new(
+                format!(
+                    "LIFETIME STATISTICS ({})",
+                    state.args.profile.to_uppercase()
+                ),
+                left + Vec2::Y * line_height * 1.5,
+                1.0,
+            )
+            .with_anchor_left(),
+        );
+
+        for (i, (name, value)) in state.stats.stats.rows().into_iter().enumerate() {
+            let p = left - Vec2::Y * line_height * i as f32;
+            canvas.label(TextLabel::new(name.to_uppercase(), p, 0.7).with_anchor_left());
+            canvas.label(
+                TextLabel::new(value, p + Vec2::X * 250.0, 0.7)
+                    .with_anchor_left()
+                    .with_color(TEAL),
+            );
+        }
+
+        canvas.label(
+            TextLabel::new(
+                format!(
+                    "ACHIEVEMENTS {}/{}",
+                    state.stats.achievements.len(),
+                    ACHIEVEMENTS.len()
+                ),
+                right + Vec2::Y * line_height * 1.5,
+                1.0,
+            )
+            .with_anchor_left(),
+        );
+
+        for (i, a) in ACHIEVEMENTS.iter().enumerate() {
+            let p = right - Vec2::Y * line_height * 1.5 * i as f32;
+            let color = if state.stats.is_unlocked(a) {
+                GOLD
+            } else {
+                GRAY.with_alpha(0.5)
+            };
+            canvas.label(
+                TextLabel::new(a.name.to_uppercase(), p, 0.7)
+                    .with_anchor_left()
+                    .with_color(color),
+            );
+            canvas.label(
+                TextLabel::new(
+                    a.description.to_string(),
+                    p - Vec2::Y * line_height * 0.6,
+                    0.5,
+                )
+                .with_anchor_left()
+                .with_color(color.with_alpha(0.6)),
+            );
+        }
+
+        Some(())
+    }
+}
@@ -5,8 +5,10 @@ use crate::game::GameState;
 use crate::graph::Graph;
 use crate::input::InputState;
 use crate::input::{FrameId, MouseButt};
+use crate::keybindings::Keybindings;
 use crate::onclick::OnClick;
 use crate::scenes::{Render, TextLabel};
+use crate::z_index::ZOrdering;
 use bevy::color::palettes::css::*;
 use bevy::prelude::*;
 use layout::layout::Tree;
@@ -61,8 +63,8 @@ impl TelescopeContext {
         self.camera.on_game_tick();
     }
 
-    pub fn on_render_tick(&mut self, input: &InputState) {
-        self.camera.handle_input(input);
+    pub fn on_render_tick(&mut self, input: &InputState, keys: &Keybindings) {
+        self.camera.handle_input(input, keys);
     }
 
     pub fn screen_radius(state: &GameState) -> f32 {
@@ -101,6 +103,48 @@ impl TelescopeContext {
     }
 }
 
+/// Position from which the telescope is observing, in world coordinates.
+fn observer_position(state: &GameState) -> DVec2 {
+    state
+        .piloting()
+        .and_then(|id| state.universe.pv(id))
+        .map(|pv| pv.pos)
+        .unwrap_or(state.orbital_context.origin())
+}
+
+/// Planets lie in the orbital plane, so they're drawn along the horizon,
+/// sized by their angular radius as seen from the observer.
+fn draw_telescope_planets(canvas: &mut Canvas, state: &GameState) {
+    let observer = observer_position(state);
+    let screen_radius = TelescopeContext::screen_radius(state);
+    let stamp = state.universe.stamp();
+
+    for id in state.universe.planets.planet_ids() {
+        let Some((body, pv, _, sys)) = state.universe.planets.lookup(id, stamp) else {
+            continue;
+        };
+        let rel = pv.pos - observer;
+        let distance = rel.length();
+        if distance <= body.radius {
+            continue;
+        }
+
+        let az = rel.to_angle();
+        let (p, alpha, _) = TelescopeContext::screen_position(az, 0.0, state);
+        let angular_radius = (body.radius / distance).asin();
+        let size = (angular_radius * state.telescope_context.scale()) as f32 * screen_radius;
+        canvas
+            .sprite(
+                p,
+                0.0,
+                state.planet_sprite(&sys.name),
+                ZOrdering::Planet,
+                Vec2::splat(size.max(4.0) * 2.0),
+            )
+            .color = Some(WHITE.with_alpha(alpha));
+    }
+}
+
 fn get_frequency_spectrum(x: f32, d: f32, fc: f32) -> f32 {
     let rsq = (d * -20.0).exp();
     let blackbody = 0.7 / (x / 250.0);
@@ -143,6 +187,8 @@ impl Render for TelescopeContext {
             draw_circle(&mut canvas.gizmos, p, *radius, color.with_alpha(alpha));
         }
 
+        draw_telescope_planets(canvas, state);
+
         draw_graph(
             canvas,
             &graph,
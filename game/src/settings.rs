@@ -1,12 +1,80 @@
+use crate::alarms::AlarmAction;
+use crate::double_click::DoubleClickBindings;
+use crate::frame_budget::FrameBudgets;
+use crate::hud::SceneHuds;
+use crate::music::MusicSettings;
+use crate::palette::ColorPalette;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Settings {
     pub ui_button_height: f32,
     pub controller_cursor_speed: f32,
     pub draw_transform_tree: bool,
+    /// Draw generated textures even for planets with hand-made sprites.
+    pub procedural_planet_textures: bool,
+    /// Seconds of sim time that deleted vehicles can be restored for.
+    pub deleted_vehicle_retention: u32,
+    /// Extra randomly-placed vehicles to stream in at startup.
+    pub background_traffic: u32,
+    /// Keep an alarm set for the next transfer window to the pilot's target.
+    pub transfer_window_alarms: bool,
+    /// Action taken by newly created alarms when they go off.
+    pub default_alarm_action: AlarmAction,
+    /// Seconds of sim time ahead to warn of the piloted vehicle crashing.
+    pub collision_warning_secs: u32,
+    /// Days of sim time to predict ahead for piloted, selected and other
+    /// focused vehicles.
+    pub focus_horizon_days: u32,
+    /// Hours of sim time to predict ahead for all other vehicles.
+    pub background_horizon_hours: u32,
+    /// Vehicles cost money: crashes destroy them, insurance can be bought
+    /// per mission, and lost designs are rebuilt at landing sites.
+    pub career_mode: bool,
+    /// Which HUD widgets each scene shows, and the corners they're
+    /// stacked in.
+    pub hud: SceneHuds,
+    /// Colors for groups, orbits and notifications, including palettes
+    /// for color vision deficiencies.
+    pub color_palette: ColorPalette,
+    /// Minimum seconds of sim time between the first burns of vehicles
+    /// flying a shared maneuver plan.
+    pub shared_plan_stagger_secs: u32,
+    /// Soundtrack volume, and how it reacts to what's going on.
+    pub music: MusicSettings,
+    /// Highest time warp, in sim seconds per second, at which labels and
+    /// markers are shown. Above it they fade out.
+    pub label_max_warp: u32,
+    /// Load part sprites only once something draws them, and show parts
+    /// under construction by tinting the finished sprite rather than
+    /// keeping a baked image for each stage of building.
+    pub low_memory_sprites: bool,
+    /// Save the orbital scene as one compressed binary archive instead of
+    /// a YAML file for each kind of object.
+    pub compressed_saves: bool,
+    /// Seconds between autosaves of the orbital scene, or 0 for none.
+    /// Needs compressed saves.
+    pub autosave_interval_secs: u32,
+    /// Milliseconds an autosave may spend writing each frame.
+    pub autosave_budget_ms: u32,
+    /// Trigger commands by holding the right mouse button and drawing
+    /// strokes, as mapped in the gestures file.
+    pub mouse_gestures: bool,
+    /// Turn idle vehicles to face each scheduled burn ahead of time, and
+    /// call off plans whose vehicles aren't lined up when a burn is due.
+    pub burn_attitude_hold: bool,
+    /// Vehicles which crash near a landing site leave salvage for others
+    /// to pick up.
+    pub crash_salvage: bool,
+    /// Frame time allowed in each scene before costly visuals are
+    /// switched off to keep up.
+    pub frame_budgets: FrameBudgets,
+    /// What double-clicking orbiters, planets, landing sites and empty
+    /// space does in the orbital view.
+    pub double_click: DoubleClickBindings,
 }
 
 impl Default for Settings {
@@ -15,6 +83,29 @@ impl Default for Settings {
             ui_button_height: 32.0,
             controller_cursor_speed: 6.0,
             draw_transform_tree: false,
+            procedural_planet_textures: false,
+            deleted_vehicle_retention: 120,
+            background_traffic: 0,
+            transfer_window_alarms: true,
+            default_alarm_action: AlarmAction::Notify,
+            collision_warning_secs: 600,
+            focus_horizon_days: 7,
+            background_horizon_hours: 6,
+            career_mode: false,
+            hud: SceneHuds::default(),
+            color_palette: ColorPalette::default(),
+            shared_plan_stagger_secs: 600,
+            music: MusicSettings::default(),
+            label_max_warp: 3600,
+            low_memory_sprites: false,
+            compressed_saves: true,
+            autosave_interval_secs: 300,
+            autosave_budget_ms: 4,
+            mouse_gestures: false,
+            burn_attitude_hold: false,
+            crash_salvage: true,
+            frame_budgets: FrameBudgets::default(),
+            double_click: DoubleClickBindings::default(),
         }
     }
 }
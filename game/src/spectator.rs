@@ -0,0 +1,204 @@
+use crate::onclick::OnClick;
+use crate::ui::InteractionEvent;
+use starling::prelude::*;
+use std::collections::VecDeque;
+
+/// How long, in wall time, the director holds on one subject before
+/// cutting to the next.
+pub const DIRECTOR_SHOT_DURATION: Nanotime = Nanotime::millis(12_000);
+
+/// Subjects the director avoids cutting back to until others have had a
+/// turn.
+const DIRECTOR_MEMORY: usize = 3;
+
+/// Read-only mode for demo kiosks and streams. While it's on, the camera
+/// and info panels work as usual, but nothing which changes the game goes
+/// through. The director, if enabled, moves the camera between whatever
+/// is most interesting at the time.
+#[derive(Debug, Clone, Default)]
+pub struct Spectator {
+    active: bool,
+    director: bool,
+    last_cut: Option<Nanotime>,
+    recent: VecDeque<EntityId>,
+}
+
+impl Spectator {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+        if !active {
+            self.director = false;
+        }
+    }
+
+    pub fn is_directing(&self) -> bool {
+        self.active && self.director
+    }
+
+    pub fn set_director(&mut self, director: bool) {
+        self.director = director;
+        self.last_cut = None;
+        self.recent.clear();
+    }
+
+    /// Whether a button press may go through. Only those which move the
+    /// camera or change what's shown are allowed while spectating.
+    pub fn allows_click(&self, id: &OnClick) -> bool {
+        !self.active
+            || matches!(
+                id,
+                OnClick::Orbiter(_)
+                    | OnClick::CenterCamera(_)
+                    | OnClick::CurrentBody(_)
+                    | OnClick::ToggleDrawMode
+                    | OnClick::ToggleRotatingFrame
+                    | OnClick::TogglePhaseArcs
+                    | OnClick::InspectGroup(_)
+                    | OnClick::ToggleRailPanel(_)
+                    | OnClick::ToggleOrbitHistory
+                    | OnClick::ToggleDvMap
+                    | OnClick::ToggleConicConstruction
+                    | OnClick::ToggleRecords
+                    | OnClick::ScrubOrbitHistory(_)
+                    | OnClick::ScrubToOrbitRecord(_)
+                    | OnClick::ClearOrbitScrub
+                    | OnClick::ToggleVehicleInfo
+                    | OnClick::ToggleFlowPreview
+                    | OnClick::ToggleHudWidget(_)
+                    | OnClick::HudWidgetHandle(_)
+                    | OnClick::CloseStructureInspector
+                    | OnClick::CycleColorPalette
+                    | OnClick::ToggleMusic
+                    | OnClick::CycleDoubleClick(_)
+                    | OnClick::ResumeGame
+                    | OnClick::DismissExitDialog
+                    | OnClick::DismissHandover
+                    | OnClick::ToggleSpectator
+                    | OnClick::ToggleDirector
+                    | OnClick::Nullopt
+            )
+    }
+
+    /// Whether a keyboard interaction may go through while spectating.
+    pub fn allows_interaction(&self, e: &InteractionEvent) -> bool {
+        !self.active
+            || matches!(
+                e,
+                InteractionEvent::Orbits
+                    | InteractionEvent::DrawMode
+                    | InteractionEvent::ToggleRotatingFrame
+                    | InteractionEvent::ToggleFullscreen
+                    | InteractionEvent::ToggleHelp
+                    | InteractionEvent::ToggleSpectator
+                    | InteractionEvent::Escape
+                    | InteractionEvent::RedrawGui
+                    | InteractionEvent::MoveLeft
+                    | InteractionEvent::MoveRight
+                    | InteractionEvent::MoveUp
+                    | InteractionEvent::MoveDown
+                    | InteractionEvent::ZoomIn
+                    | InteractionEvent::ZoomOut
+                    | InteractionEvent::Reset
+                    | InteractionEvent::FollowPilot
+            )
+    }
+
+    /// Picks the subject for the next shot once the current one has run
+    /// its course, given how interesting each candidate is right now.
+    /// Prefers the most interesting candidate not seen recently.
+    pub fn next_shot(
+        &mut self,
+        wall_time: Nanotime,
+        candidates: impl IntoIterator<Item = (EntityId, f64)>,
+    ) -> Option<EntityId> {
+        if !self.is_directing() {
+            return None;
+        }
+        if self
+            .last_cut
+            .is_some_and(|t| wall_time - t < DIRECTOR_SHOT_DURATION)
+        {
+            return None;
+        }
+
+        let mut candidates: Vec<_> = candidates.into_iter().collect();
+        candidates.sort_by(|(a, x), (b, y)| y.total_cmp(x).then(a.cmp(b)));
+        let fresh = candidates
+            .iter()
+            .find(|(id, _)| !self.recent.contains(id))
+            .or(candidates.first());
+        let (id, _) = *fresh?;
+
+        self.last_cut = Some(wall_time);
+        self.recent.retain(|r| *r != id);
+        self.recent.push_back(id);
+        while self.recent.len() > DIRECTOR_MEMORY.min(candidates.len().saturating_sub(1)) {
+            self.recent.pop_front();
+        }
+        Some(id)
+    }
+}
+
+/// How much there is to see around a vehicle: burns, close approaches to
+/// its target and flight low over a body all rank above coasting.
+pub fn director_interest(universe: &Universe, id: EntityId) -> Option<f64> {
+    let sv = universe.surface_vehicles.get(&id)?;
+    if sv.is_landed() {
+        return Some(0.5);
+    }
+
+    let mut score = 1.0;
+    if sv.vehicle().is_thrusting() {
+        score += 3.0;
+    }
+    if sv
+        .target_relative_pv
+        .is_some_and(|pv| pv.pos.length() < 10_000.0)
+    {
+        score += 2.0;
+    }
+    if let Some((body, ..)) = universe.planets.lookup(sv.parent(), universe.stamp()) {
+        let altitude = sv.body.pv.pos.length() - body.radius;
+        if altitude < 50_000.0 {
+            score += 2.0;
+        }
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spectating_blocks_changes_and_director_rotates() {
+        let mut spec = Spectator::default();
+        assert!(spec.allows_click(&OnClick::DeleteOrbiter));
+        assert!(spec.allows_interaction(&InteractionEvent::Spawn));
+
+        spec.set_active(true);
+        assert!(!spec.allows_click(&OnClick::DeleteOrbiter));
+        assert!(!spec.allows_interaction(&InteractionEvent::Spawn));
+        assert!(!spec.allows_interaction(&InteractionEvent::ToggleDebugConsole));
+        assert!(spec.allows_click(&OnClick::CenterCamera(EntityId(1))));
+        assert!(spec.allows_interaction(&InteractionEvent::ZoomIn));
+
+        let candidates = [(EntityId(1), 1.0), (EntityId(2), 4.0), (EntityId(3), 2.0)];
+        assert_eq!(spec.next_shot(Nanotime::zero(), candidates), None);
+
+        spec.set_director(true);
+        let t = |s| Nanotime::secs(s);
+        assert_eq!(spec.next_shot(t(0), candidates), Some(EntityId(2)));
+        assert_eq!(spec.next_shot(t(5), candidates), None);
+        assert_eq!(spec.next_shot(t(12), candidates), Some(EntityId(3)));
+        assert_eq!(spec.next_shot(t(24), candidates), Some(EntityId(1)));
+        assert_eq!(spec.next_shot(t(36), candidates), Some(EntityId(2)));
+
+        spec.set_active(false);
+        assert!(!spec.is_directing());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::game::GameState;
+use crate::profiler::*;
 use crate::scenes::*;
 use bevy::prelude::*;
 
@@ -25,9 +26,10 @@ pub struct StaticSprite(usize, String);
 
 pub fn update_static_sprites(
     mut commands: Commands,
-    state: Res<GameState>,
+    mut state: ResMut<GameState>,
     mut query: Query<(Entity, &mut Sprite, &mut Transform, &mut StaticSprite)>,
 ) {
+    let span = ProfileSpan::start(ProfileStage::Sprites);
     let sprites: Vec<StaticSpriteDescriptor> = state.sprites.clone();
 
     let mut sprite_entities: Vec<_> = query.iter_mut().collect();
@@ -76,4 +78,6 @@ pub fn update_static_sprites(
             commands.entity(e).despawn();
         }
     }
+
+    state.profiler.finish(span);
 }
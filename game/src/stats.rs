@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use starling::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::path::Path;
+
+/// Touchdowns faster than this are counted as crashes rather than landings.
+pub const CRASH_SPEED: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub dv_expended: f64,
+    pub distance_traveled: f64,
+    pub landings: u64,
+    pub dockings: u64,
+    pub crashes: u64,
+    pub parts_placed: u64,
+}
+
+impl LifetimeStats {
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "Delta-V expended",
+                format!("{:.1} km/s", self.dv_expended / 1000.0),
+            ),
+            ("Distance traveled", distance_str(self.distance_traveled)),
+            ("Landings", self.landings.to_string()),
+            ("Dockings", self.dockings.to_string()),
+            ("Crashes", self.crashes.to_string()),
+            ("Parts placed", self.parts_placed.to_string()),
+        ]
+    }
+}
+
+pub struct Achievement {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub is_earned: fn(&LifetimeStats) -> bool,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        name: "The Eagle Has Landed",
+        description: "Land safely for the first time",
+        is_earned: |s| s.landings >= 1,
+    },
+    Achievement {
+        name: "Frequent Flyer",
+        description: "Land safely 25 times",
+        is_earned: |s| s.landings >= 25,
+    },
+    Achievement {
+        name: "Lithobraking",
+        description: "Hit the ground a little too hard",
+        is_earned: |s| s.crashes >= 1,
+    },
+    Achievement {
+        name: "Contact",
+        description: "Make contact with another vehicle",
+        is_earned: |s| s.dockings >= 1,
+    },
+    Achievement {
+        name: "Shipwright",
+        description: "Place 100 parts in the editor",
+        is_earned: |s| s.parts_placed >= 100,
+    },
+    Achievement {
+        name: "Big Burner",
+        description: "Expend 10 km/s of delta-V",
+        is_earned: |s| s.dv_expended >= 10_000.0,
+    },
+    Achievement {
+        name: "Voyager",
+        description: "Travel one million kilometers",
+        is_earned: |s| s.distance_traveled >= 1e9,
+    },
+];
+
+/// Lifetime statistics and unlocked achievements for a single profile.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StatsStore {
+    pub stats: LifetimeStats,
+    pub achievements: BTreeSet<String>,
+}
+
+impl StatsStore {
+    pub fn is_unlocked(&self, achievement: &Achievement) -> bool {
+        self.achievements.contains(achievement.name)
+    }
+
+    /// Unlocks any achievements whose thresholds have been crossed,
+    /// returning only the newly unlocked ones.
+    pub fn check_achievements(&mut self) -> Vec<&'static Achievement> {
+        let mut ret = Vec::new();
+        for a in ACHIEVEMENTS {
+            if !self.is_unlocked(a) && (a.is_earned)(&self.stats) {
+                self.achievements.insert(a.name.to_string());
+                ret.push(a);
+            }
+        }
+        ret
+    }
+}
+
+/// Totals for the current play session, shown in the pause menu. Unlike
+/// the lifetime stats these cover every vehicle, and aren't saved.
+#[derive(Debug, Clone, Default)]
+pub struct SessionSummary {
+    /// Sim time when the session started.
+    pub start: Option<Nanotime>,
+    pub fuel_used: Mass,
+    pub events: u64,
+    last_fuel: HashMap<EntityId, Mass>,
+}
+
+impl SessionSummary {
+    /// Adds up the fuel burned by each vehicle since the last update.
+    /// Refueling isn't counted against it.
+    pub fn update(&mut self, stamp: Nanotime, fuel: impl IntoIterator<Item = (EntityId, Mass)>) {
+        self.start.get_or_insert(stamp);
+        let mut last_fuel = HashMap::new();
+        for (id, mass) in fuel {
+            if let Some(last) = self.last_fuel.get(&id) {
+                if last.to_grams() > mass.to_grams() {
+                    self.fuel_used += *last - mass;
+                }
+            }
+            last_fuel.insert(id, mass);
+        }
+        self.last_fuel = last_fuel;
+    }
+
+    pub fn rows(&self, now: Nanotime) -> Vec<(&'static str, String)> {
+        let elapsed = self
+            .start
+            .map(|start| now - start)
+            .filter(|t| *t > Nanotime::zero())
+            .unwrap_or_default();
+        vec![
+            ("Sim time elapsed", duration_str(elapsed)),
+            ("Fuel used", self.fuel_used.to_string()),
+            ("Events", self.events.to_string()),
+        ]
+    }
+}
+
+pub fn load_stats_from_file(filename: &Path) -> Result<StatsStore, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    Ok(serde_yaml::from_str(&s)?)
+}
+
+pub fn write_stats_to_file(stats: &StatsStore, filename: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(stats)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn achievements_unlock_once() {
+        let mut store = StatsStore::default();
+        assert!(store.check_achievements().is_empty());
+
+        store.stats.landings = 1;
+        let unlocked = store.check_achievements();
+        assert_eq!(unlocked.len(), 1);
+        assert_eq!(unlocked[0].name, "The Eagle Has Landed");
+        assert!(store.check_achievements().is_empty());
+    }
+
+    #[test]
+    fn session_counts_fuel_burned_but_not_refueled() {
+        let (a, b) = (EntityId(1), EntityId(2));
+        let kg = Mass::kilograms;
+        let mut session = SessionSummary::default();
+        session.update(Nanotime::secs(10), [(a, kg(100)), (b, kg(50))]);
+        session.update(Nanotime::secs(20), [(a, kg(90)), (b, kg(50))]);
+        session.update(Nanotime::secs(30), [(a, kg(100)), (b, kg(45))]);
+        assert_eq!(session.fuel_used, kg(15));
+        assert_eq!(session.start, Some(Nanotime::secs(10)));
+        assert_eq!(
+            session.rows(Nanotime::secs(70))[0].1,
+            duration_str(Nanotime::secs(60))
+        );
+    }
+}
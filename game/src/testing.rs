@@ -0,0 +1,19 @@
+use starling::prelude::*;
+
+/// A universe of the real-scale solar system with `count` empty vehicles
+/// in circular orbits from 400 km up, each 10 km above the last.
+pub(crate) fn fleet_in_leo(count: usize) -> (Universe, Vec<EntityId>) {
+    let mut universe = Universe::new(starling::examples::rss());
+    let planet = universe.planets.id;
+    let body = universe.planets.body;
+    let ids = (0..count)
+        .map(|i| {
+            let r = body.radius + 400_000.0 + 10_000.0 * i as f64;
+            let orbit = SparseOrbit::circular(r, body, Nanotime::zero(), false);
+            universe
+                .add_orbital_vehicle(Vehicle::new(), GlobalOrbit(planet, orbit))
+                .unwrap()
+        })
+        .collect();
+    (universe, ids)
+}
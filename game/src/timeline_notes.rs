@@ -0,0 +1,138 @@
+use crate::alarms::AlarmAction;
+use serde::{Deserialize, Serialize};
+use starling::prelude::*;
+use std::error::Error;
+use std::path::Path;
+
+/// A note left by the player at a moment on the mission timeline, which
+/// can optionally go off like an alarm when that moment comes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineNote {
+    pub text: String,
+    pub stamp: Nanotime,
+    #[serde(default)]
+    pub alarm: Option<AlarmAction>,
+}
+
+impl TimelineNote {
+    pub fn new(text: impl Into<String>, stamp: Nanotime) -> Self {
+        Self {
+            text: text.into(),
+            stamp,
+            alarm: None,
+        }
+    }
+
+    pub fn with_alarm(mut self, action: AlarmAction) -> Self {
+        self.alarm = Some(action);
+        self
+    }
+
+    /// Steps through no alarm and then each alarm action in turn.
+    pub fn cycle_alarm(&mut self) {
+        self.alarm = match self.alarm {
+            None => enum_iterator::first(),
+            Some(a) => enum_iterator::next(&a),
+        };
+    }
+}
+
+/// Every note on the timeline, kept sorted by sim time. Unlike alarms,
+/// notes stay around after their time has passed.
+#[derive(Debug, Clone, Default)]
+pub struct TimelineNotes {
+    notes: Vec<TimelineNote>,
+}
+
+impl TimelineNotes {
+    pub fn new(mut notes: Vec<TimelineNote>) -> Self {
+        notes.sort_by_key(|n| n.stamp);
+        Self { notes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TimelineNote> + use<'_> {
+        self.notes.iter()
+    }
+
+    pub fn as_slice(&self) -> &[TimelineNote] {
+        &self.notes
+    }
+
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut TimelineNote> {
+        self.notes.get_mut(i)
+    }
+
+    /// Adds a note, after any others at the same time, and returns its
+    /// position.
+    pub fn add(&mut self, note: TimelineNote) -> usize {
+        let i = self.notes.partition_point(|n| n.stamp <= note.stamp);
+        self.notes.insert(i, note);
+        i
+    }
+
+    pub fn remove(&mut self, i: usize) -> Option<TimelineNote> {
+        (i < self.notes.len()).then(|| self.notes.remove(i))
+    }
+
+    /// Notes from `start` up to but not including `end`.
+    pub fn between(&self, start: Nanotime, end: Nanotime) -> &[TimelineNote] {
+        let a = self.notes.partition_point(|n| n.stamp < start);
+        let b = self.notes.partition_point(|n| n.stamp < end);
+        &self.notes[a..b.max(a)]
+    }
+}
+
+pub fn load_timeline_notes_from_file(filename: &Path) -> Result<TimelineNotes, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    Ok(TimelineNotes::new(serde_yaml::from_str(&s)?))
+}
+
+pub fn write_timeline_notes_to_file(
+    notes: &TimelineNotes,
+    filename: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(notes.as_slice())?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_stay_sorted_and_can_be_windowed() {
+        let mut notes = TimelineNotes::new(vec![
+            TimelineNote::new("window opens", Nanotime::secs(300)),
+            TimelineNote::new("start aerobrake here", Nanotime::secs(100)),
+        ]);
+        assert_eq!(notes.add(TimelineNote::new("burn", Nanotime::secs(200))), 1);
+
+        let texts: Vec<_> = notes.iter().map(|n| n.text.as_str()).collect();
+        assert_eq!(texts, vec!["start aerobrake here", "burn", "window opens"]);
+
+        let window = notes.between(Nanotime::secs(100), Nanotime::secs(300));
+        assert_eq!(window.len(), 2);
+        assert!(notes
+            .between(Nanotime::secs(400), Nanotime::secs(0))
+            .is_empty());
+
+        let note = notes.get_mut(0).unwrap();
+        note.cycle_alarm();
+        assert_eq!(note.alarm, Some(AlarmAction::Notify));
+        for _ in enum_iterator::all::<AlarmAction>() {
+            note.cycle_alarm();
+        }
+        assert_eq!(note.alarm, None);
+    }
+}
@@ -0,0 +1,205 @@
+use starling::prelude::*;
+
+/// Shape of a tween's progress over its duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps linear progress in [0, 1] to eased progress in [0, 1].
+    pub fn apply(&self, s: f32) -> f32 {
+        let s = s.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => s,
+            Self::EaseOut => 1.0 - (1.0 - s).powi(3),
+            Self::EaseInOut => s * s * (3.0 - 2.0 * s),
+        }
+    }
+}
+
+/// A value animated from one level to another over a fixed span of wall
+/// time. It's sampled as a function of the time, rather than stepped each
+/// frame, so animations run at the same speed at any frame rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween {
+    from: f32,
+    to: f32,
+    start: Nanotime,
+    duration: Nanotime,
+    easing: Easing,
+}
+
+impl Tween {
+    pub fn new(value: f32, duration: Nanotime, easing: Easing) -> Self {
+        Self {
+            from: value,
+            to: value,
+            start: Nanotime::ZERO,
+            duration,
+            easing,
+        }
+    }
+
+    pub fn value(&self, now: Nanotime) -> f32 {
+        if self.duration <= Nanotime::ZERO {
+            return self.to;
+        }
+        let s = (now - self.start).to_secs() / self.duration.to_secs();
+        self.from + (self.to - self.from) * self.easing.apply(s)
+    }
+
+    pub fn target(&self) -> f32 {
+        self.to
+    }
+
+    pub fn is_done(&self, now: Nanotime) -> bool {
+        now >= self.start + self.duration
+    }
+
+    /// Starts animating toward a new target from wherever the tween is
+    /// now. Setting the current target again does nothing.
+    pub fn set_target(&mut self, target: f32, now: Nanotime) {
+        if target == self.to {
+            return;
+        }
+        self.from = self.value(now);
+        self.to = target;
+        self.start = now;
+    }
+}
+
+/// A value which eases exponentially toward a moving target, like a
+/// low-pass filter, but by the wall time elapsed between updates rather
+/// than per update.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Smoothed {
+    value: f32,
+    half_life: Nanotime,
+    updated: Nanotime,
+}
+
+impl Smoothed {
+    pub fn new(value: f32, half_life: Nanotime) -> Self {
+        Self {
+            value,
+            half_life,
+            updated: Nanotime::ZERO,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn update(&mut self, target: f32, now: Nanotime) {
+        let dt = (now - self.updated).to_secs().max(0.0);
+        self.updated = now;
+        let k = if self.half_life > Nanotime::ZERO {
+            0.5f32.powf(dt / self.half_life.to_secs())
+        } else {
+            0.0
+        };
+        self.value = target + (self.value - target) * k;
+    }
+}
+
+/// Open or closed state of a menu, with an animated openness for the
+/// transition between them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collapsible {
+    collapsed: bool,
+    anim: Tween,
+}
+
+impl Collapsible {
+    pub const DURATION: Nanotime = Nanotime::millis(180);
+
+    pub fn new(collapsed: bool) -> Self {
+        let value = if collapsed { 0.0 } else { 1.0 };
+        Self {
+            collapsed,
+            anim: Tween::new(value, Self::DURATION, Easing::EaseInOut),
+        }
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool, now: Nanotime) {
+        self.collapsed = collapsed;
+        self.anim.set_target(if collapsed { 0.0 } else { 1.0 }, now);
+    }
+
+    pub fn toggle(&mut self, now: Nanotime) {
+        self.set_collapsed(!self.collapsed, now);
+    }
+
+    /// How far open the menu is drawn, from 0 to 1.
+    pub fn openness(&self, now: Nanotime) -> f32 {
+        self.anim.value(now)
+    }
+
+    /// How many of a menu's `n` rows to show, so the menu unrolls a row at
+    /// a time as it opens.
+    pub fn visible_rows(&self, n: usize, now: Nanotime) -> usize {
+        (n as f32 * self.openness(now)).ceil() as usize
+    }
+}
+
+/// Level of a transient element which ramps in over `ramp` after it
+/// appears and ramps back out over `ramp` before it expires, given its
+/// age and total lifetime.
+pub fn envelope(age: Nanotime, lifetime: Nanotime, ramp: Nanotime) -> f32 {
+    if ramp <= Nanotime::ZERO {
+        return 1.0;
+    }
+    let rise = age.to_secs() / ramp.to_secs();
+    let fall = (lifetime - age).to_secs() / ramp.to_secs();
+    Easing::EaseOut.apply(rise.min(fall))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tweens_depend_only_on_time() {
+        let mut t = Tween::new(0.0, Nanotime::millis(200), Easing::Linear);
+        t.set_target(1.0, Nanotime::secs(1));
+        assert_eq!(t.value(Nanotime::secs(1)), 0.0);
+        assert!((t.value(Nanotime::millis(1100)) - 0.5).abs() < 1E-5);
+        assert_eq!(t.value(Nanotime::secs(5)), 1.0);
+        assert!(t.is_done(Nanotime::millis(1200)));
+
+        t.set_target(0.0, Nanotime::millis(1100));
+        assert!((t.value(Nanotime::millis(1100)) - 0.5).abs() < 1E-5);
+
+        let mut coarse = Smoothed::new(0.0, Nanotime::millis(100));
+        let mut fine = coarse;
+        coarse.update(1.0, Nanotime::millis(200));
+        for i in 1..=20 {
+            fine.update(1.0, Nanotime::millis(10 * i));
+        }
+        assert!((coarse.value() - 0.75).abs() < 1E-4);
+        assert!((coarse.value() - fine.value()).abs() < 1E-4);
+
+        let mut menu = Collapsible::new(true);
+        assert_eq!(menu.visible_rows(10, Nanotime::ZERO), 0);
+        menu.toggle(Nanotime::secs(1));
+        assert!(!menu.is_collapsed());
+        let halfway = Nanotime::secs(1) + Nanotime::millis(90);
+        let rows = menu.visible_rows(10, halfway);
+        assert!(rows > 0 && rows < 10, "{}", rows);
+        assert_eq!(menu.visible_rows(10, Nanotime::secs(2)), 10);
+
+        let life = Nanotime::secs(10);
+        let ramp = Nanotime::secs(1);
+        assert_eq!(envelope(Nanotime::ZERO, life, ramp), 0.0);
+        assert_eq!(envelope(Nanotime::secs(5), life, ramp), 1.0);
+        assert_eq!(envelope(life, life, ramp), 0.0);
+    }
+}
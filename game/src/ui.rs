@@ -1,8 +1,19 @@
+use crate::double_click::DoubleClickTarget;
+use crate::drawing::vehicle_sprite_path;
+use crate::economy::*;
+use crate::frame_budget::CostlyVisual;
 use crate::game::GameState;
+use crate::groups::GroupStats;
+use crate::hud::hud_area;
 use crate::input::{FrameId, MouseButt};
+use crate::notifications::NotificationType;
 use crate::onclick::OnClick;
+use crate::ownership::{Handover, HandoverTarget};
+use crate::profiler::*;
 use crate::scenes::*;
 use crate::sim_rate::SimRate;
+use crate::tween::envelope;
+use crate::vehicle_query::VehicleQuery;
 use bevy::core_pipeline::bloom::Bloom;
 use bevy::prelude::*;
 use bevy::render::{
@@ -12,6 +23,7 @@ use bevy::render::{
 };
 use bevy::sprite::Anchor;
 use bevy::text::TextBounds;
+use enum_iterator::all;
 use layout::layout::{Node, Size, TextJustify, Tree};
 use starling::prelude::*;
 
@@ -39,6 +51,8 @@ pub enum InteractionEvent {
     CreateGroup,
     CursorMode,
     DrawMode,
+    ToggleRotatingFrame,
+    PinMeasurement,
     RedrawGui,
     ToggleFullscreen,
 
@@ -59,6 +73,9 @@ pub enum InteractionEvent {
     StrafeRight,
 
     ToggleDebugConsole,
+    ToggleHelp,
+    ToggleSpectator,
+    FollowPilot,
 }
 
 pub struct UiPlugin;
@@ -137,35 +154,127 @@ pub const DELETE_SOMETHING_COLOR: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
 pub const UI_BACKGROUND_COLOR: [f32; 4] = [0.05, 0.05, 0.05, 1.0];
 pub const PILOT_FAVORITES_COLOR: [f32; 4] = [0.3, 0.3, 0.9, 1.0];
 pub const EXIT_OVERLAY_BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.95];
+pub const PROGRESS_BAR_COLOR: [f32; 4] = [0.3, 0.8, 0.3, 1.0];
+
+/// Window width below which scenes switch to their compact layout, with
+/// the top bar wrapped onto two rows and side panels folded into a rail.
+pub const COMPACT_LAYOUT_WIDTH: f32 = 1280.0;
+
+/// Window height at which buttons are drawn at their configured height.
+/// Shorter windows scale them down, to no less than `MIN_UI_SCALE`.
+pub const REFERENCE_WINDOW_HEIGHT: f32 = 900.0;
+
+pub const MIN_UI_SCALE: f32 = 0.6;
+
+pub const ICON_RAIL_WIDTH: f32 = 52.0;
+
+pub fn ui_scale(window_height: f32) -> f32 {
+    (window_height / REFERENCE_WINDOW_HEIGHT).clamp(MIN_UI_SCALE, 1.0)
+}
 
 pub fn top_bar(state: &GameState) -> Node<OnClick> {
-    Node::row(Size::Fit)
+    let h = state.button_height();
+
+    let scenes = SceneType::all().map(|st| {
+        let s = format!("{:?}", st);
+        let id = OnClick::GoToScene(st);
+        Node::button(s, id, 120, h).enabled(state.scene != st)
+    });
+
+    let rates = SimRate::all().map(|r| {
+        let s = r.as_str();
+        let id = OnClick::SimSpeed(r);
+        Node::button(s, id, 50, h).enabled(state.universe_ticks_per_game_tick != r)
+    });
+
+    let hud = Node::button("HUD", OnClick::ToggleHudEditor, 80, Size::Grow)
+        .enabled(state.settings.hud.get(state.scene).is_some());
+
+    let first = Node::row(Size::Fit)
         .with_color(UI_BACKGROUND_COLOR)
         .with_child(Node::button("Save", OnClick::Save, 80, Size::Grow))
         .with_child(Node::button("Load", OnClick::Load, 80, Size::Grow))
         .with_child(Node::vline())
-        .with_children(SceneType::all().map(|st| {
-            let s = format!("{:?}", st);
-            let id = OnClick::GoToScene(st);
-            Node::button(s, id, 120, state.settings.ui_button_height).enabled(state.scene != st)
-        }))
+        .with_children(scenes);
+
+    if !state.is_compact_layout() {
+        return first
+            .with_child(Node::vline())
+            .with_children(rates)
+            .with_child(Node::vline())
+            .with_child(hud)
+            .with_child(date_or_spacer(state))
+            .with_child(Node::vline())
+            .with_child(Node::button("Exit", OnClick::Exit, 80, Size::Grow));
+    }
+
+    let first = first
+        .with_child(Node::grow().invisible())
         .with_child(Node::vline())
-        .with_children(SimRate::all().map(|r| {
-            let s = r.as_str();
-            let id = OnClick::SimSpeed(r);
-            Node::button(s, id, 50, state.settings.ui_button_height)
-                .enabled(state.universe_ticks_per_game_tick != r)
-        }))
-        .with_child(
-            Node::text(
-                Size::Grow,
-                state.settings.ui_button_height,
-                crate::scenes::orbital::date_info(state),
-            )
-            .enabled(false),
-        )
+        .with_child(Node::button("Exit", OnClick::Exit, 80, Size::Grow));
+
+    let second = Node::row(Size::Fit)
+        .with_color(UI_BACKGROUND_COLOR)
+        .with_children(rates)
         .with_child(Node::vline())
-        .with_child(Node::button("Exit", OnClick::Exit, 80, Size::Grow))
+        .with_child(hud)
+        .with_child(date_or_spacer(state));
+
+    Node::row(Size::Fit)
+        .down()
+        .tight()
+        .invisible()
+        .with_child(first)
+        .with_child(second)
+}
+
+/// Folds side panels into a narrow column of toggle buttons, with at most
+/// one panel expanded beside it. Panels with nothing in them get no button.
+pub fn icon_rail(
+    state: &GameState,
+    panels: Vec<(&str, Node<OnClick>)>,
+    expanded: Option<usize>,
+    onclick: impl Fn(usize) -> OnClick,
+) -> Node<OnClick> {
+    let h = state.button_height();
+    let mut rail = Node::new(ICON_RAIL_WIDTH, Size::Fit)
+        .down()
+        .with_padding(0.0)
+        .with_color(UI_BACKGROUND_COLOR);
+    let mut open = None;
+
+    for (i, (label, panel)) in panels.into_iter().enumerate() {
+        if panel.is_leaf() {
+            continue;
+        }
+        let button = Node::button(label, onclick(i), Size::Grow, h);
+        if expanded == Some(i) {
+            rail.add_child(button.with_color(PILOT_FAVORITES_COLOR));
+            open = Some(panel);
+        } else {
+            rail.add_child(button);
+        }
+    }
+
+    Node::new(Size::Fit, Size::Fit)
+        .tight()
+        .invisible()
+        .with_child(rail)
+        .with_child(open)
+}
+
+/// The date is a HUD widget in scenes with a HUD, and fixed in the top
+/// bar otherwise.
+fn date_or_spacer(state: &GameState) -> Node<OnClick> {
+    if state.settings.hud.get(state.scene).is_some() {
+        return Node::grow().invisible();
+    }
+    Node::text(
+        Size::Grow,
+        state.button_height(),
+        crate::scenes::orbital::date_info(state),
+    )
+    .enabled(false)
 }
 
 pub fn basic_scenes_layout(state: &GameState) -> Tree<OnClick> {
@@ -174,19 +283,22 @@ pub fn basic_scenes_layout(state: &GameState) -> Tree<OnClick> {
         return Tree::new();
     }
 
-    let top_bar = top_bar(state);
-    let notif_bar = notification_bar(state, Size::Fixed(900.0));
-
     let layout = Node::new(vb.span.x, vb.span.y)
         .tight()
         .invisible()
         .down()
-        .with_child(top_bar)
-        .with_child(notif_bar);
+        .with_child(top_bar(state))
+        .with_child(hud_area(state, Vec::new()));
 
     Tree::new().with_layout(layout, Vec2::ZERO)
 }
 
+/// Time over which notifications slide in and out of the notification bar.
+const NOTIFICATION_SLIDE_TIME: Nanotime = Nanotime::millis(250);
+
+/// Distance, in pixels, which notifications slide in from.
+const NOTIFICATION_SLIDE_DISTANCE: f32 = 300.0;
+
 pub fn notification_bar(state: &GameState, width: Size) -> Node<OnClick> {
     Node::new(width, Size::Fit)
         .down()
@@ -194,10 +306,37 @@ pub fn notification_bar(state: &GameState, width: Size) -> Node<OnClick> {
         .invisible()
         .with_children(state.notifications.iter().rev().take(20).rev().map(|n| {
             let s = format!("{}", n);
-            Node::new(width, 28)
-                .with_text(s)
-                .with_justify(TextJustify::Left)
-                .with_color([0.0, 0.0, 0.0, 0.0])
+            let slide = envelope(
+                state.wall_time - n.wall_time,
+                n.duration(),
+                NOTIFICATION_SLIDE_TIME,
+            );
+            let inset = Node::new(NOTIFICATION_SLIDE_DISTANCE * (1.0 - slide), 28).invisible();
+            let severity = state.settings.color_palette.severity(n.kind.severity());
+            let swatch = Node::new(6, 28).with_color(severity.to_f32_array());
+            let row = Node::new(width, 28)
+                .tight()
+                .invisible()
+                .with_child(inset)
+                .with_child(swatch);
+            let width = Size::Grow;
+            let entry = match n.kind {
+                NotificationType::OrbiterDeleted(id) if state.universe.recycle_bin.contains(id) => {
+                    Node::button(
+                        format!("{} [Undo delete]", s),
+                        OnClick::UndoDelete(id),
+                        width,
+                        28,
+                    )
+                    .with_justify(TextJustify::Left)
+                    .with_color(DELETE_SOMETHING_COLOR)
+                }
+                _ => Node::new(width, 28)
+                    .with_text(s)
+                    .with_justify(TextJustify::Left)
+                    .with_color([0.0, 0.0, 0.0, 0.0]),
+            };
+            row.with_child(entry)
         }))
 }
 
@@ -222,6 +361,57 @@ pub fn exit_prompt_overlay(button_height: f32, w: f32, h: f32) -> Node<OnClick>
             button_height,
         ));
 
+    centered_overlay(window, w, h)
+}
+
+/// Asks for confirmation before vehicles change hands.
+pub fn handover_prompt_overlay(
+    state: &GameState,
+    handover: &Handover,
+    w: f32,
+    h: f32,
+) -> Node<OnClick> {
+    let bh = state.button_height();
+    let mut window = Node::new(400, Size::Fit)
+        .down()
+        .with_color(UI_BACKGROUND_COLOR)
+        .with_child(
+            Node::row(bh)
+                .with_text(format!(
+                    "Hand over {} vehicle(s) {}?",
+                    handover.ids.len(),
+                    state.handover_destination(handover.target)
+                ))
+                .enabled(false),
+        );
+    for id in handover.ids.iter().take(MAX_HANDOVER_ROWS) {
+        window.add_child(Node::row(bh).with_text(state.callsign(*id)).enabled(false));
+    }
+    if handover.ids.len() > MAX_HANDOVER_ROWS {
+        let more = format!("...and {} more", handover.ids.len() - MAX_HANDOVER_ROWS);
+        window.add_child(Node::row(bh).with_text(more).enabled(false));
+    }
+    window.add_child(Node::button(
+        "Confirm",
+        OnClick::ConfirmHandover,
+        Size::Grow,
+        bh,
+    ));
+    window.add_child(Node::button(
+        "Cancel",
+        OnClick::DismissHandover,
+        Size::Grow,
+        bh,
+    ));
+
+    centered_overlay(window, w, h)
+}
+
+/// Most vehicles listed by name in the handover dialog.
+const MAX_HANDOVER_ROWS: usize = 6;
+
+/// Dims the whole screen behind a window in its center.
+fn centered_overlay(window: Node<OnClick>, w: f32, h: f32) -> Node<OnClick> {
     let col = Node::column(Size::Fit)
         .invisible()
         .down()
@@ -236,10 +426,197 @@ pub fn exit_prompt_overlay(button_height: f32, w: f32, h: f32) -> Node<OnClick>
         .with_child(Node::grow().invisible())
 }
 
+/// Shown when the game is paused with Escape, with a summary of the
+/// session so far.
+pub fn pause_menu_overlay(state: &GameState, w: f32, h: f32) -> Node<OnClick> {
+    let bh = state.button_height();
+    let button = |s: &str, onclick: OnClick| Node::button(s, onclick, Size::Grow, bh);
+
+    let mut window = Node::new(330, Size::Fit)
+        .down()
+        .with_color(UI_BACKGROUND_COLOR)
+        .with_child(Node::row(bh).with_text("Paused").enabled(false))
+        .with_child(button("Resume", OnClick::ResumeGame))
+        .with_child(button("Save", OnClick::Save))
+        .with_child(button("Load", OnClick::Load))
+        .with_child(button(
+            &format!("Palette: {}", state.settings.color_palette),
+            OnClick::CycleColorPalette,
+        ))
+        .with_child(button(
+            match state.settings.music.enabled {
+                true => "Music: On",
+                false => "Music: Off",
+            },
+            OnClick::ToggleMusic,
+        ))
+        .with_children(enum_iterator::all::<DoubleClickTarget>().map(|t| {
+            button(
+                &format!("Double-click {}: {}", t, state.settings.double_click.get(t)),
+                OnClick::CycleDoubleClick(t),
+            )
+        }))
+        .with_child(button("Main Menu", OnClick::GoToScene(SceneType::MainMenu)))
+        .with_child(button("Exit", OnClick::Exit))
+        .with_child(Node::hline());
+
+    for (name, value) in state.session.rows(state.universe.stamp()) {
+        window.add_child(
+            Node::row(bh)
+                .with_text(format!("{}: {}", name, value))
+                .enabled(false),
+        );
+    }
+
+    centered_overlay(window, w, h)
+}
+
+/// Keybindings and mouse gestures for the current scene, in a column per
+/// section.
+pub fn help_overlay(state: &GameState) -> Node<OnClick> {
+    let dims = state.input.screen_bounds.span;
+    let h = state.button_height() * 0.7;
+
+    let columns = state
+        .keybindings
+        .help(state.scene)
+        .into_iter()
+        .map(|section| {
+            let title = Node::row(h).with_text(section.title).enabled(false);
+            let rows = section.entries.into_iter().map(|(input, description)| {
+                Node::new(Size::Grow, h)
+                    .tight()
+                    .invisible()
+                    .with_child(
+                        Node::text(140, h, input)
+                            .with_justify(TextJustify::Left)
+                            .enabled(false),
+                    )
+                    .with_child(
+                        Node::text(Size::Grow, h, description)
+                            .with_justify(TextJustify::Left)
+                            .enabled(false),
+                    )
+            });
+            Node::new(420, Size::Fit)
+                .down()
+                .with_color(UI_BACKGROUND_COLOR)
+                .with_child(title)
+                .with_child(Node::hline())
+                .with_children(rows)
+        });
+
+    let window = Node::new(Size::Fit, Size::Fit)
+        .invisible()
+        .with_children(columns);
+
+    let col = Node::column(Size::Fit)
+        .invisible()
+        .down()
+        .with_child(Node::grow().invisible())
+        .with_child(window)
+        .with_child(Node::grow().invisible());
+
+    Node::new(dims.x, dims.y)
+        .with_color(EXIT_OVERLAY_BACKGROUND_COLOR)
+        .with_child(Node::grow().invisible())
+        .with_child(col)
+        .with_child(Node::grow().invisible())
+}
+
+pub const PROFILER_OVERLAY_WIDTH: f32 = 460.0;
+
+/// Rolling frame timings of each profiled stage. Stages averaging over
+/// their budget are highlighted.
+pub fn profiler_overlay(state: &GameState) -> Node<OnClick> {
+    let h = state.button_height() * 0.7;
+    let profiler = &state.profiler;
+
+    let rows = all::<ProfileStage>().map(|stage| {
+        let text = match profiler.timing(stage) {
+            Some(t) => format!(
+                "{:<16} {:>6} {:>6} {:>6} / {} us",
+                stage.to_string(),
+                t.last.as_micros(),
+                t.average.as_micros(),
+                t.peak.as_micros(),
+                stage.budget().as_micros(),
+            ),
+            None => format!("{:<16} --", stage.to_string()),
+        };
+        let row = Node::row(h)
+            .with_text(text)
+            .with_justify(TextJustify::Left)
+            .enabled(false);
+        if profiler.is_over_budget(stage) {
+            row.with_color(DELETE_SOMETHING_COLOR)
+        } else {
+            row
+        }
+    });
+
+    let over = profiler.over_budget();
+    let status = if over.is_empty() {
+        Node::row(h).with_text("All stages within budget")
+    } else {
+        let names: Vec<_> = over.iter().map(|s| s.to_string()).collect();
+        Node::row(h)
+            .with_text(format!("Over budget: {}", names.join(", ")))
+            .with_color(DELETE_SOMETHING_COLOR)
+    };
+
+    let degradation = &state.degradation;
+    let budget = match state.settings.frame_budgets.get(state.scene) {
+        Some(b) => format!("{} ms", b.as_millis()),
+        None => "none".to_string(),
+    };
+    let frame = match degradation.average() {
+        Some(avg) => format!("{:0.1} ms", avg.as_secs_f64() * 1000.0),
+        None => "--".to_string(),
+    };
+    let shed: Vec<_> = degradation.shed().map(|v| v.to_string()).collect();
+    let degraded = Node::row(h)
+        .with_text(format!(
+            "Frame {} / budget {}, degradation {}/{}",
+            frame,
+            budget,
+            degradation.level(),
+            enum_iterator::cardinality::<CostlyVisual>(),
+        ))
+        .with_justify(TextJustify::Left)
+        .enabled(false);
+    let disabled = if shed.is_empty() {
+        Node::row(h).with_text("All visuals enabled")
+    } else {
+        Node::row(h)
+            .with_text(format!("Disabled: {}", shed.join(", ")))
+            .with_color(DELETE_SOMETHING_COLOR)
+    };
+
+    Node::new(PROFILER_OVERLAY_WIDTH, Size::Fit)
+        .down()
+        .with_color(UI_BACKGROUND_COLOR)
+        .with_child(
+            Node::row(h)
+                .with_text(format!(
+                    "Profiler (last/avg/peak, {} frames)",
+                    PROFILER_WINDOW
+                ))
+                .enabled(false),
+        )
+        .with_child(Node::hline())
+        .with_children(rows)
+        .with_child(Node::hline())
+        .with_child(status.with_justify(TextJustify::Left).enabled(false))
+        .with_child(Node::hline())
+        .with_child(degraded)
+        .with_child(disabled.with_justify(TextJustify::Left).enabled(false))
+}
+
 pub fn console_overlay(state: &GameState) -> Node<OnClick> {
     let dims = state.input.screen_bounds.span;
 
-    let button_height = state.settings.ui_button_height * 0.6;
+    let button_height = state.button_height() * 0.6;
     let offset = "   ";
     let cursor = if crate::drawing::is_blinking(state.wall_time) {
         "_"
@@ -250,7 +627,7 @@ pub fn console_overlay(state: &GameState) -> Node<OnClick> {
     let spacer = Node::grow().invisible();
 
     let cmd = Node::row(button_height)
-        .with_text(format!("{}> {}{}", offset, state.console.cmd(), cursor))
+        .with_text(format!("{}{}{}", offset, state.console.prompt(), cursor))
         .with_justify(TextJustify::Left)
         .with_color(UI_BACKGROUND_COLOR);
 
@@ -334,25 +711,15 @@ pub fn piloting_buttons(state: &GameState, width: Size) -> Node<OnClick> {
 
     let _x = if let Some(p) = state.orbital_context.piloting {
         wrapper.add_child({
-            let s = format!("Piloting {}", p);
-            let b = Node::button(
-                s,
-                OnClick::Orbiter(p),
-                Size::Grow,
-                state.settings.ui_button_height,
-            );
-            delete_wrapper(OnClick::ClearPilot, b, state.settings.ui_button_height)
+            let s = format!("Piloting {}", state.callsign(p));
+            let b = Node::button(s, OnClick::Orbiter(p), Size::Grow, state.button_height());
+            delete_wrapper(OnClick::ClearPilot, b, state.button_height())
         });
     } else if let Some(p) = state.orbital_context.following {
         if state.universe.surface_vehicles.contains_key(&p) {
             wrapper.add_child({
-                let s = format!("Pilot {}", p);
-                Node::button(
-                    s,
-                    OnClick::SetPilot(p),
-                    Size::Grow,
-                    state.settings.ui_button_height,
-                )
+                let s = format!("Pilot {}", state.callsign(p));
+                Node::button(s, OnClick::SetPilot(p), Size::Grow, state.button_height())
             });
         }
     } else {
@@ -361,7 +728,7 @@ pub fn piloting_buttons(state: &GameState, width: Size) -> Node<OnClick> {
                 "No craft selected",
                 OnClick::Nullopt,
                 Size::Grow,
-                state.settings.ui_button_height,
+                state.button_height(),
             )
             .enabled(false),
         );
@@ -369,26 +736,16 @@ pub fn piloting_buttons(state: &GameState, width: Size) -> Node<OnClick> {
 
     let _y = if let Some(p) = target {
         wrapper.add_child({
-            let s = format!("Targeting {}", p);
-            let b = Node::button(
-                s,
-                OnClick::Orbiter(p),
-                Size::Grow,
-                state.settings.ui_button_height,
-            );
-            delete_wrapper(OnClick::ClearTarget, b, state.settings.ui_button_height)
+            let s = format!("Targeting {}", state.callsign(p));
+            let b = Node::button(s, OnClick::Orbiter(p), Size::Grow, state.button_height());
+            delete_wrapper(OnClick::ClearTarget, b, state.button_height())
         });
         true
     } else if let Some(p) = state.orbital_context.following {
         if state.universe.surface_vehicles.contains_key(&p) {
             wrapper.add_child({
-                let s = format!("Target {}", p);
-                Node::button(
-                    s,
-                    OnClick::SetTarget(p),
-                    Size::Grow,
-                    state.settings.ui_button_height,
-                )
+                let s = format!("Target {}", state.callsign(p));
+                Node::button(s, OnClick::SetTarget(p), Size::Grow, state.button_height())
             });
             true
         } else {
@@ -398,13 +755,52 @@ pub fn piloting_buttons(state: &GameState, width: Size) -> Node<OnClick> {
         false
     };
 
+    if state.piloting().is_some() {
+        wrapper.add_child(Node::button(
+            "Spawn Practice Target",
+            OnClick::SpawnPracticeTarget,
+            Size::Grow,
+            state.button_height(),
+        ));
+    }
+
+    if let Some((p, t)) = state.piloting().zip(target) {
+        let b = if state.universe.rendezvous.contains_key(&p) {
+            let s = format!("Rendezvous with {}", state.callsign(t));
+            let b = Node::button(s, OnClick::Orbiter(t), Size::Grow, state.button_height());
+            delete_wrapper(OnClick::CancelRendezvous(p), b, state.button_height())
+        } else {
+            Node::button(
+                "Rendezvous with target",
+                OnClick::Rendezvous,
+                Size::Grow,
+                state.button_height(),
+            )
+        };
+        wrapper.add_child(b);
+    }
+
+    if state.piloting().is_some() && target.is_some() {
+        let s = if state.orbital_context.rotating_frame {
+            "Frame: LVLH"
+        } else {
+            "Frame: Inertial"
+        };
+        wrapper.add_child(Node::button(
+            s,
+            OnClick::ToggleRotatingFrame,
+            Size::Grow,
+            state.button_height(),
+        ));
+    }
+
     if state.piloting().is_some() && target.is_some() {
         wrapper.add_child({
             Node::button(
                 "Swap",
                 OnClick::SwapOwnshipTarget,
                 Size::Grow,
-                state.settings.ui_button_height,
+                state.button_height(),
             )
         });
     }
@@ -412,110 +808,1609 @@ pub fn piloting_buttons(state: &GameState, width: Size) -> Node<OnClick> {
     wrapper
 }
 
-pub fn selected_button(state: &GameState, width: Size) -> Node<OnClick> {
-    let s = format!("{} selected", state.orbital_context.selected.len());
-    let b = Node::button(
-        s,
-        OnClick::SelectedCount,
-        width,
-        state.settings.ui_button_height,
-    )
-    .enabled(false);
-    if state.orbital_context.selected.is_empty() {
-        b
-    } else {
-        delete_wrapper(OnClick::ClearTracks, b, state.settings.ui_button_height)
-    }
-}
+/// Buttons for assigning a registered control law to the piloted vehicle.
+pub fn control_law_buttons(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
 
-pub fn orbiter_list(
-    state: &GameState,
-    root: &mut Node<OnClick>,
-    max_cells: usize,
-    mut ids: Vec<EntityId>,
-) {
-    ids.sort();
+    let Some(sv) = state
+        .piloting()
+        .and_then(|id| state.universe.surface_vehicles.get(&id))
+    else {
+        return wrapper;
+    };
 
-    let rows = (ids.len().min(max_cells) as f32 / 4.0).ceil() as u32;
-    let grid = Node::grid(
-        Size::Grow,
-        rows * state.settings.ui_button_height as u32,
-        rows,
-        4,
-        4.0,
-        |i| {
-            if i as usize > max_cells {
-                return None;
-            }
-            let id = ids.get(i as usize)?;
-            let s = format!("{id}");
-            Some(
-                Node::grow()
-                    .with_on_click(OnClick::Orbiter(*id))
-                    .with_text(s)
-                    .enabled(Some(*id) != state.orbital_context.following),
-            )
-        },
+    wrapper.add_child(
+        Node::row(state.button_height())
+            .with_text(sv.controller.mode().to_status_str())
+            .enabled(false),
     );
-    root.add_child(grid);
 
-    if ids.len() > max_cells {
-        let n = ids.len() - max_cells;
-        let s = format!("...And {} more", n);
-        root.add_child(
-            Node::new(Size::Grow, state.settings.ui_button_height)
-                .with_text(s)
-                .enabled(false),
+    for law in state.universe.control_laws.iter() {
+        let policy = VehicleControlPolicy::ControlLaw(law.name().to_string());
+        let active = *sv.controller.mode() == policy;
+        wrapper.add_child(
+            Node::button(
+                law.name(),
+                OnClick::SetControllerPolicy(policy),
+                Size::Grow,
+                state.button_height(),
+            )
+            .enabled(!active),
         );
     }
+
+    wrapper
 }
 
-pub fn left_right_arrows(
-    width: impl Into<Size>,
-    height: impl Into<Size>,
-    left: OnClick,
-    right: OnClick,
-) -> Node<OnClick> {
-    let height = height.into();
-    let left = Node::button("-", left, Size::Grow, height);
-    let right = Node::button("+", right, Size::Grow, height);
-    Node::new(width, height)
-        .with_padding(0.0)
+/// Phase angle readouts to the pilot's target, if it can be reached by a
+/// transfer about a common body.
+pub fn phase_angle_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
         .invisible()
-        .with_child(left)
-        .with_child(right)
-}
+        .with_padding(0.0);
 
-pub fn layout(state: &GameState) -> Tree<OnClick> {
-    match state.scene {
-        SceneType::MainMenu => MainMenuContext::ui(state),
-        SceneType::Telescope => TelescopeContext::ui(state),
-        SceneType::Orbital => OrbitalContext::ui(state),
-        SceneType::Editor => EditorContext::ui(state),
-    }
-    .unwrap_or(Tree::new())
-}
+    let Some(info) = state.phase_info() else {
+        return wrapper;
+    };
 
-#[derive(Component)]
-pub struct UiElement;
+    let or_never = |t: Option<Nanotime>| t.map(duration_str).unwrap_or("never".to_string());
 
-fn map_bytes(image: &mut Image, func: impl Fn(&mut [u8], u32, u32, u32, u32)) {
-    let w = image.width();
-    let h = image.height();
-    for x in 0..w {
-        for y in 0..h {
-            if let Some(bytes) = image.pixel_bytes_mut(UVec3::new(x, y, 0)) {
-                func(bytes, x, y, w, h);
-            }
-        }
+    for s in [
+        format!("Phase angle {:0.1} deg", info.current.to_degrees()),
+        format!("Hohmann phase {:0.1} deg", info.required.to_degrees()),
+        format!("Next window {}", or_never(info.time_to_window)),
+        format!("Synodic period {}", or_never(info.synodic_period)),
+    ] {
+        wrapper.add_child(Node::row(state.button_height()).with_text(s).enabled(false));
     }
-}
 
-fn generate_button_sprite(
-    node: &Node<OnClick>,
-    is_clicked: bool,
-    is_hover: bool,
-) -> (Image, f32, f32) {
+    let s = if state.orbital_context.show_phase_arcs {
+        "Hide phase arcs"
+    } else {
+        "Show phase arcs"
+    };
+    wrapper.add_child(Node::button(
+        s,
+        OnClick::TogglePhaseArcs,
+        Size::Grow,
+        state.button_height(),
+    ));
+
+    wrapper
+}
+
+/// One-click circularization burns for the piloted vehicle, with what
+/// each costs. Other adjustments are made with the `adjust` command.
+pub fn orbit_adjustment_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let Some(id) = state.piloting() else {
+        return wrapper;
+    };
+    let Some(sv) = state.universe.surface_vehicles.get(&id) else {
+        return wrapper;
+    };
+    if sv.current_orbit().is_none() {
+        return wrapper;
+    }
+    let remaining = sv.vehicle().remaining_dv();
+
+    let h = state.button_height();
+    for (s, adjustment) in [
+        ("Circularize at Ap", OrbitAdjustment::CircularizeAtApoapsis),
+        ("Circularize at Pe", OrbitAdjustment::CircularizeAtPeriapsis),
+    ] {
+        let Ok((plan, cost)) = state.plan_orbit_adjustment(id, adjustment) else {
+            continue;
+        };
+        wrapper.add_child(
+            Node::button(
+                format!("{} ({:0.1} m/s)", s, cost),
+                OnClick::AdjustOrbit(adjustment),
+                Size::Grow,
+                h,
+            )
+            .enabled(plan.dv() <= remaining),
+        );
+    }
+
+    wrapper
+}
+
+/// The piloted vehicle's maneuver node, with its burn and where it leaves
+/// the vehicle, and controls to schedule or discard it.
+pub fn maneuver_node_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let Some(id) = state.piloting() else {
+        return wrapper;
+    };
+    let h = state.button_height();
+    let Some(node) = state.orbital_context.maneuver_nodes.get(&id) else {
+        if state.orbital_context.cursor_mode == CursorMode::ManeuverNode {
+            wrapper.add_child(
+                Node::row(h)
+                    .with_text("Click the orbit to place a node")
+                    .enabled(false),
+            );
+        }
+        return wrapper;
+    };
+
+    let stamp = state.universe.stamp();
+    for s in [
+        format!("Maneuver in {}", node.stamp - stamp),
+        format!("Prograde {:0.1} m/s", node.prograde),
+        format!("Radial {:0.1} m/s", node.radial),
+        format!("Total {:0.1} m/s", node.magnitude()),
+    ] {
+        wrapper.add_child(Node::row(h).with_text(s).enabled(false));
+    }
+
+    let planned = state.plan_maneuver_node(id);
+    if let Ok((plan, _)) = &planned {
+        let t = plan.terminal;
+        let ap = if t.will_escape() {
+            "escape".to_string()
+        } else {
+            distance_str(t.apoapsis_r() - t.body.radius)
+        };
+        let s = format!(
+            "Pe {} / Ap {}",
+            distance_str(t.periapsis_r() - t.body.radius),
+            ap
+        );
+        wrapper.add_child(Node::row(h).with_text(s).enabled(false));
+    }
+
+    let remaining = state
+        .universe
+        .surface_vehicles
+        .get(&id)
+        .map(|sv| sv.vehicle().remaining_dv())
+        .unwrap_or(0.0);
+    wrapper.add_child(
+        Node::button("Commit", OnClick::CommitManeuverNode, Size::Grow, h)
+            .enabled(planned.is_ok_and(|(plan, _)| plan.dv() <= remaining)),
+    );
+    wrapper.add_child(Node::button(
+        "Delete",
+        OnClick::DeleteManeuverNode,
+        Size::Grow,
+        h,
+    ));
+
+    wrapper
+}
+
+/// Launch guidance tuning for the piloted vehicle, shown while it is on
+/// the ground or climbing to orbit.
+pub fn ascent_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let Some(profile) = state.is_ascending().and_then(|id| state.ascent_profile(id)) else {
+        return wrapper;
+    };
+
+    let h = state.button_height();
+    wrapper.add_child(Node::row(h).with_text("Ascent guidance").enabled(false));
+
+    for param in AscentParam::ALL {
+        let s = format!("{} {}", param, profile.value_str(param));
+        wrapper.add_child(
+            Node::row(h)
+                .invisible()
+                .tight()
+                .with_child(Node::button("-", OnClick::AdjustAscent(param, -1), h, h))
+                .with_child(Node::row(h).with_text(s).enabled(false))
+                .with_child(Node::button("+", OnClick::AdjustAscent(param, 1), h, h)),
+        );
+    }
+
+    wrapper.add_child(Node::button(
+        "Reset to defaults",
+        OnClick::ResetAscent,
+        Size::Grow,
+        h,
+    ));
+    wrapper.add_child(Node::button(
+        "Launch",
+        OnClick::LaunchToOrbit,
+        Size::Grow,
+        h,
+    ));
+
+    wrapper
+}
+
+/// Docking traffic at any selected or followed vehicle, and controls to
+/// queue the pilot and the selected vehicles to dock at the pilot's target.
+pub fn docking_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let h = state.button_height();
+    let ctx = &state.orbital_context;
+
+    if let Some(pilot) = state.piloting() {
+        let target = state
+            .universe
+            .surface_vehicles
+            .get(&pilot)
+            .and_then(|sv| sv.target())
+            .filter(|t| state.universe.surface_vehicles.contains_key(t));
+        if let Some(queue) = state.universe.docking_queue_of(pilot) {
+            wrapper.add_child(Node::button(
+                format!("Cancel docking at {}", queue.target),
+                OnClick::CancelDocking(pilot),
+                Size::Grow,
+                h,
+            ));
+        } else if let Some(target) = target {
+            let others = ctx
+                .selected
+                .iter()
+                .filter(|id| **id != pilot && **id != target)
+                .count();
+            wrapper.add_child(Node::button(
+                format!("Dock {} at {}", others + 1, target),
+                OnClick::RequestDocking,
+                Size::Grow,
+                h,
+            ));
+        }
+
+        if let Some(target) = target.and_then(|t| state.universe.surface_vehicles.get_key_value(&t))
+        {
+            let (id, sv) = target;
+            let s = if state.universe.captures.contains_key(&pilot) {
+                format!("Captured by {}", sv.vehicle().name())
+            } else if sv.capture_assist {
+                format!("Capture assist on at {}", sv.vehicle().name())
+            } else {
+                format!("Capture assist off at {}", sv.vehicle().name())
+            };
+            wrapper.add_child(Node::button(
+                s,
+                OnClick::ToggleCaptureAssist(*id),
+                Size::Grow,
+                h,
+            ));
+        }
+
+        for (guest, joint) in state.universe.docked.get(&pilot).into_iter().flatten() {
+            wrapper.add_child(Node::button(
+                format!("Undock {}", joint.name()),
+                OnClick::Undock(pilot, *guest),
+                Size::Grow,
+                h,
+            ));
+        }
+    }
+
+    let watched = |id: &EntityId| ctx.selected.contains(id) || ctx.following == Some(*id);
+    for queue in state
+        .universe
+        .docking
+        .values()
+        .filter(|q| watched(&q.target))
+    {
+        wrapper.add_child(
+            Node::row(h)
+                .with_text(format!("Docking traffic at {}", queue.target))
+                .enabled(false),
+        );
+        let target_pv = state.universe.pv(queue.target);
+        let mut holding = 0;
+        for (i, request) in queue.requests().enumerate() {
+            let range = state
+                .universe
+                .pv(request.vehicle)
+                .zip(target_pv)
+                .map(|(a, b)| format!("{:0.0} m", a.pos.distance(b.pos)))
+                .unwrap_or_default();
+            let phase = match request.phase {
+                DockingPhase::Cleared(_) => "cleared".to_string(),
+                DockingPhase::Holding => {
+                    holding += 1;
+                    format!("holding #{}", holding)
+                }
+            };
+            let mut s = format!("{}. {} {}, {}", i + 1, request.vehicle, phase, range);
+            if request.wave_offs > 0 {
+                s += &format!(" ({}x waved off)", request.wave_offs);
+            }
+            wrapper.add_child(Node::row(h).with_text(s).enabled(false));
+        }
+    }
+
+    wrapper
+}
+
+/// The two vehicles items are passed between: the two selected vehicles
+/// if exactly two are, otherwise the pilot and its target.
+fn transfer_pair(state: &GameState) -> Option<(EntityId, EntityId)> {
+    let ctx = &state.orbital_context;
+    if ctx.selected.len() == 2 {
+        let mut ids: Vec<_> = ctx.selected.iter().copied().collect();
+        ids.sort();
+        return Some((ids[0], ids[1]));
+    }
+    let pilot = state.piloting()?;
+    let target = state.universe.surface_vehicles.get(&pilot)?.target()?;
+    Some((pilot, target))
+}
+
+/// Items aboard a pair of vehicles alongside one another, with controls
+/// to pass them from one to the other.
+pub fn transfer_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let h = state.button_height();
+    let Some((a, b)) = transfer_pair(state) else {
+        return wrapper;
+    };
+    let (Some(sa), Some(sb)) = (
+        state.universe.surface_vehicles.get(&a),
+        state.universe.surface_vehicles.get(&b),
+    ) else {
+        return wrapper;
+    };
+
+    let in_range = sa.parent() == sb.parent() && in_transfer_range(sa.pv(), sb.pv());
+    let s = if in_range {
+        format!(
+            "Transfer between {} and {}",
+            state.callsign(a),
+            state.callsign(b)
+        )
+    } else {
+        format!(
+            "{} and {} are too far apart",
+            state.callsign(a),
+            state.callsign(b)
+        )
+    };
+    wrapper.add_child(Node::row(h).with_text(s).enabled(false));
+
+    for t in state.universe.transfers.iter().filter(|t| t.involves(a, b)) {
+        let s = format!(
+            "{} to {}: {}",
+            t.item(),
+            state.callsign(t.destination()),
+            t.moved()
+        );
+        let button = Node::button(s, OnClick::Nullopt, Size::Grow, h).enabled(false);
+        wrapper.add_child(delete_wrapper(OnClick::CancelTransfer(a, b), button, h));
+    }
+
+    for (from, to, src, dst) in [(a, b, sa, sb), (b, a, sb, sa)] {
+        let mut items: Vec<_> = src.vehicle.inventory().into_iter().collect();
+        items.retain(|(_, mass)| *mass > Mass::ZERO);
+        items.sort_by_key(|(item, _)| item.to_string());
+        for (item, mass) in items {
+            let s = format!("{} ({}) to {}", item, mass, state.callsign(to));
+            wrapper.add_child(
+                Node::button(s, OnClick::StartTransfer(from, to, item), Size::Grow, h)
+                    .enabled(in_range && dst.vehicle.room_for(item) > Mass::ZERO),
+            );
+        }
+    }
+
+    wrapper
+}
+
+/// Kind, size and condition of the structure clicked on in the orbital
+/// view, with controls to edit, repair or demolish it.
+pub fn structure_inspector(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let Some((id, s)) = state
+        .orbital_context
+        .inspected_structure
+        .and_then(|id| Some((id, state.universe.structures.get(&id)?)))
+    else {
+        return wrapper;
+    };
+
+    let h = state.button_height();
+    let site = s
+        .site
+        .and_then(|p| state.universe.landing_pads.get(&p))
+        .map(|p| p.name.clone())
+        .unwrap_or("None".to_string());
+
+    for text in [
+        format!("Structure {}", id),
+        format!("Site {}", site),
+        format!(
+            "Health {:0.0}/{:0.0} ({:0.0}%)",
+            s.health,
+            s.max_health(),
+            s.health_fraction() * 100.0
+        ),
+    ] {
+        wrapper.add_child(Node::row(h).with_text(text).enabled(false));
+    }
+
+    wrapper.add_child(Node::button(
+        format!("Kind: {}", s.kind),
+        OnClick::CycleStructureKind(id),
+        Size::Grow,
+        h,
+    ));
+
+    for (label, value, dw, dh) in [("Width", s.width, 1, 0), ("Height", s.height, 0, 1)] {
+        wrapper.add_child(
+            Node::row(h)
+                .invisible()
+                .tight()
+                .with_child(Node::button(
+                    "-",
+                    OnClick::ResizeStructure(id, -dw, -dh),
+                    h,
+                    h,
+                ))
+                .with_child(
+                    Node::row(h)
+                        .with_text(format!("{} {:0.0} m", label, value))
+                        .enabled(false),
+                )
+                .with_child(Node::button(
+                    "+",
+                    OnClick::ResizeStructure(id, dw, dh),
+                    h,
+                    h,
+                )),
+        );
+    }
+
+    wrapper.add_child(
+        Node::button("Repair", OnClick::RepairStructure(id), Size::Grow, h)
+            .enabled(s.health < s.max_health()),
+    );
+    wrapper.add_child(
+        Node::button("Demolish", OnClick::DemolishStructure(id), Size::Grow, h)
+            .with_color(DELETE_SOMETHING_COLOR),
+    );
+    wrapper.add_child(Node::button(
+        "Close",
+        OnClick::CloseStructureInspector,
+        Size::Grow,
+        h,
+    ));
+
+    wrapper
+}
+
+/// Details of the landing site beneath the piloted vehicle: its size,
+/// growth toward the next level and the services it offers.
+pub fn landing_site_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let Some(pad) = state.landing_site() else {
+        return wrapper;
+    };
+
+    let growth = match pad.level_progress() {
+        Some(p) => format!("{:0.0}% to next level", p * 100.0),
+        None => "Fully developed".to_string(),
+    };
+
+    for s in [
+        format!("{} ({})", pad.name, pad.level_name()),
+        format!("Population {}", pad.population()),
+        format!("Deliveries {}", pad.deliveries),
+        growth,
+    ] {
+        wrapper.add_child(Node::row(state.button_height()).with_text(s).enabled(false));
+    }
+
+    for service in SiteService::ALL {
+        let s = if service == SiteService::Refueling && pad.has_service(service) {
+            format!("{} ({:0.0} kg/s)", service, pad.refuel_rate())
+        } else if pad.has_service(service) {
+            service.to_string()
+        } else {
+            format!("{} (level {})", service, service.required_level())
+        };
+        wrapper.add_child(
+            Node::row(state.button_height())
+                .with_text(s)
+                .enabled(pad.has_service(service)),
+        );
+    }
+
+    let h = state.button_height();
+    let bar_width = match width {
+        Size::Fixed(w) => w,
+        _ => 200.0,
+    };
+
+    if let Some((id, sv)) = state
+        .piloting()
+        .and_then(|id| Some((id, state.universe.surface_vehicles.get(&id)?)))
+        .filter(|(_, sv)| sv.is_landed())
+    {
+        wrapper.add_child(
+            Node::button(
+                "Refuel",
+                OnClick::QueueServiceTask(ServiceTask::Refuel(id)),
+                Size::Grow,
+                h,
+            )
+            .enabled(pad.has_service(SiteService::Refueling)),
+        );
+        for (pid, part) in sv
+            .vehicle
+            .parts()
+            .filter(|(_, p)| !p.is_built())
+            .take(MAX_REPAIR_BUTTONS)
+        {
+            wrapper.add_child(
+                Node::button(
+                    format!("Repair {}", part.prototype().part_name()),
+                    OnClick::QueueServiceTask(ServiceTask::Repair(id, *pid)),
+                    Size::Grow,
+                    h,
+                )
+                .enabled(pad.has_service(SiteService::Repair)),
+            );
+        }
+    }
+
+    for (i, drone) in pad.drones.iter().enumerate() {
+        let Some(task) = drone.task() else {
+            wrapper.add_child(
+                Node::row(h)
+                    .with_text(format!("Drone {}: idle", i + 1))
+                    .enabled(false),
+            );
+            continue;
+        };
+        let progress = state
+            .universe
+            .surface_vehicles
+            .get(&task.vehicle())
+            .map(|sv| task.progress(sv.vehicle()))
+            .unwrap_or(0.0);
+        wrapper.add_child(
+            Node::row(h)
+                .with_text(format!("Drone {}: {}", i + 1, task))
+                .enabled(false),
+        );
+        wrapper.add_child(progress_bar(progress, bar_width, h / 4.0));
+    }
+
+    for (i, task) in pad.tasks.iter().enumerate() {
+        let b = Node::button(format!("Queued: {}", task), OnClick::Nullopt, Size::Grow, h)
+            .enabled(false);
+        wrapper.add_child(delete_wrapper(OnClick::CancelServiceTask(i), b, h));
+    }
+
+    wrapper
+}
+
+/// Funds, insurance for the piloted vehicle, and lost vehicles which can
+/// be rebuilt, shown in career mode.
+pub fn career_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    if !state.settings.career_mode {
+        return wrapper;
+    }
+
+    let h = state.button_height();
+    let ledger = &state.ledger;
+
+    wrapper.add_child(
+        Node::row(h)
+            .with_text(format!("Funds {}", credits_str(ledger.funds())))
+            .enabled(false),
+    );
+
+    if let Some((id, sv)) = state
+        .piloting()
+        .and_then(|id| Some((id, state.universe.surface_vehicles.get(&id)?)))
+    {
+        let value = vehicle_value(sv.vehicle());
+        let n = if ledger.is_insured(id) {
+            let s = format!("Insured for {}", credits_str(value * INSURANCE_COVERAGE));
+            Node::row(h).with_text(s).enabled(false)
+        } else {
+            let premium = insurance_premium(value);
+            Node::button(
+                format!("Insure for {}", credits_str(premium)),
+                OnClick::InsureVehicle(id),
+                Size::Grow,
+                h,
+            )
+            .enabled(premium <= ledger.funds())
+        };
+        wrapper.add_child(n);
+    }
+
+    let can_rebuild = state.rebuild_site().is_some();
+    for (i, lost) in ledger.lost.iter().enumerate() {
+        wrapper.add_child(
+            Node::button(
+                format!(
+                    "Rebuild {} ({})",
+                    lost.design.title(),
+                    credits_str(lost.value)
+                ),
+                OnClick::RebuildLostVehicle(i),
+                Size::Grow,
+                h,
+            )
+            .enabled(can_rebuild && lost.value <= ledger.funds()),
+        );
+    }
+
+    wrapper
+}
+
+/// Most parts listed for repair at once in the landing site panel.
+const MAX_REPAIR_BUTTONS: usize = 4;
+
+/// Bar filled to the given fraction of its width.
+pub fn progress_bar(fraction: f64, width: f32, height: f32) -> Node<OnClick> {
+    let fill = width * fraction.clamp(0.0, 1.0) as f32;
+    Node::new(width, height)
+        .tight()
+        .with_color(UI_BACKGROUND_COLOR)
+        .with_child(Node::new(fill, height).with_color(PROGRESS_BAR_COLOR))
+}
+
+/// Agenda of upcoming alarms, soonest first. Clicking an alarm cycles what
+/// it does when it goes off; the X button deletes it.
+pub fn alarm_calendar(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    if state.alarms.is_empty() {
+        return wrapper;
+    }
+
+    wrapper.add_child(
+        Node::row(state.button_height())
+            .with_text(format!("Alarms ({})", state.alarms.len()))
+            .enabled(false),
+    );
+
+    let now = state.universe.stamp();
+    for (i, alarm) in state.alarms.iter().enumerate() {
+        let s = format!(
+            "T-{} {} [{}]",
+            duration_str(alarm.stamp - now),
+            alarm.name,
+            alarm.action
+        );
+        let b = Node::button(
+            s,
+            OnClick::CycleAlarmAction(i),
+            Size::Grow,
+            state.button_height(),
+        );
+        wrapper.add_child(delete_wrapper(
+            OnClick::DeleteAlarm(i),
+            b,
+            state.button_height(),
+        ));
+    }
+
+    wrapper
+}
+
+/// Every note on the mission timeline, with how long until or since its
+/// time and what its alarm will do.
+pub fn timeline_notes_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let h = state.button_height();
+    wrapper.add_child(Node::button(
+        format!("Add note ({})", state.timeline_notes.len()),
+        OnClick::AddTimelineNote,
+        Size::Grow,
+        h,
+    ));
+
+    let now = state.universe.stamp();
+    for (i, note) in state.timeline_notes.iter().enumerate() {
+        let when = if note.stamp >= now {
+            format!("T-{}", duration_str(note.stamp - now))
+        } else {
+            format!("T+{}", duration_str(now - note.stamp))
+        };
+        let alarm = match note.alarm {
+            Some(a) => a.to_string(),
+            None => "no alarm".to_string(),
+        };
+        let b = Node::button(
+            format!("{} {} [{}]", when, note.text, alarm),
+            OnClick::CycleTimelineNoteAlarm(i),
+            Size::Grow,
+            h,
+        );
+        wrapper.add_child(delete_wrapper(OnClick::DeleteTimelineNote(i), b, h));
+    }
+
+    wrapper
+}
+
+/// Every waypoint, with buttons to center the camera on it, target it or
+/// delete it.
+pub fn waypoint_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let h = state.button_height();
+    wrapper.add_child(Node::button(
+        "Add waypoint",
+        OnClick::AddWaypoint,
+        Size::Grow,
+        h,
+    ));
+
+    let stamp = state.universe.stamp();
+    let mut waypoints: Vec<_> = state.universe.waypoints.iter().collect();
+    waypoints.sort_by_key(|(id, _)| **id);
+    for (id, w) in waypoints {
+        let Some((body, _, _, sys)) = state.universe.planets.lookup(w.planet_id, stamp) else {
+            continue;
+        };
+        let where_ = if w.is_surface() {
+            format!("on {}", sys.name)
+        } else {
+            format!("{:0.0} km over {}", w.altitude(&body) / 1000.0, sys.name)
+        };
+        let button = Node::button(
+            format!("{} {}", w.name, where_),
+            OnClick::CenterCamera(*id),
+            Size::Grow,
+            h,
+        );
+        let target = Node::button("Tgt", OnClick::SetTarget(*id), h * 2.0, h)
+            .enabled(state.piloting().is_some());
+        wrapper.add_child(
+            Node::row(h)
+                .invisible()
+                .tight()
+                .with_child(delete_wrapper(OnClick::DeleteWaypoint(*id), button, h))
+                .with_child(target),
+        );
+    }
+
+    wrapper
+}
+
+/// Button which selects every vehicle matching a saved filter, labeled
+/// with how many there are right now.
+pub fn smart_list_button(state: &GameState, i: usize, width: impl Into<Size>) -> Node<OnClick> {
+    let filter = &state.saved_filters[i];
+    let h = state.button_height();
+    match VehicleQuery::parse(&filter.query) {
+        Ok(query) => Node::button(
+            format!("{} ({})", filter.name, state.find_vehicles(&query).len()),
+            OnClick::ApplySavedFilter(i),
+            width,
+            h,
+        ),
+        Err(_) => Node::button(
+            format!("{} (invalid)", filter.name),
+            OnClick::Nullopt,
+            width,
+            h,
+        )
+        .enabled(false),
+    }
+}
+
+/// Saved vehicle filters, which can be applied to the selection or pinned
+/// to the sidebar.
+pub fn smart_list_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let h = state.button_height();
+    if state.saved_filters.is_empty() {
+        wrapper.add_child(
+            Node::row(h)
+                .with_text("No saved filters; try \"find ... --save NAME\"")
+                .enabled(false),
+        );
+        return wrapper;
+    }
+
+    for (i, filter) in state.saved_filters.iter().enumerate() {
+        let button = smart_list_button(state, i, Size::Grow);
+        let pin = if filter.pinned { "Unpin" } else { "Pin" };
+        wrapper.add_child(
+            Node::row(h)
+                .invisible()
+                .tight()
+                .with_child(delete_wrapper(OnClick::DeleteSavedFilter(i), button, h))
+                .with_child(Node::button(
+                    pin,
+                    OnClick::TogglePinnedFilter(i),
+                    h * 2.5,
+                    h,
+                )),
+        );
+        wrapper.add_child(Node::row(h).with_text(filter.query.clone()).enabled(false));
+    }
+
+    wrapper
+}
+
+/// Ideal delta-v costs between the canonical orbits of the system, and
+/// what it would take the piloted vehicle to reach each of them.
+pub fn dv_map_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let h = state.button_height();
+    wrapper.add_child(Node::button(
+        "Delta-v map",
+        OnClick::ToggleDvMap,
+        Size::Grow,
+        h,
+    ));
+
+    if !state.orbital_context.show_dv_map {
+        return wrapper;
+    }
+
+    let now = state.universe.stamp();
+    let planets = &state.universe.planets;
+    let map = DvMap::new(planets);
+    let label = |node: DvNode| {
+        let name = planets
+            .lookup(node.body(), now)
+            .map(|(_, _, _, sys)| sys.name.clone())
+            .unwrap_or_else(|| format!("{}", node.body()));
+        match node {
+            DvNode::Surface(_) => format!("{} surface", name),
+            DvNode::LowOrbit(id) => {
+                let km = map
+                    .low_orbit(id)
+                    .zip(planets.lookup(id, now))
+                    .map(|(o, (body, ..))| (o.periapsis_r() - body.radius) / 1000.0)
+                    .unwrap_or(0.0);
+                format!("{} {:0.0} km orbit", name, km)
+            }
+            DvNode::Transfer(_) => format!("{} transfer", name),
+        }
+    };
+
+    for leg in map.legs() {
+        let s = format!(
+            "{} - {}: {:0.0} m/s",
+            label(leg.from),
+            label(leg.to),
+            leg.dv
+        );
+        wrapper.add_child(Node::row(h).with_text(s).enabled(false));
+    }
+
+    let Some(sv) = state
+        .piloting()
+        .and_then(|id| state.universe.surface_vehicles.get(&id))
+    else {
+        return wrapper;
+    };
+
+    let remaining = sv.vehicle().remaining_dv();
+    let accel = sv.vehicle().accel();
+    wrapper.add_child(
+        Node::row(h)
+            .with_text(format!("{} has {:0.0} m/s", sv.vehicle().name(), remaining))
+            .enabled(false),
+    );
+
+    // planning onto the low orbit once, and going by the map from there
+    let start = if sv.is_landed() {
+        Some((DvNode::Surface(sv.parent()), 0.0))
+    } else {
+        sv.current_orbit().and_then(|GlobalOrbit(parent, orbit)| {
+            let low = DvNode::LowOrbit(parent);
+            Some((low, map.cost_from_orbit(&orbit, parent, low, now)?))
+        })
+    };
+    let Some((start, to_start)) = start else {
+        return wrapper;
+    };
+
+    let mut nodes: Vec<DvNode> = map.legs().iter().flat_map(|l| [l.from, l.to]).collect();
+    nodes.sort();
+    nodes.dedup();
+    for node in nodes {
+        let Some(cost) = map.cost(start, node).map(|c| c + to_start) else {
+            continue;
+        };
+        let burn = if accel > 0.0 {
+            format!(", {} burn", duration_str(Nanotime::secs_f64(cost / accel)))
+        } else {
+            String::new()
+        };
+        let s = format!("To {}: {:0.0} m/s{}", label(node), cost, burn);
+        wrapper.add_child(Node::row(h).with_text(s).enabled(cost <= remaining));
+    }
+
+    wrapper
+}
+
+/// Switch for spectator mode and its camera director.
+pub fn spectator_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let h = state.button_height();
+    let spectator = &state.spectator;
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    wrapper.add_child(Node::button(
+        format!("Spectator mode: {}", on_off(spectator.is_active())),
+        OnClick::ToggleSpectator,
+        Size::Grow,
+        h,
+    ));
+    if let Some(status) = state.network.status() {
+        wrapper.add_child(Node::row(h).with_text(status).enabled(false));
+    }
+
+    if !spectator.is_active() {
+        return wrapper;
+    }
+
+    wrapper.add_child(Node::button(
+        format!("Auto director: {}", on_off(spectator.is_directing())),
+        OnClick::ToggleDirector,
+        Size::Grow,
+        h,
+    ));
+    if let Some(id) = state.orbital_context.following {
+        wrapper.add_child(
+            Node::row(h)
+                .with_text(format!("Watching {}", state.callsign(id)))
+                .enabled(false),
+        );
+    }
+
+    wrapper
+}
+
+/// Holders of the vehicle lifetime records, such as the oldest satellite
+/// still flying.
+pub fn records_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let h = state.button_height();
+    wrapper.add_child(Node::button(
+        "Records",
+        OnClick::ToggleRecords,
+        Size::Grow,
+        h,
+    ));
+
+    if !state.orbital_context.show_records {
+        return wrapper;
+    }
+
+    let now = state.universe.stamp();
+    let site_name = |id: EntityId| {
+        if let Some(pad) = state.universe.landing_pads.get(&id) {
+            pad.name.clone()
+        } else if let Some((_, _, _, sys)) = state.universe.planets.lookup(id, now) {
+            sys.name.clone()
+        } else {
+            id.to_string()
+        }
+    };
+
+    let board = state.records.leaderboard(now, site_name);
+    if board.is_empty() {
+        wrapper.add_child(Node::row(h).with_text("No records yet").enabled(false));
+    }
+    for r in board {
+        wrapper.add_child(
+            Node::row(h)
+                .with_text(format!("{}: {} ({})", r.title, r.holder, r.value))
+                .enabled(false),
+        );
+    }
+
+    wrapper
+}
+
+/// Every group of vehicles, or the members, fuel and controls of the one
+/// being inspected.
+pub fn group_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let groups = state.universe.unique_groups();
+    if groups.is_empty() {
+        return wrapper;
+    }
+
+    let h = state.button_height();
+    let group_button = |gid: EntityId| {
+        let color: Srgba = state.group_color(gid).with_luminance(0.3).into();
+        Node::button(
+            state.group_name(gid),
+            OnClick::InspectGroup(gid),
+            Size::Grow,
+            h,
+        )
+        .with_color(color.to_f32_array())
+    };
+
+    let Some(gid) = state
+        .orbital_context
+        .inspected_group
+        .filter(|g| groups.contains(g))
+    else {
+        wrapper.add_child(
+            Node::row(h)
+                .with_text(format!("Groups ({})", groups.len()))
+                .enabled(false),
+        );
+        for gid in groups {
+            wrapper.add_child(group_button(gid));
+        }
+        return wrapper;
+    };
+
+    wrapper.add_child(delete_wrapper(
+        OnClick::DisbandGroup(gid),
+        group_button(gid),
+        h,
+    ));
+
+    let stats = GroupStats::new(&state.universe, gid);
+    for s in [
+        format!("{} members", stats.members),
+        format!(
+            "Fuel {} ({:0.0}% avg)",
+            stats.fuel_mass,
+            stats.mean_fuel * 100.0
+        ),
+        format!("Least dV {:0.0} m/s", stats.min_dv.unwrap_or(0.0)),
+    ] {
+        wrapper.add_child(Node::row(h).with_text(s).enabled(false));
+    }
+
+    let current = state.group_style(gid).color;
+    let mut swatches = Node::row(h).invisible().tight();
+    for (i, color) in state
+        .settings
+        .color_palette
+        .swatches()
+        .into_iter()
+        .enumerate()
+    {
+        let color: Srgba = color.with_luminance(0.3).into();
+        swatches.add_child(
+            Node::button("", OnClick::SetGroupColor(gid, i), Size::Grow, h)
+                .with_color(color.to_f32_array())
+                .enabled(current != Some(i)),
+        );
+    }
+    wrapper.add_child(swatches);
+
+    wrapper.add_child(Node::button(
+        "Rename",
+        OnClick::RenameGroup(gid),
+        Size::Grow,
+        h,
+    ));
+    wrapper.add_child(Node::button(
+        "Select members",
+        OnClick::Group(gid),
+        Size::Grow,
+        h,
+    ));
+    let can_split = state
+        .orbital_context
+        .selected
+        .iter()
+        .any(|id| state.universe.group_membership(id) == Some(gid));
+    wrapper.add_child(
+        Node::button(
+            "Split off selected",
+            OnClick::SplitGroup(gid),
+            Size::Grow,
+            h,
+        )
+        .enabled(can_split),
+    );
+    let can_move = state
+        .orbital_context
+        .selected
+        .iter()
+        .any(|id| state.universe.group_membership(id) != Some(gid));
+    wrapper.add_child(
+        Node::button(
+            "Move selected here",
+            OnClick::HandOverSelected(HandoverTarget::Group(gid)),
+            Size::Grow,
+            h,
+        )
+        .enabled(can_move),
+    );
+    for other in groups.into_iter().filter(|g| *g != gid) {
+        wrapper.add_child(Node::button(
+            format!("Merge in {}", state.group_name(other)),
+            OnClick::MergeGroups(gid, other),
+            Size::Grow,
+            h,
+        ));
+    }
+
+    wrapper
+}
+
+/// Orbits the piloted or followed vehicle has been on, and a control to
+/// scrub back through them.
+pub fn orbit_history_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let Some((id, history)) = state.orbit_history_subject() else {
+        return wrapper;
+    };
+
+    let h = state.button_height();
+    let ctx = &state.orbital_context;
+    wrapper.add_child(Node::button(
+        format!(
+            "Orbit history of {} ({})",
+            state.callsign(id),
+            history.len()
+        ),
+        OnClick::ToggleOrbitHistory,
+        Size::Grow,
+        h,
+    ));
+
+    if !ctx.show_orbit_history {
+        return wrapper;
+    }
+
+    let now = state.universe.stamp();
+    for (i, record) in history.records().enumerate() {
+        let GlobalOrbit(parent, orbit) = record.orbit;
+        let Some((body, _, _, sys)) = state.universe.planets.lookup(parent, now) else {
+            continue;
+        };
+        let km = |r: f64| (r - body.radius) / 1000.0;
+        let apoapsis = if orbit.is_hyperbolic() {
+            "esc".to_string()
+        } else {
+            format!("{:0.0}", km(orbit.apoapsis_r()))
+        };
+        let s = format!(
+            "{} {:0.0}x{} km {}",
+            record.start.to_date(),
+            km(orbit.periapsis_r()),
+            apoapsis,
+            sys.name
+        );
+        let selected = ctx.history_scrub.is_some_and(|t| record.contains(t));
+        wrapper.add_child(
+            Node::button(s, OnClick::ScrubToOrbitRecord(i), Size::Grow, h).enabled(!selected),
+        );
+        let end = record.end.unwrap_or(now);
+        for note in state.timeline_notes.between(record.start, end) {
+            let s = format!("{} {}", note.stamp.to_date(), note.text);
+            wrapper.add_child(Node::row(h).with_text(s).enabled(false));
+        }
+    }
+
+    let scrub = match ctx.history_scrub {
+        Some(t) => t.to_date().to_string(),
+        None => "Now".to_string(),
+    };
+    wrapper.add_child(
+        Node::row(h)
+            .invisible()
+            .tight()
+            .with_child(Node::button("<<", OnClick::ScrubOrbitHistory(-10), h, h))
+            .with_child(Node::button("<", OnClick::ScrubOrbitHistory(-1), h, h))
+            .with_child(Node::row(h).with_text(scrub).enabled(false))
+            .with_child(Node::button(">", OnClick::ScrubOrbitHistory(1), h, h))
+            .with_child(Node::button(">>", OnClick::ScrubOrbitHistory(10), h, h)),
+    );
+    if ctx.history_scrub.is_some() {
+        wrapper.add_child(Node::button(
+            "Clear",
+            OnClick::ClearOrbitScrub,
+            Size::Grow,
+            h,
+        ));
+    }
+
+    wrapper
+}
+
+/// Countdown to the piloted vehicle hitting a body, with a button to plan
+/// a burn that avoids it.
+pub fn collision_warning_panel(state: &GameState, width: Size) -> Node<OnClick> {
+    let wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let Some(impact) = state.collision_warning else {
+        return wrapper;
+    };
+
+    let h = state.button_height();
+    let remaining = impact - state.universe.stamp();
+    wrapper
+        .with_child(
+            Node::row(h)
+                .with_text(format!("IMPACT IN {}", duration_str(remaining)))
+                .with_color([0.6, 0.05, 0.05, 0.9]),
+        )
+        .with_child(Node::button(
+            "Suggest avoidance burn",
+            OnClick::SuggestAvoidanceBurn,
+            Size::Grow,
+            h,
+        ))
+}
+
+/// Progress of vehicles still being streamed into the universe, and of
+/// vehicle files being imported. Empty once loading is complete.
+pub fn loading_indicator(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    if let Some(import) = &state.vehicle_import {
+        let (checked, total) = import.progress();
+        wrapper.add_child(
+            Node::row(state.button_height())
+                .with_text(format!("Importing vehicles {}/{}", checked, total))
+                .enabled(false),
+        );
+    }
+
+    let (loaded, total) = state.universe.loading.progress();
+    if total == 0 {
+        return wrapper;
+    }
+
+    wrapper.with_child(
+        Node::row(state.button_height())
+            .with_text(format!(
+                "Loading vehicles {}/{} ({}%)",
+                loaded,
+                total,
+                100 * loaded / total
+            ))
+            .enabled(false),
+    )
+}
+
+/// List of pinned measurement annotations. Clicking an entry toggles its
+/// visibility; the X button deletes it. The box beside each entry picks it
+/// out for the bulk actions shown above the list, and shift-clicking picks
+/// out a range.
+pub fn annotation_buttons(state: &GameState, width: Size) -> Node<OnClick> {
+    let mut wrapper = Node::new(width, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+
+    let h = state.button_height();
+    let selection = &state.pinned_selection;
+    if !selection.is_empty() {
+        let vehicles = !state.pinned_vehicles().is_empty();
+        wrapper.add_child(
+            Node::row(h)
+                .invisible()
+                .tight()
+                .with_child(Node::button(
+                    format!("Unpin {}", selection.len()),
+                    OnClick::UnpinSelected,
+                    Size::Grow,
+                    h,
+                ))
+                .with_child(Node::button(
+                    "Show/Hide",
+                    OnClick::TogglePinnedVisibility,
+                    Size::Grow,
+                    h,
+                )),
+        );
+        wrapper.add_child(
+            Node::row(h)
+                .invisible()
+                .tight()
+                .with_child(
+                    Node::button(
+                        "Select Vehicles",
+                        OnClick::SelectPinnedVehicles,
+                        Size::Grow,
+                        h,
+                    )
+                    .enabled(vehicles),
+                )
+                .with_child(
+                    Node::button("Group", OnClick::GroupPinnedVehicles, Size::Grow, h)
+                        .enabled(vehicles),
+                )
+                .with_child(Node::button("X", OnClick::ClearPinnedSelection, h, h)),
+        );
+    }
+
+    for (i, a) in state.annotations.iter().enumerate() {
+        let s = format!(
+            "{} {}{}",
+            a.kind,
+            a.value_str(&state.universe),
+            if a.visible { "" } else { " (hidden)" }
+        );
+        let check = if selection.contains(i) { "+" } else { "" };
+        let b = Node::button(s, OnClick::ToggleAnnotation(i), Size::Grow, h);
+        wrapper.add_child(
+            Node::row(h)
+                .invisible()
+                .tight()
+                .with_child(Node::button(check, OnClick::SelectPinned(i), h, h))
+                .with_child(delete_wrapper(OnClick::DeleteAnnotation(i), b, h)),
+        );
+    }
+
+    wrapper
+}
+
+/// Picture of a vehicle next to its name and class, to tell it apart from
+/// others with similar names. Uses the same generated sprite as is drawn
+/// in the orbital view.
+pub fn vehicle_card(state: &GameState, id: EntityId, width: Size) -> Option<Node<OnClick>> {
+    let vehicle = state.universe.surface_vehicles.get(&id)?.vehicle();
+    let h = state.button_height();
+    let thumbnail = Node::button("", OnClick::CenterCamera(id), h * 3.0, h * 3.0)
+        .with_sprite(vehicle_sprite_path(vehicle.discriminator()));
+    let mut info = Node::new(Size::Grow, Size::Fit)
+        .down()
+        .invisible()
+        .with_padding(0.0);
+    for s in [
+        state.callsign(id),
+        format!("{} class", vehicle.model()),
+        format!(
+            "{} parts, {}",
+            vehicle.parts().count(),
+            vehicle.total_mass()
+        ),
+    ] {
+        info.add_child(Node::row(h).with_text(s).enabled(false));
+    }
+    Some(
+        Node::new(width, Size::Fit)
+            .invisible()
+            .tight()
+            .with_child(thumbnail)
+            .with_child(info),
+    )
+}
+
+/// How the vehicle is lined up for its next scheduled burn, and whether
+/// burn attitudes are held automatically.
+pub fn burn_alignment_buttons(
+    state: &GameState,
+    id: EntityId,
+    width: Size,
+) -> Option<Node<OnClick>> {
+    let alignment = state.universe.burn_alignment(id)?;
+    let h = state.button_height();
+    let until = alignment.burn_at - state.universe.stamp();
+    let s = format!(
+        "Burn in {}: {:0.1} deg off",
+        duration_str(until),
+        alignment.error().to_degrees().abs()
+    );
+    let hold = if state.settings.burn_attitude_hold {
+        "Auto-hold: on"
+    } else {
+        "Auto-hold: off"
+    };
+    Some(
+        Node::new(width, Size::Fit)
+            .down()
+            .invisible()
+            .with_padding(0.0)
+            .with_child(Node::row(h).with_text(s).enabled(alignment.is_aligned()))
+            .with_child(Node::button(
+                hold,
+                OnClick::ToggleBurnAutoHold,
+                Size::Grow,
+                h,
+            )),
+    )
+}
+
+/// Stages left on a multi-stage vehicle, and a button to jettison the
+/// active one.
+pub fn staging_buttons(state: &GameState, id: EntityId, width: Size) -> Option<Node<OnClick>> {
+    let vehicle = &state.universe.surface_vehicles.get(&id)?.vehicle;
+    if vehicle.stage_count() < 2 {
+        return None;
+    }
+    let h = state.button_height();
+    let info = vehicle.stage_info();
+    let active = info.first()?;
+    let s = format!(
+        "Stage {} of {}: {:0.0} m/s",
+        active.stage,
+        info.len(),
+        active.dv
+    );
+    Some(
+        Node::new(width, Size::Fit)
+            .down()
+            .invisible()
+            .with_padding(0.0)
+            .with_child(Node::row(h).with_text(s).enabled(false))
+            .with_child(
+                Node::button("Activate Stage", OnClick::ActivateStage(id), Size::Grow, h)
+                    .enabled(vehicle.can_jettison().is_ok()),
+            ),
+    )
+}
+
+pub fn selected_button(state: &GameState, width: Size) -> Node<OnClick> {
+    let s = format!("{} selected", state.orbital_context.selected.len());
+    let b = Node::button(s, OnClick::SelectedCount, width, state.button_height()).enabled(false);
+    if state.orbital_context.selected.is_empty() {
+        b
+    } else {
+        delete_wrapper(OnClick::ClearTracks, b, state.button_height())
+    }
+}
+
+pub fn orbiter_list(
+    state: &GameState,
+    root: &mut Node<OnClick>,
+    max_cells: usize,
+    mut ids: Vec<EntityId>,
+) {
+    ids.sort();
+
+    let rows = (ids.len().min(max_cells) as f32 / 4.0).ceil() as u32;
+    let grid = Node::grid(
+        Size::Grow,
+        rows * state.button_height() as u32,
+        rows,
+        4,
+        4.0,
+        |i| {
+            if i as usize > max_cells {
+                return None;
+            }
+            let id = ids.get(i as usize)?;
+            let s = format!("{id}");
+            Some(
+                Node::grow()
+                    .with_on_click(OnClick::Orbiter(*id))
+                    .with_text(s)
+                    .enabled(Some(*id) != state.orbital_context.following),
+            )
+        },
+    );
+    root.add_child(grid);
+
+    if ids.len() > max_cells {
+        let n = ids.len() - max_cells;
+        let s = format!("...And {} more", n);
+        root.add_child(
+            Node::new(Size::Grow, state.button_height())
+                .with_text(s)
+                .enabled(false),
+        );
+    }
+}
+
+pub fn left_right_arrows(
+    width: impl Into<Size>,
+    height: impl Into<Size>,
+    left: OnClick,
+    right: OnClick,
+) -> Node<OnClick> {
+    let height = height.into();
+    let left = Node::button("-", left, Size::Grow, height);
+    let right = Node::button("+", right, Size::Grow, height);
+    Node::new(width, height)
+        .with_padding(0.0)
+        .invisible()
+        .with_child(left)
+        .with_child(right)
+}
+
+pub fn layout(state: &GameState) -> Tree<OnClick> {
+    match state.scene {
+        SceneType::MainMenu => MainMenuContext::ui(state),
+        SceneType::Stats => StatsContext::ui(state),
+        SceneType::Telescope => TelescopeContext::ui(state),
+        SceneType::Orbital => OrbitalContext::ui(state),
+        SceneType::Editor => EditorContext::ui(state),
+        SceneType::Scenario => ScenarioEditorContext::ui(state),
+    }
+    .unwrap_or(Tree::new())
+}
+
+#[derive(Component)]
+pub struct UiElement;
+
+fn map_bytes(image: &mut Image, func: impl Fn(&mut [u8], u32, u32, u32, u32)) {
+    let w = image.width();
+    let h = image.height();
+    for x in 0..w {
+        for y in 0..h {
+            if let Some(bytes) = image.pixel_bytes_mut(UVec3::new(x, y, 0)) {
+                func(bytes, x, y, w, h);
+            }
+        }
+    }
+}
+
+fn generate_button_sprite(
+    node: &Node<OnClick>,
+    is_clicked: bool,
+    is_hover: bool,
+) -> (Image, f32, f32) {
     let aabb = node.aabb();
     let w = (aabb.span.x as u32).max(1);
     let h = (aabb.span.y as u32).max(1);
@@ -586,20 +2481,43 @@ fn do_ui_sprites(
         return;
     }
 
+    let span = ProfileSpan::start(ProfileStage::UiBuild);
+
     let mut ui = layout(&state);
 
+    if state.profiler.show {
+        let x = (vb.span.x - PROFILER_OVERLAY_WIDTH) / 2.0;
+        ui.add_layout(profiler_overlay(&state), Vec2::new(x, 0.0))
+    }
+
     if state.console.is_active() {
         ui.add_layout(console_overlay(&state), Vec2::ZERO)
     }
 
+    if state.show_help {
+        ui.add_layout(help_overlay(&state), Vec2::ZERO)
+    }
+
+    if state.pause_menu.is_some() {
+        ui.add_layout(pause_menu_overlay(&state, vb.span.x, vb.span.y), Vec2::ZERO)
+    }
+
+    if let Some(handover) = &state.pending_handover {
+        ui.add_layout(
+            handover_prompt_overlay(&state, handover, vb.span.x, vb.span.y),
+            Vec2::ZERO,
+        )
+    }
+
     if state.is_exit_prompt {
         ui.add_layout(
-            exit_prompt_overlay(state.settings.ui_button_height, vb.span.x, vb.span.y),
+            exit_prompt_overlay(state.button_height(), vb.span.x, vb.span.y),
             Vec2::ZERO,
         )
     }
 
     state.ui = ui;
+    state.profiler.finish(span);
 
     for (lid, layout) in state.ui.layouts().iter().enumerate() {
         for n in layout.iter() {
@@ -0,0 +1,301 @@
+use starling::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// File extensions which are considered when scanning for vehicles.
+const VEHICLE_EXTENSIONS: [&str; 3] = ["vehicle", "yaml", "yml"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// Copied into the vehicle directory, or would be on a dry run.
+    Accepted(PathBuf),
+    /// Same content as a vehicle which is already installed or was earlier
+    /// in the same import.
+    Duplicate(PathBuf),
+    /// A different vehicle already has this file name.
+    NameConflict(PathBuf),
+    Invalid(String),
+}
+
+impl std::fmt::Display for ImportOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Accepted(p) => write!(f, "imported as {}", p.display()),
+            Self::Duplicate(p) => write!(f, "duplicate of {}", p.display()),
+            Self::NameConflict(p) => write!(f, "conflicts with {}", p.display()),
+            Self::Invalid(e) => write!(f, "invalid: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportEntry {
+    pub source: PathBuf,
+    pub outcome: ImportOutcome,
+}
+
+/// Hash of a vehicle's layout and staging, ignoring how its file happens
+/// to be formatted and the order in which parts and pipes are listed.
+pub fn vehicle_content_hash(storage: &VehicleFileStorage) -> u64 {
+    let mut parts: Vec<_> = storage
+        .parts
+        .iter()
+        .map(|p| (&p.partname, p.pos.to_array(), p.rot, p.stage))
+        .collect();
+    parts.sort();
+    let mut lines: Vec<_> = storage.lines.iter().map(|p| p.to_array()).collect();
+    lines.sort();
+
+    let mut s = std::hash::DefaultHasher::new();
+    storage.name.hash(&mut s);
+    parts.hash(&mut s);
+    lines.hash(&mut s);
+    s.finish()
+}
+
+fn read_storage(path: &Path) -> Result<VehicleFileStorage, String> {
+    let s = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_yaml::from_str(&s).map_err(|e| e.to_string())
+}
+
+/// Every file under `dir`, at any depth, which looks like it might be a
+/// vehicle, in a stable order.
+pub fn find_vehicle_files(dir: &Path) -> Vec<PathBuf> {
+    let mut ret = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|e| VEHICLE_EXTENSIONS.iter().any(|v| e == *v))
+            {
+                ret.push(path);
+            }
+        }
+    }
+    ret.sort();
+    ret
+}
+
+/// Copies vehicle files from a directory tree, such as a downloaded ship
+/// pack, into the vehicle directory. Files are checked against the part
+/// database and against installed vehicles one at a time, so that large
+/// imports can be spread over several frames.
+#[derive(Debug, Clone)]
+pub struct VehicleImport {
+    source: PathBuf,
+    dest: PathBuf,
+    dry_run: bool,
+    pending: VecDeque<PathBuf>,
+    total: usize,
+    known: HashMap<u64, PathBuf>,
+    entries: Vec<ImportEntry>,
+}
+
+impl VehicleImport {
+    pub fn new(source: &Path, dest: &Path, dry_run: bool) -> Result<Self, String> {
+        if !source.is_dir() {
+            return Err(format!("{} is not a directory", source.display()));
+        }
+        let pending: VecDeque<_> = find_vehicle_files(source).into();
+        let known = find_vehicle_files(dest)
+            .into_iter()
+            .filter_map(|p| Some((vehicle_content_hash(&read_storage(&p).ok()?), p)))
+            .collect();
+        Ok(Self {
+            source: source.to_path_buf(),
+            dest: dest.to_path_buf(),
+            dry_run,
+            total: pending.len(),
+            pending,
+            known,
+            entries: Vec::new(),
+        })
+    }
+
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Number of files checked and total number found.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.total - self.pending.len(), self.total)
+    }
+
+    pub fn entries(&self) -> &[ImportEntry] {
+        &self.entries
+    }
+
+    /// Checks and copies the next file, returning what became of it.
+    pub fn step(&mut self, parts: &HashMap<String, PartPrototype>) -> Option<&ImportEntry> {
+        let source = self.pending.pop_front()?;
+        let outcome = self.import(&source, parts);
+        self.entries.push(ImportEntry { source, outcome });
+        self.entries.last()
+    }
+
+    fn import(&mut self, source: &Path, parts: &HashMap<String, PartPrototype>) -> ImportOutcome {
+        let storage = match read_storage(source) {
+            Ok(s) => s,
+            Err(e) => return ImportOutcome::Invalid(e),
+        };
+        if storage.parts.is_empty() {
+            return ImportOutcome::Invalid("vehicle has no parts".to_string());
+        }
+        if let Some(p) = storage
+            .parts
+            .iter()
+            .find(|p| !parts.contains_key(&p.partname))
+        {
+            return ImportOutcome::Invalid(NoPartError(p.partname.clone()).to_string());
+        }
+
+        let hash = vehicle_content_hash(&storage);
+        if let Some(existing) = self.known.get(&hash) {
+            return ImportOutcome::Duplicate(existing.clone());
+        }
+
+        let Some(stem) = source.file_stem() else {
+            return ImportOutcome::Invalid("no file name".to_string());
+        };
+        let dest = self.dest.join(stem).with_extension("vehicle");
+        if dest.exists() || self.known.values().any(|p| *p == dest) {
+            return ImportOutcome::NameConflict(dest);
+        }
+
+        if !self.dry_run {
+            if let Err(e) = std::fs::copy(source, &dest) {
+                return ImportOutcome::Invalid(format!("failed to copy: {}", e));
+            }
+        }
+        self.known.insert(hash, dest.clone());
+        ImportOutcome::Accepted(dest)
+    }
+
+    pub fn summary(&self) -> String {
+        let count =
+            |f: fn(&ImportOutcome) -> bool| self.entries.iter().filter(|e| f(&e.outcome)).count();
+        format!(
+            "{} {} vehicles from {}, {} duplicates, {} conflicts, {} invalid",
+            if self.dry_run {
+                "Would import"
+            } else {
+                "Imported"
+            },
+            count(|o| matches!(o, ImportOutcome::Accepted(_))),
+            self.source.display(),
+            count(|o| matches!(o, ImportOutcome::Duplicate(_))),
+            count(|o| matches!(o, ImportOutcome::NameConflict(_))),
+            count(|o| matches!(o, ImportOutcome::Invalid(_))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_are_validated_and_deduplicated() {
+        let root = std::env::temp_dir().join(format!("vehicle-import-{}", std::process::id()));
+        let (source, dest) = (root.join("pack"), root.join("vehicles"));
+        std::fs::create_dir_all(source.join("nested")).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+
+        let frame = PartPrototype::Generic(Generic::new(
+            "frame".to_string(),
+            UVec2::new(10, 10),
+            PartLayer::Structural,
+            Mass::kilograms(90),
+        ));
+        let parts = HashMap::from([("frame".to_string(), frame)]);
+        let vehicle = |name: &str, part: &str, x: i32| {
+            format!(
+                "name: '{}'\nparts:\n- partname: {}\n  pos: [{}, 0]\n  rot: East\nlines: []\n",
+                name, part, x
+            )
+        };
+
+        std::fs::write(dest.join("existing.vehicle"), vehicle("Old", "frame", 0)).unwrap();
+        std::fs::write(source.join("a.vehicle"), vehicle("A", "frame", 0)).unwrap();
+        // same content as a.vehicle, formatted differently
+        std::fs::write(
+            source.join("nested/b.yaml"),
+            "name: A\nlines: []\nparts: [{partname: frame, pos: [0, 0], rot: East}]\n",
+        )
+        .unwrap();
+        std::fs::write(source.join("nested/copy.yml"), vehicle("Old", "frame", 0)).unwrap();
+        std::fs::write(source.join("existing.vehicle"), vehicle("New", "frame", 10)).unwrap();
+        std::fs::write(source.join("odd.vehicle"), vehicle("Odd", "wing", 0)).unwrap();
+        std::fs::write(source.join("notes.txt"), "not a vehicle").unwrap();
+
+        let mut import = VehicleImport::new(&source, &dest, false).unwrap();
+        assert_eq!(import.progress(), (0, 5));
+        while import.step(&parts).is_some() {}
+        assert!(import.is_done());
+
+        let outcome = |name: &str| {
+            import
+                .entries()
+                .iter()
+                .find(|e| e.source.ends_with(name))
+                .map(|e| e.outcome.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            outcome("a.vehicle"),
+            ImportOutcome::Accepted(dest.join("a.vehicle"))
+        );
+        assert_eq!(
+            outcome("b.yaml"),
+            ImportOutcome::Duplicate(dest.join("a.vehicle"))
+        );
+        assert_eq!(
+            outcome("copy.yml"),
+            ImportOutcome::Duplicate(dest.join("existing.vehicle"))
+        );
+        assert_eq!(
+            outcome("existing.vehicle"),
+            ImportOutcome::NameConflict(dest.join("existing.vehicle"))
+        );
+        assert!(matches!(outcome("odd.vehicle"), ImportOutcome::Invalid(_)));
+        assert_eq!(find_vehicle_files(&dest).len(), 2);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn vehicles_staged_differently_are_not_duplicates() {
+        let part = |x: i32, stage: usize| VehiclePartFileStorage {
+            partname: "frame".to_string(),
+            pos: IVec2::new(x, 0),
+            rot: Rotation::East,
+            stage,
+        };
+        let mut storage = VehicleFileStorage {
+            name: "Stacked".to_string(),
+            parts: vec![part(0, 0), part(10, 1)],
+            lines: Default::default(),
+            loadouts: Vec::new(),
+        };
+        let before = vehicle_content_hash(&storage);
+        storage.parts.reverse();
+        assert_eq!(vehicle_content_hash(&storage), before);
+        storage.parts = vec![part(0, 1), part(10, 0)];
+        assert_ne!(vehicle_content_hash(&storage), before);
+    }
+}
@@ -0,0 +1,460 @@
+use serde::{Deserialize, Serialize};
+use starling::prelude::*;
+use std::error::Error;
+use std::path::Path;
+
+/// What sort of value a field holds, which decides the comparisons and
+/// values it accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Number,
+    /// A number from 0 to 100, written with or without a percent sign.
+    Percent,
+    Text,
+    Flag,
+}
+
+/// Every field a filter can test, with its kind and a description.
+pub const QUERY_FIELDS: &[(&str, FieldKind, &str)] = &[
+    ("id", FieldKind::Number, "entity ID, without its kind"),
+    ("name", FieldKind::Text, "vehicle name"),
+    ("model", FieldKind::Text, "vehicle design"),
+    ("parent", FieldKind::Text, "body the vehicle is orbiting"),
+    ("group", FieldKind::Text, "name of the vehicle's group"),
+    ("controller", FieldKind::Text, "autopilot mode, e.g. idle"),
+    ("target", FieldKind::Text, "name of the targeted vehicle"),
+    ("fuel", FieldKind::Percent, "fuel remaining, of capacity"),
+    ("dv", FieldKind::Number, "delta-v remaining, m/s"),
+    ("mass", FieldKind::Number, "total mass, tonnes"),
+    ("altitude", FieldKind::Number, "height above the parent, km"),
+    ("periapsis", FieldKind::Number, "periapsis altitude, km"),
+    ("apoapsis", FieldKind::Number, "apoapsis altitude, km"),
+    ("ecc", FieldKind::Number, "orbital eccentricity"),
+    ("landed", FieldKind::Flag, "resting on the surface"),
+    ("thrusting", FieldKind::Flag, "engines firing"),
+    ("derelict", FieldKind::Flag, "abandoned by the fleet"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    /// Text contains the value, ignoring case.
+    Contains,
+}
+
+impl QueryOp {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "<" => Self::Lt,
+            "<=" => Self::Le,
+            ">" => Self::Gt,
+            ">=" => Self::Ge,
+            "=" | "==" => Self::Eq,
+            "!=" => Self::Ne,
+            "~" => Self::Contains,
+            _ => return None,
+        })
+    }
+
+    fn allowed_for(&self, kind: FieldKind) -> bool {
+        match kind {
+            FieldKind::Number | FieldKind::Percent => *self != Self::Contains,
+            FieldKind::Text => matches!(self, Self::Eq | Self::Ne | Self::Contains),
+            FieldKind::Flag => matches!(self, Self::Eq | Self::Ne),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryValue {
+    Number(f64),
+    Text(String),
+    Flag(bool),
+}
+
+/// One comparison, like `fuel < 20%`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryClause {
+    pub field: &'static str,
+    pub op: QueryOp,
+    pub value: QueryValue,
+}
+
+impl QueryClause {
+    fn matches(&self, facts: &VehicleFacts) -> bool {
+        let Some(actual) = facts.get(self.field) else {
+            return self.op == QueryOp::Ne;
+        };
+        match (&actual, &self.value) {
+            (QueryValue::Number(a), QueryValue::Number(b)) => match self.op {
+                QueryOp::Lt => a < b,
+                QueryOp::Le => a <= b,
+                QueryOp::Gt => a > b,
+                QueryOp::Ge => a >= b,
+                QueryOp::Eq => a == b,
+                QueryOp::Ne => a != b,
+                QueryOp::Contains => false,
+            },
+            (QueryValue::Text(a), QueryValue::Text(b)) => {
+                let (a, b) = (a.to_lowercase(), b.to_lowercase());
+                match self.op {
+                    QueryOp::Eq => a == b,
+                    QueryOp::Ne => a != b,
+                    QueryOp::Contains => a.contains(&b),
+                    _ => false,
+                }
+            }
+            (QueryValue::Flag(a), QueryValue::Flag(b)) => match self.op {
+                QueryOp::Eq => a == b,
+                QueryOp::Ne => a != b,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(String),
+    And,
+    Or,
+}
+
+fn is_op_char(c: char) -> bool {
+    matches!(c, '<' | '>' | '=' | '!' | '~')
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut ret = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some(q) if q == c => break,
+                    Some(x) => text.push(x),
+                    None => return Err(format!("Unclosed quote in \"{}\"", s)),
+                }
+            }
+            ret.push(Token::Quoted(text));
+        } else if c == '&' || c == '|' {
+            chars.next();
+            if chars.next() != Some(c) {
+                return Err(format!("Expected \"{}{}\"", c, c));
+            }
+            ret.push(if c == '&' { Token::And } else { Token::Or });
+        } else if is_op_char(c) {
+            let mut op = String::new();
+            while let Some(&x) = chars.peek().filter(|x| is_op_char(**x)) {
+                op.push(x);
+                chars.next();
+            }
+            ret.push(Token::Op(op));
+        } else {
+            let mut word = String::new();
+            while let Some(&x) = chars
+                .peek()
+                .filter(|x| !x.is_whitespace() && !is_op_char(**x) && !"\"'&|".contains(**x))
+            {
+                word.push(x);
+                chars.next();
+            }
+            ret.push(match word.to_lowercase().as_str() {
+                "and" => Token::And,
+                "or" => Token::Or,
+                _ => Token::Word(word),
+            });
+        }
+    }
+    Ok(ret)
+}
+
+fn parse_value(field: &str, kind: FieldKind, token: Option<Token>) -> Result<QueryValue, String> {
+    let text = match token {
+        Some(Token::Word(s)) | Some(Token::Quoted(s)) => s,
+        _ => return Err(format!("Expected a value to compare \"{}\" with", field)),
+    };
+    let number = |s: &str| {
+        s.parse::<f64>()
+            .map_err(|_| format!("\"{}\" takes a number, not \"{}\"", field, text))
+    };
+    Ok(match kind {
+        FieldKind::Number => QueryValue::Number(number(&text)?),
+        FieldKind::Percent => QueryValue::Number(number(text.trim_end_matches('%'))?),
+        FieldKind::Text => QueryValue::Text(text),
+        FieldKind::Flag => match text.to_lowercase().as_str() {
+            "true" | "yes" | "1" => QueryValue::Flag(true),
+            "false" | "no" | "0" => QueryValue::Flag(false),
+            _ => return Err(format!("\"{}\" is true or false, not \"{}\"", field, text)),
+        },
+    })
+}
+
+/// A filter over vehicles, such as `fuel < 20% and parent == Luna`. Made
+/// of comparisons joined by `and` and `or`, where `and` binds tighter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VehicleQuery {
+    source: String,
+    /// Any one of these must hold; each holds if all its clauses match.
+    any_of: Vec<Vec<QueryClause>>,
+}
+
+impl VehicleQuery {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let mut any_of = Vec::new();
+        let mut all_of = Vec::new();
+        loop {
+            let field = match tokens.next() {
+                Some(Token::Word(w)) => w.to_lowercase(),
+                Some(t) => return Err(format!("Expected a field name, found {:?}", t)),
+                None if any_of.is_empty() && all_of.is_empty() => {
+                    return Err("Empty filter".to_string())
+                }
+                None => return Err("Filter ends after \"and\" or \"or\"".to_string()),
+            };
+            let (field, kind) = QUERY_FIELDS
+                .iter()
+                .find(|(name, ..)| *name == field)
+                .map(|(name, kind, _)| (*name, *kind))
+                .ok_or(format!("No field named \"{}\"", field))?;
+            let op = match tokens.next() {
+                Some(Token::Op(op)) => {
+                    QueryOp::parse(&op).ok_or(format!("No comparison \"{}\"", op))?
+                }
+                _ => return Err(format!("Expected a comparison after \"{}\"", field)),
+            };
+            if !op.allowed_for(kind) {
+                return Err(format!("\"{}\" can't be compared with {:?}", field, op));
+            }
+            let value = parse_value(field, kind, tokens.next())?;
+            all_of.push(QueryClause { field, op, value });
+
+            match tokens.next() {
+                Some(Token::And) => (),
+                Some(Token::Or) => any_of.push(std::mem::take(&mut all_of)),
+                Some(t) => return Err(format!("Expected \"and\" or \"or\", found {:?}", t)),
+                None => break,
+            }
+        }
+        any_of.push(all_of);
+        Ok(Self {
+            source: s.trim().to_string(),
+            any_of,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn matches(&self, facts: &VehicleFacts) -> bool {
+        self.any_of
+            .iter()
+            .any(|all| all.iter().all(|c| c.matches(facts)))
+    }
+}
+
+/// Everything about a vehicle a filter can test.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VehicleFacts {
+    pub id: EntityId,
+    pub name: String,
+    pub model: String,
+    pub parent: Option<String>,
+    pub group: Option<String>,
+    pub controller: String,
+    pub target: Option<String>,
+    /// Percent of fuel capacity remaining.
+    pub fuel: f64,
+    pub dv: f64,
+    /// Tonnes.
+    pub mass: f64,
+    /// Kilometers above the parent's surface.
+    pub altitude: Option<f64>,
+    pub periapsis: Option<f64>,
+    pub apoapsis: Option<f64>,
+    pub ecc: Option<f64>,
+    pub landed: bool,
+    pub thrusting: bool,
+    pub derelict: bool,
+}
+
+/// Short name for an autopilot mode, as written in filters.
+pub fn controller_keyword(policy: &VehicleControlPolicy) -> String {
+    match policy {
+        VehicleControlPolicy::Idle => "idle".to_string(),
+        VehicleControlPolicy::External => "external".to_string(),
+        VehicleControlPolicy::PositionHold(_) => "hold".to_string(),
+        VehicleControlPolicy::LaunchToOrbit(_) => "launch".to_string(),
+        VehicleControlPolicy::BurnPrograde => "prograde".to_string(),
+        VehicleControlPolicy::BurnRetrograde => "retrograde".to_string(),
+        VehicleControlPolicy::HoldAttitude(_) => "attitude".to_string(),
+        VehicleControlPolicy::ControlLaw(name) => name.clone(),
+    }
+}
+
+impl VehicleFacts {
+    /// Gathers what the universe knows about a vehicle. Group names and
+    /// fleet membership are left for the caller to fill in.
+    pub fn gather(universe: &Universe, id: EntityId) -> Option<Self> {
+        let sv = universe.surface_vehicles.get(&id)?;
+        let vehicle = sv.vehicle();
+        let body = universe.lup_planet(sv.parent());
+        let radius = body
+            .as_ref()
+            .and_then(|b| b.named_body())
+            .map(|(_, b)| b.radius);
+        let km_above = |r: f64| radius.map(|radius| (r - radius) / 1000.0);
+        let orbit = sv.current_orbit().map(|GlobalOrbit(_, orbit)| orbit);
+        let target = sv.target().map(|t| {
+            universe
+                .surface_vehicles
+                .get(&t)
+                .map(|v| v.vehicle().name().to_string())
+                .unwrap_or(t.to_string())
+        });
+
+        Some(Self {
+            id,
+            name: vehicle.name().to_string(),
+            model: vehicle.model().to_string(),
+            parent: body
+                .as_ref()
+                .and_then(|b| b.named_body())
+                .map(|(name, _)| name.clone()),
+            group: None,
+            controller: controller_keyword(sv.controller.mode()),
+            target,
+            fuel: vehicle.fuel_percentage() * 100.0,
+            dv: vehicle.remaining_dv(),
+            mass: vehicle.total_mass().to_kg_f64() / 1000.0,
+            altitude: km_above(sv.pv().pos.length()),
+            periapsis: orbit.and_then(|o| km_above(o.periapsis_r())),
+            apoapsis: orbit.and_then(|o| km_above(o.apoapsis_r())),
+            ecc: orbit.map(|o| o.ecc()),
+            landed: sv.is_landed(),
+            thrusting: vehicle.is_thrusting(),
+            derelict: false,
+        })
+    }
+
+    fn get(&self, field: &str) -> Option<QueryValue> {
+        let text = |s: &Option<String>| s.clone().map(QueryValue::Text);
+        let number = |x: Option<f64>| x.map(QueryValue::Number);
+        match field {
+            "id" => Some(QueryValue::Number(self.id.index() as f64)),
+            "name" => Some(QueryValue::Text(self.name.clone())),
+            "model" => Some(QueryValue::Text(self.model.clone())),
+            "parent" => text(&self.parent),
+            "group" => text(&self.group),
+            "controller" => Some(QueryValue::Text(self.controller.clone())),
+            "target" => text(&self.target),
+            "fuel" => Some(QueryValue::Number(self.fuel)),
+            "dv" => Some(QueryValue::Number(self.dv)),
+            "mass" => Some(QueryValue::Number(self.mass)),
+            "altitude" => number(self.altitude),
+            "periapsis" => number(self.periapsis),
+            "apoapsis" => number(self.apoapsis),
+            "ecc" => number(self.ecc),
+            "landed" => Some(QueryValue::Flag(self.landed)),
+            "thrusting" => Some(QueryValue::Flag(self.thrusting)),
+            "derelict" => Some(QueryValue::Flag(self.derelict)),
+            _ => None,
+        }
+    }
+}
+
+/// A filter the player has kept under a name. Pinned filters show up as
+/// one-click smart lists in the sidebar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+pub fn load_saved_filters_from_file(filename: &Path) -> Result<Vec<SavedFilter>, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    Ok(serde_yaml::from_str(&s)?)
+}
+
+pub fn write_saved_filters_to_file(
+    filters: &[SavedFilter],
+    filename: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(filters)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_parse_and_match_vehicles() {
+        let tanker = VehicleFacts {
+            id: EntityId(4),
+            name: "Tanker 2".to_string(),
+            parent: Some("Luna".to_string()),
+            group: Some("fleet-a".to_string()),
+            controller: "idle".to_string(),
+            fuel: 12.0,
+            periapsis: Some(90.0),
+            ..Default::default()
+        };
+        let probe = VehicleFacts {
+            id: EntityId(7),
+            name: "Probe".to_string(),
+            parent: Some("Earth".to_string()),
+            controller: "prograde".to_string(),
+            fuel: 80.0,
+            landed: true,
+            ..Default::default()
+        };
+        let check = |s: &str| {
+            let q = VehicleQuery::parse(s).unwrap();
+            (q.matches(&tanker), q.matches(&probe))
+        };
+
+        assert_eq!(check("fuel < 20%"), (true, false));
+        assert_eq!(check("fuel<20 && parent==luna"), (true, false));
+        assert_eq!(check("controller == idle or landed == yes"), (true, true));
+        assert_eq!(check("group == \"fleet-a\""), (true, false));
+        assert_eq!(check("group != 'fleet-a'"), (false, true));
+        assert_eq!(check("name ~ tank"), (true, false));
+        assert_eq!(check("periapsis > 50"), (true, false));
+        assert_eq!(check("fuel > 50 or fuel < 5 and id == 4"), (false, true));
+
+        for bad in [
+            "",
+            "fuel",
+            "fuel <",
+            "speed > 3",
+            "fuel ~ 3",
+            "name > x",
+            "dv < 10%",
+            "landed == maybe",
+            "fuel < 20 and",
+            "name == \"open",
+        ] {
+            assert!(VehicleQuery::parse(bad).is_err(), "{:?} parsed", bad);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use bevy::log::error;
+use starling::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A vehicle file, summarized when it was last read.
+#[derive(Debug, Clone)]
+pub struct VehicleListing {
+    /// File stem, by which the vehicle is spawned.
+    pub model: String,
+    pub path: PathBuf,
+    /// Name stored in the file.
+    pub name: String,
+    pub parts: usize,
+    pub dry_mass: Mass,
+    modified: Option<SystemTime>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn read_listing(
+    model: String,
+    path: PathBuf,
+    parts: &HashMap<String, PartPrototype>,
+) -> Result<VehicleListing, String> {
+    let s = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let storage: VehicleFileStorage = serde_yaml::from_str(&s).map_err(|e| e.to_string())?;
+    let dry_mass = storage
+        .parts
+        .iter()
+        .filter_map(|p| parts.get(&p.partname))
+        .map(|p| p.dry_mass())
+        .sum();
+    Ok(VehicleListing {
+        model,
+        name: storage.name,
+        parts: storage.parts.len(),
+        dry_mass,
+        modified: modified(&path),
+        path,
+    })
+}
+
+/// Vehicles in the vehicle directory. The directory is only read again
+/// when asked to, so that menus and spawning don't touch the filesystem
+/// every frame; `is_stale` cheaply checks whether that's needed.
+#[derive(Debug, Clone, Default)]
+pub struct VehicleRegistry {
+    dir: PathBuf,
+    dir_modified: Option<SystemTime>,
+    listings: Vec<VehicleListing>,
+}
+
+impl VehicleRegistry {
+    pub fn new(dir: &Path, parts: &HashMap<String, PartPrototype>) -> Self {
+        let mut ret = Self {
+            dir: dir.to_path_buf(),
+            ..Self::default()
+        };
+        ret.refresh(parts);
+        ret
+    }
+
+    /// Rescans the directory, only reading files which are new or have
+    /// changed since the last scan. Returns whether anything changed.
+    pub fn refresh(&mut self, parts: &HashMap<String, PartPrototype>) -> bool {
+        self.dir_modified = modified(&self.dir);
+        let mut old: HashMap<PathBuf, VehicleListing> = self
+            .listings
+            .drain(..)
+            .map(|l| (l.path.clone(), l))
+            .collect();
+        let count = old.len();
+        let mut changed = false;
+
+        if let Ok(entries) = std::fs::read_dir(&self.dir) {
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+                let Some(model) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                    continue;
+                };
+                let listing = match old.remove(&path) {
+                    Some(l) if l.modified == modified(&path) => l,
+                    _ => {
+                        changed = true;
+                        match read_listing(model, path.clone(), parts) {
+                            Ok(l) => l,
+                            Err(e) => {
+                                error!("Failed to read {}: {}", path.display(), e);
+                                continue;
+                            }
+                        }
+                    }
+                };
+                self.listings.push(listing);
+            }
+        }
+
+        self.listings.sort_by(|a, b| a.model.cmp(&b.model));
+        changed || self.listings.len() != count
+    }
+
+    /// Whether files have been added to or removed from the directory
+    /// since it was last scanned.
+    pub fn is_stale(&self) -> bool {
+        modified(&self.dir) != self.dir_modified
+    }
+
+    pub fn listings(&self) -> &[VehicleListing] {
+        &self.listings
+    }
+
+    pub fn get(&self, model: &str) -> Option<&VehicleListing> {
+        self.listings.iter().find(|l| l.model == model)
+    }
+
+    pub fn len(&self) -> usize {
+        self.listings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_rereads_only_changed_files() {
+        let dir = std::env::temp_dir().join(format!("vehicle-registry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let frame = PartPrototype::Generic(Generic::new(
+            "frame".to_string(),
+            UVec2::new(10, 10),
+            PartLayer::Structural,
+            Mass::kilograms(90),
+        ));
+        let parts = HashMap::from([("frame".to_string(), frame)]);
+        let vehicle = |name: &str, n: usize| {
+            let parts: String = (0..n)
+                .map(|i| format!("- partname: frame\n  pos: [{}, 0]\n  rot: East\n", i * 10))
+                .collect();
+            format!("name: '{}'\nparts:\n{}lines: []\n", name, parts)
+        };
+
+        std::fs::write(dir.join("a.vehicle"), vehicle("Alpha", 2)).unwrap();
+        let mut registry = VehicleRegistry::new(&dir, &parts);
+        assert_eq!(registry.len(), 1);
+        let a = registry.get("a").unwrap();
+        assert_eq!((a.name.as_str(), a.parts), ("Alpha", 2));
+        assert_eq!(a.dry_mass, Mass::kilograms(180));
+        assert!(!registry.refresh(&parts));
+
+        std::fs::write(dir.join("b.vehicle"), vehicle("Beta", 1)).unwrap();
+        std::fs::write(dir.join("broken.vehicle"), "not: [a vehicle").unwrap();
+        assert!(registry.refresh(&parts));
+        let models: Vec<_> = registry.listings().iter().map(|l| &l.model).collect();
+        assert_eq!(models, ["a", "b"]);
+
+        std::fs::remove_file(dir.join("a.vehicle")).unwrap();
+        assert!(registry.refresh(&parts));
+        assert!(registry.get("a").is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,6 +6,7 @@ pub enum ZOrdering {
     Shipscope,
     ThrustParticles,
     Vehicle,
+    CargoFill,
     EditorInteriorPart,
     EditorPipe,
     EditorPipeJoint,
@@ -28,6 +29,7 @@ pub enum ZOrdering {
     Ui,
     Ui2,
     Ui3,
+    Cursor,
 }
 
 impl ZOrdering {
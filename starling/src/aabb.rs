@@ -1,7 +1,7 @@
 use crate::math::{linspace, rand, rotate, Vec2, PI};
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 pub struct AABB {
     pub center: Vec2,
     pub span: Vec2,
@@ -0,0 +1,198 @@
+use crate::math::*;
+use serde::{Deserialize, Serialize};
+
+/// Zero-lift drag coefficient of every aerodynamic surface.
+const PARASITIC_DRAG: f64 = 0.02;
+
+/// Drag due to lift, as a fraction of the lift coefficient squared. This
+/// corresponds to a wing with an aspect ratio of about six.
+const INDUCED_DRAG: f64 = 0.066;
+
+/// Extra drag from the flow separating off of a stalled surface.
+const STALL_DRAG: f64 = 0.1;
+
+/// Exponential atmosphere, as far up as its ceiling.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Atmosphere {
+    /// Density at the surface, in kilograms per cubic meter.
+    pub surface_density: f64,
+    /// Altitude over which the density falls by a factor of e, in meters.
+    pub scale_height: f64,
+    /// Altitude above which the air is treated as vacuum, in meters.
+    pub ceiling: f64,
+}
+
+impl Atmosphere {
+    pub const EARTH: Self = Self {
+        surface_density: 1.225,
+        scale_height: 8_500.0,
+        ceiling: 100_000.0,
+    };
+
+    pub fn density(&self, altitude: f64) -> f64 {
+        if altitude > self.ceiling {
+            return 0.0;
+        }
+        self.surface_density * (-altitude.max(0.0) / self.scale_height).exp()
+    }
+}
+
+/// A part which generates lift and drag when moving through air, such as
+/// a wing. The surface is treated as a flat plate lying along the part's
+/// length.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AeroSurface {
+    /// Area of the surface, in square meters.
+    pub area: f64,
+    /// Angle of attack past which the surface stalls, in degrees.
+    pub stall_angle_deg: f64,
+}
+
+impl Default for AeroSurface {
+    fn default() -> Self {
+        Self {
+            area: 1.0,
+            stall_angle_deg: 15.0,
+        }
+    }
+}
+
+impl AeroSurface {
+    pub fn stall_angle(&self) -> f64 {
+        self.stall_angle_deg.to_radians()
+    }
+
+    pub fn is_stalled(&self, angle_of_attack: f64) -> bool {
+        angle_of_attack.abs() > self.stall_angle()
+    }
+
+    /// Thin airfoil lift up to the stall, past which the flow separates
+    /// and the surface lifts like a flat plate, which is far less.
+    pub fn lift_coefficient(&self, angle_of_attack: f64) -> f64 {
+        if self.is_stalled(angle_of_attack) {
+            (2.0 * angle_of_attack).sin()
+        } else {
+            2.0 * PI_64 * angle_of_attack
+        }
+    }
+
+    pub fn drag_coefficient(&self, angle_of_attack: f64) -> f64 {
+        if self.is_stalled(angle_of_attack) {
+            PARASITIC_DRAG + STALL_DRAG + 2.0 * angle_of_attack.sin().powi(2)
+        } else {
+            PARASITIC_DRAG + INDUCED_DRAG * self.lift_coefficient(angle_of_attack).powi(2)
+        }
+    }
+}
+
+/// Air loads on a vehicle, in the frame of the body it's moving about.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AeroForces {
+    /// Force perpendicular to the vehicle's velocity, in newtons.
+    pub lift: DVec2,
+    /// Force opposing the vehicle's velocity, in newtons.
+    pub drag: DVec2,
+    /// Angle between the vehicle's heading and its velocity, in radians.
+    pub angle_of_attack: f64,
+    /// Whether any of the vehicle's surfaces have stalled.
+    pub stalled: bool,
+}
+
+impl AeroForces {
+    pub fn total(&self) -> DVec2 {
+        self.lift + self.drag
+    }
+}
+
+/// Angle of attack of a surface whose chord points along `chord`, moving
+/// along `velocity`. A flat plate is the same either way round, so this
+/// is always within a quarter turn of zero.
+pub fn angle_of_attack(chord: f64, velocity: DVec2) -> f64 {
+    let a = wrap_pi_npi_f64(chord - velocity.to_angle());
+    if a > PI_64 / 2.0 {
+        a - PI_64
+    } else if a < -PI_64 / 2.0 {
+        a + PI_64
+    } else {
+        a
+    }
+}
+
+/// Sums the lift and drag on surfaces, each given with the angle of its
+/// chord relative to the vehicle, for a vehicle facing `heading` and
+/// moving at `velocity` through air of the given density.
+pub fn aero_forces(
+    surfaces: impl IntoIterator<Item = (AeroSurface, f64)>,
+    heading: f64,
+    velocity: DVec2,
+    density: f64,
+) -> AeroForces {
+    let speed = velocity.length();
+    let mut forces = AeroForces::default();
+    if speed == 0.0 {
+        return forces;
+    }
+    let q = 0.5 * density * speed.powi(2);
+    let along = velocity / speed;
+    for (surface, chord) in surfaces {
+        let alpha = angle_of_attack(heading + chord, velocity);
+        forces.lift += along.perp() * q * surface.area * surface.lift_coefficient(alpha);
+        forces.drag -= along * q * surface.area * surface.drag_coefficient(alpha);
+        forces.stalled |= surface.is_stalled(alpha);
+    }
+    forces.angle_of_attack = angle_of_attack(heading, velocity);
+    forces
+}
+
+/// Angle of a velocity above the local horizon, in radians, for a vehicle
+/// at `pos` relative to the center of the body it's flying over.
+pub fn flight_path_angle(pos: DVec2, vel: DVec2) -> f64 {
+    let (up, speed) = (pos.normalize_or_zero(), vel.length());
+    if speed == 0.0 {
+        return 0.0;
+    }
+    (vel.dot(up) / speed).clamp(-1.0, 1.0).asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wings_lift_until_they_stall() {
+        let atmo = Atmosphere::EARTH;
+        assert_eq!(atmo.density(0.0), atmo.surface_density);
+        assert!(atmo.density(atmo.scale_height) < atmo.surface_density / 2.0);
+        assert_eq!(atmo.density(atmo.ceiling + 1.0), 0.0);
+
+        // level flight to the right, nose up five degrees
+        let wing = AeroSurface::default();
+        let vel = DVec2::new(100.0, 0.0);
+        let f = aero_forces([(wing, 0.0)], 5f64.to_radians(), vel, 1.0);
+        assert!((f.angle_of_attack - 5f64.to_radians()).abs() < 1e-9);
+        assert!(f.lift.y > 0.0 && f.lift.x.abs() < 1e-9);
+        assert!(f.drag.x < 0.0 && f.drag.y.abs() < 1e-9);
+        assert!(f.lift.length() > 5.0 * f.drag.length());
+        assert!(!f.stalled);
+
+        // flying backwards is the same to a flat plate
+        let g = aero_forces([(wing, PI_64)], 5f64.to_radians(), vel, 1.0);
+        assert!(g.lift.distance(f.lift) < 1e-6);
+
+        let near = aero_forces([(wing, 0.0)], 14f64.to_radians(), vel, 1.0);
+        let stalled = aero_forces([(wing, 0.0)], 16f64.to_radians(), vel, 1.0);
+        assert!(!near.stalled && stalled.stalled);
+        assert!(stalled.lift.length() < near.lift.length() / 2.0);
+        assert!(stalled.drag.length() > near.drag.length());
+
+        assert_eq!(
+            aero_forces([(wing, 0.0)], 0.0, vel, 0.0).total(),
+            DVec2::ZERO
+        );
+
+        let pos = DVec2::new(0.0, 1000.0);
+        assert!((flight_path_angle(pos, DVec2::new(10.0, -10.0)) + PI_64 / 4.0).abs() < 1e-9);
+        assert_eq!(flight_path_angle(pos, DVec2::X), 0.0);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::nanotime::Nanotime;
+use crate::orbits::SparseOrbit;
+use crate::pv::PV;
+use rayon::prelude::*;
+
+/// Times at which to sample a single orbit.
+#[derive(Debug, Clone)]
+pub struct SampleJob {
+    pub orbit: SparseOrbit,
+    pub stamps: Vec<Nanotime>,
+}
+
+impl SampleJob {
+    pub fn new(orbit: SparseOrbit, stamps: Vec<Nanotime>) -> Self {
+        Self { orbit, stamps }
+    }
+}
+
+/// How bulk trajectory predictions are evaluated. Every prediction feature
+/// goes through this, so that a faster backend only has to be added here.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PropagationBackend {
+    /// One at a time on the calling thread.
+    Serial,
+    /// Spread over a thread pool.
+    #[default]
+    Parallel,
+}
+
+impl PropagationBackend {
+    /// Position and velocity of each job's orbit at each of its stamps, in
+    /// the order given. Samples the orbit can't be evaluated at are None.
+    pub fn sample(&self, jobs: &[SampleJob]) -> Vec<Vec<Option<PV>>> {
+        self.map(jobs, |job| {
+            job.stamps.iter().map(|t| job.orbit.pv(*t).ok()).collect()
+        })
+    }
+
+    /// Applies `f` to every item, keeping their order.
+    pub fn map<T: Sync, U: Send>(&self, items: &[T], f: impl Fn(&T) -> U + Sync + Send) -> Vec<U> {
+        match self {
+            Self::Serial => items.iter().map(f).collect(),
+            Self::Parallel => items.par_iter().map(f).collect(),
+        }
+    }
+
+    /// Applies `f` to every item in place, in no particular order.
+    pub fn for_each_mut<T: Send, I>(&self, items: I, f: impl Fn(T) + Sync + Send)
+    where
+        I: IntoParallelIterator<Item = T> + IntoIterator<Item = T>,
+    {
+        match self {
+            Self::Serial => IntoIterator::into_iter(items).for_each(f),
+            Self::Parallel => IntoParallelIterator::into_par_iter(items).for_each(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbits::Body;
+
+    #[test]
+    fn backends_agree() {
+        let body = Body::LUNA;
+        let jobs: Vec<SampleJob> = (1..20)
+            .map(|i| {
+                let orbit = SparseOrbit::circular(
+                    body.radius + 10_000.0 * i as f64,
+                    body,
+                    Nanotime::zero(),
+                    i % 2 == 0,
+                );
+                let stamps = (0..50).map(|s| Nanotime::secs(s * 60)).collect();
+                SampleJob::new(orbit, stamps)
+            })
+            .collect();
+
+        let serial = PropagationBackend::Serial.sample(&jobs);
+        let parallel = PropagationBackend::Parallel.sample(&jobs);
+        assert_eq!(serial.len(), jobs.len());
+        assert_eq!(serial, parallel);
+        assert!(serial.iter().flatten().all(|pv| pv.is_some()));
+
+        let mut radii: Vec<f64> = jobs.iter().map(|j| j.orbit.periapsis_r()).collect();
+        PropagationBackend::Parallel.for_each_mut(&mut radii, |r| *r *= 2.0);
+        assert_eq!(radii[0], 2.0 * jobs[0].orbit.periapsis_r());
+    }
+}
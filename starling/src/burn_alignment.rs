@@ -0,0 +1,126 @@
+use crate::prelude::*;
+
+/// How long before a scheduled burn a vehicle starts holding the burn
+/// attitude, when auto-hold is on.
+pub const BURN_HOLD_LEAD: Nanotime = Nanotime::millis(60_000);
+
+/// Largest angle, in radians, between a vehicle's heading and a burn
+/// vector at which the vehicle counts as lined up for the burn.
+pub const BURN_ALIGNMENT_TOLERANCE: f64 = 0.05;
+
+/// How well a vehicle is lined up for its next scheduled burn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurnAlignment {
+    pub burn_at: Nanotime,
+    pub dv: DVec2,
+    /// Heading the vehicle needs to thrust along the burn vector.
+    pub required: f64,
+    /// Heading the vehicle actually has.
+    pub current: f64,
+}
+
+impl BurnAlignment {
+    pub fn new(burn_at: Nanotime, dv: DVec2, current: f64) -> Self {
+        Self {
+            burn_at,
+            dv,
+            required: dv.to_angle(),
+            current,
+        }
+    }
+
+    /// Signed angle to turn through to line up with the burn, from -pi to
+    /// pi.
+    pub fn error(&self) -> f64 {
+        wrap_pi_npi_f64(self.required - self.current)
+    }
+
+    pub fn is_aligned(&self) -> bool {
+        self.error().abs() <= BURN_ALIGNMENT_TOLERANCE
+    }
+}
+
+impl Universe {
+    /// How the given vehicle is lined up for its next scheduled burn, if
+    /// it has one.
+    pub fn burn_alignment(&self, id: EntityId) -> Option<BurnAlignment> {
+        let (t, dv) = self.maneuvers.next_burn_for(id)?;
+        let sv = self.surface_vehicles.get(&id)?;
+        Some(BurnAlignment::new(t, dv, sv.body.angle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::universe_in_leo;
+    use std::time::Duration;
+
+    #[test]
+    fn burns_are_held_and_aborted_when_misaligned() {
+        let (mut universe, leo) = universe_in_leo();
+        let body = universe.planets.body;
+        let t0 = universe.stamp();
+        let dest = SparseOrbit::circular(body.radius + 2_000_000.0, body, t0, false);
+        let mut add = || universe.add_orbital_vehicle(Vehicle::new(), leo).unwrap();
+        let (held, busy) = (add(), add());
+        let plan = best_maneuver_plan(&leo.1, &dest, t0 + Nanotime::mins(5)).unwrap();
+        let (first, dv) = plan.dvs().next().unwrap();
+        universe.maneuvers.schedule(held, plan.clone());
+        universe.maneuvers.schedule(busy, plan);
+        universe.maneuvers.auto_hold = true;
+
+        // one already faces the burn; the other is held facing elsewhere
+        let sv = universe.surface_vehicles.get_mut(&held).unwrap();
+        sv.body.angle = dv.to_angle();
+        sv.body.angular_velocity = 0.0;
+        let sv = universe.surface_vehicles.get_mut(&busy).unwrap();
+        sv.body.angle = dv.to_angle() + 1.0;
+        let angle = sv.body.angle;
+        sv.controller
+            .set_policy(VehicleControlPolicy::HoldAttitude(Some(angle)));
+
+        let alignment = universe.burn_alignment(held).unwrap();
+        assert_eq!(alignment.burn_at, first);
+        assert_eq!(alignment.required, dv.to_angle());
+
+        // batches stop where holds begin, so they're in place by burn time
+        assert_eq!(
+            universe.maneuvers.next_hold(t0),
+            Some(first - BURN_HOLD_LEAD)
+        );
+        let events = universe.events.subscribe();
+        let signals = ControlSignals::new();
+        while universe.stamp() <= first {
+            let (ticks, _, _) = universe.on_sim_ticks(1000, &signals, Duration::MAX);
+            assert!(ticks > 0);
+        }
+
+        // the idle vehicle was held facing the burn, flew it, and is
+        // still holding for the next one
+        assert!(universe.maneuvers.get(held).is_some());
+        assert!(universe.maneuvers.is_holding(held));
+        assert!(matches!(
+            universe.surface_vehicles[&held].controller.mode(),
+            VehicleControlPolicy::HoldAttitude(Some(_))
+        ));
+
+        // the other was under other orders, so couldn't be turned in time
+        assert!(universe.maneuvers.get(busy).is_none());
+        let aborted: Vec<_> = universe
+            .events
+            .poll(events)
+            .into_iter()
+            .filter_map(|e| match e.event {
+                UniverseEvent::BurnAborted(id, _) => Some(id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(aborted, vec![busy]);
+
+        // holds are let go once the plan is done
+        universe.maneuvers.cancel(held);
+        universe.on_sim_ticks(1, &signals, Duration::MAX);
+        assert!(universe.surface_vehicles[&held].controller.is_idle());
+    }
+}
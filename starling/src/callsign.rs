@@ -0,0 +1,56 @@
+/// Base name given to vehicles which come without one.
+pub const DEFAULT_CALLSIGN: &str = "Vessel";
+
+/// Splits a callsign into its base name and designation number, so that
+/// "Infallible-3" gives ("Infallible", Some(3)).
+pub fn split_callsign(callsign: &str) -> (&str, Option<u32>) {
+    if let Some((base, n)) = callsign.rsplit_once('-') {
+        if !base.is_empty() && !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) {
+            if let Ok(n) = n.parse() {
+                return (base, Some(n));
+            }
+        }
+    }
+    (callsign, None)
+}
+
+/// A callsign for a vehicle wanting to be called `name` which no other
+/// vehicle has. That's the name itself when it's free, or else its base
+/// name with the lowest designation from 2 up that isn't taken.
+pub fn unique_callsign<'a>(name: &str, taken: impl IntoIterator<Item = &'a str>) -> String {
+    let name = name.trim();
+    let name = if name.is_empty() {
+        DEFAULT_CALLSIGN
+    } else {
+        name
+    };
+    let taken: Vec<&str> = taken.into_iter().collect();
+    if !taken.contains(&name) {
+        return name.to_string();
+    }
+
+    let (base, _) = split_callsign(name);
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|c| !taken.contains(&c.as_str()))
+        .unwrap_or_else(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callsigns_get_the_lowest_free_designation() {
+        assert_eq!(split_callsign("Infallible-3"), ("Infallible", Some(3)));
+        assert_eq!(split_callsign("Jean-Luc"), ("Jean-Luc", None));
+        assert_eq!(split_callsign("-4"), ("-4", None));
+
+        let taken = ["Infallible", "Infallible-2", "Kamchatka"];
+        assert_eq!(unique_callsign("Smooth Criminal", taken), "Smooth Criminal");
+        assert_eq!(unique_callsign("Infallible", taken), "Infallible-3");
+        assert_eq!(unique_callsign("Infallible-2", taken), "Infallible-3");
+        assert_eq!(unique_callsign("Kamchatka", taken), "Kamchatka-2");
+        assert_eq!(unique_callsign("  ", taken), DEFAULT_CALLSIGN);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Universe state as of some moment. Vehicles and the larger maps sit
+/// behind `Arc`s, so that checkpoints taken one after another share
+/// whatever hasn't changed in between rather than each holding a copy.
+#[derive(Debug, Clone)]
+pub struct UniverseCheckpoint {
+    pub(crate) stamp: Nanotime,
+    pub(crate) ticks: u128,
+    pub(crate) ids: IdAllocator,
+    pub(crate) legacy_next: i64,
+    pub(crate) legacy_ids: IdRemap,
+    pub(crate) surface_vehicles: HashMap<EntityId, Arc<SurfaceSpacecraftEntity>>,
+    pub(crate) constellations: HashMap<EntityId, EntityId>,
+    pub(crate) landing_pads: HashMap<EntityId, LandingPad>,
+    pub(crate) structures: Arc<HashMap<EntityId, Structure>>,
+    pub(crate) terrain: Arc<HashMap<EntityId, SiteTerrain>>,
+    pub(crate) waypoints: Arc<HashMap<EntityId, Waypoint>>,
+    pub(crate) traffic: BackgroundTraffic,
+    pub(crate) maneuvers: ScheduledManeuvers,
+    pub(crate) rendezvous: HashMap<EntityId, OrbitalController>,
+    pub(crate) docked: HashMap<EntityId, Vec<(EntityId, DockingJoint)>>,
+    pub(crate) docking: HashMap<EntityId, DockingQueue>,
+    pub(crate) captures: HashMap<EntityId, EntityId>,
+    pub(crate) transfers: Vec<ItemTransfer>,
+    pub(crate) script: ScenarioScript,
+    pub(crate) loading: LoadQueue,
+    pub(crate) recycle_bin: Arc<RecycleBin>,
+    pub(crate) quarantine: Quarantine,
+}
+
+/// `current` behind an `Arc`, reusing `previous` rather than copying
+/// `current` if the two are the same.
+pub(crate) fn share<T: Clone + PartialEq>(current: &T, previous: Option<&Arc<T>>) -> Arc<T> {
+    match previous {
+        Some(p) if **p == *current => p.clone(),
+        _ => Arc::new(current.clone()),
+    }
+}
+
+impl UniverseCheckpoint {
+    pub fn stamp(&self) -> Nanotime {
+        self.stamp
+    }
+}
+
+/// Ring buffer of universe snapshots, captured at a fixed interval of
+/// sim time. Planets are static and are not included in the snapshot.
+/// Each snapshot shares what's unchanged with the one before it.
+#[derive(Debug)]
+pub struct CheckpointBuffer {
+    interval: Nanotime,
+    capacity: usize,
+    checkpoints: VecDeque<UniverseCheckpoint>,
+}
+
+impl CheckpointBuffer {
+    pub fn new(interval: Nanotime, capacity: usize) -> Self {
+        Self {
+            interval,
+            capacity,
+            checkpoints: VecDeque::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checkpoints.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.checkpoints.clear();
+    }
+
+    pub fn latest(&self) -> Option<&UniverseCheckpoint> {
+        self.checkpoints.back()
+    }
+
+    /// Captures a checkpoint if at least one interval of sim time has
+    /// elapsed since the last one. Returns true if a checkpoint was taken.
+    pub fn update(&mut self, universe: &Universe) -> bool {
+        let stamp = universe.stamp();
+
+        // universe time went backwards, i.e. something was loaded or rewound
+        self.checkpoints.retain(|c| c.stamp <= stamp);
+
+        if let Some(latest) = self.latest() {
+            if stamp - latest.stamp < self.interval {
+                return false;
+            }
+        }
+
+        let checkpoint = universe.checkpoint_since(self.latest());
+        self.checkpoints.push_back(checkpoint);
+        while self.checkpoints.len() > self.capacity {
+            self.checkpoints.pop_front();
+        }
+        true
+    }
+
+    /// Restores the most recent checkpoint which is at least `dt` older
+    /// than the current universe time, discarding everything newer.
+    /// Returns the stamp of the restored checkpoint.
+    pub fn rewind(&mut self, universe: &mut Universe, dt: Nanotime) -> Option<Nanotime> {
+        let target = universe.stamp() - dt;
+        let idx = self.checkpoints.iter().rposition(|c| c.stamp <= target)?;
+        self.checkpoints.truncate(idx + 1);
+        let cp = self.checkpoints.back()?;
+        universe.restore(cp);
+        Some(cp.stamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_for(universe: &mut Universe, buffer: &mut CheckpointBuffer, dur: Nanotime) {
+        let end = universe.stamp() + dur;
+        while universe.stamp() < end {
+            universe.run_batch_ticks(1);
+            buffer.update(universe);
+        }
+    }
+
+    #[test]
+    fn checkpoint_ring_buffer_is_bounded() {
+        let mut universe = Universe::empty();
+        let mut buffer = CheckpointBuffer::new(Nanotime::secs(5), 4);
+        run_for(&mut universe, &mut buffer, Nanotime::secs(60));
+        assert_eq!(buffer.len(), 4);
+        assert!(universe.stamp() - buffer.latest().unwrap().stamp() < Nanotime::secs(5));
+    }
+
+    #[test]
+    fn rewind_restores_older_checkpoint() {
+        let mut universe = Universe::empty();
+        let mut buffer = CheckpointBuffer::new(Nanotime::secs(5), 100);
+        run_for(&mut universe, &mut buffer, Nanotime::secs(60));
+
+        let before = universe.stamp();
+        let restored = buffer.rewind(&mut universe, Nanotime::secs(30)).unwrap();
+        assert_eq!(universe.stamp(), restored);
+        assert!(before - restored >= Nanotime::secs(30));
+        assert!(before - restored < Nanotime::secs(35));
+        assert_eq!(buffer.latest().unwrap().stamp(), restored);
+
+        // can't rewind past the oldest checkpoint
+        assert!(buffer.rewind(&mut universe, Nanotime::secs(1000)).is_none());
+    }
+
+    #[test]
+    fn rewinding_past_a_deletion_empties_the_bin() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let id = universe
+            .add_surface_vehicle(planet, Vehicle::new(), 0.0, 100.0)
+            .unwrap();
+        let checkpoint = universe.checkpoint();
+
+        universe.soft_delete(id).unwrap();
+        universe.restore(&checkpoint);
+        assert!(universe.surface_vehicles.contains_key(&id));
+        assert!(!universe.recycle_bin.contains(id));
+        assert!(universe.undelete(id).is_none());
+
+        // and a vehicle deleted before the checkpoint is still in the bin
+        universe.soft_delete(id).unwrap();
+        let checkpoint = universe.checkpoint();
+        universe.undelete(id).unwrap();
+        universe.restore(&checkpoint);
+        assert!(!universe.surface_vehicles.contains_key(&id));
+        assert!(universe.undelete(id).is_some());
+    }
+
+    #[test]
+    fn checkpoints_round_trip_waypoints() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let kept = universe
+            .add_waypoint(Waypoint::surface("Kept", planet, 0.5))
+            .unwrap();
+        let checkpoint = universe.checkpoint();
+
+        let placed = universe
+            .add_waypoint(Waypoint::orbital("Placed", planet, DVec2::X * 3000.0))
+            .unwrap();
+        universe.remove_waypoint(kept);
+        universe.restore(&checkpoint);
+
+        assert_eq!(universe.waypoints.len(), 1);
+        assert_eq!(universe.waypoints[&kept].name, "Kept");
+        assert!(!universe.waypoints.contains_key(&placed));
+
+        // the ids handed out since are handed out again, in step with the
+        // legacy counter
+        let again = universe
+            .add_waypoint(Waypoint::surface("Again", planet, 1.0))
+            .unwrap();
+        assert_eq!(again, placed);
+        assert_eq!(
+            universe.checkpoint().legacy_next,
+            checkpoint.legacy_next + 1
+        );
+    }
+
+    #[test]
+    fn consecutive_checkpoints_share_unchanged_state() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let id = universe
+            .add_surface_vehicle(planet, Vehicle::new(), 0.0, 100.0)
+            .unwrap();
+        universe
+            .add_waypoint(Waypoint::surface("Pad", planet, 0.5))
+            .unwrap();
+
+        let first = universe.checkpoint();
+        let second = universe.checkpoint_since(Some(&first));
+        assert!(Arc::ptr_eq(
+            &first.surface_vehicles[&id],
+            &second.surface_vehicles[&id]
+        ));
+        assert!(Arc::ptr_eq(&first.waypoints, &second.waypoints));
+
+        let vel = universe.surface_vehicles[&id].body.pv.vel;
+        universe.surface_vehicles.get_mut(&id).unwrap().body.pv.vel += DVec2::X;
+        universe.add_waypoint(Waypoint::surface("Other", planet, 1.0));
+        let third = universe.checkpoint_since(Some(&second));
+        assert!(!Arc::ptr_eq(
+            &second.surface_vehicles[&id],
+            &third.surface_vehicles[&id]
+        ));
+        assert!(!Arc::ptr_eq(&second.waypoints, &third.waypoints));
+        assert!(Arc::ptr_eq(&second.structures, &third.structures));
+
+        universe.restore(&first);
+        assert_eq!(universe.surface_vehicles[&id].body.pv.vel, vel);
+        assert_eq!(universe.waypoints.len(), 1);
+    }
+}
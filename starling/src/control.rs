@@ -1,9 +1,57 @@
+use crate::id::EntityId;
 use crate::nanotime::Nanotime;
 use crate::orbits::GlobalOrbit;
-use crate::planning::{best_maneuver_plan, ManeuverPlan};
+use crate::planning::{best_maneuver_plan, rendezvous_plan, ManeuverPlan};
+use crate::pv::PV;
+
+/// Distance from its target within which a vehicle has made rendezvous,
+/// in meters.
+pub const RENDEZVOUS_RANGE: f64 = 2_000.0;
+
+/// Speed relative to its target below which a vehicle has made
+/// rendezvous, in meters per second.
+pub const RENDEZVOUS_SPEED: f64 = 10.0;
+
+/// What the controller is steering the vehicle toward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlPolicy {
+    /// Into the destination orbit, anywhere along it.
+    Transfer,
+    /// Alongside the given vehicle, whose orbit is the destination.
+    Rendezvous(EntityId),
+}
+
+/// Why a vehicle gave up on a rendezvous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RendezvousAbort {
+    TargetLost,
+    DifferentBody,
+    NoPlan,
+    CalledOff,
+    Missed,
+}
+
+impl std::fmt::Display for RendezvousAbort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TargetLost => write!(f, "target is gone"),
+            Self::DifferentBody => write!(f, "not orbiting the same body as the target"),
+            Self::NoPlan => write!(f, "no way to reach the target"),
+            Self::CalledOff => write!(f, "maneuver was called off"),
+            Self::Missed => write!(f, "missed the target"),
+        }
+    }
+}
+
+/// Whether a vehicle has made rendezvous with its target, given the state
+/// of each about the same body.
+pub fn is_rendezvous(pv: PV, target: PV) -> bool {
+    pv.pos.distance(target.pos) < RENDEZVOUS_RANGE && pv.vel.distance(target.vel) < RENDEZVOUS_SPEED
+}
 
 #[derive(Debug, Clone)]
 pub struct OrbitalController {
+    policy: ControlPolicy,
     last_update: Nanotime,
     current: Option<GlobalOrbit>,
     destination: Option<GlobalOrbit>,
@@ -13,6 +61,7 @@ pub struct OrbitalController {
 impl OrbitalController {
     pub fn idle() -> Self {
         OrbitalController {
+            policy: ControlPolicy::Transfer,
             last_update: Nanotime::zero(),
             current: None,
             destination: None,
@@ -21,6 +70,7 @@ impl OrbitalController {
     }
 
     pub fn clear(&mut self) {
+        self.policy = ControlPolicy::Transfer;
         self.destination = None;
         self.plan = None;
     }
@@ -75,21 +125,81 @@ impl OrbitalController {
         destination: GlobalOrbit,
         stamp: Nanotime,
     ) -> Result<(), &'static str> {
+        self.policy = ControlPolicy::Transfer;
         self.destination = Some(destination);
         self.reroute(stamp)
     }
 
+    /// Plans to meet up with the target vehicle, currently in the given
+    /// orbit.
+    pub fn set_rendezvous(
+        &mut self,
+        target: EntityId,
+        orbit: GlobalOrbit,
+        stamp: Nanotime,
+    ) -> Result<(), &'static str> {
+        self.policy = ControlPolicy::Rendezvous(target);
+        self.destination = Some(orbit);
+        self.reroute(stamp)
+    }
+
+    /// Follows the target of a rendezvous to its latest orbit, replanning
+    /// if the target has maneuvered or the vehicle has strayed from the
+    /// plan. Returns whether there's a new plan.
+    pub fn update_rendezvous(
+        &mut self,
+        stamp: Nanotime,
+        orbit: GlobalOrbit,
+        target: GlobalOrbit,
+    ) -> Result<bool, &'static str> {
+        self.last_update = stamp;
+        self.current = Some(orbit);
+
+        let moved = self
+            .destination
+            .is_none_or(|d| d.0 != target.0 || !d.1.is_similar(&target.1));
+        self.destination = Some(target);
+
+        let on_plan = self
+            .plan
+            .as_ref()
+            .and_then(|m| m.segment_at(stamp))
+            .is_some_and(|s| orbit.1.is_similar(&s.orbit));
+
+        if on_plan && !moved {
+            return Ok(false);
+        }
+        self.reroute(stamp)?;
+        Ok(true)
+    }
+
     pub fn reroute(&mut self, stamp: Nanotime) -> Result<(), &'static str> {
         let c = self.current.as_ref().ok_or("No current orbit")?;
         let d = self.destination.as_ref().ok_or("No destination orbit")?;
         if c.0 != d.0 {
             return Err("Cannot path between bodies");
         }
-        let p = best_maneuver_plan(&c.1, &d.1, stamp)?;
+        let p = match self.policy {
+            ControlPolicy::Transfer => best_maneuver_plan(&c.1, &d.1, stamp)?,
+            ControlPolicy::Rendezvous(_) => {
+                rendezvous_plan(&c.1, &d.1, stamp).ok_or("No rendezvous plan")?
+            }
+        };
         self.plan = Some(p);
         Ok(())
     }
 
+    pub fn policy(&self) -> ControlPolicy {
+        self.policy
+    }
+
+    pub fn rendezvous_target(&self) -> Option<EntityId> {
+        match self.policy {
+            ControlPolicy::Rendezvous(id) => Some(id),
+            ControlPolicy::Transfer => None,
+        }
+    }
+
     pub fn destination(&self) -> Option<&GlobalOrbit> {
         self.destination.as_ref()
     }
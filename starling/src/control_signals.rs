@@ -2,6 +2,7 @@ use crate::id::EntityId;
 use crate::vehicle::VehicleControl;
 use std::collections::HashMap;
 
+#[derive(Debug, Clone)]
 pub struct ControlSignals {
     pub piloting_commands: HashMap<EntityId, VehicleControl>,
 }
@@ -0,0 +1,511 @@
+use crate::prelude::*;
+
+/// Distance from the target of the innermost ring of holding points.
+pub const HOLDING_DISTANCE: f64 = 150.0;
+
+/// Radial spacing between successive rings of holding points.
+pub const HOLDING_SPACING: f64 = 50.0;
+
+/// Holding points per ring. They're spread over the half of the ring
+/// facing away from the docking axis, clear of the approach corridor.
+const HOLDING_POINTS_PER_RING: usize = 5;
+
+/// Separation at which a cleared vehicle is considered docked.
+pub const DOCKING_CONTACT_DISTANCE: f64 = 8.0;
+
+/// Fastest relative speed at contact which counts as a successful dock.
+pub const MAX_CONTACT_SPEED: f64 = 1.0;
+
+/// Range from the target within which the closing rate is policed.
+pub const WAVE_OFF_RANGE: f64 = 40.0;
+
+/// Fastest closing rate allowed within the wave-off range.
+pub const MAX_CLOSING_RATE: f64 = 3.0;
+
+/// Approach gates, as ranges from the target paired with the fastest
+/// closing rate allowed inside them, from the outermost in.
+pub const APPROACH_GATES: [(f64, f64); 4] = [
+    (1000.0, 10.0),
+    (HOLDING_DISTANCE, 5.0),
+    (WAVE_OFF_RANGE, MAX_CLOSING_RATE),
+    (DOCKING_CONTACT_DISTANCE, MAX_CONTACT_SPEED),
+];
+
+/// Innermost approach gate the given range is inside of.
+pub fn approach_gate(range: f64) -> Option<(f64, f64)> {
+    APPROACH_GATES.into_iter().rfind(|(r, _)| range <= *r)
+}
+
+/// Time a cleared vehicle has to complete its approach before it is
+/// waved off to make way for the next in line.
+pub const CLEARANCE_TIMEOUT: Nanotime = Nanotime::mins(10);
+
+/// Number of wave-offs after which a vehicle's request is dropped.
+pub const MAX_WAVE_OFFS: u32 = 3;
+
+/// Distance from a docking port within which its capture assist pulls
+/// an aligned vehicle in.
+pub const CAPTURE_RANGE: f64 = 20.0;
+
+/// Largest attitude error, and largest angle off the docking axis, at
+/// which the capture assist engages, in radians.
+pub const CAPTURE_MISALIGNMENT: f64 = 0.35;
+
+/// Spring and damping constants of the capture assist at the port, per
+/// second squared and per second. The damping is critical, so captured
+/// vehicles settle onto the port without overshooting it.
+const CAPTURE_STIFFNESS: f64 = 0.08;
+const CAPTURE_DAMPING: f64 = 0.57;
+const CAPTURE_ANGULAR_STIFFNESS: f64 = 0.5;
+const CAPTURE_ANGULAR_DAMPING: f64 = 1.4;
+
+/// Strongest accelerations the capture assist applies, in meters per
+/// second squared and radians per second squared.
+const CAPTURE_MAX_ACCEL: f64 = 0.3;
+const CAPTURE_MAX_ANGULAR_ACCEL: f64 = 0.3;
+
+/// Accelerations the capture assist applies to a vehicle near a docking
+/// port, in the world frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptureAssist {
+    pub linear: DVec2,
+    pub angular: f64,
+}
+
+/// Soft capture at the target's docking port, like the magnets on real
+/// docking mechanisms. A vehicle within capture range of the port and
+/// roughly lined up with it is gently pulled onto it and turned to face
+/// it, strongest at the port and fading out toward the edge of the range.
+///
+/// `rel` is the vehicle's state relative to the target, and the angles
+/// and angular velocities are each vehicle's own.
+pub fn capture_assist(
+    rel: PV,
+    angle: f64,
+    angular_velocity: f64,
+    target_angle: f64,
+    target_angular_velocity: f64,
+) -> Option<CaptureAssist> {
+    let axis = rotate_f64(DVec2::X, target_angle);
+    let port = axis * DOCKING_CONTACT_DISTANCE / 2.0;
+    let offset = rel.pos - port;
+    let distance = offset.length();
+    if distance > CAPTURE_RANGE {
+        return None;
+    }
+
+    let attitude_error = wrap_pi_npi_f64(angle - target_angle - PI_64);
+    let off_axis = axis.angle_to(rel.pos).abs();
+    if attitude_error.abs() > CAPTURE_MISALIGNMENT || off_axis > CAPTURE_MISALIGNMENT {
+        return None;
+    }
+
+    let strength = 1.0 - distance / CAPTURE_RANGE;
+    let linear = (-CAPTURE_STIFFNESS * offset - CAPTURE_DAMPING * rel.vel) * strength;
+    let angular = (-CAPTURE_ANGULAR_STIFFNESS * attitude_error
+        - CAPTURE_ANGULAR_DAMPING * (angular_velocity - target_angular_velocity))
+        * strength;
+
+    Some(CaptureAssist {
+        linear: linear.clamp_length_max(CAPTURE_MAX_ACCEL),
+        angular: angular.clamp(-CAPTURE_MAX_ANGULAR_ACCEL, CAPTURE_MAX_ANGULAR_ACCEL),
+    })
+}
+
+/// Farthest apart the faces of two docking ports can be for them to
+/// latch, in meters.
+pub const PORT_LATCH_DISTANCE: f64 = 0.5;
+
+/// Largest angle, in radians, by which two ports can be off from facing
+/// each other squarely and still latch.
+pub const PORT_LATCH_MISALIGNMENT: f64 = 0.1;
+
+/// Speed at which an undocked vehicle is pushed away from the one it was
+/// docked to, in meters per second.
+pub const UNDOCK_SPEED: f64 = 0.5;
+
+/// A docking port on each of two vehicles which are close enough together,
+/// and lined up well enough, to latch. Both bodies are in the same frame.
+pub fn latching_ports(
+    a: &Vehicle,
+    a_body: &RigidBody,
+    b: &Vehicle,
+    b_body: &RigidBody,
+) -> Option<(PartId, PartId)> {
+    let faces = |v: &Vehicle, body: &RigidBody| -> Vec<(PartId, DVec2, f64)> {
+        v.docking_ports()
+            .map(|(id, face, rot)| {
+                let pos = body.pv.pos + rotate_f64(face, body.angle);
+                (id, pos, body.angle + rot.to_angle())
+            })
+            .collect()
+    };
+    let theirs = faces(b, b_body);
+    for (ours, p, dir) in faces(a, a_body) {
+        for (id, q, other) in &theirs {
+            let error = wrap_pi_npi_f64(dir - other - PI_64);
+            if error.abs() < PORT_LATCH_MISALIGNMENT && p.distance(*q) < PORT_LATCH_DISTANCE {
+                return Some((ours, *id));
+            }
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockingPhase {
+    Holding,
+    /// Cleared to approach the docking port at the given time.
+    Cleared(Nanotime),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaveOffReason {
+    TooFast,
+    HardContact,
+    TimedOut,
+}
+
+impl std::fmt::Display for WaveOffReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::TooFast => "closing too fast",
+            Self::HardContact => "contact too hard",
+            Self::TimedOut => "approach timed out",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DockingRequest {
+    pub vehicle: EntityId,
+    pub phase: DockingPhase,
+    pub wave_offs: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockingOutcome {
+    Docked(EntityId),
+    WavedOff(EntityId, WaveOffReason),
+    /// Dropped from the queue after too many wave-offs.
+    Aborted(EntityId),
+}
+
+/// Traffic control for vehicles docking at a single target. Vehicles wait
+/// at holding points around the target, and are cleared to approach one
+/// at a time, in the order they asked. Approaches which are too fast or
+/// take too long are waved off, sending the vehicle to the back of the
+/// line.
+///
+/// The target's docking axis is its body +X axis, so cleared vehicles
+/// approach along it, nose first toward the target.
+#[derive(Debug, Clone)]
+pub struct DockingQueue {
+    pub target: EntityId,
+    requests: Vec<DockingRequest>,
+}
+
+impl DockingQueue {
+    pub fn new(target: EntityId) -> Self {
+        Self {
+            target,
+            requests: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    pub fn requests(&self) -> impl Iterator<Item = &DockingRequest> + use<'_> {
+        self.requests.iter()
+    }
+
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.requests.iter().any(|r| r.vehicle == id)
+    }
+
+    /// Adds a vehicle to the back of the queue, returning its place in
+    /// line. Vehicles already queued keep their place.
+    pub fn request(&mut self, id: EntityId) -> usize {
+        if let Some(i) = self.requests.iter().position(|r| r.vehicle == id) {
+            return i;
+        }
+        self.requests.push(DockingRequest {
+            vehicle: id,
+            phase: DockingPhase::Holding,
+            wave_offs: 0,
+        });
+        self.requests.len() - 1
+    }
+
+    pub fn cancel(&mut self, id: EntityId) {
+        self.requests.retain(|r| r.vehicle != id);
+    }
+
+    /// Offset from the target, in the target's body frame, of the given
+    /// holding point.
+    pub fn holding_point(slot: usize) -> DVec2 {
+        let ring = slot / HOLDING_POINTS_PER_RING;
+        let i = slot % HOLDING_POINTS_PER_RING;
+        let angle = PI_64 / 2.0 + PI_64 * i as f64 / (HOLDING_POINTS_PER_RING - 1) as f64;
+        rotate_f64(DVec2::X, angle) * (HOLDING_DISTANCE + HOLDING_SPACING * ring as f64)
+    }
+
+    /// Pose the vehicle should hold relative to the target, given the
+    /// target's attitude. Holding vehicles face the target; cleared
+    /// vehicles aim for a point just inside contact range on the docking
+    /// axis, facing opposite the target's nose.
+    pub fn guidance(&self, id: EntityId, target_angle: f64) -> Option<Pose> {
+        let request = self.requests.iter().find(|r| r.vehicle == id)?;
+        match request.phase {
+            DockingPhase::Cleared(_) => {
+                let port = rotate_f64(DVec2::X, target_angle) * DOCKING_CONTACT_DISTANCE / 2.0;
+                Some((port, target_angle + PI_64))
+            }
+            DockingPhase::Holding => {
+                let slot = self
+                    .requests
+                    .iter()
+                    .filter(|r| r.phase == DockingPhase::Holding)
+                    .position(|r| r.vehicle == id)?;
+                let p = rotate_f64(Self::holding_point(slot), target_angle);
+                Some((p, (-p).to_angle()))
+            }
+        }
+    }
+
+    /// Advances the queue given each vehicle's position and velocity
+    /// relative to the target. Vehicles without a relative state have
+    /// gone away and are dropped.
+    pub fn update(
+        &mut self,
+        stamp: Nanotime,
+        rel: impl Fn(EntityId) -> Option<PV>,
+    ) -> Vec<DockingOutcome> {
+        self.requests.retain(|r| rel(r.vehicle).is_some());
+
+        let mut outcomes = Vec::new();
+        let Some(first) = self.requests.first_mut() else {
+            return outcomes;
+        };
+
+        let since = match first.phase {
+            DockingPhase::Holding => {
+                first.phase = DockingPhase::Cleared(stamp);
+                return outcomes;
+            }
+            DockingPhase::Cleared(since) => since,
+        };
+
+        let Some(pv) = rel(first.vehicle) else {
+            return outcomes;
+        };
+        let distance = pv.pos.length();
+        let speed = pv.vel.length();
+        let closing_rate = -pv.pos.normalize_or_zero().dot(pv.vel);
+
+        let wave_off = if distance < DOCKING_CONTACT_DISTANCE {
+            if speed <= MAX_CONTACT_SPEED {
+                outcomes.push(DockingOutcome::Docked(first.vehicle));
+                self.requests.remove(0);
+                return outcomes;
+            }
+            Some(WaveOffReason::HardContact)
+        } else if distance < WAVE_OFF_RANGE && closing_rate > MAX_CLOSING_RATE {
+            Some(WaveOffReason::TooFast)
+        } else if stamp - since > CLEARANCE_TIMEOUT {
+            Some(WaveOffReason::TimedOut)
+        } else {
+            None
+        };
+
+        if let Some(reason) = wave_off {
+            let mut request = self.requests.remove(0);
+            request.wave_offs += 1;
+            if request.wave_offs >= MAX_WAVE_OFFS {
+                outcomes.push(DockingOutcome::Aborted(request.vehicle));
+            } else {
+                outcomes.push(DockingOutcome::WavedOff(request.vehicle, reason));
+                request.phase = DockingPhase::Holding;
+                self.requests.push(request);
+            }
+        }
+
+        outcomes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use more_asserts::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn docking_queue_sequences_approaches() {
+        let (a, b, c) = (EntityId(1), EntityId(2), EntityId(3));
+        let mut queue = DockingQueue::new(EntityId(100));
+        assert_eq!(queue.request(a), 0);
+        assert_eq!(queue.request(b), 1);
+        assert_eq!(queue.request(c), 2);
+        assert_eq!(queue.request(a), 0);
+
+        let far = PV::from_f64(DVec2::new(0.0, 300.0), DVec2::ZERO);
+        let mut rel: HashMap<EntityId, PV> = [(a, far), (b, far), (c, far)].into();
+
+        let t = Nanotime::secs(1);
+        assert!(queue.update(t, |id| rel.get(&id).copied()).is_empty());
+        let phases: Vec<_> = queue.requests().map(|r| r.phase).collect();
+        assert_eq!(
+            phases,
+            vec![
+                DockingPhase::Cleared(t),
+                DockingPhase::Holding,
+                DockingPhase::Holding
+            ]
+        );
+
+        // holders get distinct points well clear of the docking axis
+        let p = queue.guidance(b, 0.0).unwrap().0;
+        let q = queue.guidance(c, 0.0).unwrap().0;
+        assert!(p.distance(q) > 10.0);
+        assert!(p.x <= 1E-9 && q.x <= 1E-9);
+
+        // a rushes in and is waved off to the back of the line
+        rel.insert(
+            a,
+            PV::from_f64(DVec2::new(20.0, 0.0), DVec2::new(-5.0, 0.0)),
+        );
+        assert_eq!(
+            queue.update(t, |id| rel.get(&id).copied()),
+            vec![DockingOutcome::WavedOff(a, WaveOffReason::TooFast)]
+        );
+        queue.update(t, |id| rel.get(&id).copied());
+        assert!(matches!(
+            queue.requests().next(),
+            Some(DockingRequest {
+                vehicle,
+                phase: DockingPhase::Cleared(_),
+                ..
+            }) if *vehicle == b
+        ));
+
+        // b makes a gentle contact
+        rel.insert(b, PV::from_f64(DVec2::new(5.0, 0.0), DVec2::new(-0.2, 0.0)));
+        assert_eq!(
+            queue.update(t, |id| rel.get(&id).copied()),
+            vec![DockingOutcome::Docked(b)]
+        );
+
+        // c vanishes, and a is next
+        rel.remove(&c);
+        queue.update(t, |id| rel.get(&id).copied());
+        assert_eq!(queue.requests().count(), 1);
+        assert_eq!(queue.requests().next().unwrap().vehicle, a);
+
+        // a times out until it's dropped entirely
+        rel.insert(a, far);
+        let mut outcomes = Vec::new();
+        for i in 1..10 {
+            let late = t + CLEARANCE_TIMEOUT * 2 * i;
+            outcomes.extend(queue.update(late, |id| rel.get(&id).copied()));
+        }
+        assert_eq!(outcomes.last(), Some(&DockingOutcome::Aborted(a)));
+        assert!(queue.is_empty());
+
+        assert_eq!(approach_gate(5000.0), None);
+        assert_eq!(approach_gate(100.0), Some((HOLDING_DISTANCE, 5.0)));
+        assert_eq!(approach_gate(1.0).unwrap().1, MAX_CONTACT_SPEED);
+    }
+
+    #[test]
+    fn capture_assist_pulls_aligned_vehicles_onto_the_port() {
+        let target_angle = 0.3;
+        let mut rel = PV::from_f64(rotate_f64(DVec2::new(15.0, 2.0), target_angle), DVec2::ZERO);
+        let mut angle = target_angle + PI_64 + 0.2;
+        let mut angular_velocity = 0.0;
+
+        let far = PV::from_f64(rotate_f64(DVec2::X * 30.0, target_angle), DVec2::ZERO);
+        assert!(capture_assist(far, angle, 0.0, target_angle, 0.0).is_none());
+        assert!(capture_assist(rel, angle + 1.0, 0.0, target_angle, 0.0).is_none());
+        let behind = PV::from_f64(-rel.pos, DVec2::ZERO);
+        assert!(capture_assist(behind, angle, 0.0, target_angle, 0.0).is_none());
+
+        let dt = 0.04;
+        for _ in 0..10_000 {
+            let Some(assist) = capture_assist(rel, angle, angular_velocity, target_angle, 0.0)
+            else {
+                panic!("lost capture at {:?}", rel);
+            };
+            assert!(assist.linear.length() <= CAPTURE_MAX_ACCEL + 1E-9);
+            rel.vel += assist.linear * dt;
+            rel.pos += rel.vel * dt;
+            angular_velocity += assist.angular * dt;
+            angle += angular_velocity * dt;
+        }
+
+        assert_lt!(rel.pos.length(), DOCKING_CONTACT_DISTANCE);
+        assert_lt!(rel.vel.length(), MAX_CONTACT_SPEED);
+        assert_lt!(wrap_pi_npi_f64(angle - target_angle - PI_64).abs(), 0.01);
+    }
+
+    #[test]
+    fn rewinding_restores_docking_queues() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let mut add = |angle| {
+            universe
+                .add_surface_vehicle(planet, Vehicle::new(), angle, 100.0)
+                .unwrap()
+        };
+        let (host, a, b) = (add(0.0), add(0.1), add(0.2));
+
+        universe.request_docking(a, host).unwrap();
+        let checkpoint = universe.checkpoint();
+        assert_eq!(universe.request_docking(b, host), Some(1));
+        universe.cancel_docking(a);
+
+        universe.restore(&checkpoint);
+        let queue = universe.docking_queue_of(a).unwrap();
+        assert_eq!(queue.requests().count(), 1);
+        assert!(universe.docking_queue_of(b).is_none());
+    }
+
+    #[test]
+    fn rewinding_restores_captures() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let host = universe
+            .add_surface_vehicle(planet, Vehicle::new(), 0.0, 10_000.0)
+            .unwrap();
+        let guest = universe
+            .add_surface_vehicle(planet, Vehicle::new(), 0.0, 10_000.0)
+            .unwrap();
+        let (pv, angle) = {
+            let sv = universe.surface_vehicles.get_mut(&host).unwrap();
+            sv.capture_assist = true;
+            (sv.body.pv, sv.body.angle)
+        };
+        let sv = universe.surface_vehicles.get_mut(&guest).unwrap();
+        sv.set_target(host);
+        sv.body.pv.pos = pv.pos + rotate_f64(DVec2::new(15.0, 2.0), angle);
+        sv.body.angle = angle + PI_64 + 0.2;
+
+        universe.on_sim_tick(&ControlSignals::new());
+        assert_eq!(universe.captures.get(&guest), Some(&host));
+        let checkpoint = universe.checkpoint();
+
+        universe
+            .surface_vehicles
+            .get_mut(&guest)
+            .unwrap()
+            .set_target(None);
+        universe.on_sim_tick(&ControlSignals::new());
+        assert!(universe.captures.is_empty());
+
+        universe.restore(&checkpoint);
+        assert_eq!(universe.captures.get(&guest), Some(&host));
+    }
+}
@@ -0,0 +1,219 @@
+use crate::orbits::vis_viva_equation;
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+/// Height of the canonical low orbit over the top of a body's atmosphere,
+/// or its surface if it has none, as a fraction of that radius.
+pub const LOW_ORBIT_MARGIN: f64 = 0.05;
+
+/// A canonical place in a planetary system to travel between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DvNode {
+    /// Landed, at rest on the surface.
+    Surface(EntityId),
+    /// Circular orbit just clear of the atmosphere.
+    LowOrbit(EntityId),
+    /// On the transfer orbit from the parent's low orbit, arriving at the
+    /// edge of this body's SOI.
+    Transfer(EntityId),
+}
+
+impl DvNode {
+    pub fn body(&self) -> EntityId {
+        match self {
+            Self::Surface(id) | Self::LowOrbit(id) | Self::Transfer(id) => *id,
+        }
+    }
+}
+
+/// Ideal cost of getting from one node to a neighbouring one. Costs are
+/// impulsive, without gravity or drag losses, and the same in either
+/// direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DvLeg {
+    pub from: DvNode,
+    pub to: DvNode,
+    pub dv: f64,
+}
+
+/// Approximate delta-v costs between the canonical orbits of a planetary
+/// system. Transfers to moons are taken from the maneuver planner, so the
+/// map agrees with the plans it would make for a vehicle in low orbit.
+#[derive(Debug, Clone, Default)]
+pub struct DvMap {
+    low_orbits: BTreeMap<EntityId, SparseOrbit>,
+    legs: Vec<DvLeg>,
+}
+
+pub fn low_orbit_radius(body: &Body) -> f64 {
+    let ceiling = body.atmosphere.map(|a| a.ceiling).unwrap_or(0.0);
+    ((body.radius + ceiling) * (1.0 + LOW_ORBIT_MARGIN)).min(body.soi * 0.5)
+}
+
+/// Ideal cost of climbing from rest on the surface to a circular orbit of
+/// radius `r`, via a transfer ellipse grazing the surface.
+fn ascent_dv(body: &Body, r: f64) -> f64 {
+    let a = (body.radius + r) / 2.0;
+    let liftoff = vis_viva_equation(body.mu(), body.radius, a);
+    let circularize = vis_viva_equation(body.mu(), r, r) - vis_viva_equation(body.mu(), r, a);
+    liftoff + circularize
+}
+
+/// Cost of capturing into a circular orbit of radius `r` from a hyperbolic
+/// approach with excess speed `v_inf`, burning at periapsis.
+fn capture_dv(body: &Body, r: f64, v_inf: f64) -> f64 {
+    let mu = body.mu();
+    (v_inf.powi(2) + 2.0 * mu / r).sqrt() - (mu / r).sqrt()
+}
+
+impl DvMap {
+    pub fn new(system: &PlanetarySystem) -> Self {
+        let mut map = Self::default();
+        map.add_system(system, false);
+        map
+    }
+
+    fn add_system(&mut self, system: &PlanetarySystem, retrograde: bool) {
+        let body = system.body;
+        let r = low_orbit_radius(&body);
+        let low = SparseOrbit::circular(r, body, Nanotime::zero(), retrograde);
+        self.low_orbits.insert(system.id, low);
+        self.legs.push(DvLeg {
+            from: DvNode::Surface(system.id),
+            to: DvNode::LowOrbit(system.id),
+            dv: ascent_dv(&body, r),
+        });
+
+        for (orbit, sub) in &system.subsystems {
+            let low = SparseOrbit::circular(r, body, Nanotime::zero(), orbit.is_retrograde());
+            self.add_system(sub, orbit.is_retrograde());
+
+            let Ok(plan) = best_maneuver_plan(&low, orbit, Nanotime::zero()) else {
+                continue;
+            };
+            let mut burns = plan.dvs().map(|(_, dv)| dv.length());
+            let Some(injection) = burns.next() else {
+                continue;
+            };
+            // the planner's second burn matches the moon's velocity, which is
+            // exactly the excess speed a vehicle arrives at the moon with
+            let v_inf = burns.next().unwrap_or(0.0);
+            let r_sub = low_orbit_radius(&sub.body);
+
+            self.legs.push(DvLeg {
+                from: DvNode::LowOrbit(system.id),
+                to: DvNode::Transfer(sub.id),
+                dv: injection,
+            });
+            self.legs.push(DvLeg {
+                from: DvNode::Transfer(sub.id),
+                to: DvNode::LowOrbit(sub.id),
+                dv: capture_dv(&sub.body, r_sub, v_inf),
+            });
+        }
+    }
+
+    pub fn legs(&self) -> &[DvLeg] {
+        &self.legs
+    }
+
+    /// The canonical low orbit around a body.
+    pub fn low_orbit(&self, id: EntityId) -> Option<&SparseOrbit> {
+        self.low_orbits.get(&id)
+    }
+
+    /// Cheapest total cost of getting from one node to another.
+    pub fn cost(&self, from: DvNode, to: DvNode) -> Option<f64> {
+        let mut best: BTreeMap<DvNode, f64> = BTreeMap::new();
+        let mut open = vec![(from, 0.0_f64)];
+        best.insert(from, 0.0);
+
+        while let Some(i) = (0..open.len()).min_by(|a, b| open[*a].1.total_cmp(&open[*b].1)) {
+            let (node, cost) = open.swap_remove(i);
+            if node == to {
+                return Some(cost);
+            }
+            if best.get(&node).is_some_and(|c| *c < cost) {
+                continue;
+            }
+            for leg in &self.legs {
+                let next = if leg.from == node {
+                    leg.to
+                } else if leg.to == node {
+                    leg.from
+                } else {
+                    continue;
+                };
+                let c = cost + leg.dv;
+                if best.get(&next).is_none_or(|b| c < *b) {
+                    best.insert(next, c);
+                    open.push((next, c));
+                }
+            }
+        }
+        None
+    }
+
+    /// Cost of getting from `orbit` around `parent` to a node, by way of
+    /// the planner's transfer to the low orbit of `parent`.
+    pub fn cost_from_orbit(
+        &self,
+        orbit: &SparseOrbit,
+        parent: EntityId,
+        to: DvNode,
+        now: Nanotime,
+    ) -> Option<f64> {
+        let low = self.low_orbit(parent)?;
+        let to_low = if orbit.is_similar(low) {
+            0.0
+        } else {
+            best_maneuver_plan(orbit, low, now).ok()?.dv()
+        };
+        Some(to_low + self.cost(DvNode::LowOrbit(parent), to)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dv_map_matches_closed_form_hohmann() {
+        let system = default_example();
+        let map = DvMap::new(&system);
+        let earth = system.id;
+        let (orbit, luna) = &system.subsystems[0];
+        let luna = luna.id;
+
+        let mu = system.body.mu();
+        let r1 = low_orbit_radius(&system.body);
+        let r2 = orbit.semi_major_axis;
+        let a = (r1 + r2) / 2.0;
+        let injection = vis_viva_equation(mu, r1, a) - (mu / r1).sqrt();
+
+        let leg = map
+            .legs()
+            .iter()
+            .find(|l| l.to == DvNode::Transfer(luna))
+            .unwrap();
+        assert!((leg.dv - injection).abs() / injection < 0.01, "{:?}", leg);
+
+        // legs cost the same in either direction
+        let there = map
+            .cost(DvNode::Surface(earth), DvNode::Surface(luna))
+            .unwrap();
+        let back = map
+            .cost(DvNode::Surface(luna), DvNode::Surface(earth))
+            .unwrap();
+        assert!((there - back).abs() < 1e-6);
+        assert!(there > leg.dv);
+
+        let low = map.low_orbit(earth).unwrap();
+        let from_low = map.cost_from_orbit(low, earth, DvNode::Transfer(luna), Nanotime::zero());
+        assert!((from_low.unwrap() - leg.dv).abs() < 1e-6);
+        assert_eq!(
+            map.cost(DvNode::Surface(earth), DvNode::Surface(earth)),
+            Some(0.0)
+        );
+    }
+}
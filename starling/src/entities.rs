@@ -1,6 +1,19 @@
 use crate::prelude::*;
+use crate::propagator::PredictError;
 
-#[derive(Debug)]
+/// How far a vehicle may stray from its predicted trajectory, in meters
+/// and meters per second, before the prediction is discarded.
+const PREDICTION_POS_TOLERANCE: f64 = 1.0;
+const PREDICTION_VEL_TOLERANCE: f64 = 0.01;
+
+/// Altitude below which vehicles aren't given a reference orbit.
+pub(crate) const MIN_ORBIT_ALTITUDE: f64 = 2_000.0;
+
+/// Times to resume propagating a trajectory which crosses many spheres of
+/// influence before giving up on predicting it.
+const PREDICTION_ATTEMPTS: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct SurfaceSpacecraftEntity {
     pub planet_id: EntityId,
     pub vehicle: Vehicle,
@@ -12,7 +25,11 @@ pub struct SurfaceSpacecraftEntity {
     orbiter: Option<Orbiter>,
     altitude: Option<f64>,
     clamped_to_ground: bool,
+    aero: Option<AeroForces>,
     pub target_relative_pv: Option<PV>,
+    /// Whether the docking port gently pulls in vehicles targeting this
+    /// one once they're close and lined up.
+    pub capture_assist: bool,
 }
 
 impl SurfaceSpacecraftEntity {
@@ -33,10 +50,19 @@ impl SurfaceSpacecraftEntity {
             orbiter: None,
             altitude: None,
             clamped_to_ground: false,
+            aero: None,
             target_relative_pv: None,
+            capture_assist: true,
         }
     }
 
+    /// Replaces the reference orbit, discarding the predicted trajectory
+    /// which was based on the old one.
+    pub fn replace_orbit(&mut self, orbit: Option<SparseOrbit>) {
+        self.orbit = orbit;
+        self.orbiter = None;
+    }
+
     pub fn current_orbit(&self) -> Option<GlobalOrbit> {
         Some(GlobalOrbit(self.planet_id, self.orbit?))
     }
@@ -49,6 +75,10 @@ impl SurfaceSpacecraftEntity {
         self.vehicle = vehicle;
     }
 
+    pub fn set_name(&mut self, name: String) {
+        self.vehicle.set_name(name);
+    }
+
     pub fn parent(&self) -> EntityId {
         self.planet_id
     }
@@ -57,6 +87,16 @@ impl SurfaceSpacecraftEntity {
         self.body.pv
     }
 
+    pub fn is_landed(&self) -> bool {
+        self.clamped_to_ground
+    }
+
+    /// Air loads on the vehicle as of the last tick, if it has surfaces
+    /// which generate lift and is flying through an atmosphere.
+    pub fn aero(&self) -> Option<&AeroForces> {
+        self.aero.as_ref()
+    }
+
     pub fn target(&self) -> Option<EntityId> {
         self.target
     }
@@ -69,13 +109,81 @@ impl SurfaceSpacecraftEntity {
         self.orbiter.iter().flat_map(|o| o.props())
     }
 
+    /// Ensures the predicted trajectory covers `stamp`, starting a new
+    /// prediction from the vehicle's orbit at `now` if it has none.
+    fn predict_through(
+        &mut self,
+        now: Nanotime,
+        stamp: Nanotime,
+        planets: &PlanetarySystem,
+    ) -> Option<&Orbiter> {
+        if self.orbiter.is_none() {
+            self.orbiter = Some(Orbiter::new(self.current_orbit()?, now));
+        }
+        let orbiter = self.orbiter.as_mut()?;
+        // each call to propagate_to crosses a limited number of SOI
+        // boundaries, which long batches may exceed
+        for _ in 0..PREDICTION_ATTEMPTS {
+            let terminated = orbiter
+                .props()
+                .last()
+                .is_some_and(|p| matches!(p.horizon, HorizonState::Terminating(..)));
+            if terminated || orbiter.propagator_at(stamp).is_some() {
+                return self.orbiter.as_ref();
+            }
+            match orbiter.propagate_to(now, stamp - now, planets) {
+                Ok(()) | Err(PredictError::TooManyIterations) => (),
+                Err(_) => break,
+            }
+        }
+        self.orbiter = None;
+        None
+    }
+
+    /// Time at which the vehicle's predicted trajectory hits a body, if it
+    /// does so before `stamp`, extending the prediction from `now` as
+    /// needed.
+    pub fn impact_before(
+        &mut self,
+        now: Nanotime,
+        stamp: Nanotime,
+        planets: &PlanetarySystem,
+    ) -> Option<Nanotime> {
+        self.orbit?;
+        let t = self
+            .predict_through(now, stamp, planets)?
+            .collision_time()?;
+        (t < stamp).then_some(t)
+    }
+
+    /// Advances the vehicle by `delta_time` to `stamp` without simulating
+    /// its physics. Orbiting vehicles are moved along their predicted
+    /// trajectory, so they land exactly where the propagator puts them,
+    /// across any sphere of influence changes along the way.
     pub fn step_on_rails(
         &mut self,
         delta_time: Nanotime,
         stamp: Nanotime,
         planets: &PlanetarySystem,
     ) {
-        if let Some(pv) = &self.orbit.map(|o| o.pv(stamp).ok()).flatten() {
+        let predicted = match self.orbit {
+            Some(_) => self
+                .predict_through(stamp - delta_time, stamp, planets)
+                .and_then(|o| {
+                    // trajectories which end in an impact stop there
+                    let p = o.propagator_at(stamp).or(o.props().last())?;
+                    let t = p.end().map_or(stamp, |end| end.min(stamp));
+                    Some((p.parent(), p.orbit.1, p.pv(t)?))
+                }),
+            None => None,
+        };
+
+        let on_prediction = predicted.is_some();
+        if let Some((parent, orbit, pv)) = predicted {
+            self.planet_id = parent;
+            self.orbit = Some(orbit);
+            self.body.pv = pv;
+        } else if let Some(pv) = &self.orbit.and_then(|o| o.pv(stamp).ok()) {
             self.body.pv = *pv;
         } else {
             let accel = BodyFrameAccel {
@@ -100,6 +208,10 @@ impl SurfaceSpacecraftEntity {
         let alt = self.body.pv.pos.length() - parent_body.radius;
         self.altitude = Some(alt);
 
+        // not applied, but takes the vehicle off of rails once it reaches
+        // the atmosphere
+        self.update_aero(&parent_body, alt);
+
         if alt < 2_000.0 {
             self.orbit = None;
         }
@@ -109,7 +221,34 @@ impl SurfaceSpacecraftEntity {
             self.body.angular_velocity = 0.0;
         }
 
-        self.reparent_if_necessary(parent_pv, planets, stamp);
+        // the propagator already decides which body the vehicle orbits,
+        // and the nearest body may briefly disagree at SOI boundaries
+        if !on_prediction {
+            self.reparent_if_necessary(parent_pv, planets, stamp);
+        }
+    }
+
+    /// Works out the air loads on the vehicle, returning the acceleration
+    /// they cause. Drag is limited so that it can't reverse the vehicle's
+    /// velocity within a single tick.
+    fn update_aero(&mut self, parent_body: &Body, altitude: f64) -> DVec2 {
+        let density = parent_body.air_density(altitude);
+        let mass = self.vehicle.total_mass().to_kg_f64();
+        let mut surfaces = self.vehicle.aero_surfaces().peekable();
+        if density == 0.0 || mass == 0.0 || surfaces.peek().is_none() {
+            self.aero = None;
+            return DVec2::ZERO;
+        }
+        let forces = aero_forces(surfaces, self.body.angle, self.body.pv.vel, density);
+        self.aero = Some(forces);
+        let accel = forces.total() / mass;
+        let dv = accel.length() * PHYSICS_CONSTANT_DELTA_TIME.to_secs_f64();
+        let speed = self.body.pv.vel.length();
+        if dv > speed {
+            accel * speed / dv
+        } else {
+            accel
+        }
     }
 
     fn reparent_to(
@@ -144,7 +283,13 @@ impl SurfaceSpacecraftEntity {
         Some(())
     }
 
-    pub fn step(&mut self, planets: &PlanetarySystem, stamp: Nanotime, ext: VehicleControl) {
+    pub fn step(
+        &mut self,
+        planets: &PlanetarySystem,
+        laws: &ControlLawRegistry,
+        stamp: Nanotime,
+        ext: VehicleControl,
+    ) {
         let (parent_body, parent_pv) = match planets.lookup(self.planet_id, stamp) {
             Some((body, pv, _, _)) => (body, pv),
             None => todo!(),
@@ -172,12 +317,12 @@ impl SurfaceSpacecraftEntity {
                     VehicleControlStatus::UnderExternalControl
                 },
             ),
-            (VehicleControlPolicy::LaunchToOrbit(altitude), _) => enter_orbit_control_law(
+            (VehicleControlPolicy::LaunchToOrbit(profile), _) => enter_orbit_control_law(
                 &parent_body,
                 &self.body,
                 &self.vehicle,
                 self.orbit.as_ref(),
-                *altitude,
+                profile,
             ),
             (VehicleControlPolicy::BurnPrograde, _) => {
                 burn_along_velocity_vector_control_law(&self.body, &self.vehicle, true)
@@ -192,6 +337,16 @@ impl SurfaceSpacecraftEntity {
             (VehicleControlPolicy::PositionHold(_), _) => {
                 (VehicleControl::NULLOPT, VehicleControlStatus::Idling)
             }
+            (VehicleControlPolicy::ControlLaw(name), _) => match laws.get(name) {
+                Some(law) => law.control(&ControlContext {
+                    body: &self.body,
+                    vehicle: &self.vehicle,
+                    parent: &parent_body,
+                    orbit: self.orbit.as_ref(),
+                    gravity,
+                }),
+                None => (VehicleControl::NULLOPT, VehicleControlStatus::Idling),
+            },
         };
 
         self.controller.set_status(status);
@@ -216,9 +371,11 @@ impl SurfaceSpacecraftEntity {
         let alt = self.body.pv.pos.length() - parent_body.radius;
         self.altitude = Some(alt);
 
+        let air = self.update_aero(&parent_body, alt);
+
         let accel = self.vehicle.body_frame_accel();
         self.body
-            .on_sim_tick(accel, gravity, PHYSICS_CONSTANT_DELTA_TIME);
+            .on_sim_tick(accel, gravity + air, PHYSICS_CONSTANT_DELTA_TIME);
 
         self.clamped_to_ground = self.body.clamp_with_elevation(parent_body.radius);
 
@@ -231,6 +388,17 @@ impl SurfaceSpacecraftEntity {
         self.update_orbit(planets, alt, parent_body, stamp);
     }
 
+    /// Instantaneously changes the vehicle's velocity, as for an ideal
+    /// burn, discarding its predicted trajectory.
+    pub fn apply_impulse(&mut self, dv: DVec2, planets: &PlanetarySystem, stamp: Nanotime) {
+        let Some((parent_body, _, _, _)) = planets.lookup(self.planet_id, stamp) else {
+            return;
+        };
+        self.body.pv.vel += dv;
+        let alt = self.body.pv.pos.length() - parent_body.radius;
+        self.update_orbit(planets, alt, parent_body, stamp);
+    }
+
     fn update_orbit(
         &mut self,
         _planets: &PlanetarySystem,
@@ -238,19 +406,54 @@ impl SurfaceSpacecraftEntity {
         parent_body: Body,
         stamp: Nanotime,
     ) {
-        self.orbit = if altitude > 2_000.0 {
+        self.orbit = if altitude > MIN_ORBIT_ALTITUDE {
             SparseOrbit::from_pv(self.body.pv, parent_body, stamp)
         } else {
             None
         };
 
-        // if let Some(orbit) = self.current_orbit() {
-        //     let mut orbiter = Orbiter::new(orbit, stamp);
-        //     if let Err(e) = orbiter.propagate_to(stamp, Nanotime::days(3), planets) {
-        //         dbg!(e);
-        //     }
-        //     self.orbiter = Some(orbiter);
-        // }
+        // the prediction is kept until the vehicle drifts off of it, such
+        // as by thrusting or reparenting
+        let on_track = self.orbit.is_some()
+            && self.orbiter.as_ref().is_some_and(|o| {
+                o.propagator_at(stamp).map(|p| p.parent()) == Some(self.planet_id)
+                    && o.pvl(stamp).is_some_and(|pv| {
+                        pv.pos.distance(self.body.pv.pos) < PREDICTION_POS_TOLERANCE
+                            && pv.vel.distance(self.body.pv.vel) < PREDICTION_VEL_TOLERANCE
+                    })
+            });
+        if !on_track {
+            self.orbiter = None;
+        }
+    }
+
+    /// Extends the predicted trajectory to at least `horizon` past `stamp`.
+    /// Once extended, the trajectory isn't extended again until less than
+    /// half of the horizon remains.
+    pub fn extend_prediction(
+        &mut self,
+        stamp: Nanotime,
+        horizon: Nanotime,
+        planets: &PlanetarySystem,
+    ) -> Option<()> {
+        if self.orbiter.is_none() {
+            self.orbiter = Some(Orbiter::new(self.current_orbit()?, stamp));
+        }
+        let orbiter = self.orbiter.as_mut()?;
+        if orbiter.horizon_end()? - stamp > horizon / 2 {
+            return Some(());
+        }
+        if orbiter.propagate_to(stamp, horizon, planets).is_err() {
+            self.orbiter = None;
+            return None;
+        }
+        Some(())
+    }
+
+    /// End of the predicted trajectory, if it has been propagated and
+    /// isn't fully known.
+    pub fn prediction_horizon(&self) -> Option<Nanotime> {
+        self.orbiter.as_ref()?.horizon_end()
     }
 
     pub fn can_be_on_rails(&self) -> bool {
@@ -262,6 +465,106 @@ impl SurfaceSpacecraftEntity {
             _ => false,
         };
         let has_orbit = self.orbit.is_some();
-        is_idle && (has_orbit || self.clamped_to_ground)
+        let in_flight = self.aero.is_some() && !self.clamped_to_ground;
+        is_idle && !in_flight && (has_orbit || self.clamped_to_ground)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::rss;
+
+    #[test]
+    fn predictions_extend_lazily() {
+        let mut universe = Universe::new(rss());
+        let earth = universe.planets.id;
+        let body = universe.planets.body;
+        let radius = LUNA_ORBITAL_RADIUS * 0.95;
+        let orbit = SparseOrbit::circular(radius, body, Nanotime::zero(), false);
+        let id = universe
+            .add_orbital_vehicle(Vehicle::new(), GlobalOrbit(earth, orbit))
+            .unwrap();
+        let horizon = |u: &Universe| u.surface_vehicles[&id].prediction_horizon();
+
+        assert_eq!(horizon(&universe), None);
+
+        universe.propagate_orbiters(|_, _| Nanotime::hours(1));
+        let short = horizon(&universe).unwrap();
+        assert!(short >= Nanotime::hours(1));
+
+        universe.propagate_orbiters(|_, _| Nanotime::hours(1));
+        assert_eq!(horizon(&universe), Some(short));
+
+        universe.propagate_orbiters(|_, _| Nanotime::days(2));
+        let long = horizon(&universe).unwrap();
+        assert!(long >= Nanotime::days(2));
+    }
+
+    fn orbiting_universe(parent: &str, pv: impl Fn(Body) -> PV) -> (Universe, EntityId) {
+        let mut universe = Universe::new(rss());
+        let id = universe.lup_planet_by_name(parent).unwrap();
+        let (body, _, _, _) = universe.planets.lookup(id, Nanotime::zero()).unwrap();
+        let orbit = SparseOrbit::from_pv(pv(body), body, Nanotime::zero()).unwrap();
+        let id = universe
+            .add_orbital_vehicle(Vehicle::new(), GlobalOrbit(id, orbit))
+            .unwrap();
+        (universe, id)
+    }
+
+    #[test]
+    fn batch_ticks_agree_with_per_tick() {
+        let leo = |body: Body| {
+            let r = body.radius + 400_000.0;
+            PV::from_f64((r, 0.0), (0.0, (body.mu() / r).sqrt()))
+        };
+        let (mut per_tick, id) = orbiting_universe("Earth", leo);
+        let (mut batched, _) = orbiting_universe("Earth", leo);
+        let (mut chunked, _) = orbiting_universe("Earth", leo);
+        let pos = |u: &Universe| u.surface_vehicles[&id].pv().pos;
+
+        let ticks = 4000;
+        let signals = ControlSignals::new();
+        for _ in 0..ticks {
+            per_tick.on_sim_tick(&signals);
+        }
+        batched.run_batch_ticks(ticks);
+        for _ in 0..ticks / 100 {
+            chunked.run_batch_ticks(100);
+        }
+
+        assert_eq!(per_tick.stamp(), batched.stamp());
+        // per-tick physics integrates numerically, so it drifts from the
+        // analytic trajectory by a few meters per orbit
+        assert!(pos(&per_tick).distance(pos(&batched)) < 25.0);
+        assert!(pos(&chunked).distance(pos(&batched)) < 1E-3);
+
+        // over days of sim time, and out of the moon's SOI, the size of
+        // the batches makes no difference
+        let escape = |body: Body| {
+            let r = body.radius + 100_000.0;
+            PV::from_f64((r, 0.0), (0.0, 1.1 * (2.0 * body.mu() / r).sqrt()))
+        };
+        let (mut batched, id) = orbiting_universe("Luna", escape);
+        let (mut chunked, _) = orbiting_universe("Luna", escape);
+        let earth = batched.planets.id;
+        let ticks_per_hour = PHYSICS_CONSTANT_UPDATE_RATE * 3600;
+        batched.run_batch_ticks(ticks_per_hour * 24 * 3);
+        for _ in 0..24 * 3 {
+            chunked.run_batch_ticks(ticks_per_hour);
+        }
+
+        let (a, b) = (
+            &batched.surface_vehicles[&id],
+            &chunked.surface_vehicles[&id],
+        );
+        assert_eq!(a.parent(), earth);
+        assert_eq!(b.parent(), earth);
+        assert!(
+            a.pv().pos.distance(b.pv().pos) < 1.0,
+            "{} {}",
+            a.pv(),
+            b.pv()
+        );
     }
 }
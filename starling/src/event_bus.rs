@@ -0,0 +1,266 @@
+use crate::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+/// Number of past events kept on the bus. Subscribers which fall further
+/// behind than this miss the oldest events.
+pub const EVENT_HISTORY: usize = 1000;
+
+/// Something which happened to an entity in the universe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniverseEvent {
+    Spawned(EntityId),
+    Destroyed(EntityId),
+    /// A destroyed entity was brought back from the recycle bin.
+    Restored(EntityId),
+    SoiChange {
+        id: EntityId,
+        from: EntityId,
+        to: EntityId,
+    },
+    Landed(EntityId, TouchdownScore),
+    Docked(EntityId, EntityId),
+    /// A vehicle's approach was waved off by the target's docking traffic
+    /// control, sending it to the back of the queue.
+    WavedOff(EntityId, EntityId, WaveOffReason),
+    /// A vehicle was dropped from a docking queue after too many
+    /// wave-offs.
+    DockingAborted(EntityId, EntityId),
+    /// The scenario script's event with the given index ran.
+    Scripted(usize),
+    /// A vehicle was taken out of the simulation because it couldn't be
+    /// given a sound orbit.
+    Quarantined(EntityId, QuarantineReason),
+    /// A vehicle's scheduled maneuver plan was called off because it
+    /// wasn't lined up for a burn when the burn came due. Carries the
+    /// heading error in radians.
+    BurnAborted(EntityId, f64),
+    /// A landed vehicle picked up salvage lying near it.
+    SalvageRecovered(EntityId, Item, Mass),
+    /// A vehicle jettisoned its spent stage, which became the second
+    /// entity.
+    Staged(EntityId, EntityId),
+    /// The first vehicle's docking port latched onto one of the second's,
+    /// joining it onto the second.
+    Latched(EntityId, EntityId),
+    /// The first vehicle came away from the second, which it had been
+    /// docked to.
+    Undocked(EntityId, EntityId),
+    /// A vehicle on rendezvous autopilot made it alongside its target.
+    RendezvousAchieved(EntityId, EntityId),
+    /// A vehicle on rendezvous autopilot gave up on reaching its target.
+    RendezvousAborted(EntityId, EntityId, RendezvousAbort),
+    /// Items stopped moving from the first vehicle to the second because
+    /// the first ran out or the second filled up. Carries the mass moved.
+    TransferComplete(EntityId, EntityId, Item, Mass),
+    /// Items stopped moving from the first vehicle to the second before
+    /// either ran out of them or of room.
+    TransferAborted(EntityId, EntityId, TransferAbort),
+}
+
+impl UniverseEvent {
+    /// Describes the event, naming the entities involved with `name`, such
+    /// as by their callsigns.
+    pub fn describe(&self, name: impl Fn(EntityId) -> String) -> String {
+        match self {
+            Self::Spawned(id) => format!("{} spawned", name(*id)),
+            Self::Destroyed(id) => format!("{} destroyed", name(*id)),
+            Self::Restored(id) => format!("{} restored", name(*id)),
+            Self::SoiChange { id, from, to } => {
+                format!(
+                    "{} left SOI of {} for {}",
+                    name(*id),
+                    name(*from),
+                    name(*to)
+                )
+            }
+            Self::Landed(id, score) => format!("{} landed: {}", name(*id), score),
+            Self::Docked(id, target) => format!("{} docked with {}", name(*id), name(*target)),
+            Self::WavedOff(id, target, reason) => {
+                format!("{} waved off from {}: {}", name(*id), name(*target), reason)
+            }
+            Self::DockingAborted(id, target) => {
+                format!("{} gave up docking with {}", name(*id), name(*target))
+            }
+            Self::Scripted(i) => format!("Scripted event {} ran", i),
+            Self::Quarantined(id, reason) => format!("{} quarantined: {}", name(*id), reason),
+            Self::BurnAborted(id, error) => format!(
+                "{} aborted its maneuver: {:0.1} deg off the burn heading",
+                name(*id),
+                error.to_degrees().abs()
+            ),
+            Self::SalvageRecovered(id, item, mass) => {
+                format!("{} recovered {} of salvaged {}", name(*id), mass, item)
+            }
+            Self::Staged(id, spent) => format!("{} jettisoned stage {}", name(*id), name(*spent)),
+            Self::Latched(id, host) => format!("{} latched onto {}", name(*id), name(*host)),
+            Self::Undocked(id, host) => format!("{} undocked from {}", name(*id), name(*host)),
+            Self::RendezvousAchieved(id, target) => {
+                format!("{} made rendezvous with {}", name(*id), name(*target))
+            }
+            Self::RendezvousAborted(id, target, reason) => {
+                format!(
+                    "{} gave up rendezvous with {}: {}",
+                    name(*id),
+                    name(*target),
+                    reason
+                )
+            }
+            Self::TransferComplete(from, to, item, mass) => {
+                format!(
+                    "{} passed {} of {} to {}",
+                    name(*from),
+                    mass,
+                    item,
+                    name(*to)
+                )
+            }
+            Self::TransferAborted(from, to, reason) => {
+                format!(
+                    "Transfer from {} to {} stopped: {}",
+                    name(*from),
+                    name(*to),
+                    reason
+                )
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for UniverseEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.describe(|id| id.to_string()))
+    }
+}
+
+/// An event as published on the bus, numbered in the order in which it
+/// was emitted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StampedEvent {
+    pub seq: u64,
+    pub stamp: Nanotime,
+    pub event: UniverseEvent,
+}
+
+impl std::fmt::Display for StampedEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{} [{}] {}", self.seq, self.stamp, self.event)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriberId(u64);
+
+/// The canonical stream of entity events. Each subscriber keeps its own
+/// place in the stream, and is handed every event emitted since it last
+/// polled, so any number of consumers can read the same events
+/// independently.
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    history: VecDeque<StampedEvent>,
+    next_seq: u64,
+    cursors: HashMap<SubscriberId, u64>,
+    next_subscriber: u64,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn emit(&mut self, stamp: Nanotime, event: UniverseEvent) {
+        self.history.push_back(StampedEvent {
+            seq: self.next_seq,
+            stamp,
+            event,
+        });
+        self.next_seq += 1;
+        while self.history.len() > EVENT_HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    /// Registers a new subscriber, which will receive events emitted from
+    /// now on.
+    pub fn subscribe(&mut self) -> SubscriberId {
+        let id = SubscriberId(self.next_subscriber);
+        self.next_subscriber += 1;
+        self.cursors.insert(id, self.next_seq);
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriberId) {
+        self.cursors.remove(&id);
+    }
+
+    /// Takes every event emitted since the subscriber last polled. Unknown
+    /// subscribers receive nothing.
+    pub fn poll(&mut self, id: SubscriberId) -> Vec<StampedEvent> {
+        let Some(cursor) = self.cursors.get_mut(&id) else {
+            return Vec::new();
+        };
+        let ret: Vec<_> = self
+            .history
+            .iter()
+            .filter(|e| e.seq >= *cursor)
+            .copied()
+            .collect();
+        *cursor = self.next_seq;
+        ret
+    }
+
+    /// Up to `n` of the most recent events, oldest first.
+    pub fn recent(&self, n: usize) -> impl Iterator<Item = &StampedEvent> + use<'_> {
+        self.history
+            .iter()
+            .skip(self.history.len().saturating_sub(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscribers_read_independently() {
+        let mut bus = EventBus::new();
+        bus.emit(Nanotime::ZERO, UniverseEvent::Spawned(EntityId(1)));
+
+        let a = bus.subscribe();
+        let b = bus.subscribe();
+        bus.emit(Nanotime::secs(1), UniverseEvent::Spawned(EntityId(2)));
+        bus.emit(Nanotime::secs(2), UniverseEvent::Destroyed(EntityId(2)));
+
+        let events: Vec<_> = bus.poll(a).into_iter().map(|e| e.event).collect();
+        assert_eq!(
+            events,
+            vec![
+                UniverseEvent::Spawned(EntityId(2)),
+                UniverseEvent::Destroyed(EntityId(2)),
+            ]
+        );
+        assert!(bus.poll(a).is_empty());
+
+        bus.emit(Nanotime::secs(3), UniverseEvent::Restored(EntityId(2)));
+        assert_eq!(bus.poll(a).len(), 1);
+        assert_eq!(bus.poll(b).len(), 3);
+
+        bus.unsubscribe(b);
+        bus.emit(Nanotime::secs(4), UniverseEvent::Destroyed(EntityId(2)));
+        assert!(bus.poll(b).is_empty());
+
+        for _ in 0..EVENT_HISTORY * 2 {
+            bus.emit(Nanotime::secs(5), UniverseEvent::Spawned(EntityId(3)));
+        }
+        assert_eq!(bus.poll(a).len(), EVENT_HISTORY);
+        assert_eq!(bus.recent(10).count(), 10);
+    }
+
+    #[test]
+    fn events_name_entities_as_asked() {
+        let (a, b) = (EntityId(1), EntityId(2));
+        let event = UniverseEvent::Latched(a, b);
+        assert_eq!(event.to_string(), "1 latched onto 2");
+        let names = |id: EntityId| if id == a { "Ares" } else { "Hub" }.to_string();
+        assert_eq!(event.describe(names), "Ares latched onto Hub");
+    }
+}
@@ -1,8 +1,10 @@
+use crate::aerodynamics::Atmosphere;
+use crate::id::{EntityId, EntityKind};
 use crate::math::*;
 use crate::nanotime::Nanotime;
 use crate::orbits::{Body, SparseOrbit};
 use crate::quantities::*;
-use crate::scenario::{ObjectIdTracker, PlanetarySystem};
+use crate::scenario::PlanetarySystem;
 
 pub fn make_earth() -> Body {
     Body::with_mass(63.0, 1000.0, 15000.0)
@@ -42,12 +44,13 @@ pub fn consistency_orbits(body: Body) -> Vec<SparseOrbit> {
 }
 
 pub fn rss() -> PlanetarySystem {
-    let mut id = ObjectIdTracker::new();
-    let earth_body = Body::with_mu(EARTH_RADIUS, EARTH_MU, EARTH_SOI);
-    let mut earth = PlanetarySystem::new(id.next(), "Earth", earth_body);
+    let id = |name| EntityId::named(EntityKind::Planet, name);
+    let earth_body =
+        Body::with_mu(EARTH_RADIUS, EARTH_MU, EARTH_SOI).with_atmosphere(Atmosphere::EARTH);
+    let mut earth = PlanetarySystem::new(id("Earth"), "Earth", earth_body);
 
     let luna_body = Body::with_mu(LUNA_RADIUS, LUNA_MU, LUNA_SOI);
-    let luna = PlanetarySystem::new(id.next(), "Luna", luna_body);
+    let luna = PlanetarySystem::new(id("Luna"), "Luna", luna_body);
     let luna_orbit = SparseOrbit::circular(
         LUNA_ORBITAL_RADIUS as f64,
         earth_body,
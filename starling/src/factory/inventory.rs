@@ -70,6 +70,18 @@ impl Item {
         }
     }
 
+    /// Propellants and oxidizers.
+    pub fn is_fuel(&self) -> bool {
+        matches!(self, Item::Methane | Item::H2 | Item::O2)
+    }
+
+    pub fn is_food(&self) -> bool {
+        matches!(
+            self,
+            Item::Bread | Item::Calzones | Item::Wheat | Item::Corn | Item::Milk
+        )
+    }
+
     pub fn is_fluid(&self) -> bool {
         match self {
             Item::Iron => false,
@@ -198,6 +210,33 @@ impl std::fmt::Display for Inventory {
     }
 }
 
+impl std::fmt::Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Iron => "Iron",
+            Self::Copper => "Copper",
+            Self::Magnesium => "Magnesium",
+            Self::Silicon => "Silicon",
+            Self::Titanium => "Titanium",
+            Self::Ice => "Ice",
+            Self::Bread => "Bread",
+            Self::Water => "Water",
+            Self::Methane => "Methane",
+            Self::H2 => "Hydrogen",
+            Self::CO2 => "Carbon Dioxide",
+            Self::O2 => "Oxygen",
+            Self::People => "People",
+            Self::Calzones => "Calzones",
+            Self::Geodes => "Geodes",
+            Self::Wheat => "Wheat",
+            Self::Corn => "Corn",
+            Self::Milk => "Milk",
+            Self::Power => "Power",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 impl std::fmt::Display for ItemCount {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}/{}", self.count, self.capacity)
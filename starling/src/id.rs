@@ -1,13 +1,157 @@
+use enum_iterator::{all, Sequence};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What sort of thing an ID refers to. Each kind has its own run of
+/// indices, kept in the upper half of the ID, so that vehicles, sites and
+/// groups never collide with each other or with scenario-defined bodies.
+///
+/// The discriminants are the tags written into saved IDs, so they must
+/// never change. New kinds take a new value.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Sequence, Deserialize, Serialize,
+)]
+pub enum EntityKind {
+    /// IDs written before kinds existed, which are a bare counter.
+    Legacy = 0,
+    Planet = 1,
+    Vehicle = 2,
+    Site = 3,
+    Structure = 4,
+    Waypoint = 5,
+    Group = 6,
+}
+
+impl EntityKind {
+    fn tag(&self) -> i64 {
+        *self as i64
+    }
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            Self::Legacy => "",
+            Self::Planet => "P",
+            Self::Vehicle => "V",
+            Self::Site => "S",
+            Self::Structure => "B",
+            Self::Waypoint => "W",
+            Self::Group => "G",
+        }
+    }
+}
 
 #[derive(
     Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, Hash,
 )]
 pub struct EntityId(pub i64);
 
+impl EntityId {
+    pub fn new(kind: EntityKind, index: u32) -> Self {
+        Self((kind.tag() << 32) | index as i64)
+    }
+
+    /// An ID derived from a name, so that the same definition gets the
+    /// same ID no matter the order things are loaded in.
+    pub fn named(kind: EntityKind, name: &str) -> Self {
+        // FNV-1a, which unlike the std hasher is stable across builds
+        let hash = name.bytes().fold(0x811c9dc5u32, |h, b| {
+            (h ^ b as u32).wrapping_mul(0x01000193)
+        });
+        Self::new(kind, hash)
+    }
+
+    /// The kind encoded in the ID. Unrecognized tags are treated as
+    /// legacy IDs.
+    pub fn kind(&self) -> EntityKind {
+        let tag = self.0 >> 32;
+        all::<EntityKind>()
+            .find(|k| k.tag() == tag)
+            .unwrap_or(EntityKind::Legacy)
+    }
+
+    pub fn index(&self) -> u32 {
+        self.0 as u32
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.kind() == EntityKind::Legacy
+    }
+}
+
 impl std::fmt::Display for EntityId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match self.kind() {
+            EntityKind::Legacy => write!(f, "{}", self.0),
+            k => write!(f, "{}-{}", k.prefix(), self.index()),
+        }
+    }
+}
+
+/// Parses IDs as displayed, like `V-12`, or bare legacy numbers.
+impl std::str::FromStr for EntityId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || format!("\"{}\" isn't an entity ID", s);
+        let Some((prefix, index)) = s.split_once('-') else {
+            return s.parse().map(EntityId).map_err(|_| bad());
+        };
+        let kind = all::<EntityKind>()
+            .find(|k| k != &EntityKind::Legacy && k.prefix().eq_ignore_ascii_case(prefix))
+            .ok_or_else(bad)?;
+        let index = index.parse().map_err(|_| bad())?;
+        Ok(EntityId::new(kind, index))
+    }
+}
+
+/// Hands out IDs, counting separately for each kind.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct IdAllocator {
+    next: HashMap<EntityKind, u32>,
+}
+
+impl IdAllocator {
+    pub fn next(&mut self, kind: EntityKind) -> EntityId {
+        let n = self.next.entry(kind).or_default();
+        let id = EntityId::new(kind, *n);
+        *n += 1;
+        id
+    }
+
+    /// Makes sure the given ID, such as one read from a save, is never
+    /// handed out again.
+    pub fn reserve(&mut self, id: EntityId) {
+        let n = self.next.entry(id.kind()).or_default();
+        *n = (*n).max(id.index().saturating_add(1));
+    }
+}
+
+/// Translation from old IDs to current ones, for data written by earlier
+/// versions or by another universe. IDs without an entry are kept as is.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IdRemap(HashMap<EntityId, EntityId>);
+
+impl IdRemap {
+    pub fn insert(&mut self, from: EntityId, to: EntityId) {
+        if from != to {
+            self.0.insert(from, to);
+        }
+    }
+
+    pub fn get(&self, id: EntityId) -> EntityId {
+        self.0.get(&id).copied().unwrap_or(id)
+    }
+
+    pub fn apply(&self, id: &mut EntityId) {
+        *id = self.get(*id);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
     }
 }
 
@@ -45,3 +189,60 @@ impl ObjectId {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_get_separate_counters_and_legacy_ids_remap() {
+        let mut ids = IdAllocator::default();
+        let v = ids.next(EntityKind::Vehicle);
+        let g = ids.next(EntityKind::Group);
+        assert_eq!((v.index(), g.index()), (0, 0));
+        assert_ne!(v, g);
+        assert_eq!(v.kind(), EntityKind::Vehicle);
+        assert_eq!(v.to_string(), "V-0");
+        assert_eq!("v-0".parse(), Ok(v));
+        assert_eq!("1002".parse(), Ok(EntityId(1002)));
+        assert!("X-1".parse::<EntityId>().is_err());
+
+        ids.reserve(EntityId::new(EntityKind::Vehicle, 10));
+        assert_eq!(ids.next(EntityKind::Vehicle).index(), 11);
+        assert_eq!(ids.next(EntityKind::Group).index(), 1);
+
+        let legacy = EntityId(1002);
+        assert!(legacy.is_legacy());
+        assert_eq!(legacy.to_string(), "1002");
+
+        let earth = EntityId::named(EntityKind::Planet, "Earth");
+        assert_eq!(earth, EntityId::named(EntityKind::Planet, "Earth"));
+        assert_ne!(earth, EntityId::named(EntityKind::Planet, "Luna"));
+        assert_eq!(earth.kind(), EntityKind::Planet);
+
+        let mut remap = IdRemap::default();
+        remap.insert(EntityId(900), earth);
+        let mut id = EntityId(900);
+        remap.apply(&mut id);
+        assert_eq!(id, earth);
+        assert_eq!(remap.get(legacy), legacy);
+    }
+
+    #[test]
+    fn kind_tags_are_pinned() {
+        let tags: Vec<_> = all::<EntityKind>().map(|k| (k, k.tag())).collect();
+        assert_eq!(
+            tags,
+            vec![
+                (EntityKind::Legacy, 0),
+                (EntityKind::Planet, 1),
+                (EntityKind::Vehicle, 2),
+                (EntityKind::Site, 3),
+                (EntityKind::Structure, 4),
+                (EntityKind::Waypoint, 5),
+                (EntityKind::Group, 6),
+            ]
+        );
+        assert_eq!(EntityId::new(EntityKind::Vehicle, 7).0, (2 << 32) | 7);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::math::*;
+use crate::nanotime::Nanotime;
+use crate::orbits::Body;
+use crate::pv::PV;
+
+/// Bounds on the integration step, in seconds. Steps shrink as the ground
+/// approaches, so that the impact point is found precisely.
+const MIN_STEP: f64 = 0.01;
+const MAX_STEP: f64 = 10.0;
+
+/// Fraction of the time it would take to fall straight to the ground at
+/// the current speed taken as each step.
+const STEP_FRACTION: f64 = 0.05;
+
+/// Most points kept along the predicted path.
+const PATH_POINTS: usize = 64;
+
+/// Where and how fast a falling vehicle will hit the ground, relative to
+/// the center of the body it's falling onto.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpactPrediction {
+    pub time_to_impact: Nanotime,
+    /// Point on the surface where the vehicle hits.
+    pub pos: DVec2,
+    pub vel: DVec2,
+    /// Positions along the way down, starting from the vehicle.
+    pub path: Vec<DVec2>,
+}
+
+impl ImpactPrediction {
+    pub fn speed(&self) -> f64 {
+        self.vel.length()
+    }
+}
+
+fn step(pv: PV, body: &Body, dt: f64) -> PV {
+    let a0 = body.gravity(pv.pos);
+    let pos = pv.pos + pv.vel * dt + a0 * 0.5 * dt * dt;
+    let vel = pv.vel + (a0 + body.gravity(pos)) * 0.5 * dt;
+    PV { pos, vel }
+}
+
+/// Follows a vehicle falling freely under the body's gravity until it
+/// hits the surface, if it does so within the horizon. Unlike a sparse
+/// orbit, this works for slow hops which never get anywhere near orbit.
+pub fn predict_impact(pv: PV, body: &Body, horizon: Nanotime) -> Option<ImpactPrediction> {
+    let horizon = horizon.to_secs_f64();
+    let altitude = |pv: &PV| pv.pos.length() - body.radius;
+    let mut pv = pv;
+    let mut t = 0.0;
+    let mut path = vec![pv.pos];
+
+    while t < horizon {
+        let alt = altitude(&pv);
+        let dt =
+            (STEP_FRACTION * alt.max(0.0) / pv.vel.length().max(1.0)).clamp(MIN_STEP, MAX_STEP);
+        let next = step(pv, body, dt);
+        let next_alt = altitude(&next);
+        if next_alt <= 0.0 {
+            let f = (alt / (alt - next_alt)).clamp(0.0, 1.0);
+            let pos = pv.pos.lerp(next.pos, f).normalize_or_zero() * body.radius;
+            path.push(pos);
+            let stride = path.len().div_ceil(PATH_POINTS).max(1);
+            let last = path.len() - 1;
+            let path = path
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| i % stride == 0 || *i == last)
+                .map(|(_, p)| p)
+                .collect();
+            return Some(ImpactPrediction {
+                time_to_impact: Nanotime::secs_f64(t + f * dt),
+                pos,
+                vel: pv.vel.lerp(next.vel, f),
+                path,
+            });
+        }
+        pv = next;
+        t += dt;
+        path.push(pv.pos);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falling_objects_hit_the_ground() {
+        let body = Body::LUNA;
+        let g = body.gravity(DVec2::new(0.0, body.radius)).length();
+        let h = 1000.0;
+        let drop = PV {
+            pos: DVec2::new(0.0, body.radius + h),
+            vel: DVec2::ZERO,
+        };
+        let impact = predict_impact(drop, &body, Nanotime::secs(600)).unwrap();
+        let t = impact.time_to_impact.to_secs_f64();
+        assert!((t - (2.0 * h / g).sqrt()).abs() < 0.5);
+        assert!((impact.speed() - (2.0 * g * h).sqrt()).abs() < 1.0);
+        assert!(impact.pos.distance(DVec2::new(0.0, body.radius)) < 1.0);
+        assert!(impact.path.len() <= PATH_POINTS + 1);
+
+        // a hop lands downrange, later than the drop
+        let hop = PV {
+            pos: drop.pos,
+            vel: DVec2::new(50.0, 20.0),
+        };
+        let landing = predict_impact(hop, &body, Nanotime::secs(600)).unwrap();
+        assert!(landing.pos.x > 0.0);
+        assert!(landing.time_to_impact > impact.time_to_impact);
+
+        // nothing comes down from a circular orbit
+        let r = body.radius + 100_000.0;
+        let orbiting = PV {
+            pos: DVec2::new(0.0, r),
+            vel: DVec2::new((body.mu() / r).sqrt(), 0.0),
+        };
+        assert_eq!(predict_impact(orbiting, &body, Nanotime::secs(3600)), None);
+    }
+}
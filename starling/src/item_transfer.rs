@@ -0,0 +1,228 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How close two vehicles must be to pass items between them, in meters.
+pub const TRANSFER_RANGE: f64 = 50.0;
+
+/// Fastest two vehicles may drift relative to one another and still pass
+/// items between them, in meters per second.
+pub const TRANSFER_MAX_SPEED: f64 = 1.0;
+
+/// Mass passed from one vehicle to the other each second of sim time, in
+/// kilograms.
+pub const TRANSFER_RATE: f64 = 100.0;
+
+/// Why items stopped moving between two vehicles before either ran out of
+/// them or of room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferAbort {
+    VehicleLost,
+    OutOfRange,
+}
+
+impl std::fmt::Display for TransferAbort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VehicleLost => write!(f, "vehicle is gone"),
+            Self::OutOfRange => write!(f, "vehicles drifted apart"),
+        }
+    }
+}
+
+/// Whether two vehicles about the same body are close enough, and
+/// drifting slowly enough, to pass items between them.
+pub fn in_transfer_range(a: PV, b: PV) -> bool {
+    a.pos.distance(b.pos) < TRANSFER_RANGE && a.vel.distance(b.vel) < TRANSFER_MAX_SPEED
+}
+
+/// An item being passed from one vehicle to another alongside it, a
+/// little each tick, until the source runs out or the destination fills
+/// up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemTransfer {
+    source: EntityId,
+    destination: EntityId,
+    item: Item,
+    moved: Mass,
+}
+
+impl ItemTransfer {
+    pub fn new(source: EntityId, destination: EntityId, item: Item) -> Self {
+        Self {
+            source,
+            destination,
+            item,
+            moved: Mass::ZERO,
+        }
+    }
+
+    pub fn source(&self) -> EntityId {
+        self.source
+    }
+
+    pub fn destination(&self) -> EntityId {
+        self.destination
+    }
+
+    pub fn item(&self) -> Item {
+        self.item
+    }
+
+    /// Mass passed across so far.
+    pub fn moved(&self) -> Mass {
+        self.moved
+    }
+
+    /// Whether the transfer is between the given vehicles, either way.
+    pub fn involves(&self, a: EntityId, b: EntityId) -> bool {
+        (self.source, self.destination) == (a, b) || (self.source, self.destination) == (b, a)
+    }
+
+    /// Moves as much of the item as `dt` seconds allow, limited by what
+    /// the source holds and the room the destination has for it. Returns
+    /// true once the source has run out or the destination is full.
+    pub fn step(&mut self, source: &mut Vehicle, destination: &mut Vehicle, dt: f64) -> bool {
+        let mass = Mass::from_kg_f32((TRANSFER_RATE * dt) as f32)
+            .clamp(Mass::ZERO, source.item_mass(self.item))
+            .clamp(Mass::ZERO, destination.room_for(self.item));
+        let taken = source.unload(self.item, mass);
+        let stowed = destination.stow(self.item, taken);
+        // whatever didn't fit after all goes back where it came from
+        if stowed < taken {
+            source.stow(self.item, taken - stowed);
+        }
+        self.moved += stowed;
+        stowed == Mass::ZERO
+            || source.item_mass(self.item) == Mass::ZERO
+            || destination.room_for(self.item) == Mass::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{test_tanker, universe_in_leo};
+    use std::collections::HashMap;
+
+    #[test]
+    fn fuel_moves_at_the_transfer_rate_until_the_destination_is_full() {
+        let mut source = test_tanker(1.0);
+        let mut destination = test_tanker(0.5);
+        let mut transfer = ItemTransfer::new(EntityId(1), EntityId(2), Item::Methane);
+        let total = source.total_mass() + destination.total_mass();
+
+        assert!(!transfer.step(&mut source, &mut destination, 1.0));
+        assert_eq!(transfer.moved(), Mass::kilograms(100));
+        assert_eq!(source.item_mass(Item::Methane), Mass::kilograms(900));
+        assert_eq!(destination.item_mass(Item::Methane), Mass::kilograms(600));
+
+        for _ in 0..3 {
+            assert!(!transfer.step(&mut source, &mut destination, 1.0));
+        }
+        assert!(transfer.step(&mut source, &mut destination, 1.0));
+        assert_eq!(transfer.moved(), Mass::kilograms(500));
+        assert_eq!(source.item_mass(Item::Methane), Mass::kilograms(500));
+        assert_eq!(destination.room_for(Item::Methane), Mass::ZERO);
+        assert_eq!(source.total_mass() + destination.total_mass(), total);
+
+        // nothing moves when the source has none of the item
+        let mut oxygen = ItemTransfer::new(EntityId(2), EntityId(1), Item::O2);
+        assert!(oxygen.step(&mut destination, &mut source, 1.0));
+        assert_eq!(oxygen.moved(), Mass::ZERO);
+    }
+
+    #[test]
+    fn vehicles_alongside_pass_fuel_until_one_drifts_off() {
+        let (mut universe, orbit) = universe_in_leo();
+        let source = universe
+            .add_orbital_vehicle(test_tanker(1.0), orbit)
+            .unwrap();
+        let destination = universe
+            .add_orbital_vehicle(test_tanker(0.0), orbit)
+            .unwrap();
+        let events = universe.events.subscribe();
+
+        assert!(universe
+            .start_transfer(source, source, Item::Methane)
+            .is_err());
+        assert!(universe
+            .start_transfer(destination, source, Item::Methane)
+            .is_err());
+        universe
+            .start_transfer(source, destination, Item::Methane)
+            .unwrap();
+
+        universe.run_batch_ticks(60);
+        let moved = universe.surface_vehicles[&destination]
+            .vehicle
+            .item_mass(Item::Methane);
+        assert!(moved > Mass::ZERO && moved < Mass::kilograms(1000));
+
+        universe.run_batch_ticks(1000);
+        assert!(universe.transfers.is_empty());
+        assert_eq!(
+            universe.surface_vehicles[&destination]
+                .vehicle
+                .item_mass(Item::Methane),
+            Mass::kilograms(1000)
+        );
+        let done = UniverseEvent::TransferComplete(
+            source,
+            destination,
+            Item::Methane,
+            Mass::kilograms(1000),
+        );
+        assert!(universe.events.poll(events).iter().any(|e| e.event == done));
+        assert_eq!(
+            done.to_string(),
+            format!("{} passed 1.0 t of Methane to {}", source, destination)
+        );
+
+        universe
+            .start_transfer(destination, source, Item::Methane)
+            .unwrap();
+        let sv = universe.surface_vehicles.get_mut(&source).unwrap();
+        sv.body.pv.pos += DVec2::X * TRANSFER_RANGE * 2.0;
+        universe.on_sim_tick(&ControlSignals::new());
+        assert!(universe.transfers.is_empty());
+        let aborted =
+            UniverseEvent::TransferAborted(destination, source, TransferAbort::OutOfRange);
+        assert!(universe
+            .events
+            .poll(events)
+            .iter()
+            .any(|e| e.event == aborted));
+    }
+
+    #[test]
+    fn transfers_carry_on_after_loading() {
+        let (mut universe, orbit) = universe_in_leo();
+        let source = universe
+            .add_orbital_vehicle(test_tanker(1.0), orbit)
+            .unwrap();
+        let destination = universe
+            .add_orbital_vehicle(test_tanker(0.0), orbit)
+            .unwrap();
+        let parts: HashMap<_, _> = test_tanker(0.0)
+            .parts()
+            .map(|(_, p)| (p.prototype().sprite_path().to_string(), p.prototype()))
+            .collect();
+        universe
+            .start_transfer(source, destination, Item::Methane)
+            .unwrap();
+        universe.run_batch_ticks(60);
+
+        let (mut loaded, _) =
+            Universe::from_file_storage(universe.to_file_storage(), &parts).unwrap();
+        assert_eq!(loaded.transfers.len(), 1);
+        assert_eq!(loaded.transfers[0].moved(), universe.transfers[0].moved());
+        loaded.run_batch_ticks(1000);
+        assert!(loaded.transfers.is_empty());
+        assert_eq!(
+            loaded.surface_vehicles[&destination]
+                .vehicle
+                .item_mass(Item::Methane),
+            Mass::kilograms(1000)
+        );
+    }
+}
@@ -0,0 +1,495 @@
+use crate::prelude::*;
+use std::collections::VecDeque;
+
+/// Horizontal widening of the approach corridor per meter of altitude.
+pub const CORRIDOR_SLOPE: f64 = 0.5;
+
+/// Development needed to reach each site level above an outpost.
+const LEVEL_THRESHOLDS: [f64; 3] = [100.0, 400.0, 1000.0];
+
+const LEVEL_NAMES: [&str; 4] = ["Outpost", "Settlement", "Town", "City"];
+
+/// Refueling rate in kilograms per second at each site level.
+const REFUEL_RATES: [f64; 4] = [0.0, 5.0, 20.0, 50.0];
+
+/// Services a landing site offers to vehicles parked on its pad. Each is
+/// unlocked once the site reaches the corresponding level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiteService {
+    Refueling,
+    Repair,
+    Market,
+}
+
+impl SiteService {
+    pub const ALL: [SiteService; 3] = [Self::Refueling, Self::Repair, Self::Market];
+
+    pub fn required_level(&self) -> usize {
+        match self {
+            Self::Refueling => 1,
+            Self::Repair => 2,
+            Self::Market => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for SiteService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Refueling => write!(f, "Refueling"),
+            Self::Repair => write!(f, "Repair"),
+            Self::Market => write!(f, "Market"),
+        }
+    }
+}
+
+/// A designated touchdown zone on the surface of a planet. The pad is
+/// centered at `angle` radians around the planet, and extends `width`
+/// meters along the surface.
+///
+/// Each pad is also a settlement which grows as vehicles deliver to it,
+/// and which keeps one service drone per level to work through its queue
+/// of service tasks.
+#[derive(Debug, Clone)]
+pub struct LandingPad {
+    pub name: String,
+    pub planet_id: EntityId,
+    pub angle: f64,
+    pub width: f64,
+    pub development: f64,
+    pub deliveries: u32,
+    pub drones: Vec<ServiceDrone>,
+    pub tasks: VecDeque<ServiceTask>,
+    /// Number of buildings put up around the site so far.
+    pub structures_built: usize,
+}
+
+impl LandingPad {
+    pub fn new(name: impl Into<String>, planet_id: EntityId, angle: f64, width: f64) -> Self {
+        Self {
+            name: name.into(),
+            planet_id,
+            angle: wrap_0_2pi_f64(angle),
+            width,
+            development: 0.0,
+            deliveries: 0,
+            drones: Vec::new(),
+            tasks: VecDeque::new(),
+            structures_built: 0,
+        }
+    }
+
+    /// Records a vehicle landing on this pad, growing the site by the
+    /// touchdown score.
+    pub fn record_delivery(&mut self, points: u32) {
+        self.deliveries += 1;
+        self.development += points as f64;
+    }
+
+    pub fn level(&self) -> usize {
+        LEVEL_THRESHOLDS
+            .iter()
+            .take_while(|t| self.development >= **t)
+            .count()
+    }
+
+    pub fn level_name(&self) -> &'static str {
+        LEVEL_NAMES[self.level()]
+    }
+
+    pub fn population(&self) -> u32 {
+        12 + (self.development * 4.0) as u32
+    }
+
+    /// Fraction of the way to the next level, or None at the top level.
+    pub fn level_progress(&self) -> Option<f64> {
+        let level = self.level();
+        let next = *LEVEL_THRESHOLDS.get(level)?;
+        let prev = level
+            .checked_sub(1)
+            .map(|i| LEVEL_THRESHOLDS[i])
+            .unwrap_or(0.0);
+        Some((self.development - prev) / (next - prev))
+    }
+
+    pub fn has_service(&self, service: SiteService) -> bool {
+        self.level() >= service.required_level()
+    }
+
+    pub fn services(&self) -> impl Iterator<Item = SiteService> + use<'_> {
+        SiteService::ALL
+            .into_iter()
+            .filter(|s| self.has_service(*s))
+    }
+
+    /// Rate at which landed vehicles are refueled, in kilograms per second.
+    pub fn refuel_rate(&self) -> f64 {
+        REFUEL_RATES[self.level()]
+    }
+
+    pub fn drone_count(&self) -> usize {
+        self.level()
+    }
+
+    /// Tasks being worked on by drones, in drone order.
+    pub fn active_tasks(&self) -> impl Iterator<Item = ServiceTask> + use<'_> {
+        self.drones.iter().filter_map(|d| d.task())
+    }
+
+    /// Adds a task to the back of the queue. Returns false if the site
+    /// doesn't offer the service, or the task is already queued or active.
+    pub fn queue_task(&mut self, task: ServiceTask) -> bool {
+        if !self.has_service(task.service())
+            || self.tasks.contains(&task)
+            || self.active_tasks().any(|t| t == task)
+        {
+            return false;
+        }
+        self.tasks.push_back(task);
+        true
+    }
+
+    pub fn cancel_task(&mut self, index: usize) -> Option<ServiceTask> {
+        self.tasks.remove(index)
+    }
+
+    /// Whether any drones are working or have work waiting.
+    pub fn is_busy(&self) -> bool {
+        !self.tasks.is_empty() || self.drones.iter().any(|d| !d.is_idle())
+    }
+
+    /// Builds drones up to the site's allowance, lined up in a hangar
+    /// beside the pad, and hands queued tasks to idle drones.
+    pub fn dispatch_drones(&mut self) {
+        while self.drones.len() < self.drone_count() {
+            let x = self.width / 2.0 + 4.0 + 3.0 * self.drones.len() as f64;
+            self.drones.push(ServiceDrone::new(DVec2::new(x, 1.0)));
+        }
+        for drone in &mut self.drones {
+            if !drone.is_idle() {
+                continue;
+            }
+            match self.tasks.pop_front() {
+                Some(task) => drone.assign(task),
+                None => break,
+            }
+        }
+    }
+
+    /// Position in the site's frame, as used by its drones, of a point
+    /// relative to the planet center.
+    pub fn site_position(&self, pos: DVec2, radius: f64) -> DVec2 {
+        DVec2::new(self.surface_offset(pos, radius), pos.length() - radius)
+    }
+
+    /// Position relative to the planet center of a point in the site's
+    /// frame.
+    pub fn from_site_position(&self, p: DVec2, radius: f64) -> DVec2 {
+        let r = radius + p.y;
+        rotate_f64(DVec2::X * r, self.angle + p.x / radius)
+    }
+
+    /// Position of the pad center, relative to the planet center.
+    pub fn local_position(&self, radius: f64) -> DVec2 {
+        rotate_f64(DVec2::X * radius, self.angle)
+    }
+
+    /// Signed distance along the surface from the pad center to the
+    /// point directly below `pos`, which is relative to the planet center.
+    pub fn surface_offset(&self, pos: DVec2, radius: f64) -> f64 {
+        wrap_pi_npi_f64(pos.to_angle() - self.angle) * radius
+    }
+
+    pub fn contains(&self, pos: DVec2, radius: f64) -> bool {
+        self.surface_offset(pos, radius).abs() <= self.width / 2.0
+    }
+
+    /// Endpoints of the left and right walls of the approach corridor,
+    /// which widens with altitude up to `height` meters above the pad.
+    pub fn corridor(&self, radius: f64, height: f64) -> [(DVec2, DVec2); 2] {
+        let up = rotate_f64(DVec2::X, self.angle);
+        let side = up.perp();
+        let center = self.local_position(radius);
+        let spread = self.width / 2.0 + height * CORRIDOR_SLOPE;
+        let w = self.width / 2.0;
+        [
+            (center - side * w, center - side * spread + up * height),
+            (center + side * w, center + side * spread + up * height),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchdownScore {
+    pub pad: Option<EntityId>,
+    pub speed: f64,
+    pub tilt: f64,
+    pub offset: f64,
+    pub occupied: bool,
+}
+
+impl TouchdownScore {
+    pub fn new(
+        pad: Option<(EntityId, &LandingPad)>,
+        body: &RigidBody,
+        radius: f64,
+        occupied: bool,
+    ) -> Self {
+        let tilt = wrap_pi_npi_f64(body.angle - body.pv.pos.to_angle()).abs();
+        Self {
+            pad: pad.map(|(id, _)| id),
+            speed: body.pv.vel.length(),
+            tilt,
+            offset: pad
+                .map(|(_, p)| p.surface_offset(body.pv.pos, radius).abs() / (p.width / 2.0))
+                .unwrap_or(1.0),
+            occupied,
+        }
+    }
+
+    /// The pad this landing counts toward, if any. A vehicle which sets
+    /// down on a pad someone else is parked on is turned away: it isn't
+    /// serviced there and doesn't grow the site.
+    pub fn accepted_pad(&self) -> Option<EntityId> {
+        self.pad.filter(|_| !self.occupied)
+    }
+
+    /// Score out of 100. Landing off-pad or on an occupied pad scores zero.
+    pub fn points(&self) -> u32 {
+        if self.pad.is_none() || self.occupied {
+            return 0;
+        }
+        let speed_penalty = (self.speed * 10.0).min(50.0);
+        let tilt_penalty = self.tilt.to_degrees().min(25.0);
+        let offset_penalty = self.offset.clamp(0.0, 1.0) * 25.0;
+        (100.0 - speed_penalty - tilt_penalty - offset_penalty).max(0.0) as u32
+    }
+
+    pub fn grade(&self) -> &'static str {
+        if self.pad.is_none() {
+            return "Off-pad";
+        } else if self.occupied {
+            return "Pad occupied";
+        }
+        match self.points() {
+            90.. => "Perfect",
+            70..90 => "Good",
+            40..70 => "Hard",
+            _ => "Rough",
+        }
+    }
+}
+
+impl std::fmt::Display for TouchdownScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}/100): {:.2} m/s, {:.1} deg tilt, {:.0}% off center",
+            self.grade(),
+            self.points(),
+            self.speed,
+            self.tilt.to_degrees(),
+            self.offset * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touchdown_scoring() {
+        let radius = 1000.0;
+        let pad = LandingPad::new("Pad", EntityId(0), PI_64 / 2.0, 20.0);
+        let center = pad.local_position(radius);
+        assert!(pad.contains(center, radius));
+        assert!(!pad.contains(rotate_f64(center, 0.02), radius));
+
+        let mut body = RigidBody::ZERO;
+        body.pv = PV::from_f64(center, DVec2::ZERO);
+        body.angle = PI_64 / 2.0;
+
+        let perfect = TouchdownScore::new(Some((EntityId(1), &pad)), &body, radius, false);
+        assert_eq!(perfect.points(), 100);
+
+        body.pv.vel = DVec2::new(0.0, -3.0);
+        let hard = TouchdownScore::new(Some((EntityId(1), &pad)), &body, radius, false);
+        assert_eq!(hard.points(), 70);
+
+        let occupied = TouchdownScore::new(Some((EntityId(1), &pad)), &body, radius, true);
+        assert_eq!(occupied.points(), 0);
+
+        let off_pad = TouchdownScore::new(None, &body, radius, false);
+        assert_eq!(off_pad.points(), 0);
+    }
+
+    #[test]
+    fn sites_grow_with_deliveries() {
+        let mut pad = LandingPad::new("Pad", EntityId(0), 0.0, 20.0);
+        assert_eq!(pad.level_name(), "Outpost");
+        assert_eq!(pad.refuel_rate(), 0.0);
+        assert_eq!(pad.services().count(), 0);
+
+        pad.record_delivery(60);
+        assert_eq!(pad.level(), 0);
+        assert_eq!(pad.level_progress(), Some(0.6));
+
+        pad.record_delivery(90);
+        assert_eq!(pad.level(), 1);
+        assert!(pad.has_service(SiteService::Refueling));
+        assert!(!pad.has_service(SiteService::Repair));
+        assert!(pad.refuel_rate() > 0.0);
+
+        for _ in 0..10 {
+            pad.record_delivery(100);
+        }
+        assert_eq!(pad.deliveries, 12);
+        assert_eq!(pad.level_name(), "City");
+        assert_eq!(pad.level_progress(), None);
+        assert_eq!(pad.services().collect::<Vec<_>>(), SiteService::ALL);
+    }
+
+    #[test]
+    fn drones_work_through_task_queue() {
+        let mut pad = LandingPad::new("Pad", EntityId(0), 0.0, 20.0);
+        let refuel = ServiceTask::Refuel(EntityId(1));
+        assert!(!pad.queue_task(refuel));
+
+        pad.record_delivery(150);
+        assert!(pad.queue_task(refuel));
+        assert!(!pad.queue_task(refuel));
+        assert!(pad.queue_task(ServiceTask::Refuel(EntityId(2))));
+
+        pad.dispatch_drones();
+        assert_eq!(pad.drones.len(), 1);
+        assert_eq!(pad.active_tasks().collect::<Vec<_>>(), [refuel]);
+        assert_eq!(pad.tasks.len(), 1);
+        assert!(!pad.queue_task(refuel));
+
+        pad.drones[0].finish();
+        pad.dispatch_drones();
+        assert_eq!(pad.tasks.len(), 0);
+        pad.drones[0].finish();
+        assert!(!pad.is_busy());
+
+        let p = DVec2::new(3.0, 7.0);
+        let world = pad.from_site_position(p, 1000.0);
+        assert!(pad.site_position(world, 1000.0).distance(p) < 1E-9);
+    }
+
+    #[test]
+    fn rewinding_restores_site_growth_and_drones() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let id = universe
+            .add_landing_pad(LandingPad::new("Pad", planet, 0.0, 20.0))
+            .unwrap();
+        let checkpoint = universe.checkpoint();
+
+        let pad = universe.landing_pads.get_mut(&id).unwrap();
+        pad.record_delivery(150);
+        pad.queue_task(ServiceTask::Refuel(EntityId(1)));
+        pad.dispatch_drones();
+        assert_eq!(pad.drones.len(), 1);
+
+        universe.restore(&checkpoint);
+        let pad = &universe.landing_pads[&id];
+        assert_eq!(pad.deliveries, 0);
+        assert_eq!(pad.development, 0.0);
+        assert!(pad.drones.is_empty());
+        assert!(!pad.is_busy());
+    }
+
+    /// Drops an empty vehicle onto the surface at angle zero, and waits
+    /// for it to settle.
+    fn land(universe: &mut Universe, planet: EntityId) -> EntityId {
+        let id = universe
+            .add_surface_vehicle(planet, Vehicle::new(), 0.0, 1.0)
+            .unwrap();
+        universe.surface_vehicles.get_mut(&id).unwrap().body.pv.vel = DVec2::ZERO;
+        for _ in 0..1000 {
+            universe.on_sim_tick(&ControlSignals::new());
+            if universe.surface_vehicles[&id].is_landed() {
+                break;
+            }
+        }
+        id
+    }
+
+    #[test]
+    fn landings_on_occupied_pads_are_turned_away() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let pad = universe
+            .add_landing_pad(LandingPad::new("Pad", planet, 0.0, 20.0))
+            .unwrap();
+        let other = universe
+            .add_landing_pad(LandingPad::new("Other", planet, 0.5, 20.0))
+            .unwrap();
+        universe
+            .landing_pads
+            .get_mut(&pad)
+            .unwrap()
+            .record_delivery(150);
+
+        let serving = |universe: &Universe, id| {
+            let task = ServiceTask::Refuel(id);
+            let pad = &universe.landing_pads[&pad];
+            pad.tasks.contains(&task) || pad.active_tasks().any(|t| t == task)
+        };
+
+        let first = land(&mut universe, planet);
+        assert_eq!(universe.pad_occupant(pad, None), Some(first));
+        assert!(serving(&universe, first));
+        let pos = universe.surface_vehicles[&first].body.pv.pos;
+        assert_eq!(universe.free_pad_near(planet, pos, None), Some(other));
+        assert_eq!(universe.free_pad_near(planet, pos, Some(first)), Some(pad));
+
+        let structures = universe.structures.len();
+        let sub = universe.events.subscribe();
+        let second = land(&mut universe, planet);
+        let score = universe
+            .events
+            .poll(sub)
+            .into_iter()
+            .filter_map(|e| match e.event {
+                UniverseEvent::Landed(id, score) if id == second => Some(score),
+                _ => None,
+            })
+            .next_back()
+            .unwrap();
+        assert_eq!(score.pad, Some(pad));
+        assert_eq!(score.accepted_pad(), None);
+        assert!(!serving(&universe, second));
+        assert_eq!(universe.structures.len(), structures);
+    }
+
+    #[test]
+    fn rewinding_puts_drones_back_where_they_were() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let pad = universe
+            .add_landing_pad(LandingPad::new("Pad", planet, 0.0, 20.0))
+            .unwrap();
+        universe
+            .landing_pads
+            .get_mut(&pad)
+            .unwrap()
+            .record_delivery(150);
+        let id = land(&mut universe, planet);
+        let drone = |universe: &Universe| universe.landing_pads[&pad].drones[0].clone();
+        assert_eq!(drone(&universe).task(), Some(ServiceTask::Refuel(id)));
+
+        let checkpoint = universe.checkpoint();
+        let before = drone(&universe).pos();
+        for _ in 0..20 {
+            universe.on_sim_tick(&ControlSignals::new());
+        }
+        assert!(drone(&universe).pos().distance(before) > 0.1);
+
+        universe.restore(&checkpoint);
+        assert_eq!(drone(&universe).pos(), before);
+        assert_eq!(drone(&universe).task(), Some(ServiceTask::Refuel(id)));
+    }
+}
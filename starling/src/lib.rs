@@ -1,33 +1,66 @@
 pub mod aabb;
+pub mod aerodynamics;
+pub mod batch_propagation;
 pub mod belts;
 pub mod bezier;
+pub mod burn_alignment;
+pub mod callsign;
 pub mod casts;
+pub mod checkpoint;
 pub mod construction_bot;
 pub mod control;
 pub mod control_signals;
+pub mod docking;
+pub mod dv_map;
 pub mod entities;
+pub mod event_bus;
 pub mod examples;
 pub mod factory;
 pub mod file_export;
 pub mod id;
+pub mod impact;
+pub mod item_transfer;
+pub mod landing_pad;
+pub mod load_queue;
 pub mod lpf;
+pub mod lvlh;
+pub mod maneuver_node;
 pub mod math;
 pub mod nanotime;
+pub mod orbit_crossings;
+pub mod orbit_history;
+pub mod orbit_normalization;
 pub mod orbital_luts;
 pub mod orbiter;
 pub mod orbits;
 pub mod parts;
 pub mod pid;
+pub mod planet_defs;
+pub mod planet_texture;
 pub mod planning;
 pub mod plants;
 pub mod prelude;
 pub mod propagator;
 pub mod pv;
 pub mod quantities;
+pub mod recycle_bin;
 pub mod region;
+pub mod save_archive;
 pub mod scenario;
+pub mod scenario_file;
+pub mod scenario_script;
+pub mod service_drone;
+pub mod shared_plans;
+pub mod snapshot;
+pub mod structure;
 pub mod surface;
 pub mod take;
+pub mod terrain;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod thrust_particles;
+pub mod traffic;
 pub mod universe;
+pub mod universe_file;
 pub mod vehicle;
+pub mod waypoint;
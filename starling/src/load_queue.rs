@@ -0,0 +1,97 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// A vehicle whose entity ID has been reserved, but which has not yet been
+/// constructed and added to the universe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingVehicle {
+    pub id: EntityId,
+    pub model: String,
+    pub orbit: GlobalOrbit,
+}
+
+/// Vehicles waiting to be streamed into the universe over several ticks,
+/// so that huge scenarios don't stall at startup.
+#[derive(Debug, Clone, Default)]
+pub struct LoadQueue {
+    pending: VecDeque<PendingVehicle>,
+    total: usize,
+}
+
+impl LoadQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.pending.iter().any(|p| p.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PendingVehicle> + use<'_> {
+        self.pending.iter()
+    }
+
+    pub fn push(&mut self, pending: PendingVehicle) {
+        self.pending.push_back(pending);
+        self.total += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<PendingVehicle> {
+        let ret = self.pending.pop_front();
+        if self.pending.is_empty() {
+            self.total = 0;
+        }
+        ret
+    }
+
+    /// Number of vehicles loaded and total number queued, since the queue
+    /// was last empty.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.total - self.pending.len(), self.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_ids_resolve_once_loaded() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let body = universe.planets.body;
+        let orbit = SparseOrbit::circular(body.radius * 2.0, body, Nanotime::zero(), false);
+        let orbit = GlobalOrbit(planet, orbit);
+
+        let ids: Vec<_> = (0..3)
+            .map(|_| universe.queue_orbital_vehicle("drone", orbit))
+            .collect();
+        assert_eq!(universe.loading.progress(), (0, 3));
+
+        for id in &ids {
+            assert!(universe.is_loading(*id));
+            assert!(universe.lup_orbiter(*id).is_none());
+            assert!(universe.pv(*id).is_none());
+        }
+
+        let pending = universe.loading.pop().unwrap();
+        assert_eq!(pending.id, ids[0]);
+        assert!(universe.finish_loading(pending, Vehicle::new()).is_some());
+        assert!(!universe.is_loading(ids[0]));
+        assert!(universe.lup_orbiter(ids[0]).is_some());
+        assert_eq!(universe.loading.progress(), (1, 3));
+
+        universe.loading.pop().unwrap();
+        universe.loading.pop().unwrap();
+        assert_eq!(universe.loading.progress(), (0, 0));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::prelude::*;
+use std::collections::VecDeque;
+
+/// Time span of chaser positions kept by a `RelativeTrack`.
+pub const RELATIVE_TRACK_DURATION: Nanotime = Nanotime::mins(20);
+
+/// Shortest time between samples recorded by a `RelativeTrack`.
+const RELATIVE_TRACK_INTERVAL: Nanotime = Nanotime::millis(2000);
+
+/// Local-vertical, local-horizontal frame centered on a target. The x axis
+/// points along the target's velocity (V-bar), and the y axis points
+/// radially away from the parent body (R-bar). The frame rotates with the
+/// target's orbital motion, so a chaser in the same orbit appears fixed.
+#[derive(Debug, Clone, Copy)]
+pub struct LvlhFrame {
+    pub origin: PV,
+    pub vbar: DVec2,
+    pub rbar: DVec2,
+    pub angular_velocity: f64,
+}
+
+impl LvlhFrame {
+    /// Constructs the frame from the target's position and velocity,
+    /// relative to the body it orbits.
+    pub fn new(target: PV) -> Option<Self> {
+        let r = target.pos.length();
+        if r == 0.0 {
+            return None;
+        }
+        let rbar = target.pos / r;
+        let h = cross2d(target.pos, target.vel);
+        let vbar = if h >= 0.0 { rbar.perp() } else { -rbar.perp() };
+        Some(Self {
+            origin: target,
+            vbar,
+            rbar,
+            angular_velocity: h / (r * r),
+        })
+    }
+
+    fn rotate(&self, v: DVec2) -> DVec2 {
+        DVec2::new(v.dot(self.vbar), v.dot(self.rbar))
+    }
+
+    /// Transforms a state relative to the same parent body into this frame,
+    /// removing the frame's rotation from the relative velocity.
+    pub fn to_frame(&self, pv: PV) -> PV {
+        let rel = pv - self.origin;
+        let omega_cross_r = rel.pos.perp() * self.angular_velocity;
+        PV::from_f64(self.rotate(rel.pos), self.rotate(rel.vel - omega_cross_r))
+    }
+}
+
+/// Samples the position of `chaser` in the LVLH frame of `target` over the
+/// given time span. Both orbits must be around the same parent body.
+pub fn relative_trajectory(
+    chaser: &SparseOrbit,
+    target: &SparseOrbit,
+    start: Nanotime,
+    end: Nanotime,
+    nsamples: usize,
+) -> Vec<DVec2> {
+    tspace(start, end, nsamples)
+        .into_iter()
+        .filter_map(|t| {
+            let frame = LvlhFrame::new(target.pv(t).ok()?)?;
+            Some(frame.to_frame(chaser.pv(t).ok()?).pos)
+        })
+        .collect()
+}
+
+/// Rate at which the distance to the frame origin is changing, given a
+/// state in the frame. Negative while closing.
+pub fn range_rate(rel: PV) -> f64 {
+    let r = rel.pos.length();
+    if r == 0.0 {
+        return 0.0;
+    }
+    rel.pos.dot(rel.vel) / r
+}
+
+/// Recent positions of a chaser in its target's LVLH frame, for plotting
+/// where the approach has been. Changing either vehicle, or going back in
+/// time, starts a new track.
+#[derive(Debug, Clone, Default)]
+pub struct RelativeTrack {
+    pair: Option<(EntityId, EntityId)>,
+    samples: VecDeque<(Nanotime, DVec2)>,
+}
+
+impl RelativeTrack {
+    pub fn record(&mut self, chaser: EntityId, target: EntityId, stamp: Nanotime, pos: DVec2) {
+        let rewound = self.samples.back().is_some_and(|(t, _)| *t > stamp);
+        if self.pair != Some((chaser, target)) || rewound {
+            self.samples.clear();
+            self.pair = Some((chaser, target));
+        }
+
+        if let Some((t, _)) = self.samples.back() {
+            if stamp - *t < RELATIVE_TRACK_INTERVAL {
+                return;
+            }
+        }
+
+        self.samples.push_back((stamp, pos));
+        while let Some((t, _)) = self.samples.front() {
+            if stamp - *t <= RELATIVE_TRACK_DURATION {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pair = None;
+        self.samples.clear();
+    }
+
+    /// Recorded positions, oldest first, if the track is of this pair.
+    pub fn points(
+        &self,
+        chaser: EntityId,
+        target: EntityId,
+    ) -> impl Iterator<Item = DVec2> + use<'_> {
+        let matches = self.pair == Some((chaser, target));
+        self.samples
+            .iter()
+            .filter(move |_| matches)
+            .map(|(_, p)| *p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coorbital_chaser_is_stationary() {
+        let body = Body::LUNA;
+        let target = SparseOrbit::circular(2_000_000.0, body, Nanotime::ZERO, false);
+        let lead = target.pv(Nanotime::ZERO).unwrap();
+        let frame = LvlhFrame::new(lead).unwrap();
+
+        // a chaser on the same orbit, a little ways behind the target
+        let chaser_pos = rotate_f64(lead.pos, -0.0001);
+        let chaser_vel = rotate_f64(lead.vel, -0.0001);
+        let chaser =
+            SparseOrbit::from_pv(PV::from_f64(chaser_pos, chaser_vel), body, Nanotime::ZERO)
+                .unwrap();
+
+        let rel = frame.to_frame(PV::from_f64(chaser_pos, chaser_vel));
+        assert!(rel.pos.x < -150.0);
+        assert!(rel.pos.y.abs() < 1.0);
+        assert!(rel.vel.length() < 1e-6);
+
+        let traj = relative_trajectory(&chaser, &target, Nanotime::ZERO, Nanotime::hours(2), 20);
+        assert_eq!(traj.len(), 20);
+        for p in traj {
+            assert!(p.distance(rel.pos) < 1.0, "{} {}", p, rel.pos);
+        }
+    }
+
+    #[test]
+    fn relative_track_keeps_recent_samples() {
+        let (a, b) = (EntityId(1), EntityId(2));
+        assert!(range_rate(PV::from_f64(DVec2::new(100.0, 0.0), DVec2::new(-2.0, 5.0))) < 0.0);
+
+        let mut track = RelativeTrack::default();
+        for i in 0..1000 {
+            track.record(a, b, Nanotime::secs(i), DVec2::X * i as f64);
+        }
+        let points: Vec<_> = track.points(a, b).collect();
+        assert!(points.len() < 1000 && points.len() > 100);
+        assert_eq!(points.last(), Some(&DVec2::new(998.0, 0.0)));
+        assert_eq!(track.points(b, a).count(), 0);
+
+        // a new target starts over
+        track.record(a, EntityId(3), Nanotime::secs(1000), DVec2::ZERO);
+        assert_eq!(track.points(a, b).count(), 0);
+        assert_eq!(track.points(a, EntityId(3)).count(), 1);
+    }
+}
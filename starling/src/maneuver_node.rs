@@ -0,0 +1,98 @@
+use crate::orbit_crossings::ta_at_angle;
+use crate::prelude::*;
+
+/// A single burn placed by hand on an orbit: when it happens, and how
+/// much delta-v it adds along and across the direction of travel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManeuverNode {
+    pub stamp: Nanotime,
+    /// Delta-v along the velocity at the node, in m/s.
+    pub prograde: f64,
+    /// Delta-v perpendicular to prograde and away from the body, in m/s.
+    pub radial: f64,
+}
+
+impl ManeuverNode {
+    pub fn new(stamp: Nanotime) -> Self {
+        Self {
+            stamp,
+            prograde: 0.0,
+            radial: 0.0,
+        }
+    }
+
+    /// Node with no burn at the next time after `now` that a closed orbit
+    /// passes the angle of `pos` about its body.
+    pub fn at_position(orbit: &SparseOrbit, now: Nanotime, pos: DVec2) -> Option<Self> {
+        let ta = ta_at_angle(orbit, pos.to_angle());
+        Some(Self::new(orbit.t_next_ta(now, ta)?))
+    }
+
+    /// Moves the node to a new time, keeping its burn.
+    pub fn with_stamp(self, stamp: Nanotime) -> Self {
+        Self { stamp, ..self }
+    }
+
+    /// Prograde and radial directions at the node.
+    pub fn axes(&self, orbit: &SparseOrbit) -> Option<(DVec2, DVec2)> {
+        let pv = orbit.pv(self.stamp).ok()?;
+        let prograde = pv.vel.try_normalize()?;
+        let radial = prograde.perp();
+        let radial = if radial.dot(pv.pos) < 0.0 {
+            -radial
+        } else {
+            radial
+        };
+        Some((prograde, radial))
+    }
+
+    pub fn dv(&self, orbit: &SparseOrbit) -> Option<DVec2> {
+        let (prograde, radial) = self.axes(orbit)?;
+        Some(prograde * self.prograde + radial * self.radial)
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        self.prograde.hypot(self.radial)
+    }
+
+    /// Plan carrying out the burn, starting from `orbit` at `now`.
+    pub fn plan(&self, now: Nanotime, orbit: &SparseOrbit) -> Option<ManeuverPlan> {
+        if self.stamp <= now {
+            return None;
+        }
+        ManeuverPlan::new(now, *orbit, &[(self.stamp, self.dv(orbit)?)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nodes_burn_along_the_orbit_where_placed() {
+        let t0 = Nanotime::zero();
+        let body = Body::LUNA;
+        let orbit = SparseOrbit::circular(body.radius * 2.0, body, t0, false);
+
+        // placed at the far side of where the orbit is now
+        let here = orbit.pv(t0).unwrap().pos;
+        let mut node = ManeuverNode::at_position(&orbit, t0, -here).unwrap();
+        let half = orbit.period().unwrap() / 2;
+        assert!((node.stamp - half).to_secs_f64().abs() < 1.0);
+
+        let (prograde, radial) = node.axes(&orbit).unwrap();
+        let pv = orbit.pv(node.stamp).unwrap();
+        assert!(prograde.dot(pv.vel.normalize()) > 0.999);
+        assert!(radial.dot(pv.pos.normalize()) > 0.999);
+
+        node.prograde = 30.0;
+        node.radial = -40.0;
+        assert_eq!(node.magnitude(), 50.0);
+        let plan = node.plan(t0, &orbit).unwrap();
+        assert!((plan.dv() - 50.0).abs() < 1E-9);
+        assert!(plan.terminal.apoapsis_r() > orbit.apoapsis_r());
+
+        // nodes already behind us can't be planned
+        assert!(node.plan(node.stamp, &orbit).is_none());
+    }
+}
@@ -206,11 +206,74 @@ pub fn wrap_pi_npi_f64(x: f64) -> f64 {
     f64::atan2(x.sin(), x.cos())
 }
 
+/// Samples the curve `f` between `a` and `b`, subdividing wherever the
+/// midpoint of a segment strays from its chord by more than `tolerance`,
+/// up to `max_depth` times. Returns the parameters of the sampled points,
+/// in order, including both ends.
+pub fn adaptive_sample(
+    a: f64,
+    b: f64,
+    tolerance: f32,
+    max_depth: u32,
+    f: impl Fn(f64) -> Vec2,
+) -> Vec<f64> {
+    fn refine(
+        a: (f64, Vec2),
+        b: (f64, Vec2),
+        depth: u32,
+        tolerance: f32,
+        f: &impl Fn(f64) -> Vec2,
+        out: &mut Vec<f64>,
+    ) {
+        let m = (a.0 + b.0) / 2.0;
+        let pm = f(m);
+        let deviation = pm.distance((a.1 + b.1) / 2.0);
+        if depth > 0 && deviation > tolerance {
+            refine(a, (m, pm), depth - 1, tolerance, f, out);
+            refine((m, pm), b, depth - 1, tolerance, f, out);
+        } else {
+            out.push(b.0);
+        }
+    }
+
+    // a coarse first pass, so features narrower than the whole span
+    // aren't skipped over
+    const INITIAL_SEGMENTS: usize = 16;
+    let ts = linspace_f64(a, b, INITIAL_SEGMENTS + 1);
+    let mut out = vec![a];
+    for w in ts.windows(2) {
+        refine(
+            (w[0], f(w[0])),
+            (w[1], f(w[1])),
+            max_depth,
+            tolerance,
+            &f,
+            &mut out,
+        );
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_float_eq::assert_float_absolute_eq;
 
+    #[test]
+    fn adaptive_sampling_refines_only_where_curved() {
+        let line = adaptive_sample(0.0, 1.0, 0.01, 10, |t| Vec2::splat(t as f32));
+        assert_eq!(line.len(), 17);
+
+        let arc = |t: f64| Vec2::from_angle(t as f32) * 100.0;
+        let coarse = adaptive_sample(0.0, PI_64, 0.1, 10, arc);
+        let fine = adaptive_sample(0.0, PI_64, 0.01, 10, arc);
+        assert_eq!(coarse.first(), Some(&0.0));
+        assert_eq!(coarse.last(), Some(&PI_64));
+        assert!(fine.windows(2).all(|w| w[0] < w[1]));
+        assert!(coarse.len() > line.len());
+        assert!(fine.len() > coarse.len());
+    }
+
     #[test]
     fn linspace_is_cool() {
         let t = linspace(-0.3, 0.6, 12);
@@ -39,7 +39,7 @@ impl Nanotime {
         Nanotime(s * Nanotime::PER_SEC)
     }
 
-    pub fn mins(m: i64) -> Self {
+    pub const fn mins(m: i64) -> Self {
         Nanotime(m * Nanotime::PER_MINUTE)
     }
 
@@ -131,6 +131,19 @@ impl std::fmt::Display for Date {
     }
 }
 
+/// Formats a span of time as days, hours, minutes and seconds.
+pub fn duration_str(t: Nanotime) -> String {
+    let secs = t.0.abs() / Nanotime::PER_SEC;
+    let (days, rem) = (secs / 86400, secs % 86400);
+    let (h, m, s) = (rem / 3600, rem % 3600 / 60, rem % 60);
+    let sign = if t.0 < 0 { "-" } else { "" };
+    if days > 0 {
+        format!("{}{}d {:02}:{:02}:{:02}", sign, days, h, m, s)
+    } else {
+        format!("{}{:02}:{:02}:{:02}", sign, h, m, s)
+    }
+}
+
 fn fmt(s: &Nanotime, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let disp = s.0.abs();
     if s.0 >= 0 {
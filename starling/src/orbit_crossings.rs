@@ -0,0 +1,146 @@
+use crate::prelude::*;
+
+/// Angles sampled around the body when looking for where two orbits
+/// cross.
+const CROSSING_SEARCH_STEPS: usize = 360;
+
+/// Point where two coplanar orbits about the same body cross, with what
+/// it takes to meet the target there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCrossing {
+    /// Angle of the crossing about the body.
+    pub angle: f64,
+    /// Position of the crossing relative to the body.
+    pub pos: DVec2,
+    /// Next times each orbiter passes through the crossing.
+    pub own_time: Nanotime,
+    pub target_time: Nanotime,
+    /// Velocity change needed to match the target's velocity on arriving
+    /// at the crossing.
+    pub dv: f64,
+}
+
+impl OrbitCrossing {
+    /// How much later the target reaches the crossing than the orbiter
+    /// does. Negative if the target arrives first.
+    pub fn arrival_difference(&self) -> Nanotime {
+        self.target_time - self.own_time
+    }
+}
+
+/// True anomaly at which the orbit passes the given angle about its body.
+pub(crate) fn ta_at_angle(orbit: &SparseOrbit, angle: f64) -> f64 {
+    if orbit.is_retrograde() {
+        orbit.arg_periapsis - angle
+    } else {
+        angle - orbit.arg_periapsis
+    }
+}
+
+/// Distance from the body at which the orbit passes the given angle, if
+/// it reaches that angle at all.
+fn radius_at_polar_angle(orbit: &SparseOrbit, angle: f64) -> Option<f64> {
+    let ta = ta_at_angle(orbit, angle);
+    (1.0 + orbit.ecc() * ta.cos() > 0.0)
+        .then(|| orbit.radius_at(ta))
+        .filter(|r| r.is_finite() && *r > 0.0)
+}
+
+/// Where two closed orbits about the same body cross, in no particular
+/// order. Two conics sharing a focus cross at most twice, like the
+/// ascending and descending nodes of orbits in different planes.
+pub fn orbit_crossings(
+    own: &SparseOrbit,
+    target: &SparseOrbit,
+    now: Nanotime,
+) -> Vec<OrbitCrossing> {
+    let gap = |angle: f64| -> Option<f64> {
+        Some(radius_at_polar_angle(own, angle)? - radius_at_polar_angle(target, angle)?)
+    };
+
+    let step = 2.0 * PI_64 / CROSSING_SEARCH_STEPS as f64;
+    let mut ret = Vec::new();
+    for i in 0..CROSSING_SEARCH_STEPS {
+        let (mut lo, mut hi) = (i as f64 * step, (i + 1) as f64 * step);
+        let (Some(mut glo), Some(ghi)) = (gap(lo), gap(hi)) else {
+            continue;
+        };
+        if glo == 0.0 || glo.signum() == ghi.signum() {
+            continue;
+        }
+        for _ in 0..50 {
+            let mid = (lo + hi) / 2.0;
+            let Some(gmid) = gap(mid) else {
+                break;
+            };
+            if gmid.signum() == glo.signum() {
+                (lo, glo) = (mid, gmid);
+            } else {
+                hi = mid;
+            }
+        }
+        let angle = (lo + hi) / 2.0;
+        if let Some(c) = crossing_at(own, target, angle, now) {
+            ret.push(c);
+        }
+    }
+    ret
+}
+
+fn crossing_at(
+    own: &SparseOrbit,
+    target: &SparseOrbit,
+    angle: f64,
+    now: Nanotime,
+) -> Option<OrbitCrossing> {
+    let own_time = own.t_next_ta(now, ta_at_angle(own, angle))?;
+    let target_time = target.t_next_ta(now, ta_at_angle(target, angle))?;
+    let pv = own.pv(own_time).ok()?;
+    let tv = target.pv(target_time).ok()?;
+    Some(OrbitCrossing {
+        angle: wrap_0_2pi_f64(angle),
+        pos: pv.pos,
+        own_time,
+        target_time,
+        dv: tv.vel.distance(pv.vel),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossings_lie_on_both_orbits() {
+        let body = Body::with_mass(63.0, 1000.0, 15000.0);
+        let t0 = Nanotime::zero();
+        let circle = SparseOrbit::circular(800.0, body, t0, false);
+        let ellipse = SparseOrbit::new(1200.0, 500.0, 0.7, body, t0, false).unwrap();
+
+        let crossings = orbit_crossings(&circle, &ellipse, t0);
+        assert_eq!(crossings.len(), 2);
+        for c in &crossings {
+            assert!((c.pos.length() - 800.0).abs() < 1E-3);
+            let target = ellipse.pv(c.target_time).unwrap();
+            assert!(target.pos.distance(c.pos) < 1E-2);
+            assert!(c.own_time > t0 && c.target_time > t0);
+            assert!(c.dv > 0.0);
+        }
+        // the crossings are mirrored about the ellipse's major axis
+        let mid = (crossings[0].angle + crossings[1].angle) / 2.0;
+        assert!(
+            wrap_pi_npi_f64(mid - 0.7).abs() < 1E-6
+                || wrap_pi_npi_f64(mid - 0.7 - PI_64).abs() < 1E-6
+        );
+
+        // nested orbits never cross
+        let inner = SparseOrbit::circular(400.0, body, t0, false);
+        assert!(orbit_crossings(&inner, &circle, t0).is_empty());
+
+        // going the other way round, the target has to be met head on
+        let retro = SparseOrbit::new(1200.0, 500.0, 0.7, body, t0, true).unwrap();
+        let head_on = orbit_crossings(&circle, &retro, t0);
+        assert_eq!(head_on.len(), 2);
+        assert!(head_on[0].dv > crossings[0].dv + crossings[1].dv);
+    }
+}
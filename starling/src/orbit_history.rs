@@ -0,0 +1,203 @@
+use crate::id::EntityId;
+use crate::nanotime::Nanotime;
+use crate::orbits::GlobalOrbit;
+use std::collections::{HashMap, VecDeque};
+
+/// Most orbits kept per vehicle. The oldest are dropped first.
+const MAX_RECORDS: usize = 256;
+
+/// Orbits which begin less than this long after the previous one replace
+/// it rather than being recorded separately, so that a burn is recorded
+/// as a handful of orbits rather than one per tick.
+const MERGE_WINDOW: Nanotime = Nanotime::millis(30_000);
+
+/// Relative change in the periapsis or apoapsis radius, or in the position
+/// of the periapsis, beyond which an orbit is considered changed.
+const CHANGE_TOLERANCE: f64 = 1e-3;
+
+/// An orbit, and the span of sim time over which a vehicle followed it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitRecord {
+    pub orbit: GlobalOrbit,
+    pub start: Nanotime,
+    /// None while this is the orbit the vehicle is on.
+    pub end: Option<Nanotime>,
+}
+
+impl OrbitRecord {
+    pub fn contains(&self, stamp: Nanotime) -> bool {
+        self.start <= stamp && self.end.is_none_or(|end| stamp < end)
+    }
+}
+
+fn is_same_orbit(a: &GlobalOrbit, b: &GlobalOrbit) -> bool {
+    let (GlobalOrbit(pa, a), GlobalOrbit(pb, b)) = (a, b);
+    if pa != pb || a.is_retrograde() != b.is_retrograde() {
+        return false;
+    }
+    let scale = a.periapsis_r().max(1.0);
+    let close = |x: f64, y: f64| (x - y).abs() <= scale * CHANGE_TOLERANCE;
+    let apoapsis = match (a.is_hyperbolic(), b.is_hyperbolic()) {
+        (false, false) => close(a.apoapsis_r(), b.apoapsis_r()),
+        (true, true) => true,
+        _ => false,
+    };
+    apoapsis
+        && close(a.periapsis_r(), b.periapsis_r())
+        && a.periapsis().distance(b.periapsis()) <= scale * CHANGE_TOLERANCE
+}
+
+/// Orbits a single vehicle has followed, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct OrbitHistory {
+    records: VecDeque<OrbitRecord>,
+}
+
+impl OrbitHistory {
+    pub fn records(&self) -> impl DoubleEndedIterator<Item = &OrbitRecord> + use<'_> {
+        self.records.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The orbit the vehicle was following at the given time, if any was
+    /// recorded.
+    pub fn at(&self, stamp: Nanotime) -> Option<&OrbitRecord> {
+        self.records.iter().rev().find(|r| r.contains(stamp))
+    }
+
+    /// Span of sim time covered by the history, up to `now` for an orbit
+    /// which is still being followed.
+    pub fn span(&self, now: Nanotime) -> Option<(Nanotime, Nanotime)> {
+        let first = self.records.front()?;
+        let last = self.records.back()?;
+        Some((first.start, last.end.unwrap_or(now)))
+    }
+
+    /// Notes the orbit the vehicle is on at the given time, or that it
+    /// isn't in orbit.
+    pub fn record(&mut self, orbit: Option<GlobalOrbit>, stamp: Nanotime) {
+        let current = self.records.back_mut().filter(|r| r.end.is_none());
+        match (current, orbit) {
+            (Some(r), Some(o)) if is_same_orbit(&r.orbit, &o) => (),
+            (Some(r), Some(o)) if stamp - r.start < MERGE_WINDOW => r.orbit = o,
+            (Some(r), o) => {
+                r.end = Some(stamp);
+                if let Some(orbit) = o {
+                    self.push(orbit, stamp);
+                }
+            }
+            (None, Some(o)) => self.push(o, stamp),
+            (None, None) => (),
+        }
+    }
+
+    fn push(&mut self, orbit: GlobalOrbit, stamp: Nanotime) {
+        self.records.push_back(OrbitRecord {
+            orbit,
+            start: stamp,
+            end: None,
+        });
+        while self.records.len() > MAX_RECORDS {
+            self.records.pop_front();
+        }
+    }
+
+    /// Forgets everything after the given time, as when rewinding.
+    pub fn truncate_after(&mut self, stamp: Nanotime) {
+        self.records.retain(|r| r.start <= stamp);
+        if let Some(r) = self.records.back_mut() {
+            if r.end.is_some_and(|end| end > stamp) {
+                r.end = None;
+            }
+        }
+    }
+}
+
+/// Orbit history of every vehicle.
+#[derive(Debug, Clone, Default)]
+pub struct OrbitArchive {
+    histories: HashMap<EntityId, OrbitHistory>,
+}
+
+impl OrbitArchive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&OrbitHistory> {
+        self.histories.get(&id)
+    }
+
+    pub fn record(&mut self, id: EntityId, orbit: Option<GlobalOrbit>, stamp: Nanotime) {
+        if orbit.is_none() && !self.histories.contains_key(&id) {
+            return;
+        }
+        self.histories.entry(id).or_default().record(orbit, stamp);
+    }
+
+    pub fn truncate_after(&mut self, stamp: Nanotime) {
+        for history in self.histories.values_mut() {
+            history.truncate_after(stamp);
+        }
+        self.histories.retain(|_, h| !h.is_empty());
+    }
+
+    pub(crate) fn retain(&mut self, f: impl Fn(EntityId) -> bool) {
+        self.histories.retain(|id, _| f(*id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbits::{Body, SparseOrbit};
+
+    #[test]
+    fn history_records_each_distinct_orbit() {
+        let body = Body::LUNA;
+        let t = |secs| Nanotime::secs(secs);
+        let orbit = |r| {
+            Some(GlobalOrbit(
+                EntityId(0),
+                SparseOrbit::circular(r, body, t(0), false),
+            ))
+        };
+
+        let mut history = OrbitHistory::default();
+        history.record(orbit(5_000_000.0), t(0));
+        history.record(orbit(5_000_000.0), t(100));
+        assert_eq!(history.len(), 1);
+
+        // a burn, whose intermediate orbits are merged together
+        history.record(orbit(5_500_000.0), t(1000));
+        history.record(orbit(5_900_000.0), t(1010));
+        history.record(orbit(6_000_000.0), t(1020));
+        assert_eq!(history.len(), 2);
+        history.record(orbit(6_000_000.0), t(2000));
+        history.record(None, t(3000));
+        history.record(None, t(3100));
+        assert_eq!(history.len(), 2);
+
+        let first = history.at(t(500)).unwrap();
+        assert_eq!(first.end, Some(t(1000)));
+        assert_eq!(first.orbit, orbit(5_000_000.0).unwrap());
+        let second = history.at(t(1500)).unwrap();
+        assert_eq!(second.orbit, orbit(6_000_000.0).unwrap());
+        assert_eq!(history.at(t(3050)), None);
+        assert_eq!(history.span(t(4000)), Some((t(0), t(3000))));
+
+        history.truncate_after(t(1500));
+        assert_eq!(history.len(), 2);
+        assert!(history.at(t(3050)).is_some());
+        history.truncate_after(t(500));
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.at(t(500)).unwrap().end, None);
+    }
+}
@@ -0,0 +1,283 @@
+use crate::entities::MIN_ORBIT_ALTITUDE;
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How far a vehicle's stored orbit may disagree with its position and
+/// velocity before the difference is reported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalizationTolerance {
+    /// Meters.
+    pub position: f64,
+    /// Meters per second.
+    pub velocity: f64,
+}
+
+impl Default for NormalizationTolerance {
+    fn default() -> Self {
+        Self {
+            position: 10.0,
+            velocity: 0.01,
+        }
+    }
+}
+
+/// How a vehicle's orbit changed when it was re-derived.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrbitChange {
+    /// The stored orbit put the vehicle this far from where it really is.
+    Drifted { position: f64, velocity: f64 },
+    /// The vehicle had an orbit it shouldn't have had, lacked one it
+    /// should have had, or had one which couldn't be evaluated.
+    Replaced,
+}
+
+impl std::fmt::Display for OrbitChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Drifted { position, velocity } => {
+                write!(f, "drifted by {:0.1} m, {:0.3} m/s", position, velocity)
+            }
+            Self::Replaced => write!(f, "orbit replaced"),
+        }
+    }
+}
+
+/// Why a vehicle couldn't be given a sound orbit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuarantineReason {
+    UnknownParent(EntityId),
+    /// Position or velocity isn't a finite number.
+    InvalidState,
+    /// Inside its parent without resting on the surface.
+    BelowSurface,
+    /// No orbit could be derived from its position and velocity.
+    NoOrbit,
+}
+
+impl std::fmt::Display for QuarantineReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownParent(id) => write!(f, "orbits {}, which doesn't exist", id),
+            Self::InvalidState => write!(f, "position or velocity is not a number"),
+            Self::BelowSurface => write!(f, "is below the surface of its parent"),
+            Self::NoOrbit => write!(f, "has no valid orbit"),
+        }
+    }
+}
+
+/// Re-derives a vehicle's reference orbit from its position and velocity
+/// about `parent`. Returns how the orbit changed, if by more than
+/// `tolerance`, or why no sound orbit could be found.
+pub fn rectify_orbit(
+    sv: &mut SurfaceSpacecraftEntity,
+    parent: Option<Body>,
+    stamp: Nanotime,
+    tolerance: NormalizationTolerance,
+) -> Result<Option<OrbitChange>, QuarantineReason> {
+    let pv = sv
+        .pv()
+        .filter_numerr()
+        .ok_or(QuarantineReason::InvalidState)?;
+    let parent = parent.ok_or(QuarantineReason::UnknownParent(sv.parent()))?;
+    let altitude = pv.pos.length() - parent.radius;
+    if altitude < 0.0 && !sv.is_landed() {
+        return Err(QuarantineReason::BelowSurface);
+    }
+
+    let orbit = if altitude > MIN_ORBIT_ALTITUDE {
+        Some(SparseOrbit::from_pv(pv, parent, stamp).ok_or(QuarantineReason::NoOrbit)?)
+    } else {
+        None
+    };
+
+    let change = match (sv.orbit, orbit) {
+        (None, None) => None,
+        (Some(old), Some(_)) => match old.pv(stamp) {
+            Ok(old) => {
+                let position = old.pos.distance(pv.pos);
+                let velocity = old.vel.distance(pv.vel);
+                (position > tolerance.position || velocity > tolerance.velocity)
+                    .then_some(OrbitChange::Drifted { position, velocity })
+            }
+            Err(_) => Some(OrbitChange::Replaced),
+        },
+        _ => Some(OrbitChange::Replaced),
+    };
+
+    if change.is_some() {
+        sv.replace_orbit(orbit);
+    } else {
+        sv.orbit = orbit;
+    }
+    Ok(change)
+}
+
+/// What a normalization pass found.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NormalizationReport {
+    pub checked: usize,
+    /// Vehicles whose orbits changed by more than the tolerance.
+    pub rectified: Vec<(EntityId, OrbitChange)>,
+    pub quarantined: Vec<(EntityId, QuarantineReason)>,
+}
+
+impl NormalizationReport {
+    pub fn is_clean(&self) -> bool {
+        self.rectified.is_empty() && self.quarantined.is_empty()
+    }
+}
+
+/// A vehicle set aside because it couldn't be given a sound orbit.
+#[derive(Debug, Clone)]
+pub struct QuarantinedEntity {
+    pub id: EntityId,
+    pub reason: QuarantineReason,
+    pub quarantined_at: Nanotime,
+    pub vehicle: SurfaceSpacecraftEntity,
+    pub group: Option<EntityId>,
+    /// Vehicles docked to this one, which it carries along.
+    pub docked: Vec<(EntityId, DockingJoint)>,
+}
+
+/// Holding area for vehicles which can't be simulated. They're kept, not
+/// deleted, so that the player can see what was wrong and decide what to
+/// do with them.
+#[derive(Debug, Clone, Default)]
+pub struct Quarantine {
+    entries: Vec<QuarantinedEntity>,
+}
+
+impl Quarantine {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.entries.iter().any(|e| e.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &QuarantinedEntity> + use<'_> {
+        self.entries.iter()
+    }
+
+    pub fn insert(&mut self, entity: QuarantinedEntity) {
+        self.entries.retain(|e| e.id != entity.id);
+        self.entries.push(entity);
+    }
+
+    pub fn take(&mut self, id: EntityId) -> Option<QuarantinedEntity> {
+        let idx = self.entries.iter().position(|e| e.id == id)?;
+        Some(self.entries.remove(idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::universe_in_leo;
+
+    #[test]
+    fn orbits_are_rederived_and_broken_vehicles_quarantined() {
+        let mut universe = Universe::new(crate::examples::rss());
+        let planet = universe.planets.id;
+        let body = universe.planets.body;
+        let mut add = |r: f64| {
+            let orbit = SparseOrbit::circular(body.radius + r, body, Nanotime::zero(), false);
+            universe
+                .add_orbital_vehicle(Vehicle::new(), GlobalOrbit(planet, orbit))
+                .unwrap()
+        };
+        let ids = [
+            add(400_000.0),
+            add(500_000.0),
+            add(600_000.0),
+            add(700_000.0),
+        ];
+
+        // consistent orbits pass untouched
+        let report = universe.normalize_orbits(NormalizationTolerance::default());
+        assert_eq!(report.checked, 4);
+        assert!(report.is_clean(), "{:?}", report);
+
+        // as if saved under a slightly different gravitational parameter
+        let mut heavier = body;
+        heavier.mu *= 1.001;
+        let sv = universe.surface_vehicles.get_mut(&ids[0]).unwrap();
+        let r = sv.pv().pos.length();
+        sv.orbit = Some(SparseOrbit::circular(r, heavier, Nanotime::zero(), false));
+        universe
+            .surface_vehicles
+            .get_mut(&ids[1])
+            .unwrap()
+            .body
+            .pv
+            .vel
+            .x = f64::NAN;
+        universe
+            .surface_vehicles
+            .get_mut(&ids[2])
+            .unwrap()
+            .body
+            .pv
+            .pos *= 0.5;
+        universe
+            .surface_vehicles
+            .get_mut(&ids[3])
+            .unwrap()
+            .planet_id = EntityId(-7);
+
+        let report = universe.normalize_orbits(NormalizationTolerance::default());
+        assert_eq!(report.rectified.len(), 1);
+        assert_eq!(report.rectified[0].0, ids[0]);
+        assert!(matches!(
+            report.rectified[0].1,
+            OrbitChange::Drifted { velocity, .. } if velocity > 0.01
+        ));
+        assert_eq!(
+            report.quarantined,
+            vec![
+                (ids[1], QuarantineReason::InvalidState),
+                (ids[2], QuarantineReason::BelowSurface),
+                (ids[3], QuarantineReason::UnknownParent(EntityId(-7))),
+            ]
+        );
+
+        // quarantined vehicles are set aside, not deleted
+        assert_eq!(universe.surface_vehicles.len(), 1);
+        assert_eq!(universe.quarantine.len(), 3);
+        assert!(universe.normalize_orbits(Default::default()).is_clean());
+        assert!(universe.discard_quarantined(ids[2]).is_some());
+        assert!(universe.recycle_bin.contains(ids[2]));
+        assert!(!universe.quarantine.contains(ids[2]));
+    }
+
+    #[test]
+    fn quarantine_survives_rewinding_and_saving() {
+        let (mut universe, orbit) = universe_in_leo();
+        let id = universe.add_orbital_vehicle(Vehicle::new(), orbit).unwrap();
+        let checkpoint = universe.checkpoint();
+        universe.surface_vehicles.get_mut(&id).unwrap().body.pv.pos *= 0.5;
+        universe.normalize_orbits(NormalizationTolerance::default());
+        assert!(universe.quarantine.contains(id));
+
+        let storage = universe.to_file_storage();
+        let (loaded, failed) =
+            Universe::from_file_storage(storage, &std::collections::HashMap::new()).unwrap();
+        assert!(failed.is_empty());
+        let entry = loaded.quarantine.iter().next().unwrap();
+        assert_eq!(entry.id, id);
+        assert_eq!(entry.reason, QuarantineReason::BelowSurface);
+        assert!(!loaded.surface_vehicles.contains_key(&id));
+
+        // once rewound, the vehicle is live again and can't be discarded
+        universe.restore(&checkpoint);
+        assert!(universe.surface_vehicles.contains_key(&id));
+        assert!(universe.quarantine.is_empty());
+        assert!(universe.discard_quarantined(id).is_none());
+        assert!(!universe.recycle_bin.contains(id));
+    }
+}
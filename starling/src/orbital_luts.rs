@@ -82,6 +82,7 @@ fn get_orbit_with_ecc(ecc: f64) -> Vec<f64> {
         radius: 1.0,
         mu: 1000.0 * 12000.0,
         soi: 100000.0,
+        atmosphere: None,
     };
     let epoch = Nanotime::zero();
     let retrograde = false;
@@ -4,7 +4,7 @@ use crate::scenario::*;
 use crate::{nanotime::Nanotime, orbits::GlobalOrbit};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Orbiter {
     props: Vec<Propagator>,
 }
@@ -53,6 +53,35 @@ impl Orbiter {
         })
     }
 
+    /// Returns the time at which this orbiter hits a body, if it does so
+    /// within the propagated horizon.
+    pub fn collision_time(&self) -> Option<Nanotime> {
+        self.props.iter().find_map(|p| match p.horizon {
+            HorizonState::Terminating(t, EventType::Collide(_)) => Some(t),
+            _ => None,
+        })
+    }
+
+    /// Returns the first sphere-of-influence boundary crossing, if any,
+    /// along with the propagator which terminates at that crossing.
+    pub fn next_soi_crossing(&self) -> Option<(Nanotime, EventType, &Propagator)> {
+        self.props.iter().find_map(|p| match p.horizon {
+            HorizonState::Transition(t, e @ (EventType::Escape(_) | EventType::Encounter(_))) => {
+                Some((t, e, p))
+            }
+            _ => None,
+        })
+    }
+
+    /// End of the propagated trajectory, or None if the trajectory is
+    /// fully known, because it is periodic or ends in an event.
+    pub fn horizon_end(&self) -> Option<Nanotime> {
+        match self.props.last()?.horizon {
+            HorizonState::Continuing(t) => Some(t),
+            _ => None,
+        }
+    }
+
     pub fn will_change(&self) -> bool {
         self.props
             .first()
@@ -1,4 +1,5 @@
 use crate::aabb::{AABB, OBB};
+use crate::aerodynamics::Atmosphere;
 use crate::id::EntityId;
 use crate::math::*;
 use crate::nanotime::Nanotime;
@@ -12,6 +13,14 @@ pub fn hyperbolic_range_ta(ecc: f32) -> f32 {
     (-1.0 / ecc).acos()
 }
 
+/// Signed angle from a parent body's velocity to an escape asymptote.
+/// Escapes within a right angle of the parent's motion are prograde, and
+/// raise the escaping orbit around the grandparent; the rest are
+/// retrograde, and lower it.
+pub fn escape_angle(departing: DVec2, parent_velocity: DVec2) -> f64 {
+    parent_velocity.angle_to(departing)
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum ApproachInfo<T: Clone + Copy> {
     NonIntersecting {
@@ -103,6 +112,16 @@ fn true_to_eccentric(true_anomaly: Anomaly, ecc: f64) -> Anomaly {
     }
 }
 
+/// Where a body is along its orbit at one instant, measured the three usual
+/// ways from periapsis, in radians. On escape orbits the eccentric and
+/// mean anomalies are the hyperbolic ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anomalies {
+    pub true_anomaly: f64,
+    pub eccentric: f64,
+    pub mean: f64,
+}
+
 fn eccentric_to_mean(eccentric_anomaly: Anomaly, ecc: f64) -> Anomaly {
     match eccentric_anomaly {
         Anomaly::Elliptical(v) => Anomaly::Elliptical(v - ecc * v.sin()),
@@ -116,6 +135,8 @@ pub struct Body {
     pub radius: f64,
     pub mu: f64,
     pub soi: f64,
+    #[serde(default)]
+    pub atmosphere: Option<Atmosphere>,
 }
 
 impl Body {
@@ -123,6 +144,7 @@ impl Body {
         radius: 1_737_400.0,
         mu: 4.902800118E12,
         soi: 12_000_000.0,
+        atmosphere: None,
     };
 
     pub const fn with_mass(radius: f64, mass: f64, soi: f64) -> Self {
@@ -130,11 +152,28 @@ impl Body {
             radius,
             mu: mass * 12000.0,
             soi,
+            atmosphere: None,
         }
     }
 
     pub const fn with_mu(radius: f64, mu: f64, soi: f64) -> Self {
-        Body { radius, mu, soi }
+        Body {
+            radius,
+            mu,
+            soi,
+            atmosphere: None,
+        }
+    }
+
+    pub const fn with_atmosphere(mut self, atmosphere: Atmosphere) -> Self {
+        self.atmosphere = Some(atmosphere);
+        self
+    }
+
+    /// Density of the air at the given altitude, or zero for bodies
+    /// without an atmosphere.
+    pub fn air_density(&self, altitude: f64) -> f64 {
+        self.atmosphere.map_or(0.0, |a| a.density(altitude))
     }
 
     pub fn mu(&self) -> f64 {
@@ -375,6 +414,17 @@ impl SparseOrbit {
         Some(wrap_pi_npi_f64(ta))
     }
 
+    /// Outbound true anomaly at which the orbit reaches radius `r`, if it
+    /// ever does. The inbound crossing is at the negative of this.
+    pub fn ta_at_radius(&self, r: f64) -> Option<f64> {
+        let p = self.radius_at(0.0) * (1.0 + self.eccentricity);
+        if !p.is_finite() || p <= 0.0 {
+            return None;
+        }
+        let cos = (p / r - 1.0) / self.eccentricity;
+        (-1.0..=1.0).contains(&cos).then(|| cos.acos())
+    }
+
     pub fn radius_at(&self, true_anomaly: f64) -> f64 {
         if self.eccentricity == 1.0 {
             let mu = self.body.mu() as f64;
@@ -455,6 +505,24 @@ impl SparseOrbit {
         self.position_at(0.0)
     }
 
+    /// Points from the focus toward periapsis, with a length equal to the
+    /// eccentricity.
+    pub fn eccentricity_vector(&self) -> DVec2 {
+        DVec2::from_angle(self.arg_periapsis) * self.eccentricity
+    }
+
+    pub fn anomalies_at(&self, stamp: Nanotime) -> Option<Anomalies> {
+        let ta = self.ta_at_time(stamp)?;
+        let eccentric =
+            true_to_eccentric(Anomaly::with_ecc(self.eccentricity, ta), self.eccentricity);
+        let mean = eccentric_to_mean(eccentric, self.eccentricity);
+        Some(Anomalies {
+            true_anomaly: ta,
+            eccentric: eccentric.as_f64(),
+            mean: mean.as_f64(),
+        })
+    }
+
     pub fn periapsis_r(&self) -> f64 {
         self.radius_at(0.0)
     }
@@ -503,6 +571,25 @@ impl SparseOrbit {
         Some(p * (n + 1) + tp)
     }
 
+    /// Next time after `current` at which an elliptical orbit passes
+    /// through the given true anomaly.
+    pub fn t_next_ta(&self, current: Nanotime, true_anomaly: f64) -> Option<Nanotime> {
+        if self.eccentricity >= 1.0 {
+            return None;
+        }
+        let period = self.period()?;
+        let tp = self.t_next_p(current)? - period;
+        let eccentric = true_to_eccentric(
+            Anomaly::with_ecc(self.eccentricity, wrap_pi_npi_f64(true_anomaly)),
+            self.eccentricity,
+        );
+        let mean = eccentric_to_mean(eccentric, self.eccentricity)
+            .as_f64()
+            .rem_euclid(2.0 * PI_64);
+        let t = tp + Nanotime::secs_f64(mean / self.mean_motion());
+        Some(if t <= current { t + period } else { t })
+    }
+
     pub fn asymptotes(&self) -> Option<(DVec2, DVec2)> {
         if self.eccentricity < 1.0 {
             return None;
@@ -516,6 +603,26 @@ impl SparseOrbit {
         Some((u.rotate(ua), u.rotate(ub)))
     }
 
+    /// Speed which the orbiter keeps far from the body on a hyperbolic
+    /// orbit, also known as the hyperbolic excess velocity.
+    pub fn v_infinity(&self) -> Option<f64> {
+        (self.eccentricity > 1.0 && self.semi_major_axis < 0.0)
+            .then(|| (-self.body.mu() / self.semi_major_axis).sqrt())
+    }
+
+    /// Unit directions of travel at infinity on a hyperbolic orbit, as
+    /// (arriving, departing).
+    pub fn asymptote_directions(&self) -> Option<(DVec2, DVec2)> {
+        if self.eccentricity <= 1.0 {
+            return None;
+        }
+        let ta = (-1.0 / self.eccentricity).acos();
+        let s = if self.is_retrograde() { -1.0 } else { 1.0 };
+        let arriving = -DVec2::from_angle(self.arg_periapsis - s * ta);
+        let departing = DVec2::from_angle(self.arg_periapsis + s * ta);
+        Some((arriving, departing))
+    }
+
     pub fn center(&self) -> DVec2 {
         (self.apoapsis() + self.periapsis()) / 2.0
     }
@@ -1136,6 +1243,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn true_anomaly_at_radius() {
+        for orbit in consistency_orbits(make_earth()) {
+            if !orbit.semi_major_axis.is_finite() {
+                assert!(orbit.ta_at_radius(1000.0).is_none());
+                continue;
+            }
+            let r = orbit.body.soi / 2.0;
+            let Some(ta) = orbit.ta_at_radius(r) else {
+                assert!(orbit.periapsis_r() > r || orbit.apoapsis_r() < r);
+                continue;
+            };
+            assert_ge!(ta, 0.0);
+            assert_relative_eq!(orbit.radius_at(ta), r, max_relative = 1E-6);
+            assert_relative_eq!(orbit.radius_at(-ta), r, max_relative = 1E-6);
+        }
+    }
+
     #[test]
     fn orbit_001_elliptical() {
         orbit_consistency_test(
@@ -1456,6 +1581,43 @@ mod tests {
         assert_relative_eq!(stumpff_3(20.0), 0.060859215);
     }
 
+    #[test]
+    fn hyperbolic_asymptotes() {
+        let body = Body {
+            radius: 100.0,
+            mu: 1000.0 * 12000.0,
+            soi: 1E12,
+            atmosphere: None,
+        };
+        let vel = DVec2::new(-300.0, 0.0);
+
+        // prograde, then the same orbit mirrored to run retrograde
+        for pos in [DVec2::new(500.0, 300.0), DVec2::new(500.0, -300.0)] {
+            let orbit = SparseOrbit::from_pv((pos, vel), body, Nanotime::zero()).unwrap();
+            assert_eq!(orbit.is_retrograde(), pos.y < 0.0);
+            let vinf = orbit.v_infinity().unwrap();
+            let energy = vel.length_squared() - 2.0 * body.mu() / pos.length();
+            assert_relative_eq!(vinf, energy.sqrt(), max_relative = 1E-6);
+
+            let (arriving, departing) = orbit.asymptote_directions().unwrap();
+            let late = orbit.pv(Nanotime::secs(2000)).unwrap().vel;
+            let early = orbit.pv(Nanotime::secs(-2000)).unwrap().vel;
+            assert_lt!(late.angle_to(departing).abs(), 0.02);
+            assert_lt!(early.angle_to(arriving).abs(), 0.02);
+            assert_relative_eq!(late.length(), vinf, max_relative = 0.02);
+        }
+
+        let circular = SparseOrbit::circular(500.0, body, Nanotime::zero(), false);
+        assert!(circular.v_infinity().is_none());
+        assert!(circular.asymptote_directions().is_none());
+
+        assert_lt!(
+            escape_angle(DVec2::new(1.0, 0.5), DVec2::X).abs(),
+            PI_64 / 2.0
+        );
+        assert_gt!(escape_angle(-DVec2::X, DVec2::X).abs(), PI_64 / 2.0);
+    }
+
     #[test]
     fn inverse_orbit() {
         const TEST_POSITION: DVec2 = DVec2::new(500.0, 300.0);
@@ -1465,6 +1627,7 @@ mod tests {
             radius: 100.0,
             mu: 1000.0 * 12000.0,
             soi: 10000.0,
+            atmosphere: None,
         };
 
         let o1 =
@@ -1504,6 +1667,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn anomalies_follow_keplers_equation() {
+        let body = Body::with_mass(63.0, 1000.0, 15000.0);
+        let pv = PV::from_f64((430.0, 230.0), (-50.14, 40.13));
+        let orbit = SparseOrbit::from_pv(pv, body, Nanotime::zero()).unwrap();
+        let e = orbit.ecc();
+
+        let ev = orbit.eccentricity_vector();
+        assert_relative_eq!(ev.length(), e, epsilon = 1e-9);
+        assert_relative_eq!(
+            ev.normalize().dot(orbit.periapsis().normalize()),
+            1.0,
+            epsilon = 1e-9
+        );
+
+        let period = orbit.period().unwrap();
+        let n = orbit.mean_motion();
+        let mut last: Option<(Nanotime, Anomalies)> = None;
+        for t in tspace(Nanotime::zero(), period, 40) {
+            let a = orbit.anomalies_at(t).unwrap();
+            assert_relative_eq!(a.mean, a.eccentric - e * a.eccentric.sin(), epsilon = 1e-9);
+            let pos = orbit.pv(t).unwrap().pos;
+            assert_lt!(
+                orbit.position_at(a.true_anomaly).distance(pos),
+                1e-3 * pos.length()
+            );
+            // mean anomaly advances uniformly with time
+            if let Some((t0, last)) = last {
+                let dm = wrap_0_2pi_f64(a.mean - last.mean);
+                let dt = (t - t0).to_secs_f64();
+                assert_relative_eq!(dm, n * dt, epsilon = 1e-3);
+            }
+            last = Some((t, a));
+        }
+    }
+
     #[test]
     fn time_at_periapsis() {
         let body = Body::with_mass(50.0, 1000.0, 1E8);
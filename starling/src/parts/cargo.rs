@@ -1,13 +1,38 @@
 use crate::factory::{Item, Mass};
 use crate::math::*;
+use enum_iterator::Sequence;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Restricts which items a cargo container will accept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Sequence, Deserialize, Serialize)]
+pub enum CargoFilter {
+    /// Any solid cargo.
+    #[default]
+    Any,
+    /// Propellants only, carried in canisters.
+    Fuel,
+    /// Food only, including perishables like milk.
+    Food,
+}
+
+impl CargoFilter {
+    pub fn allows(&self, item: Item) -> bool {
+        match self {
+            Self::Any => item.is_solid_cargo(),
+            Self::Fuel => item.is_fuel(),
+            Self::Food => item.is_food(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Cargo {
     name: String,
     dry_mass: Mass,
     max_cargo_mass: Mass,
     dims: UVec2,
+    #[serde(default)]
+    filter: CargoFilter,
 }
 
 impl Cargo {
@@ -17,9 +42,15 @@ impl Cargo {
             dry_mass,
             max_cargo_mass,
             dims,
+            filter: CargoFilter::Any,
         }
     }
 
+    pub fn with_filter(mut self, filter: CargoFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
     pub fn part_name(&self) -> &str {
         &self.name
     }
@@ -36,12 +67,35 @@ impl Cargo {
         self.max_cargo_mass
     }
 
+    pub fn filter(&self) -> CargoFilter {
+        self.filter
+    }
+
+    pub fn accepts(&self, item: Item) -> bool {
+        self.filter.allows(item)
+    }
+
+    pub fn percent_filled(&self, data: &CargoInstanceData) -> f64 {
+        if self.max_cargo_mass == Mass::ZERO {
+            return 0.0;
+        }
+        data.contents_mass().to_kg_f64() / self.max_cargo_mass.to_kg_f64()
+    }
+
+    /// Room left for `item`, which is zero if this container won't take it.
+    pub fn remaining_capacity(&self, item: Item, data: &CargoInstanceData) -> Mass {
+        if !self.accepts(item) || (!data.has_any(item) && !data.has_empty_slot()) {
+            return Mass::ZERO;
+        }
+        self.capacity_mass() - data.contents_mass().clamp(Mass::ZERO, self.capacity_mass())
+    }
+
     pub fn put(&self, item: Item, mass: Mass, data: &mut CargoInstanceData) {
         if !data.has_any(item) && !data.has_empty_slot() {
             return;
         }
 
-        if !item.is_solid_cargo() {
+        if !self.accepts(item) {
             return;
         }
 
@@ -61,7 +115,7 @@ impl Cargo {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CargoInstanceData {
     contents: [Option<(Item, Mass)>; 4],
 }
@@ -101,11 +155,34 @@ impl CargoInstanceData {
             .sum()
     }
 
-    pub fn put(&mut self, item: Item, mass: Mass) {
-        if !item.is_solid_cargo() {
-            return;
+    pub fn mass_of(&self, item: Item) -> Mass {
+        self.contents()
+            .filter(|(i, _)| *i == item)
+            .map(|(_, mass)| mass)
+            .sum()
+    }
+
+    /// Takes up to `mass` of `item` out of the container, freeing its slot
+    /// if all of it is taken. Returns the mass taken.
+    pub fn take(&mut self, item: Item, mass: Mass) -> Mass {
+        let mut taken = Mass::ZERO;
+        for slot in &mut self.contents {
+            let Some((i, stored)) = *slot else {
+                continue;
+            };
+            if i != item {
+                continue;
+            }
+            let t = (mass - taken).clamp(Mass::ZERO, stored);
+            taken += t;
+            *slot = (t < stored).then(|| (i, stored - t));
         }
+        taken
+    }
 
+    /// Stores items without any checks; containers decide what they
+    /// accept and how much, in `Cargo::put`.
+    pub fn put(&mut self, item: Item, mass: Mass) {
         if self.has_any(item) {
             for slot in &mut self.contents {
                 if let Some((slot, stored)) = slot {
@@ -124,3 +201,42 @@ impl CargoInstanceData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cargo_filters_restrict_contents() {
+        let food = Cargo::new(
+            "pantry".into(),
+            Mass::kilograms(100),
+            Mass::kilograms(1000),
+            UVec2::new(20, 20),
+        )
+        .with_filter(CargoFilter::Food);
+        let mut data = CargoInstanceData::new();
+
+        food.put(Item::Iron, Mass::kilograms(10), &mut data);
+        food.put(Item::Methane, Mass::kilograms(10), &mut data);
+        assert_eq!(data.contents_mass(), Mass::ZERO);
+        assert_eq!(food.remaining_capacity(Item::Iron, &data), Mass::ZERO);
+
+        food.put(Item::Milk, Mass::kilograms(300), &mut data);
+        food.put(Item::Bread, Mass::kilograms(900), &mut data);
+        assert_eq!(data.mass_of(Item::Milk), Mass::kilograms(300));
+        assert_eq!(data.mass_of(Item::Bread), Mass::kilograms(700));
+        assert_eq!(food.percent_filled(&data), 1.0);
+        assert_eq!(food.remaining_capacity(Item::Corn, &data), Mass::ZERO);
+
+        let general = Cargo::new(
+            "crate".into(),
+            Mass::kilograms(100),
+            Mass::kilograms(1000),
+            UVec2::new(20, 20),
+        );
+        assert!(general.accepts(Item::Iron));
+        assert!(!general.accepts(Item::Milk));
+        assert!(!general.accepts(Item::H2));
+    }
+}
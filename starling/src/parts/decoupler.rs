@@ -0,0 +1,42 @@
+use crate::factory::Mass;
+use crate::math::*;
+use serde::{Deserialize, Serialize};
+
+/// Structural ring holding one stage of a vehicle to the next, which
+/// comes apart when its stage is jettisoned.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct Decoupler {
+    name: String,
+    dims: UVec2,
+    mass: Mass,
+    /// Speed, in meters per second, at which the spent stage is pushed
+    /// away on separation.
+    ejection_speed: f64,
+}
+
+impl Decoupler {
+    pub fn new(name: String, dims: UVec2, mass: Mass, ejection_speed: f64) -> Self {
+        Self {
+            name,
+            dims,
+            mass,
+            ejection_speed,
+        }
+    }
+
+    pub fn part_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn dims(&self) -> UVec2 {
+        self.dims
+    }
+
+    pub fn mass(&self) -> Mass {
+        self.mass
+    }
+
+    pub fn ejection_speed(&self) -> f64 {
+        self.ejection_speed
+    }
+}
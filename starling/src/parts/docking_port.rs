@@ -0,0 +1,31 @@
+use crate::factory::Mass;
+use crate::math::*;
+use serde::{Deserialize, Serialize};
+
+/// Mating ring at which two vehicles can dock. The port faces the way
+/// the part is rotated, and two ports latch together at their outer
+/// faces, joining the vehicles into one.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct DockingPort {
+    name: String,
+    dims: UVec2,
+    mass: Mass,
+}
+
+impl DockingPort {
+    pub fn new(name: String, dims: UVec2, mass: Mass) -> Self {
+        Self { name, dims, mass }
+    }
+
+    pub fn part_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn dims(&self) -> UVec2 {
+        self.dims
+    }
+
+    pub fn mass(&self) -> Mass {
+        self.mass
+    }
+}
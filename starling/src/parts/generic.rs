@@ -1,14 +1,18 @@
+use crate::aerodynamics::AeroSurface;
 use crate::factory::Mass;
 use crate::math::*;
 use crate::parts::PartLayer;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Generic {
     name: String,
     dims: UVec2,
     layer: PartLayer,
     mass: Mass,
+    /// Present for parts which generate lift, such as wings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aero: Option<AeroSurface>,
 }
 
 impl Generic {
@@ -18,6 +22,7 @@ impl Generic {
             dims,
             layer,
             mass,
+            aero: None,
         }
     }
 
@@ -36,4 +41,8 @@ impl Generic {
     pub fn mass(&self) -> Mass {
         self.mass
     }
+
+    pub fn aero(&self) -> Option<AeroSurface> {
+        self.aero
+    }
 }
@@ -1,6 +1,6 @@
 use crate::prelude::PHYSICS_CONSTANT_DELTA_TIME;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Gyro {
     pub target_velocity: f64,
     pub angular_velocity: f64,
@@ -2,13 +2,13 @@ use crate::factory::*;
 use crate::math::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 pub struct Machine {
     dims: UVec2,
     mass: Mass,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct MachineInstanceData {
     pub recipe: RecipeListing,
     pub steps_completed: u32,
@@ -2,7 +2,7 @@ use crate::factory::Mass;
 use crate::math::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Magnetorquer {
     dims: UVec2,
     part_name: String,
@@ -10,7 +10,7 @@ pub struct Magnetorquer {
     mass: Mass,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MagnetorquerInstanceData {
     current_torque: f32,
 }
@@ -1,4 +1,6 @@
 pub mod cargo;
+pub mod decoupler;
+pub mod docking_port;
 pub mod generic;
 pub mod gyro;
 pub mod machine;
@@ -10,6 +12,8 @@ pub mod tank;
 pub mod thruster;
 
 pub use cargo::*;
+pub use decoupler::*;
+pub use docking_port::*;
 pub use generic::*;
 pub use gyro::*;
 pub use machine::*;
@@ -1,4 +1,5 @@
 use crate::aabb::*;
+use crate::aerodynamics::AeroSurface;
 use crate::factory::Mass;
 use crate::math::*;
 use crate::parts::*;
@@ -17,6 +18,8 @@ pub enum PartPrototype {
     Magnetorquer(Magnetorquer),
     Machine(Machine),
     Generic(Generic),
+    Decoupler(Decoupler),
+    DockingPort(DockingPort),
 }
 
 pub fn rotate_dims(rot: Rotation, part_meters: Vec2) -> Vec2 {
@@ -37,6 +40,8 @@ impl PartPrototype {
             Self::Magnetorquer(p) => p.dims(),
             Self::Generic(p) => p.dims(),
             Self::Machine(p) => p.dims(),
+            Self::Decoupler(p) => p.dims(),
+            Self::DockingPort(p) => p.dims(),
         }
     }
 
@@ -53,6 +58,8 @@ impl PartPrototype {
             Self::Magnetorquer(p) => p.part_name(),
             Self::Generic(p) => p.part_name(),
             Self::Machine(p) => p.part_name(),
+            Self::Decoupler(p) => p.part_name(),
+            Self::DockingPort(p) => p.part_name(),
         }
     }
 
@@ -65,6 +72,8 @@ impl PartPrototype {
             Self::Magnetorquer(p) => p.mass(),
             Self::Generic(p) => p.mass(),
             Self::Machine(p) => p.mass(),
+            Self::Decoupler(p) => p.mass(),
+            Self::DockingPort(p) => p.mass(),
         }
     }
 
@@ -77,6 +86,8 @@ impl PartPrototype {
             Self::Magnetorquer(..) => PartLayer::Internal,
             Self::Generic(p) => p.layer(),
             Self::Machine(..) => PartLayer::Internal,
+            Self::Decoupler(..) => PartLayer::Structural,
+            Self::DockingPort(..) => PartLayer::Structural,
         }
     }
 
@@ -118,7 +129,7 @@ impl PartLayer {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum InstantiatedPartVariant {
     Thruster(ThrusterModel, ThrusterInstanceData),
     Tank(TankModel, TankInstanceData),
@@ -127,15 +138,21 @@ pub enum InstantiatedPartVariant {
     Magnetorquer(Magnetorquer, MagnetorquerInstanceData),
     Machine(Machine, MachineInstanceData),
     Generic(Generic),
+    Decoupler(Decoupler),
+    DockingPort(DockingPort),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InstantiatedPart {
     builds_performed: u32,
     builds_required: u32,
     pos: IVec2,
     rot: Rotation,
     dims: UVec2,
+    /// Stage the part belongs to. Stages are jettisoned in ascending
+    /// order, so the lowest numbered stage still attached is the active
+    /// one.
+    stage: usize,
     variant: InstantiatedPartVariant,
 }
 
@@ -162,6 +179,8 @@ impl InstantiatedPart {
         let variant = match proto {
             PartPrototype::Cargo(c) => InstantiatedPartVariant::Cargo(c, CargoInstanceData::new()),
             PartPrototype::Generic(g) => InstantiatedPartVariant::Generic(g),
+            PartPrototype::Decoupler(d) => InstantiatedPartVariant::Decoupler(d),
+            PartPrototype::DockingPort(d) => InstantiatedPartVariant::DockingPort(d),
             PartPrototype::Machine(m) => {
                 InstantiatedPartVariant::Machine(m, MachineInstanceData::default())
             }
@@ -181,6 +200,7 @@ impl InstantiatedPart {
             pos,
             rot,
             dims,
+            stage: 0,
             variant,
         }
     }
@@ -194,6 +214,8 @@ impl InstantiatedPart {
             InstantiatedPartVariant::Magnetorquer(m, _) => PartPrototype::Magnetorquer(m),
             InstantiatedPartVariant::Machine(m, _) => PartPrototype::Machine(m),
             InstantiatedPartVariant::Generic(g) => PartPrototype::Generic(g),
+            InstantiatedPartVariant::Decoupler(d) => PartPrototype::Decoupler(d),
+            InstantiatedPartVariant::DockingPort(d) => PartPrototype::DockingPort(d),
         }
     }
 
@@ -210,6 +232,8 @@ impl InstantiatedPart {
             InstantiatedPartVariant::Magnetorquer(m, _) => m.mass(),
             InstantiatedPartVariant::Machine(m, _) => m.mass(),
             InstantiatedPartVariant::Generic(g) => g.mass(),
+            InstantiatedPartVariant::Decoupler(d) => d.mass(),
+            InstantiatedPartVariant::DockingPort(d) => d.mass(),
         }
     }
 
@@ -223,6 +247,23 @@ impl InstantiatedPart {
         self.builds_performed = self.builds_required;
     }
 
+    pub fn unbuild_all(&mut self) {
+        self.builds_performed = 0;
+    }
+
+    pub fn builds_performed(&self) -> u32 {
+        self.builds_performed
+    }
+
+    pub fn set_builds_performed(&mut self, builds: u32) {
+        self.builds_performed = builds.min(self.builds_required);
+    }
+
+    /// Number of builds needed to construct the part from scratch.
+    pub fn builds_required(&self) -> u32 {
+        self.builds_required
+    }
+
     pub fn percent_built(&self) -> f32 {
         (self.builds_performed as f32 / self.builds_required as f32).clamp(0.0, 1.0)
     }
@@ -280,6 +321,14 @@ impl InstantiatedPart {
         self.rot
     }
 
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+
+    pub fn set_stage(&mut self, stage: usize) {
+        self.stage = stage;
+    }
+
     pub fn set_rotation(&mut self, rot: Rotation) {
         self.rot = rot;
     }
@@ -385,4 +434,28 @@ impl InstantiatedPart {
             None
         }
     }
+
+    pub fn as_decoupler(&self) -> Option<&Decoupler> {
+        if let InstantiatedPartVariant::Decoupler(d) = &self.variant {
+            Some(d)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_docking_port(&self) -> Option<&DockingPort> {
+        if let InstantiatedPartVariant::DockingPort(d) = &self.variant {
+            Some(d)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_aero_surface(&self) -> Option<AeroSurface> {
+        if let InstantiatedPartVariant::Generic(g) = &self.variant {
+            g.aero()
+        } else {
+            None
+        }
+    }
 }
@@ -2,7 +2,7 @@ use crate::factory::Mass;
 use crate::math::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 pub struct Radar {
     dims: UVec2,
     mass: Mass,
@@ -2,7 +2,7 @@ use crate::factory::{Item, Mass};
 use crate::math::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct TankModel {
     name: String,
     dims: UVec2,
@@ -11,6 +11,15 @@ pub struct TankModel {
 }
 
 impl TankModel {
+    pub fn new(name: String, dims: UVec2, dry_mass: Mass, max_fluid_mass: Mass) -> Self {
+        Self {
+            name,
+            dims,
+            dry_mass,
+            max_fluid_mass,
+        }
+    }
+
     pub fn part_name(&self) -> &str {
         &self.name
     }
@@ -56,12 +65,21 @@ impl TankModel {
         self.max_fluid_mass
     }
 
+    /// Room left for `item`, which is zero if the tank holds something
+    /// else or it isn't a fluid.
+    pub fn remaining_capacity(&self, item: Item, data: &TankInstanceData) -> Mass {
+        if !item.is_fluid() || data.item().is_some_and(|i| i != item) {
+            return Mass::ZERO;
+        }
+        self.max_fluid_mass - data.contents_mass().clamp(Mass::ZERO, self.max_fluid_mass)
+    }
+
     pub fn percent_filled(&self, data: &TankInstanceData) -> f64 {
         data.contents_mass().to_kg_f64() / self.max_fluid_mass.to_kg_f64()
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
 pub struct TankInstanceData {
     stored: Option<(Item, Mass)>,
 }
@@ -84,4 +102,15 @@ impl TankInstanceData {
     pub fn clear_contents(&mut self) {
         self.stored = None;
     }
+
+    /// Drains up to `mass` of the contents, leaving the tank empty if all
+    /// of it is taken. Returns the mass drained.
+    pub fn take(&mut self, mass: Mass) -> Mass {
+        let Some((item, stored)) = self.stored else {
+            return Mass::ZERO;
+        };
+        let taken = mass.clamp(Mass::ZERO, stored);
+        self.stored = (taken < stored).then(|| (item, stored - taken));
+        taken
+    }
 }
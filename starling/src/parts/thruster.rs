@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 /// Definition of a thruster model.
 /// These are stats common to all thrusters
 /// of a given type, i.e. F1, J2, LEM descent, etc
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct ThrusterModel {
     dims: UVec2,
     mass: Mass,
@@ -57,7 +57,7 @@ impl ThrusterModel {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct ThrusterInstanceData {
     throttle: f32,
     target_throttle: f32,
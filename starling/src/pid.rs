@@ -1,6 +1,6 @@
 use crate::math::rand;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PDCtrl {
     kp: f64,
     kd: f64,
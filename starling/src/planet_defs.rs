@@ -0,0 +1,295 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+/// Name of the planetary system the game starts in.
+pub const DEFAULT_PLANET_SYSTEM: &str = "rss";
+
+/// Loaded planetary systems, by name.
+pub type PlanetCatalogs = HashMap<String, PlanetCatalog>;
+
+/// Parent body, by name, and the orbit about it.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PlanetOrbitDefinition {
+    pub parent: String,
+    #[serde(flatten)]
+    pub spec: OrbitSpec,
+}
+
+/// A planet or moon as written in a planet definition file. Exactly one of
+/// `mu` or `mass` must be given; mass is in kilograms.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PlanetDefinition {
+    pub name: String,
+    pub radius: f64,
+    #[serde(default)]
+    pub mu: Option<f64>,
+    #[serde(default)]
+    pub mass: Option<f64>,
+    pub soi: f64,
+    #[serde(default)]
+    pub atmosphere: Option<Atmosphere>,
+    /// Tint used for this body in menus and lists.
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+    /// Sprite drawn for this body, defaulting to its name.
+    #[serde(default)]
+    pub sprite: Option<String>,
+    /// The root of the system has no orbit.
+    #[serde(default)]
+    pub orbit: Option<PlanetOrbitDefinition>,
+}
+
+impl PlanetDefinition {
+    pub fn body(&self) -> Option<Body> {
+        let mu = match (self.mu, self.mass) {
+            (Some(mu), None) => mu,
+            (None, Some(mass)) => mass * GRAVITATIONAL_CONSTANT,
+            _ => return None,
+        };
+        let body = Body::with_mu(self.radius, mu, self.soi);
+        Some(match self.atmosphere {
+            Some(a) => body.with_atmosphere(a),
+            None => body,
+        })
+    }
+
+    pub fn sprite(&self) -> &str {
+        self.sprite.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanetDefError {
+    Empty,
+    UnknownSystem(String),
+    DuplicateName(String),
+    BadMass(String),
+    UnknownParent(String, String),
+    Invalid(String, ScenarioIssue),
+}
+
+impl std::fmt::Display for PlanetDefError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "No planets defined"),
+            Self::UnknownSystem(n) => write!(f, "No planetary system named \"{}\"", n),
+            Self::DuplicateName(n) => write!(f, "Planet \"{}\" is defined twice", n),
+            Self::BadMass(n) => write!(f, "Planet \"{}\" needs exactly one of mu or mass", n),
+            Self::UnknownParent(n, p) => {
+                write!(f, "Planet \"{}\" orbits unknown body \"{}\"", n, p)
+            }
+            Self::Invalid(n, issue) => write!(f, "Planet \"{}\": {}", n, issue),
+        }
+    }
+}
+
+impl Error for PlanetDefError {}
+
+/// A planetary system loaded from the assets. Bodies are given IDs derived
+/// from their names, so scenarios and saves referring to them see the same
+/// IDs however the definitions are ordered.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct PlanetCatalog {
+    #[serde(skip)]
+    pub name: String,
+    pub planets: Vec<PlanetDefinition>,
+}
+
+impl PlanetCatalog {
+    pub fn get(&self, name: &str) -> Option<&PlanetDefinition> {
+        self.planets.iter().find(|p| p.name == name)
+    }
+
+    /// Flattens the definitions into scenario bodies, checked with the same
+    /// rules as the scenario editor.
+    pub fn to_scenario(&self) -> Result<Scenario, PlanetDefError> {
+        if self.planets.is_empty() {
+            return Err(PlanetDefError::Empty);
+        }
+
+        let mut ids = HashMap::new();
+        for p in &self.planets {
+            let id = EntityId::named(EntityKind::Planet, &p.name);
+            if ids.insert(p.name.as_str(), id).is_some() {
+                return Err(PlanetDefError::DuplicateName(p.name.clone()));
+            }
+        }
+
+        let mut bodies = Vec::new();
+        for p in &self.planets {
+            let body = p.body().ok_or(PlanetDefError::BadMass(p.name.clone()))?;
+            let orbit = match &p.orbit {
+                Some(o) => {
+                    let parent = ids.get(o.parent.as_str()).ok_or_else(|| {
+                        PlanetDefError::UnknownParent(p.name.clone(), o.parent.clone())
+                    })?;
+                    Some((*parent, o.spec))
+                }
+                None => None,
+            };
+            bodies.push(ScenarioBody {
+                id: ids[p.name.as_str()],
+                name: p.name.clone(),
+                body,
+                orbit,
+            });
+        }
+
+        let scenario = Scenario {
+            name: self.name.clone(),
+            bodies,
+            system: Some(self.name.clone()),
+            ..Default::default()
+        };
+
+        if let Some(issue) = scenario.validate().first() {
+            let name = scenario
+                .bodies
+                .iter()
+                .find(|b| issue.involves(b.id))
+                .map(|b| b.name.clone())
+                .unwrap_or_default();
+            return Err(PlanetDefError::Invalid(name, *issue));
+        }
+
+        Ok(scenario)
+    }
+
+    pub fn to_system(&self) -> Result<PlanetarySystem, PlanetDefError> {
+        let scenario = self.to_scenario()?;
+        scenario
+            .to_system()
+            .map_err(|e| PlanetDefError::Invalid(String::new(), e))
+    }
+}
+
+/// Replaces the bodies of a scenario which names its planetary system with
+/// that system's. Sites and vehicles on bodies of the same name are moved
+/// over to the system's IDs. Scenarios without one are left alone.
+pub fn resolve_scenario_system(
+    scenario: &mut Scenario,
+    catalogs: &PlanetCatalogs,
+) -> Result<(), PlanetDefError> {
+    let Some(name) = &scenario.system else {
+        return Ok(());
+    };
+    let catalog = catalogs
+        .get(name)
+        .ok_or_else(|| PlanetDefError::UnknownSystem(name.clone()))?;
+    let bodies = catalog.to_scenario()?.bodies;
+    let mut remap = IdRemap::default();
+    for old in &scenario.bodies {
+        if let Some(new) = bodies.iter().find(|b| b.name == old.name) {
+            remap.insert(old.id, new.id);
+        }
+    }
+    scenario.remap_ids(&remap);
+    scenario.bodies = bodies;
+    Ok(())
+}
+
+/// Loads and validates a single planetary system. The system is named
+/// after the file.
+pub fn load_planet_catalog_from_file(path: &Path) -> Result<PlanetCatalog, Box<dyn Error>> {
+    let s = std::fs::read_to_string(path)?;
+    let mut catalog: PlanetCatalog = serde_yaml::from_str(&s)?;
+    catalog.name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    catalog.to_scenario()?;
+    Ok(catalog)
+}
+
+/// Loads every planetary system in the directory, by name, along with a
+/// message for each file which failed to load.
+pub fn load_planet_catalogs_from_dir(
+    dir: &Path,
+) -> Result<(PlanetCatalogs, Vec<String>), Box<dyn Error>> {
+    let mut catalogs = HashMap::new();
+    let mut failures = Vec::new();
+    for entry in std::fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().map(|e| e != "yaml").unwrap_or(true) {
+            continue;
+        }
+        match load_planet_catalog_from_file(&path) {
+            Ok(c) => {
+                catalogs.insert(c.name.clone(), c);
+            }
+            Err(e) => failures.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    Ok((catalogs, failures))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = include_str!("../../assets/planets/rss.yaml");
+
+    #[test]
+    fn rss_definitions_match_the_builtin_system() {
+        let mut catalog: PlanetCatalog = serde_yaml::from_str(RSS).unwrap();
+        catalog.name = "rss".into();
+        let system = catalog.to_system().unwrap();
+        let builtin = crate::examples::rss();
+        assert_eq!(system.planet_ids(), builtin.planet_ids());
+        for id in builtin.planet_ids() {
+            let (a, pa, _, sa) = system.lookup(id, Nanotime::zero()).unwrap();
+            let (b, pb, _, sb) = builtin.lookup(id, Nanotime::zero()).unwrap();
+            assert_eq!(sa.name, sb.name);
+            assert_eq!(a, b);
+            assert!(pa.pos.distance(pb.pos) < 1.0);
+        }
+        assert!(catalog.get("Earth").unwrap().color.is_some());
+        assert_eq!(catalog.get("Luna").unwrap().sprite(), "Luna");
+
+        let mut bad = catalog.clone();
+        bad.planets[1].orbit.as_mut().unwrap().parent = "Mars".into();
+        assert_eq!(
+            bad.to_scenario(),
+            Err(PlanetDefError::UnknownParent("Luna".into(), "Mars".into()))
+        );
+
+        let mut bad = catalog.clone();
+        bad.planets[1].mass = Some(7.3E22);
+        assert_eq!(
+            bad.to_scenario(),
+            Err(PlanetDefError::BadMass("Luna".into()))
+        );
+
+        let mut bad = catalog.clone();
+        bad.planets[1].soi = bad.planets[1].radius;
+        assert!(matches!(
+            bad.to_scenario(),
+            Err(PlanetDefError::Invalid(n, ScenarioIssue::BadBody(_))) if n == "Luna"
+        ));
+
+        let catalogs = HashMap::from([("rss".to_string(), catalog.clone())]);
+        let mut scenario = Scenario {
+            name: "Moon base".into(),
+            system: Some("rss".into()),
+            ..Default::default()
+        };
+        resolve_scenario_system(&mut scenario, &catalogs).unwrap();
+        assert_eq!(scenario.bodies.len(), 2);
+        assert!(scenario.to_system().is_ok());
+        scenario.system = Some("kerbol".into());
+        assert_eq!(
+            resolve_scenario_system(&mut scenario, &catalogs),
+            Err(PlanetDefError::UnknownSystem("kerbol".into()))
+        );
+
+        let mut bad = catalog.clone();
+        bad.planets.push(bad.planets[1].clone());
+        assert_eq!(
+            bad.to_scenario(),
+            Err(PlanetDefError::DuplicateName("Luna".into()))
+        );
+    }
+}
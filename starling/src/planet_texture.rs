@@ -0,0 +1,148 @@
+use crate::math::*;
+use image::{Rgba, RgbaImage};
+use noise::{NoiseFn, Perlin};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanetTextureStyle {
+    Cratered,
+    Continents,
+    Banded,
+}
+
+impl PlanetTextureStyle {
+    pub fn from_seed(seed: u64) -> Self {
+        match seed % 3 {
+            0 => Self::Cratered,
+            1 => Self::Continents,
+            _ => Self::Banded,
+        }
+    }
+}
+
+/// Stable seed derived from a planet name, so a given planet always
+/// gets the same texture.
+pub fn planet_seed(name: &str) -> u64 {
+    // FNV-1a
+    name.bytes().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn fbm(noise: &Perlin, p: DVec3, octaves: u32) -> f64 {
+    let mut amplitude = 0.5;
+    let mut frequency = 1.0;
+    let mut sum = 0.0;
+    for _ in 0..octaves {
+        sum += amplitude * noise.get((p * frequency).to_array());
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum
+}
+
+fn random_color(rng: &mut StdRng, lo: f32, hi: f32) -> Vec3 {
+    Vec3::new(
+        rng.gen_range(lo..hi),
+        rng.gen_range(lo..hi),
+        rng.gen_range(lo..hi),
+    )
+}
+
+/// Generates a square, shaded disc texture of the given pixel size. Pixels
+/// outside the disc are fully transparent.
+pub fn generate_planet_texture(seed: u64, size: u32) -> RgbaImage {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let noise = Perlin::new(rng.gen());
+    let style = PlanetTextureStyle::from_seed(seed);
+
+    let primary = random_color(&mut rng, 0.2, 0.8);
+    let secondary = random_color(&mut rng, 0.2, 0.8);
+    let frequency = rng.gen_range(1.5..4.0);
+
+    let craters: Vec<(Vec2, f32)> = match style {
+        PlanetTextureStyle::Cratered => (0..rng.gen_range(10..40))
+            .map(|_| {
+                let c = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+                (c, rng.gen_range(0.02..0.15))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut img = RgbaImage::new(size, size);
+
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        let u = Vec2::new(
+            x as f32 / size as f32 * 2.0 - 1.0,
+            1.0 - y as f32 / size as f32 * 2.0,
+        );
+        let r2 = u.length_squared();
+        if r2 > 1.0 {
+            *pixel = Rgba([0, 0, 0, 0]);
+            continue;
+        }
+
+        let z = (1.0 - r2).sqrt();
+        let p = DVec3::new(u.x as f64, u.y as f64, z as f64) * frequency;
+        let n = fbm(&noise, p, 5) as f32;
+
+        let color = match style {
+            PlanetTextureStyle::Cratered => {
+                let mut c = primary.lerp(secondary, 0.5 + n);
+                for (center, radius) in &craters {
+                    let d = u.distance(*center) / radius;
+                    if d < 0.8 {
+                        c *= 0.75;
+                    } else if d < 1.0 {
+                        c *= 1.15;
+                    }
+                }
+                c
+            }
+            PlanetTextureStyle::Continents => {
+                let ocean = Vec3::new(0.1, 0.2, 0.5).lerp(primary, 0.2);
+                let land = Vec3::new(0.3, 0.45, 0.2).lerp(secondary, 0.3);
+                if u.y.abs() > 0.85 - n * 0.2 {
+                    Vec3::splat(0.9)
+                } else if n > 0.05 {
+                    land * (0.8 + n)
+                } else {
+                    ocean
+                }
+            }
+            PlanetTextureStyle::Banded => {
+                let band = (u.y * 12.0 + n * 3.0).sin() * 0.5 + 0.5;
+                primary.lerp(secondary, band)
+            }
+        };
+
+        // limb darkening
+        let color = (color * (0.5 + 0.5 * z)).clamp(Vec3::ZERO, Vec3::ONE);
+        *pixel = Rgba([
+            (color.x * 255.0) as u8,
+            (color.y * 255.0) as u8,
+            (color.z * 255.0) as u8,
+            255,
+        ]);
+    }
+
+    img
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_is_deterministic_disc() {
+        let seed = planet_seed("Luna");
+        let a = generate_planet_texture(seed, 64);
+        let b = generate_planet_texture(seed, 64);
+        assert_eq!(a, b);
+        assert_eq!(a.get_pixel(0, 0)[3], 0);
+        assert_eq!(a.get_pixel(32, 32)[3], 255);
+        assert_ne!(generate_planet_texture(planet_seed("Earth"), 64), a);
+    }
+}
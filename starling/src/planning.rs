@@ -1,9 +1,18 @@
+use crate::burn_alignment::BURN_HOLD_LEAD;
 use crate::math::*;
 use crate::nanotime::Nanotime;
 use crate::orbits::{vis_viva_equation, OrbitClass, SparseOrbit};
 use crate::propagator::{search_condition, ConvergeError};
 use crate::pv::PV;
 
+/// Least time ahead of now at which a rendezvous plan's first burn is put,
+/// so that the vehicle can be turned to face it in time.
+const RENDEZVOUS_MIN_LEAD: Nanotime = BURN_HOLD_LEAD;
+
+/// Most revolutions a vehicle will spend in a phasing orbit waiting to
+/// meet its target.
+const MAX_PHASING_REVOLUTIONS: u32 = 4;
+
 #[derive(Debug, Clone)]
 pub struct ManeuverPlan {
     pub initial: SparseOrbit,
@@ -78,6 +87,54 @@ impl ManeuverPlan {
         self.segments.iter().find(|s| s.is_valid(stamp))
     }
 
+    /// Time at which the outcome of the first burn is evaluated. This is the
+    /// second burn for multi-burn plans, otherwise half an orbit after the
+    /// first burn.
+    pub fn encounter_time(&self) -> Option<Nanotime> {
+        match self.segments.get(1) {
+            Some(s) => Some(s.end),
+            None => Some(self.segments.first()?.end + self.terminal.period()? / 2),
+        }
+    }
+
+    /// Position and velocity at the encounter time along the nominal plan.
+    pub fn encounter(&self) -> Option<PV> {
+        let t = self.encounter_time()?;
+        let orbit = self
+            .segments
+            .get(1)
+            .map(|s| s.orbit)
+            .unwrap_or(self.terminal);
+        orbit.pv(t).ok()
+    }
+
+    /// Executes the first burn of this plan with its magnitude scaled by
+    /// `1 + magnitude_error`, and shifted in time by `timing_error`.
+    pub fn perturbed(
+        &self,
+        magnitude_error: f64,
+        timing_error: Nanotime,
+    ) -> Option<PerturbedManeuver> {
+        let first = self.segments.first()?;
+        let t = first.end + timing_error;
+        if t <= first.start {
+            return None;
+        }
+        let pv = self.initial.pv(t).ok()? + PV::vel(first.impulse * (1.0 + magnitude_error));
+        let orbit = SparseOrbit::from_pv(pv, self.initial.body, t)?;
+        let encounter = self
+            .encounter_time()
+            .filter(|te| *te > t)
+            .and_then(|te| orbit.pv(te).ok());
+        Some(PerturbedManeuver {
+            magnitude_error,
+            timing_error,
+            start: t,
+            orbit,
+            encounter,
+        })
+    }
+
     pub fn then(&self, other: Self) -> Result<Self, &'static str> {
         if self.end() > other.start() {
             return Err("Self ends after new plan begins");
@@ -115,6 +172,17 @@ impl std::fmt::Display for ManeuverPlan {
     }
 }
 
+/// The trajectory resulting from a perturbed first burn of a
+/// `ManeuverPlan`, used to visualize how sensitive a plan is to errors.
+#[derive(Debug, Clone)]
+pub struct PerturbedManeuver {
+    pub magnitude_error: f64,
+    pub timing_error: Nanotime,
+    pub start: Nanotime,
+    pub orbit: SparseOrbit,
+    pub encounter: Option<PV>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ManeuverSegment {
     pub start: Nanotime,
@@ -305,12 +373,106 @@ fn generate_maneuver_plans(
     [direct, hohmann].into_iter().flatten().collect()
 }
 
+/// Plans a rendezvous with a target in the orbit `dst`, whose phase
+/// matters here and not just its path. If the two orbits are alike, the
+/// vehicle drops into a phasing orbit which meets the target after some
+/// whole number of revolutions; otherwise it waits for the transfer window
+/// and transfers across. Either way, a closing burn matches the target's
+/// velocity on arrival.
 pub fn rendezvous_plan(
     src: &SparseOrbit,
     dst: &SparseOrbit,
     now: Nanotime,
 ) -> Option<ManeuverPlan> {
-    hohmann_transfer(src, dst, now)
+    if src.is_retrograde() != dst.is_retrograde() {
+        return None;
+    }
+
+    let (t1, dv1, approach, t2) = if src.is_similar(dst) {
+        phasing_burn(src, dst, now + RENDEZVOUS_MIN_LEAD)?
+    } else {
+        let info = phase_info(src, dst, now)?;
+        let synodic = info.synodic_period?;
+        let mut t1 = now + info.time_to_window?;
+        while t1 < now + RENDEZVOUS_MIN_LEAD {
+            t1 += synodic;
+        }
+        rendezvous_transfer_burn(src, dst, t1)?
+    };
+
+    let before = approach.pv(t2).ok()?;
+    let after = dst.pv(t2).ok()?;
+    let dv2 = after.vel - before.vel;
+
+    ManeuverPlan::new(now, *src, &[(t1, dv1), (t2, dv2)])
+}
+
+/// Burn at `t1` onto half of a transfer ellipse which reaches the
+/// destination orbit directly across the body. Returns the burn, the
+/// orbit it leaves the vehicle on, and the time of arrival.
+fn rendezvous_transfer_burn(
+    src: &SparseOrbit,
+    dst: &SparseOrbit,
+    t1: Nanotime,
+) -> Option<(Nanotime, DVec2, SparseOrbit, Nanotime)> {
+    let mu = src.body.mu();
+    let before = src.pv(t1).ok()?;
+    let r1 = before.pos.length();
+    let r2 = dst.radius_at_angle(before.pos.to_angle() + PI_64);
+    let v1 = vis_viva_equation(mu, r1, (r1 + r2) / 2.0);
+
+    // burning along the horizontal puts an apsis of the transfer here
+    let mut horizontal = before.pos.perp().normalize_or_zero();
+    if horizontal.dot(before.vel) < 0.0 {
+        horizontal = -horizontal;
+    }
+    let after = PV::from_f64(before.pos, horizontal * v1);
+    let transfer = SparseOrbit::from_pv(after, src.body, t1)?;
+    let t2 = t1 + transfer.period()? / 2;
+    Some((t1, after.vel - before.vel, transfer, t2))
+}
+
+/// Burn at `t1` onto a phasing orbit which comes back around to the same
+/// point just as the target reaches it. The fewest revolutions for which
+/// the phasing orbit stays clear of the surface are used, and of the two
+/// ways to phase, by dropping lower to catch up or climbing higher to let
+/// the target lap around, the cheaper one.
+fn phasing_burn(
+    src: &SparseOrbit,
+    dst: &SparseOrbit,
+    t1: Nanotime,
+) -> Option<(Nanotime, DVec2, SparseOrbit, Nanotime)> {
+    let mu = src.body.mu();
+    let before = src.pv(t1).ok()?;
+    let target = dst.pv(t1).ok()?;
+    let dir = if src.is_retrograde() { -1.0 } else { 1.0 };
+    let lead = wrap_0_2pi_f64(dir * before.pos.angle_to(target.pos));
+    let n = dst.mean_motion();
+    let r = before.pos.length();
+
+    for revs in 1..=MAX_PHASING_REVOLUTIONS {
+        let k = revs as f64;
+        let candidates = [
+            (2.0 * PI_64 * k - lead) / (n * k),
+            (2.0 * PI_64 * (k + 1.0) - lead) / (n * k),
+        ];
+        let best = candidates
+            .into_iter()
+            .filter_map(|period| {
+                let a = (mu * (period / (2.0 * PI_64)).powi(2)).cbrt();
+                let vel = before.vel.normalize_or_zero() * vis_viva_equation(mu, r, a);
+                let phasing = SparseOrbit::from_pv(PV::from_f64(before.pos, vel), src.body, t1)?;
+                let clear =
+                    phasing.periapsis_r() > src.body.radius && phasing.apoapsis_r() < src.body.soi;
+                clear.then(|| (vel - before.vel, phasing))
+            })
+            .min_by(|(a, _), (b, _)| a.length().total_cmp(&b.length()));
+        if let Some((dv, phasing)) = best {
+            let t2 = t1 + phasing.period()? * revs;
+            return Some((t1, dv, phasing, t2));
+        }
+    }
+    None
 }
 
 pub fn best_maneuver_plan(
@@ -327,6 +489,195 @@ pub fn best_maneuver_plan(
     plans.first().cloned().ok_or("No plan")
 }
 
+/// Phase angle readouts between two elliptical orbits around the same
+/// body, treating both as circular for the purposes of transfer timing.
+#[derive(Debug, Clone, Copy)]
+pub struct PhaseInfo {
+    /// Angle from the source to the target, measured in the source's
+    /// direction of travel, in [0, 2pi).
+    pub current: f64,
+    /// Phase angle at which a Hohmann transfer departing now would arrive
+    /// at the target, in [0, 2pi).
+    pub required: f64,
+    /// Time until the current phase angle next equals the required one.
+    pub time_to_window: Option<Nanotime>,
+    /// Time between successive transfer windows.
+    pub synodic_period: Option<Nanotime>,
+}
+
+pub fn phase_info(src: &SparseOrbit, dst: &SparseOrbit, stamp: Nanotime) -> Option<PhaseInfo> {
+    src.period()?;
+    dst.period()?;
+
+    let p1 = src.pv(stamp).ok()?.pos;
+    let p2 = dst.pv(stamp).ok()?.pos;
+
+    let sign = |o: &SparseOrbit| if o.is_retrograde() { -1.0 } else { 1.0 };
+    let dir = sign(src);
+
+    let current = wrap_0_2pi_f64(dir * p1.angle_to(p2));
+
+    let mu = src.body.mu();
+    let a_transfer = (src.semi_major_axis + dst.semi_major_axis) / 2.0;
+    let transfer_time = PI_64 * (a_transfer.powi(3) / mu).sqrt();
+    let n1 = src.mean_motion();
+    let n2 = sign(dst) * dir * dst.mean_motion();
+    let required = wrap_0_2pi_f64(PI_64 - n2 * transfer_time);
+
+    // rate at which the phase angle changes, in the source's frame
+    let rate = n2 - n1;
+    let (time_to_window, synodic_period) = if rate.abs() < 1E-12 {
+        (None, None)
+    } else {
+        let dphi = if rate > 0.0 {
+            wrap_0_2pi_f64(required - current)
+        } else {
+            wrap_0_2pi_f64(current - required)
+        };
+        (
+            Some(Nanotime::secs_f64(dphi / rate.abs())),
+            Some(Nanotime::secs_f64(2.0 * PI_64 / rate.abs())),
+        )
+    };
+
+    Some(PhaseInfo {
+        current,
+        required,
+        time_to_window,
+        synodic_period,
+    })
+}
+
+/// Smallest impulsive burn, applied at `stamp`, which raises the periapsis
+/// of `orbit` to at least `min_periapsis_r`. Burn directions are sampled
+/// around the full circle, and the magnitude bisected along each.
+pub fn periapsis_raise_burn(
+    orbit: &SparseOrbit,
+    stamp: Nanotime,
+    min_periapsis_r: f64,
+) -> Option<DVec2> {
+    let pv = orbit.pv(stamp).ok()?;
+    let raises = |dv: DVec2| {
+        SparseOrbit::from_pv(PV::from_f64(pv.pos, pv.vel + dv), orbit.body, stamp)
+            .map(|o| o.periapsis_r() >= min_periapsis_r)
+            .unwrap_or(false)
+    };
+
+    if raises(DVec2::ZERO) {
+        return Some(DVec2::ZERO);
+    }
+
+    let escape = (2.0 * orbit.body.mu() / pv.pos.length()).sqrt();
+    let max_dv = pv.vel.length() + escape;
+
+    linspace_f64(0.0, 2.0 * PI_64, 180)
+        .into_iter()
+        .filter_map(|angle| {
+            let u = rotate_f64(DVec2::X, angle);
+            if !raises(u * max_dv) {
+                return None;
+            }
+            let (mut lo, mut hi) = (0.0, max_dv);
+            for _ in 0..40 {
+                let mid = (lo + hi) / 2.0;
+                if raises(u * mid) {
+                    hi = mid;
+                } else {
+                    lo = mid;
+                }
+            }
+            Some(u * hi)
+        })
+        .min_by(|a, b| a.length().total_cmp(&b.length()))
+}
+
+/// Single-burn change to the shape or orientation of an orbit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrbitAdjustment {
+    /// Burn at the next apoapsis to match circular orbit speed there.
+    CircularizeAtApoapsis,
+    /// Burn at the next periapsis to match circular orbit speed there.
+    CircularizeAtPeriapsis,
+    /// Burn at the next periapsis to move the apoapsis to this radius.
+    SetApoapsis(f64),
+    /// Burn at the next apoapsis to move the periapsis to this radius.
+    SetPeriapsis(f64),
+    /// Rotate the argument of periapsis by this angle, in radians,
+    /// keeping the size and shape of the orbit. The burn is made where
+    /// the old and new orbits cross.
+    RotateArgp(f64),
+}
+
+impl std::fmt::Display for OrbitAdjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CircularizeAtApoapsis => write!(f, "Circularize at apoapsis"),
+            Self::CircularizeAtPeriapsis => write!(f, "Circularize at periapsis"),
+            Self::SetApoapsis(r) => write!(f, "Set apoapsis radius to {:0.0} m", r),
+            Self::SetPeriapsis(r) => write!(f, "Set periapsis radius to {:0.0} m", r),
+            Self::RotateArgp(a) => write!(f, "Rotate periapsis by {:0.1} deg", a.to_degrees()),
+        }
+    }
+}
+
+/// Plan of the burn which makes the given adjustment to `orbit`, made no
+/// sooner than `now`. None if the orbit has no apsis to burn at, or the
+/// adjustment makes no sense for it.
+pub fn orbit_adjustment_plan(
+    orbit: &SparseOrbit,
+    now: Nanotime,
+    adjustment: OrbitAdjustment,
+) -> Option<ManeuverPlan> {
+    let mu = orbit.body.mu();
+
+    // burn along the current velocity at an apsis, so that the opposite
+    // apsis ends up at `opposite_r`
+    let apsis_burn = |t: Nanotime, opposite_r: Option<f64>| -> Option<(Nanotime, DVec2)> {
+        let before = orbit.pv_universal(t).ok()?;
+        let r = before.pos.length();
+        let opposite_r = opposite_r.unwrap_or(r);
+        if opposite_r <= 0.0 {
+            return None;
+        }
+        let speed = vis_viva_equation(mu, r, (r + opposite_r) / 2.0);
+        Some((t, before.vel.normalize_or_zero() * speed - before.vel))
+    };
+
+    let burn = match adjustment {
+        OrbitAdjustment::CircularizeAtApoapsis => apsis_burn(orbit.t_next_ta(now, PI_64)?, None)?,
+        OrbitAdjustment::CircularizeAtPeriapsis => apsis_burn(orbit.t_next_p(now)?, None)?,
+        OrbitAdjustment::SetApoapsis(r) => apsis_burn(orbit.t_next_p(now)?, Some(r))?,
+        OrbitAdjustment::SetPeriapsis(r) => apsis_burn(orbit.t_next_ta(now, PI_64)?, Some(r))?,
+        OrbitAdjustment::RotateArgp(angle) => {
+            // circular orbits have no periapsis to rotate
+            if orbit.ecc() < 1e-6 || orbit.ecc() >= 1.0 {
+                return None;
+            }
+            let rotated = SparseOrbit::new(
+                orbit.apoapsis_r(),
+                orbit.periapsis_r(),
+                orbit.arg_periapsis + angle,
+                orbit.body,
+                now,
+                orbit.is_retrograde(),
+            )?;
+            // the orbits cross halfway between their periapses, and again
+            // opposite that; take whichever comes first
+            let sign = if orbit.is_retrograde() { -1.0 } else { 1.0 };
+            let ta = sign * angle / 2.0;
+            let (t, ta) = [ta, ta + PI_64]
+                .into_iter()
+                .filter_map(|ta| Some((orbit.t_next_ta(now, ta)?, ta)))
+                .min_by_key(|(t, _)| *t)?;
+            let before = orbit.pv_universal(t).ok()?;
+            let after = rotated.velocity_at(ta - sign * angle);
+            (t, after - before.vel)
+        }
+    };
+
+    ManeuverPlan::new(now, *orbit, &[burn])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -409,4 +760,140 @@ mod tests {
             maneuver_plan_is_continuous(&plan);
         }
     }
+
+    #[test]
+    fn unperturbed_maneuver_matches_plan() {
+        for _ in 0..20 {
+            let plan = match best_maneuver_plan(&random_orbit(), &random_orbit(), Nanotime::zero())
+            {
+                Ok(p) => p,
+                Err(_) => continue,
+            };
+
+            let nominal = plan.encounter().unwrap();
+            let p = plan.perturbed(0.0, Nanotime::ZERO).unwrap();
+            let d = p.encounter.unwrap().pos.distance(nominal.pos);
+            assert!(d < 20.0, "Expected difference to be smaller: {}", d);
+
+            if let Some(p) = plan.perturbed(0.05, Nanotime::ZERO) {
+                if let Some(e) = p.encounter {
+                    assert!(e.pos.distance(nominal.pos) > d);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn phase_angle_reaches_window() {
+        let body = Body::with_mass(63.0, 1000.0, 15000.0);
+        let inner = SparseOrbit::circular(200.0, body, Nanotime::zero(), false);
+        let outer = SparseOrbit::circular(600.0, body, Nanotime::zero(), false);
+
+        let info = phase_info(&inner, &outer, Nanotime::zero()).unwrap();
+        assert!(info.current.abs() < 1E-6);
+
+        // outer target must lead the inner vehicle
+        assert!(info.required > 0.0 && info.required < PI_64);
+
+        let synodic = info.synodic_period.unwrap();
+        let expected = 2.0 * PI_64 / (inner.mean_motion() - outer.mean_motion()).abs();
+        assert!((synodic.to_secs_f64() - expected).abs() < 1E-3);
+
+        let t = info.time_to_window.unwrap();
+        let later = phase_info(&inner, &outer, t).unwrap();
+        let error = wrap_pi_npi_f64(later.current - later.required);
+        assert!(error.abs() < 1E-3, "phase error at window: {}", error);
+    }
+
+    #[test]
+    fn rendezvous_arrives_alongside_target() {
+        let body = Body::with_mass(63.0, 1000.0, 15000.0);
+        let now = Nanotime::zero();
+        let inner = SparseOrbit::circular(200.0, body, now, false);
+        let period = inner.period().unwrap();
+        let outer = SparseOrbit::circular(600.0, body, now + period / 3, false);
+        // the same orbit as the vehicle, but a sixth of a lap ahead
+        let ahead = SparseOrbit::circular(200.0, body, now - period / 6, false);
+
+        for target in [outer, ahead] {
+            let plan = rendezvous_plan(&inner, &target, now).unwrap();
+            let (t1, _) = plan.dvs().next().unwrap();
+            assert!(t1 > now);
+
+            let end = plan.end();
+            let pv = plan.terminal.pv(end).unwrap();
+            let expected = target.pv(end).unwrap();
+            let d = pv.pos.distance(expected.pos);
+            let v = pv.vel.distance(expected.vel);
+            assert!(d < 2.0, "arrived {} away from the target", d);
+            assert!(v < 1E-6, "arrived at {} relative to the target", v);
+        }
+
+        let retrograde = SparseOrbit::circular(600.0, body, now, true);
+        assert!(rendezvous_plan(&inner, &retrograde, now).is_none());
+    }
+
+    #[test]
+    fn avoidance_burn_raises_periapsis() {
+        let body = Body::with_mass(63.0, 1000.0, 15000.0);
+        let pv = PV::from_f64(DVec2::X * 300.0, DVec2::new(-60.0, 100.0));
+        let orbit = SparseOrbit::from_pv(pv, body, Nanotime::zero()).unwrap();
+        assert!(orbit.periapsis_r() < body.radius);
+
+        let target = body.radius + 20.0;
+        let dv = periapsis_raise_burn(&orbit, Nanotime::zero(), target).unwrap();
+        let raised = PV::from_f64(pv.pos, pv.vel + dv);
+        let raised = SparseOrbit::from_pv(raised, body, Nanotime::zero()).unwrap();
+        assert!(raised.periapsis_r() >= target);
+
+        // circularizing here also clears the surface, but costs more
+        let circular = DVec2::Y * (body.mu() / 300.0).sqrt();
+        let circularize = (circular - pv.vel).length();
+        assert!(
+            dv.length() < circularize,
+            "{} > {}",
+            dv.length(),
+            circularize
+        );
+
+        let safe = periapsis_raise_burn(&raised, Nanotime::zero(), target).unwrap();
+        assert_eq!(safe, DVec2::ZERO);
+    }
+
+    #[test]
+    fn orbit_adjustments_reach_their_targets() {
+        let body = Body::with_mass(63.0, 1000.0, 15000.0);
+        let t0 = Nanotime::secs(7);
+        for retrograde in [false, true] {
+            let orbit = SparseOrbit::new(4000.0, 1500.0, 0.7, body, t0, retrograde).unwrap();
+            let plan = |adj| orbit_adjustment_plan(&orbit, t0, adj).unwrap();
+            let close = |a: f64, b: f64| (a - b).abs() < 1.0;
+
+            let circ = plan(OrbitAdjustment::CircularizeAtApoapsis);
+            assert!(circ.terminal.ecc() < 1e-3, "{}", circ.terminal);
+            assert!(close(circ.terminal.semi_major_axis, 4000.0));
+            assert!(circ.dvs().all(|(t, _)| t > t0));
+
+            let circ = plan(OrbitAdjustment::CircularizeAtPeriapsis);
+            assert!(close(circ.terminal.semi_major_axis, 1500.0));
+
+            let raised = plan(OrbitAdjustment::SetApoapsis(6000.0)).terminal;
+            assert!(close(raised.apoapsis_r(), 6000.0));
+            assert!(close(raised.periapsis_r(), 1500.0));
+
+            let lowered = plan(OrbitAdjustment::SetPeriapsis(1200.0)).terminal;
+            assert!(close(lowered.periapsis_r(), 1200.0));
+            assert!(close(lowered.apoapsis_r(), 4000.0));
+
+            let rotated = plan(OrbitAdjustment::RotateArgp(0.5)).terminal;
+            assert_eq!(rotated.is_retrograde(), retrograde);
+            assert!(close(rotated.apoapsis_r(), 4000.0));
+            assert!(close(rotated.periapsis_r(), 1500.0));
+            let error = wrap_pi_npi_f64(rotated.arg_periapsis - orbit.arg_periapsis - 0.5);
+            assert!(error.abs() < 1e-3, "{} {}", retrograde, error);
+        }
+
+        let circular = SparseOrbit::circular(2000.0, body, t0, false);
+        assert!(orbit_adjustment_plan(&circular, t0, OrbitAdjustment::RotateArgp(1.0)).is_none());
+    }
 }
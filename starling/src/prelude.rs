@@ -1,32 +1,69 @@
 pub use crate::aabb::{Polygon, AABB, OBB};
+pub use crate::aerodynamics::*;
+pub use crate::batch_propagation::*;
 pub use crate::belts::AsteroidBelt;
 pub use crate::bezier::*;
+pub use crate::burn_alignment::*;
+pub use crate::callsign::*;
 pub use crate::casts::*;
+pub use crate::checkpoint::{CheckpointBuffer, UniverseCheckpoint};
 pub use crate::construction_bot::*;
-pub use crate::control::OrbitalController;
+pub use crate::control::{
+    is_rendezvous, ControlPolicy, OrbitalController, RendezvousAbort, RENDEZVOUS_RANGE,
+    RENDEZVOUS_SPEED,
+};
 pub use crate::control_signals::*;
+pub use crate::docking::*;
+pub use crate::dv_map::*;
 pub use crate::entities::*;
+pub use crate::event_bus::*;
 pub use crate::examples::{default_example, make_earth, make_luna};
 pub use crate::factory::*;
 pub use crate::file_export::export_orbit_data;
-pub use crate::id::{EntityId, ObjectId};
+pub use crate::id::{EntityId, EntityKind, IdAllocator, IdRemap, ObjectId};
+pub use crate::impact::*;
+pub use crate::item_transfer::*;
+pub use crate::landing_pad::*;
+pub use crate::load_queue::{LoadQueue, PendingVehicle};
 pub use crate::lpf::*;
+pub use crate::lvlh::*;
+pub use crate::maneuver_node::ManeuverNode;
 pub use crate::math::*;
-pub use crate::nanotime::Nanotime;
+pub use crate::nanotime::{duration_str, Nanotime};
+pub use crate::orbit_crossings::*;
+pub use crate::orbit_history::*;
+pub use crate::orbit_normalization::*;
 pub use crate::orbital_luts::lookup_ta_from_ma;
 pub use crate::orbiter::Orbiter;
-pub use crate::orbits::{hyperbolic_range_ta, Body, GlobalOrbit, SparseOrbit};
+pub use crate::orbits::{escape_angle, hyperbolic_range_ta, Body, GlobalOrbit, SparseOrbit};
 pub use crate::parts::*;
 pub use crate::pid::*;
-pub use crate::planning::{best_maneuver_plan, get_next_intersection, ManeuverPlan};
+pub use crate::planet_defs::*;
+pub use crate::planet_texture::{generate_planet_texture, planet_seed};
+pub use crate::planning::{
+    best_maneuver_plan, get_next_intersection, orbit_adjustment_plan, periapsis_raise_burn,
+    phase_info, ManeuverPlan, OrbitAdjustment, PerturbedManeuver, PhaseInfo,
+};
 pub use crate::plants::Plant;
 pub use crate::propagator::{EventType, HorizonState, Propagator};
 pub use crate::pv::*;
 pub use crate::quantities::*;
+pub use crate::recycle_bin::{DeletedEntity, RecycleBin};
 pub use crate::region::Region;
+pub use crate::save_archive::*;
 pub use crate::scenario::{ObjectLookup, PlanetarySystem, ScenarioObject};
+pub use crate::scenario_file::*;
+pub use crate::scenario_script::*;
+pub use crate::service_drone::*;
+pub use crate::shared_plans::*;
+pub use crate::snapshot::*;
+pub use crate::structure::*;
 pub use crate::surface::*;
 pub use crate::take::*;
+pub use crate::terrain::*;
 pub use crate::thrust_particles::*;
+pub use crate::traffic::*;
 pub use crate::universe::*;
+pub use crate::universe_file::*;
 pub use crate::vehicle::*;
+pub use crate::waypoint::*;
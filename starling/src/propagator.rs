@@ -6,7 +6,7 @@ use crate::scenario::*;
 use glam::f32::Vec2;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 pub enum EventType {
     Collide(EntityId),
     Escape(EntityId),
@@ -123,7 +123,7 @@ pub(crate) enum BadObjectNextState {
     BadPosition,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 pub enum HorizonState {
     Continuing(Nanotime),
     Indefinite,
@@ -149,7 +149,7 @@ impl HorizonState {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Propagator {
     pub orbit: GlobalOrbit,
     pub start: Nanotime,
@@ -3,6 +3,9 @@ pub const LUNA_MU: f64 = 4.904E12;
 pub const EARTH_MU: f64 = 3.986E14;
 pub const SUN_MU: f64 = 1.327124E20;
 
+// m^3/(kg s^2)
+pub const GRAVITATIONAL_CONSTANT: f64 = 6.674E-11;
+
 pub const EARTH_RADIUS: f64 = 6_378_137.0;
 pub const EARTH_SOI: f64 = 900_000_000.0;
 
@@ -0,0 +1,105 @@
+use crate::prelude::*;
+
+/// A vehicle which has been removed from the universe, but which can
+/// still be restored until its retention period has elapsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeletedEntity {
+    pub id: EntityId,
+    pub deleted_at: Nanotime,
+    pub vehicle: SurfaceSpacecraftEntity,
+    pub group: Option<EntityId>,
+    /// Vehicles docked to this one, which it carries along.
+    pub docked: Vec<(EntityId, DockingJoint)>,
+}
+
+/// Holding area for deleted vehicles. Entries are kept for `retention`
+/// of sim time before being permanently discarded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecycleBin {
+    pub retention: Nanotime,
+    entries: Vec<DeletedEntity>,
+}
+
+impl RecycleBin {
+    pub fn new(retention: Nanotime) -> Self {
+        Self {
+            retention,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.entries.iter().any(|e| e.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DeletedEntity> + use<'_> {
+        self.entries.iter()
+    }
+
+    pub fn insert(&mut self, entity: DeletedEntity) {
+        self.entries.retain(|e| e.id != entity.id);
+        self.entries.push(entity);
+    }
+
+    pub fn take(&mut self, id: EntityId) -> Option<DeletedEntity> {
+        let idx = self.entries.iter().position(|e| e.id == id)?;
+        Some(self.entries.remove(idx))
+    }
+
+    /// Sim time remaining before the given entity is permanently removed.
+    pub fn time_remaining(&self, id: EntityId, stamp: Nanotime) -> Option<Nanotime> {
+        let e = self.entries.iter().find(|e| e.id == id)?;
+        Some(e.deleted_at + self.retention - stamp)
+    }
+
+    /// Permanently discards every entry whose retention period has elapsed,
+    /// returning them. Entries deleted after `stamp`, i.e. before the
+    /// universe was rewound, are silently dropped since the rewound universe
+    /// already contains them.
+    pub fn purge(&mut self, stamp: Nanotime) -> Vec<DeletedEntity> {
+        self.entries.retain(|e| e.deleted_at <= stamp);
+        let retention = self.retention;
+        let (expired, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|e| stamp - e.deleted_at >= retention);
+        self.entries = kept;
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soft_delete_and_restore() {
+        let mut universe = Universe::empty();
+        universe.recycle_bin.retention = Nanotime::secs(10);
+        let planet = universe.planets.id;
+        let id = universe
+            .add_surface_vehicle(planet, Vehicle::new(), 0.0, 100.0)
+            .unwrap();
+
+        universe.soft_delete(id).unwrap();
+        assert!(!universe.surface_vehicles.contains_key(&id));
+        assert!(universe.recycle_bin.contains(id));
+
+        universe.undelete(id).unwrap();
+        assert!(universe.surface_vehicles.contains_key(&id));
+        assert!(universe.recycle_bin.is_empty());
+
+        universe.soft_delete(id).unwrap();
+        assert!(universe.recycle_bin.purge(Nanotime::secs(5)).is_empty());
+        let purged = universe.recycle_bin.purge(Nanotime::secs(10));
+        assert_eq!(purged.iter().map(|e| e.id).collect::<Vec<_>>(), vec![id]);
+        assert!(universe.undelete(id).is_none());
+    }
+}
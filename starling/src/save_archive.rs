@@ -0,0 +1,565 @@
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const MAGIC: &[u8; 8] = b"STRLSAV1";
+
+/// Magic, then the offset and length of the index, as little-endian u64s.
+const HEADER_LEN: u64 = 24;
+
+/// Where one record lives in an archive, and the length and a CRC32 of
+/// its uncompressed contents for telling whether it has changed. The
+/// checksum is stable across builds, unlike the standard library's
+/// hashers, so records written by one version of the game are still
+/// recognized by the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub offset: u64,
+    pub len: u64,
+    pub hash: u64,
+    /// Uncompressed length. Archives written before this was recorded
+    /// have zero here, so their records are all rewritten once.
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// Compressed binary save file. Each record is encoded as CBOR and
+/// deflated on its own, and an index of where each one lives is pointed
+/// to by the header, so records can be read without inflating the rest.
+///
+/// Incremental saves append only the records which changed, then a new
+/// index, and only then repoint the header; a save cut short leaves the
+/// previous one intact. Space held by superseded records is reclaimed by
+/// the next full save.
+#[derive(Debug, Clone)]
+pub struct SaveArchive {
+    path: PathBuf,
+    index: BTreeMap<String, ArchiveEntry>,
+    /// Length of the file, where the next record will be appended.
+    end: u64,
+    /// Length of the current index, which ends the file.
+    index_len: u64,
+}
+
+/// A record's encoded bytes and their checksum.
+type Encoded<'a> = (Cow<'a, [u8]>, u64);
+
+type Encoder = Arc<dyn Fn() -> Result<Vec<u8>, Box<dyn Error>> + Send + Sync>;
+
+#[derive(Clone)]
+enum RecordBytes {
+    Encoded { bytes: Vec<u8>, hash: u64 },
+    Deferred(Encoder),
+}
+
+impl std::fmt::Debug for RecordBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Encoded { bytes, hash } => f
+                .debug_struct("Encoded")
+                .field("len", &bytes.len())
+                .field("hash", hash)
+                .finish(),
+            Self::Deferred(_) => f.write_str("Deferred"),
+        }
+    }
+}
+
+/// A record to be saved, either already encoded or holding on to its
+/// value until it's written.
+#[derive(Debug, Clone)]
+pub struct SaveRecord {
+    pub key: String,
+    bytes: RecordBytes,
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes)?;
+    Ok(bytes)
+}
+
+fn checksum(bytes: &[u8]) -> u64 {
+    crc32fast::hash(bytes) as u64
+}
+
+impl SaveRecord {
+    /// Encodes `value` now.
+    pub fn new<T: Serialize>(key: impl Into<String>, value: &T) -> Result<Self, Box<dyn Error>> {
+        let bytes = encode(value)?;
+        Ok(Self {
+            key: key.into(),
+            bytes: RecordBytes::Encoded {
+                hash: checksum(&bytes),
+                bytes,
+            },
+        })
+    }
+
+    /// Keeps `value` to be encoded when the record is written, so that an
+    /// incremental save spreads the cost of encoding over its steps too.
+    pub fn deferred<T: Serialize + Send + Sync + 'static>(
+        key: impl Into<String>,
+        value: T,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            bytes: RecordBytes::Deferred(Arc::new(move || encode(&value))),
+        }
+    }
+
+    /// Like `deferred`, but builds the value to encode only when the
+    /// record is written, for values which are costly to put together.
+    pub fn lazy<T, F>(key: impl Into<String>, build: F) -> Self
+    where
+        T: Serialize,
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        Self {
+            key: key.into(),
+            bytes: RecordBytes::Deferred(Arc::new(move || encode(&build()))),
+        }
+    }
+
+    fn encoded(&self) -> Result<Encoded<'_>, Box<dyn Error>> {
+        match &self.bytes {
+            RecordBytes::Encoded { bytes, hash } => Ok((Cow::Borrowed(bytes), *hash)),
+            RecordBytes::Deferred(encoder) => {
+                let bytes = encoder()?;
+                let hash = checksum(&bytes);
+                Ok((Cow::Owned(bytes), hash))
+            }
+        }
+    }
+
+    /// The encoded record and its hash, if it has been encoded yet.
+    fn encoded_now(&self) -> Option<Encoded<'_>> {
+        match &self.bytes {
+            RecordBytes::Encoded { bytes, hash } => Some((Cow::Borrowed(bytes), *hash)),
+            RecordBytes::Deferred(_) => None,
+        }
+    }
+}
+
+/// Reads the `len` bytes at `offset` in `file`, checking first that they
+/// lie within it, so that a corrupt length fails to load rather than
+/// asking for more memory than there is.
+fn section(mut file: File, offset: u64, len: u64) -> Result<impl Read, Box<dyn Error>> {
+    let file_len = file.metadata()?.len();
+    if offset < HEADER_LEN || offset.checked_add(len).is_none_or(|end| end > file_len) {
+        return Err(format!(
+            "section of {} bytes at {} runs past the end of the file ({} bytes)",
+            len, offset, file_len
+        )
+        .into());
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    Ok(file.take(len))
+}
+
+fn compress(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?)
+}
+
+impl SaveArchive {
+    /// Reads the header and index of an existing archive.
+    pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut file = File::open(path)?;
+        let mut header = [0u8; HEADER_LEN as usize];
+        file.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err("not a save archive".into());
+        }
+        let word = |i: usize| u64::from_le_bytes(header[i..i + 8].try_into().unwrap());
+        let (offset, len) = (word(8), word(16));
+
+        let end = file.metadata()?.len();
+        let index = ciborium::from_reader(DeflateDecoder::new(section(file, offset, len)?))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            index,
+            end,
+            index_len: len,
+        })
+    }
+
+    /// Writes every record to a new archive, replacing whatever was at
+    /// `path`.
+    pub fn write_full(path: &Path, records: Vec<SaveRecord>) -> Result<Self, Box<dyn Error>> {
+        let (mut archive, mut save) = Self::begin_full(path, records)?;
+        while !save.step(&mut archive, Duration::MAX)? {}
+        Ok(archive)
+    }
+
+    /// Starts writing every record to a new archive, which replaces
+    /// whatever was at `path` once the save completes. Until then it's
+    /// written alongside, so a save cut short leaves the old one intact.
+    pub fn begin_full(
+        path: &Path,
+        records: Vec<SaveRecord>,
+    ) -> Result<(Self, IncrementalSave), Box<dyn Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        File::create(&tmp)?.write_all(&[0; HEADER_LEN as usize])?;
+        let archive = Self {
+            path: tmp,
+            index: BTreeMap::new(),
+            end: HEADER_LEN,
+            index_len: 0,
+        };
+        let save = IncrementalSave {
+            pending: records,
+            removed: Vec::new(),
+            rename_to: Some(path.to_path_buf()),
+        };
+        Ok((archive, save))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> + use<'_> {
+        self.index.keys().map(|k| k.as_str())
+    }
+
+    pub fn entry(&self, key: &str) -> Option<&ArchiveEntry> {
+        self.index.get(key)
+    }
+
+    /// Length of the archive file in bytes.
+    pub fn file_len(&self) -> u64 {
+        self.end
+    }
+
+    /// Bytes taken up by records which have since been replaced.
+    pub fn wasted_bytes(&self) -> u64 {
+        let live: u64 = self.index.values().map(|e| e.len).sum();
+        self.end.saturating_sub(HEADER_LEN + live + self.index_len)
+    }
+
+    pub fn read<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Box<dyn Error>> {
+        let Some(entry) = self.index.get(key) else {
+            return Ok(None);
+        };
+        let file = File::open(&self.path)?;
+        let bytes = section(file, entry.offset, entry.len)?;
+        Ok(Some(ciborium::from_reader(DeflateDecoder::new(bytes))?))
+    }
+
+    /// Reads every record whose key starts with `prefix`, in key order.
+    pub fn read_all<T: DeserializeOwned>(&self, prefix: &str) -> Result<Vec<T>, Box<dyn Error>> {
+        Ok(self
+            .read_all_keyed(prefix)?
+            .into_iter()
+            .map(|(_, v)| v)
+            .collect())
+    }
+
+    /// Like `read_all`, along with the rest of each record's key after
+    /// `prefix`.
+    pub fn read_all_keyed<T: DeserializeOwned>(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, T)>, Box<dyn Error>> {
+        let keys: Vec<_> = self.keys().filter(|k| k.starts_with(prefix)).collect();
+        keys.into_iter()
+            .filter_map(|k| {
+                let v = self.read(k).transpose()?;
+                Some(v.map(|v| (k[prefix.len()..].to_string(), v)))
+            })
+            .collect()
+    }
+
+    fn is_unchanged(&self, key: &str, bytes: &[u8], hash: u64) -> bool {
+        self.index
+            .get(key)
+            .is_some_and(|e| e.hash == hash && e.size == bytes.len() as u64)
+    }
+
+    fn append(&mut self, key: &str, bytes: &[u8], hash: u64) -> Result<(), Box<dyn Error>> {
+        let size = bytes.len() as u64;
+        let bytes = compress(bytes)?;
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        file.write_all(&bytes)?;
+        self.index.insert(
+            key.to_string(),
+            ArchiveEntry {
+                offset: self.end,
+                len: bytes.len() as u64,
+                hash,
+                size,
+            },
+        );
+        self.end += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Appends the index and points the header at it.
+    fn commit(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut index = Vec::new();
+        ciborium::into_writer(&self.index, &mut index)?;
+        let index = compress(&index)?;
+        let mut file = OpenOptions::new().write(true).open(&self.path)?;
+        file.seek(SeekFrom::Start(self.end))?;
+        file.write_all(&index)?;
+        file.sync_data()?;
+
+        let mut header = MAGIC.to_vec();
+        header.extend(self.end.to_le_bytes());
+        header.extend((index.len() as u64).to_le_bytes());
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&header)?;
+        self.end += index.len() as u64;
+        self.index_len = index.len() as u64;
+        Ok(())
+    }
+
+    /// Starts an incremental save of `records` into this archive. Records
+    /// which are unchanged since the last save are skipped, and those
+    /// missing from `records` are dropped from the index. Deferred records
+    /// are encoded and checked as the save steps.
+    pub fn begin_save(&self, records: Vec<SaveRecord>) -> IncrementalSave {
+        let keys: HashSet<&str> = records.iter().map(|r| r.key.as_str()).collect();
+        let removed = self
+            .index
+            .keys()
+            .filter(|k| !keys.contains(k.as_str()))
+            .cloned()
+            .collect();
+        let pending = records
+            .into_iter()
+            .filter(|r| {
+                r.encoded_now()
+                    .is_none_or(|(bytes, hash)| !self.is_unchanged(&r.key, &bytes, hash))
+            })
+            .collect();
+        IncrementalSave {
+            pending,
+            removed,
+            rename_to: None,
+        }
+    }
+}
+
+/// Save in progress which writes changed records a few at a time, so that
+/// autosaving doesn't stall the game for longer than a frame.
+#[derive(Debug, Clone)]
+pub struct IncrementalSave {
+    pending: Vec<SaveRecord>,
+    removed: Vec<String>,
+    /// Where a full save's archive goes once it's complete.
+    rename_to: Option<PathBuf>,
+}
+
+impl IncrementalSave {
+    /// Records left to encode or write.
+    pub fn remaining(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Encodes and writes records until `budget` has run out, always
+    /// getting through at least one. Those which turn out unchanged are
+    /// skipped. Once none are left, commits the new index and returns true.
+    pub fn step(
+        &mut self,
+        archive: &mut SaveArchive,
+        budget: Duration,
+    ) -> Result<bool, Box<dyn Error>> {
+        let start = Instant::now();
+        while let Some(record) = self.pending.pop() {
+            let (bytes, hash) = record.encoded()?;
+            if !archive.is_unchanged(&record.key, &bytes, hash) {
+                archive.append(&record.key, &bytes, hash)?;
+            }
+            if start.elapsed() > budget {
+                break;
+            }
+        }
+        if !self.pending.is_empty() {
+            return Ok(false);
+        }
+        for key in self.removed.drain(..) {
+            archive.index.remove(&key);
+        }
+        archive.commit()?;
+        if let Some(path) = self.rename_to.take() {
+            std::fs::rename(&archive.path, &path)?;
+            archive.path = path;
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archives_save_incrementally_and_survive_reopening() {
+        let path = std::env::temp_dir().join("starling_save_archive_test.strl");
+        let record = |k: &str, v: &Vec<u32>| SaveRecord::new(k, v).unwrap();
+        let big: Vec<u32> = (0..10_000).map(|i| i % 7).collect();
+
+        let archive = SaveArchive::write_full(
+            &path,
+            vec![
+                record("a", &big),
+                record("b", &vec![1, 2, 3]),
+                record("c", &vec![4]),
+            ],
+        )
+        .unwrap();
+        let size = std::fs::metadata(&path).unwrap().len();
+        assert!(size < 1000, "{} bytes", size);
+        assert_eq!(archive.wasted_bytes(), 0);
+
+        // only "b" changed, and "c" was removed
+        let mut archive = SaveArchive::open(&path).unwrap();
+        let a = *archive.entry("a").unwrap();
+        let mut save = archive.begin_save(vec![record("a", &big), record("b", &vec![5, 6])]);
+        assert_eq!(save.remaining(), 1);
+        assert!(save.step(&mut archive, Duration::ZERO).unwrap());
+
+        let archive = SaveArchive::open(&path).unwrap();
+        assert_eq!(archive.entry("a"), Some(&a));
+        assert_eq!(archive.read::<Vec<u32>>("a").unwrap(), Some(big));
+        assert_eq!(archive.read::<Vec<u32>>("b").unwrap(), Some(vec![5, 6]));
+        assert_eq!(archive.read::<Vec<u32>>("c").unwrap(), None);
+        assert_eq!(archive.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!(archive.wasted_bytes() > 0);
+
+        std::fs::write(&path, "not an archive").unwrap();
+        assert!(SaveArchive::open(&path).is_err());
+        _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn deferred_records_are_encoded_as_the_save_steps() {
+        let path = std::env::temp_dir().join("starling_save_archive_deferred_test.strl");
+        let values: Vec<Vec<u32>> = (0..5).map(|i| vec![i; 100]).collect();
+        let deferred = |values: &[Vec<u32>]| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| SaveRecord::deferred(format!("v/{}", i), v.clone()))
+                .collect::<Vec<_>>()
+        };
+        let mut archive = SaveArchive::write_full(&path, deferred(&values)).unwrap();
+        let before: Vec<ArchiveEntry> = archive.index.values().copied().collect();
+        let entry = *archive.entry("v/2").unwrap();
+        assert_eq!(entry.hash, checksum(&encode(&values[2]).unwrap()));
+
+        // every record waits to be encoded, but only the changed one is
+        // written
+        let mut changed = values.clone();
+        changed[3] = vec![7];
+        let old_index = archive.index_len;
+        let mut save = archive.begin_save(deferred(&changed));
+        assert_eq!(save.remaining(), 5);
+        let mut steps = 0;
+        while !save.step(&mut archive, Duration::ZERO).unwrap() {
+            steps += 1;
+        }
+        assert_eq!(steps, 4);
+
+        let archive = SaveArchive::open(&path).unwrap();
+        let after: Vec<ArchiveEntry> = archive.index.values().copied().collect();
+        let rewritten: Vec<_> = (0..5).filter(|i| before[*i] != after[*i]).collect();
+        assert_eq!(rewritten, vec![3]);
+        assert_eq!(archive.read::<Vec<u32>>("v/3").unwrap(), Some(vec![7]));
+        assert_eq!(archive.wasted_bytes(), before[3].len + old_index);
+        _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn full_saves_stream_and_replace_the_archive_when_done() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let path = std::env::temp_dir().join("starling_save_archive_full_test.strl");
+        let old = SaveRecord::new("v/0", &vec![1u32]).unwrap();
+        SaveArchive::write_full(&path, vec![old]).unwrap();
+
+        // records are only built as the save gets to them
+        let built = Arc::new(AtomicUsize::new(0));
+        let records = (0..3)
+            .map(|i| {
+                let built = built.clone();
+                SaveRecord::lazy(format!("v/{}", i), move || {
+                    built.fetch_add(1, Ordering::SeqCst);
+                    vec![i as u32 + 10]
+                })
+            })
+            .collect();
+        let (mut archive, mut save) = SaveArchive::begin_full(&path, records).unwrap();
+        assert_eq!(built.load(Ordering::SeqCst), 0);
+        assert!(!save.step(&mut archive, Duration::ZERO).unwrap());
+        assert_eq!(built.load(Ordering::SeqCst), 1);
+
+        // the old archive stands until the new one is complete
+        let before = SaveArchive::open(&path).unwrap();
+        assert_eq!(before.read::<Vec<u32>>("v/0").unwrap(), Some(vec![1]));
+        assert_eq!(before.read::<Vec<u32>>("v/1").unwrap(), None);
+
+        while !save.step(&mut archive, Duration::ZERO).unwrap() {}
+        assert_eq!(built.load(Ordering::SeqCst), 3);
+        assert!(!path.with_extension("tmp").exists());
+        let after = SaveArchive::open(&path).unwrap();
+        assert_eq!(after.read::<Vec<u32>>("v/0").unwrap(), Some(vec![10]));
+        assert_eq!(after.read::<Vec<u32>>("v/2").unwrap(), Some(vec![12]));
+        assert_eq!(archive.read::<Vec<u32>>("v/1").unwrap(), Some(vec![11]));
+        _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn records_with_colliding_checksums_are_still_rewritten() {
+        let path = std::env::temp_dir().join("starling_save_archive_collision_test.strl");
+        let short = vec![1u32];
+        let long = vec![1u32, 2, 3];
+        let mut archive =
+            SaveArchive::write_full(&path, vec![SaveRecord::new("a", &short).unwrap()]).unwrap();
+        let size = encode(&short).unwrap().len() as u64;
+        assert_eq!(archive.entry("a").unwrap().size, size);
+        let save = archive.begin_save(vec![SaveRecord::new("a", &short).unwrap()]);
+        assert_eq!(save.remaining(), 0);
+
+        // as if the new contents happened to have the old checksum
+        archive.index.get_mut("a").unwrap().hash = checksum(&encode(&long).unwrap());
+        let save = archive.begin_save(vec![SaveRecord::new("a", &long).unwrap()]);
+        assert_eq!(save.remaining(), 1);
+        _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn corrupt_lengths_fail_to_load() {
+        let path = std::env::temp_dir().join("starling_save_archive_corrupt_test.strl");
+        let record = SaveRecord::new("a", &vec![1u32, 2, 3]).unwrap();
+        let mut archive = SaveArchive::write_full(&path, vec![record]).unwrap();
+
+        // a record claiming to run far past the end of the file
+        archive.index.get_mut("a").unwrap().len = u64::MAX / 2;
+        assert!(archive.read::<Vec<u32>>("a").is_err());
+        archive.index.get_mut("a").unwrap().offset = u64::MAX;
+        assert!(archive.read::<Vec<u32>>("a").is_err());
+
+        // and a header pointing at an index which isn't there
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(SaveArchive::open(&path).is_err());
+        bytes.truncate(HEADER_LEN as usize + 4);
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(SaveArchive::open(&path).is_err());
+        _ = std::fs::remove_file(&path);
+    }
+}
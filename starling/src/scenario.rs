@@ -2,23 +2,25 @@ use crate::entities::*;
 use crate::id::*;
 use crate::math::*;
 use crate::nanotime::Nanotime;
-use crate::orbits::{Body, SparseOrbit};
+use crate::orbits::{Body, GlobalOrbit, SparseOrbit};
 use crate::propagator::EventType;
 use crate::pv::PV;
+use crate::structure::Structure;
 use serde::{Deserialize, Serialize};
 
+/// Numbers planets which don't come from a named definition, such as
+/// those added in the scenario editor.
 #[derive(Debug, Clone, Copy)]
-pub struct ObjectIdTracker(EntityId);
+pub struct ObjectIdTracker(u32);
 
 impl ObjectIdTracker {
     pub fn new() -> Self {
-        ObjectIdTracker(EntityId(900))
+        ObjectIdTracker(0)
     }
 
     pub fn next(&mut self) -> EntityId {
-        let ret = self.0;
-        self.0 .0 += 1;
-        ret
+        self.0 += 1;
+        EntityId::new(EntityKind::Planet, self.0 - 1)
     }
 }
 
@@ -26,6 +28,7 @@ impl ObjectIdTracker {
 pub enum ScenarioObject<'a> {
     Orbiter(&'a SurfaceSpacecraftEntity),
     Body(&'a String, Body),
+    Structure(&'a Structure),
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +50,13 @@ impl<'a> ObjectLookup<'a> {
         }
     }
 
+    pub fn structure(&self) -> Option<&'a Structure> {
+        match self.1 {
+            ScenarioObject::Structure(s) => Some(s),
+            _ => None,
+        }
+    }
+
     pub fn body(&self) -> Option<Body> {
         match self.1 {
             ScenarioObject::Body(_, b) => Some(b),
@@ -139,6 +149,20 @@ impl PlanetarySystem {
         self.lookup_inner(id, stamp, PV::ZERO, None)
     }
 
+    /// Orbit of the given planet about its parent, or None for the root
+    /// body or an unknown ID.
+    pub fn orbit_of(&self, id: EntityId) -> Option<GlobalOrbit> {
+        for (orbit, sub) in &self.subsystems {
+            if sub.id == id {
+                return Some(GlobalOrbit(self.id, *orbit));
+            }
+            if let Some(o) = sub.orbit_of(id) {
+                return Some(o);
+            }
+        }
+        None
+    }
+
     pub fn potential_at(&self, pos: DVec2, stamp: Nanotime) -> f64 {
         let r = pos.length().clamp(10.0, std::f64::MAX);
         let mut ret = -self.body.mu() / r;
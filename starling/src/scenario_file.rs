@@ -0,0 +1,672 @@
+use crate::prelude::*;
+use crate::scenario::ObjectIdTracker;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+/// Orbit about a parent body, as entered in the scenario editor. The
+/// orbiter starts at periapsis when the scenario begins.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct OrbitSpec {
+    pub periapsis: f64,
+    pub apoapsis: f64,
+    pub arg_periapsis: f64,
+    pub retrograde: bool,
+}
+
+impl OrbitSpec {
+    pub fn circular(radius: f64, angle: f64) -> Self {
+        Self {
+            periapsis: radius,
+            apoapsis: radius,
+            arg_periapsis: wrap_0_2pi_f64(angle),
+            retrograde: false,
+        }
+    }
+
+    pub fn to_orbit(&self, parent: Body) -> Option<SparseOrbit> {
+        if self.periapsis <= 0.0 || self.apoapsis < self.periapsis {
+            return None;
+        }
+        if self.periapsis == self.apoapsis {
+            let o =
+                SparseOrbit::circular(self.periapsis, parent, Nanotime::zero(), self.retrograde);
+            let p = rotate_f64(o.initial.pos, self.arg_periapsis);
+            let v = rotate_f64(o.initial.vel, self.arg_periapsis);
+            return SparseOrbit::from_pv(PV::from_f64(p, v), parent, Nanotime::zero());
+        }
+        SparseOrbit::new(
+            self.apoapsis,
+            self.periapsis,
+            self.arg_periapsis,
+            parent,
+            Nanotime::zero(),
+            self.retrograde,
+        )
+    }
+
+    /// Where the orbiter starts, relative to its parent.
+    pub fn initial_position(&self) -> DVec2 {
+        rotate_f64(DVec2::X * self.periapsis, self.arg_periapsis)
+    }
+
+    /// Circular orbits keep their phase at time zero; elliptical orbits
+    /// are reset to start at periapsis.
+    fn from_orbit(orbit: &SparseOrbit) -> Self {
+        let pos = orbit
+            .pv(Nanotime::zero())
+            .map(|pv| pv.pos)
+            .unwrap_or(orbit.initial.pos);
+        let circular = orbit.ecc() < 1E-6;
+        Self {
+            periapsis: orbit.periapsis_r(),
+            apoapsis: orbit.apoapsis_r(),
+            arg_periapsis: wrap_0_2pi_f64(if circular {
+                pos.to_angle()
+            } else {
+                orbit.arg_periapsis
+            }),
+            retrograde: orbit.is_retrograde(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ScenarioBody {
+    pub id: EntityId,
+    pub name: String,
+    pub body: Body,
+    /// Parent body and orbit about it. The root of the system has none.
+    pub orbit: Option<(EntityId, OrbitSpec)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ScenarioSite {
+    pub name: String,
+    pub planet_id: EntityId,
+    pub angle: f64,
+    pub width: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ScenarioVehicle {
+    pub model: String,
+    pub parent: EntityId,
+    pub orbit: OrbitSpec,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScenarioIssue {
+    NoRoot,
+    ExtraRoot(EntityId),
+    DuplicateId(EntityId),
+    UnknownParent(EntityId),
+    ParentCycle(EntityId),
+    BadBody(EntityId),
+    BadOrbit(EntityId),
+    OrbitHitsParent(EntityId),
+    SoiOutsideParent(EntityId),
+    SoiOverlap(EntityId, EntityId),
+    SiteUnknownPlanet(usize),
+    SiteTooWide(usize),
+    VehicleUnknownParent(usize),
+    VehicleBadOrbit(usize),
+    EventUnknownParent(usize),
+    EventBadOrbit(usize),
+}
+
+impl ScenarioIssue {
+    /// Whether this issue is about the given body.
+    pub fn involves(&self, id: EntityId) -> bool {
+        match *self {
+            Self::ExtraRoot(b)
+            | Self::DuplicateId(b)
+            | Self::UnknownParent(b)
+            | Self::ParentCycle(b)
+            | Self::BadBody(b)
+            | Self::BadOrbit(b)
+            | Self::OrbitHitsParent(b)
+            | Self::SoiOutsideParent(b) => b == id,
+            Self::SoiOverlap(a, b) => a == id || b == id,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for ScenarioIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoRoot => write!(f, "No root body"),
+            Self::ExtraRoot(id) => write!(f, "{} has no parent, but isn't the root", id),
+            Self::DuplicateId(id) => write!(f, "{} is used by more than one body", id),
+            Self::UnknownParent(id) => write!(f, "{} orbits a body which doesn't exist", id),
+            Self::ParentCycle(id) => write!(f, "{} ends up orbiting itself", id),
+            Self::BadBody(id) => {
+                write!(f, "{} needs positive radius and mass, and SOI > radius", id)
+            }
+            Self::BadOrbit(id) => write!(f, "{} has an invalid orbit", id),
+            Self::OrbitHitsParent(id) => write!(f, "{} passes through its parent", id),
+            Self::SoiOutsideParent(id) => write!(f, "{} SOI leaves its parent's SOI", id),
+            Self::SoiOverlap(a, b) => write!(f, "{} and {} have overlapping SOIs", a, b),
+            Self::SiteUnknownPlanet(i) => write!(f, "Site {} is on a body which doesn't exist", i),
+            Self::SiteTooWide(i) => write!(f, "Site {} is wider than its planet", i),
+            Self::VehicleUnknownParent(i) => {
+                write!(f, "Vehicle {} orbits a body which doesn't exist", i)
+            }
+            Self::VehicleBadOrbit(i) => {
+                write!(
+                    f,
+                    "Vehicle {} must orbit above the surface, within the SOI",
+                    i
+                )
+            }
+            Self::EventUnknownParent(i) => {
+                write!(f, "Event {} spawns around a body which doesn't exist", i)
+            }
+            Self::EventBadOrbit(i) => {
+                write!(
+                    f,
+                    "Event {} must spawn above the surface, within the SOI",
+                    i
+                )
+            }
+        }
+    }
+}
+
+/// A custom planetary system, with landing sites and vehicles to start
+/// with, as built in the scenario editor. Bodies are stored flat, each
+/// naming its parent, and are assembled into a `PlanetarySystem` once the
+/// scenario validates.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Scenario {
+    pub name: String,
+    pub bodies: Vec<ScenarioBody>,
+    pub sites: Vec<ScenarioSite>,
+    pub vehicles: Vec<ScenarioVehicle>,
+    /// Things which happen at set times once the scenario is running.
+    #[serde(default)]
+    pub events: Vec<ScriptedEvent>,
+    /// Planetary system from the assets which supplies the bodies, by
+    /// name. Bodies listed in the file are replaced by the system's.
+    #[serde(default)]
+    pub system: Option<String>,
+}
+
+/// Laplace sphere of influence of a body orbiting at `distance`.
+pub fn laplace_soi(distance: f64, mu: f64, parent_mu: f64) -> f64 {
+    distance * (mu / parent_mu).powf(0.4)
+}
+
+impl Scenario {
+    pub fn from_system(name: impl Into<String>, system: &PlanetarySystem) -> Self {
+        fn flatten(
+            sys: &PlanetarySystem,
+            orbit: Option<(EntityId, OrbitSpec)>,
+            out: &mut Vec<ScenarioBody>,
+        ) {
+            out.push(ScenarioBody {
+                id: sys.id,
+                name: sys.name.clone(),
+                body: sys.body,
+                orbit,
+            });
+            for (o, sub) in &sys.subsystems {
+                flatten(sub, Some((sys.id, OrbitSpec::from_orbit(o))), out);
+            }
+        }
+
+        let mut bodies = Vec::new();
+        flatten(system, None, &mut bodies);
+        Self {
+            name: name.into(),
+            bodies,
+            sites: Vec::new(),
+            vehicles: Vec::new(),
+            events: Vec::new(),
+            system: None,
+        }
+    }
+
+    pub fn body(&self, id: EntityId) -> Option<&ScenarioBody> {
+        self.bodies.iter().find(|b| b.id == id)
+    }
+
+    pub fn body_mut(&mut self, id: EntityId) -> Option<&mut ScenarioBody> {
+        self.bodies.iter_mut().find(|b| b.id == id)
+    }
+
+    pub fn root(&self) -> Option<&ScenarioBody> {
+        self.bodies.iter().find(|b| b.orbit.is_none())
+    }
+
+    pub fn children(&self, id: EntityId) -> impl Iterator<Item = &ScenarioBody> + use<'_> {
+        self.bodies
+            .iter()
+            .filter(move |b| b.orbit.map(|(p, _)| p) == Some(id))
+    }
+
+    fn next_id(&self) -> EntityId {
+        let mut tracker = ObjectIdTracker::new();
+        loop {
+            let id = tracker.next();
+            if self.body(id).is_none() {
+                return id;
+            }
+        }
+    }
+
+    /// Adds a body, as the root if there's no parent. Its size and SOI are
+    /// scaled from the parent so that it starts out valid.
+    pub fn add_body(&mut self, parent: Option<EntityId>, orbit: OrbitSpec) -> EntityId {
+        let id = self.next_id();
+        let (name, body, orbit) = match parent.and_then(|p| self.body(p)) {
+            Some(p) => {
+                let mu = p.body.mu * 0.01;
+                let soi = laplace_soi(orbit.periapsis, mu, p.body.mu);
+                let body = Body::with_mu((p.body.radius * 0.25).min(soi * 0.5), mu, soi);
+                let n = self.children(p.id).count() + 1;
+                (format!("{} {}", p.name, n), body, Some((p.id, orbit)))
+            }
+            None => (
+                "Primary".to_string(),
+                Body::with_mu(EARTH_RADIUS, EARTH_MU, EARTH_SOI).with_atmosphere(Atmosphere::EARTH),
+                None,
+            ),
+        };
+        self.bodies.push(ScenarioBody {
+            id,
+            name,
+            body,
+            orbit,
+        });
+        id
+    }
+
+    /// Removes a body, along with everything orbiting or sitting on it.
+    pub fn remove_body(&mut self, id: EntityId) {
+        let mut doomed = vec![id];
+        let mut i = 0;
+        while let Some(&current) = doomed.get(i) {
+            for child in self.children(current) {
+                if !doomed.contains(&child.id) {
+                    doomed.push(child.id);
+                }
+            }
+            i += 1;
+        }
+        self.bodies.retain(|b| !doomed.contains(&b.id));
+        self.sites.retain(|s| !doomed.contains(&s.planet_id));
+        self.vehicles.retain(|v| !doomed.contains(&v.parent));
+        self.events.retain(|e| match &e.action {
+            ScriptAction::SpawnVehicle(v) => !doomed.contains(&v.parent),
+            ScriptAction::Message(_) => true,
+        });
+    }
+
+    /// Position of the body at the start of the scenario, relative to the
+    /// root. None if it doesn't trace back to the root.
+    pub fn position(&self, id: EntityId) -> Option<DVec2> {
+        let mut pos = DVec2::ZERO;
+        let mut current = self.body(id)?;
+        for _ in 0..self.bodies.len() {
+            let Some((parent, orbit)) = current.orbit else {
+                return Some(pos);
+            };
+            pos += orbit.initial_position();
+            current = self.body(parent)?;
+        }
+        None
+    }
+
+    /// Innermost body whose SOI contains the given point.
+    pub fn body_at(&self, pos: DVec2) -> Option<EntityId> {
+        self.bodies
+            .iter()
+            .filter_map(|b| {
+                let p = self.position(b.id)?;
+                (p.distance(pos) < b.body.soi).then_some((b.id, b.body.soi))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
+    }
+
+    fn depth(&self, id: EntityId) -> Option<usize> {
+        let mut current = self.body(id)?;
+        for depth in 0..self.bodies.len() {
+            let Some((parent, _)) = current.orbit else {
+                return Some(depth);
+            };
+            current = self.body(parent)?;
+        }
+        None
+    }
+
+    pub fn validate(&self) -> Vec<ScenarioIssue> {
+        let mut issues = Vec::new();
+
+        let roots: Vec<_> = self.bodies.iter().filter(|b| b.orbit.is_none()).collect();
+        if roots.is_empty() {
+            issues.push(ScenarioIssue::NoRoot);
+        }
+        for extra in roots.iter().skip(1) {
+            issues.push(ScenarioIssue::ExtraRoot(extra.id));
+        }
+
+        for (i, b) in self.bodies.iter().enumerate() {
+            if self.bodies[..i].iter().any(|o| o.id == b.id) {
+                issues.push(ScenarioIssue::DuplicateId(b.id));
+            }
+            if b.body.radius <= 0.0 || b.body.mu <= 0.0 || b.body.soi <= b.body.radius {
+                issues.push(ScenarioIssue::BadBody(b.id));
+            }
+
+            let Some((parent_id, orbit)) = b.orbit else {
+                continue;
+            };
+            let Some(parent) = self.body(parent_id) else {
+                issues.push(ScenarioIssue::UnknownParent(b.id));
+                continue;
+            };
+            if self.depth(b.id).is_none() {
+                issues.push(ScenarioIssue::ParentCycle(b.id));
+                continue;
+            }
+            if orbit.to_orbit(parent.body).is_none() {
+                issues.push(ScenarioIssue::BadOrbit(b.id));
+                continue;
+            }
+            if orbit.periapsis <= parent.body.radius + b.body.radius {
+                issues.push(ScenarioIssue::OrbitHitsParent(b.id));
+            }
+            if orbit.apoapsis + b.body.soi >= parent.body.soi {
+                issues.push(ScenarioIssue::SoiOutsideParent(b.id));
+            }
+
+            // siblings sweep out annuli about their parent, which mustn't
+            // overlap, or their SOIs could eventually intersect
+            for other in self.children(parent_id) {
+                if other.id <= b.id {
+                    continue;
+                }
+                let Some((_, o)) = other.orbit else {
+                    continue;
+                };
+                let (lo, hi) = (orbit.periapsis - b.body.soi, orbit.apoapsis + b.body.soi);
+                let (olo, ohi) = (o.periapsis - other.body.soi, o.apoapsis + other.body.soi);
+                if lo < ohi && olo < hi {
+                    issues.push(ScenarioIssue::SoiOverlap(b.id, other.id));
+                }
+            }
+        }
+
+        for (i, site) in self.sites.iter().enumerate() {
+            match self.body(site.planet_id) {
+                None => issues.push(ScenarioIssue::SiteUnknownPlanet(i)),
+                Some(p) if site.width >= p.body.radius * 2.0 * PI_64 => {
+                    issues.push(ScenarioIssue::SiteTooWide(i))
+                }
+                _ => (),
+            }
+        }
+
+        for (i, v) in self.vehicles.iter().enumerate() {
+            match self.vehicle_orbit_ok(v) {
+                None => issues.push(ScenarioIssue::VehicleUnknownParent(i)),
+                Some(false) => issues.push(ScenarioIssue::VehicleBadOrbit(i)),
+                Some(true) => (),
+            }
+        }
+
+        for (i, e) in self.events.iter().enumerate() {
+            let ScriptAction::SpawnVehicle(v) = &e.action else {
+                continue;
+            };
+            match self.vehicle_orbit_ok(v) {
+                None => issues.push(ScenarioIssue::EventUnknownParent(i)),
+                Some(false) => issues.push(ScenarioIssue::EventBadOrbit(i)),
+                Some(true) => (),
+            }
+        }
+
+        issues
+    }
+
+    /// Whether a vehicle's orbit lies above its parent's surface and within
+    /// its SOI, or None if the parent doesn't exist.
+    fn vehicle_orbit_ok(&self, v: &ScenarioVehicle) -> Option<bool> {
+        let parent = self.body(v.parent)?;
+        Some(
+            v.orbit.to_orbit(parent.body).is_some()
+                && v.orbit.periapsis > parent.body.radius
+                && v.orbit.apoapsis < parent.body.soi,
+        )
+    }
+
+    /// Assembles the planetary system, failing with the first issue found.
+    pub fn to_system(&self) -> Result<PlanetarySystem, ScenarioIssue> {
+        if let Some(issue) = self.validate().first() {
+            return Err(*issue);
+        }
+
+        fn build(scenario: &Scenario, b: &ScenarioBody) -> PlanetarySystem {
+            let mut sys = PlanetarySystem::new(b.id, b.name.clone(), b.body);
+            for child in scenario.children(b.id) {
+                if let Some((_, spec)) = child.orbit {
+                    if let Some(orbit) = spec.to_orbit(b.body) {
+                        sys.orbit(orbit, build(scenario, child));
+                    }
+                }
+            }
+            sys
+        }
+
+        let root = self.root().ok_or(ScenarioIssue::NoRoot)?;
+        Ok(build(self, root))
+    }
+
+    /// Renames body IDs throughout the scenario, including everything
+    /// which refers to a body.
+    pub fn remap_ids(&mut self, remap: &IdRemap) {
+        for b in &mut self.bodies {
+            remap.apply(&mut b.id);
+            if let Some((parent, _)) = &mut b.orbit {
+                remap.apply(parent);
+            }
+        }
+        for s in &mut self.sites {
+            remap.apply(&mut s.planet_id);
+        }
+        for v in &mut self.vehicles {
+            remap.apply(&mut v.parent);
+        }
+        for e in &mut self.events {
+            if let ScriptAction::SpawnVehicle(v) = &mut e.action {
+                remap.apply(&mut v.parent);
+            }
+        }
+    }
+
+    /// Gives bodies saved with a bare numeric ID the ID derived from their
+    /// name, as definitions in the assets have.
+    pub fn upgrade_legacy_ids(&mut self) {
+        let mut remap = IdRemap::default();
+        for b in self.bodies.iter().filter(|b| b.id.is_legacy()) {
+            let id = EntityId::named(EntityKind::Planet, &b.name);
+            if self.body(id).is_none() {
+                remap.insert(b.id, id);
+            }
+        }
+        self.remap_ids(&remap);
+    }
+
+    /// Creates a universe for the scenario. Vehicles are queued to be
+    /// loaded by model name.
+    pub fn to_universe(&self) -> Result<Universe, ScenarioIssue> {
+        let mut universe = Universe::new(self.to_system()?);
+        for site in &self.sites {
+            universe.add_landing_pad(LandingPad::new(
+                site.name.clone(),
+                site.planet_id,
+                site.angle,
+                site.width,
+            ));
+        }
+        for (i, v) in self.vehicles.iter().enumerate() {
+            let parent = self
+                .body(v.parent)
+                .ok_or(ScenarioIssue::VehicleUnknownParent(i))?;
+            let orbit = v
+                .orbit
+                .to_orbit(parent.body)
+                .ok_or(ScenarioIssue::VehicleBadOrbit(i))?;
+            universe.queue_orbital_vehicle(v.model.clone(), GlobalOrbit(v.parent, orbit));
+        }
+        universe.script = ScenarioScript::new(self.events.clone());
+        Ok(universe)
+    }
+}
+
+pub fn load_scenario_from_file(filename: &Path) -> Result<Scenario, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    let mut scenario: Scenario = serde_yaml::from_str(&s)?;
+    scenario.upgrade_legacy_ids();
+    Ok(scenario)
+}
+
+pub fn write_scenario_to_file(scenario: &Scenario, filename: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(scenario)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::default_example;
+
+    #[test]
+    fn scenarios_validate_and_round_trip() {
+        let mut scenario = Scenario::from_system("rss", &default_example());
+        assert_eq!(scenario.bodies.len(), 2);
+        assert_eq!(scenario.validate(), vec![]);
+
+        let root = scenario.root().unwrap().id;
+        let luna = scenario.children(root).next().unwrap().clone();
+        let original = default_example();
+        let luna_pos = original.lookup(luna.id, Nanotime::zero()).unwrap().1.pos;
+        assert!(scenario.position(luna.id).unwrap().distance(luna_pos) < 1.0);
+
+        let radius = scenario.body(root).unwrap().body.soi * 0.2;
+        let moon = scenario.add_body(Some(root), OrbitSpec::circular(radius, 2.0));
+        assert_eq!(scenario.validate(), vec![]);
+        assert_eq!(
+            scenario.body_at(scenario.position(moon).unwrap()),
+            Some(moon)
+        );
+
+        scenario.sites.push(ScenarioSite {
+            name: "Pad".into(),
+            planet_id: moon,
+            angle: 0.0,
+            width: 10.0,
+        });
+        scenario.vehicles.push(ScenarioVehicle {
+            model: "remora".into(),
+            parent: root,
+            orbit: OrbitSpec::circular(scenario.body(root).unwrap().body.radius * 1.5, 0.0),
+        });
+        let spawn = ScenarioVehicle {
+            model: "remora".into(),
+            parent: moon,
+            orbit: OrbitSpec::circular(scenario.body(moon).unwrap().body.radius * 1.5, 0.0),
+        };
+        scenario.events.push(ScriptedEvent {
+            stamp: Nanotime::secs(60),
+            action: ScriptAction::SpawnVehicle(spawn.clone()),
+        });
+        scenario.events.push(ScriptedEvent {
+            stamp: Nanotime::secs(30),
+            action: ScriptAction::Message("Launch window opens".into()),
+        });
+        let mut universe = scenario.to_universe().unwrap();
+        assert_eq!(universe.planets.planet_ids().len(), 3);
+        assert_eq!(universe.landing_pads.len(), 1);
+        assert_eq!(universe.loading.len(), 1);
+        let ticks = (Nanotime::secs(90).inner() / PHYSICS_CONSTANT_DELTA_TIME.inner()) as u32;
+        universe.run_batch_ticks(ticks);
+        assert_eq!(universe.loading.len(), 2);
+        assert_eq!(universe.script.next_event(), None);
+
+        let yaml = serde_yaml::to_string(&scenario).unwrap();
+        let back: Scenario = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back, scenario);
+
+        // pushing the new moon out onto Luna's path breaks SOI nesting
+        scenario.body_mut(moon).unwrap().orbit = Some((root, luna.orbit.unwrap().1));
+        assert!(scenario.validate().contains(&ScenarioIssue::SoiOverlap(
+            luna.id.min(moon),
+            luna.id.max(moon)
+        )));
+
+        scenario.body_mut(root).unwrap().orbit = Some((moon, OrbitSpec::circular(1.0, 0.0)));
+        assert!(scenario.validate().contains(&ScenarioIssue::NoRoot));
+        assert!(scenario.to_system().is_err());
+
+        scenario.remove_body(luna.id);
+        assert_eq!(scenario.bodies.len(), 2);
+        scenario.remove_body(moon);
+        assert!(scenario.bodies.is_empty());
+        assert!(scenario.sites.is_empty());
+        assert!(scenario.vehicles.is_empty());
+        assert_eq!(scenario.events.len(), 1);
+    }
+
+    #[test]
+    fn legacy_ids_are_upgraded_and_saved_ids_kept() {
+        let mut scenario = Scenario::from_system("rss", &default_example());
+        let earth = scenario.root().unwrap().id;
+        let luna = scenario.children(earth).next().unwrap().id;
+        assert_eq!(luna, EntityId::named(EntityKind::Planet, "Luna"));
+        scenario.sites.push(ScenarioSite {
+            name: "Pad".into(),
+            planet_id: luna,
+            angle: 0.0,
+            width: 10.0,
+        });
+        let current = scenario.clone();
+
+        // as written before IDs had kinds
+        let mut old = IdRemap::default();
+        old.insert(earth, EntityId(900));
+        old.insert(luna, EntityId(901));
+        scenario.remap_ids(&old);
+        assert_eq!(scenario.sites[0].planet_id, EntityId(901));
+        scenario.upgrade_legacy_ids();
+        assert_eq!(scenario, current);
+
+        let mut universe = scenario.to_universe().unwrap();
+        let pad = *universe.landing_pads.keys().next().unwrap();
+        assert_eq!(pad.kind(), EntityKind::Site);
+        assert_eq!(universe.legacy_ids().get(EntityId(901)), luna);
+        assert_eq!(universe.legacy_ids().get(EntityId(1002)), pad);
+
+        let mut legacy = Structure::new(StructureKind::Tower, EntityId(901), 0.0, 10.0, 10.0);
+        legacy.site = Some(EntityId(1002));
+        let saved = EntityId::new(EntityKind::Structure, 40);
+        let tank = Structure::new(StructureKind::Tank, luna, 1.0, 10.0, 10.0);
+        universe.replace_structures(vec![(Some(saved), tank), (Some(EntityId(1005)), legacy)]);
+        assert_eq!(universe.structures.len(), 2);
+        assert!(universe.structures.contains_key(&saved));
+        let (id, s) = universe
+            .structures
+            .iter()
+            .find(|(id, _)| **id != saved)
+            .unwrap();
+        assert_eq!(id.kind(), EntityKind::Structure);
+        assert_eq!((s.planet_id, s.site), (luna, Some(pad)));
+        assert_eq!(universe.add_structure(s.clone()).unwrap().index(), 42);
+    }
+}
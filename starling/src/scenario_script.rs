@@ -0,0 +1,132 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Something a scenario makes happen at a set time.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum ScriptAction {
+    /// Queues a vehicle to be loaded onto the given orbit.
+    SpawnVehicle(ScenarioVehicle),
+    /// Shows the player a message.
+    Message(String),
+}
+
+impl std::fmt::Display for ScriptAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SpawnVehicle(v) => write!(f, "Spawn {} around {}", v.model, v.parent),
+            Self::Message(s) => write!(f, "\"{}\"", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ScriptedEvent {
+    pub stamp: Nanotime,
+    pub action: ScriptAction,
+}
+
+/// The timed events of a scenario, run by the universe as sim time passes.
+/// Each event runs once, in order of time, and is then kept around so
+/// that consumers can look it up by its index.
+#[derive(Debug, Clone, Default)]
+pub struct ScenarioScript {
+    events: Vec<ScriptedEvent>,
+    next: usize,
+}
+
+impl ScenarioScript {
+    pub fn new(mut events: Vec<ScriptedEvent>) -> Self {
+        events.sort_by_key(|e| e.stamp);
+        Self { events, next: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<&ScriptedEvent> {
+        self.events.get(i)
+    }
+
+    /// Events yet to run, with their indices.
+    pub fn pending(&self) -> impl Iterator<Item = (usize, &ScriptedEvent)> + use<'_> {
+        self.events.iter().enumerate().skip(self.next)
+    }
+
+    pub fn next_event(&self) -> Option<Nanotime> {
+        self.events.get(self.next).map(|e| e.stamp)
+    }
+
+    /// Marks every event due at or before `stamp` as run, returning their
+    /// indices.
+    pub(crate) fn take_due(&mut self, stamp: Nanotime) -> std::ops::Range<usize> {
+        let start = self.next;
+        while self.events.get(self.next).is_some_and(|e| e.stamp <= stamp) {
+            self.next += 1;
+        }
+        start..self.next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(secs: i64, s: &str) -> ScriptedEvent {
+        ScriptedEvent {
+            stamp: Nanotime::secs(secs),
+            action: ScriptAction::Message(s.to_string()),
+        }
+    }
+
+    #[test]
+    fn scripted_events_run_once_in_order() {
+        let mut script = ScenarioScript::new(vec![
+            message(20, "second"),
+            message(10, "first"),
+            message(30, "third"),
+        ]);
+        assert_eq!(script.next_event(), Some(Nanotime::secs(10)));
+        assert!(script.take_due(Nanotime::secs(5)).is_empty());
+
+        let due = script.take_due(Nanotime::secs(20));
+        let actions: Vec<_> = due.map(|i| script.get(i).unwrap().action.clone()).collect();
+        assert_eq!(
+            actions,
+            vec![
+                ScriptAction::Message("first".to_string()),
+                ScriptAction::Message("second".to_string())
+            ]
+        );
+        assert!(script.take_due(Nanotime::secs(20)).is_empty());
+        assert_eq!(script.pending().count(), 1);
+        assert_eq!(script.take_due(Nanotime::secs(100)), 2..3);
+        assert_eq!(script.next_event(), None);
+    }
+
+    #[test]
+    fn rewinding_replays_scripted_events() {
+        let mut universe = Universe::empty();
+        let start = universe.stamp();
+        universe.script = ScenarioScript::new(vec![ScriptedEvent {
+            stamp: start + Nanotime::secs(1),
+            action: ScriptAction::Message("hello".to_string()),
+        }]);
+        let checkpoint = universe.checkpoint();
+
+        while universe.stamp() < start + Nanotime::secs(2) {
+            universe.run_batch_ticks(1);
+        }
+        assert_eq!(universe.script.next_event(), None);
+
+        universe.restore(&checkpoint);
+        assert_eq!(
+            universe.script.next_event(),
+            Some(start + Nanotime::secs(1))
+        );
+    }
+}
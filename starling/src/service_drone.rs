@@ -0,0 +1,158 @@
+use crate::prelude::*;
+
+/// Height above a serviced vehicle's bounding radius at which a drone
+/// hovers while it works.
+pub const DRONE_HOVER_HEIGHT: f64 = 2.0;
+
+/// Distance from its hover point within which a drone can do work.
+pub const DRONE_WORK_RADIUS: f64 = 1.5;
+
+/// Part builds a drone completes per second while repairing.
+pub const REPAIR_BUILDS_PER_SECOND: f64 = 4.0;
+
+/// A job for a landing site's service drones, performed on a vehicle
+/// parked on the site's pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceTask {
+    Refuel(EntityId),
+    Repair(EntityId, PartId),
+}
+
+impl ServiceTask {
+    pub fn vehicle(&self) -> EntityId {
+        match self {
+            Self::Refuel(id) | Self::Repair(id, _) => *id,
+        }
+    }
+
+    pub fn service(&self) -> SiteService {
+        match self {
+            Self::Refuel(_) => SiteService::Refueling,
+            Self::Repair(_, _) => SiteService::Repair,
+        }
+    }
+
+    /// Fraction of the task which is done, judged by the state of the
+    /// vehicle.
+    pub fn progress(&self, vehicle: &Vehicle) -> f64 {
+        match self {
+            Self::Refuel(_) => vehicle.fuel_percentage(),
+            Self::Repair(_, part) => vehicle
+                .get_part(*part)
+                .map(|p| p.percent_built() as f64)
+                .unwrap_or(1.0),
+        }
+    }
+
+    /// Does `dt` seconds of work on the vehicle, accumulating partial
+    /// repairs in `work`. Returns true once the task is complete.
+    pub fn perform(
+        &self,
+        vehicle: &mut Vehicle,
+        refuel_rate: f64,
+        dt: f64,
+        work: &mut f64,
+    ) -> bool {
+        match self {
+            Self::Refuel(_) => {
+                let added = vehicle.refuel(Mass::from_kg_f32((refuel_rate * dt) as f32));
+                added == Mass::ZERO
+            }
+            Self::Repair(_, part) => {
+                *work += dt * REPAIR_BUILDS_PER_SECOND;
+                while *work >= 1.0 {
+                    vehicle.build_part(*part);
+                    *work -= 1.0;
+                }
+                vehicle.get_part(*part).is_none_or(|p| p.is_built())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ServiceTask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Refuel(id) => write!(f, "Refuel {}", id),
+            Self::Repair(id, part) => write!(f, "Repair {} part {:?}", id, part),
+        }
+    }
+}
+
+/// A small hovering robot based at a landing site. Positions are in the
+/// site's frame: x is meters along the surface from the pad center, and
+/// y is altitude above the surface.
+#[derive(Debug, Clone)]
+pub struct ServiceDrone {
+    bot: ConBot,
+    home: DVec2,
+    task: Option<ServiceTask>,
+    work: f64,
+}
+
+impl ServiceDrone {
+    pub fn new(home: DVec2) -> Self {
+        let mut bot = ConBot::new(PV::from_f64(home, DVec2::ZERO));
+        bot.set_target_pos(home);
+        Self {
+            bot,
+            home,
+            task: None,
+            work: 0.0,
+        }
+    }
+
+    pub fn pos(&self) -> DVec2 {
+        self.bot.pos()
+    }
+
+    pub fn angle(&self) -> f64 {
+        self.bot.angle()
+    }
+
+    pub fn task(&self) -> Option<ServiceTask> {
+        self.task
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.task.is_none()
+    }
+
+    pub fn assign(&mut self, task: ServiceTask) {
+        self.task = Some(task);
+        self.work = 0.0;
+    }
+
+    /// Drops the current task, and heads home.
+    pub fn finish(&mut self) {
+        self.task = None;
+        self.bot.set_target_pos(self.home);
+    }
+
+    /// Whether the drone is close enough to its destination to work.
+    pub fn arrived(&self) -> bool {
+        self.bot
+            .target_pos()
+            .is_some_and(|p| p.distance(self.pos()) < DRONE_WORK_RADIUS)
+    }
+
+    /// Flies toward the given hover point for one tick, then does a tick
+    /// of work on the vehicle if it has arrived. Returns true once the
+    /// current task is complete.
+    pub fn service(&mut self, hover: DVec2, vehicle: &mut Vehicle, refuel_rate: f64) -> bool {
+        self.bot.set_target_pos(hover);
+        self.bot.on_sim_tick();
+        let Some(task) = self.task else {
+            return false;
+        };
+        if !self.arrived() {
+            return false;
+        }
+        let dt = PHYSICS_CONSTANT_DELTA_TIME.to_secs_f64();
+        task.perform(vehicle, refuel_rate, dt, &mut self.work)
+    }
+
+    pub fn on_sim_tick(&mut self) {
+        self.bot.on_sim_tick();
+    }
+}
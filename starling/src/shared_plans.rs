@@ -0,0 +1,344 @@
+use crate::burn_alignment::BURN_HOLD_LEAD;
+use crate::id::EntityId;
+use crate::math::*;
+use crate::nanotime::Nanotime;
+use crate::orbits::{GlobalOrbit, SparseOrbit};
+use crate::planning::ManeuverPlan;
+use std::collections::{HashMap, HashSet};
+
+/// Number of steps over one orbit in which to look for the point where a
+/// vehicle reaches the template's phase, before refining by bisection.
+const PHASE_SEARCH_STEPS: usize = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlanShareError {
+    DifferentBody,
+    OppositeDirection,
+    NoPhaseMatch,
+    InvalidPlan,
+    InsufficientDv { needed: f64, available: f64 },
+    Impact,
+}
+
+impl std::fmt::Display for PlanShareError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DifferentBody => write!(f, "orbits a different body"),
+            Self::OppositeDirection => write!(f, "orbits in the opposite direction"),
+            Self::NoPhaseMatch => write!(f, "isn't in a closed orbit"),
+            Self::InvalidPlan => write!(f, "plan could not be constructed"),
+            Self::InsufficientDv { needed, available } => {
+                write!(f, "needs {:0.1} m/s but has {:0.1} m/s", needed, available)
+            }
+            Self::Impact => write!(f, "plan would hit the surface"),
+        }
+    }
+}
+
+/// A maneuver plan copied from one vehicle so that others can fly it.
+/// Burns are kept relative to the first one, which is tied to the angle
+/// about the body at which it happens rather than to a time.
+#[derive(Debug, Clone)]
+pub struct PlanTemplate {
+    parent: EntityId,
+    retrograde: bool,
+    angle: f64,
+    heading: f64,
+    burns: Vec<(Nanotime, DVec2)>,
+}
+
+impl PlanTemplate {
+    pub fn new(parent: EntityId, plan: &ManeuverPlan) -> Option<Self> {
+        let (t0, _) = plan.dvs().next()?;
+        let pv = plan.initial.pv(t0).ok()?;
+        Some(Self {
+            parent,
+            retrograde: plan.initial.is_retrograde(),
+            angle: pv.pos.to_angle(),
+            heading: pv.vel.to_angle(),
+            burns: plan.dvs().map(|(t, dv)| (t - t0, dv)).collect(),
+        })
+    }
+
+    pub fn parent(&self) -> EntityId {
+        self.parent
+    }
+
+    pub fn dv(&self) -> f64 {
+        self.burns.iter().map(|(_, dv)| dv.length()).sum()
+    }
+
+    /// Fits the template to a vehicle in the given orbit, with the first
+    /// burn at the first time no earlier than `earliest` that the vehicle
+    /// passes the angle about the body where the original first burn took
+    /// place. Burns are rotated with the vehicle's direction of travel, so
+    /// a prograde burn stays prograde.
+    pub fn fit(
+        &self,
+        orbit: &GlobalOrbit,
+        earliest: Nanotime,
+        available_dv: f64,
+    ) -> Result<ManeuverPlan, PlanShareError> {
+        let GlobalOrbit(parent, orbit) = orbit;
+        if *parent != self.parent {
+            return Err(PlanShareError::DifferentBody);
+        }
+        if orbit.is_retrograde() != self.retrograde {
+            return Err(PlanShareError::OppositeDirection);
+        }
+
+        let needed = self.dv();
+        if needed > available_dv {
+            return Err(PlanShareError::InsufficientDv {
+                needed,
+                available: available_dv,
+            });
+        }
+
+        let t0 = time_at_angle(orbit, self.angle, earliest).ok_or(PlanShareError::NoPhaseMatch)?;
+        let pv = orbit.pv(t0).map_err(|_| PlanShareError::NoPhaseMatch)?;
+        let rotation = pv.vel.to_angle() - self.heading;
+
+        let dvs: Vec<_> = self
+            .burns
+            .iter()
+            .map(|(dt, dv)| (t0 + *dt, rotate_f64(*dv, rotation)))
+            .collect();
+        let plan = ManeuverPlan::new(earliest, *orbit, &dvs).ok_or(PlanShareError::InvalidPlan)?;
+
+        let body = orbit.body;
+        let impacts = plan
+            .segments
+            .iter()
+            .skip(1)
+            .map(|s| &s.orbit)
+            .chain([&plan.terminal])
+            .any(|o| o.periapsis_r() < body.radius);
+        if impacts {
+            return Err(PlanShareError::Impact);
+        }
+
+        Ok(plan)
+    }
+}
+
+/// First time no earlier than `earliest` at which an orbiter in the given
+/// orbit passes the given angle about the body.
+fn time_at_angle(orbit: &SparseOrbit, angle: f64, earliest: Nanotime) -> Option<Nanotime> {
+    let period = orbit.period()?;
+    let offset = |t: Nanotime| -> Option<f64> {
+        let pv = orbit.pv(t).ok()?;
+        Some(wrap_pi_npi_f64(pv.pos.to_angle() - angle))
+    };
+
+    let step = period / PHASE_SEARCH_STEPS as i64;
+    let mut a = earliest;
+    let mut fa = offset(a)?;
+    if fa == 0.0 {
+        return Some(a);
+    }
+    for _ in 0..=PHASE_SEARCH_STEPS {
+        let b = a + step;
+        let fb = offset(b)?;
+        // a sign change that isn't the wrap from pi to -pi
+        if fa.signum() != fb.signum() && (fa - fb).abs() < PI_64 {
+            let (mut lo, mut hi, mut flo) = (a, b, fa);
+            while hi - lo > Nanotime::millis(1) {
+                let mid = lo + (hi - lo) / 2;
+                let fm = offset(mid)?;
+                if fm.signum() == flo.signum() {
+                    (lo, flo) = (mid, fm);
+                } else {
+                    hi = mid;
+                }
+            }
+            return Some(hi);
+        }
+        (a, fa) = (b, fb);
+    }
+    None
+}
+
+#[derive(Debug, Clone)]
+struct ScheduledPlan {
+    plan: ManeuverPlan,
+    last_burn: Option<Nanotime>,
+}
+
+impl ScheduledPlan {
+    fn pending_burns(&self) -> impl Iterator<Item = (Nanotime, DVec2)> + use<'_> {
+        let last = self.last_burn;
+        self.plan
+            .dvs()
+            .filter(move |(t, _)| last.is_none_or(|l| *t > l))
+    }
+}
+
+/// Maneuver plans scheduled for vehicles, such as those copied from
+/// another vehicle, keyed by vehicle. Burns are applied as impulses at
+/// their scheduled times, and a plan is dropped once it's complete.
+#[derive(Debug, Clone, Default)]
+pub struct ScheduledManeuvers {
+    plans: HashMap<EntityId, ScheduledPlan>,
+    /// Turn idle vehicles to face each burn ahead of time, and abort
+    /// plans whose vehicles aren't lined up when a burn comes due.
+    pub auto_hold: bool,
+    /// Vehicles whose attitude is being held for a burn.
+    holding: HashSet<EntityId>,
+}
+
+impl ScheduledManeuvers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn schedule(&mut self, id: EntityId, plan: ManeuverPlan) {
+        self.plans.insert(
+            id,
+            ScheduledPlan {
+                plan,
+                last_burn: None,
+            },
+        );
+    }
+
+    pub fn cancel(&mut self, id: EntityId) -> Option<ManeuverPlan> {
+        self.plans.remove(&id).map(|s| s.plan)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&ManeuverPlan> {
+        self.plans.get(&id).map(|s| &s.plan)
+    }
+
+    pub fn len(&self) -> usize {
+        self.plans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plans.is_empty()
+    }
+
+    /// Earliest pending burn of any scheduled plan.
+    pub fn next_burn(&self) -> Option<Nanotime> {
+        self.plans
+            .values()
+            .filter_map(|s| s.pending_burns().next().map(|(t, _)| t))
+            .min()
+    }
+
+    /// Next pending burn of the given vehicle's plan.
+    pub fn next_burn_for(&self, id: EntityId) -> Option<(Nanotime, DVec2)> {
+        self.plans.get(&id)?.pending_burns().next()
+    }
+
+    /// Earliest time after `stamp` at which a vehicle should start
+    /// holding attitude for a burn, if auto-hold is on.
+    pub fn next_hold(&self, stamp: Nanotime) -> Option<Nanotime> {
+        if !self.auto_hold {
+            return None;
+        }
+        self.plans
+            .values()
+            .filter_map(|s| s.pending_burns().next().map(|(t, _)| t - BURN_HOLD_LEAD))
+            .filter(|t| *t > stamp)
+            .min()
+    }
+
+    pub fn is_holding(&self, id: EntityId) -> bool {
+        self.holding.contains(&id)
+    }
+
+    pub(crate) fn start_hold(&mut self, id: EntityId) {
+        self.holding.insert(id);
+    }
+
+    /// Stops holding attitude for the given vehicle, returning whether it
+    /// was being held.
+    pub(crate) fn stop_hold(&mut self, id: EntityId) -> bool {
+        self.holding.remove(&id)
+    }
+
+    /// Vehicles being held which no longer have a plan.
+    pub(crate) fn stale_holds(&self) -> Vec<EntityId> {
+        self.holding
+            .iter()
+            .filter(|id| !self.plans.contains_key(id))
+            .copied()
+            .collect()
+    }
+
+    /// Removes and returns the burns which are due at the given time,
+    /// dropping plans which have no more burns.
+    pub(crate) fn take_due(&mut self, stamp: Nanotime) -> Vec<(EntityId, DVec2)> {
+        let mut due = Vec::new();
+        for (id, s) in &mut self.plans {
+            let burns: Vec<_> = s.pending_burns().take_while(|(t, _)| *t <= stamp).collect();
+            for (t, dv) in burns {
+                s.last_burn = Some(t);
+                due.push((*id, dv));
+            }
+        }
+        self.plans.retain(|_, s| s.pending_burns().next().is_some());
+        due
+    }
+
+    pub(crate) fn retain(&mut self, f: impl Fn(EntityId) -> bool) {
+        self.plans.retain(|id, _| f(*id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbits::Body;
+    use crate::planning::best_maneuver_plan;
+
+    #[test]
+    fn shared_plans_are_phase_matched_and_checked() {
+        let t0 = Nanotime::zero();
+        let body = Body::LUNA;
+        let leader = SparseOrbit::circular(5_000_000.0, body, t0, false);
+        let dest = SparseOrbit::circular(7_000_000.0, body, t0, false);
+        let plan = best_maneuver_plan(&leader, &dest, t0).unwrap();
+        let template = PlanTemplate::new(EntityId(0), &plan).unwrap();
+        let (first, _) = plan.dvs().next().unwrap();
+
+        // a follower half an orbit behind, on the same orbit
+        let period = leader.period().unwrap();
+        let follower = SparseOrbit::circular(5_000_000.0, body, t0 + period / 2, false);
+        let earliest = first + Nanotime::mins(10);
+        let fitted = template
+            .fit(&GlobalOrbit(EntityId(0), follower), earliest, 1e6)
+            .unwrap();
+        let (start, _) = fitted.dvs().next().unwrap();
+        assert!(start >= earliest);
+        let (p, q) = (
+            plan.initial.pv(first).unwrap().pos,
+            follower.pv(start).unwrap().pos,
+        );
+        assert!(p.angle_to(q).abs() < 1e-3);
+        assert!((fitted.dv() - plan.dv()).abs() < 1e-6);
+        assert!((fitted.terminal.apoapsis_r() - plan.terminal.apoapsis_r()).abs() < 1_000.0);
+
+        let other = GlobalOrbit(EntityId(1), follower);
+        assert_eq!(
+            template.fit(&other, earliest, 1e6).unwrap_err(),
+            PlanShareError::DifferentBody
+        );
+        assert!(matches!(
+            template.fit(&GlobalOrbit(EntityId(0), follower), earliest, 1.0),
+            Err(PlanShareError::InsufficientDv { .. })
+        ));
+
+        let mut scheduled = ScheduledManeuvers::new();
+        scheduled.schedule(EntityId(5), fitted.clone());
+        assert_eq!(scheduled.next_burn(), Some(start));
+        assert!(scheduled.take_due(start - Nanotime::secs(1)).is_empty());
+        assert_eq!(scheduled.take_due(start).len(), 1);
+        assert_eq!(
+            scheduled.take_due(fitted.end()).len(),
+            fitted.segments.len() - 1
+        );
+        assert!(scheduled.is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::prelude::*;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io::{Read, Write};
+
+/// Bumped whenever the layout of a snapshot changes, so that observers
+/// can refuse a host they can't understand.
+pub const SNAPSHOT_PROTOCOL: u32 = 1;
+
+/// Largest frame a decoder will wait for. Anything claiming to be bigger
+/// is taken to be garbage.
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// Largest a frame may grow to once inflated, so that a small frame of
+/// garbage can't balloon without bound.
+const MAX_DECODED_LEN: u64 = 256 * 1024 * 1024;
+
+/// State of one vehicle as streamed from a host to its observers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntitySnapshot {
+    pub id: EntityId,
+    pub parent: EntityId,
+    pub callsign: String,
+    pub pv: PV,
+    pub angle: f64,
+    pub angular_velocity: f64,
+    pub orbit: Option<SparseOrbit>,
+    pub target: Option<EntityId>,
+    /// Layout of the vehicle. Only sent when an observer hasn't seen this
+    /// vehicle yet, or it has been rebuilt since.
+    pub design: Option<VehicleFileStorage>,
+}
+
+/// Every vehicle in a universe at one instant. Planets aren't included;
+/// observers are expected to have loaded the same system as the host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseSnapshot {
+    pub protocol: u32,
+    pub stamp: Nanotime,
+    pub ticks: u128,
+    pub entities: Vec<EntitySnapshot>,
+}
+
+/// Encodes a value as one length-prefixed frame of deflated CBOR.
+pub fn encode_frame<T: Serialize>(value: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    ciborium::into_writer(value, &mut encoder)?;
+    let body = encoder.finish()?;
+    let mut ret = (body.len() as u32).to_le_bytes().to_vec();
+    ret.write_all(&body)?;
+    Ok(ret)
+}
+
+/// Splits a byte stream back into the frames written by `encode_frame`,
+/// however the bytes happen to arrive.
+#[derive(Debug, Clone, Default)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Decodes the next complete frame, if one has arrived.
+    pub fn next_frame<T: DeserializeOwned>(&mut self) -> Result<Option<T>, Box<dyn Error>> {
+        let Some(header) = self.buf.get(..4) else {
+            return Ok(None);
+        };
+        let len = u32::from_le_bytes(header.try_into()?) as usize;
+        if len > MAX_FRAME_LEN {
+            return Err(format!("frame of {} bytes is too large", len).into());
+        }
+        if self.buf.len() < 4 + len {
+            return Ok(None);
+        }
+        let frame: Vec<u8> = self.buf.drain(..4 + len).skip(4).collect();
+        Ok(Some(decode_frame(&frame, MAX_DECODED_LEN)?))
+    }
+}
+
+/// Inflates and parses the body of one frame, giving up once more than
+/// `limit` bytes have come out.
+fn decode_frame<T: DeserializeOwned>(body: &[u8], limit: u64) -> Result<T, Box<dyn Error>> {
+    let mut inflated = DeflateDecoder::new(body).take(limit + 1);
+    let mut buf = Vec::new();
+    inflated.read_to_end(&mut buf)?;
+    if buf.len() as u64 > limit {
+        return Err(format!("frame inflates past {} bytes", limit).into());
+    }
+    Ok(ciborium::from_reader(buf.as_slice())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn snapshots_rebuild_vehicles_on_observers() {
+        let frame = Generic::new(
+            "frame".to_string(),
+            UVec2::new(10, 10),
+            PartLayer::Structural,
+            Mass::kilograms(400),
+        );
+        let parts = HashMap::from([("frame".to_string(), PartPrototype::Generic(frame))]);
+        let vehicle = VehicleFileStorage {
+            name: "Box".to_string(),
+            parts: vec![VehiclePartFileStorage {
+                partname: "frame".to_string(),
+                pos: IVec2::ZERO,
+                rot: Rotation::East,
+                stage: 0,
+            }],
+            lines: HashSet::new(),
+            loadouts: Vec::new(),
+        }
+        .build("Boxy".to_string(), &parts)
+        .unwrap();
+
+        let mut host = Universe::empty();
+        let id = host
+            .add_surface_vehicle(EntityId(0), vehicle, 0.0, 1000.0)
+            .unwrap();
+        let mut observer = Universe::empty();
+        let mut sent = HashMap::new();
+
+        // designs go out once, and snapshots survive being cut into pieces
+        let snapshot = host.snapshot(&mut sent);
+        assert!(snapshot.entities[0].design.is_some());
+        let mut decoder = FrameDecoder::default();
+        let bytes = encode_frame(&snapshot).unwrap();
+        let (a, b) = bytes.split_at(bytes.len() / 2);
+        decoder.push(a);
+        assert!(decoder.next_frame::<UniverseSnapshot>().unwrap().is_none());
+        decoder.push(b);
+        let snapshot: UniverseSnapshot = decoder.next_frame().unwrap().unwrap();
+        assert!(observer.apply_snapshot(&snapshot, &parts).is_empty());
+
+        let sv = observer.surface_vehicles.get(&id).unwrap();
+        assert_eq!(sv.vehicle().model(), "Box");
+        assert_eq!(sv.pv(), host.surface_vehicles[&id].pv());
+
+        host.surface_vehicles.get_mut(&id).unwrap().body.angle = 1.0;
+        let snapshot = host.snapshot(&mut sent);
+        assert!(snapshot.entities[0].design.is_none());
+        observer.apply_snapshot(&snapshot, &parts);
+        assert_eq!(observer.surface_vehicles[&id].body.angle, 1.0);
+
+        // a vehicle with no design can't be built
+        let mut late = Universe::empty();
+        assert_eq!(late.apply_snapshot(&snapshot, &parts), vec![id]);
+
+        host.remove(id);
+        observer.apply_snapshot(&host.snapshot(&mut sent), &parts);
+        assert!(observer.surface_vehicles.is_empty());
+        assert!(sent.is_empty());
+    }
+
+    #[test]
+    fn frames_which_inflate_too_far_are_refused() {
+        let bytes = encode_frame(&vec![0u8; 4096]).unwrap();
+        assert!(decode_frame::<Vec<u8>>(&bytes[4..], 1024).is_err());
+        assert_eq!(
+            decode_frame::<Vec<u8>>(&bytes[4..], 1 << 20).unwrap().len(),
+            4096
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+/// Damage taken by a structure per meter per second of impact speed.
+pub const IMPACT_DAMAGE_PER_MPS: f64 = 5.0;
+
+/// Spacing along the surface between neighboring site buildings.
+const SITE_STRUCTURE_SPACING: f64 = 10.0;
+
+/// Gap between the edge of a pad and its nearest buildings.
+const SITE_STRUCTURE_MARGIN: f64 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum StructureKind {
+    Habitat,
+    Hangar,
+    Tank,
+    Tower,
+}
+
+impl StructureKind {
+    pub const ALL: [StructureKind; 4] = [Self::Habitat, Self::Hangar, Self::Tank, Self::Tower];
+
+    /// Health per meter of height, so sturdier kinds take more hits.
+    fn toughness(&self) -> f64 {
+        match self {
+            Self::Habitat => 10.0,
+            Self::Hangar => 15.0,
+            Self::Tank => 5.0,
+            Self::Tower => 8.0,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let i = Self::ALL.iter().position(|k| k == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+impl std::fmt::Display for StructureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Building on the surface of a planet, centered `angle` radians around
+/// it. Structures are static, but can be damaged by vehicles running
+/// into them, and are destroyed once their health runs out.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Structure {
+    pub kind: StructureKind,
+    pub planet_id: EntityId,
+    pub angle: f64,
+    pub width: f64,
+    pub height: f64,
+    pub health: f64,
+    /// Landing site this structure was built for, if any.
+    pub site: Option<EntityId>,
+}
+
+impl Structure {
+    pub fn new(
+        kind: StructureKind,
+        planet_id: EntityId,
+        angle: f64,
+        width: f64,
+        height: f64,
+    ) -> Self {
+        let mut s = Self {
+            kind,
+            planet_id,
+            angle: wrap_0_2pi_f64(angle),
+            width,
+            height,
+            health: 0.0,
+            site: None,
+        };
+        s.health = s.max_health();
+        s
+    }
+
+    pub fn max_health(&self) -> f64 {
+        self.height * self.kind.toughness()
+    }
+
+    pub fn health_fraction(&self) -> f64 {
+        (self.health / self.max_health()).clamp(0.0, 1.0)
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.health <= 0.0
+    }
+
+    /// Returns true if this damage destroyed the structure.
+    pub fn damage(&mut self, amount: f64) -> bool {
+        self.health = (self.health - amount).max(0.0);
+        self.is_destroyed()
+    }
+
+    pub fn repair(&mut self) {
+        self.health = self.max_health();
+    }
+
+    /// Changes the size of the structure, keeping the same fraction of
+    /// its health.
+    pub fn resize(&mut self, width: f64, height: f64) {
+        let frac = self.health_fraction();
+        self.width = width.max(1.0);
+        self.height = height.max(1.0);
+        self.health = self.max_health() * frac;
+    }
+
+    pub fn set_kind(&mut self, kind: StructureKind) {
+        let frac = self.health_fraction();
+        self.kind = kind;
+        self.health = self.max_health() * frac;
+    }
+
+    /// Center of the structure's base, relative to the planet center.
+    pub fn local_position(&self, radius: f64) -> DVec2 {
+        rotate_f64(DVec2::X * radius, self.angle)
+    }
+
+    /// Outline of the structure relative to the planet center, starting
+    /// and ending at the base.
+    pub fn corners(&self, radius: f64) -> [DVec2; 4] {
+        let up = rotate_f64(DVec2::X, self.angle);
+        let side = up.perp() * self.width / 2.0;
+        let base = self.local_position(radius);
+        [
+            base - side,
+            base - side + up * self.height,
+            base + side + up * self.height,
+            base + side,
+        ]
+    }
+
+    /// Whether `pos`, relative to the planet center, is inside the
+    /// structure.
+    pub fn contains(&self, pos: DVec2, radius: f64) -> bool {
+        let offset = wrap_pi_npi_f64(pos.to_angle() - self.angle) * radius;
+        let altitude = pos.length() - radius;
+        offset.abs() <= self.width / 2.0 && altitude <= self.height
+    }
+}
+
+/// The `index`th building put up around a landing site as it grows,
+/// alternating sides of the pad and working outward.
+pub fn site_structure(pad: &LandingPad, radius: f64, index: usize) -> Structure {
+    let i = index / 2;
+    let sign = if index.is_multiple_of(2) { -1.0 } else { 1.0 };
+    let offset = pad.width / 2.0 + SITE_STRUCTURE_MARGIN + i as f64 * SITE_STRUCTURE_SPACING;
+    let height = 6.0 + ((i * 7 + index) % 5) as f64 * 3.0;
+    let kind = StructureKind::ALL[index % StructureKind::ALL.len()];
+    Structure::new(
+        kind,
+        pad.planet_id,
+        pad.angle + sign * offset / radius,
+        6.0,
+        height,
+    )
+}
+
+/// Number of buildings a landing site of the given level has.
+pub fn site_structure_count(level: usize) -> usize {
+    (1 + level * 2) * 2
+}
+
+/// A structure read from a file, with the ID it was saved with if it has one.
+pub type LoadedStructure = (Option<EntityId>, Structure);
+
+/// Reads structures along with the IDs they were saved with. Files written
+/// before IDs were saved hold bare structures, which are given new IDs.
+pub fn load_structures_from_file(filename: &Path) -> Result<Vec<LoadedStructure>, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    if let Ok(saved) = serde_yaml::from_str::<Vec<(EntityId, Structure)>>(&s) {
+        return Ok(saved.into_iter().map(|(id, x)| (Some(id), x)).collect());
+    }
+    let bare: Vec<Structure> = serde_yaml::from_str(&s)?;
+    Ok(bare.into_iter().map(|x| (None, x)).collect())
+}
+
+pub fn write_structures_to_file(
+    structures: &[(EntityId, Structure)],
+    filename: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(structures)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structures_take_damage_and_round_trip() {
+        let radius = 1000.0;
+        let pad = LandingPad::new("Pad", EntityId(0), 0.0, 40.0);
+        let mut s = site_structure(&pad, radius, 1);
+        assert_eq!(s.health, s.max_health());
+
+        let base = s.local_position(radius);
+        assert!(s.contains(base * 1.001, radius));
+        assert!(!s.contains(base * 1.5, radius));
+        assert!(!pad.contains(base, radius));
+
+        // sites alternate sides of the pad
+        let other = site_structure(&pad, radius, 0);
+        assert!(other.angle > PI_64 && s.angle < PI_64);
+
+        assert!(!s.damage(s.max_health() / 2.0));
+        s.resize(s.width, s.height * 2.0);
+        assert!((s.health_fraction() - 0.5).abs() < 1E-9);
+        assert!(s.damage(s.max_health()));
+        s.repair();
+        assert!(!s.is_destroyed());
+
+        let yaml = serde_yaml::to_string(&vec![s.clone(), other.clone()]).unwrap();
+        let back: Vec<Structure> = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back, vec![s, other]);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+/// Width along the surface of each chunk of a landing site's terrain.
+pub const TERRAIN_CHUNK_WIDTH: f64 = 50.0;
+
+/// How far along the surface either side of its pad a landing site's
+/// terrain extends.
+pub const SITE_DOMAIN_HALF_WIDTH: f64 = 2_000.0;
+
+/// Impact speed, in meters per second, above which a falling object
+/// scorches the ground.
+pub const SCORCH_SPEED: f64 = 10.0;
+
+/// Impact speed above which a falling object leaves a crater.
+pub const CRATER_SPEED: f64 = 60.0;
+
+/// Salvage this far along the surface from a landed vehicle, or nearer,
+/// is picked up by it.
+pub const SALVAGE_PICKUP_RANGE: f64 = 25.0;
+
+/// Fraction of a wreck's dry mass left lying around as salvage.
+const SALVAGE_FRACTION: f64 = 0.2;
+
+/// What a wreck's structure is salvaged as, by fraction of mass.
+const SALVAGE_MATERIALS: [(Item, f64); 3] = [
+    (Item::Iron, 0.6),
+    (Item::Titanium, 0.25),
+    (Item::Copper, 0.15),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecalKind {
+    Scorch,
+    Crater,
+}
+
+/// Mark left on the ground by an impact. Offsets are along the surface
+/// from the center of the site's pad.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TerrainDecal {
+    pub kind: DecalKind,
+    pub offset: f64,
+    pub radius: f64,
+    pub stamp: Nanotime,
+}
+
+impl TerrainDecal {
+    /// The mark left by an impact at the given speed, if it was hard
+    /// enough to leave one.
+    pub fn for_impact(offset: f64, speed: f64, stamp: Nanotime) -> Option<Self> {
+        let kind = if speed > CRATER_SPEED {
+            DecalKind::Crater
+        } else if speed > SCORCH_SPEED {
+            DecalKind::Scorch
+        } else {
+            return None;
+        };
+        Some(Self {
+            kind,
+            offset,
+            radius: 2.0 * speed.sqrt(),
+            stamp,
+        })
+    }
+}
+
+/// Pile of material thrown from a wreck, which landed vehicles can pick
+/// up.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SalvageItem {
+    pub item: Item,
+    pub mass: Mass,
+    pub offset: f64,
+    /// Wreck it came from, which doesn't pick up its own salvage.
+    pub source: EntityId,
+}
+
+/// Ground around a landing site, marked by whatever has hit it and
+/// strewn with salvage. Decals are kept by the chunk of surface they're
+/// centered in, so that only the chunks in view need to be drawn.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SiteTerrain {
+    pub pad: EntityId,
+    chunks: BTreeMap<i64, Vec<TerrainDecal>>,
+    salvage: Vec<SalvageItem>,
+}
+
+impl SiteTerrain {
+    pub fn new(pad: EntityId) -> Self {
+        Self {
+            pad,
+            ..Default::default()
+        }
+    }
+
+    pub fn chunk_of(offset: f64) -> i64 {
+        (offset / TERRAIN_CHUNK_WIDTH).floor() as i64
+    }
+
+    pub fn in_domain(offset: f64) -> bool {
+        offset.abs() <= SITE_DOMAIN_HALF_WIDTH
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty() && self.salvage.is_empty()
+    }
+
+    pub fn add_decal(&mut self, decal: TerrainDecal) {
+        self.chunks
+            .entry(Self::chunk_of(decal.offset))
+            .or_default()
+            .push(decal);
+    }
+
+    /// Decals in the chunks overlapping the given stretch of surface.
+    pub fn decals_between(&self, from: f64, to: f64) -> impl Iterator<Item = &TerrainDecal> {
+        self.chunks
+            .range(Self::chunk_of(from)..=Self::chunk_of(to))
+            .flat_map(|(_, d)| d.iter())
+    }
+
+    pub fn decals(&self) -> impl Iterator<Item = &TerrainDecal> {
+        self.chunks.values().flatten()
+    }
+
+    pub fn salvage(&self) -> &[SalvageItem] {
+        &self.salvage
+    }
+
+    /// Strews the given material in piles within `radius` of `offset`.
+    pub fn scatter_salvage(
+        &mut self,
+        source: EntityId,
+        offset: f64,
+        radius: f64,
+        materials: impl IntoIterator<Item = (Item, Mass)>,
+    ) {
+        for (item, mass) in materials {
+            if mass == Mass::ZERO {
+                continue;
+            }
+            let spread = rand(-1.0, 1.0) as f64 * radius;
+            self.salvage.push(SalvageItem {
+                item,
+                mass,
+                offset: offset + spread,
+                source,
+            });
+        }
+    }
+
+    /// Removes the salvage within pickup range of `offset` which isn't
+    /// from `picker` itself.
+    pub fn take_salvage(&mut self, picker: EntityId, offset: f64) -> Vec<SalvageItem> {
+        let in_reach = |s: &SalvageItem| {
+            s.source != picker && (s.offset - offset).abs() <= SALVAGE_PICKUP_RANGE
+        };
+        let (taken, kept) = std::mem::take(&mut self.salvage)
+            .into_iter()
+            .partition(in_reach);
+        self.salvage = kept;
+        taken
+    }
+
+    pub fn put_back(&mut self, item: SalvageItem) {
+        self.salvage.push(item);
+    }
+}
+
+/// Material recoverable from the wreck of the given vehicle.
+pub fn wreck_salvage(vehicle: &Vehicle) -> Vec<(Item, Mass)> {
+    let total = vehicle.dry_mass().to_kg_f64() * SALVAGE_FRACTION;
+    SALVAGE_MATERIALS
+        .iter()
+        .map(|(item, frac)| (*item, Mass::from_kg_f32((total * frac) as f32)))
+        .collect()
+}
+
+pub fn load_terrain_from_file(filename: &Path) -> Result<Vec<SiteTerrain>, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    Ok(serde_yaml::from_str(&s)?)
+}
+
+pub fn write_terrain_to_file(
+    terrain: &[SiteTerrain],
+    filename: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(terrain)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impacts_mark_chunks_and_leave_salvage() {
+        let mut universe = Universe::new(crate::examples::rss());
+        let planet = universe.planets.id;
+        let radius = universe.planets.body.radius;
+        let pad = universe
+            .add_landing_pad(LandingPad::new("Pad", planet, 0.0, 40.0))
+            .unwrap();
+        let at = |offset: f64| rotate_f64(DVec2::X * radius, offset / radius);
+        assert_eq!(universe.site_domain_at(planet, at(0.0)), Some((pad, 0.0)));
+        let (_, offset) = universe.site_domain_at(planet, at(-120.0)).unwrap();
+        assert!((offset + 120.0).abs() < 1E-6);
+        assert!(universe
+            .site_domain_at(planet, at(SITE_DOMAIN_HALF_WIDTH * 1.5))
+            .is_none());
+
+        let t = Nanotime::zero();
+        assert!(TerrainDecal::for_impact(0.0, SCORCH_SPEED, t).is_none());
+        let scorch = TerrainDecal::for_impact(-120.0, 20.0, t).unwrap();
+        let crater = TerrainDecal::for_impact(300.0, 100.0, t).unwrap();
+        assert_eq!(scorch.kind, DecalKind::Scorch);
+        assert_eq!(crater.kind, DecalKind::Crater);
+        assert!(crater.radius > scorch.radius);
+
+        let mut terrain = SiteTerrain::new(pad);
+        terrain.add_decal(scorch);
+        terrain.add_decal(crater);
+        assert_eq!(SiteTerrain::chunk_of(-120.0), -3);
+        assert_eq!(terrain.decals().count(), 2);
+        assert_eq!(terrain.decals_between(-200.0, 0.0).count(), 1);
+
+        let wreck = EntityId(7);
+        let materials = [
+            (Item::Iron, Mass::kilograms(60)),
+            (Item::Copper, Mass::ZERO),
+        ];
+        terrain.scatter_salvage(wreck, 300.0, crater.radius, materials);
+        assert_eq!(terrain.salvage().len(), 1);
+        assert!((terrain.salvage()[0].offset - 300.0).abs() <= crater.radius);
+
+        // wrecks don't pick up their own salvage, and others must be close
+        assert!(terrain.take_salvage(wreck, 300.0).is_empty());
+        assert!(terrain.take_salvage(EntityId(8), 0.0).is_empty());
+        let taken = terrain.take_salvage(EntityId(8), 300.0);
+        assert_eq!(taken.len(), 1);
+        assert!(terrain.salvage().is_empty());
+
+        let yaml = serde_yaml::to_string(&vec![terrain.clone()]).unwrap();
+        let back: Vec<SiteTerrain> = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back, vec![terrain]);
+    }
+}
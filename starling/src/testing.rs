@@ -0,0 +1,42 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+
+/// A universe of the real-scale solar system, and a circular orbit 400 km
+/// above its central body to put vehicles on.
+pub(crate) fn universe_in_leo() -> (Universe, GlobalOrbit) {
+    let universe = Universe::new(crate::examples::rss());
+    let body = universe.planets.body;
+    let orbit = SparseOrbit::circular(body.radius + 400_000.0, body, universe.stamp(), false);
+    let orbit = GlobalOrbit(universe.planets.id, orbit);
+    (universe, orbit)
+}
+
+/// A one-tonne methane tank piped to a docking port, with its tank filled
+/// to the given fraction.
+pub(crate) fn test_tanker(fuel: f64) -> Vehicle {
+    let tank = PartPrototype::Tank(TankModel::new(
+        "tank".to_string(),
+        UVec2::new(10, 10),
+        Mass::kilograms(100),
+        Mass::kilograms(1000),
+    ));
+    let port = PartPrototype::DockingPort(DockingPort::new(
+        "docking-port".to_string(),
+        UVec2::new(4, 8),
+        Mass::kilograms(50),
+    ));
+    let pipes: HashSet<_> = (0..14).map(|x| IVec2::new(x, 5)).collect();
+    let mut vehicle = Vehicle::from_parts(
+        "Tanker".to_string(),
+        "TNK".to_string(),
+        vec![
+            (IVec2::ZERO, Rotation::East, tank),
+            (IVec2::new(10, 1), Rotation::East, port),
+        ],
+        pipes,
+    );
+    let mut loadout = Loadout::new("fuel", fuel);
+    loadout.fuel_item = Item::Methane;
+    vehicle.apply_loadout(&loadout);
+    vehicle
+}
@@ -3,6 +3,16 @@
 // use bevy::prelude::{Alpha, Mix, Srgba};
 use crate::prelude::*;
 
+/// Exhaust reaching the ground from further away than this, in meters,
+/// doesn't kick up any dust.
+pub const DUST_RANGE: f64 = 40.0;
+
+/// Thrust, in newtons, at which dust is kicked up at full strength.
+const DUST_REFERENCE_THRUST: f64 = 400_000.0;
+
+/// Surface gravity against which dust hangs in the air as long as usual.
+const DUST_REFERENCE_GRAVITY: f64 = 9.81;
+
 #[derive(Debug)]
 pub struct ThrustParticle {
     pub parent: EntityId,
@@ -41,6 +51,21 @@ impl ThrustParticle {
         }
     }
 
+    fn dust(parent: EntityId, pv: PV, lifetime: Nanotime) -> Self {
+        Self {
+            parent,
+            pv,
+            atmo: 1.0,
+            age: Nanotime::zero(),
+            lifetime,
+            initial_color: [0.62, 0.56, 0.47, 0.8],
+            final_color: [0.5, 0.47, 0.43, 0.5],
+            depth: rand(0.0, 1000.0),
+            angle: pv.vel.to_angle() as f32,
+            scale: 0.6,
+        }
+    }
+
     fn step(&mut self) {
         self.pv.pos += self.pv.vel * PHYSICS_CONSTANT_DELTA_TIME.to_secs_f64();
         self.pv.vel *= 1.0 - 0.02 * self.atmo as f64;
@@ -108,6 +133,66 @@ impl ThrustParticleEffects {
             }
         }
     }
+
+    /// Kicks up dust where the exhaust of a thruster strikes the surface of
+    /// `planet`, if the nozzle is close enough to the ground. More thrust
+    /// throws up more dust, and lower gravity lets it hang around longer.
+    pub fn add_dust(
+        &mut self,
+        parent: EntityId,
+        body: &RigidBody,
+        part: &InstantiatedPart,
+        planet: &Body,
+    ) {
+        let Some((t, d)) = part.as_thruster() else {
+            return;
+        };
+        if t.is_rcs || !part.is_built() || !d.is_thrusting(t) {
+            return;
+        }
+
+        let pos = body.pv.pos + rotate_f64(part.center_meters().as_dvec2(), body.angle);
+        let u = rotate_f64(rotate_f64(DVec2::X, part.rotation().to_angle()), body.angle);
+        let Some((hit, dist)) = exhaust_impingement(pos, -u, planet.radius) else {
+            return;
+        };
+
+        let strength =
+            (1.0 - dist / DUST_RANGE) * (t.current_thrust(d) / DUST_REFERENCE_THRUST).sqrt();
+        let gravity = planet.mu() / planet.radius.powi(2);
+        let hang = (DUST_REFERENCE_GRAVITY / gravity).sqrt().clamp(1.0, 3.0);
+        let n = (strength * 8.0).round().min(12.0) as u32;
+
+        let normal = hit.normalize_or_zero();
+        let tangent = normal.perp();
+        for _ in 0..n {
+            let side = if rand(0.0, 1.0) < 0.5 { -1.0 } else { 1.0 };
+            let speed = rand(10.0, 30.0) as f64 * strength * hang;
+            let lift = rand(1.0, 6.0) as f64 * hang;
+            let vel = tangent * side * speed + normal * lift;
+            let lifetime = Nanotime::secs_f64(rand(1.0, 2.5) as f64 * hang);
+            let pv = PV::from_f64(hit + normal, vel);
+            self.particles
+                .push(ThrustParticle::dust(parent, pv, lifetime));
+        }
+    }
+}
+
+/// Where a jet of exhaust leaving `pos` along `dir` strikes a body of
+/// `radius` centered on the origin, and how far it travels to get there,
+/// if that is within [DUST_RANGE].
+pub fn exhaust_impingement(pos: DVec2, dir: DVec2, radius: f64) -> Option<(DVec2, f64)> {
+    let dir = dir.normalize_or_zero();
+    let b = pos.dot(dir);
+    let c = pos.length_squared() - radius.powi(2);
+    let disc = b * b - c;
+    if disc < 0.0 {
+        return None;
+    }
+    let dist = -b - disc.sqrt();
+    (0.0..=DUST_RANGE)
+        .contains(&dist)
+        .then(|| (pos + dir * dist, dist))
 }
 
 pub fn add_particles_from_vehicle(
@@ -127,3 +212,34 @@ pub fn add_particles_from_vehicle(
         }
     }
 }
+
+pub fn add_dust_from_vehicle(
+    particles: &mut ThrustParticleEffects,
+    parent: EntityId,
+    vehicle: &Vehicle,
+    body: &RigidBody,
+    planet: &Body,
+) {
+    for (_, part) in vehicle.parts() {
+        particles.add_dust(parent, body, part, planet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exhaust_only_raises_dust_near_the_ground() {
+        let radius = 1000.0;
+        let down = DVec2::new(0.0, -1.0);
+
+        let (hit, dist) = exhaust_impingement(DVec2::new(0.0, 1020.0), down, radius).unwrap();
+        assert!((dist - 20.0).abs() < 1e-9);
+        assert!((hit - DVec2::new(0.0, 1000.0)).length() < 1e-9);
+
+        assert!(exhaust_impingement(DVec2::new(0.0, 1100.0), down, radius).is_none());
+        assert!(exhaust_impingement(DVec2::new(0.0, 1020.0), -down, radius).is_none());
+        assert!(exhaust_impingement(DVec2::new(0.0, 1020.0), DVec2::X, radius).is_none());
+    }
+}
@@ -0,0 +1,299 @@
+use crate::control::OrbitalController;
+use crate::id::EntityId;
+use crate::math::*;
+use crate::nanotime::Nanotime;
+use crate::orbits::{GlobalOrbit, SparseOrbit};
+use std::collections::HashMap;
+
+/// Range of hours between a background vehicle's transfers to new orbits.
+const TRANSFER_INTERVAL_HOURS: (f32, f32) = (6.0, 24.0);
+
+/// Range of ages, in days, at which background vehicles retire.
+const LIFETIME_DAYS: (f32, f32) = (3.0, 10.0);
+
+/// Disposal orbits are circular, at this multiple of the apoapsis of the
+/// orbit the vehicle retires from.
+const DISPOSAL_RADIUS_FACTOR: f64 = 1.3;
+
+/// Range of factors by which a transfer scales each apsis of the orbit.
+/// Kept clear of 1.0 so the new orbit isn't similar to the old one.
+const TRANSFER_SCALE: (f32, f32) = (1.1, 1.4);
+
+fn random_duration(hours: (f32, f32)) -> Nanotime {
+    Nanotime::secs_f64(rand(hours.0, hours.1) as f64 * 3600.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrafficTask {
+    StationKeeping,
+    Transfer,
+    Disposal,
+    Retired,
+}
+
+impl TrafficTask {
+    pub fn to_status_str(&self) -> &'static str {
+        match self {
+            TrafficTask::StationKeeping => "Station keeping",
+            TrafficTask::Transfer => "Transferring orbits",
+            TrafficTask::Disposal => "Moving to disposal orbit",
+            TrafficTask::Retired => "Retired",
+        }
+    }
+}
+
+/// Autonomous behavior of a single background vehicle. Holds the vehicle
+/// to its home orbit, moves it to a new home every so often, and once it
+/// is old enough sends it to a disposal orbit, where it stays.
+#[derive(Debug, Clone)]
+pub struct TrafficAgent {
+    born: Nanotime,
+    lifetime: Nanotime,
+    next_transfer: Nanotime,
+    home: Option<GlobalOrbit>,
+    task: Option<TrafficTask>,
+    controller: OrbitalController,
+    last_burn: Option<Nanotime>,
+}
+
+impl TrafficAgent {
+    pub fn new(stamp: Nanotime) -> Self {
+        Self {
+            born: stamp,
+            lifetime: random_duration((LIFETIME_DAYS.0 * 24.0, LIFETIME_DAYS.1 * 24.0)),
+            next_transfer: stamp + random_duration(TRANSFER_INTERVAL_HOURS),
+            home: None,
+            task: None,
+            controller: OrbitalController::idle(),
+            last_burn: None,
+        }
+    }
+
+    pub fn task(&self) -> Option<TrafficTask> {
+        self.task
+    }
+
+    pub fn home(&self) -> Option<&GlobalOrbit> {
+        self.home.as_ref()
+    }
+
+    pub fn age(&self, stamp: Nanotime) -> Nanotime {
+        stamp - self.born
+    }
+
+    pub fn controller(&self) -> &OrbitalController {
+        &self.controller
+    }
+
+    /// Time of the next burn of the current maneuver plan, if any.
+    pub fn next_burn(&self) -> Option<Nanotime> {
+        self.pending_burns().next().map(|(t, _)| t)
+    }
+
+    fn pending_burns(&self) -> impl Iterator<Item = (Nanotime, DVec2)> + use<'_> {
+        let last = self.last_burn;
+        self.controller
+            .plan()
+            .into_iter()
+            .flat_map(|p| p.dvs())
+            .filter(move |(t, _)| last.is_none_or(|l| *t > l))
+    }
+
+    /// Advances the agent given the vehicle's current orbit. Returns the
+    /// impulse the vehicle should apply now, if a burn is due.
+    pub fn update(&mut self, stamp: Nanotime, orbit: GlobalOrbit) -> Option<DVec2> {
+        if self.task == Some(TrafficTask::Retired) {
+            return None;
+        }
+
+        let home = *self.home.get_or_insert(orbit);
+
+        let due = self.pending_burns().next().filter(|(t, _)| *t <= stamp);
+        if let Some((t, dv)) = due {
+            self.last_burn = Some(t);
+            return Some(dv);
+        }
+
+        // late burns, such as those skipped over at high time warp, leave
+        // the vehicle off the plan, and the controller replans from there
+        if self.controller.needs_update(stamp) {
+            if self.controller.update(stamp, orbit).is_err() {
+                self.controller.clear();
+            }
+            if self.controller.is_idle() {
+                self.next_task(stamp, orbit, home);
+            }
+        }
+
+        None
+    }
+
+    fn next_task(&mut self, stamp: Nanotime, orbit: GlobalOrbit, home: GlobalOrbit) {
+        if self.task == Some(TrafficTask::Disposal) {
+            self.task = Some(TrafficTask::Retired);
+            return;
+        }
+
+        let (task, destination) = if self.age(stamp) > self.lifetime {
+            (TrafficTask::Disposal, disposal_orbit(&home, stamp))
+        } else if stamp > self.next_transfer {
+            self.next_transfer = stamp + random_duration(TRANSFER_INTERVAL_HOURS);
+            (TrafficTask::Transfer, transfer_orbit(&orbit, stamp))
+        } else if !orbit.1.is_similar(&home.1) {
+            (TrafficTask::StationKeeping, Some(home))
+        } else {
+            self.task = None;
+            return;
+        };
+
+        let planned = destination.map(|d| (d, self.controller.set_destination(d, stamp)));
+        match planned {
+            Some((d, Ok(()))) => {
+                self.task = Some(task);
+                self.last_burn = None;
+                if task == TrafficTask::Transfer {
+                    self.home = Some(d);
+                }
+            }
+            _ => {
+                // most likely the vehicle has left its home body; it
+                // settles into whatever orbit it has now
+                self.controller.clear();
+                self.task = None;
+                self.home = Some(orbit);
+            }
+        }
+    }
+}
+
+/// Random orbit about the same body with each apsis moved by a random
+/// factor, staying well clear of the surface and the edge of the SOI.
+fn transfer_orbit(current: &GlobalOrbit, stamp: Nanotime) -> Option<GlobalOrbit> {
+    let GlobalOrbit(parent, orbit) = current;
+    let body = orbit.body;
+    let (lo, hi) = (body.radius * 1.5, body.soi * 0.8);
+    if lo >= hi {
+        return None;
+    }
+    let scale = |r: f64| {
+        let f = rand(TRANSFER_SCALE.0, TRANSFER_SCALE.1) as f64;
+        let r = if randint(0, 2) == 0 { r * f } else { r / f };
+        r.clamp(lo, hi)
+    };
+    let r1 = scale(orbit.periapsis_r());
+    let r2 = scale(orbit.apoapsis_r());
+    let argp = rand(0.0, 2.0 * PI) as f64;
+    let orbit = SparseOrbit::new(
+        r1.max(r2),
+        r1.min(r2),
+        argp,
+        body,
+        stamp,
+        orbit.is_retrograde(),
+    )?;
+    Some(GlobalOrbit(*parent, orbit))
+}
+
+fn disposal_orbit(home: &GlobalOrbit, stamp: Nanotime) -> Option<GlobalOrbit> {
+    let GlobalOrbit(parent, orbit) = home;
+    let radius = (orbit.apoapsis_r() * DISPOSAL_RADIUS_FACTOR).min(orbit.body.soi * 0.9);
+    let orbit = SparseOrbit::circular(radius, orbit.body, stamp, orbit.is_retrograde());
+    Some(GlobalOrbit(*parent, orbit))
+}
+
+/// Background vehicles under autonomous control, keyed by vehicle. Player
+/// vehicles are never enlisted, and vehicles the player takes control of
+/// are released for good.
+#[derive(Debug, Clone, Default)]
+pub struct BackgroundTraffic {
+    agents: HashMap<EntityId, TrafficAgent>,
+}
+
+impl BackgroundTraffic {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enlist(&mut self, id: EntityId, stamp: Nanotime) {
+        self.agents.insert(id, TrafficAgent::new(stamp));
+    }
+
+    pub fn release(&mut self, id: EntityId) -> Option<TrafficAgent> {
+        self.agents.remove(&id)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&TrafficAgent> {
+        self.agents.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.agents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+
+    /// Earliest burn any agent has planned.
+    pub fn next_burn(&self) -> Option<Nanotime> {
+        self.agents.values().filter_map(|a| a.next_burn()).min()
+    }
+
+    pub(crate) fn agents_mut(&mut self) -> impl Iterator<Item = (&EntityId, &mut TrafficAgent)> {
+        self.agents.iter_mut()
+    }
+
+    pub(crate) fn retain(&mut self, f: impl Fn(EntityId) -> bool) {
+        self.agents.retain(|id, _| f(*id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbits::Body;
+
+    fn circular(radius: f64, stamp: Nanotime) -> GlobalOrbit {
+        GlobalOrbit(
+            EntityId(0),
+            SparseOrbit::circular(radius, Body::LUNA, stamp, false),
+        )
+    }
+
+    #[test]
+    fn agents_station_keep_and_retire() {
+        let t0 = Nanotime::zero();
+        let mut agent = TrafficAgent::new(t0);
+
+        assert_eq!(agent.update(t0, circular(5_000_000.0, t0)), None);
+        assert_eq!(agent.task(), None);
+
+        // drifted well off the home orbit
+        let t1 = t0 + Nanotime::secs(2);
+        assert_eq!(agent.update(t1, circular(6_000_000.0, t1)), None);
+        assert_eq!(agent.task(), Some(TrafficTask::StationKeeping));
+        assert_eq!(agent.controller().destination(), agent.home());
+
+        let burns: Vec<_> = agent.controller().plan().unwrap().dvs().collect();
+        assert!(!burns.is_empty());
+        for (t, dv) in burns {
+            assert!(t >= t1);
+            assert_eq!(agent.update(t, circular(6_000_000.0, t1)), Some(dv));
+        }
+        assert_eq!(agent.next_burn(), None);
+
+        // long past any lifetime, back home, and so free to retire
+        let old = t0 + Nanotime::days(30);
+        assert_eq!(agent.update(old, circular(5_000_000.0, old)), None);
+        assert_eq!(agent.task(), Some(TrafficTask::Disposal));
+        let disposal = agent.controller().destination().unwrap().1;
+        assert!(disposal.periapsis_r() > 5_000_000.0);
+
+        let later = old + Nanotime::secs(2);
+        assert_eq!(
+            agent.update(later, GlobalOrbit(EntityId(0), disposal)),
+            None
+        );
+        assert_eq!(agent.task(), Some(TrafficTask::Retired));
+    }
+}
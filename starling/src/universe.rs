@@ -1,16 +1,63 @@
+use crate::checkpoint::share;
 use crate::control_signals::ControlSignals;
 use crate::prelude::*;
 use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
 pub struct Universe {
-    stamp: Nanotime,
-    ticks: u128,
-    next_entity_id: EntityId,
+    pub(crate) stamp: Nanotime,
+    pub(crate) ticks: u128,
+    pub(crate) ids: IdAllocator,
+    /// What the next ID would have been before IDs had kinds, to work out
+    /// the old IDs of planets and sites referred to by older saves.
+    legacy_next: i64,
+    legacy_ids: IdRemap,
     pub surface_vehicles: HashMap<EntityId, SurfaceSpacecraftEntity>,
     pub planets: PlanetarySystem,
     pub constellations: HashMap<EntityId, EntityId>,
     pub thrust_particles: ThrustParticleEffects,
+    pub landing_pads: HashMap<EntityId, LandingPad>,
+    /// Entity events, for consumers like notifications, statistics and
+    /// scripting to subscribe to.
+    pub events: EventBus,
+    pub recycle_bin: RecycleBin,
+    /// Vehicles which couldn't be given a sound orbit on load.
+    pub quarantine: Quarantine,
+    pub control_laws: ControlLawRegistry,
+    /// Vehicles with reserved IDs which have yet to be constructed.
+    pub loading: LoadQueue,
+    /// Docking traffic control, keyed by the vehicle being docked with.
+    pub docking: HashMap<EntityId, DockingQueue>,
+    /// Vehicles held by the capture assist of their target's docking port
+    /// as of the last tick, and the target holding each.
+    pub captures: HashMap<EntityId, EntityId>,
+    /// Vehicles joined onto others at their docking ports, by the vehicle
+    /// they're joined onto, in the order they docked.
+    pub docked: HashMap<EntityId, Vec<(EntityId, DockingJoint)>>,
+    pub structures: HashMap<EntityId, Structure>,
+    /// Impact marks and salvage around each landing site, by pad.
+    pub terrain: HashMap<EntityId, SiteTerrain>,
+    /// Whether vehicles which crash near a landing site leave salvage.
+    pub scatter_salvage: bool,
+    /// Named markers placed by the player.
+    pub waypoints: HashMap<EntityId, Waypoint>,
+    /// Autonomous behavior of background vehicles.
+    pub traffic: BackgroundTraffic,
+    /// Maneuver plans scheduled for vehicles, such as plans shared across
+    /// a constellation.
+    pub maneuvers: ScheduledManeuvers,
+    /// Rendezvous autopilots, keyed by the vehicle flying to meet its
+    /// target. Burns are flown through the scheduled maneuvers.
+    pub rendezvous: HashMap<EntityId, OrbitalController>,
+    /// Items being passed between vehicles alongside one another.
+    pub transfers: Vec<ItemTransfer>,
+    /// Timed events of the scenario being played.
+    pub script: ScenarioScript,
+    /// Orbits each vehicle has followed. Not part of checkpoints, but
+    /// trimmed back when one is restored.
+    pub orbit_history: OrbitArchive,
+    /// How predictions for many vehicles at once are evaluated.
+    pub propagation: PropagationBackend,
 }
 
 impl Universe {
@@ -20,14 +67,41 @@ impl Universe {
     }
 
     pub fn new(planets: PlanetarySystem) -> Self {
+        // planets were numbered from 900 in the order the system lists them
+        let mut legacy_ids = IdRemap::default();
+        for (i, id) in planets.planet_ids().into_iter().enumerate() {
+            legacy_ids.insert(EntityId(900 + i as i64), id);
+        }
         Self {
             stamp: Nanotime::zero(),
             ticks: 0,
-            next_entity_id: EntityId(1002),
+            ids: IdAllocator::default(),
+            legacy_next: 1002,
+            legacy_ids,
             surface_vehicles: HashMap::new(),
             planets,
             constellations: HashMap::new(),
             thrust_particles: ThrustParticleEffects::new(),
+            landing_pads: HashMap::new(),
+            events: EventBus::new(),
+            recycle_bin: RecycleBin::new(Nanotime::mins(2)),
+            quarantine: Quarantine::default(),
+            control_laws: ControlLawRegistry::with_defaults(),
+            loading: LoadQueue::new(),
+            docking: HashMap::new(),
+            captures: HashMap::new(),
+            docked: HashMap::new(),
+            structures: HashMap::new(),
+            terrain: HashMap::new(),
+            scatter_salvage: true,
+            waypoints: HashMap::new(),
+            traffic: BackgroundTraffic::new(),
+            maneuvers: ScheduledManeuvers::new(),
+            rendezvous: HashMap::new(),
+            transfers: Vec::new(),
+            script: ScenarioScript::default(),
+            orbit_history: OrbitArchive::new(),
+            propagation: PropagationBackend::default(),
         }
     }
 
@@ -39,14 +113,338 @@ impl Universe {
         self.ticks
     }
 
-    fn next_entity_id(&mut self) -> EntityId {
-        let ret = self.next_entity_id;
-        self.next_entity_id.0 += 1;
-        ret
+    fn next_entity_id(&mut self, kind: EntityKind) -> EntityId {
+        self.legacy_next += 1;
+        self.ids.next(kind)
+    }
+
+    /// Translation from IDs written by older versions, which shared one
+    /// counter between every kind of entity, to the current ones.
+    pub fn legacy_ids(&self) -> &IdRemap {
+        &self.legacy_ids
+    }
+
+    pub fn checkpoint(&self) -> UniverseCheckpoint {
+        self.checkpoint_since(None)
+    }
+
+    /// Captures a checkpoint which shares whatever is unchanged since
+    /// `previous` with it.
+    pub fn checkpoint_since(&self, previous: Option<&UniverseCheckpoint>) -> UniverseCheckpoint {
+        let surface_vehicles = self
+            .surface_vehicles
+            .iter()
+            .map(|(id, sv)| {
+                let prev = previous.and_then(|p| p.surface_vehicles.get(id));
+                (*id, share(sv, prev))
+            })
+            .collect();
+        UniverseCheckpoint {
+            stamp: self.stamp,
+            ticks: self.ticks,
+            ids: self.ids.clone(),
+            legacy_next: self.legacy_next,
+            legacy_ids: self.legacy_ids.clone(),
+            surface_vehicles,
+            constellations: self.constellations.clone(),
+            landing_pads: self.landing_pads.clone(),
+            structures: share(&self.structures, previous.map(|p| &p.structures)),
+            terrain: share(&self.terrain, previous.map(|p| &p.terrain)),
+            waypoints: share(&self.waypoints, previous.map(|p| &p.waypoints)),
+            traffic: self.traffic.clone(),
+            maneuvers: self.maneuvers.clone(),
+            rendezvous: self.rendezvous.clone(),
+            docked: self.docked.clone(),
+            docking: self.docking.clone(),
+            captures: self.captures.clone(),
+            transfers: self.transfers.clone(),
+            script: self.script.clone(),
+            loading: self.loading.clone(),
+            recycle_bin: share(&self.recycle_bin, previous.map(|p| &p.recycle_bin)),
+            quarantine: self.quarantine.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, checkpoint: &UniverseCheckpoint) {
+        self.stamp = checkpoint.stamp;
+        self.ticks = checkpoint.ticks;
+        self.ids = checkpoint.ids.clone();
+        self.legacy_next = checkpoint.legacy_next;
+        self.legacy_ids = checkpoint.legacy_ids.clone();
+        self.surface_vehicles = checkpoint
+            .surface_vehicles
+            .iter()
+            .map(|(id, sv)| (*id, SurfaceSpacecraftEntity::clone(sv)))
+            .collect();
+        self.constellations = checkpoint.constellations.clone();
+        self.landing_pads = checkpoint.landing_pads.clone();
+        self.structures = HashMap::clone(&checkpoint.structures);
+        self.terrain = HashMap::clone(&checkpoint.terrain);
+        self.waypoints = HashMap::clone(&checkpoint.waypoints);
+        self.traffic = checkpoint.traffic.clone();
+        self.maneuvers = checkpoint.maneuvers.clone();
+        self.rendezvous = checkpoint.rendezvous.clone();
+        self.docked = checkpoint.docked.clone();
+        self.docking = checkpoint.docking.clone();
+        self.captures = checkpoint.captures.clone();
+        self.transfers = checkpoint.transfers.clone();
+        // vehicles spawned by the script since are dropped with the ids
+        // reserved for them
+        self.script = checkpoint.script.clone();
+        self.loading = checkpoint.loading.clone();
+        // retention is a setting, not state, so it's left as it is
+        let retention = self.recycle_bin.retention;
+        self.recycle_bin = RecycleBin::clone(&checkpoint.recycle_bin);
+        self.recycle_bin.retention = retention;
+        self.quarantine = checkpoint.quarantine.clone();
+        self.orbit_history.truncate_after(checkpoint.stamp);
+        self.thrust_particles.particles.clear();
+    }
+
+    /// Snapshot of every vehicle, to stream to observers. `sent` holds the
+    /// designs an observer already has, by discriminator, and is updated
+    /// to include those in this snapshot.
+    pub fn snapshot(&self, sent: &mut HashMap<EntityId, u64>) -> UniverseSnapshot {
+        sent.retain(|id, _| self.surface_vehicles.contains_key(id));
+        let entities = self
+            .surface_vehicles
+            .iter()
+            .map(|(id, sv)| {
+                let vehicle = sv.vehicle();
+                let known = sent.insert(*id, vehicle.discriminator());
+                let design = (known != Some(vehicle.discriminator()))
+                    .then(|| VehicleFileStorage::from_vehicle(vehicle));
+                EntitySnapshot {
+                    id: *id,
+                    parent: sv.parent(),
+                    callsign: vehicle.name().to_string(),
+                    pv: sv.body.pv,
+                    angle: sv.body.angle,
+                    angular_velocity: sv.body.angular_velocity,
+                    orbit: sv.orbit,
+                    target: sv.target(),
+                    design,
+                }
+            })
+            .collect();
+        UniverseSnapshot {
+            protocol: SNAPSHOT_PROTOCOL,
+            stamp: self.stamp,
+            ticks: self.ticks,
+            entities,
+        }
+    }
+
+    /// Brings an observer's universe in line with a snapshot from its
+    /// host, building vehicles out of `parts`. Vehicles the host no longer
+    /// has are removed. Returns the vehicles which couldn't be built.
+    pub fn apply_snapshot(
+        &mut self,
+        snapshot: &UniverseSnapshot,
+        parts: &HashMap<String, PartPrototype>,
+    ) -> Vec<EntityId> {
+        self.stamp = snapshot.stamp;
+        self.ticks = snapshot.ticks;
+
+        let ids: HashSet<EntityId> = snapshot.entities.iter().map(|e| e.id).collect();
+        let gone: Vec<EntityId> = self
+            .surface_vehicles
+            .keys()
+            .filter(|id| !ids.contains(id))
+            .copied()
+            .collect();
+        for id in gone {
+            self.remove(id);
+        }
+
+        let mut failed = Vec::new();
+        for e in &snapshot.entities {
+            let vehicle = e
+                .design
+                .as_ref()
+                .and_then(|d| d.build(e.callsign.clone(), parts).ok());
+            match (self.surface_vehicles.get_mut(&e.id), vehicle) {
+                (Some(sv), Some(vehicle)) => sv.overwrite_vehicle(vehicle),
+                (Some(_), None) => (),
+                (None, Some(vehicle)) => {
+                    let sv = SurfaceSpacecraftEntity::new(
+                        e.parent,
+                        vehicle,
+                        RigidBody::ZERO,
+                        VehicleController::idle(),
+                    );
+                    self.surface_vehicles.insert(e.id, sv);
+                    self.events.emit(self.stamp, UniverseEvent::Spawned(e.id));
+                }
+                (None, None) => {
+                    failed.push(e.id);
+                    continue;
+                }
+            }
+            let Some(sv) = self.surface_vehicles.get_mut(&e.id) else {
+                continue;
+            };
+            sv.planet_id = e.parent;
+            sv.set_name(e.callsign.clone());
+            sv.body = RigidBody {
+                pv: e.pv,
+                angle: e.angle,
+                angular_velocity: e.angular_velocity,
+            };
+            sv.orbit = e.orbit;
+            sv.set_target(e.target);
+        }
+        failed
+    }
+
+    /// Destroys a vehicle, along with any vehicles docked to it. Returns
+    /// the design of each vehicle destroyed, the host's first, with the
+    /// guests' parts split back off of the host.
+    pub fn remove(&mut self, id: EntityId) -> Vec<(EntityId, Vehicle)> {
+        let Some(mut sv) = self.surface_vehicles.remove(&id) else {
+            return Vec::new();
+        };
+        let joints = self.docked.remove(&id).unwrap_or_default();
+        let guests: Vec<_> = joints
+            .iter()
+            .rev()
+            .map(|(guest, joint)| (*guest, sv.vehicle.undock(joint)))
+            .collect();
+        let mut destroyed = vec![(id, sv.vehicle)];
+        destroyed.extend(guests.into_iter().rev());
+        for (id, _) in &destroyed {
+            self.constellations.remove(id);
+            self.traffic.release(*id);
+            self.maneuvers.cancel(*id);
+            self.rendezvous.remove(id);
+            self.cancel_docking(*id);
+            self.docking.remove(id);
+            self.transfers
+                .retain(|t| t.source() != *id && t.destination() != *id);
+            self.events.emit(self.stamp, UniverseEvent::Destroyed(*id));
+        }
+        destroyed
+    }
+
+    /// Moves a vehicle into the recycle bin, from which it can be restored
+    /// with `undelete` until the bin's retention period elapses.
+    pub fn soft_delete(&mut self, id: EntityId) -> Option<()> {
+        let vehicle = self.surface_vehicles.remove(&id)?;
+        let group = self.constellations.remove(&id);
+        let docked = self.docked.remove(&id).unwrap_or_default();
+        self.recycle_bin.insert(DeletedEntity {
+            id,
+            deleted_at: self.stamp,
+            vehicle,
+            group,
+            docked,
+        });
+        self.events.emit(self.stamp, UniverseEvent::Destroyed(id));
+        Some(())
+    }
+
+    /// Re-derives every vehicle's reference orbit from its position and
+    /// velocity, as after loading state written under other physics
+    /// constants. Vehicles which can't be given a sound orbit are moved
+    /// to quarantine, rather than left to fail later.
+    pub fn normalize_orbits(&mut self, tolerance: NormalizationTolerance) -> NormalizationReport {
+        let mut report = NormalizationReport::default();
+        let mut ids: Vec<EntityId> = self.surface_vehicles.keys().copied().collect();
+        ids.sort();
+        for id in ids {
+            let Some(parent) = self.surface_vehicles.get(&id).map(|sv| sv.parent()) else {
+                continue;
+            };
+            let body = self.planets.lookup(parent, self.stamp).map(|(b, ..)| b);
+            let Some(sv) = self.surface_vehicles.get_mut(&id) else {
+                continue;
+            };
+            report.checked += 1;
+            match rectify_orbit(sv, body, self.stamp, tolerance) {
+                Ok(Some(change)) => report.rectified.push((id, change)),
+                Ok(None) => (),
+                Err(reason) => {
+                    self.quarantine_vehicle(id, reason);
+                    report.quarantined.push((id, reason));
+                }
+            }
+        }
+        report
+    }
+
+    /// Takes a vehicle out of the simulation, along with any orders it
+    /// had, and keeps it in quarantine.
+    fn quarantine_vehicle(&mut self, id: EntityId, reason: QuarantineReason) -> Option<()> {
+        let vehicle = self.surface_vehicles.remove(&id)?;
+        let group = self.constellations.remove(&id);
+        self.traffic.release(id);
+        self.maneuvers.cancel(id);
+        self.rendezvous.remove(&id);
+        self.cancel_docking(id);
+        let docked = self.docked.remove(&id).unwrap_or_default();
+        self.quarantine.insert(QuarantinedEntity {
+            id,
+            reason,
+            quarantined_at: self.stamp,
+            vehicle,
+            group,
+            docked,
+        });
+        self.events
+            .emit(self.stamp, UniverseEvent::Quarantined(id, reason));
+        Some(())
     }
 
-    pub fn remove(&mut self, id: EntityId) {
-        self.surface_vehicles.remove(&id);
+    /// Moves a quarantined vehicle into the recycle bin, after which it's
+    /// discarded as any deleted vehicle would be.
+    pub fn discard_quarantined(&mut self, id: EntityId) -> Option<()> {
+        let entry = self.quarantine.take(id)?;
+        self.recycle_bin.insert(DeletedEntity {
+            id,
+            deleted_at: self.stamp,
+            vehicle: entry.vehicle,
+            group: entry.group,
+            docked: entry.docked,
+        });
+        Some(())
+    }
+
+    /// Restores a vehicle from the recycle bin, in the state it was in
+    /// when it was deleted.
+    pub fn undelete(&mut self, id: EntityId) -> Option<()> {
+        let mut entity = self.recycle_bin.take(id)?;
+        // another vehicle may have taken its callsign in the meantime
+        let callsign = self.callsign_for(entity.vehicle.vehicle().name(), Some(id));
+        entity.vehicle.set_name(callsign);
+        self.surface_vehicles.insert(id, entity.vehicle);
+        if let Some(gid) = entity.group {
+            self.constellations.insert(id, gid);
+        }
+        if !entity.docked.is_empty() {
+            self.docked.insert(id, entity.docked);
+        }
+        self.events.emit(self.stamp, UniverseEvent::Restored(id));
+        Some(())
+    }
+
+    /// A callsign for a vehicle wanting to be called `name` which isn't
+    /// already in use by any vehicle other than `id`.
+    pub fn callsign_for(&self, name: &str, id: Option<EntityId>) -> String {
+        let taken = self
+            .surface_vehicles
+            .iter()
+            .filter(|(other, _)| Some(**other) != id)
+            .map(|(_, sv)| sv.vehicle().name());
+        unique_callsign(name, taken)
+    }
+
+    /// Renames a vehicle, returning the callsign it ends up with, which
+    /// carries a designation number if the name was already in use.
+    pub fn rename_vehicle(&mut self, id: EntityId, name: &str) -> Option<String> {
+        let callsign = self.callsign_for(name, Some(id));
+        let sv = self.surface_vehicles.get_mut(&id)?;
+        sv.set_name(callsign.clone());
+        Some(callsign)
     }
 
     pub fn on_sim_ticks(
@@ -59,10 +457,16 @@ impl Universe {
         let mut actual_ticks = 0;
         let mut exec_time = Duration::ZERO;
 
-        let batch_mode = if self.can_run_batch_mode() && signals.is_empty() {
-            self.run_batch_ticks(ticks);
+        let batch_ticks = if self.can_run_batch_mode() && signals.is_empty() {
+            self.batch_ticks_before_impact(ticks)
+        } else {
+            0
+        };
+
+        let batch_mode = if batch_ticks > 0 {
+            self.run_batch_ticks(batch_ticks);
             exec_time = std::time::Instant::now() - start;
-            actual_ticks = ticks;
+            actual_ticks = batch_ticks;
             true
         } else {
             for _ in 0..ticks {
@@ -83,6 +487,37 @@ impl Universe {
         self.surface_vehicles
             .iter()
             .all(|(_, sv)| sv.can_be_on_rails())
+            && self.landing_pads.values().all(|p| !p.is_busy())
+            && self.docking.is_empty()
+    }
+
+    /// How many of the given ticks can be run in batch mode before any
+    /// vehicle's predicted trajectory hits a body, so that impacts are
+    /// left to per-tick physics. Extends predictions through the batch.
+    fn batch_ticks_before_impact(&mut self, ticks: u32) -> u32 {
+        let now = self.stamp;
+        let end = now + PHYSICS_CONSTANT_DELTA_TIME * ticks;
+        let impact = self
+            .surface_vehicles
+            .values_mut()
+            .filter_map(|sv| sv.impact_before(now, end, &self.planets))
+            .min();
+        // background traffic and scheduled burns are applied at tick
+        // boundaries, so batches stop short of the next one
+        let burn = self
+            .traffic
+            .next_burn()
+            .into_iter()
+            .chain(self.maneuvers.next_burn())
+            .chain(self.maneuvers.next_hold(now))
+            .chain(self.script.next_event())
+            .filter(|t| *t < end)
+            .min();
+        let impact = impact.into_iter().chain(burn).min();
+        match impact {
+            Some(t) => ((t - now).inner() / PHYSICS_CONSTANT_DELTA_TIME.inner()) as u32,
+            None => ticks,
+        }
     }
 
     fn step_surface_vehicles(&mut self, signals: &ControlSignals) {
@@ -94,10 +529,11 @@ impl Universe {
                 .get(&id)
                 .unwrap_or(&VehicleControl::NULLOPT);
 
-            sv.step(&self.planets, stamp, ext);
+            sv.step(&self.planets, &self.control_laws, stamp, ext);
 
-            let atmo = match self.planets.lookup(sv.parent(), stamp) {
-                Some((body, _, _, _)) => {
+            let planet = self.planets.lookup(sv.parent(), stamp).map(|(b, ..)| b);
+            let atmo = match planet {
+                Some(body) => {
                     let altitude = sv.body.pv.pos.length() - body.radius;
                     (1.0 - altitude / 200_000.0).clamp(0.0, 1.0)
                 }
@@ -111,6 +547,16 @@ impl Universe {
                 &sv.body,
                 atmo as f32,
             );
+
+            if let Some(planet) = planet {
+                add_dust_from_vehicle(
+                    &mut self.thrust_particles,
+                    sv.planet_id,
+                    &sv.vehicle,
+                    &sv.body,
+                    &planet,
+                );
+            }
         }
     }
 
@@ -132,15 +578,36 @@ impl Universe {
         }
     }
 
+    fn parents(&self) -> HashMap<EntityId, EntityId> {
+        self.surface_vehicles
+            .iter()
+            .map(|(id, sv)| (*id, sv.parent()))
+            .collect()
+    }
+
+    fn emit_soi_changes(&mut self, parents: HashMap<EntityId, EntityId>) {
+        for (id, from) in parents {
+            let Some(to) = self.surface_vehicles.get(&id).map(|sv| sv.parent()) else {
+                continue;
+            };
+            if to != from {
+                self.events
+                    .emit(self.stamp, UniverseEvent::SoiChange { id, from, to });
+            }
+        }
+    }
+
     pub fn run_batch_ticks(&mut self, ticks: u32) {
         self.ticks += ticks as u128;
         let old_stamp = self.stamp;
         let delta_time = PHYSICS_CONSTANT_DELTA_TIME * ticks;
         self.stamp = old_stamp + delta_time;
 
+        let parents = self.parents();
         for (_, sv) in &mut self.surface_vehicles {
             sv.step_on_rails(delta_time, self.stamp, &self.planets);
         }
+        self.emit_soi_changes(parents);
 
         if ticks == 1 {
             self.thrust_particles.step();
@@ -148,6 +615,14 @@ impl Universe {
             self.thrust_particles.particles.clear();
         }
 
+        self.captures.clear();
+        self.recover_salvage();
+        self.update_background_traffic();
+        self.update_scheduled_maneuvers();
+        self.update_rendezvous();
+        self.update_transfers(delta_time);
+        self.run_script();
+        self.update_orbit_history();
         self.update_vehicle_relative_info();
     }
 
@@ -157,14 +632,982 @@ impl Universe {
 
         self.thrust_particles.step();
 
-        self.step_surface_vehicles(signals);
+        let airborne: HashMap<EntityId, RigidBody> = self
+            .surface_vehicles
+            .iter()
+            .filter(|(_, sv)| !sv.is_landed())
+            .map(|(id, sv)| (*id, sv.body))
+            .collect();
+
+        let mut signals = signals.clone();
+        for (id, ctrl) in self.docking_commands() {
+            let cmd = signals.piloting_commands.entry(id).or_insert(ctrl);
+            if cmd.is_nullopt() {
+                *cmd = ctrl;
+            }
+        }
+
+        let parents = self.parents();
+        self.step_surface_vehicles(&signals);
+        self.emit_soi_changes(parents);
+        self.apply_capture_assist();
+        self.update_docking();
+        self.latch_docking_ports();
+
+        self.score_touchdowns(&airborne);
+        self.recover_salvage();
+        self.damage_structures_hit(&airborne);
+        self.update_service_drones();
+        self.update_background_traffic();
+        self.update_scheduled_maneuvers();
+        self.update_rendezvous();
+        self.update_transfers(PHYSICS_CONSTANT_DELTA_TIME);
+        self.run_script();
+        self.update_orbit_history();
 
         self.constellations
             .retain(|id, _| self.surface_vehicles.contains_key(id));
+        self.docked
+            .retain(|id, joints| self.surface_vehicles.contains_key(id) && !joints.is_empty());
 
         self.update_vehicle_relative_info();
     }
 
+    fn score_touchdowns(&mut self, airborne: &HashMap<EntityId, RigidBody>) {
+        for (id, body) in airborne {
+            let sv = match self.surface_vehicles.get(id) {
+                Some(sv) if sv.is_landed() => sv,
+                _ => continue,
+            };
+            let radius = match self.planets.lookup(sv.parent(), self.stamp) {
+                Some((b, _, _, _)) => b.radius,
+                None => continue,
+            };
+            let pad = self.pad_at(sv.parent(), body.pv.pos);
+            let occupied = pad
+                .map(|p| self.pad_occupant(p, Some(*id)).is_some())
+                .unwrap_or(false);
+            let pad = pad.and_then(|p| Some((p, self.landing_pads.get(&p)?)));
+            let score = TouchdownScore::new(pad, body, radius, occupied);
+            let accepted = score.accepted_pad();
+            if let Some(pad) = accepted.and_then(|p| self.landing_pads.get_mut(&p)) {
+                if score.points() > 0 {
+                    pad.record_delivery(score.points());
+                }
+                pad.queue_task(ServiceTask::Refuel(*id));
+            }
+            if let Some(pad) = accepted {
+                self.grow_site_structures(pad);
+            }
+            self.events
+                .emit(self.stamp, UniverseEvent::Landed(*id, score));
+            self.mark_terrain(*id, score.speed);
+        }
+    }
+
+    /// Marks the ground where a vehicle hit it near a landing site, and
+    /// strews salvage from the wreck if the impact was hard enough.
+    fn mark_terrain(&mut self, id: EntityId, speed: f64) -> Option<()> {
+        let sv = self.surface_vehicles.get(&id)?;
+        let (pad, offset) = self.site_domain_at(sv.parent(), sv.body.pv.pos)?;
+        let decal = TerrainDecal::for_impact(offset, speed, self.stamp)?;
+        let salvage = self.scatter_salvage.then(|| wreck_salvage(sv.vehicle()));
+        let terrain = self
+            .terrain
+            .entry(pad)
+            .or_insert_with(|| SiteTerrain::new(pad));
+        terrain.add_decal(decal);
+        if let Some(salvage) = salvage {
+            terrain.scatter_salvage(id, offset, decal.radius, salvage);
+        }
+        Some(())
+    }
+
+    /// Landed vehicles pick up any salvage lying near them, as far as
+    /// their cargo holds allow.
+    fn recover_salvage(&mut self) {
+        if self.terrain.values().all(|t| t.salvage().is_empty()) {
+            return;
+        }
+        let pickers: Vec<_> = self
+            .surface_vehicles
+            .iter()
+            .filter(|(_, sv)| sv.is_landed())
+            .filter_map(|(id, sv)| Some((*id, self.site_domain_at(sv.parent(), sv.body.pv.pos)?)))
+            .collect();
+        for (id, (pad, offset)) in pickers {
+            let (Some(terrain), Some(sv)) = (
+                self.terrain.get_mut(&pad),
+                self.surface_vehicles.get_mut(&id),
+            ) else {
+                continue;
+            };
+            for mut salvage in terrain.take_salvage(id, offset) {
+                let loaded = sv.vehicle.load_cargo(salvage.item, salvage.mass);
+                if loaded > Mass::ZERO {
+                    self.events.emit(
+                        self.stamp,
+                        UniverseEvent::SalvageRecovered(id, salvage.item, loaded),
+                    );
+                }
+                if loaded < salvage.mass {
+                    salvage.mass -= loaded;
+                    terrain.put_back(salvage);
+                }
+            }
+        }
+    }
+
+    /// Damages structures which airborne vehicles ran into this tick, in
+    /// proportion to the speed of the impact.
+    fn damage_structures_hit(&mut self, airborne: &HashMap<EntityId, RigidBody>) {
+        let mut hits = Vec::new();
+        for (id, before) in airborne {
+            let Some(sv) = self.surface_vehicles.get(id) else {
+                continue;
+            };
+            let Some((body, _, _, _)) = self.planets.lookup(sv.parent(), self.stamp) else {
+                continue;
+            };
+            let pos = sv.body.pv.pos;
+            for (sid, s) in &self.structures {
+                if s.planet_id == sv.parent()
+                    && s.contains(pos, body.radius)
+                    && !s.contains(before.pv.pos, body.radius)
+                {
+                    hits.push((*sid, before.pv.vel.length() * IMPACT_DAMAGE_PER_MPS));
+                }
+            }
+        }
+        for (id, damage) in hits {
+            self.damage_structure(id, damage);
+        }
+    }
+
+    pub fn add_structure(&mut self, structure: Structure) -> Option<EntityId> {
+        self.insert_structure(None, structure)
+    }
+
+    /// Adds a structure under the given ID if it's free, as when loading
+    /// one which was saved with its ID, or under a new one if not.
+    fn insert_structure(
+        &mut self,
+        id: Option<EntityId>,
+        mut structure: Structure,
+    ) -> Option<EntityId> {
+        self.legacy_ids.apply(&mut structure.planet_id);
+        if let Some(site) = &mut structure.site {
+            self.legacy_ids.apply(site);
+        }
+        self.planets.lookup(structure.planet_id, self.stamp)?;
+        let id = match id {
+            Some(id)
+                if id.kind() == EntityKind::Structure && !self.structures.contains_key(&id) =>
+            {
+                self.ids.reserve(id);
+                id
+            }
+            _ => self.next_entity_id(EntityKind::Structure),
+        };
+        self.structures.insert(id, structure);
+        self.events.emit(self.stamp, UniverseEvent::Spawned(id));
+        Some(id)
+    }
+
+    pub fn remove_structure(&mut self, id: EntityId) -> Option<Structure> {
+        let s = self.structures.remove(&id)?;
+        self.events.emit(self.stamp, UniverseEvent::Destroyed(id));
+        Some(s)
+    }
+
+    pub fn add_waypoint(&mut self, waypoint: Waypoint) -> Option<EntityId> {
+        self.insert_waypoint(None, waypoint)
+    }
+
+    /// Adds a waypoint under the given ID if it's free, or under a new one
+    /// if not.
+    fn insert_waypoint(
+        &mut self,
+        id: Option<EntityId>,
+        mut waypoint: Waypoint,
+    ) -> Option<EntityId> {
+        self.legacy_ids.apply(&mut waypoint.planet_id);
+        self.planets.lookup(waypoint.planet_id, self.stamp)?;
+        let id = match id {
+            Some(id) if id.kind() == EntityKind::Waypoint && !self.waypoints.contains_key(&id) => {
+                self.ids.reserve(id);
+                id
+            }
+            _ => self.next_entity_id(EntityKind::Waypoint),
+        };
+        self.waypoints.insert(id, waypoint);
+        Some(id)
+    }
+
+    pub fn remove_waypoint(&mut self, id: EntityId) -> Option<Waypoint> {
+        self.waypoints.remove(&id)
+    }
+
+    /// Replaces the terrain of every landing site, dropping any for pads
+    /// which don't exist.
+    pub fn replace_terrain(&mut self, terrain: Vec<SiteTerrain>) {
+        self.terrain = terrain
+            .into_iter()
+            .map(|mut t| {
+                self.legacy_ids.apply(&mut t.pad);
+                t
+            })
+            .filter(|t| self.landing_pads.contains_key(&t.pad))
+            .map(|t| (t.pad, t))
+            .collect();
+    }
+
+    /// Replaces every waypoint with the given ones, keeping the IDs they
+    /// were saved with where there are any.
+    pub fn replace_waypoints(&mut self, waypoints: Vec<(Option<EntityId>, Waypoint)>) {
+        self.waypoints.clear();
+        for (id, w) in waypoints {
+            self.insert_waypoint(id, w);
+        }
+    }
+
+    /// Applies damage to a structure, removing it if it's destroyed.
+    /// Returns true if it was.
+    pub fn damage_structure(&mut self, id: EntityId, amount: f64) -> Option<bool> {
+        let destroyed = self.structures.get_mut(&id)?.damage(amount);
+        if destroyed {
+            self.remove_structure(id);
+        }
+        Some(destroyed)
+    }
+
+    /// Replaces every structure with the given ones, as when loading them
+    /// from a file. Sites are considered fully built, so that they don't
+    /// put up buildings which were loaded or deliberately removed. IDs
+    /// the structures were saved with are kept where there are any.
+    pub fn replace_structures(&mut self, structures: Vec<(Option<EntityId>, Structure)>) {
+        let ids: Vec<_> = self.structures.keys().copied().collect();
+        for id in ids {
+            self.remove_structure(id);
+        }
+        for (id, s) in structures {
+            self.insert_structure(id, s);
+        }
+        for pad in self.landing_pads.values_mut() {
+            pad.structures_built = site_structure_count(pad.level());
+        }
+    }
+
+    /// Puts up buildings around a landing site until it has as many as
+    /// its level calls for.
+    fn grow_site_structures(&mut self, pad_id: EntityId) -> Option<()> {
+        let pad = self.landing_pads.get(&pad_id)?;
+        let (body, _, _, _) = self.planets.lookup(pad.planet_id, self.stamp)?;
+        let built = pad.structures_built;
+        let new: Vec<_> = (built..site_structure_count(pad.level()))
+            .map(|i| Structure {
+                site: Some(pad_id),
+                ..site_structure(pad, body.radius, i)
+            })
+            .collect();
+        self.landing_pads.get_mut(&pad_id)?.structures_built += new.len();
+        for s in new {
+            self.add_structure(s);
+        }
+        Some(())
+    }
+
+    /// The structure containing the given position, which is relative to
+    /// the universe origin.
+    pub fn structure_at(&self, pos: DVec2) -> Option<EntityId> {
+        self.structures
+            .iter()
+            .find(|(_, s)| {
+                self.planets
+                    .lookup(s.planet_id, self.stamp)
+                    .map(|(body, pv, _, _)| s.contains(pos - pv.pos, body.radius))
+                    .unwrap_or(false)
+            })
+            .map(|(id, _)| *id)
+    }
+
+    /// Queues a vehicle to dock with another, returning its place in line.
+    /// A vehicle can only wait to dock at one target at a time.
+    pub fn request_docking(&mut self, id: EntityId, target: EntityId) -> Option<usize> {
+        if id == target
+            || !self.surface_vehicles.contains_key(&id)
+            || !self.surface_vehicles.contains_key(&target)
+        {
+            return None;
+        }
+        for queue in self.docking.values_mut() {
+            if queue.target != target {
+                queue.cancel(id);
+            }
+        }
+        self.docking.retain(|_, q| !q.is_empty());
+        let queue = self
+            .docking
+            .entry(target)
+            .or_insert_with(|| DockingQueue::new(target));
+        Some(queue.request(id))
+    }
+
+    pub fn cancel_docking(&mut self, id: EntityId) {
+        for queue in self.docking.values_mut() {
+            queue.cancel(id);
+        }
+        self.docking.retain(|_, q| !q.is_empty());
+    }
+
+    /// The queue the given vehicle is waiting in, if any.
+    pub fn docking_queue_of(&self, id: EntityId) -> Option<&DockingQueue> {
+        self.docking.values().find(|q| q.contains(id))
+    }
+
+    /// Controls steering each queued vehicle toward its holding point or,
+    /// once cleared, the target's docking port. Guidance is computed in
+    /// the target's frame, where the zero gravity control law applies.
+    fn docking_commands(&self) -> HashMap<EntityId, VehicleControl> {
+        let mut ret = HashMap::new();
+        for queue in self.docking.values() {
+            let Some(target) = self.surface_vehicles.get(&queue.target) else {
+                continue;
+            };
+            let Some(target_pv) = self.pv(queue.target) else {
+                continue;
+            };
+            for request in queue.requests() {
+                let Some(sv) = self.surface_vehicles.get(&request.vehicle) else {
+                    continue;
+                };
+                let Some(pv) = self.pv(request.vehicle) else {
+                    continue;
+                };
+                let Some(pose) = queue.guidance(request.vehicle, target.body.angle) else {
+                    continue;
+                };
+                let mut body = sv.body;
+                body.pv = pv - target_pv;
+                let (ctrl, _) = position_hold_control_law(pose, &body, &sv.vehicle, DVec2::ZERO);
+                ret.insert(request.vehicle, ctrl);
+            }
+        }
+        ret
+    }
+
+    /// Pulls vehicles near their target's docking port into alignment
+    /// with it, if the port's capture assist is on.
+    fn apply_capture_assist(&mut self) {
+        let dt = PHYSICS_CONSTANT_DELTA_TIME.to_secs_f64();
+        let mut assists = Vec::new();
+        for (id, sv) in &self.surface_vehicles {
+            let Some(target_id) = sv.target() else {
+                continue;
+            };
+            let Some(target) = self.surface_vehicles.get(&target_id) else {
+                continue;
+            };
+            if !target.capture_assist || sv.is_landed() {
+                continue;
+            }
+            let Some(rel) = self.pv(*id).zip(self.pv(target_id)).map(|(a, b)| a - b) else {
+                continue;
+            };
+            let assist = capture_assist(
+                rel,
+                sv.body.angle,
+                sv.body.angular_velocity,
+                target.body.angle,
+                target.body.angular_velocity,
+            );
+            if let Some(assist) = assist {
+                assists.push((*id, target_id, assist));
+            }
+        }
+
+        self.captures.clear();
+        for (id, target, assist) in assists {
+            if let Some(sv) = self.surface_vehicles.get_mut(&id) {
+                sv.body.pv.vel += assist.linear * dt;
+                sv.body.angular_velocity += assist.angular * dt;
+                self.captures.insert(id, target);
+            }
+        }
+    }
+
+    /// Joins vehicles whose docking ports have come together with those of
+    /// their targets, gently enough, onto their targets.
+    fn latch_docking_ports(&mut self) {
+        let mut latches = Vec::new();
+        for (id, sv) in &self.surface_vehicles {
+            let Some(target) = sv.target() else {
+                continue;
+            };
+            let Some(tv) = self.surface_vehicles.get(&target) else {
+                continue;
+            };
+            if sv.is_landed() || tv.is_landed() || sv.parent() != tv.parent() {
+                continue;
+            }
+            // vehicles pulling apart, as just after undocking, don't latch
+            let rel = sv.pv() - tv.pv();
+            let opening = rel.vel.dot(rel.pos.normalize_or_zero());
+            if rel.vel.length() > MAX_CONTACT_SPEED || opening > UNDOCK_SPEED / 2.0 {
+                continue;
+            }
+            if let Some((ours, theirs)) =
+                latching_ports(&tv.vehicle, &tv.body, &sv.vehicle, &sv.body)
+            {
+                latches.push((target, *id, ours, theirs));
+            }
+        }
+        for (host, guest, ours, theirs) in latches {
+            if self.join_vehicles(host, guest, ours, theirs).is_some() {
+                self.events
+                    .emit(self.stamp, UniverseEvent::Latched(guest, host));
+            }
+        }
+    }
+
+    /// Joins the guest vehicle onto the host at the given ports. The host
+    /// takes on the guest's mass and momentum, and the guest leaves the
+    /// simulation until it undocks. Vehicles with others docked to them
+    /// can be docked to, but can't dock themselves.
+    fn join_vehicles(
+        &mut self,
+        host: EntityId,
+        guest: EntityId,
+        ours: PartId,
+        theirs: PartId,
+    ) -> Option<()> {
+        if host == guest || self.docked.get(&guest).is_some_and(|d| !d.is_empty()) {
+            return None;
+        }
+        let g = self.surface_vehicles.get(&guest)?;
+        let (guest_vehicle, guest_mass, guest_vel) =
+            (g.vehicle.clone(), g.vehicle.total_mass(), g.body.pv.vel);
+
+        let sv = self.surface_vehicles.get_mut(&host)?;
+        let host_mass = sv.vehicle.total_mass();
+        let joint = sv.vehicle.dock(&guest_vehicle, ours, theirs)?;
+
+        let (mh, mg) = (host_mass.to_kg_f64(), guest_mass.to_kg_f64());
+        let vel = (sv.body.pv.vel * mh + guest_vel * mg) / (mh + mg);
+        sv.apply_impulse(vel - sv.body.pv.vel, &self.planets, self.stamp);
+
+        self.surface_vehicles.remove(&guest);
+        self.traffic.release(guest);
+        self.maneuvers.cancel(guest);
+        self.rendezvous.remove(&guest);
+        self.cancel_docking(guest);
+        self.docked.entry(host).or_default().push((guest, joint));
+        Some(())
+    }
+
+    /// Separates a vehicle docked to the host, which carries on as it was
+    /// before docking, pushed gently away from the host.
+    pub fn undock(&mut self, host: EntityId, guest: EntityId) -> Result<(), &'static str> {
+        let joints = self
+            .docked
+            .get_mut(&host)
+            .ok_or("Nothing is docked there")?;
+        let i = joints
+            .iter()
+            .position(|(id, _)| *id == guest)
+            .ok_or("Vehicle isn't docked there")?;
+        let sv = self
+            .surface_vehicles
+            .get_mut(&host)
+            .ok_or("No such vehicle")?;
+        let (_, joint) = joints.remove(i);
+        if joints.is_empty() {
+            self.docked.remove(&host);
+        }
+
+        // the guest's port faces the host, so it's pushed the other way
+        let facing = sv
+            .vehicle
+            .get_part(joint.port())
+            .map(|p| p.rotation().to_angle())
+            .unwrap_or(0.0);
+        let vehicle = sv.vehicle.undock(&joint);
+
+        // the two are pushed apart at UNDOCK_SPEED, each taking a share of
+        // it in inverse proportion to its mass, so momentum is conserved
+        let push = rotate_f64(DVec2::X, sv.body.angle + facing) * UNDOCK_SPEED;
+        let (mh, mg) = (
+            sv.vehicle.total_mass().to_kg_f64(),
+            vehicle.total_mass().to_kg_f64(),
+        );
+        let mut body = sv.body;
+        body.pv.pos += rotate_f64(joint.offset_meters(), sv.body.angle);
+        body.pv.vel -= push * mh / (mh + mg);
+        body.angle += joint.quarter_turns() as f64 * PI_64 / 2.0;
+        sv.apply_impulse(push * mg / (mh + mg), &self.planets, self.stamp);
+        let planet_id = sv.planet_id;
+        let orbit = sv
+            .orbit
+            .and_then(|o| SparseOrbit::from_pv(body.pv, o.body, self.stamp));
+
+        let mut guest_sv =
+            SurfaceSpacecraftEntity::new(planet_id, vehicle, body, VehicleController::idle());
+        guest_sv.orbit = orbit;
+        self.surface_vehicles.insert(guest, guest_sv);
+        self.events
+            .emit(self.stamp, UniverseEvent::Undocked(guest, host));
+        Ok(())
+    }
+
+    fn update_docking(&mut self) {
+        let mut docking = std::mem::take(&mut self.docking);
+        for queue in docking.values_mut() {
+            let target_pv = self.pv(queue.target);
+            let outcomes = queue.update(self.stamp, |id| Some(self.pv(id)? - target_pv?));
+            for outcome in outcomes {
+                let event = match outcome {
+                    DockingOutcome::Docked(id) => UniverseEvent::Docked(id, queue.target),
+                    DockingOutcome::WavedOff(id, reason) => {
+                        UniverseEvent::WavedOff(id, queue.target, reason)
+                    }
+                    DockingOutcome::Aborted(id) => UniverseEvent::DockingAborted(id, queue.target),
+                };
+                self.events.emit(self.stamp, event);
+            }
+        }
+        docking.retain(|_, q| !q.is_empty());
+        self.docking = docking;
+    }
+
+    /// Steps each site's service drones, which fly to vehicles parked on
+    /// the site's pad and work on them. Tasks for vehicles which have left
+    /// the pad are dropped.
+    fn update_service_drones(&mut self) {
+        for pad in self.landing_pads.values_mut() {
+            let Some((body, _, _, _)) = self.planets.lookup(pad.planet_id, self.stamp) else {
+                continue;
+            };
+            pad.dispatch_drones();
+            let rate = pad.refuel_rate();
+            let mut drones = std::mem::take(&mut pad.drones);
+            for drone in &mut drones {
+                let Some(task) = drone.task() else {
+                    drone.on_sim_tick();
+                    continue;
+                };
+                let sv = self.surface_vehicles.get_mut(&task.vehicle()).filter(|sv| {
+                    sv.is_landed()
+                        && sv.parent() == pad.planet_id
+                        && pad.contains(sv.body.pv.pos, body.radius)
+                });
+                let Some(sv) = sv else {
+                    drone.finish();
+                    continue;
+                };
+                let mut hover = pad.site_position(sv.body.pv.pos, body.radius);
+                hover.y += sv.vehicle.bounding_radius() + DRONE_HOVER_HEIGHT;
+                if drone.service(hover, &mut sv.vehicle, rate) {
+                    drone.finish();
+                }
+            }
+            pad.drones = drones;
+        }
+    }
+
+    /// Lets background traffic fly itself. Vehicles under any other kind
+    /// of control, such as piloting or docking, are left alone until
+    /// they're idle again.
+    fn update_background_traffic(&mut self) {
+        let stamp = self.stamp;
+        self.traffic
+            .retain(|id| self.surface_vehicles.contains_key(&id) || self.loading.contains(id));
+        for (id, agent) in self.traffic.agents_mut() {
+            let Some(sv) = self.surface_vehicles.get_mut(id) else {
+                continue;
+            };
+            if !sv.controller.is_idle() {
+                continue;
+            }
+            let Some(orbit) = sv.current_orbit() else {
+                continue;
+            };
+            if let Some(dv) = agent.update(stamp, orbit) {
+                sv.apply_impulse(dv, &self.planets, stamp);
+            }
+        }
+    }
+
+    /// Applies the burns of scheduled maneuver plans which have come due.
+    fn update_scheduled_maneuvers(&mut self) {
+        let stamp = self.stamp;
+        self.maneuvers
+            .retain(|id| self.surface_vehicles.contains_key(&id));
+        if self.maneuvers.auto_hold {
+            self.hold_burn_attitudes();
+        }
+        let mut aborted = HashSet::new();
+        for (id, dv) in self.maneuvers.take_due(stamp) {
+            if aborted.contains(&id) {
+                continue;
+            }
+            let Some(sv) = self.surface_vehicles.get_mut(&id) else {
+                continue;
+            };
+            let alignment = BurnAlignment::new(stamp, dv, sv.body.angle);
+            if self.maneuvers.auto_hold && !alignment.is_aligned() {
+                self.maneuvers.cancel(id);
+                aborted.insert(id);
+                self.events
+                    .emit(stamp, UniverseEvent::BurnAborted(id, alignment.error()));
+                continue;
+            }
+            sv.apply_impulse(dv, &self.planets, stamp);
+        }
+        for id in self.maneuvers.stale_holds() {
+            self.release_burn_hold(id);
+        }
+    }
+
+    /// Puts a vehicle on autopilot to meet up with the target vehicle,
+    /// scheduling the burns to get there.
+    pub fn start_rendezvous(&mut self, id: EntityId, target: EntityId) -> Result<(), &'static str> {
+        if id == target {
+            return Err("Cannot rendezvous with itself");
+        }
+        let orbit = self
+            .surface_vehicles
+            .get(&id)
+            .and_then(|sv| sv.current_orbit())
+            .ok_or("Vehicle is not in orbit")?;
+        let dst = self
+            .surface_vehicles
+            .get(&target)
+            .and_then(|sv| sv.current_orbit())
+            .ok_or("Target is not in orbit")?;
+        let mut ctrl = OrbitalController::idle();
+        ctrl.update(self.stamp, orbit)?;
+        ctrl.set_rendezvous(target, dst, self.stamp)?;
+        let plan = ctrl.plan().ok_or("No rendezvous plan")?;
+        self.maneuvers.schedule(id, plan.clone());
+        self.rendezvous.insert(id, ctrl);
+        Ok(())
+    }
+
+    /// Takes a vehicle off rendezvous autopilot, along with the burns it
+    /// had scheduled. Returns whether it was on it.
+    pub fn cancel_rendezvous(&mut self, id: EntityId) -> bool {
+        if self.rendezvous.remove(&id).is_none() {
+            return false;
+        }
+        self.maneuvers.cancel(id);
+        true
+    }
+
+    /// Keeps vehicles on rendezvous autopilot headed for their targets,
+    /// replanning as needed, and announces when each makes it or gives up.
+    fn update_rendezvous(&mut self) {
+        let stamp = self.stamp;
+        let mut replanned = Vec::new();
+        let mut finished = Vec::new();
+        for (id, ctrl) in &mut self.rendezvous {
+            let Some(target) = ctrl.rendezvous_target() else {
+                finished.push((*id, None));
+                continue;
+            };
+            let Some(sv) = self.surface_vehicles.get(id) else {
+                finished.push((*id, None));
+                continue;
+            };
+            let abort = |reason| Some(UniverseEvent::RendezvousAborted(*id, target, reason));
+            let Some(tv) = self.surface_vehicles.get(&target) else {
+                finished.push((*id, abort(RendezvousAbort::TargetLost)));
+                continue;
+            };
+            if sv.parent() != tv.parent() {
+                finished.push((*id, abort(RendezvousAbort::DifferentBody)));
+                continue;
+            }
+            if is_rendezvous(sv.pv(), tv.pv()) {
+                let event = UniverseEvent::RendezvousAchieved(*id, target);
+                finished.push((*id, Some(event)));
+                continue;
+            }
+            // the last burn is done, or the plan was dropped, such as
+            // when a burn was aborted
+            if self.maneuvers.get(*id).is_none() {
+                let ended = ctrl.plan().is_none_or(|p| stamp >= p.end());
+                let reason = if ended {
+                    RendezvousAbort::Missed
+                } else {
+                    RendezvousAbort::CalledOff
+                };
+                finished.push((*id, abort(reason)));
+                continue;
+            }
+            if !ctrl.needs_update(stamp) {
+                continue;
+            }
+            let (Some(orbit), Some(dst)) = (sv.current_orbit(), tv.current_orbit()) else {
+                finished.push((*id, abort(RendezvousAbort::NoPlan)));
+                continue;
+            };
+            match ctrl.update_rendezvous(stamp, orbit, dst) {
+                Ok(true) => replanned.extend(ctrl.plan().map(|p| (*id, p.clone()))),
+                Ok(false) => (),
+                Err(_) => finished.push((*id, abort(RendezvousAbort::NoPlan))),
+            }
+        }
+
+        for (id, plan) in replanned {
+            self.maneuvers.schedule(id, plan);
+        }
+        for (id, event) in finished {
+            self.cancel_rendezvous(id);
+            if let Some(event) = event {
+                self.events.emit(stamp, event);
+            }
+        }
+    }
+
+    /// Starts passing an item from one vehicle to another alongside it,
+    /// a little each tick. Any transfer already running between the two
+    /// is replaced.
+    pub fn start_transfer(
+        &mut self,
+        source: EntityId,
+        destination: EntityId,
+        item: Item,
+    ) -> Result<(), &'static str> {
+        if source == destination {
+            return Err("Cannot transfer to itself");
+        }
+        let sv = self
+            .surface_vehicles
+            .get(&source)
+            .ok_or("No such vehicle")?;
+        let dv = self
+            .surface_vehicles
+            .get(&destination)
+            .ok_or("No such destination")?;
+        if sv.parent() != dv.parent() || !in_transfer_range(sv.pv(), dv.pv()) {
+            return Err("Vehicles are too far apart");
+        }
+        if sv.vehicle.item_mass(item) == Mass::ZERO {
+            return Err("Nothing to transfer");
+        }
+        if dv.vehicle.room_for(item) == Mass::ZERO {
+            return Err("No room for it");
+        }
+        self.cancel_transfer(source, destination);
+        self.transfers
+            .push(ItemTransfer::new(source, destination, item));
+        Ok(())
+    }
+
+    /// Stops passing items between two vehicles, either way. Returns
+    /// whether anything was being passed.
+    pub fn cancel_transfer(&mut self, a: EntityId, b: EntityId) -> bool {
+        let n = self.transfers.len();
+        self.transfers.retain(|t| !t.involves(a, b));
+        self.transfers.len() < n
+    }
+
+    /// Moves items along for each transfer, and announces when each one
+    /// is done or the vehicles can no longer pass them.
+    fn update_transfers(&mut self, dt: Nanotime) {
+        let stamp = self.stamp;
+        let mut transfers = std::mem::take(&mut self.transfers);
+        transfers.retain_mut(|t| {
+            let (source, destination) = (t.source(), t.destination());
+            let abort = |reason| UniverseEvent::TransferAborted(source, destination, reason);
+            let [Some(sv), Some(dv)] = self
+                .surface_vehicles
+                .get_disjoint_mut([&source, &destination])
+            else {
+                self.events.emit(stamp, abort(TransferAbort::VehicleLost));
+                return false;
+            };
+            if sv.parent() != dv.parent() || !in_transfer_range(sv.pv(), dv.pv()) {
+                self.events.emit(stamp, abort(TransferAbort::OutOfRange));
+                return false;
+            }
+            if !t.step(&mut sv.vehicle, &mut dv.vehicle, dt.to_secs_f64()) {
+                return true;
+            }
+            let event = UniverseEvent::TransferComplete(source, destination, t.item(), t.moved());
+            self.events.emit(stamp, event);
+            false
+        });
+        self.transfers = transfers;
+    }
+
+    /// Points idle vehicles along their next burn once it's close, and
+    /// keeps those already held pointed along it. Vehicles taken over by
+    /// the player or another autopilot in the meantime are let go.
+    fn hold_burn_attitudes(&mut self) {
+        let stamp = self.stamp;
+        for (id, sv) in &mut self.surface_vehicles {
+            let Some((t, dv)) = self.maneuvers.next_burn_for(*id) else {
+                continue;
+            };
+            let holding = self.maneuvers.is_holding(*id);
+            let held = matches!(
+                sv.controller.mode(),
+                VehicleControlPolicy::HoldAttitude(Some(_))
+            );
+            if holding && !held {
+                self.maneuvers.stop_hold(*id);
+                continue;
+            }
+            if t - stamp > BURN_HOLD_LEAD || !(holding || sv.controller.is_idle()) {
+                continue;
+            }
+            sv.controller
+                .set_policy(VehicleControlPolicy::HoldAttitude(Some(dv.to_angle())));
+            self.maneuvers.start_hold(*id);
+        }
+    }
+
+    fn release_burn_hold(&mut self, id: EntityId) {
+        if !self.maneuvers.stop_hold(id) {
+            return;
+        }
+        if let Some(sv) = self.surface_vehicles.get_mut(&id) {
+            if let VehicleControlPolicy::HoldAttitude(_) = sv.controller.mode() {
+                sv.controller.set_idle();
+            }
+        }
+    }
+
+    /// Runs the scenario script's events which have come due, announcing
+    /// each on the event bus.
+    fn run_script(&mut self) {
+        let stamp = self.stamp;
+        for i in self.script.take_due(stamp) {
+            let Some(event) = self.script.get(i) else {
+                continue;
+            };
+            if let ScriptAction::SpawnVehicle(v) = &event.action {
+                let orbit = self
+                    .planets
+                    .lookup(v.parent, stamp)
+                    .and_then(|(body, _, _, _)| v.orbit.to_orbit(body));
+                if let Some(orbit) = orbit {
+                    let model = v.model.clone();
+                    self.queue_orbital_vehicle(model, GlobalOrbit(v.parent, orbit));
+                }
+            }
+            self.events.emit(stamp, UniverseEvent::Scripted(i));
+        }
+    }
+
+    fn update_orbit_history(&mut self) {
+        let stamp = self.stamp;
+        self.orbit_history
+            .retain(|id| self.surface_vehicles.contains_key(&id));
+        for (id, sv) in &self.surface_vehicles {
+            self.orbit_history.record(*id, sv.current_orbit(), stamp);
+        }
+    }
+
+    /// Extends each orbiting vehicle's predicted trajectory to cover the
+    /// horizon returned for it, so that important vehicles can be predicted
+    /// far ahead while background traffic is only predicted briefly.
+    pub fn propagate_orbiters(
+        &mut self,
+        horizon: impl Fn(EntityId, &SurfaceSpacecraftEntity) -> Nanotime + Sync + Send,
+    ) {
+        let (stamp, planets) = (self.stamp, &self.planets);
+        self.propagation
+            .for_each_mut(&mut self.surface_vehicles, |(id, sv)| {
+                let h = horizon(*id, sv);
+                sv.extend_prediction(stamp, h, planets);
+            });
+    }
+
+    pub fn add_landing_pad(&mut self, pad: LandingPad) -> Option<EntityId> {
+        let legacy = EntityId(self.legacy_next);
+        // sites are named after their planet and name, so that saved
+        // terrain and structures find them again however they're created
+        let planet = self
+            .planets
+            .lookup(pad.planet_id, self.stamp)?
+            .3
+            .name
+            .clone();
+        let named = EntityId::named(EntityKind::Site, &format!("{}/{}", planet, pad.name));
+        let id = if self.landing_pads.contains_key(&named) {
+            self.next_entity_id(EntityKind::Site)
+        } else {
+            self.legacy_next += 1;
+            named
+        };
+        self.legacy_ids.insert(legacy, id);
+        self.landing_pads.insert(id, pad);
+        self.grow_site_structures(id);
+        Some(id)
+    }
+
+    /// Returns the pad containing the given position, which is relative
+    /// to the center of the given planet.
+    pub fn pad_at(&self, planet_id: EntityId, pos: DVec2) -> Option<EntityId> {
+        let (body, _, _, _) = self.planets.lookup(planet_id, self.stamp)?;
+        self.landing_pads
+            .iter()
+            .find(|(_, pad)| pad.planet_id == planet_id && pad.contains(pos, body.radius))
+            .map(|(id, _)| *id)
+    }
+
+    /// Returns the landing site whose terrain contains the given position,
+    /// which is relative to the center of the given planet, and how far
+    /// along the surface the position is from the site's pad.
+    pub fn site_domain_at(&self, planet_id: EntityId, pos: DVec2) -> Option<(EntityId, f64)> {
+        let (body, _, _, _) = self.planets.lookup(planet_id, self.stamp)?;
+        self.landing_pads
+            .iter()
+            .filter(|(_, pad)| pad.planet_id == planet_id)
+            .map(|(id, pad)| (*id, pad.surface_offset(pos, body.radius)))
+            .filter(|(_, offset)| SiteTerrain::in_domain(*offset))
+            .min_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+    }
+
+    /// Returns a landed vehicle parked on the given pad, if any.
+    pub fn pad_occupant(&self, pad_id: EntityId, exclude: Option<EntityId>) -> Option<EntityId> {
+        let pad = self.landing_pads.get(&pad_id)?;
+        let (body, _, _, _) = self.planets.lookup(pad.planet_id, self.stamp)?;
+        self.surface_vehicles
+            .iter()
+            .find(|(id, sv)| {
+                Some(**id) != exclude
+                    && sv.is_landed()
+                    && sv.parent() == pad.planet_id
+                    && pad.contains(sv.body.pv.pos, body.radius)
+            })
+            .map(|(id, _)| *id)
+    }
+
+    /// Returns the nearest pad on the given planet with nobody parked on
+    /// it, other than `exclude`, to divert to from an occupied one.
+    pub fn free_pad_near(
+        &self,
+        planet_id: EntityId,
+        pos: DVec2,
+        exclude: Option<EntityId>,
+    ) -> Option<EntityId> {
+        let (body, _, _, _) = self.planets.lookup(planet_id, self.stamp)?;
+        self.landing_pads
+            .iter()
+            .filter(|(id, pad)| {
+                pad.planet_id == planet_id && self.pad_occupant(**id, exclude).is_none()
+            })
+            .min_by(|(_, a), (_, b)| {
+                let a = a.surface_offset(pos, body.radius).abs();
+                let b = b.surface_offset(pos, body.radius).abs();
+                a.total_cmp(&b)
+            })
+            .map(|(id, _)| *id)
+    }
+
     pub fn get_group_members(&mut self, gid: EntityId) -> Vec<EntityId> {
         self.constellations
             .iter()
@@ -172,6 +1615,42 @@ impl Universe {
             .collect()
     }
 
+    /// Puts the given vehicles in a new group, taking them out of any
+    /// group they were in.
+    pub fn create_group(&mut self, ids: impl IntoIterator<Item = EntityId>) -> EntityId {
+        let gid = self.next_entity_id(EntityKind::Group);
+        for id in ids {
+            if self.surface_vehicles.contains_key(&id) {
+                self.constellations.insert(id, gid);
+            }
+        }
+        gid
+    }
+
+    /// Moves every member of one group into another.
+    pub fn merge_groups(&mut self, into: EntityId, from: EntityId) {
+        for gid in self.constellations.values_mut() {
+            if *gid == from {
+                *gid = into;
+            }
+        }
+    }
+
+    /// Moves some members of a group into a new group of their own. Does
+    /// nothing if that would leave either group empty.
+    pub fn split_group(&mut self, gid: EntityId, ids: &HashSet<EntityId>) -> Option<EntityId> {
+        let members = self.get_group_members(gid);
+        let moving: Vec<_> = members
+            .iter()
+            .filter(|id| ids.contains(id))
+            .copied()
+            .collect();
+        if moving.is_empty() || moving.len() == members.len() {
+            return None;
+        }
+        Some(self.create_group(moving))
+    }
+
     pub fn group_membership(&self, id: &EntityId) -> Option<EntityId> {
         self.constellations.get(id).cloned()
     }
@@ -192,20 +1671,65 @@ impl Universe {
         self.surface_vehicles.keys().into_iter().map(|id| *id)
     }
 
-    pub fn add_orbital_vehicle(&mut self, vehicle: Vehicle, orbit: GlobalOrbit) -> Option<()> {
-        let id = self.next_entity_id();
+    pub fn add_orbital_vehicle(
+        &mut self,
+        vehicle: Vehicle,
+        orbit: GlobalOrbit,
+    ) -> Option<EntityId> {
+        let id = self.next_entity_id(EntityKind::Vehicle);
+        self.insert_orbital_vehicle(id, vehicle, orbit)?;
+        Some(id)
+    }
+
+    fn insert_orbital_vehicle(
+        &mut self,
+        id: EntityId,
+        mut vehicle: Vehicle,
+        orbit: GlobalOrbit,
+    ) -> Option<()> {
         let mut body = RigidBody::random_spin();
         body.pv = orbit.1.pv(self.stamp).ok()?; // orbiter.pv(self.stamp, &self.planets)?;
         let controller = VehicleController::idle();
-        let os = SurfaceSpacecraftEntity::new(orbit.0, vehicle, body, controller);
+        vehicle.set_name(self.callsign_for(vehicle.name(), None));
+        let mut os = SurfaceSpacecraftEntity::new(orbit.0, vehicle, body, controller);
+        os.orbit = Some(orbit.1);
         self.surface_vehicles.insert(id, os);
+        self.events.emit(self.stamp, UniverseEvent::Spawned(id));
         Some(())
     }
 
+    /// Reserves an ID for a vehicle of the given model and queues it to be
+    /// constructed later with `finish_loading`. Until then, lookups for the
+    /// returned ID behave as though the entity doesn't exist.
+    pub fn queue_orbital_vehicle(
+        &mut self,
+        model: impl Into<String>,
+        orbit: GlobalOrbit,
+    ) -> EntityId {
+        let id = self.next_entity_id(EntityKind::Vehicle);
+        self.loading.push(PendingVehicle {
+            id,
+            model: model.into(),
+            orbit,
+        });
+        id
+    }
+
+    /// Adds a vehicle popped from the load queue, propagating its orbit to
+    /// the current time.
+    pub fn finish_loading(&mut self, pending: PendingVehicle, vehicle: Vehicle) -> Option<()> {
+        self.insert_orbital_vehicle(pending.id, vehicle, pending.orbit)
+    }
+
+    /// Whether the given ID has been reserved but not yet loaded.
+    pub fn is_loading(&self, id: EntityId) -> bool {
+        self.loading.contains(id)
+    }
+
     pub fn add_surface_vehicle(
         &mut self,
         planet_id: EntityId,
-        vehicle: Vehicle,
+        mut vehicle: Vehicle,
         angle: f64,
         altitude: f64,
     ) -> Option<EntityId> {
@@ -223,13 +1747,55 @@ impl Universe {
         };
 
         let controller = VehicleController::launch();
-        let id = self.next_entity_id();
+        let id = self.next_entity_id(EntityKind::Vehicle);
+        vehicle.set_name(self.callsign_for(vehicle.name(), None));
         let sv = SurfaceSpacecraftEntity::new(planet_id, vehicle, body, controller);
         self.surface_vehicles.insert(id, sv);
+        self.events.emit(self.stamp, UniverseEvent::Spawned(id));
 
         Some(id)
     }
 
+    /// Jettisons a vehicle's active stage, which carries on as a derelict
+    /// of its own, pushed away behind the vehicle by its decouplers.
+    /// Returns the ID of the spent stage.
+    pub fn activate_stage(&mut self, id: EntityId) -> Result<EntityId, StagingError> {
+        let sv = self
+            .surface_vehicles
+            .get_mut(&id)
+            .ok_or(StagingError::NoVehicle)?;
+        let push = sv.vehicle.ejection_speed().unwrap_or(0.0);
+        let mut spent = sv.vehicle.jettison_stage()?;
+
+        // the two halves push off one another, each moving away in
+        // inverse proportion to its mass
+        let (mr, ms) = (
+            sv.vehicle.total_mass().to_kg_f64(),
+            spent.total_mass().to_kg_f64(),
+        );
+        let u = rotate_f64(DVec2::X, sv.body.angle) * push;
+        let mut body = sv.body;
+        body.pv.vel -= u * mr / (mr + ms);
+        sv.apply_impulse(u * ms / (mr + ms), &self.planets, self.stamp);
+        let planet_id = sv.planet_id;
+        let orbit = sv
+            .orbit
+            .and_then(|o| SparseOrbit::from_pv(body.pv, o.body, self.stamp));
+        let name = format!("{} Stage", sv.vehicle.name());
+
+        let spent_id = self.next_entity_id(EntityKind::Vehicle);
+        spent.set_name(self.callsign_for(&name, None));
+        let mut debris =
+            SurfaceSpacecraftEntity::new(planet_id, spent, body, VehicleController::idle());
+        debris.orbit = orbit;
+        self.surface_vehicles.insert(spent_id, debris);
+        self.events
+            .emit(self.stamp, UniverseEvent::Spawned(spent_id));
+        self.events
+            .emit(self.stamp, UniverseEvent::Staged(id, spent_id));
+        Ok(spent_id)
+    }
+
     pub fn lup_orbiter(&self, id: EntityId) -> Option<ObjectLookup> {
         let stamp = self.stamp;
         let os = self.surface_vehicles.get(&id)?;
@@ -246,6 +1812,14 @@ impl Universe {
 
         let (local, parent) = if let Some(ov) = self.surface_vehicles.get(&id) {
             (ov.pv(), ov.parent())
+        } else if let Some(s) = self.structures.get(&id) {
+            (self.structure_local_pv(s)?, s.planet_id)
+        } else if let Some(w) = self.waypoints.get(&id) {
+            let (body, _, _, _) = self.planets.lookup(w.planet_id, self.stamp)?;
+            (
+                PV::from_f64(w.local_position(&body), DVec2::ZERO),
+                w.planet_id,
+            )
         } else {
             return None;
         };
@@ -255,6 +1829,31 @@ impl Universe {
         Some(local + parent)
     }
 
+    /// State of `chaser` in the LVLH frame of `target`. Both must be
+    /// vehicles around the same body.
+    pub fn lvlh_state(&self, chaser: EntityId, target: EntityId) -> Option<PV> {
+        let c = self.surface_vehicles.get(&chaser)?;
+        let t = self.surface_vehicles.get(&target)?;
+        if c.parent() != t.parent() {
+            return None;
+        }
+        Some(LvlhFrame::new(t.pv())?.to_frame(c.pv()))
+    }
+
+    /// Position of the middle of a structure, relative to its planet.
+    fn structure_local_pv(&self, s: &Structure) -> Option<PV> {
+        let (body, _, _, _) = self.planets.lookup(s.planet_id, self.stamp)?;
+        let up = rotate_f64(DVec2::X, s.angle);
+        let pos = s.local_position(body.radius) + up * s.height / 2.0;
+        Some(PV::from_f64(pos, DVec2::ZERO))
+    }
+
+    pub fn lup_structure(&self, id: EntityId) -> Option<ObjectLookup<'_>> {
+        let s = self.structures.get(&id)?;
+        let pv = self.pv(id)?;
+        Some(ObjectLookup(id, ScenarioObject::Structure(s), pv))
+    }
+
     pub fn lup_planet(&self, id: EntityId) -> Option<ObjectLookup> {
         let stamp = self.stamp;
         let (body, pv, _, sys) = self.planets.lookup(id, stamp)?;
@@ -0,0 +1,572 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+/// Bumped whenever the layout of a saved universe changes. Older files are
+/// brought up to date on load; newer ones are refused.
+pub const UNIVERSE_FILE_VERSION: u32 = 1;
+
+/// A vehicle as it was when the universe was saved. Controllers aren't
+/// kept, so every vehicle comes back idle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VehicleStateFileStorage {
+    pub id: EntityId,
+    pub planet_id: EntityId,
+    pub callsign: String,
+    pub design: VehicleFileStorage,
+    /// Build progress and contents of each part, in the order the design
+    /// lists them.
+    #[serde(default)]
+    pub part_states: Vec<PartStateFileStorage>,
+    pub pv: PV,
+    pub angle: f64,
+    pub angular_velocity: f64,
+    pub orbit: Option<SparseOrbit>,
+    #[serde(default)]
+    pub target: Option<EntityId>,
+    #[serde(default)]
+    pub capture_assist: bool,
+    /// Vehicles docked to this one, with their parts numbered as in
+    /// `part_states`.
+    #[serde(default)]
+    pub docked: Vec<(EntityId, DockingJoint)>,
+    /// Target of the rendezvous autopilot, if it was engaged. The burns
+    /// there are planned afresh on load.
+    #[serde(default)]
+    pub rendezvous: Option<EntityId>,
+}
+
+impl VehicleStateFileStorage {
+    /// A vehicle as it is now, along with the joints of those docked to
+    /// it. Its rendezvous target is left for the caller to fill in.
+    pub fn new(
+        id: EntityId,
+        sv: &SurfaceSpacecraftEntity,
+        docked: &[(EntityId, DockingJoint)],
+    ) -> Self {
+        // parts are numbered afresh when the vehicle is built again
+        let ids = sv.vehicle().renumbered_part_ids();
+        Self {
+            id,
+            planet_id: sv.planet_id,
+            callsign: sv.vehicle().name().to_string(),
+            design: VehicleFileStorage::from_vehicle(sv.vehicle()),
+            part_states: sv.vehicle().part_states(),
+            pv: sv.body.pv,
+            angle: sv.body.angle,
+            angular_velocity: sv.body.angular_velocity,
+            orbit: sv.orbit,
+            target: sv.target(),
+            capture_assist: sv.capture_assist,
+            docked: docked
+                .iter()
+                .map(|(guest, joint)| (*guest, joint.renumbered(&ids)))
+                .collect(),
+            rendezvous: None,
+        }
+    }
+
+    /// Puts the vehicle back together out of `parts`, idle, as it was
+    /// when saved.
+    pub fn build(
+        &self,
+        parts: &HashMap<String, PartPrototype>,
+    ) -> Result<SurfaceSpacecraftEntity, NoPartError> {
+        let mut vehicle = self.design.build(self.callsign.clone(), parts)?;
+        vehicle.restore_part_states(&self.part_states);
+        let body = RigidBody {
+            pv: self.pv,
+            angle: self.angle,
+            angular_velocity: self.angular_velocity,
+        };
+        let mut sv =
+            SurfaceSpacecraftEntity::new(self.planet_id, vehicle, body, VehicleController::idle());
+        sv.replace_orbit(self.orbit);
+        sv.set_target(self.target);
+        sv.capture_assist = self.capture_assist;
+        Ok(sv)
+    }
+}
+
+/// A vehicle set aside in quarantine, and why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineFileStorage {
+    pub vehicle: VehicleStateFileStorage,
+    pub reason: QuarantineReason,
+    pub quarantined_at: Nanotime,
+    pub group: Option<EntityId>,
+}
+
+/// A landing site and how far it has been developed. Drones and their
+/// tasks start over on load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteFileStorage {
+    pub id: EntityId,
+    pub name: String,
+    pub planet_id: EntityId,
+    pub angle: f64,
+    pub width: f64,
+    pub development: f64,
+    pub deliveries: u32,
+    pub structures_built: usize,
+}
+
+/// Everything needed to pick a running universe back up where it was
+/// left. Fields added after the first version default when missing, so
+/// older files still load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseFileStorage {
+    pub version: u32,
+    pub stamp: Nanotime,
+    pub ticks: u128,
+    #[serde(default)]
+    pub ids: IdAllocator,
+    pub bodies: Vec<ScenarioBody>,
+    pub vehicles: Vec<VehicleStateFileStorage>,
+    #[serde(default)]
+    pub pending: Vec<PendingVehicle>,
+    #[serde(default)]
+    pub sites: Vec<SiteFileStorage>,
+    /// Vehicles and the groups they belong to.
+    #[serde(default)]
+    pub constellations: Vec<(EntityId, EntityId)>,
+    #[serde(default)]
+    pub structures: Vec<(EntityId, Structure)>,
+    #[serde(default)]
+    pub terrain: Vec<SiteTerrain>,
+    #[serde(default)]
+    pub waypoints: Vec<(EntityId, Waypoint)>,
+    /// Items part way through being passed between vehicles.
+    #[serde(default)]
+    pub transfers: Vec<ItemTransfer>,
+    #[serde(default)]
+    pub quarantine: Vec<QuarantineFileStorage>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UniverseFileError {
+    TooNew(u32),
+    BadSystem(ScenarioIssue),
+}
+
+impl std::fmt::Display for UniverseFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooNew(v) => write!(
+                f,
+                "Saved by a newer version (format {}, this one reads up to {})",
+                v, UNIVERSE_FILE_VERSION
+            ),
+            Self::BadSystem(issue) => write!(f, "Saved planetary system is invalid: {}", issue),
+        }
+    }
+}
+
+impl Error for UniverseFileError {}
+
+impl UniverseFileStorage {
+    /// Every ID the file refers to.
+    fn ids_in_use(&self) -> impl Iterator<Item = EntityId> + use<'_> {
+        let quarantined = self.quarantine.iter().map(|q| &q.vehicle);
+        let vehicles = self.vehicles.iter().chain(quarantined).flat_map(|v| {
+            let guests = v.docked.iter().map(|(id, _)| *id);
+            std::iter::once(v.id).chain(guests)
+        });
+        let pending = self.pending.iter().map(|p| p.id);
+        let sites = self.sites.iter().map(|s| s.id);
+        let groups = self.constellations.iter().map(|(_, g)| *g);
+        let structures = self.structures.iter().map(|(id, _)| *id);
+        let waypoints = self.waypoints.iter().map(|(id, _)| *id);
+        vehicles
+            .chain(pending)
+            .chain(sites)
+            .chain(groups)
+            .chain(structures)
+            .chain(waypoints)
+    }
+
+    /// Brings a file written by an older version up to date.
+    pub fn migrate(mut self) -> Result<Self, UniverseFileError> {
+        if self.version > UNIVERSE_FILE_VERSION {
+            return Err(UniverseFileError::TooNew(self.version));
+        }
+        // the allocator may be missing or behind, so make sure nothing in
+        // the file is handed out again
+        let ids: Vec<_> = self.ids_in_use().collect();
+        for id in ids {
+            self.ids.reserve(id);
+        }
+        self.version = UNIVERSE_FILE_VERSION;
+        Ok(self)
+    }
+}
+
+pub fn load_universe_from_file(filename: &Path) -> Result<UniverseFileStorage, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    Ok(serde_yaml::from_str(&s)?)
+}
+
+pub fn write_universe_to_file(
+    storage: &UniverseFileStorage,
+    filename: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(storage)?)?;
+    Ok(())
+}
+
+/// Entries of a map in ID order, so that saving the same universe twice
+/// gives the same file.
+fn sorted<T: Clone>(map: &HashMap<EntityId, T>) -> Vec<(EntityId, T)> {
+    let mut v: Vec<_> = map.iter().map(|(k, v)| (*k, v.clone())).collect();
+    v.sort_by_key(|(k, _)| *k);
+    v
+}
+
+impl Universe {
+    pub fn to_file_storage(&self) -> UniverseFileStorage {
+        let mut vehicles: Vec<_> = self
+            .surface_vehicles
+            .iter()
+            .map(|(id, sv)| {
+                let docked = self
+                    .docked
+                    .get(id)
+                    .map(|d| d.as_slice())
+                    .unwrap_or_default();
+                VehicleStateFileStorage {
+                    rendezvous: self.rendezvous.get(id).and_then(|c| c.rendezvous_target()),
+                    ..VehicleStateFileStorage::new(*id, sv, docked)
+                }
+            })
+            .collect();
+        vehicles.sort_by_key(|v| v.id);
+        UniverseFileStorage {
+            vehicles,
+            ..self.to_file_storage_without_vehicles()
+        }
+    }
+
+    /// Everything but the vehicles, for saves which write each vehicle
+    /// out on its own.
+    pub fn to_file_storage_without_vehicles(&self) -> UniverseFileStorage {
+        let mut sites: Vec<_> = self
+            .landing_pads
+            .iter()
+            .map(|(id, pad)| SiteFileStorage {
+                id: *id,
+                name: pad.name.clone(),
+                planet_id: pad.planet_id,
+                angle: pad.angle,
+                width: pad.width,
+                development: pad.development,
+                deliveries: pad.deliveries,
+                structures_built: pad.structures_built,
+            })
+            .collect();
+        sites.sort_by_key(|s| s.id);
+
+        UniverseFileStorage {
+            version: UNIVERSE_FILE_VERSION,
+            stamp: self.stamp,
+            ticks: self.ticks,
+            ids: self.ids.clone(),
+            bodies: Scenario::from_system("", &self.planets).bodies,
+            vehicles: Vec::new(),
+            pending: self.loading.iter().cloned().collect(),
+            sites,
+            constellations: sorted(&self.constellations),
+            structures: sorted(&self.structures),
+            terrain: sorted(&self.terrain).into_iter().map(|(_, t)| t).collect(),
+            waypoints: sorted(&self.waypoints),
+            transfers: self.transfers.clone(),
+            quarantine: self
+                .quarantine
+                .iter()
+                .map(|q| QuarantineFileStorage {
+                    vehicle: VehicleStateFileStorage::new(q.id, &q.vehicle, &q.docked),
+                    reason: q.reason,
+                    quarantined_at: q.quarantined_at,
+                    group: q.group,
+                })
+                .collect(),
+        }
+    }
+
+    /// Rebuilds a saved universe, putting vehicles together out of
+    /// `parts`. Vehicles which can't be built are left out, and their IDs
+    /// returned alongside.
+    pub fn from_file_storage(
+        storage: UniverseFileStorage,
+        parts: &HashMap<String, PartPrototype>,
+    ) -> Result<(Universe, Vec<EntityId>), UniverseFileError> {
+        let storage = storage.migrate()?;
+        let scenario = Scenario {
+            bodies: storage.bodies,
+            ..Default::default()
+        };
+        let planets = scenario.to_system().map_err(UniverseFileError::BadSystem)?;
+
+        let mut universe = Universe::new(planets);
+        universe.stamp = storage.stamp;
+        universe.ticks = storage.ticks;
+        universe.ids = storage.ids;
+
+        for s in storage.sites {
+            let mut pad = LandingPad::new(s.name, s.planet_id, s.angle, s.width);
+            pad.development = s.development;
+            pad.deliveries = s.deliveries;
+            pad.structures_built = s.structures_built;
+            universe.landing_pads.insert(s.id, pad);
+        }
+
+        let mut failed = Vec::new();
+        let mut rendezvous = Vec::new();
+        for v in storage.vehicles {
+            let Ok(sv) = v.build(parts) else {
+                failed.push(v.id);
+                continue;
+            };
+            universe.surface_vehicles.insert(v.id, sv);
+            if !v.docked.is_empty() {
+                universe.docked.insert(v.id, v.docked);
+            }
+            rendezvous.extend(v.rendezvous.map(|target| (v.id, target)));
+        }
+        for q in storage.quarantine {
+            let Ok(vehicle) = q.vehicle.build(parts) else {
+                failed.push(q.vehicle.id);
+                continue;
+            };
+            universe.quarantine.insert(QuarantinedEntity {
+                id: q.vehicle.id,
+                reason: q.reason,
+                quarantined_at: q.quarantined_at,
+                vehicle,
+                group: q.group,
+                docked: q.vehicle.docked,
+            });
+        }
+        for p in storage.pending {
+            universe.loading.push(p);
+        }
+
+        universe.constellations = storage
+            .constellations
+            .into_iter()
+            .filter(|(id, _)| universe.surface_vehicles.contains_key(id))
+            .collect();
+        universe.replace_structures(
+            storage
+                .structures
+                .into_iter()
+                .map(|(id, s)| (Some(id), s))
+                .collect(),
+        );
+        // vehicles which can't find a way there any more are left idle
+        for (id, target) in rendezvous {
+            _ = universe.start_rendezvous(id, target);
+        }
+        universe.transfers = storage
+            .transfers
+            .into_iter()
+            .filter(|t| {
+                universe.surface_vehicles.contains_key(&t.source())
+                    && universe.surface_vehicles.contains_key(&t.destination())
+            })
+            .collect();
+        universe.replace_terrain(storage.terrain);
+        universe.replace_waypoints(
+            storage
+                .waypoints
+                .into_iter()
+                .map(|(id, w)| (Some(id), w))
+                .collect(),
+        );
+
+        Ok((universe, failed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::universe_in_leo;
+    use std::collections::HashSet;
+
+    #[test]
+    fn universes_survive_saving_and_refuse_newer_files() {
+        let frame = Generic::new(
+            "frame".to_string(),
+            UVec2::new(10, 10),
+            PartLayer::Structural,
+            Mass::kilograms(400),
+        );
+        let parts = HashMap::from([("frame".to_string(), PartPrototype::Generic(frame))]);
+        let design = VehicleFileStorage {
+            name: "Box".to_string(),
+            parts: vec![VehiclePartFileStorage {
+                partname: "frame".to_string(),
+                pos: IVec2::ZERO,
+                rot: Rotation::East,
+                stage: 0,
+            }],
+            lines: HashSet::new(),
+            loadouts: Vec::new(),
+        };
+
+        let mut universe = Universe::new(crate::examples::rss());
+        let luna = EntityId::named(EntityKind::Planet, "Luna");
+        let a = universe
+            .add_surface_vehicle(luna, design.build("A".into(), &parts).unwrap(), 0.0, 1000.0)
+            .unwrap();
+        let b = universe
+            .add_surface_vehicle(luna, design.build("B".into(), &parts).unwrap(), 1.0, 1000.0)
+            .unwrap();
+        universe.surface_vehicles.get_mut(&a).unwrap().set_target(b);
+        let group = universe.create_group([a, b]);
+        let pad = universe
+            .add_landing_pad(LandingPad::new("Pad", luna, 0.5, 40.0))
+            .unwrap();
+        universe.landing_pads.get_mut(&pad).unwrap().deliveries = 3;
+        universe.run_batch_ticks(10);
+
+        // round trip through the same encoding as the save archive
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&universe.to_file_storage(), &mut bytes).unwrap();
+        let storage: UniverseFileStorage = ciborium::from_reader(bytes.as_slice()).unwrap();
+        let (mut loaded, failed) = Universe::from_file_storage(storage.clone(), &parts).unwrap();
+
+        assert!(failed.is_empty());
+        assert_eq!(loaded.stamp(), universe.stamp());
+        assert_eq!(loaded.planets.planet_ids(), universe.planets.planet_ids());
+        assert_eq!(loaded.surface_vehicles.len(), 2);
+        assert_eq!(
+            loaded.surface_vehicles[&a].pv(),
+            universe.surface_vehicles[&a].pv()
+        );
+        assert_eq!(loaded.surface_vehicles[&a].target(), Some(b));
+        assert_eq!(loaded.surface_vehicles[&b].vehicle().name(), "B");
+        assert_eq!(loaded.group_membership(&b), Some(group));
+        assert_eq!(loaded.landing_pads[&pad].deliveries, 3);
+        assert_eq!(loaded.structures.len(), universe.structures.len());
+
+        // new IDs carry on from the saved ones
+        let next = loaded.create_group([a]);
+        assert!(next != group && next.kind() == EntityKind::Group);
+
+        let mut missing = storage.clone();
+        missing.vehicles[0].design.parts[0].partname = "gone".into();
+        let (loaded, failed) = Universe::from_file_storage(missing, &parts).unwrap();
+        assert_eq!(failed, vec![a]);
+        assert_eq!(loaded.group_membership(&a), None);
+
+        let mut newer = storage;
+        newer.version = UNIVERSE_FILE_VERSION + 1;
+        assert!(matches!(
+            Universe::from_file_storage(newer, &parts),
+            Err(UniverseFileError::TooNew(_))
+        ));
+    }
+
+    #[test]
+    fn rendezvous_autopilots_carry_on_after_loading() {
+        let (mut universe, low) = universe_in_leo();
+        let body = universe.planets.body;
+        let high = SparseOrbit::circular(body.radius + 450_000.0, body, universe.stamp(), false);
+        let high = GlobalOrbit(low.0, high);
+        let chaser = universe.add_orbital_vehicle(Vehicle::new(), low).unwrap();
+        let target = universe.add_orbital_vehicle(Vehicle::new(), high).unwrap();
+        universe.start_rendezvous(chaser, target).unwrap();
+
+        let storage = universe.to_file_storage();
+        let (loaded, _) = Universe::from_file_storage(storage.clone(), &HashMap::new()).unwrap();
+        let ctrl = &loaded.rendezvous[&chaser];
+        assert_eq!(ctrl.rendezvous_target(), Some(target));
+        assert!(loaded.maneuvers.get(chaser).is_some());
+
+        // with the target gone, there's nothing to plan for
+        let mut lost = storage;
+        lost.vehicles.retain(|v| v.id != target);
+        let (loaded, _) = Universe::from_file_storage(lost, &HashMap::new()).unwrap();
+        assert!(loaded.rendezvous.is_empty());
+        assert!(loaded.maneuvers.get(chaser).is_none());
+    }
+
+    #[test]
+    fn vehicles_keep_their_fuel_cargo_and_build_progress() {
+        let tank = PartPrototype::Tank(TankModel::new(
+            "tank".to_string(),
+            UVec2::new(10, 10),
+            Mass::kilograms(100),
+            Mass::kilograms(1000),
+        ));
+        let engine = PartPrototype::Thruster(ThrusterModel::main_thruster(50_000.0, 3000.0));
+        let hold = PartPrototype::Cargo(Cargo::new(
+            "hold".to_string(),
+            Mass::kilograms(100),
+            Mass::kilograms(1000),
+            UVec2::new(10, 10),
+        ));
+        let parts: HashMap<_, _> = [tank, engine, hold]
+            .into_iter()
+            .map(|p| (p.sprite_path().to_string(), p))
+            .collect();
+        let mut vehicle = Vehicle::from_parts(
+            "Lander".to_string(),
+            "LND".to_string(),
+            vec![
+                (IVec2::new(-30, 0), Rotation::East, parts[""].clone()),
+                (IVec2::ZERO, Rotation::East, parts["tank"].clone()),
+                (IVec2::new(10, 0), Rotation::East, parts["hold"].clone()),
+            ],
+            HashSet::new(),
+        );
+        let mut loadout = Loadout::new("full", 0.8);
+        loadout.fuel_item = Item::Methane;
+        loadout.manifest.push((Item::Iron, Mass::kilograms(250)));
+        vehicle.apply_loadout(&loadout);
+        // the engine and tank are finished, but the hold is only started
+        let ids: Vec<PartId> = vehicle.parts_in_order().map(|(id, _)| *id).collect();
+        for _ in 0..2000 {
+            vehicle.build_part(ids[0]);
+            vehicle.build_part(ids[1]);
+        }
+        for _ in 0..7 {
+            vehicle.build_part(ids[2]);
+        }
+
+        let mut universe = Universe::new(crate::examples::rss());
+        let luna = EntityId::named(EntityKind::Planet, "Luna");
+        let id = universe
+            .add_surface_vehicle(luna, vehicle, 0.0, 1000.0)
+            .unwrap();
+
+        // through the same encoding as uncompressed saves
+        let yaml = serde_yaml::to_string(&universe.to_file_storage()).unwrap();
+        let storage = serde_yaml::from_str(&yaml).unwrap();
+        let (loaded, failed) = Universe::from_file_storage(storage, &parts).unwrap();
+        assert!(failed.is_empty());
+        let (before, after) = (
+            universe.surface_vehicles[&id].vehicle(),
+            loaded.surface_vehicles[&id].vehicle(),
+        );
+        assert!(before.fuel_percentage() > 0.0);
+        assert_eq!(after.fuel_percentage(), before.fuel_percentage());
+        assert!(before.remaining_dv() > 0.0);
+        assert_eq!(after.remaining_dv(), before.remaining_dv());
+        assert_eq!(after.total_mass(), before.total_mass());
+        assert_eq!(after.item_mass(Item::Iron), Mass::kilograms(250));
+        let built = |v: &Vehicle| -> Vec<u32> {
+            v.parts_in_order()
+                .map(|(_, p)| p.builds_performed())
+                .collect()
+        };
+        assert_eq!(built(after), built(before));
+        assert_eq!(built(after)[2], 7);
+    }
+}
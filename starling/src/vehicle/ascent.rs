@@ -0,0 +1,240 @@
+use crate::prelude::*;
+
+/// Guidance parameters for the launch-to-orbit autopilot. The vehicle
+/// climbs vertically until `pitch_start_altitude`, then pitches over
+/// linearly with altitude until it is `pitch_end_angle` radians off
+/// vertical at `pitch_end_altitude`. The throttle is set to hold
+/// `target_accel` until the apoapsis is high enough.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AscentProfile {
+    pub target_altitude: f64,
+    pub pitch_start_altitude: f64,
+    pub pitch_end_altitude: f64,
+    pub pitch_end_angle: f64,
+    pub target_accel: f64,
+}
+
+/// A tunable parameter of an `AscentProfile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AscentParam {
+    TargetAltitude,
+    PitchStart,
+    PitchEnd,
+    PitchAngle,
+    Acceleration,
+}
+
+impl AscentParam {
+    pub const ALL: [AscentParam; 5] = [
+        Self::TargetAltitude,
+        Self::PitchStart,
+        Self::PitchEnd,
+        Self::PitchAngle,
+        Self::Acceleration,
+    ];
+}
+
+impl std::fmt::Display for AscentParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TargetAltitude => write!(f, "Target alt."),
+            Self::PitchStart => write!(f, "Pitch start"),
+            Self::PitchEnd => write!(f, "Pitch end"),
+            Self::PitchAngle => write!(f, "Pitch angle"),
+            Self::Acceleration => write!(f, "Accel."),
+        }
+    }
+}
+
+/// Apoapsis the ascent aims for, above the target altitude, so that the
+/// circularization burn has some margin.
+pub const ASCENT_APOAPSIS_MARGIN: f64 = 10_000.0;
+
+impl AscentProfile {
+    pub fn new(target_altitude: f64) -> Self {
+        Self {
+            target_altitude,
+            pitch_start_altitude: 1_000.0,
+            pitch_end_altitude: 13_000.0,
+            pitch_end_angle: PI_64 / 2.0,
+            target_accel: 16.0,
+        }
+    }
+
+    /// Defaults scaled to the vehicle's thrust-to-weight ratio on the given
+    /// planet. Sluggish vehicles climb higher before pitching over, and
+    /// pitch over more gradually.
+    pub fn for_vehicle(vehicle: &Vehicle, planet: &Body, target_altitude: f64) -> Self {
+        let gravity = planet.gravity(DVec2::X * planet.radius).length();
+        let max_accel = vehicle.max_forward_thrust() / vehicle.total_mass().to_kg_f64();
+        let twr = if gravity > 0.0 {
+            max_accel / gravity
+        } else {
+            f64::INFINITY
+        };
+        let sluggishness = (2.0 / twr).clamp(0.5, 4.0);
+        let start = 1_000.0 * sluggishness;
+        Self {
+            target_altitude,
+            pitch_start_altitude: start,
+            pitch_end_altitude: start + 12_000.0 * sluggishness.max(1.0),
+            pitch_end_angle: PI_64 / 2.0,
+            target_accel: (gravity * 1.6).min(max_accel).max(0.0),
+        }
+    }
+
+    /// Angle off vertical commanded at the given altitude.
+    pub fn pitch_angle(&self, altitude: f64) -> f64 {
+        let span = (self.pitch_end_altitude - self.pitch_start_altitude).max(1.0);
+        let s = ((altitude - self.pitch_start_altitude) / span).clamp(0.0, 1.0);
+        s * self.pitch_end_angle
+    }
+
+    pub fn throttle(&self, vehicle: &Vehicle) -> f32 {
+        let max_accel = vehicle.max_forward_thrust() / vehicle.total_mass().to_kg_f64();
+        if max_accel <= 0.0 {
+            return 0.0;
+        }
+        (self.target_accel / max_accel).clamp(0.0, 1.0) as f32
+    }
+
+    pub fn value(&self, param: AscentParam) -> f64 {
+        match param {
+            AscentParam::TargetAltitude => self.target_altitude,
+            AscentParam::PitchStart => self.pitch_start_altitude,
+            AscentParam::PitchEnd => self.pitch_end_altitude,
+            AscentParam::PitchAngle => self.pitch_end_angle,
+            AscentParam::Acceleration => self.target_accel,
+        }
+    }
+
+    pub fn value_str(&self, param: AscentParam) -> String {
+        match param {
+            AscentParam::PitchAngle => format!("{:0.0} deg", self.pitch_end_angle.to_degrees()),
+            AscentParam::Acceleration => format!("{:0.1} m/s2", self.target_accel),
+            _ => distance_str(self.value(param)),
+        }
+    }
+
+    /// Nudges a parameter up or down by a number of steps, keeping the
+    /// profile self-consistent.
+    pub fn adjust(&mut self, param: AscentParam, steps: i32) {
+        let steps = steps as f64;
+        match param {
+            AscentParam::TargetAltitude => {
+                self.target_altitude = (self.target_altitude + steps * 50_000.0).max(50_000.0);
+            }
+            AscentParam::PitchStart => {
+                self.pitch_start_altitude = (self.pitch_start_altitude + steps * 500.0).max(0.0);
+            }
+            AscentParam::PitchEnd => {
+                self.pitch_end_altitude += steps * 1_000.0;
+            }
+            AscentParam::PitchAngle => {
+                self.pitch_end_angle =
+                    (self.pitch_end_angle + steps * 5.0f64.to_radians()).clamp(0.0, PI_64 / 2.0);
+            }
+            AscentParam::Acceleration => {
+                self.target_accel = (self.target_accel + steps).max(1.0);
+            }
+        }
+        self.pitch_end_altitude = self
+            .pitch_end_altitude
+            .max(self.pitch_start_altitude + 1_000.0);
+    }
+
+    /// Predicts the ascent trajectory, relative to the planet center, from
+    /// the given state until apoapsis. The vehicle is treated as a point
+    /// mass with constant `max_accel` which instantly holds the commanded
+    /// attitude; fuel burn and drag are ignored.
+    pub fn predict(&self, planet: &Body, pv: PV, max_accel: f64) -> Vec<DVec2> {
+        let dt = 1.0;
+        let accel = self.target_accel.min(max_accel);
+        let target_apoapsis = planet.radius + self.target_altitude + ASCENT_APOAPSIS_MARGIN;
+
+        let mut pos = pv.pos;
+        let mut vel = pv.vel;
+        let mut ret = vec![pos];
+
+        for i in 0..3600 {
+            let r = pos.length();
+            if r < planet.radius && i > 0 {
+                break;
+            }
+
+            let up = pos.normalize_or_zero();
+            let burning = apoapsis_radius(planet, pos, vel) < target_apoapsis;
+            if !burning && vel.dot(up) <= 0.0 {
+                break;
+            }
+
+            let mut a = planet.gravity(pos);
+            if burning {
+                let angle = self.pitch_angle(r - planet.radius);
+                a += rotate_f64(up, angle) * accel;
+            }
+            vel += a * dt;
+            pos += vel * dt;
+
+            if i % 5 == 0 {
+                ret.push(pos);
+            }
+        }
+
+        ret.push(pos);
+        ret
+    }
+}
+
+/// Apoapsis radius of the osculating orbit, or infinity if unbound.
+fn apoapsis_radius(planet: &Body, pos: DVec2, vel: DVec2) -> f64 {
+    let r = pos.length();
+    let energy = vel.length_squared() / 2.0 - planet.mu() / r;
+    if energy >= 0.0 {
+        return f64::INFINITY;
+    }
+    let a = -planet.mu() / (2.0 * energy);
+    let h = pos.perp_dot(vel);
+    let e = (1.0 + 2.0 * energy * h * h / planet.mu().powi(2))
+        .max(0.0)
+        .sqrt();
+    a * (1.0 + e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pitch_program_and_adjustment() {
+        let mut profile = AscentProfile::new(400_000.0);
+        assert_eq!(profile.pitch_angle(0.0), 0.0);
+        assert_eq!(profile.pitch_angle(7_000.0), PI_64 / 4.0);
+        assert_eq!(profile.pitch_angle(50_000.0), PI_64 / 2.0);
+
+        profile.adjust(AscentParam::PitchStart, 40);
+        assert_eq!(profile.pitch_start_altitude, 21_000.0);
+        assert_eq!(profile.pitch_end_altitude, 22_000.0);
+
+        profile.adjust(AscentParam::PitchAngle, 3);
+        assert_eq!(profile.pitch_end_angle, PI_64 / 2.0);
+
+        profile.adjust(AscentParam::TargetAltitude, -100);
+        assert_eq!(profile.target_altitude, 50_000.0);
+    }
+
+    #[test]
+    fn predicted_ascent_reaches_target_apoapsis() {
+        let planet = Body::LUNA;
+        let profile = AscentProfile::new(100_000.0);
+        let pv = PV::from_f64(DVec2::X * planet.radius, DVec2::ZERO);
+
+        let grounded = profile.predict(&planet, pv, 0.0);
+        assert!(grounded.iter().all(|p| p.length() <= planet.radius + 1.0));
+
+        let path = profile.predict(&planet, pv, 20.0);
+        let apoapsis = path.last().unwrap().length() - planet.radius;
+        let target = profile.target_altitude + ASCENT_APOAPSIS_MARGIN;
+        assert!((apoapsis - target).abs() < 5_000.0, "{}", apoapsis);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::math::*;
+use crate::parts::*;
+use crate::vehicle::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::Path;
+
+/// Named group of parts, and the pipes between them, saved from a region
+/// of a vehicle so it can be placed into other vehicles as a unit.
+/// Positions are in pixels, relative to the lower left corner of the
+/// group. Parts placed from an assembly are ordinary parts, so they can be
+/// captured again as part of a larger assembly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Assembly {
+    pub name: String,
+    pub dims: UVec2,
+    pub parts: Vec<VehiclePartFileStorage>,
+    pub lines: HashSet<IVec2>,
+}
+
+impl Assembly {
+    /// Captures the parts lying entirely within the given pixel bounds,
+    /// along with any pipes among them. None if there are no such parts.
+    pub fn from_region(
+        name: impl Into<String>,
+        vehicle: &Vehicle,
+        lower: IVec2,
+        upper: IVec2,
+    ) -> Option<Self> {
+        let inside: Vec<_> = vehicle
+            .parts()
+            .map(|(_, p)| p)
+            .filter(|p| {
+                let end = p.origin() + p.dims_grid().as_ivec2();
+                p.origin().cmpge(lower).all() && end.cmple(upper).all()
+            })
+            .collect();
+
+        let min = inside.iter().map(|p| p.origin()).reduce(IVec2::min)?;
+        let max = inside
+            .iter()
+            .map(|p| p.origin() + p.dims_grid().as_ivec2())
+            .reduce(IVec2::max)?;
+
+        let parts = inside
+            .iter()
+            .map(|p| VehiclePartFileStorage {
+                partname: p.prototype().sprite_path().to_string(),
+                pos: p.origin() - min,
+                rot: p.rotation(),
+                stage: 0,
+            })
+            .collect();
+
+        let lines = vehicle
+            .pipes()
+            .filter(|p| p.cmpge(min).all() && p.cmplt(max).all())
+            .map(|p| p - min)
+            .collect();
+
+        Some(Self {
+            name: name.into(),
+            dims: (max - min).as_uvec2(),
+            parts,
+            lines,
+        })
+    }
+
+    /// Parts of the assembly with its lower left corner at `origin`.
+    pub fn placed(
+        &self,
+        origin: IVec2,
+        prototypes: &HashMap<String, PartPrototype>,
+    ) -> Result<Vec<(IVec2, Rotation, PartPrototype)>, NoPartError> {
+        self.parts
+            .iter()
+            .map(|p| {
+                let proto = prototypes
+                    .get(&p.partname)
+                    .ok_or(NoPartError(p.partname.clone()))?;
+                Ok((origin + p.pos, p.rot, proto.clone()))
+            })
+            .collect()
+    }
+
+    /// Pipes of the assembly with its lower left corner at `origin`.
+    pub fn pipes_at(&self, origin: IVec2) -> impl Iterator<Item = IVec2> + use<'_> {
+        self.lines.iter().map(move |p| origin + *p)
+    }
+}
+
+/// Reads every assembly in the directory, sorted by name. Files which
+/// can't be read are skipped.
+pub fn load_assemblies_from_dir(path: &Path) -> Vec<Assembly> {
+    let mut ret: Vec<Assembly> = std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let s = std::fs::read_to_string(entry.path()).ok()?;
+            serde_yaml::from_str(&s).ok()
+        })
+        .collect();
+    ret.sort_by(|a, b| a.name.cmp(&b.name));
+    ret
+}
+
+pub fn write_assembly_to_file(assembly: &Assembly, filename: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(assembly)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Mass;
+
+    #[test]
+    fn assemblies_capture_and_place_parts() {
+        let block = PartPrototype::Generic(Generic::new(
+            "block".to_string(),
+            UVec2::new(10, 10),
+            PartLayer::Structural,
+            Mass::kilograms(400),
+        ));
+        let prototypes = HashMap::from([("block".to_string(), block.clone())]);
+
+        let vehicle = Vehicle::from_parts(
+            "".to_string(),
+            "".to_string(),
+            vec![
+                (IVec2::new(20, 20), Rotation::East, block.clone()),
+                (IVec2::new(30, 20), Rotation::North, block.clone()),
+                (IVec2::new(100, 100), Rotation::East, block.clone()),
+            ],
+            HashSet::from([IVec2::new(25, 25), IVec2::new(90, 90)]),
+        );
+
+        assert!(Assembly::from_region("none", &vehicle, IVec2::ZERO, IVec2::splat(5)).is_none());
+
+        let a =
+            Assembly::from_region("pair", &vehicle, IVec2::splat(15), IVec2::splat(50)).unwrap();
+        assert_eq!(a.dims, UVec2::new(20, 10));
+        assert_eq!(a.parts.len(), 2);
+        assert_eq!(a.lines, HashSet::from([IVec2::new(5, 5)]));
+
+        let origin = IVec2::new(-40, 0);
+        let placed = a.placed(origin, &prototypes).unwrap();
+        assert!(placed
+            .iter()
+            .any(|(p, rot, _)| *p == IVec2::new(-30, 0) && *rot == Rotation::North));
+        assert_eq!(
+            a.pipes_at(origin).collect::<Vec<_>>(),
+            vec![IVec2::new(-35, 5)]
+        );
+        assert!(a.placed(origin, &HashMap::new()).is_err());
+
+        let yaml = serde_yaml::to_string(&a).unwrap();
+        let back: Assembly = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back, a);
+    }
+}
@@ -3,7 +3,7 @@ use crate::math::*;
 use crate::vehicle::PartId;
 use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ConnectivityGroup {
     transport_lines: HashSet<IVec2>,
     connections: HashMap<PartId, IVec2>,
@@ -0,0 +1,220 @@
+use crate::math::*;
+use crate::orbits::{Body, SparseOrbit};
+use crate::vehicle::*;
+
+/// Everything a control law may use to compute a command. Positions and
+/// velocities are relative to the parent body.
+pub struct ControlContext<'a> {
+    pub body: &'a RigidBody,
+    pub vehicle: &'a Vehicle,
+    pub parent: &'a Body,
+    pub orbit: Option<&'a SparseOrbit>,
+    pub gravity: DVec2,
+}
+
+impl ControlContext<'_> {
+    pub fn altitude(&self) -> f64 {
+        self.body.pv.pos.length() - self.parent.radius
+    }
+
+    pub fn max_accel(&self) -> f64 {
+        self.vehicle.max_forward_thrust() / self.vehicle.total_mass().to_kg_f64()
+    }
+}
+
+/// A stateless policy mapping the current vehicle state to thruster and
+/// attitude commands. Laws are registered by name in a `ControlLawRegistry`
+/// and assigned to vehicles with `VehicleControlPolicy::ControlLaw`.
+pub trait ControlLaw: std::fmt::Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    fn control(&self, ctx: &ControlContext) -> (VehicleControl, VehicleControlStatus);
+}
+
+/// Points the main engine along `accel` and throttles to achieve it once
+/// the vehicle is roughly aligned.
+fn track_acceleration(ctx: &ControlContext, accel: DVec2) -> VehicleControl {
+    let mut ctrl = VehicleControl::NULLOPT;
+    let target_angle = accel.to_angle();
+    let angle_error = wrap_pi_npi_f64(target_angle - ctx.body.angle);
+    ctrl.attitude = ctx
+        .vehicle
+        .attitude_controller
+        .apply(angle_error, ctx.body.angular_velocity);
+    let max_accel = ctx.max_accel();
+    if angle_error.abs() < 0.1 && max_accel > 0.0 {
+        ctrl.plus_x.throttle = (accel.length() / max_accel).clamp(0.0, 1.0) as f32;
+    }
+    ctrl
+}
+
+/// Brings the vehicle to rest relative to its parent body.
+#[derive(Debug)]
+pub struct VelocityDamper;
+
+impl ControlLaw for VelocityDamper {
+    fn name(&self) -> &'static str {
+        "velocity-damper"
+    }
+
+    fn description(&self) -> &'static str {
+        "Null velocity relative to the parent body"
+    }
+
+    fn control(&self, ctx: &ControlContext) -> (VehicleControl, VehicleControlStatus) {
+        let vel = ctx.body.pv.vel;
+        if vel.length() < 0.2 {
+            return (VehicleControl::NULLOPT, VehicleControlStatus::Done);
+        }
+        let accel = -vel / 2.0 - ctx.gravity;
+        (
+            track_acceleration(ctx, accel),
+            VehicleControlStatus::InProgress,
+        )
+    }
+}
+
+/// Falls freely until the last possible moment, then burns at full
+/// throttle to arrive at the surface with near-zero velocity.
+#[derive(Debug)]
+pub struct HoverSlam;
+
+impl ControlLaw for HoverSlam {
+    fn name(&self) -> &'static str {
+        "hover-slam"
+    }
+
+    fn description(&self) -> &'static str {
+        "Suicide burn to touch down at minimum fuel cost"
+    }
+
+    fn control(&self, ctx: &ControlContext) -> (VehicleControl, VehicleControlStatus) {
+        let vel = ctx.body.pv.vel;
+        let altitude = ctx.altitude();
+        let speed = vel.length();
+
+        if altitude < 5.0 && speed < 1.0 {
+            return (VehicleControl::NULLOPT, VehicleControlStatus::Done);
+        }
+
+        let mut ctrl = track_acceleration(ctx, -vel);
+        ctrl.plus_x.throttle = 0.0;
+
+        let net_decel = ctx.max_accel() - ctx.gravity.length();
+        if net_decel <= 0.0 {
+            ctrl.plus_x.throttle = 1.0;
+            return (ctrl, VehicleControlStatus::StopFalling);
+        }
+
+        let stopping_distance = speed * speed / (2.0 * net_decel);
+        let status = if stopping_distance * 1.1 + 10.0 >= altitude {
+            let angle_error = wrap_pi_npi_f64((-vel).to_angle() - ctx.body.angle);
+            if angle_error.abs() < 0.2 {
+                ctrl.plus_x.throttle = 1.0;
+            }
+            VehicleControlStatus::StopFalling
+        } else {
+            VehicleControlStatus::InProgress
+        };
+
+        (ctrl, status)
+    }
+}
+
+/// Holds the current horizontal velocity while zeroing vertical velocity.
+#[derive(Debug)]
+pub struct Cruise;
+
+impl ControlLaw for Cruise {
+    fn name(&self) -> &'static str {
+        "cruise"
+    }
+
+    fn description(&self) -> &'static str {
+        "Hold altitude and horizontal speed"
+    }
+
+    fn control(&self, ctx: &ControlContext) -> (VehicleControl, VehicleControlStatus) {
+        let up = ctx.body.pv.pos.normalize_or_zero();
+        let vel = ctx.body.pv.vel;
+        let vertical = up * up.dot(vel);
+        let accel = -vertical / 2.0 - ctx.gravity;
+        (
+            track_acceleration(ctx, accel),
+            VehicleControlStatus::InProgress,
+        )
+    }
+}
+
+/// Named collection of control laws available to vehicles.
+#[derive(Debug, Default)]
+pub struct ControlLawRegistry {
+    laws: Vec<Box<dyn ControlLaw>>,
+}
+
+impl ControlLawRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_defaults() -> Self {
+        let mut reg = Self::new();
+        reg.register(Box::new(VelocityDamper));
+        reg.register(Box::new(HoverSlam));
+        reg.register(Box::new(Cruise));
+        reg
+    }
+
+    /// Adds a law, replacing any existing law with the same name.
+    pub fn register(&mut self, law: Box<dyn ControlLaw>) {
+        self.laws.retain(|l| l.name() != law.name());
+        self.laws.push(law);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn ControlLaw> {
+        self.laws
+            .iter()
+            .find(|l| l.name() == name)
+            .map(|l| l.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ControlLaw> + use<'_> {
+        self.laws.iter().map(|l| l.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Coast;
+
+    impl ControlLaw for Coast {
+        fn name(&self) -> &'static str {
+            "cruise"
+        }
+
+        fn description(&self) -> &'static str {
+            "Do nothing"
+        }
+
+        fn control(&self, _ctx: &ControlContext) -> (VehicleControl, VehicleControlStatus) {
+            (VehicleControl::NULLOPT, VehicleControlStatus::Idling)
+        }
+    }
+
+    #[test]
+    fn registry_replaces_by_name() {
+        let mut reg = ControlLawRegistry::with_defaults();
+        assert_eq!(reg.iter().count(), 3);
+        assert!(reg.get("hover-slam").is_some());
+        assert!(reg.get("warp-drive").is_none());
+
+        reg.register(Box::new(Coast));
+        assert_eq!(reg.iter().count(), 3);
+        assert_eq!(reg.get("cruise").unwrap().description(), "Do nothing");
+    }
+}
@@ -0,0 +1,398 @@
+use crate::math::*;
+use crate::parts::*;
+use crate::vehicle::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How a vehicle was joined onto another at their docking ports, kept so
+/// that it can be separated again as it was.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DockingJoint {
+    name: String,
+    model: String,
+    /// Parts of the composite which came from the docked vehicle.
+    parts: Vec<PartId>,
+    /// Pipes laid through the mated ports so that the two vehicles share
+    /// propellant, which come out again on undocking.
+    pipes: Vec<IVec2>,
+    quarter_turns: u32,
+    offset: IVec2,
+    port: PartId,
+}
+
+impl DockingJoint {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parts(&self) -> &[PartId] {
+        &self.parts
+    }
+
+    /// Quarter turns by which the docked vehicle's layout was turned to
+    /// join the composite.
+    pub fn quarter_turns(&self) -> u32 {
+        self.quarter_turns
+    }
+
+    /// Where the origin of the docked vehicle's layout landed in the
+    /// composite, in meters.
+    pub fn offset_meters(&self) -> DVec2 {
+        self.offset.as_dvec2() / PIXELS_PER_METER as f64
+    }
+
+    /// The docked vehicle's port, as a part of the composite.
+    pub fn port(&self) -> PartId {
+        self.port
+    }
+
+    /// The same joint on a composite whose parts were given new IDs, as
+    /// by `Vehicle::renumbered_part_ids`.
+    pub fn renumbered(&self, ids: &HashMap<PartId, PartId>) -> Self {
+        let renumber = |id: &PartId| ids.get(id).copied().unwrap_or(*id);
+        Self {
+            parts: self.parts.iter().map(renumber).collect(),
+            port: renumber(&self.port),
+            ..self.clone()
+        }
+    }
+}
+
+fn rotation_index(rot: Rotation) -> u32 {
+    match rot {
+        Rotation::East => 0,
+        Rotation::North => 1,
+        Rotation::West => 2,
+        Rotation::South => 3,
+    }
+}
+
+/// Middle of the outer face of a docking port, on the vehicle's grid.
+fn port_face(part: &InstantiatedPart) -> IVec2 {
+    let o = part.origin();
+    let d = part.dims_grid().as_ivec2();
+    match part.rotation() {
+        Rotation::East => IVec2::new(o.x + d.x, o.y + d.y / 2),
+        Rotation::North => IVec2::new(o.x + d.x / 2, o.y + d.y),
+        Rotation::West => IVec2::new(o.x, o.y + d.y / 2),
+        Rotation::South => IVec2::new(o.x + d.x / 2, o.y),
+    }
+}
+
+/// Cells along the middle of a docking port from its inner face to its
+/// outer one, through which the pipes of two docked vehicles are joined.
+fn port_pipe_cells(part: &InstantiatedPart) -> Vec<IVec2> {
+    let o = part.origin();
+    let d = part.dims_grid().as_ivec2();
+    match part.rotation() {
+        Rotation::East | Rotation::West => (0..d.x)
+            .map(|x| IVec2::new(o.x + x, o.y + d.y / 2))
+            .collect(),
+        Rotation::North | Rotation::South => (0..d.y)
+            .map(|y| IVec2::new(o.x + d.x / 2, o.y + y))
+            .collect(),
+    }
+}
+
+impl Vehicle {
+    /// Docking ports, with the middle of each one's outer face in meters
+    /// and the direction it faces.
+    pub fn docking_ports(&self) -> impl Iterator<Item = (PartId, DVec2, Rotation)> + use<'_> {
+        self.parts()
+            .filter(|(_, p)| p.as_docking_port().is_some())
+            .map(|(id, p)| {
+                let face = port_face(p).as_dvec2() / PIXELS_PER_METER as f64;
+                (*id, face, p.rotation())
+            })
+    }
+
+    /// Joins another vehicle onto this one, with its port `theirs` mated
+    /// face to face with this vehicle's port `ours`. The pipes of the two
+    /// are run together through the ports.
+    pub fn dock(&mut self, other: &Vehicle, ours: PartId, theirs: PartId) -> Option<DockingJoint> {
+        let host_port = self.get_part(ours)?.clone();
+        let guest_port = other.get_part(theirs)?;
+        host_port.as_docking_port()?;
+        guest_port.as_docking_port()?;
+
+        // the guest is turned to face the host's port, then moved so the
+        // outer faces of the two ports meet
+        let quarter_turns =
+            (rotation_index(host_port.rotation()) + 6 - rotation_index(guest_port.rotation())) % 4;
+        let mut guest = other.clone();
+        guest.transform_layout(quarter_turns, IVec2::ZERO);
+        let turned = port_face(guest.get_part(theirs)?);
+        let offset = port_face(&host_port) - turned;
+        guest.transform_layout(0, offset);
+        let guest_port = guest.get_part(theirs)?.clone();
+
+        let parts = self.attach(guest);
+        let port = parts.iter().copied().find(|id| {
+            self.get_part(*id)
+                .is_some_and(|p| p.origin() == guest_port.origin() && p.as_docking_port().is_some())
+        })?;
+
+        let mut pipes = port_pipe_cells(&host_port);
+        pipes.extend(port_pipe_cells(&guest_port));
+        pipes.retain(|p| !self.pipes().any(|q| q == *p));
+        self.add_pipes(pipes.iter().copied());
+
+        Some(DockingJoint {
+            name: other.name().to_string(),
+            model: other.model().to_string(),
+            parts,
+            pipes,
+            quarter_turns,
+            offset,
+            port,
+        })
+    }
+
+    /// Separates a vehicle joined on with `dock`, laid out as it was
+    /// before it docked.
+    pub fn undock(&mut self, joint: &DockingJoint) -> Vehicle {
+        for p in &joint.pipes {
+            self.remove_pipe(*p);
+        }
+        let mut guest = self.split_off(&joint.parts);
+        guest.transform_layout(0, -joint.offset);
+        guest.transform_layout(4 - joint.quarter_turns, IVec2::ZERO);
+        guest.set_name(joint.name.clone());
+        guest.set_model(joint.model.clone());
+        guest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::{test_tanker, universe_in_leo};
+    use std::collections::HashSet;
+
+    /// Turns the guest about to face the host, port to port, and matches
+    /// the host's motion so the two latch on the next tick.
+    fn line_up(universe: &mut Universe, host: EntityId, guest: EntityId, design: &Vehicle) {
+        let (_, face, _) = design.docking_ports().next().unwrap();
+        let h = universe.surface_vehicles[&host].body;
+        let host_face = h.pv.pos + rotate_f64(face, h.angle);
+        let angle = h.angle + PI_64;
+        let g = universe.surface_vehicles.get_mut(&guest).unwrap();
+        g.body.angle = angle;
+        g.body.pv = PV::from_f64(host_face - rotate_f64(face, angle), h.pv.vel);
+        g.body.angular_velocity = h.angular_velocity;
+        g.set_target(host);
+    }
+
+    #[test]
+    fn docked_vehicles_share_pipes_and_come_apart_as_they_were() {
+        let mut host = test_tanker(0.0);
+        let guest = test_tanker(0.0);
+        let (ours, face, _) = host.docking_ports().next().unwrap();
+        let (theirs, _, _) = guest.docking_ports().next().unwrap();
+        assert_eq!(face, DVec2::new(14.0, 5.0) / PIXELS_PER_METER as f64);
+
+        let mass = host.total_mass() + guest.total_mass();
+        let joint = host.dock(&guest, ours, theirs).unwrap();
+        assert_eq!(joint.quarter_turns(), 2);
+        assert_eq!(host.total_mass(), mass);
+        assert_eq!(host.parts().count(), 4);
+
+        // the guest faces back at the host, its port against the host's
+        let port = host.get_part(joint.port()).unwrap();
+        assert_eq!(port.rotation(), Rotation::West);
+        assert_eq!(port.origin(), IVec2::new(14, 1));
+
+        // both tanks are on one pipe network through the ports
+        let tanks: Vec<_> = host
+            .parts()
+            .filter(|(_, p)| p.as_tank().is_some())
+            .map(|(id, _)| *id)
+            .collect();
+        assert_eq!(tanks.len(), 2);
+        assert!(host.is_connected(tanks[0], tanks[1]));
+
+        let undocked = host.undock(&joint);
+        assert_eq!(host.parts().count(), 2);
+        assert_eq!(host.total_mass() + undocked.total_mass(), mass);
+        assert_eq!(undocked.name(), "Tanker");
+        assert_eq!(undocked.discriminator(), guest.discriminator());
+        assert_eq!(
+            undocked.pipes().collect::<HashSet<_>>(),
+            guest.pipes().collect::<HashSet<_>>()
+        );
+        assert_eq!(
+            host.pipes().collect::<HashSet<_>>(),
+            guest.pipes().collect::<HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn aligned_ports_latch_in_orbit_and_undock() {
+        let (mut universe, orbit) = universe_in_leo();
+        let design = test_tanker(0.0);
+        let host = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+        let guest = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+        let events = universe.events.subscribe();
+        line_up(&mut universe, host, guest, &design);
+
+        let mass = design.total_mass() + design.total_mass();
+        universe.on_sim_tick(&ControlSignals::new());
+        assert!(!universe.surface_vehicles.contains_key(&guest));
+        assert_eq!(universe.surface_vehicles[&host].vehicle.total_mass(), mass);
+        assert!(universe
+            .events
+            .poll(events)
+            .iter()
+            .any(|e| e.event == UniverseEvent::Latched(guest, host)));
+
+        universe.undock(host, guest).unwrap();
+        let g = &universe.surface_vehicles[&guest];
+        let h = &universe.surface_vehicles[&host];
+        assert_eq!(g.vehicle.discriminator(), design.discriminator());
+        assert_eq!(h.vehicle.total_mass(), design.total_mass());
+        assert!(g.orbit.is_some());
+        let (_, face, _) = g.vehicle.docking_ports().next().unwrap();
+        let guest_face = g.body.pv.pos + rotate_f64(face, g.body.angle);
+        let host_face = h.body.pv.pos + rotate_f64(face, h.body.angle);
+        assert!(guest_face.distance(host_face) < 0.1);
+        let rel = g.body.pv.vel - h.body.pv.vel;
+        assert!((rel.length() - UNDOCK_SPEED).abs() < 1E-6);
+        assert!(universe.undock(host, guest).is_err());
+
+        // drifting apart, so they don't latch again
+        universe
+            .surface_vehicles
+            .get_mut(&guest)
+            .unwrap()
+            .set_target(host);
+        universe.on_sim_tick(&ControlSignals::new());
+        assert!(universe.surface_vehicles.contains_key(&guest));
+    }
+
+    #[test]
+    fn composites_undock_after_saving_and_after_undeleting() {
+        let (mut universe, orbit) = universe_in_leo();
+        let design = test_tanker(0.0);
+        let parts: HashMap<_, _> = design
+            .parts()
+            .map(|(_, p)| (p.prototype().sprite_path().to_string(), p.prototype()))
+            .collect();
+        let host = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+        let guest = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+
+        line_up(&mut universe, host, guest, &design);
+        universe.on_sim_tick(&ControlSignals::new());
+        assert!(!universe.surface_vehicles.contains_key(&guest));
+
+        let (mut loaded, failed) =
+            Universe::from_file_storage(universe.to_file_storage(), &parts).unwrap();
+        assert!(failed.is_empty());
+        loaded.undock(host, guest).unwrap();
+        assert_eq!(loaded.surface_vehicles[&host].vehicle.parts().count(), 2);
+        assert_eq!(
+            loaded.surface_vehicles[&guest].vehicle.discriminator(),
+            design.discriminator()
+        );
+
+        universe.soft_delete(host).unwrap();
+        universe.on_sim_tick(&ControlSignals::new());
+        universe.undelete(host).unwrap();
+        universe.undock(host, guest).unwrap();
+        assert!(universe.surface_vehicles.contains_key(&guest));
+    }
+
+    #[test]
+    fn undocking_conserves_momentum() {
+        let (mut universe, orbit) = universe_in_leo();
+        let design = test_tanker(0.5);
+        let host = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+        let guest = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+        line_up(&mut universe, host, guest, &design);
+        universe.on_sim_tick(&ControlSignals::new());
+        assert!(!universe.surface_vehicles.contains_key(&guest));
+
+        let momentum = |universe: &Universe| {
+            universe
+                .surface_vehicles
+                .values()
+                .map(|sv| sv.body.pv.vel * sv.vehicle.total_mass().to_kg_f64())
+                .sum::<DVec2>()
+        };
+        let before = momentum(&universe);
+        let v0 = universe.surface_vehicles[&host].body.pv.vel;
+        universe.undock(host, guest).unwrap();
+        let after = momentum(&universe);
+        assert!(
+            (after - before).length() < 1E-6 * before.length(),
+            "{} vs {}",
+            before,
+            after
+        );
+
+        // equal masses, so each takes half the push
+        let h = universe.surface_vehicles[&host].body.pv.vel;
+        let g = universe.surface_vehicles[&guest].body.pv.vel;
+        assert!(((h - g).length() - UNDOCK_SPEED).abs() < 1E-6);
+        assert!(((h - v0).length() - UNDOCK_SPEED / 2.0).abs() < 1E-6);
+        assert!(((g - v0).length() - UNDOCK_SPEED / 2.0).abs() < 1E-6);
+    }
+
+    #[test]
+    fn former_hosts_can_dock_as_guests() {
+        let (mut universe, orbit) = universe_in_leo();
+        let design = test_tanker(0.0);
+        let a = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+        let b = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+
+        line_up(&mut universe, a, b, &design);
+        universe.on_sim_tick(&ControlSignals::new());
+        assert!(!universe.surface_vehicles.contains_key(&b));
+        universe.undock(a, b).unwrap();
+        assert!(!universe.docked.contains_key(&a));
+
+        // the vehicle which hosted now docks onto the one it let go of
+        line_up(&mut universe, b, a, &design);
+        universe.on_sim_tick(&ControlSignals::new());
+        assert!(!universe.surface_vehicles.contains_key(&a));
+        assert_eq!(universe.docked[&b][0].0, a);
+    }
+
+    #[test]
+    fn crashing_hosts_take_their_guests_with_them() {
+        let (mut universe, orbit) = universe_in_leo();
+        let design = test_tanker(0.0);
+        let host = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+        let guest = universe.add_orbital_vehicle(design.clone(), orbit).unwrap();
+        line_up(&mut universe, host, guest, &design);
+        universe.on_sim_tick(&ControlSignals::new());
+        assert!(!universe.surface_vehicles.contains_key(&guest));
+
+        let group = EntityId::new(EntityKind::Group, 0);
+        universe.constellations.insert(host, group);
+        universe.constellations.insert(guest, group);
+        let events = universe.events.subscribe();
+
+        let destroyed = universe.remove(host);
+        let ids: Vec<_> = destroyed.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![host, guest]);
+        for (_, vehicle) in &destroyed {
+            assert_eq!(vehicle.parts().count(), 2);
+            assert_eq!(vehicle.discriminator(), design.discriminator());
+        }
+        assert!(universe.surface_vehicles.is_empty());
+        assert!(universe.docked.is_empty());
+        assert!(universe.constellations.is_empty());
+        let gone: Vec<_> = universe
+            .events
+            .poll(events)
+            .into_iter()
+            .filter_map(|e| match e.event {
+                UniverseEvent::Destroyed(id) => Some(id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(gone, vec![host, guest]);
+        assert!(universe.remove(host).is_empty());
+    }
+}
@@ -10,17 +10,94 @@ pub struct VehicleFileStorage {
     pub name: String,
     pub parts: Vec<VehiclePartFileStorage>,
     pub lines: HashSet<IVec2>,
+    /// Loadout presets made for this vehicle in the editor.
+    #[serde(default)]
+    pub loadouts: Vec<Loadout>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl VehicleFileStorage {
+    /// The design of `vehicle`, without any loadouts. Parts are listed in
+    /// the order `Vehicle::part_states` gives their states in.
+    pub fn from_vehicle(vehicle: &Vehicle) -> Self {
+        let parts = vehicle
+            .parts_in_order()
+            .map(|(_, instance)| VehiclePartFileStorage {
+                partname: instance.prototype().sprite_path().to_string(),
+                pos: instance.origin(),
+                rot: instance.rotation(),
+                stage: instance.stage(),
+            })
+            .collect();
+        Self {
+            name: vehicle.model().to_string(),
+            parts,
+            lines: vehicle.pipes().collect(),
+            loadouts: Vec::new(),
+        }
+    }
+
+    /// Puts the vehicle back together out of the parts in `parts`.
+    pub fn build(
+        &self,
+        name: String,
+        parts: &HashMap<String, PartPrototype>,
+    ) -> Result<Vehicle, NoPartError> {
+        let mut prototypes = Vec::new();
+        for part in &self.parts {
+            let proto = parts
+                .get(&part.partname)
+                .ok_or(NoPartError(part.partname.clone()))?;
+            prototypes.push((part.pos, part.rot, proto.clone()));
+        }
+        let mut vehicle =
+            Vehicle::from_parts(name, self.name.clone(), prototypes, self.lines.clone());
+        vehicle.assign_stages(self.parts.iter().map(|p| p.stage));
+        Ok(vehicle)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VehiclePartFileStorage {
     pub partname: String,
     pub pos: IVec2,
     pub rot: Rotation,
+    #[serde(default)]
+    pub stage: usize,
+}
+
+/// How far one part of a vehicle has been built and what it holds, as
+/// opposed to where it sits in the design.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartStateFileStorage {
+    pub builds_performed: u32,
+    #[serde(default)]
+    pub tank: Option<TankInstanceData>,
+    #[serde(default)]
+    pub cargo: Option<CargoInstanceData>,
+}
+
+impl PartStateFileStorage {
+    pub fn from_part(part: &InstantiatedPart) -> Self {
+        Self {
+            builds_performed: part.builds_performed(),
+            tank: part.as_tank().map(|(_, d)| *d),
+            cargo: part.as_cargo().map(|(_, d)| d.clone()),
+        }
+    }
+
+    pub fn apply(&self, part: &mut InstantiatedPart) {
+        part.set_builds_performed(self.builds_performed);
+        if let (Some((_, d)), Some(tank)) = (part.as_tank_mut(), self.tank) {
+            *d = tank;
+        }
+        if let (Some((_, d)), Some(cargo)) = (part.as_cargo_mut(), &self.cargo) {
+            *d = cargo.clone();
+        }
+    }
 }
 
 #[derive(Debug)]
-pub struct NoPartError(String);
+pub struct NoPartError(pub String);
 
 impl std::fmt::Display for NoPartError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -37,22 +114,20 @@ pub fn load_vehicle(
 ) -> Result<Vehicle, Box<dyn std::error::Error>> {
     let s = std::fs::read_to_string(path)?;
     let storage: VehicleFileStorage = serde_yaml::from_str(&s)?;
-    let mut prototypes = Vec::new();
-    for part in &storage.parts {
-        let proto = parts
-            .get(&part.partname)
-            .ok_or(Box::new(NoPartError(part.partname.clone())))?;
-        prototypes.push((part.pos, part.rot, proto.clone()));
-    }
-    Ok(Vehicle::from_parts(
-        name,
-        storage.name,
-        prototypes,
-        storage.lines,
-    ))
+    Ok(storage.build(name, parts)?)
+}
+
+/// Loadout presets stored in a vehicle file. Empty if there are none or
+/// the file can't be read.
+pub fn load_loadouts(path: &Path) -> Vec<Loadout> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_yaml::from_str::<VehicleFileStorage>(&s).ok())
+        .map(|s| s.loadouts)
+        .unwrap_or_default()
 }
 
-fn part_from_path(path: &Path) -> Result<PartPrototype, String> {
+pub fn part_from_path(path: &Path) -> Result<PartPrototype, String> {
     let data_path = path.join("metadata.yaml");
     let s = std::fs::read_to_string(&data_path).map_err(|_| "Failed to load metadata file")?;
     serde_yaml::from_str(&s).map_err(|e| format!("Failed to parse metadata file: {}", e))
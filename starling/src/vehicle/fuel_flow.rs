@@ -0,0 +1,230 @@
+use crate::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Most propellant a single tank can push into its pipe network, in
+/// kilograms per second.
+pub const TANK_OUTFLOW_RATE: f64 = 150.0;
+
+/// How a main engine is fed through the pipe network, assuming every tank
+/// is full and every engine is at full throttle.
+#[derive(Debug, Clone)]
+pub struct EngineFeed {
+    pub engine: PartId,
+    /// Tanks which share a pipe network with the engine.
+    pub tanks: Vec<PartId>,
+    /// Propellant burned at full throttle, in kilograms per second.
+    pub demand: f64,
+    /// This engine's share of what its tanks can deliver, in kilograms
+    /// per second.
+    pub available: f64,
+}
+
+impl EngineFeed {
+    pub fn is_starved(&self) -> bool {
+        self.available < self.demand * 0.999
+    }
+
+    /// Fraction of full thrust the engine can keep up.
+    pub fn supply_fraction(&self) -> f64 {
+        if self.demand <= 0.0 {
+            return 1.0;
+        }
+        (self.available / self.demand).clamp(0.0, 1.0)
+    }
+}
+
+/// A pipe cell and the neighbouring cell propellant flows on to, with the
+/// number of cells left before it reaches an engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlowCell {
+    pub pos: IVec2,
+    pub dir: IVec2,
+    pub distance: u32,
+}
+
+/// Which tanks feed which engines, and the way propellant moves along the
+/// pipes to get there.
+#[derive(Debug, Clone, Default)]
+pub struct FuelFlowPreview {
+    pub feeds: Vec<EngineFeed>,
+    pub cells: Vec<FlowCell>,
+}
+
+impl FuelFlowPreview {
+    pub fn starved(&self) -> impl Iterator<Item = &EngineFeed> + use<'_> {
+        self.feeds.iter().filter(|f| f.is_starved())
+    }
+
+    pub fn feed(&self, engine: PartId) -> Option<&EngineFeed> {
+        self.feeds.iter().find(|f| f.engine == engine)
+    }
+
+    /// Whether the tank feeds at least one engine.
+    pub fn is_feeding(&self, tank: PartId) -> bool {
+        self.feeds.iter().any(|f| f.tanks.contains(&tank))
+    }
+}
+
+fn demand(vehicle: &Vehicle, id: PartId) -> Option<f64> {
+    let (t, _) = vehicle.get_part(id)?.as_thruster()?;
+    if t.is_rcs {
+        return None;
+    }
+    Some(t.max_thrust() / t.exhaust_velocity as f64)
+}
+
+fn is_tank(vehicle: &Vehicle, id: PartId) -> bool {
+    vehicle
+        .get_part(id)
+        .and_then(|p| p.as_tank())
+        .is_some_and(|(t, _)| t.capacity() > Mass::ZERO)
+}
+
+/// Works out the flow through the pipe networks. Each tank delivers
+/// [TANK_OUTFLOW_RATE], shared by demand between the engines it reaches.
+pub fn fuel_flow_preview(vehicle: &Vehicle) -> FuelFlowPreview {
+    let mut feeds: HashMap<PartId, EngineFeed> = HashMap::new();
+    let mut cells = Vec::new();
+
+    for (id, _) in vehicle.parts() {
+        if let Some(demand) = demand(vehicle, *id) {
+            feeds.insert(
+                *id,
+                EngineFeed {
+                    engine: *id,
+                    tanks: Vec::new(),
+                    demand,
+                    available: 0.0,
+                },
+            );
+        }
+    }
+
+    let pipes: HashSet<IVec2> = vehicle.pipes().collect();
+    let mut reach: HashMap<PartId, HashSet<PartId>> = HashMap::new();
+
+    for group in vehicle.conn_groups() {
+        let tanks: Vec<PartId> = group.ids().filter(|id| is_tank(vehicle, *id)).collect();
+        let engines: Vec<PartId> = group.ids().filter(|id| feeds.contains_key(id)).collect();
+        if tanks.is_empty() || engines.is_empty() {
+            continue;
+        }
+
+        for t in &tanks {
+            reach.entry(*t).or_default().extend(engines.iter().copied());
+        }
+
+        // walk outward from the engines' joints, so every cell points back
+        // along the shortest way to an engine
+        let mut seen = HashSet::new();
+        let mut open = VecDeque::new();
+        for p in engines.iter().filter_map(|id| group.get_pos(*id)) {
+            if seen.insert(p) {
+                cells.push(FlowCell {
+                    pos: p,
+                    dir: IVec2::ZERO,
+                    distance: 0,
+                });
+                open.push_back((p, 0));
+            }
+        }
+        while let Some((p, d)) = open.pop_front() {
+            for off in [IVec2::X, IVec2::Y, -IVec2::X, -IVec2::Y] {
+                let q = p + off;
+                if !pipes.contains(&q) || !seen.insert(q) {
+                    continue;
+                }
+                cells.push(FlowCell {
+                    pos: q,
+                    dir: -off,
+                    distance: d + 1,
+                });
+                open.push_back((q, d + 1));
+            }
+        }
+    }
+
+    // each tank's outflow is shared by demand among every engine it reaches
+    for (tank, engines) in reach {
+        let total: f64 = engines
+            .iter()
+            .filter_map(|id| feeds.get(id))
+            .map(|f| f.demand)
+            .sum();
+        for id in engines {
+            if let Some(feed) = feeds.get_mut(&id) {
+                if total > 0.0 {
+                    feed.available += TANK_OUTFLOW_RATE * feed.demand / total;
+                }
+                feed.tanks.push(tank);
+            }
+        }
+    }
+
+    let mut feeds: Vec<EngineFeed> = feeds
+        .into_values()
+        .map(|mut f| {
+            f.available = f.available.min(f.demand);
+            f.tanks.sort();
+            f.tanks.dedup();
+            f
+        })
+        .collect();
+    feeds.sort_by_key(|f| f.engine);
+
+    FuelFlowPreview { feeds, cells }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tank() -> PartPrototype {
+        PartPrototype::Tank(TankModel::new(
+            "tank".to_string(),
+            UVec2::splat(10),
+            Mass::kilograms(100),
+            Mass::kilograms(1000),
+        ))
+    }
+
+    fn engine(thrust: f64) -> PartPrototype {
+        PartPrototype::Thruster(ThrusterModel::main_thruster(thrust, 1000.0))
+    }
+
+    #[test]
+    fn shared_tank_starves_engines_and_flow_points_at_them() {
+        let mut vehicle =
+            Vehicle::from_parts("".to_string(), "".to_string(), vec![], HashSet::new());
+        let t = vehicle.add_part(tank(), IVec2::ZERO, Rotation::East);
+        let small = vehicle.add_part(engine(100_000.0), IVec2::new(40, 0), Rotation::East);
+        let big = vehicle.add_part(engine(200_000.0), IVec2::new(0, 40), Rotation::East);
+        let unpiped = vehicle.add_part(engine(100_000.0), IVec2::new(100, 100), Rotation::East);
+
+        let path = route_pipe(&vehicle, t, small).unwrap();
+        vehicle.add_pipes(path);
+        let preview = fuel_flow_preview(&vehicle);
+        let feed = preview.feed(small).unwrap();
+        assert_eq!(feed.tanks, vec![t]);
+        assert_eq!(feed.demand, 100.0);
+        assert!(!feed.is_starved());
+        assert!(preview.is_feeding(t));
+        assert!(preview.feed(unpiped).unwrap().is_starved());
+        assert_eq!(preview.feed(unpiped).unwrap().available, 0.0);
+
+        // the cell next to the engine's joint flows into it
+        let joint = preview.cells.iter().find(|c| c.distance == 0).unwrap();
+        let next = preview.cells.iter().find(|c| c.distance == 1).unwrap();
+        assert_eq!(next.pos + next.dir, joint.pos);
+
+        // a second engine on the same tank takes a share by demand
+        let path = route_pipe(&vehicle, t, big).unwrap();
+        vehicle.add_pipes(path);
+        let preview = fuel_flow_preview(&vehicle);
+        let (small, big) = (preview.feed(small).unwrap(), preview.feed(big).unwrap());
+        assert!(small.is_starved() && big.is_starved());
+        assert!((small.available + big.available - TANK_OUTFLOW_RATE).abs() < 1E-6);
+        assert!((big.available - 2.0 * small.available).abs() < 1E-6);
+        assert_eq!(preview.starved().count(), 3);
+    }
+}
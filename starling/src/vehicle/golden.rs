@@ -0,0 +1,268 @@
+use crate::math::*;
+use crate::parts::*;
+use crate::vehicle::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::path::Path;
+
+/// Number of headings at which the thrust envelope is sampled.
+const ENVELOPE_HEADINGS: usize = 8;
+
+/// Linear and angular acceleration a vehicle gets from one control input.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub input: String,
+    pub linear: DVec2,
+    pub angular: f64,
+}
+
+/// Flight characteristics of a vehicle, derived from its parts. Kept for
+/// a set of fixture vehicles so that changes to parts or to how vehicles
+/// are put together can't quietly change how they fly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VehicleStats {
+    pub dry_mass: f64,
+    pub wet_mass: f64,
+    pub remaining_dv: f64,
+    pub max_thrust: f64,
+    pub max_forward_thrust: f64,
+    pub max_backwards_thrust: f64,
+    /// Thrust available along evenly spaced headings, starting at +X, for
+    /// main engines and for RCS.
+    pub thrust_envelope: Vec<f64>,
+    pub rcs_envelope: Vec<f64>,
+    pub center_of_mass: DVec2,
+    pub moment_of_inertia: f64,
+    pub responses: Vec<ControlResponse>,
+}
+
+/// The control inputs each vehicle's response is measured for.
+fn control_inputs() -> Vec<(&'static str, VehicleControl)> {
+    let axis = |use_rcs| ThrustAxisControl {
+        use_rcs,
+        throttle: 1.0,
+    };
+    vec![
+        ("forward", VehicleControl::FORWARD),
+        (
+            "backward",
+            VehicleControl {
+                neg_x: axis(false),
+                ..VehicleControl::NULLOPT
+            },
+        ),
+        (
+            "rcs left",
+            VehicleControl {
+                plus_y: axis(true),
+                ..VehicleControl::NULLOPT
+            },
+        ),
+        (
+            "rcs right",
+            VehicleControl {
+                neg_y: axis(true),
+                ..VehicleControl::NULLOPT
+            },
+        ),
+        (
+            "turn left",
+            VehicleControl {
+                attitude: 1.0,
+                ..VehicleControl::NULLOPT
+            },
+        ),
+        (
+            "turn right",
+            VehicleControl {
+                attitude: -1.0,
+                ..VehicleControl::NULLOPT
+            },
+        ),
+    ]
+}
+
+impl VehicleStats {
+    /// Measures a vehicle as it is, fuel and all.
+    pub fn measure(vehicle: &Vehicle) -> Self {
+        let envelope = |rcs| {
+            (0..ENVELOPE_HEADINGS)
+                .map(|i| {
+                    let angle = 2.0 * PI_64 * i as f64 / ENVELOPE_HEADINGS as f64;
+                    vehicle.max_thrust_along_heading(angle, rcs)
+                })
+                .collect()
+        };
+
+        let responses = control_inputs()
+            .into_iter()
+            .map(|(input, control)| {
+                let mut v = vehicle.clone();
+                v.set_thrust_control(&control);
+                let accel = v.body_frame_accel();
+                ControlResponse {
+                    input: input.to_string(),
+                    linear: accel.linear,
+                    angular: accel.angular,
+                }
+            })
+            .collect();
+
+        Self {
+            dry_mass: vehicle.dry_mass().to_kg_f64(),
+            wet_mass: vehicle.total_mass().to_kg_f64(),
+            remaining_dv: vehicle.remaining_dv(),
+            max_thrust: vehicle.max_thrust(),
+            max_forward_thrust: vehicle.max_forward_thrust(),
+            max_backwards_thrust: vehicle.max_backwards_thrust(),
+            thrust_envelope: envelope(false),
+            rcs_envelope: envelope(true),
+            center_of_mass: vehicle.center_of_mass(),
+            moment_of_inertia: vehicle.moment_of_inertia(),
+            responses,
+        }
+    }
+
+    /// Every figure which differs from `expected` by more than
+    /// `tolerance`, described for a test failure.
+    pub fn diff(&self, expected: &Self, tolerance: f64) -> Vec<String> {
+        let mut ret = Vec::new();
+        let mut check = |what: &str, actual: f64, expected: f64| {
+            let scale = expected.abs().max(1.0);
+            if (actual - expected).abs() > tolerance * scale {
+                ret.push(format!("{}: expected {}, got {}", what, expected, actual));
+            }
+        };
+
+        check("dry mass", self.dry_mass, expected.dry_mass);
+        check("wet mass", self.wet_mass, expected.wet_mass);
+        check("remaining dv", self.remaining_dv, expected.remaining_dv);
+        check("max thrust", self.max_thrust, expected.max_thrust);
+        check(
+            "max forward thrust",
+            self.max_forward_thrust,
+            expected.max_forward_thrust,
+        );
+        check(
+            "max backwards thrust",
+            self.max_backwards_thrust,
+            expected.max_backwards_thrust,
+        );
+        for (name, actual, expected) in [
+            ("thrust", &self.thrust_envelope, &expected.thrust_envelope),
+            ("rcs", &self.rcs_envelope, &expected.rcs_envelope),
+        ] {
+            for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+                check(&format!("{} envelope [{}]", name, i), *a, *e);
+            }
+        }
+        check("com x", self.center_of_mass.x, expected.center_of_mass.x);
+        check("com y", self.center_of_mass.y, expected.center_of_mass.y);
+        check(
+            "moment of inertia",
+            self.moment_of_inertia,
+            expected.moment_of_inertia,
+        );
+        let mut missing = Vec::new();
+        for e in &expected.responses {
+            let Some(a) = self.responses.iter().find(|a| a.input == e.input) else {
+                missing.push(format!("{}: no response measured", e.input));
+                continue;
+            };
+            check(&format!("{} accel x", e.input), a.linear.x, e.linear.x);
+            check(&format!("{} accel y", e.input), a.linear.y, e.linear.y);
+            check(&format!("{} alpha", e.input), a.angular, e.angular);
+        }
+        ret.extend(missing);
+        ret
+    }
+}
+
+/// Loads every vehicle file in `dir`, fills its tanks, and measures it.
+/// Keyed by file stem.
+pub fn measure_golden_vehicles(
+    dir: &Path,
+    parts: &HashMap<String, PartPrototype>,
+) -> Result<BTreeMap<String, VehicleStats>, Box<dyn Error>> {
+    let mut ret = BTreeMap::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|e| e != "vehicle") {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut vehicle = load_vehicle(&path, name.clone(), parts)?;
+        vehicle.build_all();
+        vehicle.apply_loadout(&Loadout::new("Full fuel", 1.0));
+        ret.insert(name, VehicleStats::measure(&vehicle));
+    }
+    Ok(ret)
+}
+
+/// Differences between measured and golden stats, vehicle by vehicle,
+/// including vehicles found in only one of the two.
+pub fn golden_diff(
+    actual: &BTreeMap<String, VehicleStats>,
+    expected: &BTreeMap<String, VehicleStats>,
+    tolerance: f64,
+) -> Vec<String> {
+    let mut ret = Vec::new();
+    for (name, e) in expected {
+        match actual.get(name) {
+            Some(a) => ret.extend(
+                a.diff(e, tolerance)
+                    .into_iter()
+                    .map(|d| format!("{}: {}", name, d)),
+            ),
+            None => ret.push(format!("{}: fixture is missing", name)),
+        }
+    }
+    for name in actual.keys().filter(|n| !expected.contains_key(*n)) {
+        ret.push(format!("{}: no golden values recorded", name));
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Relative tolerance for golden values; loose enough to allow for
+    /// parts being summed in a different order, and no looser.
+    const TOLERANCE: f64 = 1e-6;
+
+    /// Run with STARLING_BLESS_GOLDEN=1 to rewrite the golden values after
+    /// an intended change in flight characteristics.
+    #[test]
+    fn golden_vehicles_fly_as_recorded() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let parts = load_parts_from_dir(&root.join("../assets/parts")).unwrap();
+        let fixtures = root.join("fixtures/golden");
+        let golden_path = fixtures.join("golden.yaml");
+        let actual = measure_golden_vehicles(&fixtures, &parts).unwrap();
+        assert!(actual.len() >= 4);
+
+        if std::env::var_os("STARLING_BLESS_GOLDEN").is_some() {
+            let s = serde_yaml::to_string(&actual).unwrap();
+            std::fs::write(&golden_path, s).unwrap();
+        }
+
+        let s = std::fs::read_to_string(&golden_path).unwrap();
+        let expected: BTreeMap<String, VehicleStats> = serde_yaml::from_str(&s).unwrap();
+        let diff = golden_diff(&actual, &expected, TOLERANCE);
+        assert!(diff.is_empty(), "\n{}", diff.join("\n"));
+
+        // and the harness does notice when something changes
+        let mut changed = actual.clone();
+        let stats = changed.values_mut().next().unwrap();
+        stats.wet_mass *= 1.01;
+        stats.responses.pop();
+        let diff = golden_diff(&changed, &expected, TOLERANCE);
+        assert_eq!(diff.len(), 2, "{:?}", diff);
+    }
+}
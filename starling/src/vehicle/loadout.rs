@@ -0,0 +1,152 @@
+use crate::factory::{Item, Mass};
+use crate::vehicle::Vehicle;
+use serde::{Deserialize, Serialize};
+
+/// How to fill a vehicle's tanks and cargo containers before it sets off.
+/// Besides the built-in presets, each vehicle file can store its own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Loadout {
+    pub name: String,
+    /// Fraction of each tank's capacity to fill, from 0 to 1.
+    pub fuel: f64,
+    /// Fluid for tanks which don't already hold one.
+    pub fuel_item: Item,
+    /// Items stowed in cargo containers, which are emptied first.
+    #[serde(default)]
+    pub manifest: Vec<(Item, Mass)>,
+}
+
+impl Loadout {
+    pub fn new(name: impl Into<String>, fuel: f64) -> Self {
+        Self {
+            name: name.into(),
+            fuel: fuel.clamp(0.0, 1.0),
+            fuel_item: Item::H2,
+            manifest: Vec::new(),
+        }
+    }
+
+    pub fn with_cargo(mut self, item: Item, mass: Mass) -> Self {
+        self.manifest.push((item, mass));
+        self
+    }
+
+    /// Presets available for every vehicle.
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::new("Full fuel", 1.0),
+            Self::new("Half fuel", 0.5),
+            Self::new("Empty", 0.0),
+        ]
+    }
+
+    /// A preset which fills a vehicle the way `vehicle` is filled now.
+    pub fn from_vehicle(name: impl Into<String>, vehicle: &Vehicle) -> Self {
+        let capacity: Mass = vehicle.tanks().map(|(t, _)| t.capacity()).sum();
+        let fuel = if capacity == Mass::ZERO {
+            0.0
+        } else {
+            vehicle.fuel_mass().to_kg_f64() / capacity.to_kg_f64()
+        };
+        let mut ret = Self::new(name, fuel);
+        if let Some(item) = vehicle.tanks().find_map(|(_, d)| d.item()) {
+            ret.fuel_item = item;
+        }
+        let mut manifest: Vec<(Item, Mass)> = Vec::new();
+        for (item, mass) in vehicle.cargo().flat_map(|(_, d)| d.contents()) {
+            match manifest.iter_mut().find(|(i, _)| *i == item) {
+                Some((_, m)) => *m += mass,
+                None => manifest.push((item, mass)),
+            }
+        }
+        ret.manifest = manifest;
+        ret
+    }
+
+    /// Parses a manifest entry written as "item=kilograms", like
+    /// "Iron=500".
+    pub fn parse_cargo(s: &str) -> Result<(Item, Mass), String> {
+        let (item, kg) = s
+            .split_once('=')
+            .ok_or(format!("Expected ITEM=KG, got \"{}\"", s))?;
+        let item = enum_iterator::all::<Item>()
+            .find(|i| format!("{:?}", i).eq_ignore_ascii_case(item.trim()))
+            .ok_or(format!("No item called \"{}\"", item))?;
+        let kg: f32 = kg
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid mass \"{}\"", kg))?;
+        if kg < 0.0 {
+            return Err(format!("Invalid mass \"{}\"", kg));
+        }
+        Ok((item, Mass::from_kg_f32(kg)))
+    }
+
+    /// Finds a preset by name, case-insensitively, among `presets` and
+    /// then the built-in ones.
+    pub fn find(name: &str, presets: &[Self]) -> Option<Self> {
+        presets
+            .iter()
+            .cloned()
+            .chain(Self::builtin())
+            .find(|l| l.name.eq_ignore_ascii_case(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn loadouts_fill_tanks_and_cargo() {
+        let mut vehicle =
+            Vehicle::from_parts("".to_string(), "".to_string(), vec![], HashSet::new());
+        let tank = TankModel::new(
+            "tank".to_string(),
+            UVec2::splat(10),
+            Mass::kilograms(100),
+            Mass::kilograms(1000),
+        );
+        let cargo = Cargo::new(
+            "cargo".to_string(),
+            Mass::kilograms(100),
+            Mass::kilograms(500),
+            UVec2::splat(10),
+        );
+        vehicle.add_part(PartPrototype::Tank(tank), IVec2::ZERO, Rotation::East);
+        vehicle.add_part(
+            PartPrototype::Cargo(cargo),
+            IVec2::new(20, 0),
+            Rotation::East,
+        );
+        vehicle.build_all();
+
+        let half = Loadout::find("half FUEL", &[]).unwrap();
+        vehicle.apply_loadout(&half);
+        assert_eq!(vehicle.fuel_mass(), Mass::kilograms(500));
+
+        let (item, mass) = Loadout::parse_cargo("iron=300").unwrap();
+        assert_eq!((item, mass), (Item::Iron, Mass::kilograms(300)));
+        assert!(Loadout::parse_cargo("unobtainium=3").is_err());
+        assert!(Loadout::parse_cargo("Iron").is_err());
+
+        let full = Loadout::new("Supply run", 1.0)
+            .with_cargo(item, mass)
+            .with_cargo(Item::Copper, Mass::kilograms(400));
+        vehicle.apply_loadout(&full);
+        assert_eq!(vehicle.fuel_mass(), Mass::kilograms(1000));
+        let cargo_mass: Mass = vehicle.cargo().map(|(_, d)| d.contents_mass()).sum();
+        assert_eq!(cargo_mass, Mass::kilograms(500));
+
+        let copy = Loadout::from_vehicle("Copy", &vehicle);
+        assert_eq!(copy.fuel, 1.0);
+        assert_eq!(copy.fuel_item, Item::H2);
+        assert_eq!(copy.manifest[0], (Item::Iron, Mass::kilograms(300)));
+
+        vehicle.apply_loadout(&Loadout::find("Empty", &[copy]).unwrap());
+        assert_eq!(vehicle.fuel_mass(), Mass::ZERO);
+        assert_eq!(vehicle.cargo().flat_map(|(_, d)| d.contents()).count(), 0);
+    }
+}
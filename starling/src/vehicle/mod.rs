@@ -1,14 +1,34 @@
+mod ascent;
+mod assembly;
 mod connectivity_group;
+mod control_law;
+mod docking_ports;
 mod file_storage;
+mod fuel_flow;
+mod golden;
+mod loadout;
+mod pipe_routing;
 mod rigid_body;
 mod sprite_generation;
+mod staging;
+mod vector_diagram;
 mod vehicle;
 mod vehicle_control;
 mod vehicle_tests;
 
+pub use ascent::*;
+pub use assembly::*;
 pub use connectivity_group::*;
+pub use control_law::*;
+pub use docking_ports::*;
 pub use file_storage::*;
+pub use fuel_flow::*;
+pub use golden::*;
+pub use loadout::*;
+pub use pipe_routing::*;
 pub use rigid_body::*;
 pub use sprite_generation::*;
+pub use staging::*;
+pub use vector_diagram::*;
 pub use vehicle::*;
 pub use vehicle_control::*;
@@ -0,0 +1,124 @@
+use crate::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+/// Cells beyond the bounds of the two parts being joined which a route may
+/// pass through on its way around other parts.
+const ROUTE_MARGIN: i32 = 16;
+
+/// Cells a part covers, by the same measure as [Vehicle::get_part_at].
+fn part_cells(part: &InstantiatedPart) -> impl Iterator<Item = IVec2> {
+    let origin = part.origin();
+    let dims = part.dims_grid().as_ivec2();
+    (0..=dims.x).flat_map(move |x| (0..=dims.y).map(move |y| origin + IVec2::new(x, y)))
+}
+
+/// Shortest run of pipe joining two parts on the internal layer, from a
+/// cell inside `from` to a cell inside `to`. The route steps around every
+/// other internal part, so as not to plumb it in along the way, but may
+/// run along existing pipe. None if there's no way through.
+pub fn route_pipe(vehicle: &Vehicle, from: PartId, to: PartId) -> Option<Vec<IVec2>> {
+    if from == to {
+        return None;
+    }
+    let a = vehicle.get_part(from)?;
+    let b = vehicle.get_part(to)?;
+
+    let upper = |p: &InstantiatedPart| p.origin() + p.dims_grid().as_ivec2();
+    let lower = a.origin().min(b.origin()) - IVec2::splat(ROUTE_MARGIN);
+    let upper = upper(a).max(upper(b)) + IVec2::splat(ROUTE_MARGIN);
+    let in_bounds = |p: IVec2| p.cmpge(lower).all() && p.cmple(upper).all();
+
+    let mut occupied: HashMap<IVec2, PartId> = HashMap::new();
+    for (id, part) in vehicle.parts() {
+        if part.prototype().layer() == PartLayer::Internal {
+            occupied.extend(part_cells(part).filter(|p| in_bounds(*p)).map(|p| (p, *id)));
+        }
+    }
+
+    let mut came_from: HashMap<IVec2, Option<IVec2>> = HashMap::new();
+    let mut open = VecDeque::new();
+    for p in part_cells(a) {
+        came_from.insert(p, None);
+        open.push_back(p);
+    }
+
+    while let Some(p) = open.pop_front() {
+        if occupied.get(&p) == Some(&to) {
+            let mut path = vec![p];
+            while let Some(Some(prev)) = came_from.get(path.last()?) {
+                path.push(*prev);
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for off in [IVec2::X, IVec2::Y, -IVec2::X, -IVec2::Y] {
+            let q = p + off;
+            if !in_bounds(q) || came_from.contains_key(&q) {
+                continue;
+            }
+            if occupied.get(&q).is_some_and(|id| *id != to && *id != from) {
+                continue;
+            }
+            came_from.insert(q, Some(p));
+            open.push_back(q);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn internal(dims: UVec2) -> PartPrototype {
+        PartPrototype::Generic(Generic::new(
+            "".to_string(),
+            dims,
+            PartLayer::Internal,
+            Mass::kilograms(100),
+        ))
+    }
+
+    #[test]
+    fn route_joins_parts_and_avoids_others() {
+        let mut vehicle =
+            Vehicle::from_parts("".to_string(), "".to_string(), vec![], HashSet::new());
+        let tank = vehicle.add_part(internal(UVec2::splat(10)), IVec2::ZERO, Rotation::East);
+        let engine = vehicle.add_part(
+            internal(UVec2::splat(10)),
+            IVec2::new(40, 0),
+            Rotation::East,
+        );
+        let blocker = vehicle.add_part(
+            internal(UVec2::new(5, 20)),
+            IVec2::new(20, -5),
+            Rotation::East,
+        );
+
+        let path = route_pipe(&vehicle, tank, engine).unwrap();
+        assert_eq!(
+            vehicle.get_part_at(path[0], PartLayer::Internal),
+            Some(tank)
+        );
+        assert_eq!(
+            vehicle.get_part_at(*path.last().unwrap(), PartLayer::Internal),
+            Some(engine)
+        );
+        assert!(path
+            .iter()
+            .all(|p| vehicle.get_part_at(*p, PartLayer::Internal) != Some(blocker)));
+        assert!(path
+            .windows(2)
+            .all(|w| (w[1] - w[0]).abs().element_sum() == 1));
+
+        vehicle.add_pipes(path);
+        assert!(vehicle
+            .conn_groups()
+            .any(|g| g.contains(tank) && g.contains(engine) && !g.contains(blocker)));
+
+        assert!(route_pipe(&vehicle, tank, tank).is_none());
+    }
+}
@@ -8,7 +8,7 @@ pub struct BodyFrameAccel {
     pub angular: f64,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct RigidBody {
     pub pv: PV,
     pub angle: f64,
@@ -8,7 +8,11 @@ pub fn read_image(path: &Path) -> Option<RgbaImage> {
 
 pub fn diagram_color(part: &PartPrototype) -> [f32; 4] {
     match part {
-        PartPrototype::Cargo(..) => [0.0, 0.45, 0.0, 1.0],
+        PartPrototype::Cargo(c) => match c.filter() {
+            CargoFilter::Any => [0.0, 0.45, 0.0, 1.0],
+            CargoFilter::Fuel => [0.8, 0.35, 0.1, 1.0],
+            CargoFilter::Food => [0.7, 0.6, 0.1, 1.0],
+        },
         PartPrototype::Thruster(..) => [1.0, 0.0, 0.0, 1.0],
         PartPrototype::Tank(..) => [1.0, 0.6, 0.0, 1.0],
         _ => match part.layer() {
@@ -0,0 +1,285 @@
+use crate::factory::Mass;
+use crate::parts::*;
+use crate::vehicle::*;
+use std::collections::BTreeSet;
+
+/// Why a vehicle's active stage couldn't be jettisoned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StagingError {
+    NoVehicle,
+    /// Only one stage is left, so there's nothing to separate from.
+    LastStage,
+    /// The active stage has no decoupler to come apart at.
+    NoDecoupler,
+}
+
+impl std::fmt::Display for StagingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoVehicle => write!(f, "no such vehicle"),
+            Self::LastStage => write!(f, "no stages left to jettison"),
+            Self::NoDecoupler => write!(f, "active stage has no decoupler"),
+        }
+    }
+}
+
+/// Performance of one stage of a vehicle, which carries every stage above
+/// it. Each stage's engines are assumed to burn only its own fuel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StageInfo {
+    pub stage: usize,
+    pub parts: usize,
+    /// Mass of the vehicle when the stage is lit, and once its fuel is
+    /// spent.
+    pub start_mass: Mass,
+    pub end_mass: Mass,
+    pub thrust: f64,
+    pub dv: f64,
+}
+
+impl Vehicle {
+    /// Stage numbers of the parts still attached, from the active stage
+    /// upwards.
+    pub fn stages(&self) -> Vec<usize> {
+        let stages: BTreeSet<_> = self.parts().map(|(_, p)| p.stage()).collect();
+        stages.into_iter().collect()
+    }
+
+    pub fn stage_count(&self) -> usize {
+        self.stages().len().max(1)
+    }
+
+    /// Stage whose engines are lit, which is the next to be jettisoned.
+    pub fn current_stage(&self) -> usize {
+        self.parts().map(|(_, p)| p.stage()).min().unwrap_or(0)
+    }
+
+    pub fn parts_in_stage(
+        &self,
+        stage: usize,
+    ) -> impl Iterator<Item = (&PartId, &InstantiatedPart)> + use<'_> {
+        self.parts().filter(move |(_, p)| p.stage() == stage)
+    }
+
+    pub fn stage_info(&self) -> Vec<StageInfo> {
+        self.stages()
+            .into_iter()
+            .map(|stage| {
+                let start_mass: Mass = self
+                    .parts()
+                    .filter(|(_, p)| p.stage() >= stage)
+                    .map(|(_, p)| p.total_mass())
+                    .sum();
+                let fuel: Mass = self
+                    .parts_in_stage(stage)
+                    .filter_map(|(_, p)| p.as_tank())
+                    .map(|(_, d)| d.contents_mass())
+                    .sum();
+                let engines: Vec<_> = self
+                    .parts_in_stage(stage)
+                    .filter_map(|(_, p)| p.as_thruster())
+                    .filter(|(t, _)| !t.is_rcs())
+                    .collect();
+                let thrust = engines.iter().map(|(t, _)| t.max_thrust()).sum();
+                let ve = if engines.is_empty() {
+                    0.0
+                } else {
+                    engines
+                        .iter()
+                        .map(|(t, _)| t.exhaust_velocity as f64)
+                        .sum::<f64>()
+                        / engines.len() as f64
+                };
+                let end_mass = start_mass - fuel;
+                let dv = if end_mass > Mass::ZERO && ve > 0.0 {
+                    rocket_equation(ve, start_mass, end_mass)
+                } else {
+                    0.0
+                };
+                StageInfo {
+                    stage,
+                    parts: self.parts_in_stage(stage).count(),
+                    start_mass,
+                    end_mass,
+                    thrust,
+                    dv,
+                }
+            })
+            .collect()
+    }
+
+    /// Speed at which the active stage is pushed away when jettisoned,
+    /// given by the strongest of its decouplers.
+    pub fn ejection_speed(&self) -> Option<f64> {
+        self.parts_in_stage(self.current_stage())
+            .filter_map(|(_, p)| p.as_decoupler())
+            .map(|d| d.ejection_speed())
+            .reduce(f64::max)
+    }
+
+    pub fn can_jettison(&self) -> Result<(), StagingError> {
+        if self.stage_count() < 2 {
+            return Err(StagingError::LastStage);
+        }
+        self.ejection_speed().ok_or(StagingError::NoDecoupler)?;
+        Ok(())
+    }
+
+    /// Separates the active stage, returning it as a vehicle of its own.
+    /// The next stage up becomes active.
+    pub fn jettison_stage(&mut self) -> Result<Vehicle, StagingError> {
+        self.can_jettison()?;
+        let ids: Vec<_> = self
+            .parts_in_stage(self.current_stage())
+            .map(|(id, _)| *id)
+            .collect();
+        Ok(self.split_off(&ids))
+    }
+
+    /// Swaps the parts of two stages, changing the order they fire in.
+    pub fn swap_stages(&mut self, a: usize, b: usize) {
+        let moves: Vec<_> = self
+            .parts()
+            .filter_map(|(id, p)| match p.stage() {
+                s if s == a => Some((*id, b)),
+                s if s == b => Some((*id, a)),
+                _ => None,
+            })
+            .collect();
+        for (id, stage) in moves {
+            self.set_part_stage(id, stage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::universe_in_leo;
+    use std::collections::HashSet;
+
+    fn two_stage_rocket() -> Vehicle {
+        let frame = PartPrototype::Generic(Generic::new(
+            "frame".to_string(),
+            UVec2::new(10, 10),
+            PartLayer::Structural,
+            Mass::kilograms(400),
+        ));
+        let engine = PartPrototype::Thruster(ThrusterModel::main_thruster(50_000.0, 3000.0));
+        let decoupler = PartPrototype::Decoupler(Decoupler::new(
+            "decoupler".to_string(),
+            UVec2::new(4, 10),
+            Mass::kilograms(50),
+            3.0,
+        ));
+        let mut vehicle = Vehicle::from_parts(
+            "Rocket".to_string(),
+            "RKT".to_string(),
+            vec![
+                (IVec2::new(-20, 0), Rotation::East, engine.clone()),
+                (IVec2::new(-4, 0), Rotation::East, decoupler),
+                (IVec2::new(0, 0), Rotation::East, frame),
+                (IVec2::new(10, 0), Rotation::East, engine),
+            ],
+            HashSet::new(),
+        );
+        vehicle.assign_stages([0, 0, 1, 1]);
+        vehicle
+    }
+
+    #[test]
+    fn stages_fire_in_order_and_jettison() {
+        let mut vehicle = two_stage_rocket();
+        let wet = vehicle.total_mass();
+        assert_eq!(vehicle.stages(), vec![0, 1]);
+        assert_eq!(vehicle.current_stage(), 0);
+
+        // only the active stage's engine counts toward thrust
+        let single = ThrusterModel::main_thruster(50_000.0, 3000.0).max_thrust();
+        assert_eq!(vehicle.max_forward_thrust(), single);
+
+        let info = vehicle.stage_info();
+        assert_eq!(info.len(), 2);
+        assert_eq!(info[0].start_mass, wet);
+        assert_eq!(info[0].parts, 2);
+        assert!(info[1].start_mass < wet);
+        assert_eq!(info[1].thrust, single);
+
+        // the upper stage engine stays dark until the lower one is gone
+        vehicle.set_thrust_control(&VehicleControl::FORWARD);
+        let lit = vehicle
+            .parts()
+            .filter_map(|(_, p)| Some((p.stage(), p.as_thruster()?)))
+            .filter(|(_, (_, d))| d.target_throttle() > 0.0)
+            .map(|(s, _)| s)
+            .collect::<Vec<_>>();
+        assert_eq!(lit, vec![0]);
+
+        assert_eq!(vehicle.ejection_speed(), Some(3.0));
+        let spent = vehicle.jettison_stage().unwrap();
+        assert_eq!(spent.parts().count(), 2);
+        assert_eq!(spent.total_mass() + vehicle.total_mass(), wet);
+        assert_eq!(vehicle.current_stage(), 1);
+        assert_eq!(vehicle.max_forward_thrust(), single);
+
+        // the last stage has nothing left to come apart from
+        assert_eq!(
+            vehicle.jettison_stage().err(),
+            Some(StagingError::LastStage)
+        );
+    }
+
+    #[test]
+    fn spent_stages_become_derelicts() {
+        let (mut universe, orbit) = universe_in_leo();
+        let id = universe
+            .add_orbital_vehicle(two_stage_rocket(), orbit)
+            .unwrap();
+        let events = universe.events.subscribe();
+        let before = universe.surface_vehicles[&id].body;
+
+        let spent = universe.activate_stage(id).unwrap();
+        let debris = &universe.surface_vehicles[&spent];
+        assert!(debris.controller.is_idle());
+        assert!(debris.orbit.is_some());
+        let remaining = &universe.surface_vehicles[&id];
+        let push = debris.body.pv.vel - remaining.body.pv.vel;
+        assert!((push.length() - 3.0).abs() < 1E-9);
+        assert!(push.dot(rotate_f64(DVec2::X, before.angle)) < 0.0);
+
+        // momentum is conserved across the separation
+        let (mr, ms) = (
+            remaining.vehicle.total_mass().to_kg_f64(),
+            debris.vehicle.total_mass().to_kg_f64(),
+        );
+        let momentum = remaining.body.pv.vel * mr + debris.body.pv.vel * ms;
+        assert!((momentum / (mr + ms) - before.pv.vel).length() < 1E-9);
+        assert!(remaining.body.pv.vel.distance(before.pv.vel) > 0.0);
+        assert_eq!(universe.surface_vehicles[&id].vehicle.current_stage(), 1);
+        assert!(universe
+            .events
+            .poll(events)
+            .iter()
+            .any(|e| e.event == UniverseEvent::Staged(id, spent)));
+
+        assert_eq!(universe.activate_stage(id), Err(StagingError::LastStage));
+        assert_eq!(
+            universe.activate_stage(EntityId(9999)),
+            Err(StagingError::NoVehicle)
+        );
+    }
+
+    #[test]
+    fn stages_without_decouplers_stay_attached() {
+        let mut vehicle = two_stage_rocket();
+        vehicle.swap_stages(0, 1);
+        assert_eq!(vehicle.current_stage(), 0);
+        assert_eq!(vehicle.parts_in_stage(0).count(), 2);
+        assert_eq!(
+            vehicle.jettison_stage().err(),
+            Some(StagingError::NoDecoupler)
+        );
+        assert_eq!(vehicle.parts().count(), 4);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::prelude::*;
+use std::fmt::Write;
+
+/// Size of one meter of the vehicle in SVG user units.
+const SVG_UNITS_PER_METER: f32 = 40.0;
+
+/// Space around the drawing for dimension lines, in SVG user units.
+const SVG_MARGIN: f32 = 60.0;
+
+const SVG_STATS_WIDTH: f32 = 260.0;
+const SVG_LINE_HEIGHT: f32 = 16.0;
+
+fn svg_color(color: [f32; 4]) -> String {
+    let [r, g, b, _] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8);
+    format!("rgb({},{},{})", r, g, b)
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Opacity with which parts of each layer are filled, so that internal
+/// parts remain visible beneath the structure around them.
+fn layer_opacity(layer: PartLayer) -> f32 {
+    match layer {
+        PartLayer::Internal => 0.9,
+        PartLayer::Plumbing => 0.9,
+        PartLayer::Structural => 0.5,
+        PartLayer::Exterior => 0.7,
+    }
+}
+
+/// Vector diagram of the vehicle, for documentation and printing. Parts
+/// are outlined and filled with the same colors as the raster schematic,
+/// with markers for the center of mass and each thruster, the overall
+/// dimensions, and the vehicle's stats alongside. None if the vehicle
+/// has no parts.
+pub fn generate_svg(vehicle: &Vehicle) -> Option<String> {
+    vehicle.parts().next()?;
+
+    let bounds = vehicle.aabb();
+    let lower = bounds.lower();
+    let upper = bounds.upper();
+    let span = bounds.span * SVG_UNITS_PER_METER;
+    let stats: Vec<String> = vehicle_info(vehicle).lines().map(escape_xml).collect();
+
+    let width = span.x + SVG_MARGIN * 2.0 + SVG_STATS_WIDTH;
+    let height =
+        (span.y + SVG_MARGIN * 2.0).max(SVG_MARGIN * 2.0 + stats.len() as f32 * SVG_LINE_HEIGHT);
+
+    // svg y increases downwards
+    let tx = |p: Vec2| {
+        Vec2::new(
+            SVG_MARGIN + (p.x - lower.x) * SVG_UNITS_PER_METER,
+            SVG_MARGIN + (upper.y - p.y) * SVG_UNITS_PER_METER,
+        )
+    };
+
+    let mut s = String::new();
+    let _ = writeln!(
+        s,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.1} {h:.1}" font-family="monospace" font-size="12">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        s,
+        r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="6" markerHeight="6" orient="auto-start-reverse"><path d="M 0 0 L 10 5 L 0 10 z" fill="context-stroke"/></marker></defs>"#
+    );
+    let _ = writeln!(s, r#"<rect width="100%" height="100%" fill="white"/>"#);
+
+    for layer in PartLayer::draw_order() {
+        let _ = writeln!(s, r#"<g id="{:?}">"#, layer);
+        for (_, part) in vehicle
+            .parts()
+            .filter(|(_, p)| p.prototype().layer() == layer)
+        {
+            let origin = part.origin_meters();
+            let dims = part.dims_meters();
+            let corner = tx(origin + Vec2::Y * dims.y);
+            let size = dims * SVG_UNITS_PER_METER;
+            let _ = writeln!(
+                s,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}" fill-opacity="{}" stroke="black" stroke-width="1"><title>{}</title></rect>"#,
+                corner.x,
+                corner.y,
+                size.x,
+                size.y,
+                svg_color(diagram_color(&part.prototype())),
+                layer_opacity(layer),
+                escape_xml(part.prototype().part_name()),
+            );
+        }
+        if layer == PartLayer::Plumbing {
+            let color = svg_color([0.6, 0.0, 0.6, 1.0]);
+            for pipe in vehicle.pipes() {
+                let p = tx((pipe + IVec2::Y).as_vec2() / PIXELS_PER_METER);
+                let size = SVG_UNITS_PER_METER / PIXELS_PER_METER;
+                let _ = writeln!(
+                    s,
+                    r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+                    p.x, p.y, size, size, color
+                );
+            }
+        }
+        let _ = writeln!(s, "</g>");
+    }
+
+    // thrusters point along the direction they push the vehicle; main
+    // engines in red and RCS in blue, as in the editor's thrust envelope
+    let _ = writeln!(s, r#"<g id="thrusters">"#);
+    for (_, part) in vehicle.parts() {
+        let Some((model, _)) = part.as_thruster() else {
+            continue;
+        };
+        let center = part.center_meters();
+        let dims = part.dims_meters();
+        let length = dims.x.max(dims.y) * 0.75;
+        let dir = rotate(Vec2::X, part.rotation().to_angle() as f32);
+        let (a, b) = (tx(center), tx(center + dir * length));
+        let color = if model.is_rcs { "blue" } else { "red" };
+        let _ = writeln!(
+            s,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="2" marker-end="url(#arrow)"/>"#,
+            a.x, a.y, b.x, b.y, color
+        );
+    }
+    let _ = writeln!(s, "</g>");
+
+    let com = tx(vehicle.center_of_mass().as_vec2());
+    let _ = writeln!(
+        s,
+        r#"<g id="com" stroke="darkorange" stroke-width="2" fill="none"><circle cx="{x:.1}" cy="{y:.1}" r="7"/><line x1="{l:.1}" y1="{y:.1}" x2="{r:.1}" y2="{y:.1}"/><line x1="{x:.1}" y1="{t:.1}" x2="{x:.1}" y2="{b:.1}"/></g>"#,
+        x = com.x,
+        y = com.y,
+        l = com.x - 10.0,
+        r = com.x + 10.0,
+        t = com.y - 10.0,
+        b = com.y + 10.0,
+    );
+
+    // overall dimensions, below and to the left of the drawing
+    let bl = tx(lower);
+    let tr = tx(upper);
+    let below = bl.y + SVG_MARGIN / 2.0;
+    let left = bl.x - SVG_MARGIN / 2.0;
+    let _ = writeln!(
+        s,
+        r#"<g id="dimensions" stroke="black" stroke-width="1" text-anchor="middle"><line x1="{:.1}" y1="{below:.1}" x2="{:.1}" y2="{below:.1}" marker-start="url(#arrow)" marker-end="url(#arrow)"/><text x="{:.1}" y="{:.1}" stroke="none">{:.1} m</text><line x1="{left:.1}" y1="{:.1}" x2="{left:.1}" y2="{:.1}" marker-start="url(#arrow)" marker-end="url(#arrow)"/><text transform="translate({:.1} {:.1}) rotate(-90)" stroke="none">{:.1} m</text></g>"#,
+        bl.x,
+        tr.x,
+        (bl.x + tr.x) / 2.0,
+        below + 16.0,
+        bounds.span.x,
+        bl.y,
+        tr.y,
+        left - 8.0,
+        (bl.y + tr.y) / 2.0,
+        bounds.span.y,
+    );
+
+    let _ = writeln!(s, r#"<g id="stats">"#);
+    let x = span.x + SVG_MARGIN * 2.0;
+    for (i, line) in stats.iter().enumerate() {
+        let y = SVG_MARGIN + i as f32 * SVG_LINE_HEIGHT;
+        let _ = writeln!(s, r#"<text x="{:.1}" y="{:.1}">{}</text>"#, x, y, line);
+    }
+    let _ = writeln!(s, "</g>");
+
+    let _ = writeln!(s, "</svg>");
+    Some(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn diagrams_outline_every_part() {
+        assert!(generate_svg(&Vehicle::new()).is_none());
+
+        let frame = PartPrototype::Generic(Generic::new(
+            "frame & truss".to_string(),
+            UVec2::new(10, 10),
+            PartLayer::Structural,
+            Mass::kilograms(400),
+        ));
+        let thruster = PartPrototype::Thruster(ThrusterModel::main_thruster(5000.0, 3500.0));
+        let vehicle = Vehicle::from_parts(
+            "".into(),
+            "".into(),
+            vec![
+                (IVec2::ZERO, Rotation::East, frame.clone()),
+                (IVec2::new(10, 0), Rotation::East, frame),
+                (IVec2::new(0, 10), Rotation::North, thruster),
+            ],
+            HashSet::from([IVec2::new(3, 3)]),
+        );
+
+        let svg = generate_svg(&vehicle).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<title>").count(), 3);
+        assert!(svg.contains("frame &amp; truss"));
+        assert_eq!(svg.matches(r#"stroke="red""#).count(), 1);
+        assert!(svg.contains(r#"id="com""#));
+        assert!(svg.contains(&format!("{:.1} m", vehicle.aabb().span.x)));
+        assert!(svg.contains("Thrusters: 1"));
+    }
+}
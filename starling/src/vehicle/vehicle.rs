@@ -1,17 +1,26 @@
 use crate::aabb::AABB;
+use crate::aerodynamics::AeroSurface;
 use crate::factory::*;
 use crate::math::*;
 use crate::nanotime::Nanotime;
 use crate::parts::*;
 use crate::pid::PDCtrl;
 use crate::vehicle::*;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 
-fn rocket_equation(ve: f64, m0: Mass, m1: Mass) -> f64 {
+pub(crate) fn rocket_equation(ve: f64, m0: Mass, m1: Mass) -> f64 {
     ve * (m0.to_kg_f64() / m1.to_kg_f64()).ln()
 }
 
+/// Whether a thruster can fire while `stage` is the active stage. RCS
+/// serves the whole vehicle, but main engines wait for the stages below
+/// them to be jettisoned.
+fn is_lit(t: &ThrusterModel, part: &InstantiatedPart, stage: usize) -> bool {
+    t.is_rcs || part.stage() == stage
+}
+
 #[allow(unused)]
 fn mass_after_maneuver(ve: f64, m0: f64, dv: f64) -> f64 {
     m0 / (dv / ve).exp()
@@ -34,10 +43,10 @@ pub fn occupied_pixels(pos: IVec2, rot: Rotation, part: &PartPrototype) -> Vec<I
     ret
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct PartId(u64);
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ThrustAxisInfo {
     max_thrust: f64,
 }
@@ -48,7 +57,7 @@ impl Default for ThrustAxisInfo {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Vehicle {
     name: String,
     model: String,
@@ -145,6 +154,12 @@ impl Vehicle {
         self.update();
     }
 
+    /// Adds many pipes at once, rebuilding connectivity only the once.
+    pub fn add_pipes(&mut self, ps: impl IntoIterator<Item = IVec2>) {
+        self.pipes.extend(ps);
+        self.update();
+    }
+
     pub fn remove_pipe(&mut self, p: IVec2) {
         self.pipes.remove(&p);
         self.update();
@@ -400,6 +415,14 @@ impl Vehicle {
         self.parts.iter()
     }
 
+    /// Parts in ascending ID order, which stays the same from one call to
+    /// the next.
+    pub fn parts_in_order(&self) -> impl Iterator<Item = (&PartId, &InstantiatedPart)> + use<'_> {
+        let mut parts: Vec<_> = self.parts.iter().collect();
+        parts.sort_by_key(|(id, _)| **id);
+        parts.into_iter()
+    }
+
     pub fn fuel_percentage(&self) -> f64 {
         let max_fuel_mass: Mass = self.tanks().map(|(t, _)| t.max_fluid_mass).sum();
         if max_fuel_mass == Mass::ZERO {
@@ -450,12 +473,13 @@ impl Vehicle {
         }
 
         let u = rotate_f64(DVec2::X, angle);
+        let stage = self.current_stage();
 
         let mut sum = 0.0;
 
         for (_, part) in &self.parts {
             if let Some((t, d)) = part.as_thruster() {
-                if t.is_rcs != rcs {
+                if t.is_rcs != rcs || !is_lit(t, part, stage) {
                     continue;
                 }
                 let v = rotate_f64(DVec2::X, part.rotation().to_angle());
@@ -666,12 +690,19 @@ impl Vehicle {
         }
 
         let com = self.center_of_mass();
+        let stage = self.current_stage();
 
         for (_, part) in &mut self.parts {
             let rot = part.rotation();
             let center_of_thrust = part.center_meters().as_dvec2();
             let u = rotate_f64(DVec2::X, part.rotation().to_angle());
+            let in_stage = part.stage() == stage;
             if let Some((t, d)) = part.as_thruster_mut() {
+                if !t.is_rcs && !in_stage {
+                    d.set_throttle(0.0);
+                    continue;
+                }
+
                 // if t.is_rcs && !saturated {
                 //     d.set_throttle(0.0);
                 //     continue;
@@ -808,6 +839,124 @@ impl Vehicle {
         self.parts.iter().filter_map(|(_, p)| p.as_thruster())
     }
 
+    /// Built surfaces which generate lift, with the angle of each one's
+    /// chord relative to the vehicle.
+    pub fn aero_surfaces(&self) -> impl Iterator<Item = (AeroSurface, f64)> + use<'_> {
+        self.parts
+            .values()
+            .filter(|p| p.is_built())
+            .filter_map(|p| Some((p.as_aero_surface()?, p.rotation().to_angle())))
+    }
+
+    pub fn cargo(&self) -> impl Iterator<Item = (&Cargo, &CargoInstanceData)> + use<'_> {
+        self.parts.values().filter_map(|p| p.as_cargo())
+    }
+
+    pub fn cargo_mass(&self) -> Mass {
+        self.cargo().map(|(_, d)| d.contents_mass()).sum()
+    }
+
+    pub fn cargo_percentage(&self) -> f64 {
+        let capacity: Mass = self.cargo().map(|(c, _)| c.capacity_mass()).sum();
+        if capacity == Mass::ZERO {
+            return 0.0;
+        }
+        self.cargo_mass().to_kg_f64() / capacity.to_kg_f64()
+    }
+
+    /// Total mass of each item aboard, across tanks and cargo containers.
+    pub fn inventory(&self) -> HashMap<Item, Mass> {
+        let mut ret: HashMap<Item, Mass> = HashMap::new();
+        let tanks = self
+            .tanks()
+            .filter_map(|(_, d)| Some((d.item()?, d.contents_mass())));
+        let cargo = self.cargo().flat_map(|(_, d)| d.contents());
+        for (item, mass) in tanks.chain(cargo) {
+            *ret.entry(item).or_default() += mass;
+        }
+        ret
+    }
+
+    pub fn item_mass(&self, item: Item) -> Mass {
+        self.inventory().get(&item).copied().unwrap_or(Mass::ZERO)
+    }
+
+    /// Room left for `item` in the cargo containers which accept it.
+    pub fn cargo_capacity(&self, item: Item) -> Mass {
+        self.cargo()
+            .map(|(c, d)| c.remaining_capacity(item, d))
+            .sum()
+    }
+
+    /// Stows up to `mass` of `item` in cargo containers which accept it.
+    /// Returns the mass actually stowed.
+    pub fn load_cargo(&mut self, item: Item, mass: Mass) -> Mass {
+        let mut remaining = mass;
+        for part in self.parts.values_mut() {
+            if remaining == Mass::ZERO {
+                break;
+            }
+            if let Some((c, d)) = part.as_cargo_mut() {
+                let before = d.contents_mass();
+                c.put(item, remaining, d);
+                remaining -= d.contents_mass() - before;
+            }
+        }
+        self.update_physical_quantities();
+        mass - remaining
+    }
+
+    /// Room left for `item` across tanks and cargo containers.
+    pub fn room_for(&self, item: Item) -> Mass {
+        let tanks: Mass = self
+            .tanks()
+            .map(|(t, d)| t.remaining_capacity(item, d))
+            .sum();
+        tanks + self.cargo_capacity(item)
+    }
+
+    /// Stores up to `mass` of `item`, in tanks if it's a fluid and then in
+    /// cargo containers. Returns the mass actually stored.
+    pub fn stow(&mut self, item: Item, mass: Mass) -> Mass {
+        let mut remaining = mass;
+        for part in self.parts.values_mut() {
+            if remaining == Mass::ZERO {
+                break;
+            }
+            if let Some((t, d)) = part.as_tank_mut() {
+                if t.remaining_capacity(item, d) == Mass::ZERO {
+                    continue;
+                }
+                let before = d.contents_mass();
+                t.put(item, remaining, d);
+                remaining -= d.contents_mass() - before;
+            }
+        }
+        remaining -= self.load_cargo(item, remaining);
+        mass - remaining
+    }
+
+    /// Takes up to `mass` of `item` out of tanks and cargo containers.
+    /// Returns the mass actually taken.
+    pub fn unload(&mut self, item: Item, mass: Mass) -> Mass {
+        let mut remaining = mass;
+        for part in self.parts.values_mut() {
+            if remaining == Mass::ZERO {
+                break;
+            }
+            if let Some((_, d)) = part.as_tank_mut() {
+                if d.item() == Some(item) {
+                    remaining -= d.take(remaining);
+                }
+            }
+            if let Some((_, d)) = part.as_cargo_mut() {
+                remaining -= d.take(item, remaining);
+            }
+        }
+        self.update_physical_quantities();
+        mass - remaining
+    }
+
     pub fn set_recipe(&mut self, id: PartId, recipe: RecipeListing) -> bool {
         if let Some(part) = self.parts.get_mut(&id) {
             if let Some((_, d)) = part.as_machine_mut() {
@@ -818,6 +967,94 @@ impl Vehicle {
         false
     }
 
+    pub fn set_part_stage(&mut self, id: PartId, stage: usize) -> bool {
+        let Some(part) = self.parts.get_mut(&id) else {
+            return false;
+        };
+        part.set_stage(stage);
+        self.update();
+        true
+    }
+
+    /// Puts parts into stages, in the order the parts were given to
+    /// `from_parts`.
+    pub fn assign_stages(&mut self, stages: impl IntoIterator<Item = usize>) {
+        for (i, stage) in stages.into_iter().enumerate() {
+            if let Some(part) = self.parts.get_mut(&PartId(i as u64)) {
+                part.set_stage(stage);
+            }
+        }
+        self.update();
+    }
+
+    /// Removes the given parts, along with the pipes running through
+    /// them, and makes a new vehicle out of them.
+    pub fn split_off(&mut self, ids: &[PartId]) -> Vehicle {
+        let mut other = Vehicle::from_parts(
+            self.name.clone(),
+            self.model.clone(),
+            Vec::new(),
+            HashSet::new(),
+        );
+        for id in ids {
+            let Some(part) = self.parts.remove(id) else {
+                continue;
+            };
+            let min = part.origin();
+            let max = min + part.dims_grid().as_ivec2();
+            let pipes: Vec<_> = self
+                .pipes
+                .iter()
+                .filter(|p| p.cmpge(min).all() && p.cmplt(max).all())
+                .copied()
+                .collect();
+            for p in pipes {
+                self.pipes.remove(&p);
+                other.pipes.insert(p);
+            }
+            let new_id = other.get_next_part_id();
+            other.parts.insert(new_id, part);
+        }
+        self.update();
+        other.update();
+        other
+    }
+
+    /// Turns the layout of every part and pipe counterclockwise about the
+    /// origin by the given number of quarter turns, then moves it by
+    /// `offset`.
+    pub fn transform_layout(&mut self, quarter_turns: u32, offset: IVec2) {
+        for _ in 0..quarter_turns % 4 {
+            for part in self.parts.values_mut() {
+                *part = part.rotated();
+            }
+            self.pipes = self
+                .pipes
+                .iter()
+                .map(|p| IVec2::new(-p.y - 1, p.x))
+                .collect();
+        }
+        for part in self.parts.values_mut() {
+            part.set_origin(part.origin() + offset);
+        }
+        self.pipes = self.pipes.iter().map(|p| *p + offset).collect();
+        self.update();
+    }
+
+    /// Adds every part and pipe of another vehicle, as laid out in it.
+    /// Returns the IDs the parts were given in this vehicle.
+    pub fn attach(&mut self, other: Vehicle) -> Vec<PartId> {
+        let mut ids = Vec::new();
+        for (_, part) in other.parts {
+            let id = self.get_next_part_id();
+            self.parts.insert(id, part);
+            ids.push(id);
+        }
+        self.pipes.extend(other.pipes);
+        self.update();
+        ids
+    }
+
     pub fn clear_contents(&mut self, id: PartId) -> bool {
         if let Some(part) = self.parts.get_mut(&id) {
             if let Some((_, d)) = part.as_tank_mut() {
@@ -834,6 +1071,72 @@ impl Vehicle {
         return false;
     }
 
+    /// Empties tanks and cargo containers, then fills them as the loadout
+    /// says. Cargo which doesn't fit is left behind.
+    pub fn apply_loadout(&mut self, loadout: &Loadout) {
+        for part in self.parts.values_mut() {
+            if let Some((t, d)) = part.as_tank_mut() {
+                let item = d.item().unwrap_or(loadout.fuel_item);
+                d.clear_contents();
+                let mass = Mass::from_kg_f32((t.capacity().to_kg_f64() * loadout.fuel) as f32);
+                t.put(item, mass.clamp(Mass::ZERO, t.capacity()), d);
+            }
+            if let Some((_, d)) = part.as_cargo_mut() {
+                d.clear_contents();
+            }
+        }
+        for (item, mass) in &loadout.manifest {
+            self.load_cargo(*item, *mass);
+        }
+        self.update_physical_quantities();
+    }
+
+    /// The ID each part will have once the vehicle is saved and built
+    /// again, which numbers parts from zero in the order they're saved.
+    pub fn renumbered_part_ids(&self) -> HashMap<PartId, PartId> {
+        self.parts_in_order()
+            .enumerate()
+            .map(|(i, (id, _))| (*id, PartId(i as u64)))
+            .collect()
+    }
+
+    /// How far each part has been built and what it holds, listed in the
+    /// same order as `VehicleFileStorage::from_vehicle` lists the parts.
+    pub fn part_states(&self) -> Vec<PartStateFileStorage> {
+        self.parts_in_order()
+            .map(|(_, part)| PartStateFileStorage::from_part(part))
+            .collect()
+    }
+
+    /// Puts back the states given by `part_states` on a vehicle built from
+    /// the design saved alongside them. States are matched to parts by
+    /// their order in the design.
+    pub fn restore_part_states(&mut self, states: &[PartStateFileStorage]) {
+        for (i, state) in states.iter().enumerate() {
+            if let Some(part) = self.parts.get_mut(&PartId(i as u64)) {
+                state.apply(part);
+            }
+        }
+        self.update();
+    }
+
+    /// Tops up tanks with the fluid they already hold, adding at most
+    /// `mass` in total. Returns the mass actually added.
+    pub fn refuel(&mut self, mass: Mass) -> Mass {
+        let mut remaining = mass;
+        for part in self.parts.values_mut() {
+            if let Some((t, d)) = part.as_tank_mut() {
+                let Some(item) = d.item() else {
+                    continue;
+                };
+                let before = d.contents_mass();
+                t.put(item, remaining, d);
+                remaining -= d.contents_mass() - before;
+            }
+        }
+        mass - remaining
+    }
+
     pub fn bounding_radius(&self) -> f64 {
         let aabb = self.aabb();
         let mut r: f64 = 0.0;
@@ -849,6 +1152,12 @@ impl Vehicle {
         }
     }
 
+    /// Total builds needed to construct every part of the vehicle, which
+    /// serves as its cost.
+    pub fn build_cost(&self) -> u32 {
+        self.parts.values().map(|p| p.builds_required()).sum()
+    }
+
     pub fn build_all(&mut self) {
         for (_, part) in &mut self.parts {
             part.build_all();
@@ -860,6 +1169,13 @@ impl Vehicle {
         self.docking_linear_controller = self.docking_linear_controller.jitter();
     }
 
+    /// Resets every part to unbuilt, leaving only the design.
+    pub fn unbuild_all(&mut self) {
+        for part in self.parts.values_mut() {
+            part.unbuild_all();
+        }
+    }
+
     pub fn build_once(&mut self) {
         for layer in PartLayer::build_order() {
             let layer_is_built = self
@@ -939,6 +1255,11 @@ pub fn vehicle_info(vehicle: &Vehicle) -> String {
         format!("Thrusters: {}", vehicle.thruster_count()),
         format!("Thrust: {:0.2} kN", vehicle.max_thrust() / 1000.0),
         format!("Tanks: {}", vehicle.tank_count()),
+        format!(
+            "Cargo: {} ({:0.0}%)",
+            vehicle.cargo_mass(),
+            vehicle.cargo_percentage() * 100.0
+        ),
         format!("Accel: {:0.2} g", vehicle.accel() / 9.81),
         format!("BFA: {:0.2} g", vehicle.body_frame_accel().linear / 9.81),
         format!("Ve: {:0.1} s", vehicle.average_linear_exhaust_velocity()),
@@ -948,6 +1269,29 @@ pub fn vehicle_info(vehicle: &Vehicle) -> String {
         format!("Fuel: {:0.1}/s", rate),
     ]
     .into_iter()
+    .chain(stage_lines(vehicle))
     .map(|s| format!("{s}\n"))
     .collect()
 }
+
+/// Mass, thrust and delta-v of each stage, for vehicles with more than
+/// one.
+fn stage_lines(vehicle: &Vehicle) -> Vec<String> {
+    if vehicle.stage_count() < 2 {
+        return Vec::new();
+    }
+    vehicle
+        .stage_info()
+        .into_iter()
+        .map(|s| {
+            format!(
+                "Stage {}: {} -> {}, {:0.2} kN, {:0.1} m/s",
+                s.stage,
+                s.start_mass,
+                s.end_mass,
+                s.thrust / 1000.0,
+                s.dv
+            )
+        })
+        .collect()
+}
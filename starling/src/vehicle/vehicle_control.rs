@@ -292,7 +292,7 @@ pub fn velocity_control_law(
     cmd
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VehicleControlStatus {
     Done,
     WaitingForInput,
@@ -338,9 +338,10 @@ pub fn enter_orbit_control_law(
     body: &RigidBody,
     vehicle: &Vehicle,
     orbit: Option<&SparseOrbit>,
-    target_altitude: f64,
+    profile: &AscentProfile,
 ) -> (VehicleControl, VehicleControlStatus) {
-    let target_apoapsis = target_altitude + 10000.0;
+    let target_altitude = profile.target_altitude;
+    let target_apoapsis = target_altitude + ASCENT_APOAPSIS_MARGIN;
     let target_periapsis = target_altitude;
 
     let altitude = body.pv.pos.length() - planet.radius;
@@ -382,13 +383,7 @@ pub fn enter_orbit_control_law(
     let apoapsis_above_target = apoapsis_altitude > target_apoapsis;
     let above_target = altitude > target_altitude;
 
-    let launch_program_target_angle = {
-        let start_altitude = 1000.0;
-        let end_altitude = 12000.0;
-        let s = ((altitude - start_altitude) / end_altitude).clamp(0.0, 1.0);
-        let off_vertical = s * PI_64 / 2.0;
-        vertical + off_vertical
-    };
+    let launch_program_target_angle = vertical + profile.pitch_angle(altitude);
 
     let circularization_angle = {
         let off_horizontal_angle = (vertical_velocity / 100.0).clamp(-PI_64 / 5.0, PI_64 / 5.0);
@@ -421,11 +416,8 @@ pub fn enter_orbit_control_law(
             (VehicleControl::NULLOPT, VehicleControlStatus::InProgress)
         }
     } else {
-        let max_accel = vehicle.max_forward_thrust() / vehicle.total_mass().to_kg_f64();
-        let target_accel = 16.0;
-        let throttle = target_accel / max_accel;
         (
-            att_and_throttle(launch_program_target_angle, throttle as f32),
+            att_and_throttle(launch_program_target_angle, profile.throttle(vehicle)),
             VehicleControlStatus::ExecutingLaunchProgram,
         )
     };
@@ -465,10 +457,12 @@ pub enum VehicleControlPolicy {
     Idle,
     External,
     PositionHold(Vec<(DVec2, f64)>),
-    LaunchToOrbit(f64),
+    LaunchToOrbit(AscentProfile),
     BurnPrograde,
     BurnRetrograde,
     HoldAttitude(Option<f64>),
+    /// Runs the named law from the universe's `ControlLawRegistry`.
+    ControlLaw(String),
 }
 
 impl VehicleControlPolicy {
@@ -481,11 +475,12 @@ impl VehicleControlPolicy {
             VehicleControlPolicy::BurnPrograde => "Burning prograde".to_string(),
             VehicleControlPolicy::BurnRetrograde => "Burning retrograde".to_string(),
             VehicleControlPolicy::HoldAttitude(_) => "Holding attitude".to_string(),
+            VehicleControlPolicy::ControlLaw(name) => format!("Running {}", name),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct VehicleController {
     status: VehicleControlStatus,
     mode: VehicleControlPolicy,
@@ -511,7 +506,9 @@ impl VehicleController {
     pub fn launch() -> Self {
         Self {
             status: VehicleControlStatus::InProgress,
-            mode: VehicleControlPolicy::LaunchToOrbit(rand(300_000.0, 700_000.0) as f64),
+            mode: VehicleControlPolicy::LaunchToOrbit(AscentProfile::new(
+                rand(300_000.0, 700_000.0) as f64,
+            )),
         }
     }
 
@@ -583,13 +580,14 @@ impl VehicleController {
         self.mode = match self.mode {
             VehicleControlPolicy::Idle => VehicleControlPolicy::External,
             VehicleControlPolicy::External => VehicleControlPolicy::PositionHold(vec![]),
-            VehicleControlPolicy::PositionHold(_) => {
-                VehicleControlPolicy::LaunchToOrbit(rand(300_000.0, 700_000.0) as f64)
-            }
+            VehicleControlPolicy::PositionHold(_) => VehicleControlPolicy::LaunchToOrbit(
+                AscentProfile::new(rand(300_000.0, 700_000.0) as f64),
+            ),
             VehicleControlPolicy::LaunchToOrbit(_) => VehicleControlPolicy::BurnPrograde,
             VehicleControlPolicy::BurnPrograde => VehicleControlPolicy::BurnRetrograde,
             VehicleControlPolicy::BurnRetrograde => VehicleControlPolicy::HoldAttitude(None),
             VehicleControlPolicy::HoldAttitude(_) => VehicleControlPolicy::Idle,
+            VehicleControlPolicy::ControlLaw(_) => VehicleControlPolicy::Idle,
         };
     }
 
@@ -61,4 +61,49 @@ mod tests {
         assert_eq!(aabb.span, Vec2::splat(0.5));
         assert_eq!(aabb.center, Vec2::splat(0.25));
     }
+
+    #[test]
+    fn vehicle_cargo_inventory() {
+        let food = Cargo::new(
+            "pantry".into(),
+            Mass::kilograms(100),
+            Mass::kilograms(500),
+            UVec2::new(10, 10),
+        )
+        .with_filter(CargoFilter::Food);
+        let general = Cargo::new(
+            "crate".into(),
+            Mass::kilograms(100),
+            Mass::kilograms(500),
+            UVec2::new(10, 10),
+        );
+
+        let mut vehicle = Vehicle::from_parts(
+            "".into(),
+            "".into(),
+            vec![
+                (IVec2::ZERO, Rotation::East, PartPrototype::Cargo(food)),
+                (IVec2::X * 10, Rotation::East, PartPrototype::Cargo(general)),
+            ],
+            HashSet::new(),
+        );
+
+        assert_eq!(vehicle.cargo_capacity(Item::Milk), Mass::kilograms(500));
+        assert_eq!(vehicle.cargo_capacity(Item::Bread), Mass::kilograms(1000));
+        assert_eq!(vehicle.cargo_capacity(Item::H2), Mass::ZERO);
+
+        assert_eq!(
+            vehicle.load_cargo(Item::Milk, Mass::kilograms(800)),
+            Mass::kilograms(500)
+        );
+        assert_eq!(
+            vehicle.load_cargo(Item::Iron, Mass::kilograms(200)),
+            Mass::kilograms(200)
+        );
+        assert_eq!(vehicle.item_mass(Item::Milk), Mass::kilograms(500));
+        assert_eq!(vehicle.item_mass(Item::Iron), Mass::kilograms(200));
+        assert_eq!(vehicle.cargo_mass(), Mass::kilograms(700));
+        assert_eq!(vehicle.total_mass(), Mass::kilograms(900));
+        assert_eq!(vehicle.inventory().len(), 2);
+    }
 }
@@ -0,0 +1,192 @@
+use crate::id::EntityId;
+use crate::math::*;
+use crate::orbits::Body;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+/// Where a waypoint is, relative to the planet it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WaypointLocation {
+    /// On the ground, at this angle around the planet, in radians.
+    Surface(f64),
+    /// At a fixed offset from the center of the planet, which needn't be
+    /// on any orbit.
+    Orbital(DVec2),
+}
+
+/// A named marker which vehicles can target and measurements can be
+/// anchored to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub name: String,
+    pub planet_id: EntityId,
+    pub location: WaypointLocation,
+}
+
+impl Waypoint {
+    pub fn surface(name: impl Into<String>, planet_id: EntityId, angle: f64) -> Self {
+        Self {
+            name: name.into(),
+            planet_id,
+            location: WaypointLocation::Surface(angle),
+        }
+    }
+
+    pub fn orbital(name: impl Into<String>, planet_id: EntityId, pos: DVec2) -> Self {
+        Self {
+            name: name.into(),
+            planet_id,
+            location: WaypointLocation::Orbital(pos),
+        }
+    }
+
+    /// Places a waypoint at a position relative to the planet's center,
+    /// snapping it to the ground if it's within `snap` of the surface.
+    pub fn at(
+        name: impl Into<String>,
+        planet_id: EntityId,
+        body: &Body,
+        pos: DVec2,
+        snap: f64,
+    ) -> Self {
+        if pos.length() - body.radius < snap {
+            Self::surface(name, planet_id, pos.to_angle())
+        } else {
+            Self::orbital(name, planet_id, pos)
+        }
+    }
+
+    pub fn is_surface(&self) -> bool {
+        matches!(self.location, WaypointLocation::Surface(_))
+    }
+
+    /// Position relative to the center of the waypoint's planet.
+    pub fn local_position(&self, body: &Body) -> DVec2 {
+        match self.location {
+            WaypointLocation::Surface(angle) => rotate_f64(DVec2::X, angle) * body.radius,
+            WaypointLocation::Orbital(pos) => pos,
+        }
+    }
+
+    /// Height above the planet's surface.
+    pub fn altitude(&self, body: &Body) -> f64 {
+        self.local_position(body).length() - body.radius
+    }
+}
+
+/// A waypoint read from a file, with the ID it was saved with if it has one.
+pub type LoadedWaypoint = (Option<EntityId>, Waypoint);
+
+/// Reads waypoints along with the IDs they were saved with. Files written
+/// before IDs were saved hold bare waypoints, which are given new IDs.
+pub fn load_waypoints_from_file(filename: &Path) -> Result<Vec<LoadedWaypoint>, Box<dyn Error>> {
+    let s = std::fs::read_to_string(filename)?;
+    if let Ok(saved) = serde_yaml::from_str::<Vec<(EntityId, Waypoint)>>(&s) {
+        return Ok(saved.into_iter().map(|(id, x)| (Some(id), x)).collect());
+    }
+    let bare: Vec<Waypoint> = serde_yaml::from_str(&s)?;
+    Ok(bare.into_iter().map(|x| (None, x)).collect())
+}
+
+pub fn write_waypoints_to_file(
+    waypoints: &[(EntityId, Waypoint)],
+    filename: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = filename.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filename, serde_yaml::to_string(waypoints)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nanotime::Nanotime;
+    use crate::prelude::*;
+    use crate::testing::{test_tanker, universe_in_leo};
+    use std::collections::HashMap;
+
+    #[test]
+    fn waypoints_resolve_relative_to_their_planet() {
+        let mut universe = Universe::empty();
+        let planet = universe.planets.id;
+        let body = universe.planets.body;
+
+        let near = DVec2::new(0.0, body.radius + 10.0);
+        let pad = Waypoint::at("Pad", planet, &body, near, 100.0);
+        assert!(pad.is_surface());
+        assert!(pad.altitude(&body).abs() < 1e-6);
+
+        let far = DVec2::new(body.radius + 50_000.0, 0.0);
+        let relay = Waypoint::at("Relay", planet, &body, far, 100.0);
+        assert_eq!(relay.location, WaypointLocation::Orbital(far));
+
+        let id = universe.add_waypoint(relay.clone()).unwrap();
+        let (_, origin, _, _) = universe.planets.lookup(planet, Nanotime::zero()).unwrap();
+        assert_eq!(universe.pv(id).unwrap().pos, origin.pos + far);
+        assert_eq!(universe.remove_waypoint(id), Some(relay));
+        assert_eq!(universe.pv(id), None);
+
+        let path = std::env::temp_dir().join(format!("waypoints-{}.yaml", std::process::id()));
+        std::fs::write(
+            &path,
+            serde_yaml::to_string(std::slice::from_ref(&pad)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(load_waypoints_from_file(&path).unwrap(), vec![(None, pad)]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn targeted_waypoints_keep_their_ids_in_side_files() {
+        let (mut universe, orbit) = universe_in_leo();
+        let body = universe.planets.body;
+        let planet = universe.planets.id;
+        let parts: HashMap<_, _> = test_tanker(0.0)
+            .parts()
+            .map(|(_, p)| (p.prototype().sprite_path().to_string(), p.prototype()))
+            .collect();
+
+        // a few waypoints come and go first, so IDs aren't handed out in
+        // the same order again on load
+        for i in 0..3 {
+            let pos = DVec2::new(body.radius + 10_000.0 * (i + 1) as f64, 0.0);
+            let id = universe.add_waypoint(Waypoint::at("Temp", planet, &body, pos, 100.0));
+            universe.remove_waypoint(id.unwrap());
+        }
+        let pos = DVec2::new(body.radius + 50_000.0, 0.0);
+        let relay = Waypoint::at("Relay", planet, &body, pos, 100.0);
+        let wid = universe.add_waypoint(relay.clone()).unwrap();
+        let vid = universe
+            .add_orbital_vehicle(test_tanker(0.0), orbit)
+            .unwrap();
+        universe
+            .surface_vehicles
+            .get_mut(&vid)
+            .unwrap()
+            .set_target(wid);
+
+        let path = std::env::temp_dir().join(format!("targets-{}.yaml", std::process::id()));
+        let waypoints: Vec<_> = universe
+            .waypoints
+            .iter()
+            .map(|(id, w)| (*id, w.clone()))
+            .collect();
+        write_waypoints_to_file(&waypoints, &path).unwrap();
+        let storage = UniverseFileStorage {
+            waypoints: Vec::new(),
+            ..universe.to_file_storage()
+        };
+
+        let (mut loaded, failed) = Universe::from_file_storage(storage, &parts).unwrap();
+        assert!(failed.is_empty());
+        loaded.replace_waypoints(load_waypoints_from_file(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.surface_vehicles[&vid].target(), Some(wid));
+        assert_eq!(loaded.waypoints.get(&wid), Some(&relay));
+        assert_eq!(loaded.pv(wid), universe.pv(wid));
+    }
+}